@@ -17,6 +17,7 @@ use futures::StreamExt;
 use risingwave_batch::executor::hash_join::HashJoinExecutor;
 use risingwave_batch::executor::test_utils::{gen_projected_data, MockExecutor};
 use risingwave_batch::executor::{BoxedExecutor, JoinType};
+use risingwave_batch::task::MemoryContext;
 use risingwave_common::catalog::schema_test_utils::field_n;
 use risingwave_common::hash;
 use risingwave_common::types::{DataType, ScalarImpl};
@@ -168,6 +169,7 @@ fn create_hash_join_executor(
         false,
         cond,
         "HashJoinExecutor".into(),
+        MemoryContext::unlimited(),
     ))
 }
 
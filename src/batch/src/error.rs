@@ -38,6 +38,12 @@ pub enum BatchError {
     #[error("Failed to send result to channel")]
     SenderError,
 
+    #[error("Memory limit exceeded: {0} needs more than the limit of {1} bytes of the query")]
+    MemoryLimitExceeded(String, usize),
+
+    #[error("Failed to spill to disk: {0}")]
+    Spill(#[from] std::io::Error),
+
     #[error(transparent)]
     Internal(#[from] anyhow::Error),
 }
@@ -0,0 +1,119 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures::stream::StreamExt;
+use futures_async_stream::try_stream;
+use risingwave_common::array::DataChunk;
+use risingwave_common::catalog::Schema;
+use risingwave_common::error::RwError;
+
+use crate::executor::{BoxedDataChunkStream, BoxedExecutor, Executor, ExecutorStats};
+
+/// For `EXPLAIN ANALYZE`, we build an [`AnalyzeExecutor`] on top of the underlying executor to
+/// collect its output and the time spent in pulling from it.
+pub struct AnalyzeExecutor {
+    child: BoxedExecutor,
+    stats: Arc<ExecutorStats>,
+}
+
+impl AnalyzeExecutor {
+    pub fn new(child: BoxedExecutor, stats: Arc<ExecutorStats>) -> Self {
+        Self { child, stats }
+    }
+}
+
+impl Executor for AnalyzeExecutor {
+    fn schema(&self) -> &Schema {
+        self.child.schema()
+    }
+
+    fn identity(&self) -> &str {
+        "AnalyzeExecutor"
+    }
+
+    fn execute(self: Box<Self>) -> BoxedDataChunkStream {
+        self.do_execute()
+    }
+}
+
+impl AnalyzeExecutor {
+    #[try_stream(boxed, ok = DataChunk, error = RwError)]
+    async fn do_execute(self: Box<Self>) {
+        let stats = self.stats;
+        let mut child_stream = self.child.execute();
+        loop {
+            let start = Instant::now();
+            let chunk = child_stream.next().await;
+            stats.record_elapsed(start.elapsed());
+            match chunk {
+                Some(chunk) => {
+                    let chunk = chunk?;
+                    stats.record_chunk(chunk.cardinality());
+                    yield chunk;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::Field;
+    use risingwave_common::test_prelude::DataChunkTestExt;
+    use risingwave_common::types::DataType;
+
+    use super::*;
+    use crate::executor::test_utils::MockExecutor;
+    use crate::executor::TaskExecutorStats;
+    use crate::task::MemoryContext;
+
+    #[tokio::test]
+    async fn test_analyze_executor() {
+        let schema = Schema::new(vec![Field::unnamed(DataType::Int32)]);
+        let mut mock_executor = MockExecutor::new(schema);
+        mock_executor.add(DataChunk::from_pretty(
+            "i
+             1
+             2
+             3 D",
+        ));
+        mock_executor.add(DataChunk::from_pretty(
+            "i
+             4",
+        ));
+
+        let task_stats = TaskExecutorStats::new(MemoryContext::unlimited());
+        let executor = Box::new(AnalyzeExecutor::new(
+            Box::new(mock_executor),
+            task_stats.register(1, "MockExecutor"),
+        ));
+        let mut stream = executor.execute();
+        while let Some(chunk) = stream.next().await {
+            chunk.unwrap();
+        }
+
+        let stats = task_stats.to_prost();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].operator_id, 1);
+        assert_eq!(stats[0].identity, "MockExecutor");
+        // The invisible row is not counted.
+        assert_eq!(stats[0].output_rows, 3);
+        assert_eq!(stats[0].output_chunks, 2);
+        assert_eq!(stats[0].peak_memory_bytes, 0);
+    }
+}
@@ -28,17 +28,22 @@ use risingwave_common::hash::{
 };
 use risingwave_common::types::DataType;
 use risingwave_common::util::chunk_coalesce::DEFAULT_CHUNK_BUFFER_SIZE;
+use risingwave_common::util::hash_util::CRC32FastBuilder;
 use risingwave_expr::vector_op::agg::{AggStateFactory, BoxedAggState};
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 use risingwave_pb::batch_plan::HashAggNode;
 
+use crate::executor::spill::{SpillPartitions, MAX_SPILL_DEPTH};
 use crate::executor::{
     BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
 };
-use crate::task::{BatchTaskContext, TaskId};
+use crate::task::{BatchTaskContext, MemoryContextRef, MemoryReservation, TaskId};
 
 type AggHashMap<K> = HashMap<K, Vec<BoxedAggState>, PrecomputedBuildHasher>;
 
+/// The estimated memory of an aggregation state, which is boxed.
+const ESTIMATED_AGG_STATE_SIZE: usize = 64;
+
 struct HashAggExecutorBuilderDispatcher;
 
 /// A dispatcher to help create specialized hash agg executor.
@@ -59,6 +64,7 @@ pub struct HashAggExecutorBuilder {
     schema: Schema,
     task_id: TaskId,
     identity: String,
+    memory_context: MemoryContextRef,
 }
 
 impl HashAggExecutorBuilder {
@@ -67,6 +73,7 @@ impl HashAggExecutorBuilder {
         child: BoxedExecutor,
         task_id: TaskId,
         identity: String,
+        memory_context: MemoryContextRef,
     ) -> Result<BoxedExecutor> {
         let group_key_columns = hash_agg_node
             .get_group_key()
@@ -104,6 +111,7 @@ impl HashAggExecutorBuilder {
             schema: Schema { fields },
             task_id,
             identity,
+            memory_context,
         };
 
         Ok(HashAggExecutorBuilderDispatcher::dispatch_by_kind(
@@ -132,11 +140,16 @@ impl BoxedExecutorBuilder for HashAggExecutorBuilder {
             inputs.remove(0),
             source.task_id.clone(),
             identity,
+            source.context().memory_context(source.task_id),
         )
     }
 }
 
 /// `HashAggExecutor` implements the hash aggregate algorithm.
+///
+/// Once the groups exceed the memory budget of the query, the rows of the new groups are spilled
+/// into partitions by the hash of the group key, while the groups in memory keep aggregating. After
+/// the groups in memory are output, the partitions are aggregated one by one in the same way.
 pub(crate) struct HashAggExecutor<K> {
     /// factories to construct aggregator for each groups
     agg_factories: Vec<AggStateFactory>,
@@ -148,6 +161,7 @@ pub(crate) struct HashAggExecutor<K> {
     group_key_types: Vec<DataType>,
     schema: Schema,
     identity: String,
+    memory_context: MemoryContextRef,
     _phantom: PhantomData<K>,
 }
 
@@ -160,6 +174,7 @@ impl<K> HashAggExecutor<K> {
             group_key_types: builder.group_key_types,
             schema: builder.schema,
             identity: builder.identity,
+            memory_context: builder.memory_context,
             _phantom: PhantomData,
         }
     }
@@ -182,84 +197,133 @@ impl<K: HashKey + Send + Sync> Executor for HashAggExecutor<K> {
 impl<K: HashKey + Send + Sync> HashAggExecutor<K> {
     #[try_stream(boxed, ok = DataChunk, error = RwError)]
     async fn do_execute(self: Box<Self>) {
-        // hash map for each agg groups
-        let mut groups = AggHashMap::<K>::default();
-
-        // consume all chunks to compute the agg result
-        #[for_await]
-        for chunk in self.child.execute() {
-            let chunk = chunk?.compact()?;
-            let keys = K::build(self.group_key_columns.as_slice(), &chunk)?;
-            for (row_id, key) in keys.into_iter().enumerate() {
-                let mut err_flag = Ok(());
-                let states: &mut Vec<BoxedAggState> = groups.entry(key).or_insert_with(|| {
-                    self.agg_factories
-                        .iter()
-                        .map(AggStateFactory::create_agg_state)
-                        .collect::<Result<Vec<_>>>()
-                        .unwrap_or_else(|x| {
-                            err_flag = Err(x);
-                            vec![]
-                        })
-                });
-                err_flag?;
-
-                // TODO: currently not a vectorized implementation
-                states
-                    .iter_mut()
-                    .for_each(|state| state.update_single(&chunk, row_id).unwrap());
+        let child_schema = self.child.schema().clone();
+        let group_size = std::mem::size_of::<K>()
+            + std::mem::size_of::<Vec<BoxedAggState>>()
+            + self.agg_factories.len() * ESTIMATED_AGG_STATE_SIZE;
+        let mut reservation = MemoryReservation::new(self.memory_context.clone(), &self.identity);
+
+        // The inputs to aggregate, with the depth of the spilled partitions.
+        let mut inputs = vec![(self.child.execute(), 0)];
+        while let Some((input, depth)) = inputs.pop() {
+            // hash map for each agg groups
+            let mut groups = AggHashMap::<K>::default();
+            let mut partitions: Option<SpillPartitions> = None;
+
+            // consume all chunks to compute the agg result
+            #[for_await]
+            for chunk in input {
+                let chunk = chunk?.compact()?;
+                let hash_codes =
+                    chunk.get_hash_values(&self.group_key_columns, CRC32FastBuilder)?;
+                let keys = K::build_from_hash_code(
+                    self.group_key_columns.as_slice(),
+                    &chunk,
+                    hash_codes.clone(),
+                );
+                // The rows of the new groups that don't fit into the memory.
+                let mut spilled_rows = vec![false; chunk.capacity()];
+                for (row_id, key) in keys.into_iter().enumerate() {
+                    if !groups.contains_key(&key) {
+                        if partitions.is_none() && !reservation.try_grow(group_size) {
+                            if depth >= MAX_SPILL_DEPTH {
+                                return Err(reservation.limit_exceeded().into());
+                            }
+                            partitions = Some(
+                                SpillPartitions::new(self.memory_context.spill_dir(), depth)
+                                    .await?,
+                            );
+                        }
+                        if partitions.is_some() {
+                            spilled_rows[row_id] = true;
+                            continue;
+                        }
+                    }
+
+                    let mut err_flag = Ok(());
+                    let states: &mut Vec<BoxedAggState> = groups.entry(key).or_insert_with(|| {
+                        self.agg_factories
+                            .iter()
+                            .map(AggStateFactory::create_agg_state)
+                            .collect::<Result<Vec<_>>>()
+                            .unwrap_or_else(|x| {
+                                err_flag = Err(x);
+                                vec![]
+                            })
+                    });
+                    err_flag?;
+
+                    // TODO: currently not a vectorized implementation
+                    states
+                        .iter_mut()
+                        .for_each(|state| state.update_single(&chunk, row_id).unwrap());
+                }
+                if let Some(partitions) = &mut partitions {
+                    let chunk = chunk.with_visibility(spilled_rows.into_iter().collect());
+                    partitions.write_chunk(&chunk, &hash_codes).await?;
+                }
             }
-        }
 
-        // generate output data chunks
-        let mut result = groups.into_iter();
-        let cardinality = DEFAULT_CHUNK_BUFFER_SIZE;
-        loop {
-            let mut group_builders: Vec<_> = self
-                .group_key_types
-                .iter()
-                .map(|datatype| datatype.create_array_builder(cardinality))
-                .collect();
-
-            let mut agg_builders: Vec<_> = self
-                .agg_factories
-                .iter()
-                .map(|agg_factory| {
-                    agg_factory
-                        .get_return_type()
-                        .create_array_builder(cardinality)
-                })
-                .collect();
-
-            let mut has_next = false;
-            let mut array_len = 0;
-            for (key, states) in result.by_ref().take(cardinality) {
-                has_next = true;
-                array_len += 1;
-                key.deserialize_to_builders(&mut group_builders[..])?;
-                states
+            // generate output data chunks
+            let mut result = groups.into_iter();
+            let cardinality = DEFAULT_CHUNK_BUFFER_SIZE;
+            loop {
+                let mut group_builders: Vec<_> = self
+                    .group_key_types
+                    .iter()
+                    .map(|datatype| datatype.create_array_builder(cardinality))
+                    .collect();
+
+                let mut agg_builders: Vec<_> = self
+                    .agg_factories
+                    .iter()
+                    .map(|agg_factory| {
+                        agg_factory
+                            .get_return_type()
+                            .create_array_builder(cardinality)
+                    })
+                    .collect();
+
+                let mut has_next = false;
+                let mut array_len = 0;
+                for (key, states) in result.by_ref().take(cardinality) {
+                    has_next = true;
+                    array_len += 1;
+                    key.deserialize_to_builders(&mut group_builders[..])?;
+                    states
+                        .into_iter()
+                        .zip_eq(&mut agg_builders)
+                        .try_for_each(|(aggregator, builder)| aggregator.output(builder))?;
+                }
+                if !has_next {
+                    break; // exit loop
+                }
+
+                let columns = group_builders
                     .into_iter()
-                    .zip_eq(&mut agg_builders)
-                    .try_for_each(|(aggregator, builder)| aggregator.output(builder))?;
-            }
-            if !has_next {
-                break; // exit loop
-            }
+                    .chain(agg_builders)
+                    .map(|b| Ok(Column::new(Arc::new(b.finish()?))))
+                    .collect::<Result<Vec<_>>>()?;
 
-            let columns = group_builders
-                .into_iter()
-                .chain(agg_builders)
-                .map(|b| Ok(Column::new(Arc::new(b.finish()?))))
-                .collect::<Result<Vec<_>>>()?;
+                let output = DataChunk::new(columns, array_len);
+                yield output;
+            }
+            reservation.free();
 
-            let output = DataChunk::new(columns, array_len);
-            yield output;
+            if let Some(partitions) = partitions {
+                for reader in partitions.into_readers().await? {
+                    let input = reader.into_executor(child_schema.clone(), self.identity.clone());
+                    inputs.push((input.execute(), depth + 1));
+                }
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::StreamExt;
+    use risingwave_common::array::Array;
     use risingwave_common::catalog::{Field, Schema};
     use risingwave_common::test_prelude::DataChunkTestExt;
     use risingwave_pb::data::data_type::TypeName;
@@ -269,6 +333,7 @@ mod tests {
 
     use super::*;
     use crate::executor::test_utils::{diff_executor_output, MockExecutor};
+    use crate::task::MemoryContext;
 
     #[tokio::test]
     async fn execute_int32_grouped() {
@@ -324,6 +389,7 @@ mod tests {
             Box::new(src_exec),
             TaskId::default(),
             "HashAggExecutor".to_string(),
+            MemoryContext::unlimited(),
         )
         .unwrap();
 
@@ -391,6 +457,7 @@ mod tests {
             Box::new(src_exec),
             TaskId::default(),
             "HashAggExecutor".to_string(),
+            MemoryContext::unlimited(),
         )
         .unwrap();
         let schema = Schema {
@@ -406,4 +473,66 @@ mod tests {
         );
         diff_executor_output(actual_exec, Box::new(expect_exec)).await;
     }
+
+    #[tokio::test]
+    async fn execute_with_spill() {
+        let t32 = DataType::Int32;
+        let mut src_exec = MockExecutor::new(Schema {
+            fields: vec![Field::unnamed(t32.clone()), Field::unnamed(t32)],
+        });
+        for round in 1..=3 {
+            let rows = (0..40).map(|key| format!("{} {}", key, round)).join("\n");
+            src_exec.add(DataChunk::from_pretty(&format!("i i\n{}", rows)));
+        }
+
+        let agg_call = AggCall {
+            r#type: Type::Sum as i32,
+            args: vec![Arg {
+                input: Some(InputRefExpr { column_idx: 1 }),
+                r#type: Some(ProstDataType {
+                    type_name: TypeName::Int32 as i32,
+                    ..Default::default()
+                }),
+            }],
+            return_type: Some(ProstDataType {
+                type_name: TypeName::Int64 as i32,
+                ..Default::default()
+            }),
+            distinct: false,
+            order_by_fields: vec![],
+            filter: None,
+        };
+        let agg_prost = HashAggNode {
+            group_key: vec![0],
+            agg_calls: vec![agg_call],
+        };
+
+        // Only a few groups fit into the memory, so the rest are spilled and aggregated later.
+        let memory_context = Arc::new(MemoryContext::new(Some(500), std::env::temp_dir()));
+        let actual_exec = HashAggExecutorBuilder::deserialize(
+            &agg_prost,
+            Box::new(src_exec),
+            TaskId::default(),
+            "HashAggExecutor".to_string(),
+            memory_context.clone(),
+        )
+        .unwrap();
+
+        let mut groups = vec![];
+        let mut stream = actual_exec.execute();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            let keys = chunk.column_at(0).array();
+            let sums = chunk.column_at(1).array();
+            groups.extend(
+                keys.as_int32()
+                    .iter()
+                    .zip_eq(sums.as_int64().iter())
+                    .map(|(key, sum)| (key.unwrap(), sum.unwrap())),
+            );
+        }
+        groups.sort_unstable();
+        assert_eq!(groups, (0..40).map(|key| (key, 6)).collect_vec());
+        assert_eq!(memory_context.used(), 0);
+    }
 }
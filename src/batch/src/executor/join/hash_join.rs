@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::collections::HashMap;
-use std::iter::empty;
+use std::iter::{empty, once};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -29,14 +29,15 @@ use risingwave_common::hash::{
 };
 use risingwave_common::types::DataType;
 use risingwave_common::util::chunk_coalesce::DataChunkBuilder;
-use risingwave_expr::expr::{build_from_prost, BoxedExpression, Expression};
+use risingwave_expr::expr::{build_from_prost, BoxedExpression, Expression, ExpressionRef};
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 
 use super::{ChunkedData, JoinType, RowId};
+use crate::executor::spill::{SpillPartitions, MAX_SPILL_DEPTH};
 use crate::executor::{
     BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
 };
-use crate::task::BatchTaskContext;
+use crate::task::{BatchTaskContext, MemoryContextRef, MemoryReservation};
 
 /// Hash Join Executor
 ///
@@ -47,6 +48,10 @@ use crate::task::BatchTaskContext;
 /// 3. Concatenate the matched pair of probe side row and build side row into a single row and push
 /// it into the data chunk builder.
 /// 4. Yield chunks from the builder.
+///
+/// If the build side exceeds the memory budget of the query, both sides are spilled into
+/// partitions by the hash of the join keys, and the partitions are joined one by one in the same
+/// way, i.e. a grace hash join.
 pub struct HashJoinExecutor<K> {
     /// Join type e.g. inner, left outer, ...
     join_type: JoinType,
@@ -65,10 +70,13 @@ pub struct HashJoinExecutor<K> {
     /// Column indices of right keys in equi join
     build_key_idxs: Vec<usize>,
     /// Non-equi join condition (optional)
-    cond: Option<BoxedExpression>,
+    cond: Option<ExpressionRef>,
     /// Whether or not to find matched build rows for probe rows with NULL keys
     match_null: bool,
     identity: String,
+    memory_context: MemoryContextRef,
+    /// How many times the rows have been spilled into partitions, 0 if not.
+    spill_depth: usize,
     _phantom: PhantomData<K>,
 }
 
@@ -186,16 +194,86 @@ impl<K: HashKey> HashJoinExecutor<K> {
         let probe_data_types = self.probe_side_source.schema().data_types();
         let build_data_types = self.build_side_source.schema().data_types();
         let full_data_types = [probe_data_types.clone(), build_data_types.clone()].concat();
+        let probe_schema = self.probe_side_source.schema().clone();
+        let build_schema = self.build_side_source.schema().clone();
+
+        // The build side together with the hash map to be built from it.
+        let build_row_size = std::mem::size_of::<K>() + std::mem::size_of::<Option<RowId>>();
+        let mut reservation = MemoryReservation::new(self.memory_context.clone(), &self.identity);
+        let mut build_partitions: Option<SpillPartitions> = None;
 
         let mut build_side = Vec::new();
         let mut build_row_count = 0;
         #[for_await]
         for build_chunk in self.build_side_source.execute() {
             let build_chunk = build_chunk?;
-            if build_chunk.cardinality() > 0 {
-                build_row_count += build_chunk.cardinality();
-                build_side.push(build_chunk.compact()?)
+            if build_chunk.cardinality() == 0 {
+                continue;
+            }
+            if let Some(partitions) = &mut build_partitions {
+                partitions
+                    .write_chunk_by_keys(build_chunk, &self.build_key_idxs)
+                    .await?;
+                continue;
+            }
+            let size = build_chunk.estimated_size() + build_chunk.cardinality() * build_row_size;
+            if !reservation.try_grow(size) {
+                if self.spill_depth >= MAX_SPILL_DEPTH {
+                    return Err(reservation.limit_exceeded().into());
+                }
+                let mut partitions =
+                    SpillPartitions::new(self.memory_context.spill_dir(), self.spill_depth).await?;
+                for chunk in build_side.drain(..).chain(once(build_chunk)) {
+                    partitions
+                        .write_chunk_by_keys(chunk, &self.build_key_idxs)
+                        .await?;
+                }
+                reservation.free();
+                build_partitions = Some(partitions);
+                continue;
+            }
+            build_row_count += build_chunk.cardinality();
+            build_side.push(build_chunk.compact()?)
+        }
+
+        if let Some(build_partitions) = build_partitions {
+            let mut probe_partitions =
+                SpillPartitions::new(self.memory_context.spill_dir(), self.spill_depth).await?;
+            #[for_await]
+            for probe_chunk in self.probe_side_source.execute() {
+                probe_partitions
+                    .write_chunk_by_keys(probe_chunk?, &self.probe_key_idxs)
+                    .await?;
+            }
+
+            // The rows of a key are in the same partition on both sides, so that the partitions
+            // can be joined separately.
+            for (probe, build) in probe_partitions
+                .into_readers()
+                .await?
+                .into_iter()
+                .zip_eq(build_partitions.into_readers().await?)
+            {
+                let mut partition = HashJoinExecutor::<K>::new(
+                    self.join_type,
+                    self.output_indices.clone(),
+                    probe.into_executor(probe_schema.clone(), self.identity.clone()),
+                    build.into_executor(build_schema.clone(), self.identity.clone()),
+                    self.probe_key_idxs.clone(),
+                    self.build_key_idxs.clone(),
+                    self.match_null,
+                    None,
+                    self.identity.clone(),
+                    self.memory_context.clone(),
+                );
+                partition.cond = self.cond.clone();
+                partition.spill_depth = self.spill_depth + 1;
+                #[for_await]
+                for chunk in Box::new(partition).execute() {
+                    yield chunk?;
+                }
             }
+            return Ok(());
         }
         let mut hash_map =
             JoinHashMap::with_capacity_and_hasher(build_row_count, PrecomputedBuildHasher);
@@ -320,10 +398,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
     }
 
     #[try_stream(boxed, ok = DataChunk, error = RwError)]
-    async fn do_inner_join_with_non_equi_condition(
-        params: EquiJoinParams<K>,
-        cond: BoxedExpression,
-    ) {
+    async fn do_inner_join_with_non_equi_condition(params: EquiJoinParams<K>, cond: ExpressionRef) {
         #[for_await]
         for chunk in Self::do_inner_join(params) {
             let mut chunk = chunk?;
@@ -396,7 +471,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             next_build_row_with_same_key,
             ..
         }: EquiJoinParams<K>,
-        cond: BoxedExpression,
+        cond: ExpressionRef,
     ) {
         let mut chunk_builder = DataChunkBuilder::with_default_size(full_data_types);
         let mut non_equi_state = LeftNonEquiJoinState {
@@ -508,7 +583,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             next_build_row_with_same_key,
             ..
         }: EquiJoinParams<K>,
-        cond: BoxedExpression,
+        cond: ExpressionRef,
     ) {
         let mut chunk_builder = DataChunkBuilder::with_default_size(full_data_types);
         let mut non_equi_state = LeftNonEquiJoinState::default();
@@ -565,7 +640,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             next_build_row_with_same_key,
             ..
         }: EquiJoinParams<K>,
-        cond: BoxedExpression,
+        cond: ExpressionRef,
     ) {
         let mut chunk_builder = DataChunkBuilder::with_default_size(full_data_types);
         let mut remaining_chunk_builder = DataChunkBuilder::with_default_size(probe_data_types);
@@ -686,7 +761,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             next_build_row_with_same_key,
             ..
         }: EquiJoinParams<K>,
-        cond: BoxedExpression,
+        cond: ExpressionRef,
     ) {
         let mut chunk_builder = DataChunkBuilder::with_default_size(full_data_types);
         let build_row_matched =
@@ -790,7 +865,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             next_build_row_with_same_key,
             ..
         }: EquiJoinParams<K>,
-        cond: BoxedExpression,
+        cond: ExpressionRef,
     ) {
         let mut chunk_builder = DataChunkBuilder::with_default_size(full_data_types);
         let mut remaining_chunk_builder = DataChunkBuilder::with_default_size(build_data_types);
@@ -919,7 +994,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             next_build_row_with_same_key,
             ..
         }: EquiJoinParams<K>,
-        cond: BoxedExpression,
+        cond: ExpressionRef,
     ) {
         let mut chunk_builder = DataChunkBuilder::with_default_size(full_data_types.clone());
         let mut remaining_chunk_builder = DataChunkBuilder::with_default_size(full_data_types);
@@ -1628,6 +1703,7 @@ impl BoxedExecutorBuilder for HashJoinExecutor<()> {
                 false,
                 cond,
                 context.plan_node().get_identity().clone(),
+                context.context().memory_context(context.task_id),
             ),
         ))
     }
@@ -1638,17 +1714,22 @@ impl HashKeyDispatcher for HashJoinExecutor<()> {
     type Output = BoxedExecutor;
 
     fn dispatch<K: HashKey>(input: Self::Input) -> Self::Output {
-        Box::new(HashJoinExecutor::<K>::new(
-            input.join_type,
-            input.output_indices,
-            input.probe_side_source,
-            input.build_side_source,
-            input.probe_key_idxs,
-            input.build_key_idxs,
-            input.match_null,
-            input.cond,
-            input.identity,
-        ))
+        Box::new(HashJoinExecutor::<K> {
+            join_type: input.join_type,
+            original_schema: input.original_schema,
+            schema: input.schema,
+            output_indices: input.output_indices,
+            probe_side_source: input.probe_side_source,
+            build_side_source: input.build_side_source,
+            probe_key_idxs: input.probe_key_idxs,
+            build_key_idxs: input.build_key_idxs,
+            cond: input.cond,
+            match_null: input.match_null,
+            identity: input.identity,
+            memory_context: input.memory_context,
+            spill_depth: input.spill_depth,
+            _phantom: PhantomData,
+        })
     }
 }
 
@@ -1664,6 +1745,7 @@ impl<K> HashJoinExecutor<K> {
         match_null: bool,
         cond: Option<BoxedExpression>,
         identity: String,
+        memory_context: MemoryContextRef,
     ) -> Self {
         let original_schema = match join_type {
             JoinType::LeftSemi | JoinType::LeftAnti => probe_side_source.schema().clone(),
@@ -1692,8 +1774,10 @@ impl<K> HashJoinExecutor<K> {
             probe_key_idxs,
             build_key_idxs,
             match_null,
-            cond,
+            cond: cond.map(ExpressionRef::from),
             identity,
+            memory_context,
+            spill_depth: 0,
             _phantom: PhantomData,
         }
     }
@@ -1721,6 +1805,7 @@ mod tests {
     };
     use crate::executor::test_utils::MockExecutor;
     use crate::executor::BoxedExecutor;
+    use crate::task::{MemoryContext, MemoryContextRef};
     struct DataChunkMerger {
         data_types: Vec<DataType>,
         array_builders: Vec<ArrayBuilderImpl>,
@@ -1906,7 +1991,11 @@ mod tests {
             )
         }
 
-        fn create_join_executor(&self, has_non_equi_cond: bool) -> BoxedExecutor {
+        fn create_join_executor(
+            &self,
+            has_non_equi_cond: bool,
+            memory_context: MemoryContextRef,
+        ) -> BoxedExecutor {
             let join_type = self.join_type;
 
             let left_child = self.create_left_executor();
@@ -1935,11 +2024,15 @@ mod tests {
                 false,
                 cond,
                 "HashJoinExecutor".to_string(),
+                memory_context,
             ))
         }
 
         async fn do_test(&self, expected: DataChunk, has_non_equi_cond: bool) {
-            let join_executor = self.create_join_executor(has_non_equi_cond);
+            self.do_test_with_spill(&expected, has_non_equi_cond).await;
+
+            let join_executor =
+                self.create_join_executor(has_non_equi_cond, MemoryContext::unlimited());
 
             let mut data_chunk_merger = DataChunkMerger::new(self.output_data_types()).unwrap();
 
@@ -1970,6 +2063,27 @@ mod tests {
             // assert_eq!(expected, result_chunk);
             assert!(is_data_chunk_eq(&expected, &result_chunk));
         }
+
+        /// Joins with the build side exceeding the memory budget, so that it's spilled and joined
+        /// partition by partition, in which case the rows are in a different order.
+        async fn do_test_with_spill(&self, expected: &DataChunk, has_non_equi_cond: bool) {
+            let memory_context = Arc::new(MemoryContext::new(Some(300), std::env::temp_dir()));
+            let join_executor =
+                self.create_join_executor(has_non_equi_cond, memory_context.clone());
+
+            let mut actual_rows = vec![];
+            let mut stream = join_executor.execute();
+            while let Some(data_chunk) = stream.next().await {
+                let data_chunk = data_chunk.unwrap().compact().unwrap();
+                actual_rows.extend(data_chunk.rows().map(|row| row.to_owned_row()));
+            }
+            actual_rows.sort();
+
+            let mut expected_rows = expected.rows().map(|row| row.to_owned_row()).collect_vec();
+            expected_rows.sort();
+            assert_eq!(expected_rows, actual_rows);
+            assert_eq!(memory_context.used(), 0);
+        }
     }
 
     /// Sql:
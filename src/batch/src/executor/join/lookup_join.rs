@@ -157,12 +157,14 @@ impl<C: BatchTaskContext> ProbeSideSource<C> {
                 root: Some(PlanNode {
                     children: vec![],
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: 0,
                     node_body: Some(self.create_row_seq_scan_node(id)?),
                 }),
                 exchange_info: Some(ExchangeInfo {
                     mode: DistributionMode::Single as i32,
                     ..Default::default()
                 }),
+                collect_executor_stats: false,
             }),
             epoch: self.epoch,
         };
@@ -246,6 +248,7 @@ impl<C: BatchTaskContext> ProbeSideSourceBuilder for ProbeSideSource<C> {
         let plan_node = PlanNode {
             children: vec![],
             identity: "LookupJoinExchangeExecutor".to_string(),
+            operator_id: 0,
             node_body: Some(exchange_node),
         };
 
@@ -715,6 +718,7 @@ mod tests {
         diff_executor_output, FakeProbeSideSourceBuilder, MockExecutor,
     };
     use crate::executor::{BoxedExecutor, LookupJoinExecutor, OrderByExecutor};
+    use crate::task::MemoryContext;
 
     pub struct MockGatherExecutor {
         chunks: Vec<DataChunk>,
@@ -809,6 +813,7 @@ mod tests {
             false,
             "OrderByExecutor".to_string(),
             2048,
+            MemoryContext::unlimited(),
         ))
     }
 
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use anyhow::anyhow;
+mod analyze;
 mod delete;
 mod expand;
 mod filter;
@@ -24,18 +25,22 @@ mod limit;
 mod merge_sort_exchange;
 pub mod monitor;
 mod order_by;
+mod over_agg;
 mod project;
 mod project_set;
 mod row_seq_scan;
 mod sort_agg;
+mod spill;
 mod sys_row_seq_scan;
 mod table_function;
 pub mod test_utils;
 mod top_n;
 mod trace;
+mod union;
 mod update;
 mod values;
 
+pub use analyze::*;
 use async_recursion::async_recursion;
 pub use delete::*;
 pub use expand::*;
@@ -50,6 +55,7 @@ pub use limit::*;
 pub use merge_sort_exchange::*;
 pub use monitor::*;
 pub use order_by::*;
+pub use over_agg::*;
 pub use project::*;
 pub use project_set::*;
 use risingwave_common::array::DataChunk;
@@ -62,6 +68,7 @@ pub use sort_agg::*;
 pub use table_function::*;
 pub use top_n::*;
 pub use trace::*;
+pub use union::*;
 pub use update::*;
 pub use values::*;
 
@@ -107,6 +114,8 @@ pub struct ExecutorBuilder<'a, C> {
     pub task_id: &'a TaskId,
     context: C,
     epoch: u64,
+    /// Set to collect the runtime statistics of the operators for `EXPLAIN ANALYZE`.
+    executor_stats: Option<TaskExecutorStatsRef>,
 }
 
 macro_rules! build_executor {
@@ -128,12 +137,24 @@ impl<'a, C: Clone> ExecutorBuilder<'a, C> {
             task_id,
             context,
             epoch,
+            executor_stats: None,
         }
     }
 
+    #[must_use]
+    pub fn with_executor_stats(mut self, executor_stats: TaskExecutorStatsRef) -> Self {
+        self.executor_stats = Some(executor_stats);
+        self
+    }
+
     #[must_use]
     pub fn clone_for_plan(&self, plan_node: &'a PlanNode) -> Self {
-        ExecutorBuilder::new(plan_node, self.task_id, self.context.clone(), self.epoch)
+        let builder =
+            ExecutorBuilder::new(plan_node, self.task_id, self.context.clone(), self.epoch);
+        match &self.executor_stats {
+            Some(executor_stats) => builder.with_executor_stats(executor_stats.clone()),
+            None => builder,
+        }
     }
 
     pub fn plan_node(&self) -> &PlanNode {
@@ -193,10 +214,19 @@ impl<'a, C: BatchTaskContext> ExecutorBuilder<'a, C> {
             NodeBody::Expand => ExpandExecutor,
             NodeBody::LookupJoin => LookupJoinExecutorBuilder,
             NodeBody::ProjectSet => ProjectSetExecutor,
+            NodeBody::Union => UnionExecutor,
+            NodeBody::OverAgg => OverAggExecutor,
         }
         .await?;
         let input_desc = real_executor.identity().to_string();
-        Ok(Box::new(TraceExecutor::new(real_executor, input_desc)) as BoxedExecutor)
+        let executor = match &self.executor_stats {
+            Some(executor_stats) => {
+                let stats = executor_stats.register(self.plan_node.operator_id, &input_desc);
+                Box::new(AnalyzeExecutor::new(real_executor, stats)) as BoxedExecutor
+            }
+            None => real_executor,
+        };
+        Ok(Box::new(TraceExecutor::new(executor, input_desc)) as BoxedExecutor)
     }
 }
 
@@ -0,0 +1,91 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use risingwave_pb::task_service::ExecutorStats as ProstExecutorStats;
+
+use crate::task::MemoryContextRef;
+
+/// The runtime statistics of an operator, collected for `EXPLAIN ANALYZE`.
+#[derive(Debug)]
+pub struct ExecutorStats {
+    operator_id: i32,
+    identity: String,
+    output_rows: AtomicU64,
+    output_chunks: AtomicU64,
+    elapsed_ns: AtomicU64,
+}
+
+impl ExecutorStats {
+    pub fn record_chunk(&self, rows: usize) {
+        self.output_rows.fetch_add(rows as u64, Ordering::Relaxed);
+        self.output_chunks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_elapsed(&self, elapsed: Duration) {
+        self.elapsed_ns
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+pub type TaskExecutorStatsRef = Arc<TaskExecutorStats>;
+
+/// The runtime statistics of the operators of a task, which are reported with its final status.
+#[derive(Debug)]
+pub struct TaskExecutorStats {
+    executors: Mutex<Vec<Arc<ExecutorStats>>>,
+    /// Where the peak memory of the operators is tracked.
+    memory_context: MemoryContextRef,
+}
+
+impl TaskExecutorStats {
+    pub fn new(memory_context: MemoryContextRef) -> Self {
+        Self {
+            executors: Mutex::new(vec![]),
+            memory_context,
+        }
+    }
+
+    /// Starts collecting the statistics of the operator of the plan node.
+    pub fn register(&self, operator_id: i32, identity: impl Into<String>) -> Arc<ExecutorStats> {
+        let stats = Arc::new(ExecutorStats {
+            operator_id,
+            identity: identity.into(),
+            output_rows: AtomicU64::new(0),
+            output_chunks: AtomicU64::new(0),
+            elapsed_ns: AtomicU64::new(0),
+        });
+        self.executors.lock().push(stats.clone());
+        stats
+    }
+
+    pub fn to_prost(&self) -> Vec<ProstExecutorStats> {
+        self.executors
+            .lock()
+            .iter()
+            .map(|stats| ProstExecutorStats {
+                operator_id: stats.operator_id,
+                identity: stats.identity.clone(),
+                output_rows: stats.output_rows.load(Ordering::Relaxed),
+                output_chunks: stats.output_chunks.load(Ordering::Relaxed),
+                elapsed_ns: stats.elapsed_ns.load(Ordering::Relaxed),
+                peak_memory_bytes: self.memory_context.peak_of(&stats.identity) as u64,
+            })
+            .collect()
+    }
+}
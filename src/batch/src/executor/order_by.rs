@@ -31,10 +31,17 @@ use risingwave_common::util::sort_util::{compare_rows_in_chunk, HeapElem, OrderP
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 
 use crate::error::BatchError;
+use crate::executor::spill::{SpillFile, SpillReader};
 use crate::executor::{
     BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
 };
-use crate::task::BatchTaskContext;
+use crate::task::{BatchTaskContext, MemoryContextRef, MemoryReservation};
+
+/// Sorts the rows of the child. The chunks are sorted one by one and then merged by a heap.
+///
+/// When the chunks exceed the memory budget of the query, they are sorted into a run and spilled
+/// to disk, and in the end the runs are merged by the heap as well, reading a chunk of each run at
+/// a time.
 pub struct OrderByExecutor {
     child: Option<BoxedExecutor>,
     sorted_indices: Vec<Vec<usize>>,
@@ -48,6 +55,9 @@ pub struct OrderByExecutor {
     identity: String,
     chunk_size: usize,
     schema: Schema,
+    memory_context: MemoryContextRef,
+    /// The sorted runs spilled to disk, one for each of the `chunks` when merging them.
+    runs: Vec<SpillReader>,
 }
 
 #[expect(clippy::too_many_arguments)]
@@ -64,6 +74,7 @@ impl OrderByExecutor {
         disable_encoding: bool,
         identity: String,
         chunk_size: usize,
+        memory_context: MemoryContextRef,
     ) -> Self {
         let schema = child.schema().clone();
         Self {
@@ -79,6 +90,8 @@ impl OrderByExecutor {
             identity,
             chunk_size,
             schema,
+            memory_context,
+            runs: vec![],
         }
     }
 }
@@ -116,39 +129,62 @@ impl BoxedExecutorBuilder for OrderByExecutor {
             false,
             source.plan_node().get_identity().clone(),
             DEFAULT_CHUNK_BUFFER_SIZE,
+            source.context().memory_context(source.task_id),
         )))
     }
 }
 
 impl OrderByExecutor {
-    fn push_heap_for_chunk(&mut self, idx: usize) {
-        while self.vis_indices[idx] < self.chunks[idx].capacity() {
-            let skip: bool = match self.chunks[idx].visibility() {
-                Some(visibility) => visibility
-                    .is_set(self.sorted_indices[idx][self.vis_indices[idx]])
-                    .map(|b| !b)
-                    .unwrap_or(false),
-                None => false,
-            };
-            if !skip {
-                let elem_idx = self.sorted_indices[idx][self.vis_indices[idx]];
-                let elem = HeapElem {
-                    order_pairs: self.order_pairs.clone(),
-                    chunk: self.chunks[idx].clone(),
-                    chunk_idx: idx,
-                    elem_idx,
-                    encoded_chunk: if self.encodable && !self.disable_encoding {
-                        Some(self.encoded_keys[idx].clone())
-                    } else {
-                        None
-                    },
+    async fn push_heap_for_chunk(&mut self, idx: usize) -> Result<()> {
+        loop {
+            while self.vis_indices[idx] < self.chunks[idx].capacity() {
+                let skip: bool = match self.chunks[idx].visibility() {
+                    Some(visibility) => visibility
+                        .is_set(self.sorted_indices[idx][self.vis_indices[idx]])
+                        .map(|b| !b)
+                        .unwrap_or(false),
+                    None => false,
                 };
-                self.min_heap.push(elem);
+                if !skip {
+                    let elem_idx = self.sorted_indices[idx][self.vis_indices[idx]];
+                    let elem = HeapElem {
+                        order_pairs: self.order_pairs.clone(),
+                        chunk: self.chunks[idx].clone(),
+                        chunk_idx: idx,
+                        elem_idx,
+                        encoded_chunk: if self.encodable && !self.disable_encoding {
+                            Some(self.encoded_keys[idx].clone())
+                        } else {
+                            None
+                        },
+                    };
+                    self.min_heap.push(elem);
+                    self.vis_indices[idx] += 1;
+                    return Ok(());
+                }
                 self.vis_indices[idx] += 1;
-                break;
             }
-            self.vis_indices[idx] += 1;
+
+            // Continue with the next chunk of the run, if the chunk is from a spilled one.
+            let chunk = match self.runs.get_mut(idx) {
+                Some(run) => run.read_chunk().await?,
+                None => None,
+            };
+            match chunk {
+                Some(chunk) => self.set_run_chunk(idx, chunk),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Replaces the chunk at `idx` with the next one of its run, which is sorted already.
+    fn set_run_chunk(&mut self, idx: usize, chunk: DataChunk) {
+        if !self.disable_encoding && self.encodable {
+            self.encoded_keys[idx] = Arc::new(encode_chunk(&chunk, &self.order_pairs));
         }
+        self.sorted_indices[idx] = (0..chunk.capacity()).collect();
+        self.vis_indices[idx] = 0;
+        self.chunks[idx] = chunk;
     }
 
     fn get_order_index_from(&self, idx: usize) -> Vec<usize> {
@@ -172,23 +208,134 @@ impl OrderByExecutor {
         index
     }
 
-    async fn collect_child_data(&mut self) -> Result<()> {
+    /// Collects the chunks of the child and fills the heap with the first row of each chunk.
+    /// Returns the memory reserved for the chunks held, which are released once it's dropped.
+    async fn collect_child_data(&mut self) -> Result<MemoryReservation> {
+        let mut reservation = MemoryReservation::new(self.memory_context.clone(), &self.identity);
+        let mut runs = vec![];
         let mut stream = self.child.take().unwrap().execute();
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
-            if !self.disable_encoding && self.encodable {
-                self.encoded_keys
-                    .push(Arc::new(encode_chunk(&chunk, &self.order_pairs)));
+            let encoded_keys = if !self.disable_encoding && self.encodable {
+                Some(Arc::new(encode_chunk(&chunk, &self.order_pairs)))
+            } else {
+                None
+            };
+            let size = chunk.estimated_size()
+                + chunk.capacity() * std::mem::size_of::<usize>()
+                + encoded_keys
+                    .as_ref()
+                    .map_or(0, |keys| keys.iter().map(Vec::len).sum());
+            if !reservation.try_grow(size) {
+                if self.chunks.is_empty() {
+                    return Err(reservation.limit_exceeded().into());
+                }
+                runs.push(self.spill_sorted_run().await?);
+                reservation.free();
+                reservation.grow(size)?;
+            }
+
+            if let Some(encoded_keys) = encoded_keys {
+                self.encoded_keys.push(encoded_keys);
             }
             self.chunks.push(chunk);
             self.sorted_indices
                 .push(self.get_order_index_from(self.chunks.len() - 1));
         }
+
+        if !runs.is_empty() {
+            // Merge the runs only, with the remaining chunks spilled as the last run.
+            if !self.chunks.is_empty() {
+                runs.push(self.spill_sorted_run().await?);
+            }
+            reservation.free();
+            for mut run in runs {
+                if let Some(chunk) = run.read_chunk().await? {
+                    self.chunks.push(DataChunk::new_dummy(0));
+                    self.sorted_indices.push(vec![]);
+                    self.vis_indices.push(0);
+                    if !self.disable_encoding && self.encodable {
+                        self.encoded_keys.push(Arc::new(vec![]));
+                    }
+                    self.set_run_chunk(self.chunks.len() - 1, chunk);
+                    self.runs.push(run);
+                }
+            }
+        }
+
         self.vis_indices = vec![0usize; self.chunks.len()];
         for idx in 0..self.chunks.len() {
-            self.push_heap_for_chunk(idx);
+            self.push_heap_for_chunk(idx).await?;
         }
-        Ok(())
+        Ok(reservation)
+    }
+
+    /// Merges the chunks held into a sorted run on disk, and clears them.
+    async fn spill_sorted_run(&mut self) -> Result<SpillReader> {
+        let mut file = SpillFile::new(self.memory_context.spill_dir()).await?;
+        self.vis_indices = vec![0usize; self.chunks.len()];
+        for idx in 0..self.chunks.len() {
+            self.push_heap_for_chunk(idx).await?;
+        }
+        while let Some(chunk) = self.next_sorted_chunk().await? {
+            file.write_chunk(chunk).await?;
+        }
+        self.chunks.clear();
+        self.sorted_indices.clear();
+        self.vis_indices.clear();
+        self.encoded_keys.clear();
+        Ok(file.into_reader().await?)
+    }
+
+    /// Pops the rows from the heap into a chunk of at most `chunk_size` rows.
+    async fn next_sorted_chunk(&mut self) -> Result<Option<DataChunk>> {
+        let mut array_builders = self.schema().create_array_builders(self.chunk_size);
+
+        let mut chunk_size = 0usize;
+        while !self.min_heap.is_empty() && chunk_size < self.chunk_size {
+            let top = self.min_heap.pop().unwrap();
+            for (idx, builder) in array_builders.iter_mut().enumerate() {
+                let chunk_arr = self.chunks[top.chunk_idx].column_at(idx).array();
+                let chunk_arr = chunk_arr.as_ref();
+                macro_rules! gen_match {
+                    ($b: ident, $a: ident, [$( $tt: ident), *]) => {
+                        match ($b, $a) {
+                            $((ArrayBuilderImpl::$tt($b), ArrayImpl::$tt($a)) => Ok($b.append($a.value_at(top.elem_idx))),)*
+                            _ => Err(BatchError::Internal(anyhow!("Unmatched array and array builder types"))),
+                        }?
+                    }
+                }
+                let _ = gen_match!(
+                    builder,
+                    chunk_arr,
+                    [
+                        Int16,
+                        Int32,
+                        Int64,
+                        Float32,
+                        Float64,
+                        Utf8,
+                        Bool,
+                        Decimal,
+                        Interval,
+                        NaiveDate,
+                        NaiveTime,
+                        NaiveDateTime,
+                        Struct
+                    ]
+                );
+            }
+            chunk_size += 1;
+            self.push_heap_for_chunk(top.chunk_idx).await?;
+        }
+        if chunk_size == 0 {
+            return Ok(None);
+        }
+        let columns = array_builders
+            .into_iter()
+            .map(|b| Ok(Column::new(Arc::new(b.finish()?))))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(DataChunk::new(columns, chunk_size)))
     }
 }
 
@@ -218,57 +365,10 @@ impl OrderByExecutor {
                 .all(is_type_encodable)
         }
 
-        self.collect_child_data().await?;
+        let _reservation = self.collect_child_data().await?;
 
-        loop {
-            let mut array_builders = self.schema().create_array_builders(self.chunk_size);
-
-            let mut chunk_size = 0usize;
-            while !self.min_heap.is_empty() && chunk_size < self.chunk_size {
-                let top = self.min_heap.pop().unwrap();
-                for (idx, builder) in array_builders.iter_mut().enumerate() {
-                    let chunk_arr = self.chunks[top.chunk_idx].column_at(idx).array();
-                    let chunk_arr = chunk_arr.as_ref();
-                    macro_rules! gen_match {
-                        ($b: ident, $a: ident, [$( $tt: ident), *]) => {
-                            match ($b, $a) {
-                                $((ArrayBuilderImpl::$tt($b), ArrayImpl::$tt($a)) => Ok($b.append($a.value_at(top.elem_idx))),)*
-                                _ => Err(BatchError::Internal(anyhow!("Unmatched array and array builder types"))),
-                            }?
-                        }
-                    }
-                    let _ = gen_match!(
-                        builder,
-                        chunk_arr,
-                        [
-                            Int16,
-                            Int32,
-                            Int64,
-                            Float32,
-                            Float64,
-                            Utf8,
-                            Bool,
-                            Decimal,
-                            Interval,
-                            NaiveDate,
-                            NaiveTime,
-                            NaiveDateTime,
-                            Struct
-                        ]
-                    );
-                }
-                chunk_size += 1;
-                self.push_heap_for_chunk(top.chunk_idx);
-            }
-            if chunk_size == 0 {
-                break;
-            }
-            let columns = array_builders
-                .into_iter()
-                .map(|b| Ok(Column::new(Arc::new(b.finish()?))))
-                .collect::<Result<Vec<_>>>()?;
-            let chunk = DataChunk::new(columns, chunk_size);
-            yield chunk
+        while let Some(chunk) = self.next_sorted_chunk().await? {
+            yield chunk;
         }
     }
 }
@@ -285,6 +385,7 @@ mod tests {
 
     use super::*;
     use crate::executor::test_utils::MockExecutor;
+    use crate::task::MemoryContext;
 
     #[tokio::test]
     async fn test_simple_order_by_executor() {
@@ -324,6 +425,7 @@ mod tests {
             false,
             "OrderByExecutor2".to_string(),
             DEFAULT_CHUNK_BUFFER_SIZE,
+            MemoryContext::unlimited(),
         ));
         let fields = &order_by_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Int32);
@@ -380,6 +482,7 @@ mod tests {
             false,
             "OrderByExecutor2".to_string(),
             DEFAULT_CHUNK_BUFFER_SIZE,
+            MemoryContext::unlimited(),
         ));
         let fields = &order_by_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Float32);
@@ -436,6 +539,7 @@ mod tests {
             false,
             "OrderByExecutor2".to_string(),
             DEFAULT_CHUNK_BUFFER_SIZE,
+            MemoryContext::unlimited(),
         ));
         let fields = &order_by_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Varchar);
@@ -453,6 +557,96 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_order_by_with_spill() {
+        let schema = Schema {
+            fields: vec![
+                Field::unnamed(DataType::Int32),
+                Field::unnamed(DataType::Varchar),
+            ],
+        };
+        let mut mock_executor = MockExecutor::new(schema.clone());
+        let mut expected = vec![];
+        for i in 0..10 {
+            let chunk = DataChunk::from_pretty(&format!(
+                "i T
+                 {} a
+                 {} b
+                 {} c",
+                30 - i,
+                i,
+                15 + i,
+            ));
+            mock_executor.add(chunk);
+            expected.extend([30 - i, i, 15 + i]);
+        }
+        expected.sort_unstable();
+        let order_pairs = vec![OrderPair {
+            column_idx: 0,
+            order_type: OrderType::Ascending,
+        }];
+
+        // Each chunk takes around 100 bytes, so the rows are spilled into several runs.
+        let memory_context = Arc::new(MemoryContext::new(Some(256), std::env::temp_dir()));
+        let order_by_executor = Box::new(OrderByExecutor::new(
+            Box::new(mock_executor),
+            vec![],
+            vec![],
+            vec![],
+            BinaryHeap::new(),
+            Arc::new(order_pairs),
+            vec![],
+            false,
+            false,
+            "OrderByExecutor".to_string(),
+            4,
+            memory_context.clone(),
+        ));
+
+        let mut actual = vec![];
+        let mut stream = order_by_executor.execute();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            assert!(chunk.cardinality() <= 4);
+            actual.extend(chunk.column_at(0).array().as_int32().iter().flatten());
+        }
+        assert_eq!(actual, expected);
+        assert_eq!(memory_context.used(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_order_by_exceeds_memory_limit() {
+        let schema = Schema {
+            fields: vec![Field::unnamed(DataType::Int32)],
+        };
+        let mut mock_executor = MockExecutor::new(schema);
+        mock_executor.add(DataChunk::from_pretty(
+            "i
+             1
+             2",
+        ));
+        let order_by_executor = Box::new(OrderByExecutor::new(
+            Box::new(mock_executor),
+            vec![],
+            vec![],
+            vec![],
+            BinaryHeap::new(),
+            Arc::new(vec![OrderPair {
+                column_idx: 0,
+                order_type: OrderType::Ascending,
+            }]),
+            vec![],
+            false,
+            false,
+            "OrderByExecutor".to_string(),
+            DEFAULT_CHUNK_BUFFER_SIZE,
+            Arc::new(MemoryContext::new(Some(1), std::env::temp_dir())),
+        ));
+        let mut stream = order_by_executor.execute();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("MemoryLimitExceeded"));
+    }
+
     // TODO: enable benches
 
     // fn benchmark_1e4(b: &mut Bencher, enable_encoding: bool) {
@@ -0,0 +1,568 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use risingwave_common::array::column::Column;
+use risingwave_common::array::{ArrayBuilderImpl, DataChunk, Row};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_expr::vector_op::agg::AggStateFactory;
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+use risingwave_pb::expr::window_frame::{Bound as ProstBound, BoundType, Type as FrameType};
+use risingwave_pb::expr::window_function::{GeneralType, Type as WindowFunctionType};
+use risingwave_pb::expr::{AggCall, WindowFrame as ProstWindowFrame, WindowFunction};
+
+use crate::executor::{
+    BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
+};
+use crate::task::BatchTaskContext;
+
+/// One side of a window frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+impl FrameBound {
+    fn from_prost(bound: &ProstBound) -> Result<Self> {
+        let offset = bound.offset as usize;
+        Ok(match bound.get_type()? {
+            BoundType::UnboundedPreceding => FrameBound::UnboundedPreceding,
+            BoundType::Preceding => FrameBound::Preceding(offset),
+            BoundType::CurrentRow => FrameBound::CurrentRow,
+            BoundType::Following => FrameBound::Following(offset),
+            BoundType::UnboundedFollowing => FrameBound::UnboundedFollowing,
+            BoundType::Unspecified => {
+                return Err(
+                    ErrorCode::InternalError("unspecified window frame bound".into()).into(),
+                )
+            }
+        })
+    }
+}
+
+/// The frame of a window function. `RANGE` frames only support `UNBOUNDED` and `CURRENT ROW`
+/// bounds, where `CURRENT ROW` means the peer group of the current row.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    is_rows: bool,
+    start: FrameBound,
+    end: FrameBound,
+}
+
+impl Frame {
+    fn from_prost(frame: &ProstWindowFrame) -> Result<Self> {
+        let is_rows = match frame.get_type()? {
+            FrameType::Rows => true,
+            FrameType::Range => false,
+            FrameType::Unspecified => {
+                return Err(ErrorCode::InternalError("unspecified window frame".into()).into())
+            }
+        };
+        let start = FrameBound::from_prost(frame.get_start()?)?;
+        let end = FrameBound::from_prost(frame.get_end()?)?;
+        let has_offset =
+            |bound| matches!(bound, FrameBound::Preceding(_) | FrameBound::Following(_));
+        if !is_rows && (has_offset(start) || has_offset(end)) {
+            return Err(
+                ErrorCode::NotImplemented("RANGE frame with offset".into(), None.into()).into(),
+            );
+        }
+        Ok(Self {
+            is_rows,
+            start,
+            end,
+        })
+    }
+
+    /// Returns the range `[start, end)` of rows in the frame of the `row_idx`-th row of a
+    /// partition with `len` rows. The peer group of the row is `[peer_start, peer_end)`.
+    fn bounds(
+        &self,
+        row_idx: usize,
+        len: usize,
+        peer_start: usize,
+        peer_end: usize,
+    ) -> (usize, usize) {
+        let start = match self.start {
+            FrameBound::UnboundedPreceding => 0,
+            FrameBound::Preceding(n) => row_idx.saturating_sub(n),
+            FrameBound::CurrentRow if self.is_rows => row_idx,
+            FrameBound::CurrentRow => peer_start,
+            FrameBound::Following(n) => (row_idx + n).min(len),
+            FrameBound::UnboundedFollowing => len,
+        };
+        let end = match self.end {
+            FrameBound::UnboundedPreceding => 0,
+            FrameBound::Preceding(n) => (row_idx + 1).saturating_sub(n),
+            FrameBound::CurrentRow if self.is_rows => row_idx + 1,
+            FrameBound::CurrentRow => peer_end,
+            FrameBound::Following(n) => (row_idx + n + 1).min(len),
+            FrameBound::UnboundedFollowing => len,
+        };
+        (start, end.max(start))
+    }
+}
+
+enum WindowFunctionKind {
+    RowNumber,
+    Rank,
+    DenseRank,
+    /// `lag` and `lead` are planned with a single-row frame, so they share the evaluation of
+    /// `first_value`.
+    FirstValue,
+    LastValue,
+    Aggregate(AggStateFactory),
+}
+
+struct WindowFunctionCall {
+    kind: WindowFunctionKind,
+    /// The column of the first argument, if any.
+    arg: Option<usize>,
+    return_type: DataType,
+    frame: Frame,
+}
+
+impl WindowFunctionCall {
+    fn from_prost(prost: &WindowFunction) -> Result<Self> {
+        let return_type = DataType::from(prost.get_return_type()?);
+        let kind = match prost.get_type()? {
+            WindowFunctionType::General(general) => {
+                match GeneralType::from_i32(*general).unwrap_or(GeneralType::Unspecified) {
+                    GeneralType::RowNumber => WindowFunctionKind::RowNumber,
+                    GeneralType::Rank => WindowFunctionKind::Rank,
+                    GeneralType::DenseRank => WindowFunctionKind::DenseRank,
+                    GeneralType::Lag | GeneralType::Lead | GeneralType::FirstValue => {
+                        WindowFunctionKind::FirstValue
+                    }
+                    GeneralType::LastValue => WindowFunctionKind::LastValue,
+                    GeneralType::Unspecified => {
+                        return Err(
+                            ErrorCode::InternalError("unspecified window function".into()).into(),
+                        )
+                    }
+                }
+            }
+            WindowFunctionType::Aggregate(agg_type) => {
+                WindowFunctionKind::Aggregate(AggStateFactory::new(&AggCall {
+                    r#type: *agg_type,
+                    args: prost.get_args().to_vec(),
+                    return_type: Some(prost.get_return_type()?.clone()),
+                    distinct: false,
+                    order_by_fields: vec![],
+                    filter: None,
+                })?)
+            }
+        };
+        let arg = prost
+            .get_args()
+            .first()
+            .map(|arg| -> Result<usize> { Ok(arg.get_input()?.get_column_idx() as usize) })
+            .transpose()?;
+        Ok(Self {
+            kind,
+            arg,
+            return_type,
+            frame: Frame::from_prost(prost.get_frame()?)?,
+        })
+    }
+
+    /// Evaluate the window function over a whole partition.
+    fn eval(&self, partition: &DataChunk, peer_groups: &[(usize, usize)]) -> Result<Column> {
+        let len = partition.cardinality();
+        let mut builder = self.return_type.create_array_builder(len);
+        match &self.kind {
+            WindowFunctionKind::RowNumber => {
+                for row_idx in 0..len {
+                    builder.append_datum(&Some(ScalarImpl::Int64(row_idx as i64 + 1)))?;
+                }
+            }
+            WindowFunctionKind::Rank => {
+                for &(peer_start, _) in peer_groups {
+                    builder.append_datum(&Some(ScalarImpl::Int64(peer_start as i64 + 1)))?;
+                }
+            }
+            WindowFunctionKind::DenseRank => {
+                let mut rank = 0i64;
+                for (row_idx, &(peer_start, _)) in peer_groups.iter().enumerate() {
+                    if peer_start == row_idx {
+                        rank += 1;
+                    }
+                    builder.append_datum(&Some(ScalarImpl::Int64(rank)))?;
+                }
+            }
+            WindowFunctionKind::FirstValue | WindowFunctionKind::LastValue => {
+                let array = partition.column_at(self.arg.unwrap()).array_ref();
+                for (row_idx, &(peer_start, peer_end)) in peer_groups.iter().enumerate() {
+                    let (start, end) = self.frame.bounds(row_idx, len, peer_start, peer_end);
+                    let datum = if start == end {
+                        None
+                    } else if matches!(self.kind, WindowFunctionKind::FirstValue) {
+                        array.datum_at(start)
+                    } else {
+                        array.datum_at(end - 1)
+                    };
+                    builder.append_datum(&datum)?;
+                }
+            }
+            WindowFunctionKind::Aggregate(factory) => {
+                self.eval_aggregate(factory, partition, peer_groups, &mut builder)?;
+            }
+        }
+        Ok(Column::new(Arc::new(builder.finish()?)))
+    }
+
+    fn eval_aggregate(
+        &self,
+        factory: &AggStateFactory,
+        partition: &DataChunk,
+        peer_groups: &[(usize, usize)],
+        builder: &mut ArrayBuilderImpl,
+    ) -> Result<()> {
+        let len = partition.cardinality();
+        if self.frame.start == FrameBound::UnboundedPreceding {
+            // The frame only grows, so the state can be updated incrementally.
+            let mut state = factory.create_agg_state()?;
+            let mut updated_end = 0;
+            for (row_idx, &(peer_start, peer_end)) in peer_groups.iter().enumerate() {
+                let (_, end) = self.frame.bounds(row_idx, len, peer_start, peer_end);
+                if end > updated_end {
+                    state.update_multi(partition, updated_end, end)?;
+                    updated_end = end;
+                }
+                state.output(builder)?;
+            }
+        } else {
+            let mut state = factory.create_agg_state()?;
+            for (row_idx, &(peer_start, peer_end)) in peer_groups.iter().enumerate() {
+                let (start, end) = self.frame.bounds(row_idx, len, peer_start, peer_end);
+                if start < end {
+                    state.update_multi(partition, start, end)?;
+                }
+                state.output_and_reset(builder)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `OverAggExecutor` evaluates window functions. It assumes that the input has already been sorted
+/// by the partition columns and then the order columns, so that each partition can be evaluated
+/// once all of its rows are buffered.
+///
+/// The output schema is `[input columns, window function results]`.
+pub struct OverAggExecutor {
+    child: BoxedExecutor,
+    partition_by: Vec<usize>,
+    order_by: Vec<usize>,
+    calls: Vec<WindowFunctionCall>,
+    schema: Schema,
+    identity: String,
+}
+
+#[async_trait::async_trait]
+impl BoxedExecutorBuilder for OverAggExecutor {
+    async fn new_boxed_executor<C: BatchTaskContext>(
+        source: &ExecutorBuilder<C>,
+        mut inputs: Vec<BoxedExecutor>,
+    ) -> Result<BoxedExecutor> {
+        ensure!(
+            inputs.len() == 1,
+            "OverAgg executor should have only 1 child!"
+        );
+        let over_agg_node = try_match_expand!(
+            source.plan_node().get_node_body().unwrap(),
+            NodeBody::OverAgg
+        )?;
+
+        let partition_by = over_agg_node
+            .get_partition_by()
+            .iter()
+            .map(|&idx| idx as usize)
+            .collect();
+        let order_by = over_agg_node
+            .get_order_by()
+            .iter()
+            .map(|order| order.index as usize)
+            .collect();
+        let calls = over_agg_node
+            .get_window_functions()
+            .iter()
+            .map(WindowFunctionCall::from_prost)
+            .try_collect()?;
+
+        Ok(Box::new(Self::new(
+            inputs.remove(0),
+            partition_by,
+            order_by,
+            calls,
+            source.plan_node().get_identity().clone(),
+        )))
+    }
+}
+
+impl OverAggExecutor {
+    fn new(
+        child: BoxedExecutor,
+        partition_by: Vec<usize>,
+        order_by: Vec<usize>,
+        calls: Vec<WindowFunctionCall>,
+        identity: String,
+    ) -> Self {
+        let fields = child
+            .schema()
+            .fields()
+            .iter()
+            .cloned()
+            .chain(
+                calls
+                    .iter()
+                    .map(|call| Field::unnamed(call.return_type.clone())),
+            )
+            .collect();
+        Self {
+            child,
+            partition_by,
+            order_by,
+            calls,
+            schema: Schema { fields },
+            identity,
+        }
+    }
+
+    #[try_stream(boxed, ok = DataChunk, error = RwError)]
+    async fn do_execute(self: Box<Self>) {
+        let Self {
+            child,
+            partition_by,
+            order_by,
+            calls,
+            ..
+        } = *self;
+        let input_types = child.schema().data_types();
+        let mut partition_rows: Vec<Row> = vec![];
+        let mut partition_key: Option<Row> = None;
+
+        #[for_await]
+        for chunk in child.execute() {
+            let chunk = chunk?.compact()?;
+            for row in chunk.rows() {
+                let key = row.row_by_indices(&partition_by);
+                if partition_key.as_ref().map_or(false, |k| k != &key) {
+                    yield Self::eval_partition(&partition_rows, &input_types, &order_by, &calls)?;
+                    partition_rows.clear();
+                }
+                partition_key = Some(key);
+                partition_rows.push(row.to_owned_row());
+            }
+        }
+        if !partition_rows.is_empty() {
+            yield Self::eval_partition(&partition_rows, &input_types, &order_by, &calls)?;
+        }
+    }
+
+    fn eval_partition(
+        rows: &[Row],
+        input_types: &[DataType],
+        order_by: &[usize],
+        calls: &[WindowFunctionCall],
+    ) -> Result<DataChunk> {
+        let partition = DataChunk::from_rows(rows, input_types)?;
+
+        // The peer group `[start, end)` of each row, i.e. the rows with the same order key.
+        let mut peer_groups = Vec::with_capacity(rows.len());
+        let mut peer_start = 0;
+        for row_idx in 1..=rows.len() {
+            if row_idx == rows.len()
+                || rows[row_idx].by_indices(order_by) != rows[peer_start].by_indices(order_by)
+            {
+                peer_groups.extend((peer_start..row_idx).map(|_| (peer_start, row_idx)));
+                peer_start = row_idx;
+            }
+        }
+
+        let window_columns: Vec<_> = calls
+            .iter()
+            .map(|call| call.eval(&partition, &peer_groups))
+            .try_collect()?;
+        let (mut columns, _) = partition.into_parts();
+        columns.extend(window_columns);
+        Ok(DataChunk::new(columns, rows.len()))
+    }
+}
+
+impl Executor for OverAggExecutor {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    fn execute(self: Box<Self>) -> BoxedDataChunkStream {
+        self.do_execute()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use risingwave_common::array::DataChunkTestExt;
+    use risingwave_pb::expr::agg_call::{Arg, Type as AggType};
+    use risingwave_pb::expr::InputRefExpr;
+
+    use super::*;
+    use crate::executor::test_utils::MockExecutor;
+
+    fn frame(is_rows: bool, start: FrameBound, end: FrameBound) -> Frame {
+        Frame {
+            is_rows,
+            start,
+            end,
+        }
+    }
+
+    fn default_frame() -> Frame {
+        frame(
+            false,
+            FrameBound::UnboundedPreceding,
+            FrameBound::CurrentRow,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_over_agg_executor() {
+        let schema = Schema {
+            fields: vec![
+                Field::unnamed(DataType::Int32),
+                Field::unnamed(DataType::Int32),
+            ],
+        };
+        let mut child = MockExecutor::new(schema);
+        child.add(DataChunk::from_pretty(
+            "i i
+             1 1
+             1 2
+             1 2",
+        ));
+        child.add(DataChunk::from_pretty(
+            "i i
+             1 4
+             2 1
+             2 3",
+        ));
+
+        let sum = AggStateFactory::new(&AggCall {
+            r#type: AggType::Sum as i32,
+            args: vec![Arg {
+                input: Some(InputRefExpr { column_idx: 1 }),
+                r#type: Some(DataType::Int32.to_protobuf()),
+            }],
+            return_type: Some(DataType::Int64.to_protobuf()),
+            distinct: false,
+            order_by_fields: vec![],
+            filter: None,
+        })
+        .unwrap();
+        let calls = vec![
+            WindowFunctionCall {
+                kind: WindowFunctionKind::RowNumber,
+                arg: None,
+                return_type: DataType::Int64,
+                frame: default_frame(),
+            },
+            WindowFunctionCall {
+                kind: WindowFunctionKind::Rank,
+                arg: None,
+                return_type: DataType::Int64,
+                frame: default_frame(),
+            },
+            WindowFunctionCall {
+                kind: WindowFunctionKind::DenseRank,
+                arg: None,
+                return_type: DataType::Int64,
+                frame: default_frame(),
+            },
+            // lag(v2, 1)
+            WindowFunctionCall {
+                kind: WindowFunctionKind::FirstValue,
+                arg: Some(1),
+                return_type: DataType::Int32,
+                frame: frame(true, FrameBound::Preceding(1), FrameBound::Preceding(1)),
+            },
+            WindowFunctionCall {
+                kind: WindowFunctionKind::Aggregate(sum),
+                arg: Some(1),
+                return_type: DataType::Int64,
+                frame: default_frame(),
+            },
+        ];
+
+        let executor = Box::new(OverAggExecutor::new(
+            Box::new(child),
+            vec![0],
+            vec![1],
+            calls,
+            "OverAggExecutor".to_string(),
+        ));
+        let mut stream = executor.execute();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            DataChunk::from_pretty(
+                "i i I I I i I
+                 1 1 1 1 1 . 1
+                 1 2 2 2 2 1 5
+                 1 2 3 2 2 2 5
+                 1 4 4 4 3 2 9"
+            )
+        );
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            DataChunk::from_pretty(
+                "i i I I I i I
+                 2 1 1 1 1 . 1
+                 2 3 2 2 2 1 4"
+            )
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn test_frame_bounds() {
+        let rows = frame(true, FrameBound::Preceding(1), FrameBound::Following(1));
+        assert_eq!(rows.bounds(0, 5, 0, 1), (0, 2));
+        assert_eq!(rows.bounds(2, 5, 2, 3), (1, 4));
+        assert_eq!(rows.bounds(4, 5, 4, 5), (3, 5));
+
+        let lead = frame(true, FrameBound::Following(2), FrameBound::Following(2));
+        assert_eq!(lead.bounds(2, 5, 2, 3), (4, 5));
+        assert_eq!(lead.bounds(3, 5, 3, 4), (5, 5));
+
+        let range = frame(
+            false,
+            FrameBound::CurrentRow,
+            FrameBound::UnboundedFollowing,
+        );
+        assert_eq!(range.bounds(2, 5, 1, 3), (1, 5));
+    }
+}
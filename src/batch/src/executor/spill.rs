@@ -0,0 +1,324 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The spill files of the operators exceeding the memory budget of the query.
+//!
+//! The file I/O is blocking, so it runs on the blocking threads of tokio rather than the async
+//! workers, which would otherwise be blocked exactly when the memory is tight.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use parking_lot::Mutex;
+use prost::Message;
+use risingwave_common::array::DataChunk;
+use risingwave_common::catalog::Schema;
+use risingwave_common::error::RwError;
+use risingwave_common::hash::HashCode;
+use risingwave_common::util::hash_util::CRC32FastBuilder;
+use risingwave_pb::data::DataChunk as ProstDataChunk;
+
+use crate::error::{BatchError, Result};
+use crate::executor::{BoxedDataChunkStream, BoxedExecutor, Executor};
+
+/// Number of partitions that a hash based operator spills its rows into.
+pub const SPILL_PARTITION_COUNT: usize = 16;
+
+/// Hash based operators spill a partition again when it still doesn't fit into the memory, using
+/// the next bits of the hash. Beyond this depth the rows are likely to share a few keys, so the
+/// operator gives up with the memory limit exceeded error.
+pub const MAX_SPILL_DEPTH: usize = 8;
+
+/// Runs the blocking file I/O on the blocking threads.
+async fn asyncify<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> std::io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(res) => Ok(res?),
+        Err(e) => Err(BatchError::Internal(anyhow!("spill task failed: {}", e))),
+    }
+}
+
+/// Encodes a chunk into the bytes written to a spill file, or `None` if it has no visible rows.
+fn encode_chunk(chunk: DataChunk) -> Result<Option<Vec<u8>>> {
+    if chunk.cardinality() == 0 {
+        return Ok(None);
+    }
+    Ok(Some(chunk.compact()?.to_protobuf().encode_to_vec()))
+}
+
+fn write_encoded(writer: &mut BufWriter<File>, bytes: &[u8]) -> std::io::Result<()> {
+    writer.write_u32::<LittleEndian>(bytes.len() as u32)?;
+    writer.write_all(bytes)
+}
+
+/// A temporary file of data chunks, which is deleted once dropped.
+pub struct SpillFile {
+    writer: Arc<Mutex<BufWriter<File>>>,
+}
+
+impl SpillFile {
+    pub async fn new(dir: &Path) -> Result<Self> {
+        let dir = dir.to_path_buf();
+        let file = asyncify(move || tempfile::tempfile_in(dir)).await?;
+        Ok(Self::from_file(file))
+    }
+
+    fn from_file(file: File) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+        }
+    }
+
+    pub async fn write_chunk(&mut self, chunk: DataChunk) -> Result<()> {
+        let bytes = match encode_chunk(chunk)? {
+            Some(bytes) => bytes,
+            None => return Ok(()),
+        };
+        let writer = self.writer.clone();
+        asyncify(move || write_encoded(&mut writer.lock(), &bytes)).await
+    }
+
+    pub async fn into_reader(self) -> Result<SpillReader> {
+        let writer = self.writer;
+        let file = asyncify(move || {
+            let writer = Arc::try_unwrap(writer)
+                .map_err(|_| std::io::Error::new(ErrorKind::Other, "spill file is being written"))?
+                .into_inner();
+            let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+            file.seek(SeekFrom::Start(0))?;
+            Ok(file)
+        })
+        .await?;
+        Ok(SpillReader {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+        })
+    }
+}
+
+/// Reads back the chunks written to a [`SpillFile`].
+pub struct SpillReader {
+    reader: Arc<Mutex<BufReader<File>>>,
+}
+
+impl SpillReader {
+    pub async fn read_chunk(&mut self) -> Result<Option<DataChunk>> {
+        let reader = self.reader.clone();
+        let buf = asyncify(move || {
+            let mut reader = reader.lock();
+            let len = match reader.read_u32::<LittleEndian>() {
+                Ok(len) => len as usize,
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            let mut buf = vec![0; len];
+            reader.read_exact(&mut buf)?;
+            Ok(Some(buf))
+        })
+        .await?;
+        let buf = match buf {
+            Some(buf) => buf,
+            None => return Ok(None),
+        };
+        let chunk = ProstDataChunk::decode(buf.as_slice())
+            .map_err(|e| BatchError::Internal(anyhow!("failed to decode spilled chunk: {}", e)))?;
+        Ok(Some(DataChunk::from_protobuf(&chunk)?))
+    }
+
+    /// Makes an executor of the chunks, to feed a spilled partition to a new operator.
+    pub fn into_executor(self, schema: Schema, identity: String) -> BoxedExecutor {
+        Box::new(SpillReaderExecutor {
+            reader: self,
+            schema,
+            identity,
+        })
+    }
+}
+
+struct SpillReaderExecutor {
+    reader: SpillReader,
+    schema: Schema,
+    identity: String,
+}
+
+impl Executor for SpillReaderExecutor {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    fn execute(self: Box<Self>) -> BoxedDataChunkStream {
+        self.do_execute()
+    }
+}
+
+impl SpillReaderExecutor {
+    #[try_stream(boxed, ok = DataChunk, error = RwError)]
+    async fn do_execute(mut self: Box<Self>) {
+        while let Some(chunk) = self.reader.read_chunk().await? {
+            yield chunk;
+        }
+    }
+}
+
+/// Spills the rows into [`SPILL_PARTITION_COUNT`] partitions by the hash of the key columns, using
+/// the bits of the hash for the `depth`.
+pub struct SpillPartitions {
+    files: Vec<SpillFile>,
+    depth: usize,
+}
+
+impl SpillPartitions {
+    pub async fn new(dir: &Path, depth: usize) -> Result<Self> {
+        let dir: PathBuf = dir.to_path_buf();
+        let files = asyncify(move || {
+            (0..SPILL_PARTITION_COUNT)
+                .map(|_| tempfile::tempfile_in(&dir))
+                .collect::<std::io::Result<Vec<_>>>()
+        })
+        .await?;
+        Ok(Self {
+            files: files.into_iter().map(SpillFile::from_file).collect(),
+            depth,
+        })
+    }
+
+    pub fn partition_of(&self, hash_code: &HashCode) -> usize {
+        (hash_code.hash_code() >> (self.depth * 4)) as usize % SPILL_PARTITION_COUNT
+    }
+
+    /// Spills the visible rows of the chunk, with the hash of the keys of all its rows computed by
+    /// [`DataChunk::get_hash_values`].
+    pub async fn write_chunk(&mut self, chunk: &DataChunk, hash_codes: &[HashCode]) -> Result<()> {
+        let partitions: Vec<_> = match chunk.visibility() {
+            Some(visibility) => hash_codes
+                .iter()
+                .zip_eq(visibility.iter())
+                .map(|(h, visible)| visible.then(|| self.partition_of(h)))
+                .collect(),
+            None => hash_codes
+                .iter()
+                .map(|h| Some(self.partition_of(h)))
+                .collect(),
+        };
+        let mut writes = Vec::with_capacity(SPILL_PARTITION_COUNT);
+        for (partition, file) in self.files.iter().enumerate() {
+            let visibility = partitions.iter().map(|p| *p == Some(partition)).collect();
+            if let Some(bytes) = encode_chunk(chunk.with_visibility(visibility))? {
+                writes.push((file.writer.clone(), bytes));
+            }
+        }
+        // The partitions are written in one go, rather than a blocking task for each of them.
+        asyncify(move || {
+            writes
+                .into_iter()
+                .try_for_each(|(writer, bytes)| write_encoded(&mut writer.lock(), &bytes))
+        })
+        .await
+    }
+
+    /// Spills the rows of the chunk by the hash of the key columns.
+    pub async fn write_chunk_by_keys(
+        &mut self,
+        chunk: DataChunk,
+        key_columns: &[usize],
+    ) -> Result<()> {
+        let chunk = chunk.compact()?;
+        let hash_codes = chunk.get_hash_values(key_columns, CRC32FastBuilder)?;
+        self.write_chunk(&chunk, &hash_codes).await
+    }
+
+    pub async fn into_readers(self) -> Result<Vec<SpillReader>> {
+        let mut readers = Vec::with_capacity(self.files.len());
+        for file in self.files {
+            readers.push(file.into_reader().await?);
+        }
+        Ok(readers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::Array;
+    use risingwave_common::test_prelude::DataChunkTestExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spill_file() {
+        let chunk = DataChunk::from_pretty(
+            "i  T
+             1  a
+             2  .
+             3  ccc D",
+        );
+        let mut file = SpillFile::new(&std::env::temp_dir()).await.unwrap();
+        file.write_chunk(chunk.clone()).await.unwrap();
+        file.write_chunk(DataChunk::new_dummy(0)).await.unwrap();
+        file.write_chunk(chunk).await.unwrap();
+
+        let expected = DataChunk::from_pretty(
+            "i  T
+             1  a
+             2  .",
+        );
+        let mut reader = file.into_reader().await.unwrap();
+        assert_eq!(reader.read_chunk().await.unwrap().unwrap(), expected);
+        assert_eq!(reader.read_chunk().await.unwrap().unwrap(), expected);
+        assert!(reader.read_chunk().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_spill_partitions() {
+        let chunk = DataChunk::from_pretty(
+            "i i
+             1 1
+             2 2
+             1 3
+             3 4 D
+             1 5",
+        );
+        let mut partitions = SpillPartitions::new(&std::env::temp_dir(), 1)
+            .await
+            .unwrap();
+        partitions.write_chunk_by_keys(chunk, &[0]).await.unwrap();
+
+        let mut rows = vec![];
+        for mut reader in partitions.into_readers().await.unwrap() {
+            let mut partition = vec![];
+            while let Some(chunk) = reader.read_chunk().await.unwrap() {
+                let array = chunk.column_at(0).array();
+                partition.extend(array.as_int32().iter().flatten());
+            }
+            // The rows of a key are in the same partition.
+            if partition.contains(&1) {
+                assert_eq!(partition.iter().filter(|k| **k == 1).count(), 3);
+            }
+            rows.extend(partition);
+        }
+        rows.sort_unstable();
+        assert_eq!(rows, vec![1, 1, 1, 2]);
+    }
+}
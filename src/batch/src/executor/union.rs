@@ -0,0 +1,154 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures_async_stream::try_stream;
+use risingwave_common::array::DataChunk;
+use risingwave_common::catalog::Schema;
+use risingwave_common::error::{Result, RwError};
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+
+use crate::executor::{
+    BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
+};
+use crate::task::BatchTaskContext;
+
+/// Union executor. It outputs all the rows of its children one by one, without removing
+/// duplicates.
+pub struct UnionExecutor {
+    inputs: Vec<BoxedExecutor>,
+    schema: Schema,
+    identity: String,
+}
+
+#[async_trait::async_trait]
+impl BoxedExecutorBuilder for UnionExecutor {
+    async fn new_boxed_executor<C: BatchTaskContext>(
+        source: &ExecutorBuilder<C>,
+        inputs: Vec<BoxedExecutor>,
+    ) -> Result<BoxedExecutor> {
+        ensure!(
+            !inputs.is_empty(),
+            "UnionExecutor should have at least 1 child!"
+        );
+        let _union_node =
+            try_match_expand!(source.plan_node().get_node_body().unwrap(), NodeBody::Union)?;
+
+        Ok(Box::new(Self::new(
+            inputs,
+            source.plan_node().get_identity().clone(),
+        )))
+    }
+}
+
+impl UnionExecutor {
+    pub fn new(inputs: Vec<BoxedExecutor>, identity: String) -> Self {
+        Self {
+            schema: inputs[0].schema().clone(),
+            inputs,
+            identity,
+        }
+    }
+
+    #[try_stream(boxed, ok = DataChunk, error = RwError)]
+    async fn do_execute(self: Box<Self>) {
+        for input in self.inputs {
+            #[for_await]
+            for data_chunk in input.execute() {
+                yield data_chunk?;
+            }
+        }
+    }
+}
+
+impl Executor for UnionExecutor {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    fn execute(self: Box<Self>) -> BoxedDataChunkStream {
+        self.do_execute()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use risingwave_common::array::{DataChunk, DataChunkTestExt};
+    use risingwave_common::catalog::{Field, Schema};
+    use risingwave_common::types::DataType;
+
+    use super::*;
+    use crate::executor::test_utils::MockExecutor;
+
+    #[tokio::test]
+    async fn test_union_executor() {
+        let schema = Schema {
+            fields: vec![
+                Field::unnamed(DataType::Int32),
+                Field::unnamed(DataType::Float32),
+            ],
+        };
+        let mut left = MockExecutor::new(schema.clone());
+        left.add(DataChunk::from_pretty(
+            "i f
+             1 6.1
+             2 8.4",
+        ));
+        left.add(DataChunk::from_pretty(
+            "i f
+             3 3.9",
+        ));
+        let mut right = MockExecutor::new(schema);
+        right.add(DataChunk::from_pretty(
+            "i f
+             1 6.1
+             4 1.2",
+        ));
+
+        let union_executor = Box::new(UnionExecutor::new(
+            vec![Box::new(left), Box::new(right)],
+            "UnionExecutor".to_string(),
+        ));
+        let fields = &union_executor.schema().fields;
+        assert_eq!(fields[0].data_type, DataType::Int32);
+        assert_eq!(fields[1].data_type, DataType::Float32);
+
+        let mut stream = union_executor.execute();
+        let expected = [
+            DataChunk::from_pretty(
+                "i f
+                 1 6.1
+                 2 8.4",
+            ),
+            DataChunk::from_pretty(
+                "i f
+                 3 3.9",
+            ),
+            DataChunk::from_pretty(
+                "i f
+                 1 6.1
+                 4 1.2",
+            ),
+        ];
+        for expected_chunk in expected {
+            let chunk = stream.next().await.unwrap().unwrap();
+            assert_eq!(chunk, expected_chunk);
+        }
+        assert!(stream.next().await.is_none());
+    }
+}
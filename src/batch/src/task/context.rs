@@ -22,7 +22,7 @@ use risingwave_source::SourceManagerRef;
 use risingwave_storage::StateStoreImpl;
 
 use crate::executor::BatchMetrics;
-use crate::task::{BatchEnvironment, TaskOutput, TaskOutputId};
+use crate::task::{BatchEnvironment, MemoryContextRef, TaskId, TaskOutput, TaskOutputId};
 
 /// Context for batch task execution.
 ///
@@ -62,6 +62,9 @@ pub trait BatchTaskContext: Clone + Send + Sync + 'static {
     }
 
     fn stats(&self) -> Arc<BatchMetrics>;
+
+    /// Get the memory budget of the query that the task belongs to.
+    fn memory_context(&self, task_id: &TaskId) -> MemoryContextRef;
 }
 
 /// Batch task context on compute node.
@@ -96,6 +99,12 @@ impl BatchTaskContext for ComputeNodeContext {
     fn stats(&self) -> Arc<BatchMetrics> {
         self.env.stats()
     }
+
+    fn memory_context(&self, task_id: &TaskId) -> MemoryContextRef {
+        self.env
+            .task_manager()
+            .memory_context(&task_id.query_id, self.env.config())
+    }
 }
 
 impl ComputeNodeContext {
@@ -0,0 +1,208 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use risingwave_common::config::BatchConfig;
+
+use crate::error::{BatchError, Result};
+
+pub type MemoryContextRef = Arc<MemoryContext>;
+
+/// The memory budget of a query on a worker node, shared by the operators of all its tasks there.
+#[derive(Debug)]
+pub struct MemoryContext {
+    /// In bytes, `None` for unlimited.
+    limit: Option<usize>,
+    used: AtomicUsize,
+    spill_dir: PathBuf,
+    /// The peak size of a reservation of the operators, by their identities.
+    peaks: Mutex<HashMap<String, usize>>,
+}
+
+impl MemoryContext {
+    pub fn new(limit: Option<usize>, spill_dir: PathBuf) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+            spill_dir,
+            peaks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &BatchConfig) -> Self {
+        let limit = match config.query_memory_limit_mb {
+            0 => None,
+            limit_mb => Some(limit_mb << 20),
+        };
+        let spill_dir = if config.spill_dir.is_empty() {
+            std::env::temp_dir()
+        } else {
+            PathBuf::from(&config.spill_dir)
+        };
+        Self::new(limit, spill_dir)
+    }
+
+    pub fn unlimited() -> MemoryContextRef {
+        Arc::new(Self::new(None, std::env::temp_dir()))
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    pub fn spill_dir(&self) -> &Path {
+        &self.spill_dir
+    }
+
+    /// The peak memory reserved at a time by the operator of the identity, for `EXPLAIN ANALYZE`.
+    pub fn peak_of(&self, identity: &str) -> usize {
+        self.peaks.lock().get(identity).copied().unwrap_or(0)
+    }
+
+    fn record_peak(&self, identity: &str, size: usize) {
+        let mut peaks = self.peaks.lock();
+        match peaks.get_mut(identity) {
+            Some(peak) => *peak = (*peak).max(size),
+            None => {
+                peaks.insert(identity.to_string(), size);
+            }
+        }
+    }
+
+    fn try_reserve(&self, bytes: usize) -> bool {
+        match self.limit {
+            None => {
+                self.used.fetch_add(bytes, Ordering::Relaxed);
+                true
+            }
+            Some(limit) => self
+                .used
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                    if used + bytes <= limit {
+                        Some(used + bytes)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok(),
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+/// The memory held by an operator under the budget of its query, which is given back on drop.
+pub struct MemoryReservation {
+    context: MemoryContextRef,
+    identity: String,
+    size: usize,
+}
+
+impl MemoryReservation {
+    pub fn new(context: MemoryContextRef, identity: impl Into<String>) -> Self {
+        Self {
+            context,
+            identity: identity.into(),
+            size: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn context(&self) -> &MemoryContextRef {
+        &self.context
+    }
+
+    /// Reserves `bytes` more. Returns false if it would exceed the budget of the query, in which
+    /// case the operator should spill what it holds and free the reservation.
+    pub fn try_grow(&mut self, bytes: usize) -> bool {
+        if self.context.try_reserve(bytes) {
+            self.size += bytes;
+            self.context.record_peak(&self.identity, self.size);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reserves `bytes` more, for the operators that can't spill.
+    pub fn grow(&mut self, bytes: usize) -> Result<()> {
+        if self.try_grow(bytes) {
+            Ok(())
+        } else {
+            Err(self.limit_exceeded())
+        }
+    }
+
+    /// The error when the operator needs more memory than the budget of the query allows.
+    pub fn limit_exceeded(&self) -> BatchError {
+        BatchError::MemoryLimitExceeded(
+            self.identity.clone(),
+            self.context.limit.unwrap_or(usize::MAX),
+        )
+    }
+
+    pub fn free(&mut self) {
+        self.context.release(self.size);
+        self.size = 0;
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_reservation() {
+        let context = Arc::new(MemoryContext::new(Some(100), std::env::temp_dir()));
+        let mut a = MemoryReservation::new(context.clone(), "a");
+        let mut b = MemoryReservation::new(context.clone(), "b");
+        assert!(a.try_grow(60));
+        assert!(!b.try_grow(50));
+        assert!(b.try_grow(40));
+        assert_eq!(context.used(), 100);
+        assert!(matches!(
+            a.grow(1),
+            Err(BatchError::MemoryLimitExceeded(identity, 100)) if identity == "a"
+        ));
+
+        a.free();
+        assert_eq!(context.used(), 40);
+        assert!(b.try_grow(50));
+        drop(b);
+        assert_eq!(context.used(), 0);
+        assert_eq!(context.peak_of("a"), 60);
+        assert_eq!(context.peak_of("b"), 90);
+        assert_eq!(context.peak_of("c"), 0);
+    }
+}
@@ -31,7 +31,7 @@ use tracing_futures::Instrument;
 
 use crate::error::BatchError::SenderError;
 use crate::error::{BatchError, Result as BatchResult};
-use crate::executor::{BoxedExecutor, ExecutorBuilder};
+use crate::executor::{BoxedExecutor, ExecutorBuilder, TaskExecutorStats, TaskExecutorStatsRef};
 use crate::rpc::service::exchange::ExchangeWriter;
 use crate::rpc::service::task_service::TaskInfoResponseResult;
 use crate::task::channel::{create_output_channel, ChanReceiverImpl, ChanSenderImpl};
@@ -194,6 +194,9 @@ pub struct BatchTaskExecution<C> {
     state_rx: Mutex<Option<tokio::sync::mpsc::Receiver<TaskInfoResponseResult>>>,
 
     epoch: u64,
+
+    /// The runtime statistics of the operators, if requested by the plan.
+    executor_stats: Option<TaskExecutorStatsRef>,
 }
 
 impl<C: BatchTaskContext> BatchTaskExecution<C> {
@@ -203,8 +206,16 @@ impl<C: BatchTaskContext> BatchTaskExecution<C> {
         context: C,
         epoch: u64,
     ) -> Result<Self> {
+        let task_id = TaskId::from(prost_tid);
+        let executor_stats = if plan.collect_executor_stats {
+            Some(Arc::new(TaskExecutorStats::new(
+                context.memory_context(&task_id),
+            )))
+        } else {
+            None
+        };
         Ok(Self {
-            task_id: TaskId::from(prost_tid),
+            task_id,
             plan,
             state: Mutex::new(TaskStatus::Pending),
             receivers: Mutex::new(Vec::new()),
@@ -213,6 +224,7 @@ impl<C: BatchTaskContext> BatchTaskExecution<C> {
             epoch,
             shutdown_tx: Mutex::new(None),
             state_rx: Mutex::new(None),
+            executor_stats,
         })
     }
 
@@ -233,17 +245,17 @@ impl<C: BatchTaskContext> BatchTaskExecution<C> {
             serde_json::to_string_pretty(self.plan.get_root()?).unwrap()
         );
 
+        let mut builder = ExecutorBuilder::new(
+            self.plan.root.as_ref().unwrap(),
+            &self.task_id,
+            self.context.clone(),
+            self.epoch,
+        );
+        if let Some(executor_stats) = &self.executor_stats {
+            builder = builder.with_executor_stats(executor_stats.clone());
+        }
         let exec = DEBUG_CONTEXT
-            .scope(
-                DebugContext::BatchQuery,
-                ExecutorBuilder::new(
-                    self.plan.root.as_ref().unwrap(),
-                    &self.task_id,
-                    self.context.clone(),
-                    self.epoch,
-                )
-                .build(),
-            )
+            .scope(DebugContext::BatchQuery, builder.build())
             .await?;
 
         // Init shutdown channel and data receivers.
@@ -314,12 +326,20 @@ impl<C: BatchTaskContext> BatchTaskExecution<C> {
         state_tx: &mut tokio::sync::mpsc::Sender<TaskInfoResponseResult>,
     ) -> BatchResult<()> {
         self.change_state(task_status);
+        // The statistics are complete once the task finishes.
+        let executor_stats = match &self.executor_stats {
+            Some(executor_stats) if task_status == TaskStatus::Finished => {
+                executor_stats.to_prost()
+            }
+            _ => vec![],
+        };
         // Notify frontend the task status.
         state_tx
             .send(Ok(TaskInfoResponse {
                 task_info: Some(TaskInfo {
                     task_id: Some(TaskId::default().to_prost()),
                     task_status: task_status.into(),
+                    executor_stats,
                 }),
                 // TODO: Fill the real status.
                 ..Default::default()
@@ -14,9 +14,10 @@
 
 use std::collections::{hash_map, HashMap};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 use parking_lot::Mutex;
+use risingwave_common::config::BatchConfig;
 use risingwave_common::error::ErrorCode::{self, TaskNotFound};
 use risingwave_common::error::{Result, RwError};
 use risingwave_pb::batch_plan::{
@@ -28,20 +29,40 @@ use tonic::Status;
 
 use crate::rpc::service::exchange::GrpcExchangeWriter;
 use crate::rpc::service::task_service::TaskInfoResponseResult;
-use crate::task::{BatchTaskExecution, ComputeNodeContext, TaskId, TaskOutput, TaskOutputId};
+use crate::task::{
+    BatchTaskExecution, ComputeNodeContext, MemoryContext, MemoryContextRef, TaskId, TaskOutput,
+    TaskOutputId,
+};
 
 /// `BatchManager` is responsible for managing all batch tasks.
 #[derive(Clone)]
 pub struct BatchManager {
     /// Every task id has a corresponding task execution.
     tasks: Arc<Mutex<HashMap<TaskId, Arc<BatchTaskExecution<ComputeNodeContext>>>>>,
+
+    /// The memory budgets of the queries, shared by their tasks on this node. The budget is gone
+    /// once the executors of the query are all dropped.
+    memory_contexts: Arc<Mutex<HashMap<String, Weak<MemoryContext>>>>,
 }
 
 impl BatchManager {
     pub fn new() -> Self {
         BatchManager {
             tasks: Arc::new(Mutex::new(HashMap::new())),
+            memory_contexts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the memory budget of the query, creating it for the first task of the query.
+    pub fn memory_context(&self, query_id: &str, config: &BatchConfig) -> MemoryContextRef {
+        let mut memory_contexts = self.memory_contexts.lock();
+        if let Some(context) = memory_contexts.get(query_id).and_then(Weak::upgrade) {
+            return context;
         }
+        memory_contexts.retain(|_, context| context.strong_count() > 0);
+        let context = Arc::new(MemoryContext::from_config(config));
+        memory_contexts.insert(query_id.to_string(), Arc::downgrade(&context));
+        context
     }
 
     pub async fn fire_task(
@@ -186,6 +207,9 @@ impl Default for BatchManager {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use risingwave_common::config::BatchConfig;
     use risingwave_common::types::DataType;
     use risingwave_expr::expr::make_i32_literal;
     use risingwave_pb::batch_plan::exchange_info::DistributionMode;
@@ -229,6 +253,24 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_memory_context_per_query() {
+        let manager = BatchManager::new();
+        let config = BatchConfig::default();
+        let a1 = manager.memory_context("a", &config);
+        let a2 = manager.memory_context("a", &config);
+        let b = manager.memory_context("b", &config);
+        assert!(Arc::ptr_eq(&a1, &a2));
+        assert!(!Arc::ptr_eq(&a1, &b));
+        assert_eq!(a1.limit(), Some(config.query_memory_limit_mb << 20));
+
+        drop(a1);
+        drop(a2);
+        let a3 = manager.memory_context("a", &config);
+        assert_eq!(Arc::strong_count(&a3), 1);
+        assert_eq!(manager.memory_contexts.lock().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_task_id_conflict() {
         let manager = BatchManager::new();
@@ -236,6 +278,7 @@ mod tests {
             root: Some(PlanNode {
                 children: vec![],
                 identity: "".to_string(),
+                operator_id: 0,
                 node_body: Some(NodeBody::Values(ValuesNode {
                     tuples: vec![],
                     fields: vec![],
@@ -245,6 +288,7 @@ mod tests {
                 mode: DistributionMode::Single as i32,
                 distribution: None,
             }),
+            collect_executor_stats: false,
         };
         let context = ComputeNodeContext::new_for_test();
         let task_id = ProstTaskId {
@@ -272,6 +316,7 @@ mod tests {
             root: Some(PlanNode {
                 children: vec![],
                 identity: "".to_string(),
+                operator_id: 0,
                 node_body: Some(NodeBody::TableFunction(TableFunctionNode {
                     table_function: Some(TableFunction {
                         function_type: Type::Generate as i32,
@@ -290,6 +335,7 @@ mod tests {
                 mode: DistributionMode::Single as i32,
                 distribution: None,
             }),
+            collect_executor_stats: false,
         };
         let context = ComputeNodeContext::new_for_test();
         let task_id = ProstTaskId {
@@ -72,6 +72,10 @@ impl Array for BoolArray {
         self.data.len()
     }
 
+    fn estimated_size(&self) -> usize {
+        self.bitmap.estimated_size() + self.data.estimated_size()
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         ArrayIterator::new(self)
     }
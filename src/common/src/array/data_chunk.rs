@@ -180,6 +180,19 @@ impl DataChunk {
         }
     }
 
+    /// The estimated bytes of the memory held by the columns and the visibility of the chunk.
+    pub fn estimated_size(&self) -> usize {
+        let vis_size = match &self.vis2 {
+            Vis::Bitmap(b) => b.estimated_size(),
+            Vis::Compact(_) => 0,
+        };
+        self.columns
+            .iter()
+            .map(|column| column.array_ref().estimated_size())
+            .sum::<usize>()
+            + vis_size
+    }
+
     /// `capacity` returns physical length of any chunk column
     pub fn capacity(&self) -> usize {
         match &self.vis2 {
@@ -780,4 +793,17 @@ mod tests {
         let reorder = chunk.reorder_columns(&[]);
         assert_eq!(reorder.cardinality(), 3);
     }
+
+    #[test]
+    fn test_estimated_size() {
+        let chunk = DataChunk::from_pretty(
+            "I  T
+             1  abc
+             2  defgh",
+        );
+        // 2 i64 values, 3 offsets, 8 bytes of strings, and the null bitmaps.
+        assert_eq!(chunk.estimated_size(), 16 + 24 + 8 + 2);
+        let chunk = chunk.with_visibility((vec![true, false]).into_iter().collect());
+        assert_eq!(chunk.estimated_size(), 16 + 24 + 8 + 3);
+    }
 }
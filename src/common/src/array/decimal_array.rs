@@ -67,6 +67,10 @@ impl Array for DecimalArray {
         self.data.len()
     }
 
+    fn estimated_size(&self) -> usize {
+        self.bitmap.estimated_size() + self.data.len() * size_of::<Decimal>()
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         ArrayIterator::new(self)
     }
@@ -166,6 +166,12 @@ impl Array for ListArray {
         self.len
     }
 
+    fn estimated_size(&self) -> usize {
+        self.bitmap.estimated_size()
+            + self.offsets.len() * std::mem::size_of::<usize>()
+            + self.value.estimated_size()
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         ArrayIterator::new(self)
     }
@@ -166,6 +166,10 @@ pub trait Array: std::fmt::Debug + Send + Sync + Sized + 'static + Into<ArrayImp
     /// Number of items of array.
     fn len(&self) -> usize;
 
+    /// The estimated bytes of the memory held by the array, which is used to track the memory of
+    /// the executors.
+    fn estimated_size(&self) -> usize;
+
     /// Get iterator of current array.
     fn iter(&self) -> Self::Iter<'_>;
 
@@ -472,6 +476,12 @@ macro_rules! impl_array {
                 self.len() == 0
             }
 
+            pub fn estimated_size(&self) -> usize {
+                match self {
+                    $( Self::$variant_name(inner) => inner.estimated_size(), )*
+                }
+            }
+
             /// Get the null `Bitmap` of the array.
             pub fn null_bitmap(&self) -> &Bitmap {
                 match self {
@@ -180,6 +180,10 @@ impl<T: PrimitiveArrayItemType> Array for PrimitiveArray<T> {
         self.data.len()
     }
 
+    fn estimated_size(&self) -> usize {
+        self.bitmap.estimated_size() + self.data.len() * size_of::<T>()
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         ArrayIterator::new(self)
     }
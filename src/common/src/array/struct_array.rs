@@ -169,6 +169,15 @@ impl Array for StructArray {
         self.len
     }
 
+    fn estimated_size(&self) -> usize {
+        self.bitmap.estimated_size()
+            + self
+                .children
+                .iter()
+                .map(|child| child.estimated_size())
+                .sum::<usize>()
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         ArrayIterator::new(self)
     }
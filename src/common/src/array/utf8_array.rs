@@ -61,6 +61,10 @@ impl Array for Utf8Array {
         self.offset.len() - 1
     }
 
+    fn estimated_size(&self) -> usize {
+        self.bitmap.estimated_size() + self.offset.len() * size_of::<usize>() + self.data.len()
+    }
+
     fn iter(&self) -> ArrayIterator<'_, Self> {
         ArrayIterator::new(self)
     }
@@ -215,6 +215,11 @@ impl Bitmap {
         self.num_bits
     }
 
+    /// The bytes held by the bitmap.
+    pub fn estimated_size(&self) -> usize {
+        self.bits.len()
+    }
+
     /// Returns true if the `Bitmap` has a length of 0.
     pub fn is_empty(&self) -> bool {
         self.bits.is_empty()
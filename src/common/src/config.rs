@@ -78,6 +78,15 @@ impl Default for ServerConfig {
 pub struct BatchConfig {
     // #[serde(default = "default::chunk_size")]
     // pub chunk_size: u32,
+    /// Memory that the operators of a query may hold on a compute node, e.g. the rows of a sort or
+    /// the hash table of a hash join. Beyond it the operators spill to disk, or fail if they
+    /// can't. 0 means unlimited.
+    #[serde(default = "default::query_memory_limit_mb")]
+    pub query_memory_limit_mb: usize,
+
+    /// Local directory for the spill files. Empty means the temporary directory of the system.
+    #[serde(default = "default::spill_dir")]
+    pub spill_dir: String,
 }
 
 impl Default for BatchConfig {
@@ -306,6 +315,14 @@ mod default {
         "tempdisk".to_string()
     }
 
+    pub fn query_memory_limit_mb() -> usize {
+        1024
+    }
+
+    pub fn spill_dir() -> String {
+        "".to_string()
+    }
+
     pub fn checkpoint_interval_ms() -> u32 {
         250
     }
@@ -15,6 +15,7 @@
 mod query_mode;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::time::Duration;
 
 pub use query_mode::QueryMode;
 
@@ -22,13 +23,14 @@ use crate::error::{ErrorCode, RwError};
 
 // This is a hack, &'static str is not allowed as a const generics argument.
 // TODO: refine this using the adt_const_params feature.
-const CONFIG_KEYS: [&str; 6] = [
+const CONFIG_KEYS: [&str; 7] = [
     "RW_IMPLICIT_FLUSH",
     "QUERY_MODE",
     "EXTRA_FLOAT_DIGITS",
     "APPLICATION_NAME",
     "DATE_STYLE",
     "RW_BATCH_ENABLE_LOOKUP_JOIN",
+    "STATEMENT_TIMEOUT",
 ];
 
 // MUST HAVE 1v1 relationship to CONFIG_KEYS. e.g. CONFIG_KEYS[IMPLICIT_FLUSH] =
@@ -39,6 +41,7 @@ const EXTRA_FLOAT_DIGITS: usize = 2;
 const APPLICATION_NAME: usize = 3;
 const DATE_STYLE: usize = 4;
 const BATCH_ENABLE_LOOKUP_JOIN: usize = 5;
+const STATEMENT_TIMEOUT: usize = 6;
 
 trait ConfigEntry: Default + FromStr<Err = RwError> {
     fn entry_name() -> &'static str;
@@ -157,6 +160,7 @@ type ExtraFloatDigit = ConfigI32<EXTRA_FLOAT_DIGITS, 1>;
 // TODO: We should use more specified type here.
 type DateStyle = ConfigString<DATE_STYLE>;
 type BatchEnableLookupJoin = ConfigBool<BATCH_ENABLE_LOOKUP_JOIN, false>;
+type StatementTimeout = ConfigI32<STATEMENT_TIMEOUT, 0>;
 
 #[derive(Default)]
 pub struct ConfigMap {
@@ -180,6 +184,9 @@ pub struct ConfigMap {
 
     /// To force the usage of lookup join instead of hash join in batch execution
     batch_enable_lookup_join: BatchEnableLookupJoin,
+
+    /// see <https://www.postgresql.org/docs/current/runtime-config-client.html#GUC-STATEMENT-TIMEOUT>
+    statement_timeout: StatementTimeout,
 }
 
 impl ConfigMap {
@@ -196,6 +203,16 @@ impl ConfigMap {
             self.date_style = val.parse()?;
         } else if key.eq_ignore_ascii_case(BatchEnableLookupJoin::entry_name()) {
             self.batch_enable_lookup_join = val.parse()?;
+        } else if key.eq_ignore_ascii_case(StatementTimeout::entry_name()) {
+            let statement_timeout: StatementTimeout = val.parse()?;
+            if *statement_timeout < 0 {
+                return Err(ErrorCode::InvalidConfigValue {
+                    config_entry: StatementTimeout::entry_name().to_string(),
+                    config_value: val.to_string(),
+                }
+                .into());
+            }
+            self.statement_timeout = statement_timeout;
         } else {
             return Err(ErrorCode::UnrecognizedConfigurationParameter(key.to_string()).into());
         }
@@ -216,6 +233,8 @@ impl ConfigMap {
             Ok(self.date_style.to_string())
         } else if key.eq_ignore_ascii_case(BatchEnableLookupJoin::entry_name()) {
             Ok(self.batch_enable_lookup_join.to_string())
+        } else if key.eq_ignore_ascii_case(StatementTimeout::entry_name()) {
+            Ok(self.statement_timeout.to_string())
         } else {
             Err(ErrorCode::UnrecognizedConfigurationParameter(key.to_string()).into())
         }
@@ -253,6 +272,11 @@ impl ConfigMap {
                 setting : self.batch_enable_lookup_join.to_string(),
                 description : String::from("To enable the usage of lookup join instead of hash join when possible for local batch execution")
             },
+            VariableInfo{
+                name : StatementTimeout::entry_name().to_lowercase(),
+                setting : self.statement_timeout.to_string(),
+                description : String::from("Sets the maximum allowed duration of a batch query in milliseconds, 0 disables the timeout.")
+            },
         ]
     }
 
@@ -279,4 +303,12 @@ impl ConfigMap {
     pub fn get_batch_enable_lookup_join(&self) -> bool {
         *self.batch_enable_lookup_join
     }
+
+    /// Returns `None` if the timeout is disabled.
+    pub fn get_statement_timeout(&self) -> Option<Duration> {
+        match *self.statement_timeout {
+            0 => None,
+            timeout => Some(Duration::from_millis(timeout as u64)),
+        }
+    }
 }
@@ -234,6 +234,16 @@ impl StreamService for StreamServiceImpl {
 
         Ok(Response::new(ActorTraceResponse { actor_traces }))
     }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn actor_stats(
+        &self,
+        request: Request<ActorStatsRequest>,
+    ) -> Result<Response<ActorStatsResponse>, Status> {
+        let req = request.into_inner();
+        let actor_stats = self.mgr.get_actor_stats(&req.actor_ids);
+        Ok(Response::new(ActorStatsResponse { actor_stats }))
+    }
 }
 
 impl StreamServiceImpl {
@@ -144,6 +144,8 @@ async fn test_table_v2_materialize() -> Result<()> {
         source_desc,
         vnodes,
         keyspace,
+        None,
+        None,
         all_column_ids.clone(),
         all_schema.clone(),
         PkIndices::from([0]),
@@ -0,0 +1,265 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use anyhow::{anyhow, Result};
+use rdkafka::ClientConfig;
+use serde::Deserialize;
+
+/// The prefix of the options passed through to the Kafka clients.
+const KAFKA_PROPERTIES_PREFIX: &str = "properties.";
+
+/// The options to connect to secured Kafka clusters, shared by the Kafka source and sink. Each of
+/// them is the librdkafka option of the same name without the `properties.` prefix.
+#[derive(Clone, Default, Deserialize)]
+pub struct KafkaSecurityProperties {
+    /// One of `PLAINTEXT`, `SSL`, `SASL_PLAINTEXT` and `SASL_SSL`.
+    #[serde(rename = "properties.security.protocol")]
+    pub security_protocol: Option<String>,
+
+    /// One of `PLAIN`, `SCRAM-SHA-256` and `SCRAM-SHA-512`.
+    #[serde(rename = "properties.sasl.mechanism")]
+    pub sasl_mechanism: Option<String>,
+
+    #[serde(rename = "properties.sasl.username")]
+    pub sasl_username: Option<String>,
+
+    #[serde(rename = "properties.sasl.password")]
+    pub sasl_password: Option<String>,
+
+    /// The CA certificate to verify the brokers.
+    #[serde(rename = "properties.ssl.ca.location")]
+    pub ssl_ca_location: Option<String>,
+
+    /// The client certificate and its private key, if the brokers authenticate the clients by SSL.
+    #[serde(rename = "properties.ssl.certificate.location")]
+    pub ssl_certificate_location: Option<String>,
+
+    #[serde(rename = "properties.ssl.key.location")]
+    pub ssl_key_location: Option<String>,
+
+    #[serde(rename = "properties.ssl.key.password")]
+    pub ssl_key_password: Option<String>,
+
+    /// The other options, where the ones with the `properties.` prefix are rejected.
+    #[serde(flatten)]
+    others: HashMap<String, String>,
+}
+
+impl Debug for KafkaSecurityProperties {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let redacted = |password: &Option<String>| password.as_ref().map(|_| "******");
+        f.debug_struct("KafkaSecurityProperties")
+            .field("security_protocol", &self.security_protocol)
+            .field("sasl_mechanism", &self.sasl_mechanism)
+            .field("sasl_username", &self.sasl_username)
+            .field("sasl_password", &redacted(&self.sasl_password))
+            .field("ssl_ca_location", &self.ssl_ca_location)
+            .field("ssl_certificate_location", &self.ssl_certificate_location)
+            .field("ssl_key_location", &self.ssl_key_location)
+            .field("ssl_key_password", &redacted(&self.ssl_key_password))
+            .finish()
+    }
+}
+
+impl KafkaSecurityProperties {
+    pub fn from_hashmap(values: &HashMap<String, String>) -> Result<Self> {
+        let properties: Self = serde_json::to_value(values)
+            .and_then(serde_json::from_value)
+            .map_err(|e| anyhow!(e))?;
+        properties.validate()?;
+        Ok(properties)
+    }
+
+    /// Checks that the options are known and consistent with each other, so that a misconfigured
+    /// source or sink fails on creation rather than on connecting to the brokers.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(key) = self
+            .others
+            .keys()
+            .find(|key| key.starts_with(KAFKA_PROPERTIES_PREFIX))
+        {
+            return Err(anyhow!("unknown Kafka option \"{}\"", key));
+        }
+
+        let protocol = self
+            .security_protocol
+            .as_deref()
+            .map(str::to_uppercase)
+            .unwrap_or_else(|| "PLAINTEXT".to_string());
+        let (uses_ssl, uses_sasl) = match protocol.as_str() {
+            "PLAINTEXT" => (false, false),
+            "SSL" => (true, false),
+            "SASL_PLAINTEXT" => (false, true),
+            "SASL_SSL" => (true, true),
+            other => {
+                return Err(anyhow!(
+                    "properties.security.protocol must be one of PLAINTEXT, SSL, SASL_PLAINTEXT \
+                     and SASL_SSL, found: {}",
+                    other
+                ))
+            }
+        };
+
+        if uses_sasl {
+            match self
+                .sasl_mechanism
+                .as_deref()
+                .map(str::to_uppercase)
+                .as_deref()
+            {
+                Some("PLAIN" | "SCRAM-SHA-256" | "SCRAM-SHA-512") => {}
+                Some(other) => {
+                    return Err(anyhow!(
+                        "properties.sasl.mechanism must be one of PLAIN, SCRAM-SHA-256 and \
+                         SCRAM-SHA-512, found: {}",
+                        other
+                    ))
+                }
+                None => {
+                    return Err(anyhow!(
+                        "properties.sasl.mechanism must be set if the security protocol is {}",
+                        protocol
+                    ))
+                }
+            }
+            if self.sasl_username.is_none() || self.sasl_password.is_none() {
+                return Err(anyhow!(
+                    "properties.sasl.username and properties.sasl.password must be set if the \
+                     security protocol is {}",
+                    protocol
+                ));
+            }
+        } else if self.sasl_mechanism.is_some()
+            || self.sasl_username.is_some()
+            || self.sasl_password.is_some()
+        {
+            return Err(anyhow!(
+                "the SASL options require the security protocol to be SASL_PLAINTEXT or SASL_SSL, \
+                 found: {}",
+                protocol
+            ));
+        }
+
+        let has_ssl_options = self.ssl_ca_location.is_some()
+            || self.ssl_certificate_location.is_some()
+            || self.ssl_key_location.is_some()
+            || self.ssl_key_password.is_some();
+        if has_ssl_options && !uses_ssl {
+            return Err(anyhow!(
+                "the SSL options require the security protocol to be SSL or SASL_SSL, found: {}",
+                protocol
+            ));
+        }
+        if self.ssl_certificate_location.is_some() != self.ssl_key_location.is_some() {
+            return Err(anyhow!(
+                "properties.ssl.certificate.location and properties.ssl.key.location must be set \
+                 together"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sets the options on the config of a Kafka client.
+    pub fn set_client(&self, config: &mut ClientConfig) {
+        // The mechanisms are case-sensitive in librdkafka.
+        let sasl_mechanism = self.sasl_mechanism.as_deref().map(str::to_uppercase);
+        let options = [
+            ("security.protocol", &self.security_protocol),
+            ("sasl.mechanism", &sasl_mechanism),
+            ("sasl.username", &self.sasl_username),
+            ("sasl.password", &self.sasl_password),
+            ("ssl.ca.location", &self.ssl_ca_location),
+            ("ssl.certificate.location", &self.ssl_certificate_location),
+            ("ssl.key.location", &self.ssl_key_location),
+            ("ssl.key.password", &self.ssl_key_password),
+        ];
+        for (key, value) in options {
+            if let Some(value) = value {
+                config.set(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+
+    use super::*;
+
+    #[test]
+    fn test_kafka_security_properties() {
+        let properties = KafkaSecurityProperties::from_hashmap(&convert_args!(hashmap!(
+            "kafka.brokers" => "localhost:9093",
+            "properties.security.protocol" => "sasl_ssl",
+            "properties.sasl.mechanism" => "scram-sha-512",
+            "properties.sasl.username" => "user",
+            "properties.sasl.password" => "secret",
+            "properties.ssl.ca.location" => "/etc/ca.pem",
+        )))
+        .unwrap();
+        assert_eq!(properties.sasl_username.as_deref(), Some("user"));
+        assert!(!format!("{:?}", properties).contains("secret"));
+
+        let mut config = ClientConfig::new();
+        properties.set_client(&mut config);
+        assert_eq!(config.get("security.protocol"), Some("sasl_ssl"));
+        assert_eq!(config.get("sasl.mechanism"), Some("SCRAM-SHA-512"));
+        assert_eq!(config.get("sasl.password"), Some("secret"));
+        assert_eq!(config.get("ssl.certificate.location"), None);
+
+        // Plaintext by default.
+        assert!(KafkaSecurityProperties::from_hashmap(&HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_kafka_security_properties_invalid() {
+        for values in [
+            // Unknown option.
+            convert_args!(hashmap!("properties.sasl.mechanisms" => "PLAIN")),
+            // Unknown protocol.
+            convert_args!(hashmap!("properties.security.protocol" => "TLS")),
+            // SASL without credentials.
+            convert_args!(hashmap!(
+                "properties.security.protocol" => "SASL_PLAINTEXT",
+                "properties.sasl.mechanism" => "PLAIN",
+            )),
+            // Unsupported mechanism.
+            convert_args!(hashmap!(
+                "properties.security.protocol" => "SASL_PLAINTEXT",
+                "properties.sasl.mechanism" => "GSSAPI",
+                "properties.sasl.username" => "user",
+                "properties.sasl.password" => "secret",
+            )),
+            // SASL options without a SASL protocol.
+            convert_args!(hashmap!("properties.sasl.username" => "user")),
+            // SSL options without a SSL protocol.
+            convert_args!(hashmap!("properties.ssl.ca.location" => "/etc/ca.pem")),
+            // Certificate without the key.
+            convert_args!(hashmap!(
+                "properties.security.protocol" => "SSL",
+                "properties.ssl.certificate.location" => "/etc/client.pem",
+            )),
+        ] {
+            assert!(
+                KafkaSecurityProperties::from_hashmap(&values).is_err(),
+                "{:?}",
+                values
+            );
+        }
+    }
+}
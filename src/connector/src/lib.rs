@@ -37,6 +37,8 @@
 extern crate core;
 
 pub mod aws_utils;
+pub mod common;
 mod macros;
+pub mod schema_registry;
 pub mod sink;
 pub mod source;
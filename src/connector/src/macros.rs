@@ -93,10 +93,10 @@ macro_rules! impl_split {
                     .to_string()
             }
 
-            pub fn update(&self, start_offset: String) -> Self {
-                match self {
-                    $( Self::$variant_name(inner) => Self::$variant_name(inner.copy_with_offset(start_offset)), )*
-                }
+            pub fn update(&self, start_offset: String) -> Result<Self> {
+                Ok(match self {
+                    $( Self::$variant_name(inner) => Self::$variant_name(inner.copy_with_offset(start_offset)?), )*
+                })
             }
         }
     }
@@ -123,7 +123,6 @@ macro_rules! impl_split_reader {
 
                 let connector = match config {
                      $( ConnectorProperties::$variant_name(props) => Self::$variant_name(Box::new($split_reader_name::new(props, state, columns).await?)), )*
-                    _ => todo!()
                 };
 
                 Ok(connector)
@@ -0,0 +1,330 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The client of the [Confluent Schema Registry](https://docs.confluent.io/platform/current/schema-registry/index.html),
+//! shared by the sources reading and the sinks writing the messages in the Confluent wire format.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use anyhow::{anyhow, Result};
+use hyper::body::Buf;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, StatusCode, Uri};
+use hyper_tls::HttpsConnector;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+/// The URL of the schema registry, e.g. `http://localhost:8081` or `https://registry:8081`.
+pub const SCHEMA_REGISTRY_KEY: &str = "schema.registry";
+/// The credentials of the basic authentication, if the registry requires.
+pub const SCHEMA_REGISTRY_USERNAME_KEY: &str = "schema.registry.username";
+pub const SCHEMA_REGISTRY_PASSWORD_KEY: &str = "schema.registry.password";
+
+/// The first byte of the messages in the Confluent wire format, which is followed by the schema id
+/// in 4 bytes of big endian.
+pub const MAGIC_BYTE: u8 = 0;
+
+/// A schema in the registry. The Avro schemas are in JSON, and the Protobuf schemas are the text
+/// of `.proto` files, which may import other schemas by `references`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ConfluentSchema {
+    pub schema: String,
+    #[serde(default)]
+    pub references: Vec<SchemaReference>,
+}
+
+/// A reference to another schema, which is imported by the name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SchemaReference {
+    pub name: String,
+    pub subject: String,
+    pub version: i32,
+}
+
+/// The type of a schema, which is `AVRO` if not specified on registration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    Avro,
+    Protobuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegisteredSchema {
+    id: i32,
+}
+
+/// The credentials of the basic authentication of the registry.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct SchemaRegistryAuth {
+    pub username: String,
+    pub password: String,
+}
+
+impl Debug for SchemaRegistryAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemaRegistryAuth")
+            .field("username", &self.username)
+            .field("password", &"******")
+            .finish()
+    }
+}
+
+impl SchemaRegistryAuth {
+    /// Returns the credentials set by [`SCHEMA_REGISTRY_USERNAME_KEY`] and
+    /// [`SCHEMA_REGISTRY_PASSWORD_KEY`], which must be set together, or `None` if neither is set.
+    pub fn from_properties(properties: &HashMap<String, String>) -> Result<Option<Self>> {
+        match (
+            properties.get(SCHEMA_REGISTRY_USERNAME_KEY),
+            properties.get(SCHEMA_REGISTRY_PASSWORD_KEY),
+        ) {
+            (Some(username), Some(password)) => Ok(Some(Self {
+                username: username.clone(),
+                password: password.clone(),
+            })),
+            (None, None) => Ok(None),
+            _ => Err(anyhow!(
+                "{} and {} must be set together",
+                SCHEMA_REGISTRY_USERNAME_KEY,
+                SCHEMA_REGISTRY_PASSWORD_KEY
+            )),
+        }
+    }
+}
+
+/// The client of the registry over HTTP or HTTPS, which is decided by the scheme of the URL.
+#[derive(Clone)]
+pub struct SchemaRegistryClient {
+    base_url: String,
+    /// The `Authorization` header of the basic authentication.
+    authorization: Option<String>,
+    client: Client<HttpsConnector<HttpConnector>>,
+}
+
+impl Debug for SchemaRegistryClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemaRegistryClient")
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl SchemaRegistryClient {
+    pub fn new(base_url: &str, auth: Option<&SchemaRegistryAuth>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            authorization: auth.map(|auth| {
+                format!(
+                    "Basic {}",
+                    base64::encode(format!("{}:{}", auth.username, auth.password))
+                )
+            }),
+            client: Client::builder().build(HttpsConnector::new()),
+        }
+    }
+
+    pub async fn get_schema_by_id(&self, id: i32) -> Result<ConfluentSchema> {
+        self.get(&format!("schemas/ids/{}", id)).await
+    }
+
+    /// Gets a version of the subject, where the version may also be `latest`.
+    pub async fn get_schema_by_subject(
+        &self,
+        subject: &str,
+        version: &str,
+    ) -> Result<ConfluentSchema> {
+        self.get(&format!(
+            "subjects/{}/versions/{}",
+            urlencoding::encode(subject),
+            version
+        ))
+        .await
+    }
+
+    pub async fn get_latest_schema(&self, subject: &str) -> Result<ConfluentSchema> {
+        self.get_schema_by_subject(subject, "latest").await
+    }
+
+    /// Registers the schema under the subject, and returns its id. Registering a schema that's
+    /// already registered returns the existing id.
+    pub async fn register(
+        &self,
+        subject: &str,
+        schema_type: SchemaType,
+        schema: &str,
+    ) -> Result<i32> {
+        let body = match schema_type {
+            SchemaType::Avro => json!({ "schema": schema }),
+            SchemaType::Protobuf => json!({ "schema": schema, "schemaType": "PROTOBUF" }),
+        };
+        let registered: RegisteredSchema = self
+            .request(
+                Method::POST,
+                &format!("subjects/{}/versions", urlencoding::encode(subject)),
+                Body::from(body.to_string()),
+            )
+            .await?;
+        Ok(registered.id)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.request(Method::GET, path, Body::empty()).await
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Body,
+    ) -> Result<T> {
+        let url = format!("{}/{}", self.base_url, path);
+        let uri: Uri = url
+            .parse()
+            .map_err(|e| anyhow!("invalid schema registry url {}: {}", url, e))?;
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/vnd.schemaregistry.v1+json");
+        if let Some(authorization) = &self.authorization {
+            request = request.header("Authorization", authorization);
+        }
+        let request = request.body(body)?;
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| anyhow!("failed to request schema registry {}: {}", url, e))?;
+        let status = response.status();
+        let body = hyper::body::aggregate(response)
+            .await
+            .map_err(|e| anyhow!("failed to read response of schema registry {}: {}", url, e))?;
+        if status != StatusCode::OK {
+            let mut message = String::new();
+            std::io::Read::read_to_string(&mut body.reader(), &mut message).ok();
+            return Err(anyhow!(
+                "schema registry {} responded {}: {}",
+                url,
+                status,
+                message
+            ));
+        }
+        serde_json::from_reader(body.reader())
+            .map_err(|e| anyhow!("invalid response of schema registry {}: {}", url, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(madsim, ignore)] // MockServer is not supported in simulation.
+    async fn test_schema_registry_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"schema":"\"string\""}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/subjects/t-value/versions/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "subject": "t-value",
+                    "version": 2,
+                    "id": 3,
+                    "schemaType": "PROTOBUF",
+                    "schema": "syntax = \"proto3\";",
+                    "references": [{"name": "a.proto", "subject": "a", "version": 1}]
+                }"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/subjects/t-value/versions"))
+            .and(body_json(
+                json!({"schema": "syntax = \"proto3\";", "schemaType": "PROTOBUF"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":3}"#))
+            .mount(&server)
+            .await;
+
+        let client = SchemaRegistryClient::new(&server.uri(), None);
+        assert_eq!(
+            client.get_schema_by_id(1).await.unwrap(),
+            ConfluentSchema {
+                schema: "\"string\"".to_string(),
+                references: vec![],
+            }
+        );
+        assert_eq!(
+            client.get_latest_schema("t-value").await.unwrap(),
+            ConfluentSchema {
+                schema: "syntax = \"proto3\";".to_string(),
+                references: vec![SchemaReference {
+                    name: "a.proto".to_string(),
+                    subject: "a".to_string(),
+                    version: 1,
+                }],
+            }
+        );
+        assert_eq!(
+            client
+                .register("t-value", SchemaType::Protobuf, "syntax = \"proto3\";")
+                .await
+                .unwrap(),
+            3
+        );
+        // Not found.
+        assert!(client.get_schema_by_id(2).await.is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(madsim, ignore)] // MockServer is not supported in simulation.
+    async fn test_schema_registry_auth() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/1"))
+            // `user:pass` in base64.
+            .and(header("Authorization", "Basic dXNlcjpwYXNz"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"schema":"\"string\""}"#))
+            .mount(&server)
+            .await;
+
+        let auth = SchemaRegistryAuth::from_properties(&convert_args!(hashmap!(
+            "schema.registry.username" => "user",
+            "schema.registry.password" => "pass",
+        )))
+        .unwrap();
+        let client = SchemaRegistryClient::new(&server.uri(), auth.as_ref());
+        assert!(client.get_schema_by_id(1).await.is_ok());
+        // Unauthorized.
+        let client = SchemaRegistryClient::new(&server.uri(), None);
+        assert!(client.get_schema_by_id(1).await.is_err());
+
+        assert_eq!(
+            SchemaRegistryAuth::from_properties(&HashMap::new()).unwrap(),
+            None
+        );
+        assert!(SchemaRegistryAuth::from_properties(&convert_args!(hashmap!(
+            "schema.registry.username" => "user",
+        )))
+        .is_err());
+    }
+}
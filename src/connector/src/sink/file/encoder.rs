@@ -0,0 +1,371 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoders of the files written by the file sink.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+use risingwave_common::array::{Op, RowRef};
+use risingwave_common::catalog::Schema;
+use risingwave_common::types::{DataType, DatumRef, ScalarRefImpl};
+use serde_json::{Map, Value};
+
+use crate::sink::serializer::datum_to_json_object;
+use crate::sink::{Result, SinkError};
+
+/// The name of the column of the operations in the changelog files.
+pub const OP_COLUMN: &str = "__op";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,
+    Json,
+    Parquet,
+}
+
+impl FileFormat {
+    pub fn from_option(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "csv" => Ok(FileFormat::Csv),
+            "json" => Ok(FileFormat::Json),
+            "parquet" => Ok(FileFormat::Parquet),
+            _ => Err(SinkError::Config(
+                "file.format must be set to \"csv\", \"json\" or \"parquet\"".to_string(),
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Csv => "csv",
+            FileFormat::Json => "jsonl",
+            FileFormat::Parquet => "parquet",
+        }
+    }
+
+    /// Encodes `rows` into a file. If `with_op` is set, the operation of each row is written to
+    /// the first column [`OP_COLUMN`].
+    pub fn encode(&self, schema: &Schema, rows: &[(Op, RowRef)], with_op: bool) -> Result<Vec<u8>> {
+        match self {
+            FileFormat::Csv => Ok(encode_csv(schema, rows, with_op)),
+            FileFormat::Json => encode_json(schema, rows, with_op),
+            FileFormat::Parquet => encode_parquet(schema, rows, with_op),
+        }
+    }
+}
+
+/// The changelog operations are written in the short forms used by Flink.
+fn op_name(op: Op) -> &'static str {
+    match op {
+        Op::Insert => "+I",
+        Op::Delete => "-D",
+        Op::UpdateDelete => "-U",
+        Op::UpdateInsert => "+U",
+    }
+}
+
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Encodes the rows into CSV with a header line. `NULL` values are written as empty fields.
+fn encode_csv(schema: &Schema, rows: &[(Op, RowRef)], with_op: bool) -> Vec<u8> {
+    let mut csv = String::new();
+    let mut write_line = |fields: Vec<String>| {
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).join(","));
+        csv.push('\n');
+    };
+
+    let op_header = with_op.then(|| OP_COLUMN.to_string());
+    write_line(op_header.into_iter().chain(schema.names()).collect());
+    for (op, row) in rows {
+        let op = with_op.then(|| op_name(*op).to_string());
+        let values = row
+            .values()
+            .map(|datum| datum.map(|scalar| scalar.to_string()).unwrap_or_default());
+        write_line(op.into_iter().chain(values).collect());
+    }
+    csv.into_bytes()
+}
+
+/// Encodes the rows into newline-delimited JSON objects.
+fn encode_json(schema: &Schema, rows: &[(Op, RowRef)], with_op: bool) -> Result<Vec<u8>> {
+    let mut json = String::new();
+    for (op, row) in rows {
+        let mut object = Map::with_capacity(schema.len() + 1);
+        if with_op {
+            object.insert(OP_COLUMN.to_string(), Value::from(op_name(*op)));
+        }
+        for (field, datum) in schema.fields.iter().zip_eq(row.values()) {
+            let value = datum_to_json_object(field, datum)
+                .map_err(|e| SinkError::JsonParse(e.to_string()))?;
+            object.insert(field.name.clone(), value);
+        }
+        json.push_str(&Value::Object(object).to_string());
+        json.push('\n');
+    }
+    Ok(json.into_bytes())
+}
+
+fn parquet_type(name: &str, data_type: &DataType) -> Result<Type> {
+    let (physical_type, converted_type) = match data_type {
+        DataType::Boolean => (PhysicalType::BOOLEAN, ConvertedType::NONE),
+        DataType::Int16 => (PhysicalType::INT32, ConvertedType::INT_16),
+        DataType::Int32 => (PhysicalType::INT32, ConvertedType::NONE),
+        DataType::Int64 => (PhysicalType::INT64, ConvertedType::NONE),
+        DataType::Float32 => (PhysicalType::FLOAT, ConvertedType::NONE),
+        DataType::Float64 => (PhysicalType::DOUBLE, ConvertedType::NONE),
+        DataType::Varchar | DataType::Decimal | DataType::Interval => {
+            (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8)
+        }
+        DataType::Date => (PhysicalType::INT32, ConvertedType::DATE),
+        DataType::Time => (PhysicalType::INT64, ConvertedType::TIME_MICROS),
+        DataType::Timestamp | DataType::Timestampz => {
+            (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS)
+        }
+        DataType::Struct { .. } | DataType::List { .. } => {
+            return Err(SinkError::Encode(format!(
+                "unsupported data type in parquet: {}",
+                data_type
+            )))
+        }
+    };
+    Type::primitive_type_builder(name, physical_type)
+        .with_repetition(Repetition::OPTIONAL)
+        .with_converted_type(converted_type)
+        .build()
+        .map_err(|e| SinkError::Encode(e.to_string()))
+}
+
+/// The values of a column in the physical types of parquet, and the definition levels, which
+/// are 0 for `NULL` values.
+#[derive(Default)]
+struct ParquetColumn<T> {
+    values: Vec<T>,
+    def_levels: Vec<i16>,
+}
+
+impl<T> ParquetColumn<T> {
+    fn push(&mut self, value: Option<T>) {
+        match value {
+            None => self.def_levels.push(0),
+            Some(value) => {
+                self.values.push(value);
+                self.def_levels.push(1);
+            }
+        }
+    }
+}
+
+/// Encodes the rows into a parquet file with a single row group. The decimal and interval values
+/// are written as strings.
+fn encode_parquet(schema: &Schema, rows: &[(Op, RowRef)], with_op: bool) -> Result<Vec<u8>> {
+    let encode_err = |e: parquet::errors::ParquetError| SinkError::Encode(e.to_string());
+
+    let mut fields = Vec::with_capacity(schema.len() + 1);
+    if with_op {
+        fields.push(Arc::new(parquet_type(OP_COLUMN, &DataType::Varchar)?));
+    }
+    for field in &schema.fields {
+        fields.push(Arc::new(parquet_type(&field.name, &field.data_type)?));
+    }
+    let parquet_schema = Type::group_type_builder("schema")
+        .with_fields(&mut fields)
+        .build()
+        .map_err(encode_err)?;
+
+    let mut buf = vec![];
+    let mut writer = SerializedFileWriter::new(
+        &mut buf,
+        Arc::new(parquet_schema),
+        Arc::new(WriterProperties::builder().build()),
+    )
+    .map_err(encode_err)?;
+    let mut row_group = writer.next_row_group().map_err(encode_err)?;
+
+    let mut column_idx = 0;
+    while let Some(mut column) = row_group.next_column().map_err(encode_err)? {
+        if with_op && column_idx == 0 {
+            let ops = rows
+                .iter()
+                .map(|(op, _)| Some(ScalarRefImpl::Utf8(op_name(*op))));
+            write_parquet_column(column.untyped(), ops)?;
+        } else {
+            let idx = column_idx - with_op as usize;
+            let datums = rows.iter().map(|(_, row)| row.value_at(idx));
+            write_parquet_column(column.untyped(), datums)?;
+        }
+        column.close().map_err(encode_err)?;
+        column_idx += 1;
+    }
+    row_group.close().map_err(encode_err)?;
+    writer.close().map_err(encode_err)?;
+
+    Ok(buf)
+}
+
+fn write_parquet_column<'a>(
+    writer: &mut ColumnWriter,
+    datums: impl Iterator<Item = DatumRef<'a>>,
+) -> Result<()> {
+    macro_rules! write_column {
+        ($writer:expr, $value:ident => $convert:expr) => {{
+            let mut column = ParquetColumn::default();
+            for datum in datums {
+                column.push(datum.map(|$value| $convert));
+            }
+            $writer
+                .write_batch(&column.values, Some(&column.def_levels), None)
+                .map(|_| ())
+                .map_err(|e| SinkError::Encode(e.to_string()))
+        }};
+    }
+
+    match writer {
+        ColumnWriter::BoolColumnWriter(w) => write_column!(w, v => match v {
+            ScalarRefImpl::Bool(v) => v,
+            _ => unreachable!(),
+        }),
+        ColumnWriter::Int32ColumnWriter(w) => write_column!(w, v => match v {
+            ScalarRefImpl::Int16(v) => v as i32,
+            ScalarRefImpl::Int32(v) => v,
+            ScalarRefImpl::NaiveDate(v) => {
+                (v.0 - chrono::NaiveDate::from_ymd(1970, 1, 1)).num_days() as i32
+            }
+            _ => unreachable!(),
+        }),
+        ColumnWriter::Int64ColumnWriter(w) => write_column!(w, v => match v {
+            // The timestamp with time zone is stored as the microseconds since the epoch.
+            ScalarRefImpl::Int64(v) => v,
+            ScalarRefImpl::NaiveTime(v) => (v.0 - chrono::NaiveTime::from_hms(0, 0, 0))
+                .num_microseconds()
+                .unwrap(),
+            ScalarRefImpl::NaiveDateTime(v) => {
+                v.0.timestamp() * 1_000_000 + v.0.timestamp_subsec_micros() as i64
+            }
+            _ => unreachable!(),
+        }),
+        ColumnWriter::FloatColumnWriter(w) => write_column!(w, v => match v {
+            ScalarRefImpl::Float32(v) => f32::from(v),
+            _ => unreachable!(),
+        }),
+        ColumnWriter::DoubleColumnWriter(w) => write_column!(w, v => match v {
+            ScalarRefImpl::Float64(v) => f64::from(v),
+            _ => unreachable!(),
+        }),
+        ColumnWriter::ByteArrayColumnWriter(w) => {
+            write_column!(w, v => ByteArray::from(v.to_string().into_bytes()))
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::array::StreamChunk;
+    use risingwave_common::catalog::Field;
+
+    use super::*;
+
+    fn test_schema() -> Schema {
+        Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+        ])
+    }
+
+    fn test_chunk() -> StreamChunk {
+        StreamChunk::from_pretty(
+            " i T
+            +  1 a,b
+            -  2 .
+            U- 3 c
+            U+ 3 d",
+        )
+    }
+
+    #[test]
+    fn test_encode_csv() {
+        let chunk = test_chunk();
+        let rows = chunk.rows().collect_vec();
+        let csv = FileFormat::Csv.encode(&test_schema(), &rows, true).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "__op,id,name\n+I,1,\"a,b\"\n-D,2,\n-U,3,c\n+U,3,d\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_json() {
+        let chunk = test_chunk();
+        let rows = chunk.rows().take(2).collect_vec();
+        let json = FileFormat::Json
+            .encode(&test_schema(), &rows, false)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"id\":1,\"name\":\"a,b\"}\n{\"id\":2,\"name\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_parquet() {
+        let chunk = test_chunk();
+        let rows = chunk.rows().collect_vec();
+        let parquet = FileFormat::Parquet
+            .encode(&test_schema(), &rows, true)
+            .unwrap();
+
+        let reader = SerializedFileReader::new(Bytes::from(parquet)).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 4);
+        let columns = metadata
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect_vec();
+        assert_eq!(columns, vec!["__op", "id", "name"]);
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.to_string())
+            .collect_vec();
+        assert_eq!(
+            rows,
+            vec![
+                r#"{__op: "+I", id: 1, name: "a,b"}"#,
+                r#"{__op: "-D", id: 2, name: null}"#,
+                r#"{__op: "-U", id: 3, name: "c"}"#,
+                r#"{__op: "+U", id: 3, name: "d"}"#,
+            ]
+        );
+    }
+}
@@ -24,16 +24,20 @@ use rdkafka::message::ToBytes;
 use rdkafka::producer::{BaseRecord, DefaultProducerContext, Producer, ThreadedProducer};
 use rdkafka::types::RDKafkaErrorCode;
 use rdkafka::ClientConfig;
-use risingwave_common::array::{ArrayResult, Op, RowRef, StreamChunk};
+use risingwave_common::array::{Op, StreamChunk};
 use risingwave_common::catalog::{Field, Schema};
-use risingwave_common::types::{DataType, DatumRef, ScalarRefImpl};
 use serde::Deserialize;
 use serde_json::{json, Map, Value};
 use tokio::task;
 use tracing::warn;
 
 use super::{Sink, SinkError};
-use crate::sink::Result;
+use crate::common::KafkaSecurityProperties;
+use crate::schema_registry::{SchemaRegistryAuth, SchemaRegistryClient, SCHEMA_REGISTRY_KEY};
+use crate::sink::serializer::{record_to_json, RowSerializer, RowSerializerImpl, SinkEncode};
+use crate::sink::{
+    Result, SINK_FORMAT_APPEND_ONLY, SINK_FORMAT_DEBEZIUM, SINK_FORMAT_OPTION, SINK_FORMAT_UPSERT,
+};
 
 pub const KAFKA_SINK: &str = "kafka";
 
@@ -50,41 +54,84 @@ pub struct KafkaConfig {
     // partition number. The partition number should set by meta.
     pub partition: Option<i32>,
 
-    pub format: String, // accept "append_only" or "debezium"
+    pub format: String, // accept "append_only", "debezium" or "upsert"
+
+    /// The encoding of the message values, and of the keys in the `upsert` format.
+    pub encode: SinkEncode,
+
+    /// The URL of the schema registry. If set, the Avro or Protobuf schemas are registered under
+    /// the subjects `<topic>-key` and `<topic>-value`, and the messages are written in the
+    /// Confluent wire format.
+    pub schema_registry: Option<String>,
+    pub schema_registry_auth: Option<SchemaRegistryAuth>,
 
     pub identifier: String,
 
     pub timeout: Duration,
     pub max_retry_num: i32,
     pub retry_interval: Duration,
+
+    pub security: KafkaSecurityProperties,
 }
 
 impl KafkaConfig {
     pub fn from_hashmap(values: HashMap<String, String>) -> Result<Self> {
-        let brokers = values
-            .get("kafka.brokers")
-            .expect("kafka.brokers must be set");
-        let identifier = values
-            .get("identifier")
-            .expect("kafka.identifier must be set");
-        let format = values.get("format").expect("format must be set");
-        if format != "append_only" && format != "debezium" {
+        let get = |key: &str| {
+            values
+                .get(key)
+                .ok_or_else(|| SinkError::Config(format!("{} must be set", key)))
+        };
+        let brokers = get("kafka.brokers")?;
+        // The identifier is filled by the sink executor, so it is absent when the properties are
+        // validated on `CREATE SINK`.
+        let identifier = values.get("identifier").cloned().unwrap_or_default();
+        let format = get(SINK_FORMAT_OPTION)?;
+        if ![
+            SINK_FORMAT_APPEND_ONLY,
+            SINK_FORMAT_DEBEZIUM,
+            SINK_FORMAT_UPSERT,
+        ]
+        .contains(&format.as_str())
+        {
             return Err(SinkError::Config(
-                "format must be set to \"append_only\" or \"debezium\"".to_string(),
+                "format must be set to \"append_only\", \"debezium\" or \"upsert\"".to_string(),
             ));
         }
+        let encode = SinkEncode::from_option(values.get(SinkEncode::KEY))?;
+        if format == SINK_FORMAT_DEBEZIUM && encode != SinkEncode::Json {
+            return Err(SinkError::Config(
+                "debezium format only supports json encode".to_string(),
+            ));
+        }
+
+        let schema_registry = values.get(SCHEMA_REGISTRY_KEY).cloned();
+        if schema_registry.is_some() && encode == SinkEncode::Json {
+            return Err(SinkError::Config(format!(
+                "{} requires avro or protobuf encode",
+                SCHEMA_REGISTRY_KEY
+            )));
+        }
 
-        let topic = values.get("kafka.topic").expect("kafka.topic must be set");
+        let schema_registry_auth = SchemaRegistryAuth::from_properties(&values)
+            .map_err(|e| SinkError::Config(e.to_string()))?;
+
+        let topic = get("kafka.topic")?;
+        let security = KafkaSecurityProperties::from_hashmap(&values)
+            .map_err(|e| SinkError::Config(e.to_string()))?;
 
         Ok(KafkaConfig {
             brokers: brokers.to_string(),
             topic: topic.to_string(),
-            identifier: identifier.to_owned(),
+            identifier,
             partition: None,
             timeout: Duration::from_secs(5), // default timeout is 5 seconds
             max_retry_num: 3,                // default max retry num is 3
             retry_interval: Duration::from_millis(100), // default retry interval is 100ms
             format: format.to_string(),
+            encode,
+            schema_registry,
+            schema_registry_auth,
+            security,
         })
     }
 }
@@ -96,23 +143,73 @@ enum KafkaSinkState {
     Running(u64),
 }
 
+/// The serializers of the message keys and values, which are created on the first chunk since
+/// the schema is unknown before.
+struct KafkaSerializers {
+    /// Only used in the `upsert` format, and serializes the primary key columns.
+    key: Option<RowSerializerImpl>,
+    value: RowSerializerImpl,
+}
+
 pub struct KafkaSink {
     pub config: KafkaConfig,
     pub conductor: KafkaTransactionConductor,
     state: KafkaSinkState,
     in_transaction_epoch: Option<u64>,
+    pk_indices: Vec<usize>,
+    serializers: Option<KafkaSerializers>,
 }
 
 impl KafkaSink {
-    pub fn new(config: KafkaConfig) -> Result<Self> {
+    pub fn new(config: KafkaConfig, pk_indices: Vec<usize>) -> Result<Self> {
+        if config.format == SINK_FORMAT_UPSERT && pk_indices.is_empty() {
+            return Err(SinkError::Config(
+                "upsert format requires the sink to have a primary key".to_string(),
+            ));
+        }
         Ok(KafkaSink {
             config: config.clone(),
             conductor: KafkaTransactionConductor::new(config)?,
             in_transaction_epoch: None,
             state: KafkaSinkState::Init,
+            pk_indices,
+            serializers: None,
         })
     }
 
+    async fn init_serializers(&mut self, schema: &Schema) -> Result<()> {
+        if self.serializers.is_none() {
+            let mut key = if self.config.format == SINK_FORMAT_UPSERT {
+                Some(RowSerializerImpl::new(
+                    self.config.encode,
+                    schema,
+                    &self.pk_indices,
+                    "Key",
+                )?)
+            } else {
+                None
+            };
+            let mut value = RowSerializerImpl::new(
+                self.config.encode,
+                schema,
+                &(0..schema.len()).collect_vec(),
+                "Value",
+            )?;
+            if let Some(url) = &self.config.schema_registry {
+                let client =
+                    SchemaRegistryClient::new(url, self.config.schema_registry_auth.as_ref());
+                if let Some(serializer) = key {
+                    let subject = format!("{}-key", self.config.topic);
+                    key = Some(register_schema(&client, &subject, serializer).await?);
+                }
+                let subject = format!("{}-value", self.config.topic);
+                value = register_schema(&client, &subject, value).await?;
+            }
+            self.serializers = Some(KafkaSerializers { key, value });
+        }
+        Ok(())
+    }
+
     // any error should report to upper level and requires revert to previous epoch.
     pub async fn do_with_retry<F, FutKR, T>(&self, f: F) -> KafkaResult<T>
     where
@@ -233,20 +330,89 @@ impl KafkaSink {
         Ok(())
     }
 
-    async fn append_only(&self, chunk: StreamChunk, schema: &Schema) -> Result<()> {
+    async fn append_only(&self, chunk: StreamChunk, value: &RowSerializerImpl) -> Result<()> {
         for (op, row) in chunk.rows() {
             if op == Op::Insert {
-                let record = Value::Object(record_to_json(row, schema.fields.clone())?).to_string();
+                let record = value.serialize(&row)?;
                 self.send(
                     BaseRecord::to(self.config.topic.as_str())
                         .key(self.gen_message_key().as_bytes())
-                        .payload(record.as_bytes()),
+                        .payload(&record),
                 )
                 .await?;
             }
         }
         Ok(())
     }
+
+    async fn upsert(&self, chunk: StreamChunk, serializers: &KafkaSerializers) -> Result<()> {
+        let key = serializers.key.as_ref().unwrap();
+        for (key, payload) in upsert_records(&chunk, key, &serializers.value)? {
+            let record = BaseRecord::<[u8], [u8]>::to(self.config.topic.as_str()).key(&key);
+            match &payload {
+                Some(payload) => self.send(record.payload(payload)).await?,
+                // A tombstone, which removes the key in a compacted topic.
+                None => self.send(record).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Registers the schema of the serializer under the subject, and makes the serializer write the
+/// messages with the id of the schema.
+async fn register_schema(
+    client: &SchemaRegistryClient,
+    subject: &str,
+    serializer: RowSerializerImpl,
+) -> Result<RowSerializerImpl> {
+    let (schema_type, schema) = serializer.registry_schema().ok_or_else(|| {
+        SinkError::Config(format!(
+            "the encode of {} has no schema to register",
+            subject
+        ))
+    })?;
+    let schema_id = client
+        .register(subject, schema_type, &schema)
+        .await
+        .map_err(|e| SinkError::SchemaRegistry(e.to_string()))?;
+    Ok(serializer.with_schema_id(schema_id))
+}
+
+/// The key and value of a message in the `upsert` format. The value is `None` for the tombstone of
+/// a deleted key.
+type UpsertRecord = (Vec<u8>, Option<Vec<u8>>);
+
+/// The messages written for `chunk` in the `upsert` format.
+fn upsert_records(
+    chunk: &StreamChunk,
+    key: &RowSerializerImpl,
+    value: &RowSerializerImpl,
+) -> Result<Vec<UpsertRecord>> {
+    let mut records = Vec::with_capacity(chunk.capacity());
+    // The key deleted by the last `UpdateDelete`, which needs no tombstone if the following
+    // `UpdateInsert` overwrites the same key.
+    let mut update_deleted_key = None;
+    for (op, row) in chunk.rows() {
+        let row_key = key.serialize(&row)?;
+        match op {
+            Op::Insert => records.push((row_key, Some(value.serialize(&row)?))),
+            Op::Delete => records.push((row_key, None)),
+            Op::UpdateDelete => update_deleted_key = Some(row_key),
+            Op::UpdateInsert => {
+                if let Some(deleted_key) = update_deleted_key.take() {
+                    if deleted_key != row_key {
+                        records.push((deleted_key, None));
+                    }
+                }
+                records.push((row_key, Some(value.serialize(&row)?)));
+            }
+        }
+    }
+    if let Some(deleted_key) = update_deleted_key {
+        records.push((deleted_key, None));
+    }
+    Ok(records)
 }
 
 #[async_trait::async_trait]
@@ -257,11 +423,14 @@ impl Sink for KafkaSink {
         // &self.in_transaction_epoch.unwrap()) && in_txn_epoch <= epoch {     return Ok(())
         // }
 
-        println!("sink chunk {:?}", chunk);
+        tracing::debug!("sink chunk {:?}", chunk);
 
+        self.init_serializers(schema).await?;
+        let serializers = self.serializers.as_ref().unwrap();
         match self.config.format.as_str() {
-            "append_only" => self.append_only(chunk, schema).await,
-            "debezium" => {
+            SINK_FORMAT_APPEND_ONLY => self.append_only(chunk, &serializers.value).await,
+            SINK_FORMAT_UPSERT => self.upsert(chunk, serializers).await,
+            SINK_FORMAT_DEBEZIUM => {
                 self.debezium_update(
                     chunk,
                     schema,
@@ -331,84 +500,6 @@ impl Debug for KafkaSink {
     }
 }
 
-fn datum_to_json_object(field: &Field, datum: DatumRef) -> ArrayResult<Value> {
-    let scalar_ref = match datum {
-        None => return Ok(Value::Null),
-        Some(datum) => datum,
-    };
-
-    let data_type = field.data_type();
-
-    let value = match (data_type, scalar_ref) {
-        (DataType::Boolean, ScalarRefImpl::Bool(v)) => {
-            json!(v)
-        }
-        (DataType::Int16, ScalarRefImpl::Int16(v)) => {
-            json!(v)
-        }
-        (DataType::Int32, ScalarRefImpl::Int32(v)) => {
-            json!(v)
-        }
-        (DataType::Int64, ScalarRefImpl::Int64(v)) => {
-            json!(v)
-        }
-        (DataType::Float32, ScalarRefImpl::Float32(v)) => {
-            json!(f32::from(v))
-        }
-        (DataType::Float64, ScalarRefImpl::Float64(v)) => {
-            json!(f64::from(v))
-        }
-        (DataType::Varchar, ScalarRefImpl::Utf8(v)) => {
-            json!(v)
-        }
-        (DataType::Decimal, ScalarRefImpl::Decimal(v)) => {
-            // fixme
-            json!(v.to_string())
-        }
-        (DataType::Time, ScalarRefImpl::NaiveTime(_v)) => {
-            unimplemented!()
-        }
-        (DataType::List { .. }, ScalarRefImpl::List(list_ref)) => {
-            let mut vec = Vec::with_capacity(field.sub_fields.len());
-            for (sub_datum_ref, sub_field) in list_ref
-                .values_ref()
-                .into_iter()
-                .zip_eq(field.sub_fields.iter())
-            {
-                let value = datum_to_json_object(sub_field, sub_datum_ref)?;
-                vec.push(value);
-            }
-            json!(vec)
-        }
-        (DataType::Struct { .. }, ScalarRefImpl::Struct(struct_ref)) => {
-            let mut map = Map::with_capacity(field.sub_fields.len());
-            for (sub_datum_ref, sub_field) in struct_ref
-                .fields_ref()
-                .into_iter()
-                .zip_eq(field.sub_fields.iter())
-            {
-                let value = datum_to_json_object(sub_field, sub_datum_ref)?;
-                map.insert(sub_field.name.clone(), value);
-            }
-            json!(map)
-        }
-        _ => unimplemented!(),
-    };
-
-    Ok(value)
-}
-
-fn record_to_json(row: RowRef, schema: Vec<Field>) -> Result<Map<String, Value>> {
-    let mut mappings = Map::with_capacity(schema.len());
-    for (field, datum_ref) in schema.iter().zip_eq(row.values()) {
-        let key = field.name.clone();
-        let value = datum_to_json_object(field, datum_ref)
-            .map_err(|e| SinkError::JsonParse(e.to_string()))?;
-        mappings.insert(key, value);
-    }
-    Ok(mappings)
-}
-
 pub fn chunk_to_json(chunk: StreamChunk, schema: &Schema) -> Result<Vec<String>> {
     let mut records: Vec<String> = Vec::with_capacity(chunk.capacity());
     for (_, row) in chunk.rows() {
@@ -463,10 +554,13 @@ pub struct KafkaTransactionConductor {
 
 impl KafkaTransactionConductor {
     fn new(config: KafkaConfig) -> Result<Self> {
-        let inner = ClientConfig::new()
+        let mut client_config = ClientConfig::new();
+        client_config
             .set("bootstrap.servers", config.brokers.as_str())
             .set("message.timeout.ms", "5000")
-            .set("transactional.id", config.identifier.as_str()) // required by kafka transaction
+            .set("transactional.id", config.identifier.as_str()); // required by kafka transaction
+        config.security.set_client(&mut client_config);
+        let inner = client_config
             .create_with_context(DefaultProducerContext)
             .expect("Producer creation error");
 
@@ -529,7 +623,9 @@ mod test {
     #[allow(unused_imports)]
     use maplit::hashmap;
     #[allow(unused_imports)]
-    use risingwave_common::types::OrderedF32;
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    #[allow(unused_imports)]
+    use risingwave_common::types::{DataType, OrderedF32};
     #[allow(unused_imports)]
     use risingwave_common::{
         array,
@@ -548,11 +644,11 @@ mod test {
         let properties = hashmap! {
             "kafka.brokers".to_string() => "localhost:29092".to_string(),
             "identifier".to_string() => "test_sink_1".to_string(),
-            "sink.type".to_string() => "append_only".to_string(),
+            "format".to_string() => "append_only".to_string(),
             "kafka.topic".to_string() => "test_topic".to_string(),
         };
         let kafka_config = KafkaConfig::from_hashmap(properties)?;
-        let mut sink = KafkaSink::new(kafka_config.clone()).unwrap();
+        let mut sink = KafkaSink::new(kafka_config.clone(), vec![]).unwrap();
 
         for i in 0..10 {
             let mut fail_flag = false;
@@ -583,6 +679,95 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_config_from_hashmap() {
+        let properties = hashmap! {
+            "kafka.brokers".to_string() => "localhost:29092".to_string(),
+            "kafka.topic".to_string() => "test_topic".to_string(),
+            "format".to_string() => "upsert".to_string(),
+            "encode".to_string() => "avro".to_string(),
+        };
+        let config = KafkaConfig::from_hashmap(properties.clone()).unwrap();
+        assert_eq!(config.format, SINK_FORMAT_UPSERT);
+        assert_eq!(config.encode, SinkEncode::Avro);
+
+        let mut missing_topic = properties.clone();
+        missing_topic.remove("kafka.topic");
+        assert!(KafkaConfig::from_hashmap(missing_topic).is_err());
+
+        let mut debezium_avro = properties.clone();
+        debezium_avro.insert("format".to_string(), "debezium".to_string());
+        assert!(KafkaConfig::from_hashmap(debezium_avro).is_err());
+
+        let mut registry = properties;
+        registry.insert(
+            "schema.registry".to_string(),
+            "http://localhost:8081".to_string(),
+        );
+        let config = KafkaConfig::from_hashmap(registry.clone()).unwrap();
+        assert_eq!(
+            config.schema_registry.as_deref(),
+            Some("http://localhost:8081")
+        );
+        assert_eq!(config.schema_registry_auth, None);
+        registry.insert("schema.registry.username".to_string(), "user".to_string());
+        // The password is missing.
+        assert!(KafkaConfig::from_hashmap(registry.clone()).is_err());
+        registry.insert("schema.registry.password".to_string(), "pass".to_string());
+        let config = KafkaConfig::from_hashmap(registry.clone()).unwrap();
+        assert_eq!(
+            config.schema_registry_auth,
+            Some(SchemaRegistryAuth {
+                username: "user".to_string(),
+                password: "pass".to_string(),
+            })
+        );
+        // The JSON messages have no schema to register.
+        registry.insert("encode".to_string(), "json".to_string());
+        assert!(KafkaConfig::from_hashmap(registry).is_err());
+    }
+
+    #[test]
+    fn test_upsert_records() {
+        let schema = Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+        ]);
+        let key = RowSerializerImpl::new(SinkEncode::Json, &schema, &[0], "Key").unwrap();
+        let value = RowSerializerImpl::new(SinkEncode::Json, &schema, &[0, 1], "Value").unwrap();
+        let chunk = StreamChunk::from_pretty(
+            " i T
+            +  1 a
+            -  2 b
+            U- 3 c
+            U+ 3 d
+            U- 4 e
+            U+ 5 e",
+        );
+
+        let records = upsert_records(&chunk, &key, &value)
+            .unwrap()
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    String::from_utf8(key).unwrap(),
+                    value.map(|value| String::from_utf8(value).unwrap()),
+                )
+            })
+            .collect_vec();
+        let record = |key: &str, value: Option<&str>| (key.to_string(), value.map(str::to_string));
+        assert_eq!(
+            records,
+            vec![
+                record(r#"{"id":1}"#, Some(r#"{"id":1,"name":"a"}"#)),
+                record(r#"{"id":2}"#, None),
+                record(r#"{"id":3}"#, Some(r#"{"id":3,"name":"d"}"#)),
+                record(r#"{"id":4}"#, None),
+                record(r#"{"id":5}"#, Some(r#"{"id":5,"name":"e"}"#)),
+            ]
+        );
+    }
+
     #[test]
     fn test_chunk_to_json() -> Result<()> {
         let mut column_i32_builder = I32ArrayBuilder::new(10);
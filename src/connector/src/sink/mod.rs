@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod file;
 pub mod kafka;
 pub mod mysql;
+pub mod postgres;
 pub mod redis;
+pub mod serializer;
 
 use std::collections::HashMap;
 
@@ -27,9 +30,19 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 pub use tracing;
 
+use crate::sink::file::{FileConfig, FileSink, FILE_SINK};
 use crate::sink::kafka::{KafkaConfig, KafkaSink, KAFKA_SINK};
-use crate::sink::mysql::{MySQLConfig, MySQLSink};
-use crate::sink::redis::{RedisConfig, RedisSink};
+use crate::sink::mysql::{MySQLConfig, MySQLSink, MYSQL_SINK};
+use crate::sink::postgres::{PostgresConfig, PostgresSink, POSTGRES_SINK};
+use crate::sink::redis::{RedisConfig, RedisSink, REDIS_SINK};
+
+/// The option of how the changes are written to the sink.
+pub const SINK_FORMAT_OPTION: &str = "format";
+/// Only the inserts are written, which requires the sink input to be append-only.
+pub const SINK_FORMAT_APPEND_ONLY: &str = "append_only";
+pub const SINK_FORMAT_DEBEZIUM: &str = "debezium";
+/// The rows are written by their primary key, and the deletes are written as tombstones.
+pub const SINK_FORMAT_UPSERT: &str = "upsert";
 
 #[async_trait]
 pub trait Sink {
@@ -45,20 +58,58 @@ pub trait Sink {
     // aborts the current transaction because some error happens. we should rollback to the last
     // commit point.
     async fn abort(&mut self) -> Result<()>;
+
+    /// Whether the sink supports the two-phase commit with [`Sink::pre_commit`] and
+    /// [`Sink::commit_prepared`]. If so, the sink executor commits the transaction of an epoch only
+    /// after the epoch is checkpointed, which makes the delivery exactly-once.
+    fn support_two_phase_commit(&self) -> bool {
+        false
+    }
+
+    /// Prepares the current transaction instead of committing it. The prepared transaction must
+    /// survive the failures of the sink, until it's committed by [`Sink::commit_prepared`] or
+    /// aborted by [`Sink::abort_prepared`].
+    ///
+    /// Returns the metadata needed to commit the transaction, which is persisted in the state
+    /// store along with the checkpoint of the epoch.
+    async fn pre_commit(&mut self) -> Result<Vec<u8>> {
+        Err(SinkError::Config(
+            "two-phase commit is not supported by the sink".to_string(),
+        ))
+    }
+
+    /// Commits the transaction prepared in `epoch`. After recovery, it can be called again for a
+    /// transaction which has been committed, in which case it should do nothing.
+    async fn commit_prepared(&mut self, _epoch: u64, _metadata: &[u8]) -> Result<()> {
+        Err(SinkError::Config(
+            "two-phase commit is not supported by the sink".to_string(),
+        ))
+    }
+
+    /// Aborts all the prepared transactions of the sink which are not committed. It's called on
+    /// recovery after the transactions of the checkpointed epochs are committed, as the data of
+    /// the others will be written again.
+    async fn abort_prepared(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, EnumAsInner)]
 pub enum SinkConfig {
     Mysql(MySQLConfig),
+    Postgres(PostgresConfig),
     Redis(RedisConfig),
-    Kafka(KafkaConfig),
+    Kafka(Box<KafkaConfig>),
+    File(FileConfig),
 }
 
 #[derive(Clone, Debug, EnumAsInner, Serialize, Deserialize)]
 pub enum SinkState {
     Kafka,
     Mysql,
+    Postgres,
     Redis,
+    File,
 }
 
 impl SinkConfig {
@@ -71,16 +122,38 @@ impl SinkConfig {
             })
         })?;
         match sink_type.to_lowercase().as_str() {
-            KAFKA_SINK => Ok(SinkConfig::Kafka(KafkaConfig::from_hashmap(properties)?)),
-            _ => unimplemented!(),
+            KAFKA_SINK => Ok(SinkConfig::Kafka(Box::new(KafkaConfig::from_hashmap(
+                properties,
+            )?))),
+            MYSQL_SINK => Ok(SinkConfig::Mysql(MySQLConfig::from_hashmap(properties)?)),
+            POSTGRES_SINK => Ok(SinkConfig::Postgres(PostgresConfig::from_hashmap(
+                properties,
+            )?)),
+            REDIS_SINK => Ok(SinkConfig::Redis(RedisConfig::from_hashmap(properties)?)),
+            FILE_SINK => Ok(SinkConfig::File(FileConfig::from_hashmap(properties)?)),
+            _ => Err(RwError::from(ErrorCode::InvalidConfigValue {
+                config_entry: SINK_TYPE_KEY.to_string(),
+                config_value: sink_type.to_string(),
+            })),
         }
     }
 
     pub fn get_connector(&self) -> &'static str {
         match self {
-            SinkConfig::Mysql(_) => "mysql",
-            SinkConfig::Kafka(_) => "kafka",
-            SinkConfig::Redis(_) => "redis",
+            SinkConfig::Mysql(_) => MYSQL_SINK,
+            SinkConfig::Postgres(_) => POSTGRES_SINK,
+            SinkConfig::Kafka(_) => KAFKA_SINK,
+            SinkConfig::Redis(_) => REDIS_SINK,
+            SinkConfig::File(_) => FILE_SINK,
+        }
+    }
+
+    /// Whether the sinks of the connector support the two-phase commit, i.e. deliver the rows
+    /// exactly once. It must agree with [`Sink::support_two_phase_commit`] of the sink.
+    pub fn support_two_phase_commit(&self) -> bool {
+        match self {
+            SinkConfig::Postgres(_) | SinkConfig::File(_) => true,
+            SinkConfig::Mysql(_) | SinkConfig::Kafka(_) | SinkConfig::Redis(_) => false,
         }
     }
 }
@@ -88,24 +161,62 @@ impl SinkConfig {
 #[derive(Debug)]
 pub enum SinkImpl {
     MySQL(Box<MySQLSink>),
+    Postgres(Box<PostgresSink>),
     Redis(Box<RedisSink>),
     Kafka(Box<KafkaSink>),
+    File(Box<FileSink>),
 }
 
 impl SinkImpl {
-    pub async fn new(cfg: SinkConfig) -> RwResult<Self> {
+    /// `pk_indices` are the primary key columns of the rows written to the sink, and
+    /// `hidden_column_indices` the columns hidden from the users, which the sinks writing to tables
+    /// skip.
+    pub async fn new(
+        cfg: SinkConfig,
+        pk_indices: Vec<usize>,
+        hidden_column_indices: Vec<usize>,
+    ) -> RwResult<Self> {
         Ok(match cfg {
-            SinkConfig::Mysql(cfg) => {
-                SinkImpl::MySQL(Box::new(MySQLSink::new(cfg).await.map_err(RwError::from)?))
-            }
-            SinkConfig::Redis(cfg) => {
-                SinkImpl::Redis(Box::new(RedisSink::new(cfg).map_err(RwError::from)?))
-            }
-            SinkConfig::Kafka(cfg) => {
-                SinkImpl::Kafka(Box::new(KafkaSink::new(cfg).map_err(RwError::from)?))
+            SinkConfig::Mysql(cfg) => SinkImpl::MySQL(Box::new(
+                MySQLSink::new(cfg, pk_indices)
+                    .await
+                    .map_err(RwError::from)?,
+            )),
+            SinkConfig::Postgres(cfg) => SinkImpl::Postgres(Box::new(
+                PostgresSink::new(cfg, pk_indices, hidden_column_indices)
+                    .await
+                    .map_err(RwError::from)?,
+            )),
+            SinkConfig::Redis(cfg) => SinkImpl::Redis(Box::new(
+                RedisSink::new(cfg, pk_indices)
+                    .await
+                    .map_err(RwError::from)?,
+            )),
+            SinkConfig::Kafka(cfg) => SinkImpl::Kafka(Box::new(
+                KafkaSink::new(*cfg, pk_indices).map_err(RwError::from)?,
+            )),
+            SinkConfig::File(cfg) => {
+                SinkImpl::File(Box::new(FileSink::new(cfg).await.map_err(RwError::from)?))
             }
         })
     }
+
+    /// Checks that the sink of `schema` can be written to the external system, e.g. the target
+    /// table exists and has compatible columns. It's called on `CREATE SINK`.
+    pub async fn validate(
+        cfg: &SinkConfig,
+        schema: &Schema,
+        hidden_column_indices: &[usize],
+    ) -> RwResult<()> {
+        match cfg {
+            SinkConfig::Postgres(cfg) => PostgresSink::validate(cfg, schema, hidden_column_indices)
+                .await
+                .map_err(RwError::from),
+            SinkConfig::Mysql(_) => MySQLSink::validate(schema).map_err(RwError::from),
+            SinkConfig::File(cfg) => FileSink::validate(cfg, schema).map_err(RwError::from),
+            SinkConfig::Redis(_) | SinkConfig::Kafka(_) => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
@@ -113,32 +224,80 @@ impl Sink for SinkImpl {
     async fn write_batch(&mut self, chunk: StreamChunk, schema: &Schema) -> Result<()> {
         match self {
             SinkImpl::MySQL(sink) => sink.write_batch(chunk, schema).await,
+            SinkImpl::Postgres(sink) => sink.write_batch(chunk, schema).await,
             SinkImpl::Redis(sink) => sink.write_batch(chunk, schema).await,
             SinkImpl::Kafka(sink) => sink.write_batch(chunk, schema).await,
+            SinkImpl::File(sink) => sink.write_batch(chunk, schema).await,
         }
     }
 
     async fn begin_epoch(&mut self, epoch: u64) -> Result<()> {
         match self {
             SinkImpl::MySQL(sink) => sink.begin_epoch(epoch).await,
+            SinkImpl::Postgres(sink) => sink.begin_epoch(epoch).await,
             SinkImpl::Redis(sink) => sink.begin_epoch(epoch).await,
             SinkImpl::Kafka(sink) => sink.begin_epoch(epoch).await,
+            SinkImpl::File(sink) => sink.begin_epoch(epoch).await,
         }
     }
 
     async fn commit(&mut self) -> Result<()> {
         match self {
             SinkImpl::MySQL(sink) => sink.commit().await,
+            SinkImpl::Postgres(sink) => sink.commit().await,
             SinkImpl::Redis(sink) => sink.commit().await,
             SinkImpl::Kafka(sink) => sink.commit().await,
+            SinkImpl::File(sink) => sink.commit().await,
         }
     }
 
     async fn abort(&mut self) -> Result<()> {
         match self {
             SinkImpl::MySQL(sink) => sink.abort().await,
+            SinkImpl::Postgres(sink) => sink.abort().await,
             SinkImpl::Redis(sink) => sink.abort().await,
             SinkImpl::Kafka(sink) => sink.abort().await,
+            SinkImpl::File(sink) => sink.abort().await,
+        }
+    }
+
+    fn support_two_phase_commit(&self) -> bool {
+        match self {
+            SinkImpl::MySQL(sink) => sink.support_two_phase_commit(),
+            SinkImpl::Postgres(sink) => sink.support_two_phase_commit(),
+            SinkImpl::Redis(sink) => sink.support_two_phase_commit(),
+            SinkImpl::Kafka(sink) => sink.support_two_phase_commit(),
+            SinkImpl::File(sink) => sink.support_two_phase_commit(),
+        }
+    }
+
+    async fn pre_commit(&mut self) -> Result<Vec<u8>> {
+        match self {
+            SinkImpl::MySQL(sink) => sink.pre_commit().await,
+            SinkImpl::Postgres(sink) => sink.pre_commit().await,
+            SinkImpl::Redis(sink) => sink.pre_commit().await,
+            SinkImpl::Kafka(sink) => sink.pre_commit().await,
+            SinkImpl::File(sink) => sink.pre_commit().await,
+        }
+    }
+
+    async fn commit_prepared(&mut self, epoch: u64, metadata: &[u8]) -> Result<()> {
+        match self {
+            SinkImpl::MySQL(sink) => sink.commit_prepared(epoch, metadata).await,
+            SinkImpl::Postgres(sink) => sink.commit_prepared(epoch, metadata).await,
+            SinkImpl::Redis(sink) => sink.commit_prepared(epoch, metadata).await,
+            SinkImpl::Kafka(sink) => sink.commit_prepared(epoch, metadata).await,
+            SinkImpl::File(sink) => sink.commit_prepared(epoch, metadata).await,
+        }
+    }
+
+    async fn abort_prepared(&mut self) -> Result<()> {
+        match self {
+            SinkImpl::MySQL(sink) => sink.abort_prepared().await,
+            SinkImpl::Postgres(sink) => sink.abort_prepared().await,
+            SinkImpl::Redis(sink) => sink.abort_prepared().await,
+            SinkImpl::Kafka(sink) => sink.abort_prepared().await,
+            SinkImpl::File(sink) => sink.abort_prepared().await,
         }
     }
 }
@@ -151,10 +310,22 @@ pub enum SinkError {
     MySQL(String),
     #[error("MySQL inner error: {0}")]
     MySQLInner(#[from] mysql_async::Error),
+    #[error("Postgres error: {0}")]
+    Postgres(String),
+    #[error("Postgres inner error: {0}")]
+    PostgresInner(#[from] tokio_postgres::Error),
     #[error("Kafka error: {0}")]
     Kafka(#[from] rdkafka::error::KafkaError),
     #[error("Json parse error: {0}")]
     JsonParse(String),
+    #[error("Encode error: {0}")]
+    Encode(String),
+    #[error("Redis error: {0}")]
+    Redis(#[from] ::redis::RedisError),
+    #[error("File error: {0}")]
+    File(String),
+    #[error("Schema registry error: {0}")]
+    SchemaRegistry(String),
     #[error("config error: {0}")]
     Config(String),
 }
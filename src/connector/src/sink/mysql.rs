@@ -12,19 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::fmt;
+use std::collections::HashMap;
 
 use async_trait::async_trait;
-use itertools::{join, Itertools};
+use itertools::Itertools;
 use mysql_async::prelude::*;
 use mysql_async::*;
 use risingwave_common::array::Op::*;
-use risingwave_common::array::StreamChunk;
+use risingwave_common::array::{RowRef, StreamChunk};
 use risingwave_common::catalog::Schema;
-use risingwave_common::types::{Datum, Decimal, ScalarImpl};
+use risingwave_common::types::{DataType, Datum, Decimal, ScalarImpl, ToOwnedDatum};
 
 use crate::sink::{Result, Sink, SinkError};
 
+pub const MYSQL_SINK: &str = "mysql";
+
 #[derive(Clone, Debug)]
 pub struct MySQLConfig {
     pub endpoint: String,
@@ -34,40 +36,104 @@ pub struct MySQLConfig {
     pub password: Option<String>,
 }
 
-// Primitive design of MySQLSink
+impl MySQLConfig {
+    pub fn from_hashmap(values: HashMap<String, String>) -> Result<Self> {
+        let endpoint = values
+            .get("mysql.endpoint")
+            .ok_or_else(|| SinkError::Config("mysql.endpoint must be set".to_string()))?;
+        let table = values
+            .get("mysql.table")
+            .ok_or_else(|| SinkError::Config("mysql.table must be set".to_string()))?;
+        let config = MySQLConfig {
+            endpoint: endpoint.to_string(),
+            table: table.to_string(),
+            database: values.get("mysql.database").cloned(),
+            user: values.get("mysql.user").cloned(),
+            password: values.get("mysql.password").cloned(),
+        };
+        config.host_and_port()?;
+
+        Ok(config)
+    }
+
+    /// Splits the endpoint in the form of `host[:port]`.
+    fn host_and_port(&self) -> Result<(&str, Option<u16>)> {
+        match self.endpoint.split_once(':') {
+            None => Ok((&self.endpoint, None)),
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| {
+                    SinkError::Config(format!("invalid port in mysql.endpoint: {}", port))
+                })?;
+                Ok((host, Some(port)))
+            }
+        }
+    }
+}
+
+/// `MySQLSink` upserts the inserted rows with `INSERT ... ON DUPLICATE KEY UPDATE`, and deletes
+/// the deleted rows by their primary key. The target table is expected to have the same primary
+/// key as the sink, otherwise the upserts become plain inserts.
+///
+/// The chunks of an epoch are buffered, and written in one `MySQL` transaction on commit. Since
+/// both statements are idempotent, replaying an epoch after recovery leaves the table unchanged.
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct MySQLSink {
     cfg: MySQLConfig,
+    pk_indices: Vec<usize>,
 
     conn: Conn,
     chunk_cache: Vec<(StreamChunk, Schema)>,
 }
 
 impl MySQLSink {
-    pub async fn new(cfg: MySQLConfig) -> Result<Self> {
-        // Build a connection and start transaction
-        let endpoint = cfg.endpoint.clone();
-        let mut endpoint = endpoint.split(':');
+    pub async fn new(cfg: MySQLConfig, pk_indices: Vec<usize>) -> Result<Self> {
+        let (host, port) = cfg.host_and_port()?;
         let mut builder = OptsBuilder::default()
             .user(cfg.user.clone())
             .pass(cfg.password.clone())
-            .ip_or_hostname(endpoint.next().unwrap())
+            .ip_or_hostname(host)
             .db_name(cfg.database.clone());
-        // TODO(nanderstabel): Fix ParseIntError
-        if let Some(port) = endpoint.next() {
-            builder = builder.tcp_port(port.parse().unwrap());
+        if let Some(port) = port {
+            builder = builder.tcp_port(port);
         }
 
         let conn = Conn::new(builder).await?;
 
         Ok(Self {
             cfg,
+            pk_indices,
             conn,
             chunk_cache: vec![],
         })
     }
 
+    /// Checks that all the columns have types which can be written to `MySQL`.
+    pub fn validate(schema: &Schema) -> Result<()> {
+        for field in &schema.fields {
+            match field.data_type {
+                DataType::Boolean
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Decimal
+                | DataType::Date
+                | DataType::Varchar
+                | DataType::Time
+                | DataType::Timestamp => {}
+                ref data_type => {
+                    return Err(SinkError::MySQL(format!(
+                        "column \"{}\" of type {:?} is not supported by the mysql sink",
+                        field.name, data_type
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn endpoint(&self) -> String {
         self.cfg.endpoint.clone()
     }
@@ -89,15 +155,9 @@ impl MySQLSink {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct MySQLValue(Value);
 
-impl fmt::Display for MySQLValue {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0.as_sql(true))
-    }
-}
-
 impl TryFrom<Datum> for MySQLValue {
     type Error = SinkError;
 
@@ -107,17 +167,25 @@ impl TryFrom<Datum> for MySQLValue {
                 ScalarImpl::Int16(v) => Ok(MySQLValue(v.into())),
                 ScalarImpl::Int32(v) => Ok(MySQLValue(v.into())),
                 ScalarImpl::Int64(v) => Ok(MySQLValue(v.into())),
-                ScalarImpl::Float32(v) => Ok(MySQLValue(f32::from(v).into())),
-                ScalarImpl::Float64(v) => Ok(MySQLValue(f64::from(v).into())),
-                ScalarImpl::Bool(v) => Ok(MySQLValue(v.into())),
+                ScalarImpl::Float32(v) if f32::from(v).is_finite() => {
+                    Ok(MySQLValue(f32::from(v).into()))
+                }
+                ScalarImpl::Float64(v) if f64::from(v).is_finite() => {
+                    Ok(MySQLValue(f64::from(v).into()))
+                }
                 ScalarImpl::Decimal(Decimal::Normalized(v)) => Ok(MySQLValue(v.into())),
-                ScalarImpl::Decimal(_) => panic!("NaN, -inf, +inf are not supported by MySQL"),
+                ScalarImpl::Float32(_) | ScalarImpl::Float64(_) | ScalarImpl::Decimal(_) => Err(
+                    SinkError::MySQL("NaN, -inf, +inf are not supported by MySQL".to_string()),
+                ),
+                ScalarImpl::Bool(v) => Ok(MySQLValue(v.into())),
                 ScalarImpl::Utf8(v) => Ok(MySQLValue(v.into())),
                 ScalarImpl::NaiveDate(v) => Ok(MySQLValue(format!("{}", v).into())),
                 ScalarImpl::NaiveTime(v) => Ok(MySQLValue(format!("{}", v).into())),
                 ScalarImpl::NaiveDateTime(v) => Ok(MySQLValue(format!("{}", v).into())),
-                // ScalarImpl::Interval(v) => Ok(MySQLValue(Value::NULL)),
-                _ => unimplemented!(),
+                v => Err(SinkError::MySQL(format!(
+                    "{:?} is not supported by MySQL",
+                    v
+                ))),
             }
         } else {
             Ok(MySQLValue(Value::NULL))
@@ -133,13 +201,18 @@ impl Sink for MySQLSink {
     }
 
     async fn begin_epoch(&mut self, _epoch: u64) -> Result<()> {
+        self.chunk_cache.clear();
         Ok(())
     }
 
     async fn commit(&mut self) -> Result<()> {
         let mut txn = self.conn.start_transaction(TxOpts::default()).await?;
         for (chunk, schema) in &self.chunk_cache {
-            write_to_mysql(&mut txn, chunk, schema, &self.cfg).await?;
+            let statements = Statements::new(schema, &self.pk_indices, &self.cfg.table);
+            // The statements are prepared once and cached by the connection.
+            for (stmt, params) in statements.bind(chunk)? {
+                txn.exec_drop(stmt, Params::Positional(params)).await?;
+            }
         }
         txn.commit().await?;
 
@@ -148,82 +221,99 @@ impl Sink for MySQLSink {
     }
 
     async fn abort(&mut self) -> Result<()> {
+        // Nothing has been sent to MySQL before commit.
+        self.chunk_cache.clear();
         Ok(())
     }
 }
 
-async fn write_to_mysql<'a>(
-    txn: &mut Transaction<'a>,
-    chunk: &StreamChunk,
-    schema: &Schema,
-    config: &MySQLConfig,
-) -> Result<()> {
-    // Closure that takes an idx to create a vector of MySQLValues from a StreamChunk 'row'.
-    let values = |idx| -> Result<Vec<MySQLValue>> {
-        chunk
-            .columns()
-            .iter()
-            .map(|x| MySQLValue::try_from(x.array_ref().datum_at(idx)))
-            .collect_vec()
-            .into_iter()
-            .collect()
-    };
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+fn row_values(row: &RowRef, indices: &[usize]) -> Result<Vec<Value>> {
+    indices
+        .iter()
+        .map(|&i| MySQLValue::try_from(row.value_at(i).to_owned_datum()).map(|v| v.0))
+        .collect()
+}
 
-    // Closure that builds a String containing WHERE conditions, i.e. 'v1=1 AND v2=2'.
-    // Perhaps better to replace this functionality with a new Sink trait method.
-    let conditions = |values: Vec<MySQLValue>| {
-        schema
+/// The statements applying the changes of a sink to `table`, whose values are bound to the `?`
+/// placeholders rather than quoted into the SQL. Rows are identified by the `pk_indices` columns,
+/// or by all the columns if the sink has no primary key.
+struct Statements {
+    upsert: String,
+    delete: String,
+    all_indices: Vec<usize>,
+    key_indices: Vec<usize>,
+}
+
+impl Statements {
+    fn new(schema: &Schema, pk_indices: &[usize], table: &str) -> Self {
+        let table = quote_identifier(table);
+        let columns = schema
             .names()
             .iter()
-            .zip_eq(values.iter())
-            .map(|(c, v)| format!("{}={}", c, v))
-            .collect::<Vec<String>>()
-    };
-
-    let mut iter = chunk.ops().iter().enumerate();
-    while let Some((idx, op)) = iter.next() {
-        // Get SQL statement
-        let stmt = match *op {
-            Insert => format!(
-                "INSERT INTO {} VALUES ({});",
-                &config.table,
-                join(values(idx)?, ",")
-            ),
-            Delete => format!(
-                "DELETE FROM {} WHERE ({});",
-                &config.table,
-                join(conditions(values(idx)?), " AND ")
-            ),
-            UpdateDelete => {
-                if let Some((idx2, UpdateInsert)) = iter.next() {
-                    format!(
-                        "UPDATE {} SET {} WHERE {};",
-                        &config.table,
-                        join(conditions(values(idx2)?), ","),
-                        join(conditions(values(idx)?), " AND ")
-                    )
-                } else {
-                    return Err(SinkError::MySQL(
-                        "UpdateDelete should always be followed by an UpdateInsert!".into(),
-                    ));
-                }
-            }
-            _ => return Err(SinkError::MySQL("Unsupported operation".into())),
+            .map(|name| quote_identifier(name))
+            .collect_vec();
+        let all_indices = (0..schema.len()).collect_vec();
+        let key_indices = if pk_indices.is_empty() {
+            all_indices.clone()
+        } else {
+            pk_indices.to_vec()
         };
-        // TODO by doc, exec_drop will simply exec query and drop the result, we may check and retry
-        // for jitter or other reasons
-        txn.exec_drop(stmt, Params::Empty).await?;
+
+        let upsert = format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON DUPLICATE KEY UPDATE {}",
+            table,
+            columns.join(","),
+            vec!["?"; columns.len()].join(","),
+            columns
+                .iter()
+                .map(|c| format!("{}=VALUES({})", c, c))
+                .join(",")
+        );
+        // `<=>` is the null-safe equality, so that the rows with `NULL` keys are matched as well.
+        let delete = format!(
+            "DELETE FROM {} WHERE {}",
+            table,
+            key_indices
+                .iter()
+                .map(|&i| format!("{} <=> ?", columns[i]))
+                .join(" AND ")
+        );
+        Self {
+            upsert,
+            delete,
+            all_indices,
+            key_indices,
+        }
     }
 
-    Ok(())
+    /// Returns the statement and the values to bind of each change of `chunk`.
+    fn bind(&self, chunk: &StreamChunk) -> Result<Vec<(&str, Vec<Value>)>> {
+        chunk
+            .rows()
+            .map(|(op, row)| match op {
+                Insert | UpdateInsert => {
+                    Ok((self.upsert.as_str(), row_values(&row, &self.all_indices)?))
+                }
+                Delete | UpdateDelete => {
+                    Ok((self.delete.as_str(), row_values(&row, &self.key_indices)?))
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
 
+    use maplit::hashmap;
     use risingwave_common::array;
     use risingwave_common::array::column::Column;
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
     use risingwave_common::array::{ArrayImpl, I32Array, Op, Utf8Array};
     use risingwave_common::catalog::Field;
     use risingwave_common::types::chrono_wrapper::*;
@@ -240,55 +330,131 @@ mod test {
         pub password: &'a str,
     }
 
+    fn mysql_value(scalar: ScalarImpl) -> Value {
+        MySQLValue::try_from(Some(scalar)).unwrap().0
+    }
+
     #[test]
     fn test_date() {
         assert_eq!(
-            MySQLValue::try_from(Some(ScalarImpl::NaiveDate(NaiveDateWrapper::default())))
-                .unwrap()
-                .to_string(),
-            "'1970-01-01'"
+            mysql_value(ScalarImpl::NaiveDate(NaiveDateWrapper::default())),
+            Value::from("1970-01-01")
         );
     }
 
     #[test]
     fn test_time() {
         assert_eq!(
-            MySQLValue::try_from(Some(ScalarImpl::NaiveTime(NaiveTimeWrapper::default())))
-                .unwrap()
-                .to_string(),
-            "'00:00:00'"
+            mysql_value(ScalarImpl::NaiveTime(NaiveTimeWrapper::default())),
+            Value::from("00:00:00")
         );
     }
 
     #[test]
     fn test_datetime() {
         assert_eq!(
-            MySQLValue::try_from(Some(ScalarImpl::NaiveDateTime(
-                NaiveDateTimeWrapper::default()
-            )))
-            .unwrap()
-            .to_string(),
-            "'1970-01-01 00:00:00'"
+            mysql_value(ScalarImpl::NaiveDateTime(NaiveDateTimeWrapper::default())),
+            Value::from("1970-01-01 00:00:00")
         );
     }
 
     #[test]
     fn test_decimal() {
         assert_eq!(
-            MySQLValue::try_from(Some(ScalarImpl::Decimal(Decimal::Normalized(
-                RustDecimal::new(0, 0)
-            ))))
-            .unwrap()
-            .to_string(),
-            "'0'"
+            mysql_value(ScalarImpl::Decimal(Decimal::Normalized(RustDecimal::new(
+                0, 0
+            )))),
+            Value::from("0")
         );
         assert_eq!(
-            MySQLValue::try_from(Some(ScalarImpl::Decimal(Decimal::Normalized(
-                RustDecimal::new(124, 5)
-            ))))
-            .unwrap()
-            .to_string(),
-            "'0.00124'"
+            mysql_value(ScalarImpl::Decimal(Decimal::Normalized(RustDecimal::new(
+                124, 5
+            )))),
+            Value::from("0.00124")
+        );
+    }
+
+    #[test]
+    fn test_unsupported_values() {
+        assert!(MySQLValue::try_from(Some(ScalarImpl::Float64(f64::NAN.into()))).is_err());
+        assert!(MySQLValue::try_from(Some(ScalarImpl::Float32(f32::INFINITY.into()))).is_err());
+        assert!(MySQLValue::try_from(Some(ScalarImpl::Decimal(Decimal::NaN))).is_err());
+        assert!(MySQLValue::try_from(Some(ScalarImpl::Interval(Default::default()))).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(MySQLSink::validate(&Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Timestamp, "ts"),
+        ]))
+        .is_ok());
+        assert!(MySQLSink::validate(&Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Interval, "i"),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_config_from_hashmap() {
+        let config = MySQLConfig::from_hashmap(hashmap! {
+            "mysql.endpoint".to_string() => "127.0.0.1:3306".to_string(),
+            "mysql.table".to_string() => "t".to_string(),
+            "mysql.user".to_string() => "root".to_string(),
+        })
+        .unwrap();
+        assert_eq!(config.host_and_port().unwrap(), ("127.0.0.1", Some(3306)));
+        assert_eq!(config.database, None);
+        assert_eq!(config.user.as_deref(), Some("root"));
+
+        assert!(MySQLConfig::from_hashmap(hashmap! {
+            "mysql.endpoint".to_string() => "127.0.0.1:port".to_string(),
+            "mysql.table".to_string() => "t".to_string(),
+        })
+        .is_err());
+        assert!(MySQLConfig::from_hashmap(hashmap! {
+            "mysql.endpoint".to_string() => "127.0.0.1".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_statements() {
+        let schema = Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+        ]);
+        let chunk = StreamChunk::from_pretty(
+            " i  T
+            + 1  a\\'
+            - 2  b
+            U- 3  c
+            U+ 3  .",
+        );
+
+        let upsert = "INSERT INTO `t` (`id`,`name`) VALUES (?,?) \
+                      ON DUPLICATE KEY UPDATE `id`=VALUES(`id`),`name`=VALUES(`name`)";
+        let delete = "DELETE FROM `t` WHERE `id` <=> ?";
+        let statements = Statements::new(&schema, &[0], "t");
+        assert_eq!(
+            statements.bind(&chunk).unwrap(),
+            vec![
+                (upsert, vec![Value::from(1), Value::from("a\\'")]),
+                (delete, vec![Value::from(2)]),
+                (delete, vec![Value::from(3)]),
+                (upsert, vec![Value::from(3), Value::NULL]),
+            ]
+        );
+
+        // Without a primary key, the rows are deleted by all the columns.
+        let statements = Statements::new(&schema, &[], "t");
+        assert_eq!(
+            statements.bind(&chunk).unwrap()[1],
+            (
+                "DELETE FROM `t` WHERE `id` <=> ? AND `name` <=> ?",
+                vec![Value::from(2), Value::from("b")]
+            )
         );
     }
 
@@ -302,7 +468,7 @@ mod test {
             user: Some("root".into()),
             password: None,
         };
-        let mut sink = MySQLSink::new(config.clone()).await?;
+        let mut sink = MySQLSink::new(config.clone(), vec![0]).await?;
 
         let schema = Schema::new(vec![
             Field {
@@ -0,0 +1,657 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use async_trait::async_trait;
+use bytes::{BufMut, BytesMut};
+use itertools::Itertools;
+use risingwave_common::array::{Op, RowRef, StreamChunk};
+use risingwave_common::catalog::Schema;
+use risingwave_common::types::{DataType, DatumRef, ScalarRefImpl};
+use tokio_postgres::types::{to_sql_checked, IsNull, ToSql, Type};
+use tokio_postgres::{Client, NoTls, Statement};
+
+use crate::sink::{Result, Sink, SinkError};
+
+pub const POSTGRES_SINK: &str = "postgres";
+
+const DEFAULT_SCHEMA: &str = "public";
+
+/// The microseconds from the Unix epoch to the Postgres epoch, 2000-01-01.
+const POSTGRES_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+#[derive(Clone, Debug)]
+pub struct PostgresConfig {
+    /// `host[:port]`
+    pub endpoint: String,
+    pub database: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// The schema of the target table, `public` by default.
+    pub schema: String,
+    pub table: String,
+    /// Prefixes the names of the prepared transactions of the sink.
+    pub identifier: String,
+}
+
+impl PostgresConfig {
+    pub fn from_hashmap(values: HashMap<String, String>) -> Result<Self> {
+        let endpoint = values
+            .get("postgres.endpoint")
+            .ok_or_else(|| SinkError::Config("postgres.endpoint must be set".to_string()))?;
+        let table = values
+            .get("postgres.table")
+            .ok_or_else(|| SinkError::Config("postgres.table must be set".to_string()))?;
+        let config = PostgresConfig {
+            endpoint: endpoint.to_string(),
+            database: values.get("postgres.database").cloned(),
+            user: values.get("postgres.user").cloned(),
+            password: values.get("postgres.password").cloned(),
+            schema: values
+                .get("postgres.schema")
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_SCHEMA.to_string()),
+            table: table.to_string(),
+            identifier: values.get("identifier").cloned().unwrap_or_default(),
+        };
+        config.connect_config()?;
+
+        Ok(config)
+    }
+
+    fn connect_config(&self) -> Result<tokio_postgres::Config> {
+        let mut config = tokio_postgres::Config::new();
+        match self.endpoint.split_once(':') {
+            None => config.host(&self.endpoint),
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| {
+                    SinkError::Config(format!("invalid port in postgres.endpoint: {}", port))
+                })?;
+                config.host(host).port(port)
+            }
+        };
+        if let Some(database) = &self.database {
+            config.dbname(database);
+        }
+        if let Some(user) = &self.user {
+            config.user(user);
+        }
+        if let Some(password) = &self.password {
+            config.password(password);
+        }
+        Ok(config)
+    }
+
+    async fn connect(&self) -> Result<Client> {
+        let (client, connection) = self.connect_config()?.connect(NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::error!("postgres sink connection error: {}", e);
+            }
+        });
+        Ok(client)
+    }
+
+    fn qualified_table(&self) -> String {
+        format!(
+            "{}.{}",
+            quote_identifier(&self.schema),
+            quote_identifier(&self.table)
+        )
+    }
+}
+
+/// `PostgresSink` upserts the inserted rows with `INSERT ... ON CONFLICT DO UPDATE`, and deletes
+/// the deleted rows by their primary key. The primary key of the sink must be a primary key or a
+/// unique constraint of the target table. The hidden columns, e.g. the row id, are not written,
+/// and a sink whose primary key has a hidden column is written as if it had no primary key.
+///
+/// Each epoch is written in a Postgres transaction, which is started on the first chunk of the
+/// epoch and committed or rolled back at the barrier. With the two-phase commit, the transaction
+/// is prepared by `PREPARE TRANSACTION` at the barrier, and committed after the epoch is
+/// checkpointed, which requires `max_prepared_transactions` to be set on the Postgres server.
+pub struct PostgresSink {
+    cfg: PostgresConfig,
+    pk_indices: Vec<usize>,
+    hidden_column_indices: Vec<usize>,
+    client: Client,
+    /// The statements prepared on the first chunk.
+    prepared: Option<PreparedStatements>,
+    in_transaction: bool,
+    epoch: u64,
+}
+
+struct PreparedStatements {
+    statements: Statements,
+    upsert: Statement,
+    delete: Statement,
+}
+
+impl PostgresSink {
+    pub async fn new(
+        cfg: PostgresConfig,
+        pk_indices: Vec<usize>,
+        hidden_column_indices: Vec<usize>,
+    ) -> Result<Self> {
+        let client = cfg.connect().await?;
+
+        Ok(Self {
+            cfg,
+            pk_indices,
+            hidden_column_indices,
+            client,
+            prepared: None,
+            in_transaction: false,
+            epoch: 0,
+        })
+    }
+
+    /// Checks that the target table exists, and has a column of a compatible type for each
+    /// visible column of the sink.
+    pub async fn validate(
+        cfg: &PostgresConfig,
+        schema: &Schema,
+        hidden_column_indices: &[usize],
+    ) -> Result<()> {
+        let client = cfg.connect().await?;
+        let rows = client
+            .query(
+                "SELECT column_name, data_type FROM information_schema.columns \
+                 WHERE table_schema = $1 AND table_name = $2",
+                &[&cfg.schema, &cfg.table],
+            )
+            .await?;
+        if rows.is_empty() {
+            return Err(SinkError::Postgres(format!(
+                "table {} does not exist",
+                cfg.qualified_table()
+            )));
+        }
+        let columns: HashMap<String, String> = rows
+            .iter()
+            .map(|row| (row.get::<_, String>(0), row.get::<_, String>(1)))
+            .collect();
+
+        for (_, field) in schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !hidden_column_indices.contains(i))
+        {
+            let pg_type = columns.get(&field.name).ok_or_else(|| {
+                SinkError::Postgres(format!(
+                    "column {} does not exist in table {}",
+                    field.name,
+                    cfg.qualified_table()
+                ))
+            })?;
+            if !is_compatible(&field.data_type, pg_type) {
+                return Err(SinkError::Postgres(format!(
+                    "column {} of type {} can not be written to the column of type {}",
+                    field.name, field.data_type, pg_type
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The names of the prepared transactions in the database.
+    async fn prepared_transactions(&self) -> Result<Vec<String>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT gid FROM pg_prepared_xacts WHERE database = current_database()",
+                &[],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+}
+
+impl fmt::Debug for PostgresSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PostgresSink")
+            .field("cfg", &self.cfg)
+            .field("pk_indices", &self.pk_indices)
+            .field("hidden_column_indices", &self.hidden_column_indices)
+            .field("in_transaction", &self.in_transaction)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether a column of `data_type` can be written to a column of `pg_type`, which is the
+/// `data_type` in `information_schema.columns`.
+fn is_compatible(data_type: &DataType, pg_type: &str) -> bool {
+    match data_type {
+        DataType::Varchar => matches!(pg_type, "character varying" | "text" | "character"),
+        DataType::Struct { .. } | DataType::List { .. } => false,
+        _ => data_type.to_string() == pg_type,
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// A column value bound to a statement parameter. It's encoded in the binary format of the
+/// target column, whose type is checked by [`PostgresSink::validate`].
+#[derive(Debug)]
+struct PostgresValue<'a> {
+    data_type: &'a DataType,
+    datum: DatumRef<'a>,
+}
+
+impl ToSql for PostgresValue<'_> {
+    to_sql_checked!();
+
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let scalar = match self.datum {
+            None => return Ok(IsNull::Yes),
+            Some(scalar) => scalar,
+        };
+        match (self.data_type, scalar) {
+            (DataType::Struct { .. } | DataType::List { .. }, _) => {
+                return Err(format!("unsupported data type: {}", self.data_type).into())
+            }
+            // The timestamp with time zone is stored as the microseconds since the Unix epoch.
+            (DataType::Timestampz, ScalarRefImpl::Int64(v)) => {
+                out.put_i64(v - POSTGRES_EPOCH_MICROS)
+            }
+            (_, ScalarRefImpl::Interval(v)) => {
+                out.put_i64(v.get_ms() * 1000);
+                out.put_i32(v.get_days());
+                out.put_i32(v.get_months());
+            }
+            (_, scalar) => out.extend_from_slice(&scalar.binary_serialize()),
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
+fn row_values<'a>(
+    row: &'a RowRef<'_>,
+    schema: &'a Schema,
+    indices: &[usize],
+) -> Vec<PostgresValue<'a>> {
+    indices
+        .iter()
+        .map(|&i| PostgresValue {
+            data_type: &schema.fields[i].data_type,
+            datum: row.value_at(i),
+        })
+        .collect()
+}
+
+/// The statements applying the changes to the target table, with the values of the visible
+/// columns bound as the parameters. Rows are identified by the primary key columns, or by all the
+/// visible columns if the sink has no primary key or a hidden one.
+#[derive(Debug)]
+struct Statements {
+    upsert: String,
+    delete: String,
+    visible_indices: Vec<usize>,
+    key_indices: Vec<usize>,
+}
+
+impl Statements {
+    fn new(
+        schema: &Schema,
+        pk_indices: &[usize],
+        hidden_column_indices: &[usize],
+        table: &str,
+    ) -> Self {
+        let visible_indices = (0..schema.len())
+            .filter(|i| !hidden_column_indices.contains(i))
+            .collect_vec();
+        let pk_indices = if pk_indices.iter().any(|i| hidden_column_indices.contains(i)) {
+            &[]
+        } else {
+            pk_indices
+        };
+        let key_indices = if pk_indices.is_empty() {
+            visible_indices.clone()
+        } else {
+            pk_indices.to_vec()
+        };
+        let names = schema.names();
+        let column = |i: usize| quote_identifier(&names[i]);
+
+        let on_conflict = if pk_indices.is_empty() {
+            String::new()
+        } else {
+            let updates = visible_indices
+                .iter()
+                .filter(|i| !pk_indices.contains(i))
+                .map(|&i| format!("{} = EXCLUDED.{}", column(i), column(i)))
+                .join(", ");
+            let action = if updates.is_empty() {
+                "DO NOTHING".to_string()
+            } else {
+                format!("DO UPDATE SET {}", updates)
+            };
+            format!(
+                " ON CONFLICT ({}) {}",
+                pk_indices.iter().map(|&i| column(i)).join(", "),
+                action
+            )
+        };
+        let upsert = format!(
+            "INSERT INTO {} ({}) VALUES ({}){}",
+            table,
+            visible_indices.iter().map(|&i| column(i)).join(", "),
+            (1..=visible_indices.len())
+                .map(|n| format!("${}", n))
+                .join(", "),
+            on_conflict
+        );
+        // `IS NOT DISTINCT FROM` matches the rows with `NULL` keys as well.
+        let delete = format!(
+            "DELETE FROM {} WHERE {}",
+            table,
+            key_indices
+                .iter()
+                .enumerate()
+                .map(|(n, &i)| format!("{} IS NOT DISTINCT FROM ${}", column(i), n + 1))
+                .join(" AND ")
+        );
+        Self {
+            upsert,
+            delete,
+            visible_indices,
+            key_indices,
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for PostgresSink {
+    async fn write_batch(&mut self, chunk: StreamChunk, schema: &Schema) -> Result<()> {
+        if !self.in_transaction {
+            self.client.batch_execute("BEGIN").await?;
+            self.in_transaction = true;
+        }
+        if self.prepared.is_none() {
+            let statements = Statements::new(
+                schema,
+                &self.pk_indices,
+                &self.hidden_column_indices,
+                &self.cfg.qualified_table(),
+            );
+            let upsert = self.client.prepare(&statements.upsert).await?;
+            let delete = self.client.prepare(&statements.delete).await?;
+            self.prepared = Some(PreparedStatements {
+                statements,
+                upsert,
+                delete,
+            });
+        }
+        let prepared = self.prepared.as_ref().unwrap();
+
+        for (op, row) in chunk.rows() {
+            let (stmt, values) = match op {
+                Op::Insert | Op::UpdateInsert => (
+                    &prepared.upsert,
+                    row_values(&row, schema, &prepared.statements.visible_indices),
+                ),
+                Op::Delete | Op::UpdateDelete => (
+                    &prepared.delete,
+                    row_values(&row, schema, &prepared.statements.key_indices),
+                ),
+            };
+            let params = values
+                .iter()
+                .map(|v| v as &(dyn ToSql + Sync))
+                .collect_vec();
+            self.client.execute(stmt, &params).await?;
+        }
+        Ok(())
+    }
+
+    async fn begin_epoch(&mut self, epoch: u64) -> Result<()> {
+        self.epoch = epoch;
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<()> {
+        if self.in_transaction {
+            self.in_transaction = false;
+            self.client.batch_execute("COMMIT").await?;
+        }
+        Ok(())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        if self.in_transaction {
+            self.in_transaction = false;
+            self.client.batch_execute("ROLLBACK").await?;
+        }
+        Ok(())
+    }
+
+    fn support_two_phase_commit(&self) -> bool {
+        true
+    }
+
+    /// Prepares the transaction, and returns its name.
+    async fn pre_commit(&mut self) -> Result<Vec<u8>> {
+        if !self.in_transaction {
+            return Ok(vec![]);
+        }
+        self.in_transaction = false;
+        let gid = format!("{}-{}", self.cfg.identifier, self.epoch);
+        self.client
+            .batch_execute(&format!("PREPARE TRANSACTION {}", quote_literal(&gid)))
+            .await?;
+        Ok(gid.into_bytes())
+    }
+
+    async fn commit_prepared(&mut self, _epoch: u64, metadata: &[u8]) -> Result<()> {
+        if metadata.is_empty() {
+            return Ok(());
+        }
+        let gid = std::str::from_utf8(metadata)
+            .map_err(|e| SinkError::Postgres(format!("invalid transaction name: {}", e)))?;
+        // The transaction may have been committed before the recovery.
+        if self.prepared_transactions().await?.iter().any(|g| g == gid) {
+            self.client
+                .batch_execute(&format!("COMMIT PREPARED {}", quote_literal(gid)))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn abort_prepared(&mut self) -> Result<()> {
+        let prefix = format!("{}-", self.cfg.identifier);
+        for gid in self.prepared_transactions().await? {
+            if gid.starts_with(&prefix) {
+                self.client
+                    .batch_execute(&format!("ROLLBACK PREPARED {}", quote_literal(&gid)))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use maplit::hashmap;
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::catalog::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_config_from_hashmap() {
+        let config = PostgresConfig::from_hashmap(hashmap! {
+            "postgres.endpoint".to_string() => "127.0.0.1:5432".to_string(),
+            "postgres.table".to_string() => "t".to_string(),
+            "postgres.user".to_string() => "postgres".to_string(),
+        })
+        .unwrap();
+        assert_eq!(config.qualified_table(), r#""public"."t""#);
+        assert_eq!(config.user.as_deref(), Some("postgres"));
+
+        assert!(PostgresConfig::from_hashmap(hashmap! {
+            "postgres.endpoint".to_string() => "127.0.0.1:port".to_string(),
+            "postgres.table".to_string() => "t".to_string(),
+        })
+        .is_err());
+        assert!(PostgresConfig::from_hashmap(hashmap! {
+            "postgres.endpoint".to_string() => "127.0.0.1".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        assert!(is_compatible(&DataType::Int32, "integer"));
+        assert!(is_compatible(&DataType::Varchar, "text"));
+        assert!(is_compatible(
+            &DataType::Timestamp,
+            "timestamp without time zone"
+        ));
+        assert!(!is_compatible(&DataType::Int64, "integer"));
+        assert!(!is_compatible(
+            &DataType::List {
+                datatype: Box::new(DataType::Int32)
+            },
+            "ARRAY"
+        ));
+    }
+
+    #[test]
+    fn test_statements() {
+        let schema = Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+            Field::with_name(DataType::Int64, "_row_id"),
+        ]);
+
+        let statements = Statements::new(&schema, &[0], &[2], "t");
+        assert_eq!(
+            statements.upsert,
+            r#"INSERT INTO t ("id", "name") VALUES ($1, $2) ON CONFLICT ("id") DO UPDATE SET "name" = EXCLUDED."name""#
+        );
+        assert_eq!(
+            statements.delete,
+            r#"DELETE FROM t WHERE "id" IS NOT DISTINCT FROM $1"#
+        );
+        assert_eq!(statements.visible_indices, vec![0, 1]);
+        assert_eq!(statements.key_indices, vec![0]);
+
+        // With a hidden primary key, the rows are inserted as they are, and deleted by all the
+        // visible columns.
+        let statements = Statements::new(&schema, &[2], &[2], "t");
+        assert_eq!(
+            statements.upsert,
+            r#"INSERT INTO t ("id", "name") VALUES ($1, $2)"#
+        );
+        assert_eq!(
+            statements.delete,
+            r#"DELETE FROM t WHERE "id" IS NOT DISTINCT FROM $1 AND "name" IS NOT DISTINCT FROM $2"#
+        );
+        assert_eq!(statements.key_indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_postgres_value() {
+        let encode = |data_type: &DataType, datum: DatumRef| {
+            let mut out = BytesMut::new();
+            let is_null = PostgresValue { data_type, datum }
+                .to_sql(&Type::ANY, &mut out)
+                .unwrap();
+            (matches!(is_null, IsNull::Yes), out.to_vec())
+        };
+
+        assert_eq!(encode(&DataType::Int32, None), (true, vec![]));
+        assert_eq!(
+            encode(&DataType::Int32, Some(ScalarRefImpl::Int32(1))),
+            (false, vec![0, 0, 0, 1])
+        );
+        assert_eq!(
+            encode(&DataType::Varchar, Some(ScalarRefImpl::Utf8("it's"))),
+            (false, b"it's".to_vec())
+        );
+        // 2000-01-01 00:00:01+00
+        assert_eq!(
+            encode(
+                &DataType::Timestampz,
+                Some(ScalarRefImpl::Int64(POSTGRES_EPOCH_MICROS + 1_000_000))
+            ),
+            (false, 1_000_000i64.to_be_bytes().to_vec())
+        );
+    }
+
+    /// Requires a local Postgres with `CREATE TABLE t (id INT PRIMARY KEY, name VARCHAR);` in
+    /// the `postgres` database.
+    #[ignore]
+    #[tokio::test]
+    async fn test_postgres_sink() -> Result<()> {
+        let config = PostgresConfig::from_hashmap(hashmap! {
+            "postgres.endpoint".to_string() => "127.0.0.1:5432".to_string(),
+            "postgres.database".to_string() => "postgres".to_string(),
+            "postgres.user".to_string() => "postgres".to_string(),
+            "postgres.table".to_string() => "t".to_string(),
+        })?;
+        let schema = Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+        ]);
+        PostgresSink::validate(&config, &schema, &[]).await?;
+
+        let mut sink = PostgresSink::new(config, vec![0], vec![]).await?;
+        sink.begin_epoch(1).await?;
+        sink.write_batch(
+            StreamChunk::from_pretty(
+                " i T
+                + 1 a
+                + 2 b",
+            ),
+            &schema,
+        )
+        .await?;
+        sink.commit().await?;
+
+        // Replaying the epoch is idempotent.
+        sink.begin_epoch(1).await?;
+        sink.write_batch(
+            StreamChunk::from_pretty(
+                " i T
+                + 1 a
+                + 2 b",
+            ),
+            &schema,
+        )
+        .await?;
+        sink.commit().await?;
+
+        Ok(())
+    }
+}
@@ -12,39 +12,446 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::fmt;
+
 use async_trait::async_trait;
-use risingwave_common::array::StreamChunk;
+use itertools::Itertools;
+use redis::aio::MultiplexedConnection;
+use redis::Pipeline;
+use risingwave_common::array::{Op, RowRef, StreamChunk};
 use risingwave_common::catalog::Schema;
+use serde_json::Value;
+
+use crate::sink::serializer::{datum_to_json_object, record_to_json};
+use crate::sink::{Result, Sink, SinkError};
 
-use crate::sink::{Result, Sink};
+pub const REDIS_SINK: &str = "redis";
+
+/// How a row is stored as the value of its key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedisValueFormat {
+    /// A string of the JSON object of the row, written by `SET`.
+    Json,
+    /// A hash from the column names to the column values, written by `HSET`. `NULL` columns are
+    /// left out.
+    Hash,
+}
 
 #[derive(Clone, Debug)]
-pub struct RedisConfig;
+pub struct RedisConfig {
+    /// e.g. `redis://127.0.0.1:6379/0`
+    pub url: String,
+    /// Prepended to the keys of all the rows.
+    pub key_prefix: String,
+    pub format: RedisValueFormat,
+}
+
+impl RedisConfig {
+    pub fn from_hashmap(values: HashMap<String, String>) -> Result<Self> {
+        let url = values
+            .get("redis.url")
+            .ok_or_else(|| SinkError::Config("redis.url must be set".to_string()))?;
+        let key_prefix = values.get("redis.key.prefix").cloned().unwrap_or_default();
+        let format = match values.get("format").map(|f| f.to_lowercase()).as_deref() {
+            None | Some("json") => RedisValueFormat::Json,
+            Some("hash") => RedisValueFormat::Hash,
+            Some(_) => {
+                return Err(SinkError::Config(
+                    "format must be set to \"json\" or \"hash\"".to_string(),
+                ))
+            }
+        };
 
-#[derive(Debug)]
-pub struct RedisSink;
+        Ok(RedisConfig {
+            url: url.to_string(),
+            key_prefix,
+            format,
+        })
+    }
+}
+
+/// `RedisSink` writes each row to the key made of its primary key columns, and removes the key
+/// when the row is deleted.
+///
+/// The writes of an epoch are buffered, and applied atomically in a `MULTI`/`EXEC` transaction
+/// on commit.
+pub struct RedisSink {
+    cfg: RedisConfig,
+    pk_indices: Vec<usize>,
+    conn: MultiplexedConnection,
+    pipe: Pipeline,
+}
 
 impl RedisSink {
-    pub fn new(_cfg: RedisConfig) -> Result<Self> {
-        todo!()
+    pub async fn new(cfg: RedisConfig, pk_indices: Vec<usize>) -> Result<Self> {
+        if pk_indices.is_empty() {
+            return Err(SinkError::Config(
+                "redis sink requires the sink to have a primary key".to_string(),
+            ));
+        }
+        let client = redis::Client::open(cfg.url.as_str())?;
+        let conn = client.get_multiplexed_tokio_connection().await?;
+
+        Ok(Self {
+            cfg,
+            pk_indices,
+            conn,
+            pipe: new_pipeline(),
+        })
+    }
+}
+
+impl fmt::Debug for RedisSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisSink")
+            .field("cfg", &self.cfg)
+            .field("pk_indices", &self.pk_indices)
+            .finish_non_exhaustive()
+    }
+}
+
+fn new_pipeline() -> Pipeline {
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    pipe
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RedisCommand {
+    Set {
+        key: String,
+        value: String,
+    },
+    /// Replaces the whole hash, so that the columns which become `NULL` are removed.
+    HashSet {
+        key: String,
+        fields: Vec<(String, String)>,
+    },
+    Del {
+        key: String,
+    },
+}
+
+impl RedisCommand {
+    fn append_to(self, pipe: &mut Pipeline) {
+        match self {
+            RedisCommand::Set { key, value } => {
+                pipe.set(key, value).ignore();
+            }
+            RedisCommand::HashSet { key, fields } => {
+                pipe.del(&key).ignore();
+                if !fields.is_empty() {
+                    pipe.hset_multiple(key, &fields).ignore();
+                }
+            }
+            RedisCommand::Del { key } => {
+                pipe.del(key).ignore();
+            }
+        }
+    }
+}
+
+/// The string representation of a column value in keys and hashes. `None` for `NULL`.
+fn value_to_string(value: Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s),
+        v => Some(v.to_string()),
+    }
+}
+
+/// The values of the columns of `row`, in the order of `indices`.
+fn row_values(row: &RowRef, schema: &Schema, indices: &[usize]) -> Result<Vec<Option<String>>> {
+    indices
+        .iter()
+        .map(|&i| {
+            datum_to_json_object(&schema.fields[i], row.value_at(i))
+                .map(value_to_string)
+                .map_err(|e| SinkError::JsonParse(e.to_string()))
+        })
+        .try_collect()
+}
+
+/// Escapes `\` and `:` in a primary key value with `\`, so that the values joined by `:` can't
+/// collide, e.g. `("a:b", "c")` and `("a", "b:c")`.
+fn escape_key_component(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(':', "\\:")
+}
+
+/// The key of a row is the key prefix followed by its escaped primary key values separated by
+/// `:`. A `NULL` primary key value is rejected, as it can't be told apart from an empty string in
+/// the key.
+fn row_key(
+    row: &RowRef,
+    schema: &Schema,
+    pk_indices: &[usize],
+    key_prefix: &str,
+) -> Result<String> {
+    let pk: Vec<String> = row_values(row, schema, pk_indices)?
+        .into_iter()
+        .zip_eq(pk_indices)
+        .map(|(value, &i)| {
+            value.map(|v| escape_key_component(&v)).ok_or_else(|| {
+                SinkError::Encode(format!(
+                    "primary key column \"{}\" of the redis sink is NULL",
+                    schema.fields[i].name
+                ))
+            })
+        })
+        .try_collect()?;
+    Ok(format!("{}{}", key_prefix, pk.join(":")))
+}
+
+fn chunk_to_commands(
+    chunk: &StreamChunk,
+    schema: &Schema,
+    pk_indices: &[usize],
+    cfg: &RedisConfig,
+) -> Result<Vec<RedisCommand>> {
+    let mut commands = Vec::with_capacity(chunk.capacity());
+    for (op, row) in chunk.rows() {
+        let key = row_key(&row, schema, pk_indices, &cfg.key_prefix)?;
+        let command = match op {
+            // Both sides of an update are applied in the same transaction, so the key is never
+            // observed as missing.
+            Op::Delete | Op::UpdateDelete => RedisCommand::Del { key },
+            Op::Insert | Op::UpdateInsert => match cfg.format {
+                RedisValueFormat::Json => RedisCommand::Set {
+                    key,
+                    value: Value::Object(record_to_json(row, schema.fields.clone())?).to_string(),
+                },
+                RedisValueFormat::Hash => {
+                    let indices = (0..schema.len()).collect_vec();
+                    let fields = schema
+                        .names()
+                        .into_iter()
+                        .zip_eq(row_values(&row, schema, &indices)?)
+                        .filter_map(|(name, value)| value.map(|value| (name, value)))
+                        .collect();
+                    RedisCommand::HashSet { key, fields }
+                }
+            },
+        };
+        commands.push(command);
     }
+    Ok(commands)
 }
 
 #[async_trait]
 impl Sink for RedisSink {
-    async fn write_batch(&mut self, _chunk: StreamChunk, _schema: &Schema) -> Result<()> {
-        todo!();
+    async fn write_batch(&mut self, chunk: StreamChunk, schema: &Schema) -> Result<()> {
+        for command in chunk_to_commands(&chunk, schema, &self.pk_indices, &self.cfg)? {
+            command.append_to(&mut self.pipe);
+        }
+        Ok(())
     }
 
     async fn begin_epoch(&mut self, _epoch: u64) -> Result<()> {
-        todo!()
+        self.pipe = new_pipeline();
+        Ok(())
     }
 
     async fn commit(&mut self) -> Result<()> {
-        todo!()
+        let pipe = std::mem::replace(&mut self.pipe, new_pipeline());
+        pipe.query_async::<_, ()>(&mut self.conn).await?;
+        Ok(())
     }
 
     async fn abort(&mut self) -> Result<()> {
-        todo!()
+        // Nothing has been sent to redis before commit.
+        self.pipe = new_pipeline();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use maplit::hashmap;
+    use redis::AsyncCommands;
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::catalog::Field;
+    use risingwave_common::types::DataType;
+
+    use super::*;
+
+    fn test_schema() -> Schema {
+        Schema::new(vec![
+            Field::with_name(DataType::Int64, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+            Field::with_name(DataType::Int32, "age"),
+        ])
+    }
+
+    fn test_config(format: RedisValueFormat) -> RedisConfig {
+        RedisConfig {
+            url: "redis://127.0.0.1:6379/".to_string(),
+            key_prefix: "user:".to_string(),
+            format,
+        }
+    }
+
+    #[test]
+    fn test_config_from_hashmap() {
+        let config = RedisConfig::from_hashmap(hashmap! {
+            "redis.url".to_string() => "redis://127.0.0.1:6379/".to_string(),
+            "format".to_string() => "HASH".to_string(),
+        })
+        .unwrap();
+        assert_eq!(config.key_prefix, "");
+        assert_eq!(config.format, RedisValueFormat::Hash);
+
+        assert!(RedisConfig::from_hashmap(hashmap! {
+            "redis.url".to_string() => "redis://127.0.0.1:6379/".to_string(),
+            "format".to_string() => "avro".to_string(),
+        })
+        .is_err());
+        assert!(RedisConfig::from_hashmap(HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_chunk_to_commands() {
+        let chunk = StreamChunk::from_pretty(
+            " I  T     i
+            + 1  alice 20
+            - 2  bob   30
+            U- 3  carol 40
+            U+ 3  carol .",
+        );
+        let schema = test_schema();
+
+        let commands =
+            chunk_to_commands(&chunk, &schema, &[0], &test_config(RedisValueFormat::Json)).unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                RedisCommand::Set {
+                    key: "user:1".to_string(),
+                    value: r#"{"age":20,"id":1,"name":"alice"}"#.to_string(),
+                },
+                RedisCommand::Del {
+                    key: "user:2".to_string()
+                },
+                RedisCommand::Del {
+                    key: "user:3".to_string()
+                },
+                RedisCommand::Set {
+                    key: "user:3".to_string(),
+                    value: r#"{"age":null,"id":3,"name":"carol"}"#.to_string(),
+                },
+            ]
+        );
+
+        let commands = chunk_to_commands(
+            &chunk,
+            &schema,
+            &[0, 1],
+            &test_config(RedisValueFormat::Hash),
+        )
+        .unwrap();
+        assert_eq!(
+            commands[0],
+            RedisCommand::HashSet {
+                key: "user:1:alice".to_string(),
+                fields: vec![
+                    ("id".to_string(), "1".to_string()),
+                    ("name".to_string(), "alice".to_string()),
+                    ("age".to_string(), "20".to_string()),
+                ],
+            }
+        );
+        assert_eq!(
+            commands[3],
+            RedisCommand::HashSet {
+                key: "user:3:carol".to_string(),
+                fields: vec![
+                    ("id".to_string(), "3".to_string()),
+                    ("name".to_string(), "carol".to_string()),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_escape_key() {
+        let chunk = StreamChunk::from_pretty(
+            " I T   i
+            + 1 a:b 20",
+        );
+        let commands = chunk_to_commands(
+            &chunk,
+            &test_schema(),
+            &[1, 2],
+            &test_config(RedisValueFormat::Json),
+        )
+        .unwrap();
+        match &commands[0] {
+            RedisCommand::Set { key, .. } => assert_eq!(key, r"user:a\:b:20"),
+            command => panic!("unexpected command: {:?}", command),
+        }
+        assert_eq!(escape_key_component(r"a\:b"), r"a\\\:b");
+    }
+
+    #[test]
+    fn test_null_key() {
+        let chunk = StreamChunk::from_pretty(
+            " I T i
+            + 1 . 20",
+        );
+        let schema = test_schema();
+
+        assert!(
+            chunk_to_commands(&chunk, &schema, &[0], &test_config(RedisValueFormat::Json)).is_ok()
+        );
+        assert!(chunk_to_commands(
+            &chunk,
+            &schema,
+            &[0, 1],
+            &test_config(RedisValueFormat::Json)
+        )
+        .is_err());
+    }
+
+    /// Requires a local redis-server, e.g. started by `./risedev d ci-redis`.
+    #[ignore]
+    #[tokio::test]
+    async fn test_redis_sink() -> Result<()> {
+        let config = test_config(RedisValueFormat::Json);
+        let schema = test_schema();
+        let mut sink = RedisSink::new(config.clone(), vec![0]).await?;
+
+        sink.begin_epoch(1).await?;
+        sink.write_batch(
+            StreamChunk::from_pretty(
+                " I T     i
+                + 1 alice 20
+                + 2 bob   30",
+            ),
+            &schema,
+        )
+        .await?;
+        sink.commit().await?;
+
+        sink.begin_epoch(2).await?;
+        sink.write_batch(
+            StreamChunk::from_pretty(
+                " I T     i
+                - 2 bob   30",
+            ),
+            &schema,
+        )
+        .await?;
+        // Nothing is written before commit.
+        let mut conn = redis::Client::open(config.url.as_str())?
+            .get_multiplexed_tokio_connection()
+            .await?;
+        assert!(conn.exists::<_, bool>("user:2").await?);
+        sink.commit().await?;
+
+        let value: String = conn.get("user:1").await?;
+        assert_eq!(value, r#"{"age":20,"id":1,"name":"alice"}"#);
+        assert!(!conn.exists::<_, bool>("user:2").await?);
+
+        Ok(())
     }
 }
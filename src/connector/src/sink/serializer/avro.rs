@@ -0,0 +1,182 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use apache_avro::types::Value;
+use apache_avro::Schema as AvroSchema;
+use risingwave_common::array::RowRef;
+use risingwave_common::catalog::Schema;
+use risingwave_common::types::{DataType, DatumRef, ScalarRefImpl};
+use serde_json::json;
+
+use super::{RowSerializer, SerializedColumns};
+use crate::sink::{Result, SinkError};
+
+/// Serializes the rows into Avro records without the container header. All the fields are
+/// nullable, i.e. unions of `null` and the type of the column.
+#[derive(Debug)]
+pub struct AvroSerializer {
+    columns: SerializedColumns,
+    schema: AvroSchema,
+}
+
+impl AvroSerializer {
+    pub fn new(schema: &Schema, indices: &[usize], name: &str) -> Result<Self> {
+        let columns = SerializedColumns::new(schema, indices);
+        let fields = columns
+            .fields
+            .iter()
+            .map(|field| {
+                Ok(json!({
+                    "name": field.name,
+                    "type": ["null", avro_type(&field.data_type)?],
+                    "default": null,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let schema = AvroSchema::parse(&json!({
+            "type": "record",
+            "name": name,
+            "fields": fields,
+        }))
+        .map_err(|e| SinkError::Encode(format!("invalid avro schema: {}", e)))?;
+
+        Ok(Self { columns, schema })
+    }
+
+    /// The Avro schema of the serialized records.
+    pub fn schema(&self) -> &AvroSchema {
+        &self.schema
+    }
+}
+
+fn avro_type(data_type: &DataType) -> Result<serde_json::Value> {
+    let avro_type = match data_type {
+        DataType::Boolean => json!("boolean"),
+        DataType::Int16 | DataType::Int32 => json!("int"),
+        DataType::Int64 => json!("long"),
+        DataType::Float32 => json!("float"),
+        DataType::Float64 => json!("double"),
+        DataType::Varchar | DataType::Decimal | DataType::Interval => json!("string"),
+        DataType::Date => json!({"type": "int", "logicalType": "date"}),
+        DataType::Time => json!({"type": "long", "logicalType": "time-micros"}),
+        DataType::Timestamp | DataType::Timestampz => {
+            json!({"type": "long", "logicalType": "timestamp-micros"})
+        }
+        DataType::Struct { .. } | DataType::List { .. } => {
+            return Err(SinkError::Encode(format!(
+                "unsupported data type in avro: {}",
+                data_type
+            )))
+        }
+    };
+    Ok(avro_type)
+}
+
+fn datum_to_avro_value(datum: DatumRef) -> Result<Value> {
+    let scalar = match datum {
+        None => return Ok(Value::Union(0, Box::new(Value::Null))),
+        Some(scalar) => scalar,
+    };
+    let value = match scalar {
+        ScalarRefImpl::Bool(v) => Value::Boolean(v),
+        ScalarRefImpl::Int16(v) => Value::Int(v as i32),
+        ScalarRefImpl::Int32(v) => Value::Int(v),
+        // The timestamp with time zone is stored as the microseconds since the epoch as well.
+        ScalarRefImpl::Int64(v) => Value::Long(v),
+        ScalarRefImpl::Float32(v) => Value::Float(f32::from(v)),
+        ScalarRefImpl::Float64(v) => Value::Double(f64::from(v)),
+        ScalarRefImpl::Utf8(v) => Value::String(v.to_string()),
+        ScalarRefImpl::Decimal(v) => Value::String(v.to_string()),
+        ScalarRefImpl::Interval(v) => Value::String(v.to_string()),
+        ScalarRefImpl::NaiveDate(v) => {
+            Value::Date((v.0 - chrono::NaiveDate::from_ymd(1970, 1, 1)).num_days() as i32)
+        }
+        ScalarRefImpl::NaiveTime(v) => Value::TimeMicros(
+            (v.0 - chrono::NaiveTime::from_hms(0, 0, 0))
+                .num_microseconds()
+                .unwrap(),
+        ),
+        ScalarRefImpl::NaiveDateTime(v) => Value::TimestampMicros(
+            v.0.timestamp() * 1_000_000 + v.0.timestamp_subsec_micros() as i64,
+        ),
+        ScalarRefImpl::Struct(_) | ScalarRefImpl::List(_) => {
+            return Err(SinkError::Encode(
+                "unsupported nested value in avro".to_string(),
+            ))
+        }
+    };
+    Ok(Value::Union(1, Box::new(value)))
+}
+
+impl RowSerializer for AvroSerializer {
+    fn serialize(&self, row: &RowRef) -> Result<Vec<u8>> {
+        let fields = self
+            .columns
+            .iter(row)
+            .map(|(field, datum)| Ok((field.name.clone(), datum_to_avro_value(datum)?)))
+            .collect::<Result<Vec<_>>>()?;
+        apache_avro::to_avro_datum(&self.schema, Value::Record(fields))
+            .map_err(|e| SinkError::Encode(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use apache_avro::from_avro_datum;
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::array::StreamChunk;
+    use risingwave_common::catalog::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_avro_serializer() {
+        let schema = Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+            Field::with_name(DataType::Float64, "price"),
+        ]);
+        let serializer = AvroSerializer::new(&schema, &[0, 1, 2], "Value").unwrap();
+        let chunk = StreamChunk::from_pretty(
+            " i T     F
+            + 1 apple .",
+        );
+        let (_, row) = chunk.rows().next().unwrap();
+        let bytes = serializer.serialize(&row).unwrap();
+
+        let value = from_avro_datum(serializer.schema(), &mut bytes.as_slice(), None).unwrap();
+        assert_eq!(
+            value,
+            Value::Record(vec![
+                ("id".to_string(), Value::Union(1, Box::new(Value::Int(1)))),
+                (
+                    "name".to_string(),
+                    Value::Union(1, Box::new(Value::String("apple".to_string())))
+                ),
+                ("price".to_string(), Value::Union(0, Box::new(Value::Null))),
+            ])
+        );
+
+        let key_serializer = AvroSerializer::new(&schema, &[0], "Key").unwrap();
+        let bytes = key_serializer.serialize(&row).unwrap();
+        let value = from_avro_datum(key_serializer.schema(), &mut bytes.as_slice(), None).unwrap();
+        assert_eq!(
+            value,
+            Value::Record(vec![(
+                "id".to_string(),
+                Value::Union(1, Box::new(Value::Int(1)))
+            )])
+        );
+    }
+}
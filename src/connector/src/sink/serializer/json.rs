@@ -0,0 +1,139 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools;
+use risingwave_common::array::{ArrayResult, RowRef};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::{DataType, DatumRef, ScalarRefImpl};
+use serde_json::{json, Map, Value};
+
+use super::{RowSerializer, SerializedColumns};
+use crate::sink::{Result, SinkError};
+
+/// Serializes the rows into JSON objects from the column names to the column values.
+#[derive(Debug)]
+pub struct JsonSerializer {
+    columns: SerializedColumns,
+}
+
+impl JsonSerializer {
+    pub fn new(schema: &Schema, indices: &[usize]) -> Self {
+        Self {
+            columns: SerializedColumns::new(schema, indices),
+        }
+    }
+}
+
+impl RowSerializer for JsonSerializer {
+    fn serialize(&self, row: &RowRef) -> Result<Vec<u8>> {
+        let mut mappings = Map::with_capacity(self.columns.fields.len());
+        for (field, datum_ref) in self.columns.iter(row) {
+            let value = datum_to_json_object(field, datum_ref)
+                .map_err(|e| SinkError::JsonParse(e.to_string()))?;
+            mappings.insert(field.name.clone(), value);
+        }
+        Ok(Value::Object(mappings).to_string().into_bytes())
+    }
+}
+
+pub(crate) fn datum_to_json_object(field: &Field, datum: DatumRef) -> ArrayResult<Value> {
+    let scalar_ref = match datum {
+        None => return Ok(Value::Null),
+        Some(datum) => datum,
+    };
+
+    let data_type = field.data_type();
+
+    let value = match (data_type, scalar_ref) {
+        (DataType::Boolean, ScalarRefImpl::Bool(v)) => {
+            json!(v)
+        }
+        (DataType::Int16, ScalarRefImpl::Int16(v)) => {
+            json!(v)
+        }
+        (DataType::Int32, ScalarRefImpl::Int32(v)) => {
+            json!(v)
+        }
+        (DataType::Int64, ScalarRefImpl::Int64(v)) => {
+            json!(v)
+        }
+        (DataType::Float32, ScalarRefImpl::Float32(v)) => {
+            json!(f32::from(v))
+        }
+        (DataType::Float64, ScalarRefImpl::Float64(v)) => {
+            json!(f64::from(v))
+        }
+        (DataType::Varchar, ScalarRefImpl::Utf8(v)) => {
+            json!(v)
+        }
+        (DataType::Decimal, ScalarRefImpl::Decimal(v)) => {
+            // fixme
+            json!(v.to_string())
+        }
+        (DataType::Date, ScalarRefImpl::NaiveDate(v)) => {
+            json!(v.to_string())
+        }
+        (DataType::Time, ScalarRefImpl::NaiveTime(v)) => {
+            json!(v.to_string())
+        }
+        (DataType::Timestamp, ScalarRefImpl::NaiveDateTime(v)) => {
+            json!(v.to_string())
+        }
+        // The timestamp with time zone is stored as the microseconds since the epoch.
+        (DataType::Timestampz, ScalarRefImpl::Int64(v)) => {
+            json!(v)
+        }
+        (DataType::Interval, ScalarRefImpl::Interval(v)) => {
+            json!(v.to_string())
+        }
+        (DataType::List { .. }, ScalarRefImpl::List(list_ref)) => {
+            let mut vec = Vec::with_capacity(field.sub_fields.len());
+            for (sub_datum_ref, sub_field) in list_ref
+                .values_ref()
+                .into_iter()
+                .zip_eq(field.sub_fields.iter())
+            {
+                let value = datum_to_json_object(sub_field, sub_datum_ref)?;
+                vec.push(value);
+            }
+            json!(vec)
+        }
+        (DataType::Struct { .. }, ScalarRefImpl::Struct(struct_ref)) => {
+            let mut map = Map::with_capacity(field.sub_fields.len());
+            for (sub_datum_ref, sub_field) in struct_ref
+                .fields_ref()
+                .into_iter()
+                .zip_eq(field.sub_fields.iter())
+            {
+                let value = datum_to_json_object(sub_field, sub_datum_ref)?;
+                map.insert(sub_field.name.clone(), value);
+            }
+            json!(map)
+        }
+        _ => unimplemented!(),
+    };
+
+    Ok(value)
+}
+
+pub(crate) fn record_to_json(row: RowRef, schema: Vec<Field>) -> Result<Map<String, Value>> {
+    let mut mappings = Map::with_capacity(schema.len());
+    for (field, datum_ref) in schema.iter().zip_eq(row.values()) {
+        let key = field.name.clone();
+        let value = datum_to_json_object(field, datum_ref)
+            .map_err(|e| SinkError::JsonParse(e.to_string()))?;
+        mappings.insert(key, value);
+    }
+    Ok(mappings)
+}
@@ -0,0 +1,204 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoders of the rows written to the sinks.
+
+mod avro;
+mod json;
+mod protobuf;
+
+pub use avro::AvroSerializer;
+use itertools::Itertools;
+pub use json::JsonSerializer;
+pub(crate) use json::{datum_to_json_object, record_to_json};
+pub use protobuf::ProtobufSerializer;
+use risingwave_common::array::RowRef;
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::DatumRef;
+use serde::Deserialize;
+
+use crate::schema_registry::{SchemaType, MAGIC_BYTE};
+use crate::sink::{Result, SinkError};
+
+/// The encoding of the messages written to the sinks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkEncode {
+    Json,
+    Avro,
+    Protobuf,
+}
+
+impl SinkEncode {
+    pub const KEY: &'static str = "encode";
+
+    /// Parses the value of the `encode` option, which is `json` by default.
+    pub fn from_option(value: Option<&String>) -> Result<Self> {
+        match value.map(|v| v.to_lowercase()).as_deref() {
+            None | Some("json") => Ok(SinkEncode::Json),
+            Some("avro") => Ok(SinkEncode::Avro),
+            Some("protobuf") => Ok(SinkEncode::Protobuf),
+            Some(_) => Err(SinkError::Config(
+                "encode must be set to \"json\", \"avro\" or \"protobuf\"".to_string(),
+            )),
+        }
+    }
+}
+
+/// Serializes some columns of the rows into the bytes of a message.
+pub trait RowSerializer {
+    fn serialize(&self, row: &RowRef) -> Result<Vec<u8>>;
+}
+
+/// The columns to be serialized and their positions in the rows.
+#[derive(Clone, Debug)]
+struct SerializedColumns {
+    fields: Vec<Field>,
+    indices: Vec<usize>,
+}
+
+impl SerializedColumns {
+    fn new(schema: &Schema, indices: &[usize]) -> Self {
+        Self {
+            fields: indices.iter().map(|&i| schema.fields[i].clone()).collect(),
+            indices: indices.to_vec(),
+        }
+    }
+
+    fn iter<'a>(&'a self, row: &'a RowRef) -> impl Iterator<Item = (&'a Field, DatumRef<'a>)> + 'a {
+        self.fields
+            .iter()
+            .zip_eq(self.indices.iter().map(|&i| row.value_at(i)))
+    }
+}
+
+/// Serializes the rows in the Confluent wire format, i.e. prefixed by the id of their schema in
+/// the schema registry.
+#[derive(Debug)]
+pub struct ConfluentSerializer {
+    inner: RowSerializerImpl,
+    schema_id: i32,
+}
+
+impl RowSerializer for ConfluentSerializer {
+    fn serialize(&self, row: &RowRef) -> Result<Vec<u8>> {
+        let data = self.inner.serialize(row)?;
+        let mut buf = Vec::with_capacity(data.len() + 6);
+        buf.push(MAGIC_BYTE);
+        buf.extend_from_slice(&self.schema_id.to_be_bytes());
+        // The Protobuf messages are followed by the indexes of the message type in the schema,
+        // where the first message type is written as a single 0.
+        if let RowSerializerImpl::Protobuf(_) = self.inner {
+            buf.push(0);
+        }
+        buf.extend(data);
+        Ok(buf)
+    }
+}
+
+#[derive(Debug)]
+pub enum RowSerializerImpl {
+    Json(JsonSerializer),
+    Avro(AvroSerializer),
+    Protobuf(ProtobufSerializer),
+    Confluent(Box<ConfluentSerializer>),
+}
+
+impl RowSerializerImpl {
+    /// Creates the serializer of the `indices` columns of `schema`. `name` is the name of the
+    /// generated schema, e.g. the Avro record or the Protobuf message.
+    pub fn new(encode: SinkEncode, schema: &Schema, indices: &[usize], name: &str) -> Result<Self> {
+        Ok(match encode {
+            SinkEncode::Json => RowSerializerImpl::Json(JsonSerializer::new(schema, indices)),
+            SinkEncode::Avro => {
+                RowSerializerImpl::Avro(AvroSerializer::new(schema, indices, name)?)
+            }
+            SinkEncode::Protobuf => {
+                RowSerializerImpl::Protobuf(ProtobufSerializer::new(schema, indices, name)?)
+            }
+        })
+    }
+
+    /// The type and the definition of the schema to register in the schema registry. `None` if
+    /// the encoding has no schema, or the schema has been registered.
+    pub fn registry_schema(&self) -> Option<(SchemaType, String)> {
+        match self {
+            RowSerializerImpl::Avro(serializer) => Some((
+                SchemaType::Avro,
+                serde_json::to_string(serializer.schema()).unwrap(),
+            )),
+            RowSerializerImpl::Protobuf(serializer) => {
+                Some((SchemaType::Protobuf, serializer.schema().to_string()))
+            }
+            RowSerializerImpl::Json(_) | RowSerializerImpl::Confluent(_) => None,
+        }
+    }
+
+    /// Writes the messages in the Confluent wire format with the id of the registered schema.
+    pub fn with_schema_id(self, schema_id: i32) -> Self {
+        RowSerializerImpl::Confluent(Box::new(ConfluentSerializer {
+            inner: self,
+            schema_id,
+        }))
+    }
+}
+
+impl RowSerializer for RowSerializerImpl {
+    fn serialize(&self, row: &RowRef) -> Result<Vec<u8>> {
+        match self {
+            RowSerializerImpl::Json(serializer) => serializer.serialize(row),
+            RowSerializerImpl::Avro(serializer) => serializer.serialize(row),
+            RowSerializerImpl::Protobuf(serializer) => serializer.serialize(row),
+            RowSerializerImpl::Confluent(serializer) => serializer.serialize(row),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::array::StreamChunk;
+    use risingwave_common::types::DataType;
+
+    use super::*;
+
+    #[test]
+    fn test_confluent_serializer() {
+        let schema = Schema::new(vec![Field::with_name(DataType::Int32, "id")]);
+        let chunk = StreamChunk::from_pretty(
+            " i
+            + 1",
+        );
+        let (_, row) = chunk.rows().next().unwrap();
+
+        let serializer = RowSerializerImpl::new(SinkEncode::Protobuf, &schema, &[0], "Value")
+            .unwrap()
+            .with_schema_id(258);
+        assert!(serializer.registry_schema().is_none());
+        assert_eq!(
+            serializer.serialize(&row).unwrap(),
+            vec![MAGIC_BYTE, 0, 0, 1, 2, 0, 8, 1]
+        );
+
+        let serializer = RowSerializerImpl::new(SinkEncode::Avro, &schema, &[0], "Value").unwrap();
+        let (schema_type, avro_schema) = serializer.registry_schema().unwrap();
+        assert_eq!(schema_type, SchemaType::Avro);
+        assert!(avro_schema.contains("\"name\":\"Value\""));
+        assert_eq!(
+            serializer.with_schema_id(1).serialize(&row).unwrap(),
+            // The union index 1 and the zigzag encoded 1.
+            vec![MAGIC_BYTE, 0, 0, 0, 1, 2, 2]
+        );
+    }
+}
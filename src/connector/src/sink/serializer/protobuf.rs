@@ -0,0 +1,176 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use prost::encoding;
+use risingwave_common::array::RowRef;
+use risingwave_common::catalog::Schema;
+use risingwave_common::types::{DataType, ScalarRefImpl};
+
+use super::{RowSerializer, SerializedColumns};
+use crate::sink::{Result, SinkError};
+
+/// Serializes the rows into Protobuf messages, whose fields are numbered from 1 in the order of
+/// the columns. The temporal and decimal values are written as strings.
+///
+/// Since proto3 has no nullable scalar fields, `NULL` values are omitted, and read as the
+/// default values by the consumers.
+#[derive(Debug)]
+pub struct ProtobufSerializer {
+    columns: SerializedColumns,
+    schema: String,
+}
+
+impl ProtobufSerializer {
+    pub fn new(schema: &Schema, indices: &[usize], name: &str) -> Result<Self> {
+        let columns = SerializedColumns::new(schema, indices);
+        let mut proto = format!("syntax = \"proto3\";\n\nmessage {} {{\n", name);
+        for (i, field) in columns.fields.iter().enumerate() {
+            writeln!(
+                proto,
+                "  {} {} = {};",
+                protobuf_type(&field.data_type)?,
+                field.name,
+                i + 1
+            )
+            .unwrap();
+        }
+        proto.push_str("}\n");
+
+        Ok(Self {
+            columns,
+            schema: proto,
+        })
+    }
+
+    /// The `.proto` definition of the serialized messages.
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+}
+
+fn protobuf_type(data_type: &DataType) -> Result<&'static str> {
+    let protobuf_type = match data_type {
+        DataType::Boolean => "bool",
+        DataType::Int16 | DataType::Int32 => "int32",
+        // The timestamp with time zone is the microseconds since the epoch.
+        DataType::Int64 | DataType::Timestampz => "int64",
+        DataType::Float32 => "float",
+        DataType::Float64 => "double",
+        DataType::Varchar
+        | DataType::Decimal
+        | DataType::Date
+        | DataType::Time
+        | DataType::Timestamp
+        | DataType::Interval => "string",
+        DataType::Struct { .. } | DataType::List { .. } => {
+            return Err(SinkError::Encode(format!(
+                "unsupported data type in protobuf: {}",
+                data_type
+            )))
+        }
+    };
+    Ok(protobuf_type)
+}
+
+impl RowSerializer for ProtobufSerializer {
+    fn serialize(&self, row: &RowRef) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        for (i, (_, datum)) in self.columns.iter(row).enumerate() {
+            let tag = i as u32 + 1;
+            let scalar = match datum {
+                None => continue,
+                Some(scalar) => scalar,
+            };
+            match scalar {
+                ScalarRefImpl::Bool(v) => encoding::bool::encode(tag, &v, &mut buf),
+                ScalarRefImpl::Int16(v) => encoding::int32::encode(tag, &(v as i32), &mut buf),
+                ScalarRefImpl::Int32(v) => encoding::int32::encode(tag, &v, &mut buf),
+                ScalarRefImpl::Int64(v) => encoding::int64::encode(tag, &v, &mut buf),
+                ScalarRefImpl::Float32(v) => encoding::float::encode(tag, &f32::from(v), &mut buf),
+                ScalarRefImpl::Float64(v) => encoding::double::encode(tag, &f64::from(v), &mut buf),
+                ScalarRefImpl::Utf8(v) => encoding::string::encode(tag, &v.to_string(), &mut buf),
+                ScalarRefImpl::Struct(_) | ScalarRefImpl::List(_) => {
+                    return Err(SinkError::Encode(
+                        "unsupported nested value in protobuf".to_string(),
+                    ))
+                }
+                scalar => encoding::string::encode(tag, &scalar.to_string(), &mut buf),
+            }
+        }
+        Ok(buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::array::StreamChunk;
+    use risingwave_common::catalog::Field;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct TestMessage {
+        #[prost(int32, tag = "1")]
+        id: i32,
+        #[prost(string, tag = "2")]
+        name: String,
+        #[prost(double, tag = "3")]
+        price: f64,
+    }
+
+    #[test]
+    fn test_protobuf_serializer() {
+        let schema = Schema::new(vec![
+            Field::with_name(DataType::Int32, "id"),
+            Field::with_name(DataType::Varchar, "name"),
+            Field::with_name(DataType::Float64, "price"),
+        ]);
+        let serializer = ProtobufSerializer::new(&schema, &[0, 1, 2], "Value").unwrap();
+        assert_eq!(
+            serializer.schema(),
+            "syntax = \"proto3\";\n\nmessage Value {\n  int32 id = 1;\n  string name = 2;\n  double price = 3;\n}\n"
+        );
+
+        let chunk = StreamChunk::from_pretty(
+            " i T     F
+            + 1 apple 1.5
+            + 2 .     .",
+        );
+        let rows = chunk
+            .rows()
+            .map(|(_, row)| {
+                let bytes = serializer.serialize(&row).unwrap();
+                <TestMessage as prost::Message>::decode(bytes.as_slice()).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                TestMessage {
+                    id: 1,
+                    name: "apple".to_string(),
+                    price: 1.5,
+                },
+                TestMessage {
+                    id: 2,
+                    name: String::new(),
+                    price: 0.0,
+                },
+            ]
+        );
+    }
+}
@@ -28,7 +28,9 @@ use crate::source::datagen::{
     DatagenProperties, DatagenSplit, DatagenSplitEnumerator, DatagenSplitReader, DATAGEN_CONNECTOR,
 };
 use crate::source::dummy_connector::DummySplitReader;
+use crate::source::filesystem::local_fs::{LocalFsProperties, LOCAL_FS_CONNECTOR};
 use crate::source::filesystem::s3::{S3Properties, S3_CONNECTOR};
+use crate::source::filesystem::{FsSplit, FsSplitEnumerator, FsSplitReader};
 use crate::source::kafka::enumerator::KafkaSplitEnumerator;
 use crate::source::kafka::source::KafkaSplitReader;
 use crate::source::kafka::{KafkaProperties, KafkaSplit, KAFKA_CONNECTOR};
@@ -36,10 +38,18 @@ use crate::source::kinesis::enumerator::client::KinesisSplitEnumerator;
 use crate::source::kinesis::source::reader::KinesisMultiSplitReader;
 use crate::source::kinesis::split::KinesisSplit;
 use crate::source::kinesis::{KinesisProperties, KINESIS_CONNECTOR};
+use crate::source::mysql_cdc::{
+    MySqlCdcProperties, MySqlCdcSplit, MySqlCdcSplitEnumerator, MySqlCdcSplitReader,
+    MYSQL_CDC_CONNECTOR,
+};
 use crate::source::nexmark::source::reader::NexmarkSplitReader;
 use crate::source::nexmark::{
     NexmarkProperties, NexmarkSplit, NexmarkSplitEnumerator, NEXMARK_CONNECTOR,
 };
+use crate::source::postgres_cdc::{
+    PostgresCdcProperties, PostgresCdcSplit, PostgresCdcSplitEnumerator, PostgresCdcSplitReader,
+    POSTGRES_CDC_CONNECTOR,
+};
 use crate::source::pulsar::source::reader::PulsarSplitReader;
 use crate::source::pulsar::{
     PulsarProperties, PulsarSplit, PulsarSplitEnumerator, PULSAR_CONNECTOR,
@@ -82,6 +92,10 @@ pub enum SplitImpl {
     Kinesis(KinesisSplit),
     Nexmark(NexmarkSplit),
     Datagen(DatagenSplit),
+    S3(FsSplit),
+    LocalFs(FsSplit),
+    MySqlCdc(MySqlCdcSplit),
+    PostgresCdc(PostgresCdcSplit),
 }
 
 pub enum SplitReaderImpl {
@@ -91,6 +105,10 @@ pub enum SplitReaderImpl {
     Nexmark(Box<NexmarkSplitReader>),
     Pulsar(Box<PulsarSplitReader>),
     Datagen(Box<DatagenSplitReader>),
+    S3(Box<S3SplitReader>),
+    LocalFs(Box<LocalFsSplitReader>),
+    MySqlCdc(Box<MySqlCdcSplitReader>),
+    PostgresCdc(Box<PostgresCdcSplitReader>),
 }
 
 pub enum SplitEnumeratorImpl {
@@ -99,6 +117,10 @@ pub enum SplitEnumeratorImpl {
     Kinesis(KinesisSplitEnumerator),
     Nexmark(NexmarkSplitEnumerator),
     Datagen(DatagenSplitEnumerator),
+    S3(S3SplitEnumerator),
+    LocalFs(LocalFsSplitEnumerator),
+    MySqlCdc(MySqlCdcSplitEnumerator),
+    PostgresCdc(PostgresCdcSplitEnumerator),
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -109,9 +131,17 @@ pub enum ConnectorProperties {
     Nexmark(NexmarkProperties),
     Datagen(DatagenProperties),
     S3(S3Properties),
+    LocalFs(LocalFsProperties),
+    MySqlCdc(MySqlCdcProperties),
+    PostgresCdc(PostgresCdcProperties),
     Dummy(()),
 }
 
+pub type S3SplitEnumerator = FsSplitEnumerator<S3Properties>;
+pub type S3SplitReader = FsSplitReader<S3Properties>;
+pub type LocalFsSplitEnumerator = FsSplitEnumerator<LocalFsProperties>;
+pub type LocalFsSplitReader = FsSplitReader<LocalFsProperties>;
+
 impl_connector_properties! {
     [ ] ,
     { Kafka, KAFKA_CONNECTOR },
@@ -119,7 +149,10 @@ impl_connector_properties! {
     { Kinesis, KINESIS_CONNECTOR },
     { Nexmark, NEXMARK_CONNECTOR },
     { Datagen, DATAGEN_CONNECTOR },
-    { S3, S3_CONNECTOR }
+    { S3, S3_CONNECTOR },
+    { LocalFs, LOCAL_FS_CONNECTOR },
+    { MySqlCdc, MYSQL_CDC_CONNECTOR },
+    { PostgresCdc, POSTGRES_CDC_CONNECTOR }
 }
 
 impl_split_enumerator! {
@@ -128,7 +161,11 @@ impl_split_enumerator! {
     { Pulsar, PulsarSplitEnumerator },
     { Kinesis, KinesisSplitEnumerator },
     { Nexmark, NexmarkSplitEnumerator },
-    { Datagen, DatagenSplitEnumerator }
+    { Datagen, DatagenSplitEnumerator },
+    { S3, S3SplitEnumerator },
+    { LocalFs, LocalFsSplitEnumerator },
+    { MySqlCdc, MySqlCdcSplitEnumerator },
+    { PostgresCdc, PostgresCdcSplitEnumerator }
 }
 
 impl_split! {
@@ -137,7 +174,11 @@ impl_split! {
     { Pulsar, PULSAR_CONNECTOR, PulsarSplit },
     { Kinesis, KINESIS_CONNECTOR, KinesisSplit },
     { Nexmark, NEXMARK_CONNECTOR, NexmarkSplit },
-    { Datagen, DATAGEN_CONNECTOR, DatagenSplit }
+    { Datagen, DATAGEN_CONNECTOR, DatagenSplit },
+    { S3, S3_CONNECTOR, FsSplit },
+    { LocalFs, LOCAL_FS_CONNECTOR, FsSplit },
+    { MySqlCdc, MYSQL_CDC_CONNECTOR, MySqlCdcSplit },
+    { PostgresCdc, POSTGRES_CDC_CONNECTOR, PostgresCdcSplit }
 }
 
 impl_split_reader! {
@@ -147,9 +188,28 @@ impl_split_reader! {
     { Kinesis, KinesisMultiSplitReader },
     { Nexmark, NexmarkSplitReader },
     { Datagen, DatagenSplitReader },
+    { S3, S3SplitReader },
+    { LocalFs, LocalFsSplitReader },
+    { MySqlCdc, MySqlCdcSplitReader },
+    { PostgresCdc, PostgresCdcSplitReader },
     { Dummy, DummySplitReader }
 }
 
+impl SplitReaderImpl {
+    /// Returns the sender of the offsets that are committed in checkpoints, if the reader has to
+    /// acknowledge them to the external source, e.g. to advance the replication slot of postgres.
+    pub fn offset_committer(&self) -> Option<OffsetCommitter> {
+        match self {
+            Self::PostgresCdc(reader) => Some(reader.offset_committer()),
+            _ => None,
+        }
+    }
+}
+
+/// The sender of the offsets of a split that are committed in a checkpoint, which are encoded
+/// as the ones of [`SourceMessage`].
+pub type OffsetCommitter = tokio::sync::mpsc::UnboundedSender<String>;
+
 pub type DataType = risingwave_common::types::DataType;
 
 #[derive(Clone, Debug)]
@@ -165,6 +225,22 @@ pub struct SourceMessage {
     pub payload: Option<Bytes>,
     pub offset: String,
     pub split_id: String,
+    #[serde(default)]
+    pub meta: SourceMeta,
+}
+
+/// The metadata of a message besides the payload, which may be read as the metadata columns of
+/// the source. Each connector fills what it has.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SourceMeta {
+    pub key: Option<Bytes>,
+    /// The milliseconds since the epoch when the message was produced or stored by the broker.
+    pub timestamp: Option<i64>,
+    pub headers: Vec<(String, Bytes)>,
+    /// Whether the payload continues the previous message of its split in the middle of a file,
+    /// so that it doesn't start with the header of the file.
+    #[serde(default)]
+    pub continued: bool,
 }
 
 /// The metadata of a split.
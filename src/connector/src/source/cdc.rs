@@ -0,0 +1,381 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::source::{SourceMessage, SourceMeta};
+
+pub(crate) const SNAPSHOT_BATCH_SIZE: usize = 1024;
+
+/// Returns the change captured from a database in the Debezium JSON format.
+pub(crate) fn debezium_json_message(
+    op: &str,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    ts_ms: i64,
+    split_id: &str,
+    offset: String,
+) -> SourceMessage {
+    let payload = json!({
+        "payload": {
+            "before": before,
+            "after": after,
+            "op": op,
+            "ts_ms": ts_ms,
+        }
+    });
+    SourceMessage {
+        payload: Some(Bytes::from(payload.to_string())),
+        offset,
+        split_id: split_id.to_string(),
+        meta: SourceMeta {
+            timestamp: Some(ts_ms),
+            ..Default::default()
+        },
+    }
+}
+
+/// A part of the snapshot of a table. The table is read in the order of the primary key, and if
+/// the source recovers before it's completed, the rest of the rows are read in a new part from a
+/// new snapshot of the database, rather than read again from the start.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub struct SnapshotPart<P> {
+    /// The position of the database the part is read at, whose changes before it are in the
+    /// part and must not be emitted again for its rows.
+    pub position: P,
+    /// The primary key in JSON of the last row read in the part, which has the rows after the
+    /// ones of the previous part up to it. The last part has all the rows after the previous ones.
+    pub last_pk: Option<String>,
+}
+
+impl<P> SnapshotPart<P> {
+    pub fn new(position: P) -> Self {
+        Self {
+            position,
+            last_pk: None,
+        }
+    }
+}
+
+/// The collation of a column of the primary key, in which its strings are compared as the snapshot
+/// is ordered. The default compares them by their bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Collation {
+    pub case_insensitive: bool,
+    /// The trailing spaces are ignored.
+    pub pad_space: bool,
+}
+
+impl Collation {
+    /// Returns the collation of a `MySQL` column, e.g. `utf8mb4_0900_ai_ci`. The binary ones
+    /// compare the bytes, and the others are approximated by comparing the uppercase, as the
+    /// `_general_ci` ones do. All of them pad spaces except `binary` and the ones of UCA 9.0.0,
+    /// i.e. `_0900_`.
+    pub fn from_mysql(name: &str) -> Self {
+        Self {
+            case_insensitive: name != "binary" && !name.ends_with("_bin"),
+            pad_space: name != "binary" && !name.contains("_0900_"),
+        }
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        let (a, b) = if self.pad_space {
+            (a.trim_end_matches(' '), b.trim_end_matches(' '))
+        } else {
+            (a, b)
+        };
+        if self.case_insensitive {
+            a.chars()
+                .flat_map(char::to_uppercase)
+                .cmp(b.chars().flat_map(char::to_uppercase))
+        } else {
+            a.as_bytes().cmp(b.as_bytes())
+        }
+    }
+}
+
+/// Returns the part having the row of the primary key, whose strings are compared in the
+/// collations of the columns, which are binary if missing.
+pub(crate) fn snapshot_part_of<'a, P>(
+    parts: &'a [SnapshotPart<P>],
+    pk: &[serde_json::Value],
+    collations: &[Collation],
+) -> Result<&'a SnapshotPart<P>> {
+    for part in &parts[..parts.len().saturating_sub(1)] {
+        let last_pk = decode_pk(part.last_pk.as_deref())?;
+        if compare_pk(pk, &last_pk, collations) != Ordering::Greater {
+            return Ok(part);
+        }
+    }
+    parts
+        .last()
+        .ok_or_else(|| anyhow!("the snapshot has no parts"))
+}
+
+/// Decodes the primary key of a part, which is empty if no row is read in it.
+pub(crate) fn decode_pk(pk: Option<&str>) -> Result<Vec<serde_json::Value>> {
+    match pk {
+        Some(pk) => serde_json::from_str(pk).map_err(|e| anyhow!("invalid primary key: {}", e)),
+        None => Ok(vec![]),
+    }
+}
+
+/// Returns the primary key of a row in JSON.
+pub(crate) fn row_pk(row: &serde_json::Value, pk_columns: &[String]) -> Vec<serde_json::Value> {
+    pk_columns
+        .iter()
+        .map(|column| row.get(column).cloned().unwrap_or_default())
+        .collect()
+}
+
+/// Compares the primary keys in the order the snapshot is read, in which the numbers are compared
+/// by their values, and the strings in the collations of the columns, which are binary if missing.
+pub(crate) fn compare_pk(
+    a: &[serde_json::Value],
+    b: &[serde_json::Value],
+    collations: &[Collation],
+) -> Ordering {
+    use serde_json::Value;
+
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) | Value::Object(_) => 4,
+        }
+    }
+
+    // The keys are compared up to the shorter one, which is the less if it's a prefix of the other.
+    #[allow(clippy::disallowed_methods)]
+    for (i, (a, b)) in a.iter().zip(b).enumerate() {
+        let ordering = match (a, b) {
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => match (a.as_u64(), b.as_u64()) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    _ => a
+                        .as_f64()
+                        .partial_cmp(&b.as_f64())
+                        .unwrap_or(Ordering::Equal),
+                },
+            },
+            (Value::String(a), Value::String(b)) => {
+                collations.get(i).copied().unwrap_or_default().compare(a, b)
+            }
+            (a, b) => rank(a).cmp(&rank(b)),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// A change in the Debezium JSON format, of the op and the rows before and after it.
+pub(crate) type Change = (
+    &'static str,
+    Option<serde_json::Value>,
+    Option<serde_json::Value>,
+);
+
+/// Removes the rows of a change which are already in the snapshot, as decided by `in_snapshot`,
+/// and returns the change of the rest, e.g. the update of a row whose new row is in the snapshot
+/// becomes the delete of the old row. Returns `None` if both rows are in the snapshot.
+pub(crate) fn exclude_snapshot_rows(
+    op: &'static str,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    mut in_snapshot: impl FnMut(&serde_json::Value) -> Result<bool>,
+) -> Result<Option<Change>> {
+    let before_kept = match &before {
+        Some(before) => Some(!in_snapshot(before)?),
+        None => None,
+    };
+    let after_kept = match &after {
+        Some(after) => Some(!in_snapshot(after)?),
+        None => None,
+    };
+    let change = match (before_kept, after_kept) {
+        (Some(true), Some(true)) | (None, Some(true)) | (Some(true), None) => {
+            Some((op, before, after))
+        }
+        (Some(true), Some(false)) => Some(("d", before, None)),
+        (Some(false), Some(true)) => Some(("c", None, after)),
+        _ => None,
+    };
+    Ok(change)
+}
+
+/// Batches the messages of the rows read in the snapshot. The last message is kept until the end,
+/// when it's given the offset marking the snapshot as completed.
+pub(crate) struct SnapshotBatcher {
+    split_id: String,
+    ts_ms: i64,
+    batch_size: usize,
+    batch: Vec<SourceMessage>,
+}
+
+impl SnapshotBatcher {
+    pub fn new(split_id: &str, batch_size: usize) -> Self {
+        Self {
+            split_id: split_id.to_string(),
+            ts_ms: chrono::Utc::now().timestamp_millis(),
+            batch_size,
+            batch: vec![],
+        }
+    }
+
+    /// Adds the row with the offset after it, and returns the batch to emit once it's full.
+    pub fn push(&mut self, row: serde_json::Value, offset: String) -> Option<Vec<SourceMessage>> {
+        self.batch.push(debezium_json_message(
+            "r",
+            None,
+            Some(row),
+            self.ts_ms,
+            &self.split_id,
+            offset,
+        ));
+        if self.batch.len() > self.batch_size {
+            let last = self.batch.pop().unwrap();
+            Some(std::mem::replace(&mut self.batch, vec![last]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rest of the messages, the last of which is given the offset of the completed
+    /// snapshot.
+    pub fn finish(mut self, offset: String) -> Option<Vec<SourceMessage>> {
+        self.batch.last_mut()?.offset = offset;
+        Some(self.batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_compare_pk() {
+        assert_eq!(compare_pk(&[json!(1)], &[json!(2)], &[]), Ordering::Less);
+        assert_eq!(
+            compare_pk(&[json!(-1)], &[json!(u64::MAX)], &[]),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_pk(&[json!(1.5)], &[json!(1)], &[]),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_pk(&[json!("B")], &[json!("a")], &[]),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_pk(&[json!(1), json!("b")], &[json!(1), json!("a")], &[]),
+            Ordering::Greater
+        );
+        assert_eq!(compare_pk(&[json!(1)], &[json!(1)], &[]), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_collation() {
+        let compare = |collation, a: &str, b: &str| {
+            compare_pk(
+                &[json!(a)],
+                &[json!(b)],
+                &[Collation::from_mysql(collation)],
+            )
+        };
+        assert_eq!(compare("utf8mb4_general_ci", "B", "a"), Ordering::Greater);
+        assert_eq!(compare("utf8mb4_general_ci", "a ", "A"), Ordering::Equal);
+        assert_eq!(compare("utf8mb4_0900_ai_ci", "a ", "A"), Ordering::Greater);
+        assert_eq!(compare("utf8mb4_bin", "B", "a"), Ordering::Less);
+        assert_eq!(compare("utf8mb4_bin", "a ", "a"), Ordering::Equal);
+        assert_eq!(compare("binary", "a ", "a"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_snapshot_part_of() {
+        let parts = vec![
+            SnapshotPart {
+                position: 1,
+                last_pk: Some("[2]".to_string()),
+            },
+            SnapshotPart {
+                position: 2,
+                last_pk: Some("[4]".to_string()),
+            },
+            SnapshotPart::new(3),
+        ];
+        let position = |pk| {
+            snapshot_part_of(&parts, &[json!(pk)], &[])
+                .unwrap()
+                .position
+        };
+        assert_eq!(position(1), 1);
+        assert_eq!(position(2), 1);
+        assert_eq!(position(3), 2);
+        assert_eq!(position(5), 3);
+        assert!(snapshot_part_of::<u64>(&[], &[json!(1)], &[]).is_err());
+    }
+
+    #[test]
+    fn test_exclude_snapshot_rows() {
+        // The rows with the keys greater than 1 are in the snapshot.
+        let in_snapshot = |row: &serde_json::Value| Ok(row["id"].as_i64().unwrap() > 1);
+        let row = |id| Some(json!({ "id": id }));
+        let exclude =
+            |op, before, after| exclude_snapshot_rows(op, before, after, in_snapshot).unwrap();
+
+        assert_eq!(exclude("c", None, row(1)), Some(("c", None, row(1))));
+        assert_eq!(exclude("c", None, row(2)), None);
+        assert_eq!(exclude("u", row(1), row(1)), Some(("u", row(1), row(1))));
+        assert_eq!(exclude("u", None, row(1)), Some(("u", None, row(1))));
+        assert_eq!(exclude("u", row(1), row(2)), Some(("d", row(1), None)));
+        assert_eq!(exclude("u", row(2), row(1)), Some(("c", None, row(1))));
+        assert_eq!(exclude("u", row(2), row(3)), None);
+        assert_eq!(exclude("d", row(1), None), Some(("d", row(1), None)));
+        assert_eq!(exclude("d", row(2), None), None);
+    }
+
+    #[test]
+    fn test_snapshot_batcher() {
+        let mut batcher = SnapshotBatcher::new("t", 2);
+        assert!(batcher.push(json!({ "id": 1 }), "1".to_string()).is_none());
+        assert!(batcher.push(json!({ "id": 2 }), "2".to_string()).is_none());
+        let batch = batcher.push(json!({ "id": 3 }), "3".to_string()).unwrap();
+        assert_eq!(
+            batch.iter().map(|m| m.offset.as_str()).collect::<Vec<_>>(),
+            vec!["1", "2"]
+        );
+        let batch = batcher.finish("done".to_string()).unwrap();
+        assert_eq!(
+            batch.iter().map(|m| m.offset.as_str()).collect::<Vec<_>>(),
+            vec!["done"]
+        );
+        assert!(SnapshotBatcher::new("t", 2)
+            .finish("done".to_string())
+            .is_none());
+    }
+}
@@ -20,7 +20,7 @@ use serde_json::{Map, Value};
 use tokio::time::{sleep, Duration, Instant};
 
 use super::DEFAULT_DATAGEN_INTERVAL;
-use crate::source::SourceMessage;
+use crate::source::{SourceMessage, SourceMeta};
 
 pub struct DatagenEventGenerator {
     pub fields_map: HashMap<String, FieldGeneratorImpl>,
@@ -74,6 +74,7 @@ impl DatagenEventGenerator {
                 payload: Some(Bytes::from(value.to_string())),
                 offset: offset.to_string(),
                 split_id: self.split_id.clone(),
+                meta: SourceMeta::default(),
             };
             generated_count += 1;
             res.push(msg);
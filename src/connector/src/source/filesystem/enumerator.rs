@@ -0,0 +1,64 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use globset::{Glob, GlobMatcher};
+use risingwave_object_store::object::{ObjectStore, ObjectStoreImpl};
+
+use crate::source::filesystem::{FsProperties, FsSplit};
+use crate::source::SplitEnumerator;
+
+/// Lists the files in the object store whose paths match the pattern. Each file is a split, so
+/// the files added later are discovered as new splits.
+pub struct FsSplitEnumerator<P> {
+    store: ObjectStoreImpl,
+    matcher: Option<GlobMatcher>,
+    _phantom: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: FsProperties> SplitEnumerator for FsSplitEnumerator<P> {
+    type Properties = P;
+    type Split = FsSplit;
+
+    async fn new(properties: P) -> Result<Self> {
+        let matcher = properties
+            .match_pattern()
+            .map(|pattern| Glob::new(pattern).map(|glob| glob.compile_matcher()))
+            .transpose()?;
+        Ok(Self {
+            store: properties.new_object_store().await?,
+            matcher,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn list_splits(&mut self) -> Result<Vec<FsSplit>> {
+        Ok(self
+            .store
+            .list("")
+            .await?
+            .into_iter()
+            .filter(|object| {
+                self.matcher
+                    .as_ref()
+                    .map_or(true, |matcher| matcher.is_match(&object.key))
+            })
+            .map(|object| FsSplit::new(object.key, 0, object.total_size))
+            .collect())
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use risingwave_object_store::object::object_metrics::ObjectStoreMetrics;
+use risingwave_object_store::object::{parse_remote_object_store, ObjectStoreImpl};
+use serde::Deserialize;
+
+use crate::source::filesystem::FsProperties;
+
+pub const LOCAL_FS_CONNECTOR: &str = "local_fs";
+
+/// Reads the files under a directory of the local disk, in the same way as the S3 source. It's
+/// mostly used in tests.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LocalFsProperties {
+    #[serde(rename = "local_fs.root")]
+    pub root: String,
+    #[serde(rename = "match_pattern", default)]
+    pub match_pattern: Option<String>,
+}
+
+#[async_trait]
+impl FsProperties for LocalFsProperties {
+    async fn new_object_store(&self) -> Result<ObjectStoreImpl> {
+        Ok(parse_remote_object_store(
+            &format!("disk://{}", self.root),
+            Arc::new(ObjectStoreMetrics::unused()),
+        )
+        .await)
+    }
+
+    fn match_pattern(&self) -> Option<&str> {
+        self.match_pattern.as_deref()
+    }
+}
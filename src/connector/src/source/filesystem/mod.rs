@@ -11,5 +11,94 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+mod enumerator;
 mod file_common;
+pub mod local_fs;
+mod reader;
 pub mod s3;
+mod split;
+
+use anyhow::Result;
+use async_trait::async_trait;
+pub use enumerator::FsSplitEnumerator;
+pub use reader::FsSplitReader;
+use risingwave_object_store::object::ObjectStoreImpl;
+pub use split::FsSplit;
+
+/// The properties of a source reading the files in an object store, e.g. S3.
+#[async_trait]
+pub trait FsProperties: Send + Sync + 'static {
+    async fn new_object_store(&self) -> Result<ObjectStoreImpl>;
+
+    /// The glob that the paths of the files to read must match. All files are read if it's not
+    /// set.
+    fn match_pattern(&self) -> Option<&str>;
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+
+    use super::local_fs::LocalFsProperties;
+    use super::*;
+    use crate::source::{ConnectorProperties, SplitEnumerator, SplitImpl, SplitReader};
+
+    #[tokio::test]
+    async fn test_local_fs_source() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("data")).unwrap();
+        std::fs::write(dir.path().join("data/1.csv"), "1,a\n2,b\n").unwrap();
+        std::fs::write(dir.path().join("data/2.csv"), "3,c\n").unwrap();
+        std::fs::write(dir.path().join("data/3.json"), "{}").unwrap();
+
+        let props = ConnectorProperties::extract(convert_args!(hashmap!(
+            "connector" => "local_fs",
+            "local_fs.root" => dir.path().to_str().unwrap(),
+            "match_pattern" => "data/*.csv",
+        )))
+        .unwrap();
+        let props = match props {
+            ConnectorProperties::LocalFs(props) => props,
+            _ => panic!("extract local_fs config failed"),
+        };
+
+        let mut enumerator = FsSplitEnumerator::new(props.clone()).await.unwrap();
+        let splits = enumerator.list_splits().await.unwrap();
+        assert_eq!(
+            splits,
+            vec![
+                FsSplit::new("data/1.csv".to_string(), 0, 8),
+                FsSplit::new("data/2.csv".to_string(), 0, 4),
+            ]
+        );
+
+        let mut reader = FsSplitReader::<LocalFsProperties>::new(
+            props.clone(),
+            Some(vec![SplitImpl::LocalFs(splits[0].clone())]),
+            None,
+        )
+        .await
+        .unwrap();
+        let messages = reader.next().await.unwrap().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].payload.as_deref(), Some(b"1,a\n2,b\n".as_ref()));
+        assert_eq!(messages[0].offset, "8");
+        assert_eq!(messages[0].split_id, "data/1.csv");
+
+        // The file is not read again once it's read.
+        let mut reader = FsSplitReader::<LocalFsProperties>::new(
+            props,
+            Some(vec![SplitImpl::LocalFs(
+                splits[0].copy_with_offset("8".to_string()).unwrap(),
+            )]),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), reader.next())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::BytesMut;
+use risingwave_object_store::object::{BlockLocation, ObjectStore, ObjectStoreImpl};
+
+use crate::source::filesystem::{FsProperties, FsSplit};
+use crate::source::{
+    Column, ConnectorState, SourceMessage, SourceMeta, SplitImpl, SplitMetaData, SplitReader,
+};
+
+/// The number of bytes read from a file at a time.
+const READ_CHUNK_SIZE: usize = 1 << 20;
+
+/// Reads the assigned file in chunks of about [`READ_CHUNK_SIZE`] bytes, each of which ends at a
+/// line break, so that a message never ends in the middle of a record unless a record spans lines.
+/// The offset is the number of bytes that have been read, from which the file is read again after
+/// the recovery.
+pub struct FsSplitReader<P> {
+    store: ObjectStoreImpl,
+    split: FsSplit,
+    chunk_size: usize,
+    _phantom: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: FsProperties> SplitReader for FsSplitReader<P> {
+    type Properties = P;
+
+    async fn new(
+        properties: P,
+        state: ConnectorState,
+        _columns: Option<Vec<Column>>,
+    ) -> Result<Self> {
+        let split = match state.and_then(|splits| splits.into_iter().next()) {
+            Some(SplitImpl::S3(split) | SplitImpl::LocalFs(split)) => split,
+            split => return Err(anyhow!("expect a file split, but found {:?}", split)),
+        };
+        Ok(Self {
+            store: properties.new_object_store().await?,
+            split,
+            chunk_size: READ_CHUNK_SIZE,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn next(&mut self) -> Result<Option<Vec<SourceMessage>>> {
+        if self.split.offset >= self.split.size {
+            // Files are never appended to, so there is nothing more to read.
+            return futures::future::pending().await;
+        }
+
+        let start = self.split.offset;
+        let payload = self.read_lines(start).await?;
+        self.split.offset = start + payload.len();
+        Ok(Some(vec![SourceMessage {
+            payload: Some(payload.freeze()),
+            offset: self.split.offset.to_string(),
+            split_id: self.split.id(),
+            meta: SourceMeta {
+                continued: start > 0,
+                ..Default::default()
+            },
+        }]))
+    }
+}
+
+impl<P> FsSplitReader<P> {
+    /// Reads the lines from `start` up to the last line break within a chunk. A line longer than a
+    /// chunk is read as a whole, and the last line of the file may have no line break.
+    async fn read_lines(&self, start: usize) -> Result<BytesMut> {
+        let mut buf = BytesMut::new();
+        let mut end = start;
+        loop {
+            let block_end = (end + self.chunk_size).min(self.split.size);
+            let block = self
+                .store
+                .read(
+                    &self.split.path,
+                    Some(BlockLocation {
+                        offset: end,
+                        size: block_end - end,
+                    }),
+                )
+                .await?;
+            let searched = buf.len();
+            buf.extend_from_slice(&block);
+            end = block_end;
+            if end >= self.split.size {
+                return Ok(buf);
+            }
+            if let Some(pos) = buf[searched..].iter().rposition(|&b| b == b'\n') {
+                // The bytes after the line break are read again with the next chunk.
+                buf.truncate(searched + pos + 1);
+                return Ok(buf);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::filesystem::local_fs::LocalFsProperties;
+
+    #[tokio::test]
+    async fn test_read_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "1,a\n22,bb\n333,ccc\n4,d\n55,ee";
+        std::fs::write(dir.path().join("1.csv"), content).unwrap();
+        let props = LocalFsProperties {
+            root: dir.path().to_str().unwrap().to_string(),
+            match_pattern: None,
+        };
+        let split = FsSplit::new("1.csv".to_string(), 0, content.len());
+
+        let mut reader = FsSplitReader::<LocalFsProperties>::new(
+            props.clone(),
+            Some(vec![SplitImpl::LocalFs(split.clone())]),
+            None,
+        )
+        .await
+        .unwrap();
+        reader.chunk_size = 6;
+        let mut chunks = vec![];
+        while reader.split.offset < reader.split.size {
+            let message = reader.next().await.unwrap().unwrap().remove(0);
+            chunks.push((
+                String::from_utf8(message.payload.unwrap().to_vec()).unwrap(),
+                message.offset,
+                message.meta.continued,
+            ));
+        }
+        assert_eq!(
+            chunks,
+            vec![
+                ("1,a\n".to_string(), "4".to_string(), false),
+                ("22,bb\n".to_string(), "10".to_string(), true),
+                // A chunk is extended to the next line break.
+                ("333,ccc\n4,d\n".to_string(), "22".to_string(), true),
+                ("55,ee".to_string(), "27".to_string(), true),
+            ]
+        );
+
+        // The file is read from the middle after the recovery.
+        let mut reader = FsSplitReader::<LocalFsProperties>::new(
+            props,
+            Some(vec![SplitImpl::LocalFs(
+                split.copy_with_offset("10".to_string()).unwrap(),
+            )]),
+            None,
+        )
+        .await
+        .unwrap();
+        let message = reader.next().await.unwrap().unwrap().remove(0);
+        assert_eq!(
+            message.payload.as_deref(),
+            Some(b"333,ccc\n4,d\n55,ee".as_ref())
+        );
+        assert_eq!(message.offset, "27");
+
+        assert!(split.copy_with_offset("abc".to_string()).is_err());
+    }
+}
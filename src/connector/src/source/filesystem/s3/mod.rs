@@ -15,8 +15,17 @@ mod s3_dir;
 mod s3_notification_event;
 mod source;
 
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use risingwave_object_store::object::object_metrics::ObjectStoreMetrics;
+use risingwave_object_store::object::{ObjectStore, ObjectStoreImpl, S3ObjectStore};
 use serde::Deserialize;
 
+use crate::aws_utils::{default_conn_config, s3_client, AwsConfigV2, AwsCredentialV2};
+use crate::source::filesystem::FsProperties;
+
 pub const S3_CONNECTOR: &str = "s3";
 
 #[derive(Clone, Debug, Deserialize)]
@@ -25,7 +34,8 @@ pub struct S3Properties {
     pub region_name: String,
     #[serde(rename = "s3.bucket_name")]
     pub bucket_name: String,
-    #[serde(rename = "sqs_queue_name")]
+    /// Only used by the reader notified of new files by SQS.
+    #[serde(rename = "sqs_queue_name", default)]
     pub sqs_queue_name: String,
     #[serde(rename = "match_pattern", default)]
     pub match_pattern: Option<String>,
@@ -34,3 +44,36 @@ pub struct S3Properties {
     #[serde(rename = "s3.credentials.secret", default)]
     pub secret: String,
 }
+
+#[async_trait]
+impl FsProperties for S3Properties {
+    async fn new_object_store(&self) -> Result<ObjectStoreImpl> {
+        let credential = if self.access.is_empty() || self.secret.is_empty() {
+            AwsCredentialV2::None
+        } else {
+            AwsCredentialV2::Static {
+                access_key: self.access.clone(),
+                secret_access: self.secret.clone(),
+                session_token: None,
+            }
+        };
+        let aws_config = AwsConfigV2 {
+            region: Some(self.region_name.clone()),
+            arn: None,
+            credential,
+            endpoint: None,
+        };
+        let client = s3_client(
+            &aws_config.load_config(None).await,
+            Some(default_conn_config()),
+        );
+        Ok(ObjectStoreImpl::S3(
+            S3ObjectStore::new_with_client(client, self.bucket_name.clone())
+                .monitored(Arc::new(ObjectStoreMetrics::unused())),
+        ))
+    }
+
+    fn match_pattern(&self) -> Option<&str> {
+        self.match_pattern.as_deref()
+    }
+}
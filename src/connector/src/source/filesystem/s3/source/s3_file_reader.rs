@@ -33,7 +33,7 @@ use tokio_util::io;
 use tokio_util::io::ReaderStream;
 
 use crate::aws_utils::{default_conn_config, s3_client, AwsConfigV2, AwsCredentialV2};
-use crate::source::base::{SourceMessage, SplitReader};
+use crate::source::base::{SourceMessage, SourceMeta, SplitReader};
 use crate::source::filesystem::file_common::{EntryStat, StatusWatch};
 use crate::source::filesystem::s3::s3_dir::FileSystemOptError::IllegalS3FilePath;
 use crate::source::filesystem::s3::s3_dir::{
@@ -364,6 +364,7 @@ impl SplitReader for S3FileReader {
                         payload: Some(msg.payload),
                         offset: new_offset.to_string(),
                         split_id: msg_id,
+                        meta: SourceMeta::default(),
                     }
                 })
                 .collect_vec(),
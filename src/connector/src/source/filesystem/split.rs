@@ -0,0 +1,62 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::anyhow;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::source::base::SplitMetaData;
+
+/// A file to be read from an object store.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Hash)]
+pub struct FsSplit {
+    /// The path of the file relative to the root of the object store.
+    pub path: String,
+    /// The number of bytes that have been read, which always ends at a line break or the end of
+    /// the file.
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl SplitMetaData for FsSplit {
+    fn id(&self) -> String {
+        self.path.clone()
+    }
+
+    fn encode_to_bytes(&self) -> Bytes {
+        Bytes::from(serde_json::to_string(self).unwrap())
+    }
+
+    fn restore_from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| anyhow!(e))
+    }
+}
+
+impl FsSplit {
+    pub fn new(path: String, offset: usize, size: usize) -> Self {
+        Self { path, offset, size }
+    }
+
+    pub fn copy_with_offset(&self, start_offset: String) -> anyhow::Result<Self> {
+        let offset = start_offset.parse().map_err(|e| {
+            anyhow!(
+                "invalid offset {:?} of file {}: {}",
+                start_offset,
+                self.path,
+                e
+            )
+        })?;
+        Ok(Self::new(self.path.clone(), offset, self.size))
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -39,6 +39,10 @@ pub struct KafkaSplitEnumerator {
     admin_client: BaseConsumer,
     start_offset: KafkaEnumeratorOffset,
 
+    /// The partitions of the last listing. The ones added to the topic afterwards are read from
+    /// the earliest offset, so that the messages before they're discovered are not skipped.
+    known_partitions: Option<HashSet<i32>>,
+
     // maybe used in the future for batch processing
     stop_offset: KafkaEnumeratorOffset,
 }
@@ -51,6 +55,7 @@ impl SplitEnumerator for KafkaSplitEnumerator {
     type Split = KafkaSplit;
 
     async fn new(properties: KafkaProperties) -> anyhow::Result<KafkaSplitEnumerator> {
+        properties.security.validate()?;
         let broker_address = properties.brokers;
         let topic = properties.topic;
 
@@ -74,8 +79,10 @@ impl SplitEnumerator for KafkaSplitEnumerator {
             scan_start_offset = KafkaEnumeratorOffset::Timestamp(time_offset)
         }
 
-        let client: BaseConsumer = rdkafka::ClientConfig::new()
-            .set("bootstrap.servers", &broker_address)
+        let mut config = rdkafka::ClientConfig::new();
+        config.set("bootstrap.servers", &broker_address);
+        properties.security.set_client(&mut config);
+        let client: BaseConsumer = config
             .create_with_context(DefaultConsumerContext)
             .map_err(|e| anyhow!(e))?;
 
@@ -84,6 +91,7 @@ impl SplitEnumerator for KafkaSplitEnumerator {
             topic,
             admin_client: client,
             start_offset: scan_start_offset,
+            known_partitions: None,
             stop_offset: KafkaEnumeratorOffset::None,
         })
     }
@@ -101,6 +109,19 @@ impl SplitEnumerator for KafkaSplitEnumerator {
             .fetch_start_offset(topic_partitions.as_ref())
             .map_err(|e| anyhow!("{}", e))?;
 
+        if let Some(known_partitions) = &self.known_partitions {
+            for partition in &topic_partitions {
+                if !known_partitions.contains(partition) {
+                    let (low, _) = self
+                        .admin_client
+                        .fetch_watermarks(self.topic.as_str(), *partition, KAFKA_SYNC_CALL_TIMEOUT)
+                        .map_err(|e| anyhow!("{}", e))?;
+                    start_offsets.insert(*partition, Some(low));
+                }
+            }
+        }
+        self.known_partitions = Some(topic_partitions.iter().copied().collect());
+
         let mut stop_offsets = self
             .fetch_stop_offset(topic_partitions.as_ref())
             .map_err(|e| anyhow!("{}", e))?;
@@ -120,6 +141,19 @@ impl SplitEnumerator for KafkaSplitEnumerator {
 }
 
 impl KafkaSplitEnumerator {
+    /// Returns the offsets of the next messages to be written to the partitions.
+    pub fn fetch_high_watermarks(&self, partitions: &[i32]) -> anyhow::Result<HashMap<i32, i64>> {
+        partitions
+            .iter()
+            .map(|partition| {
+                self.admin_client
+                    .fetch_watermarks(self.topic.as_str(), *partition, KAFKA_SYNC_CALL_TIMEOUT)
+                    .map(|watermark| (*partition, watermark.1))
+                    .map_err(|e| anyhow!(e))
+            })
+            .collect()
+    }
+
     fn fetch_stop_offset(&self, partitions: &[i32]) -> KafkaResult<HashMap<i32, Option<i64>>> {
         match self.stop_offset {
             KafkaEnumeratorOffset::Earliest => unreachable!(),
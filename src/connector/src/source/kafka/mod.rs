@@ -16,6 +16,8 @@ use std::time::Duration;
 
 use serde::Deserialize;
 
+use crate::common::KafkaSecurityProperties;
+
 pub mod enumerator;
 pub mod source;
 pub mod split;
@@ -42,6 +44,9 @@ pub struct KafkaProperties {
 
     #[serde(rename = "properties.group.id", alias = "kafka.consumer.group")]
     pub consumer_group: Option<String>,
+
+    #[serde(flatten)]
+    pub security: KafkaSecurityProperties,
 }
 
 const KAFKA_SYNC_CALL_TIMEOUT: Duration = Duration::from_secs(1);
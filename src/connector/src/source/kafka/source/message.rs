@@ -13,10 +13,10 @@
 // limitations under the License.
 
 use bytes::Bytes;
-use rdkafka::message::BorrowedMessage;
+use rdkafka::message::{BorrowedMessage, Headers};
 use rdkafka::Message;
 
-use crate::source::base::SourceMessage;
+use crate::source::base::{SourceMessage, SourceMeta};
 
 impl<'a> From<BorrowedMessage<'a>> for SourceMessage {
     fn from(message: BorrowedMessage<'a>) -> Self {
@@ -25,6 +25,20 @@ impl<'a> From<BorrowedMessage<'a>> for SourceMessage {
             payload: message.payload().map(Bytes::copy_from_slice),
             offset: message.offset().to_string(),
             split_id: message.partition().to_string(),
+            meta: SourceMeta {
+                key: message.key().map(Bytes::copy_from_slice),
+                timestamp: message.timestamp().to_millis(),
+                headers: message
+                    .headers()
+                    .map(|headers| {
+                        (0..headers.count())
+                            .filter_map(|i| headers.get(i))
+                            .map(|(name, value)| (name.to_string(), Bytes::copy_from_slice(value)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
         }
     }
 }
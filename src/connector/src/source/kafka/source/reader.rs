@@ -47,9 +47,11 @@ impl SplitReader for KafkaSplitReader {
     where
         Self: Sized,
     {
+        properties.security.validate()?;
         let bootstrap_servers = properties.brokers;
 
         let mut config = ClientConfig::new();
+        properties.security.set_client(&mut config);
 
         // disable partition eof
         config.set("enable.partition.eof", "false");
@@ -55,16 +55,20 @@ impl KafkaSplit {
         }
     }
 
-    pub fn copy_with_offset(&self, start_offset: String) -> Self {
-        Self::new(
+    pub fn copy_with_offset(&self, start_offset: String) -> anyhow::Result<Self> {
+        Ok(Self::new(
             self.partition,
-            Some(start_offset.as_str().parse::<i64>().unwrap()),
+            Some(start_offset.as_str().parse::<i64>()?),
             self.stop_offset,
             self.topic.clone(),
-        )
+        ))
     }
 
     pub fn get_topic_and_partition(&self) -> (String, i32) {
         (self.topic.clone(), self.partition)
     }
+
+    pub fn start_offset(&self) -> Option<i64> {
+        self.start_offset
+    }
 }
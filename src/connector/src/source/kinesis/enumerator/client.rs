@@ -73,7 +73,10 @@ impl SplitEnumerator for KinesisSplitEnumerator {
             .into_iter()
             .map(|x| KinesisSplit {
                 shard_id: x.shard_id().unwrap_or_default().to_string(),
-                start_position: KinesisOffset::None,
+                // The shards created by resharding are discovered later, which are read from the
+                // beginning as well. The closed ones are listed until they expire, and their
+                // readers stop after reading them to the end.
+                start_position: KinesisOffset::Earliest,
                 end_position: KinesisOffset::None,
             })
             .collect())
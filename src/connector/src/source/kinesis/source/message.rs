@@ -16,7 +16,7 @@ use aws_sdk_kinesis::model::Record;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 
-use crate::source::SourceMessage;
+use crate::source::{SourceMessage, SourceMeta};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct KinesisMessage {
@@ -24,6 +24,8 @@ pub struct KinesisMessage {
     pub sequence_number: String,
     pub partition_key: String,
     pub payload: Option<Vec<u8>>,
+    /// The approximate milliseconds since the epoch when the record arrived at the stream.
+    pub timestamp: Option<i64>,
 }
 
 impl From<KinesisMessage> for SourceMessage {
@@ -35,6 +37,12 @@ impl From<KinesisMessage> for SourceMessage {
                 .map(|payload| Bytes::copy_from_slice(payload)),
             offset: msg.sequence_number.clone(),
             split_id: msg.shard_id,
+            meta: SourceMeta {
+                key: Some(Bytes::from(msg.partition_key)),
+                timestamp: msg.timestamp,
+                headers: vec![],
+                ..Default::default()
+            },
         }
     }
 }
@@ -46,6 +54,9 @@ impl KinesisMessage {
             sequence_number: message.sequence_number.unwrap(),
             partition_key: message.partition_key.unwrap(),
             payload: Some(message.data.unwrap().into_inner()),
+            timestamp: message
+                .approximate_arrival_timestamp
+                .and_then(|timestamp| timestamp.to_millis().ok()),
         }
     }
 }
@@ -76,7 +76,8 @@ impl KinesisSplitReader {
         })
     }
 
-    pub async fn next(&mut self) -> Result<Vec<SourceMessage>> {
+    /// Returns `None` once the shard is closed by resharding and all of its records are read.
+    pub async fn next(&mut self) -> Result<Option<Vec<SourceMessage>>> {
         if self.shard_iter.is_none() {
             self.new_shard_iter().await?;
         }
@@ -97,11 +98,15 @@ impl KinesisSplitReader {
                         })
                         .collect::<Vec<SourceMessage>>();
                     if chunk.is_empty() {
+                        // The shard iterator is absent after the end of a closed shard.
+                        if self.shard_iter.is_none() {
+                            return Ok(None);
+                        }
                         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
                         continue;
                     }
                     self.latest_offset = Some(chunk.last().unwrap().offset.clone());
-                    return Ok(chunk);
+                    return Ok(Some(chunk));
                 }
                 Err(e) => match e {
                     SdkError::ServiceError { err, .. } if err.is_expired_iterator_exception() => {
@@ -118,7 +123,7 @@ impl KinesisSplitReader {
     async fn new_shard_iter(&mut self) -> Result<()> {
         let (starting_seq_num, iter_type) = if self.latest_offset.is_some() {
             (
-                self.latest_offset.take(),
+                self.latest_offset.clone(),
                 ShardIteratorType::AfterSequenceNumber,
             )
         } else {
@@ -161,7 +166,11 @@ impl KinesisSplitReader {
 async fn split_reader_into_stream(mut reader: KinesisSplitReader) {
     loop {
         match reader.next().await {
-            Ok(chunk) => yield chunk,
+            Ok(Some(chunk)) => yield chunk,
+            Ok(None) => {
+                log::info!("kinesis shard {} is closed and fully read", reader.shard_id);
+                break;
+            }
             Err(e) => {
                 log::error!("hang up kinesis reader due to polling error: {}", e);
                 drop(reader);
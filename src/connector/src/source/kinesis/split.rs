@@ -61,16 +61,16 @@ impl KinesisSplit {
         }
     }
 
-    pub fn copy_with_offset(&self, start_offset: String) -> Self {
+    pub fn copy_with_offset(&self, start_offset: String) -> anyhow::Result<Self> {
         let start_offset = if start_offset.is_empty() {
             KinesisOffset::Earliest
         } else {
             KinesisOffset::SequenceNumber(start_offset)
         };
-        Self::new(
+        Ok(Self::new(
             self.shard_id.clone(),
             start_offset,
             self.end_position.clone(),
-        )
+        ))
     }
 }
@@ -0,0 +1,74 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+
+use crate::source::mysql_cdc::{MySqlCdcProperties, MySqlCdcSplit};
+use crate::source::SplitEnumerator;
+
+/// Checks the binlog settings of the server and the table on creation, and lists the table as the
+/// only split.
+#[derive(Debug)]
+pub struct MySqlCdcSplitEnumerator {
+    table: String,
+}
+
+#[async_trait]
+impl SplitEnumerator for MySqlCdcSplitEnumerator {
+    type Properties = MySqlCdcProperties;
+    type Split = MySqlCdcSplit;
+
+    async fn new(properties: MySqlCdcProperties) -> Result<Self> {
+        properties.server_id()?;
+        let mut conn = properties.connect().await?;
+
+        let (log_bin, binlog_format, binlog_row_image): (bool, String, String) = conn
+            .query_first("SELECT @@log_bin, @@binlog_format, @@binlog_row_image")
+            .await?
+            .ok_or_else(|| anyhow!("failed to read the binlog settings"))?;
+        if !log_bin {
+            return Err(anyhow!("the binlog is not enabled on the MySQL server"));
+        }
+        if !binlog_format.eq_ignore_ascii_case("ROW")
+            || !binlog_row_image.eq_ignore_ascii_case("FULL")
+        {
+            return Err(anyhow!(
+                "binlog_format must be ROW and binlog_row_image must be FULL, found: {} and {}",
+                binlog_format,
+                binlog_row_image
+            ));
+        }
+
+        let exists: Option<u8> = conn
+            .exec_first(
+                "SELECT 1 FROM information_schema.TABLES WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?",
+                (&properties.database_name, &properties.table_name),
+            )
+            .await?;
+        if exists.is_none() {
+            return Err(anyhow!("table {} doesn't exist", properties.table()));
+        }
+        conn.disconnect().await?;
+
+        Ok(Self {
+            table: properties.table(),
+        })
+    }
+
+    async fn list_splits(&mut self) -> Result<Vec<MySqlCdcSplit>> {
+        Ok(vec![MySqlCdcSplit::new(self.table.clone(), None)])
+    }
+}
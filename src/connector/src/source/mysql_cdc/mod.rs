@@ -0,0 +1,95 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The source capturing the changes of a `MySQL` table from the binlog, without Debezium and Kafka
+//! in between. The table is read from a consistent snapshot first, and then from the binlog
+//! position where the snapshot is taken. The changes are emitted in the Debezium JSON format, so
+//! the source must be created with `ROW FORMAT DEBEZIUM_JSON`.
+//!
+//! The snapshot is read in the order of the primary key, which the table must have, so that it's
+//! resumed after the last row read if the source recovers before it's completed.
+//!
+//! The server must have the binlog enabled with `binlog_format = ROW` and `binlog_row_image =
+//! FULL`, and the user needs the `RELOAD`, `REPLICATION SLAVE` and `REPLICATION CLIENT` privileges
+//! besides `SELECT` on the table.
+
+mod enumerator;
+mod reader;
+mod split;
+
+use anyhow::{anyhow, Result};
+pub use enumerator::MySqlCdcSplitEnumerator;
+use mysql_async::{Conn, OptsBuilder};
+pub use reader::MySqlCdcSplitReader;
+use serde::Deserialize;
+pub use split::{BinlogPosition, GtidSet, MySqlCdcOffset, MySqlCdcSplit};
+
+pub const MYSQL_CDC_CONNECTOR: &str = "mysql-cdc";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MySqlCdcProperties {
+    #[serde(rename = "hostname")]
+    pub hostname: String,
+    #[serde(rename = "port", default = "default_port")]
+    pub port: String,
+    #[serde(rename = "username")]
+    pub username: String,
+    #[serde(rename = "password")]
+    pub password: String,
+    #[serde(rename = "database.name")]
+    pub database_name: String,
+    #[serde(rename = "table.name")]
+    pub table_name: String,
+    /// The id of the source as a replica, which must be unique among the replicas of the server.
+    /// A random one in `[5400, 6400)` is used by default.
+    #[serde(rename = "server.id")]
+    pub server_id: Option<String>,
+}
+
+fn default_port() -> String {
+    "3306".to_string()
+}
+
+impl MySqlCdcProperties {
+    /// The table in the form of `database.table`, which is the id of the split.
+    pub fn table(&self) -> String {
+        format!("{}.{}", self.database_name, self.table_name)
+    }
+
+    pub fn server_id(&self) -> Result<u32> {
+        match &self.server_id {
+            Some(server_id) => server_id
+                .parse()
+                .map_err(|_| anyhow!("invalid server.id: {}", server_id)),
+            None => Ok(rand::random::<u32>() % 1000 + 5400),
+        }
+    }
+
+    /// Connects to the server, with the session in UTC so that the timestamps read from the
+    /// snapshot agree with the ones in the binlog.
+    pub async fn connect(&self) -> Result<Conn> {
+        let port = self
+            .port
+            .parse()
+            .map_err(|_| anyhow!("invalid port: {}", self.port))?;
+        let opts = OptsBuilder::default()
+            .ip_or_hostname(self.hostname.clone())
+            .tcp_port(port)
+            .user(Some(self.username.clone()))
+            .pass(Some(self.password.clone()))
+            .db_name(Some(self.database_name.clone()))
+            .init(vec!["SET time_zone = '+00:00'"]);
+        Ok(Conn::new(opts).await?)
+    }
+}
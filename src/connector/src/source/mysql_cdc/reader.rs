@@ -0,0 +1,622 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use mysql_async::binlog::events::EventData;
+use mysql_async::binlog::value::BinlogValue;
+use mysql_async::prelude::*;
+use mysql_async::{BinlogRequest, Conn, Row, Value};
+
+use crate::source::cdc::{
+    debezium_json_message, decode_pk, exclude_snapshot_rows, row_pk, snapshot_part_of, Collation,
+    SnapshotBatcher, SNAPSHOT_BATCH_SIZE,
+};
+use crate::source::mysql_cdc::{
+    BinlogPosition, GtidSet, MySqlCdcOffset, MySqlCdcProperties, MySqlCdcSplit,
+};
+use crate::source::{
+    Column, ConnectorState, SnapshotPart, SourceMessage, SplitImpl, SplitMetaData, SplitReader,
+};
+
+/// A column of the `MySQL` table, whose type decides how the values are encoded in JSON.
+#[derive(Clone, Debug)]
+struct MySqlColumn {
+    name: String,
+    /// The type in lowercase without the length, e.g. `varchar`.
+    data_type: String,
+    /// The collation of the strings, which is binary for the other types.
+    collation: Collation,
+}
+
+pub struct MySqlCdcSplitReader {
+    stream: BoxStream<'static, Result<Vec<SourceMessage>>>,
+}
+
+#[async_trait]
+impl SplitReader for MySqlCdcSplitReader {
+    type Properties = MySqlCdcProperties;
+
+    async fn new(
+        properties: MySqlCdcProperties,
+        state: ConnectorState,
+        _columns: Option<Vec<Column>>,
+    ) -> Result<Self> {
+        let split = match state.and_then(|splits| splits.into_iter().next()) {
+            Some(SplitImpl::MySqlCdc(split)) => split,
+            split => return Err(anyhow!("expect MySqlCdcSplit, got {:?}", split)),
+        };
+        let server_id = properties.server_id()?;
+        let mut conn = properties.connect().await?;
+        let columns: Vec<MySqlColumn> = conn
+            .exec_map(
+                "SELECT COLUMN_NAME, DATA_TYPE, COLLATION_NAME FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+                (&properties.database_name, &properties.table_name),
+                |(name, data_type, collation): (String, String, Option<String>)| MySqlColumn {
+                    name,
+                    data_type: data_type.to_lowercase(),
+                    collation: collation.map_or_else(Collation::default, |collation| {
+                        Collation::from_mysql(&collation)
+                    }),
+                },
+            )
+            .await?;
+        if columns.is_empty() {
+            return Err(anyhow!("table {} doesn't exist", split.table));
+        }
+        let pk_columns: Vec<String> = conn
+            .exec(
+                "SELECT COLUMN_NAME FROM information_schema.KEY_COLUMN_USAGE \
+                 WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND CONSTRAINT_NAME = 'PRIMARY' \
+                 ORDER BY ORDINAL_POSITION",
+                (&properties.database_name, &properties.table_name),
+            )
+            .await?;
+        if pk_columns.is_empty() {
+            return Err(anyhow!("table {} has no primary key", split.table));
+        }
+        log::info!("launch mysql cdc reader with split: {:?}", split);
+
+        Ok(Self {
+            stream: read_table(properties, split, server_id, conn, columns, pk_columns).boxed(),
+        })
+    }
+
+    async fn next(&mut self) -> Result<Option<Vec<SourceMessage>>> {
+        self.stream.next().await.transpose()
+    }
+}
+
+/// Reads the snapshot of the table if it's not completed, and then the changes from the binlog.
+#[try_stream(ok = Vec<SourceMessage>, error = anyhow::Error)]
+async fn read_table(
+    properties: MySqlCdcProperties,
+    split: MySqlCdcSplit,
+    server_id: u32,
+    mut conn: Conn,
+    columns: Vec<MySqlColumn>,
+    pk_columns: Vec<String>,
+) {
+    let split_id = split.id();
+    let pk_collations = pk_columns
+        .iter()
+        .map(|name| {
+            columns
+                .iter()
+                .find(|c| &c.name == name)
+                .map_or_else(Collation::default, |c| c.collation)
+        })
+        .collect_vec();
+    let mut offset = match split.start_offset {
+        Some(offset) if offset.snapshot_done => offset,
+        start_offset => {
+            let (position, gtid_set) = begin_snapshot(&mut conn).await?;
+            // The binlog is read from the first part of the snapshot.
+            let mut offset = start_offset.unwrap_or_else(|| MySqlCdcOffset {
+                filename: position.filename.clone(),
+                position: position.position,
+                gtid_set,
+                ..Default::default()
+            });
+            offset.snapshot_parts.retain(|part| part.last_pk.is_some());
+            let last_pk = offset
+                .snapshot_parts
+                .last()
+                .map(|part| decode_pk(part.last_pk.as_deref()))
+                .transpose()?;
+            offset.snapshot_parts.push(SnapshotPart::new(position));
+
+            let (select, params) = snapshot_query(&properties, &columns, &pk_columns, last_pk);
+            let mut batcher = SnapshotBatcher::new(&split_id, SNAPSHOT_BATCH_SIZE);
+            let mut result = conn.exec_iter(select, params).await?;
+            while let Some(row) = result.next().await? {
+                let after = row_to_json(&columns, row_values(row))?;
+                offset.snapshot_parts.last_mut().unwrap().last_pk =
+                    Some(serde_json::to_string(&row_pk(&after, &pk_columns))?);
+                if let Some(batch) = batcher.push(after, offset.encode()) {
+                    yield batch;
+                }
+            }
+            drop(result);
+            conn.query_drop("COMMIT").await?;
+
+            offset.snapshot_done = true;
+            if let Some(batch) = batcher.finish(offset.encode()) {
+                yield batch;
+            }
+            offset
+        }
+    };
+
+    let mut gtid_set = offset
+        .gtid_set
+        .as_deref()
+        .map(GtidSet::from_str)
+        .transpose()?;
+    let mut request = BinlogRequest::new(server_id);
+    match &gtid_set {
+        Some(gtid_set) => {
+            request = request
+                .with_use_gtid(true)
+                .with_sids(gtid_set.binlog_sids()?);
+        }
+        None => {
+            request = request
+                .with_filename(offset.filename.clone().into_bytes())
+                .with_pos(offset.position);
+        }
+    }
+    let mut stream = conn.get_binlog_stream(request).await?;
+
+    // The rows of the first transaction that have been emitted before recovery.
+    let mut rows_to_skip = offset.skip_rows;
+    offset.skip_rows = 0;
+    let mut in_transaction = false;
+    let mut transaction_gtid = None;
+    while let Some(event) = stream.next().await {
+        let event = event?;
+        // The position after the event, which is zero for the artificial events.
+        let next_position = event.header().log_pos() as u64;
+        let ts_ms = event.header().timestamp() as i64 * 1000;
+        let mut committed = false;
+        let mut messages = vec![];
+        match event.read_data()? {
+            Some(EventData::RotateEvent(rotate)) => {
+                offset.filename = String::from_utf8_lossy(rotate.name_raw()).into_owned();
+                offset.position = rotate.position();
+                continue;
+            }
+            Some(EventData::GtidEvent(gtid)) => {
+                in_transaction = true;
+                transaction_gtid = Some((format_uuid(&gtid.sid()), gtid.gno()));
+            }
+            // A DDL is a transaction by itself.
+            Some(EventData::QueryEvent(query)) if query.query_raw() == b"BEGIN" => {
+                in_transaction = true;
+            }
+            Some(EventData::QueryEvent(_)) | Some(EventData::XidEvent(_)) => committed = true,
+            Some(EventData::RowsEvent(rows_event)) => {
+                let tme = stream
+                    .get_tme(rows_event.table_id())
+                    .ok_or_else(|| anyhow!("table map of {} is missing", rows_event.table_id()))?;
+                if tme.database_name() != properties.database_name
+                    || tme.table_name() != properties.table_name
+                {
+                    continue;
+                }
+                let event_position = BinlogPosition {
+                    filename: offset.filename.clone(),
+                    position: next_position,
+                };
+                for row in rows_event.rows(tme) {
+                    let (before, after) = row?;
+                    let mut op = match (&before, &after) {
+                        (None, Some(_)) => "c",
+                        (Some(_), Some(_)) => "u",
+                        (Some(_), None) => "d",
+                        (None, None) => continue,
+                    };
+                    let mut before = before
+                        .map(|row| row_to_json(&columns, row.unwrap()))
+                        .transpose()?;
+                    let mut after = after
+                        .map(|row| row_to_json(&columns, row.unwrap()))
+                        .transpose()?;
+                    // The changes of the rows read in a part of the snapshot are in it if they're
+                    // before the part.
+                    if !offset.snapshot_parts.is_empty() {
+                        let in_snapshot = |row: &serde_json::Value| -> Result<bool> {
+                            let part = snapshot_part_of(
+                                &offset.snapshot_parts,
+                                &row_pk(row, &pk_columns),
+                                &pk_collations,
+                            )?;
+                            Ok(event_position <= part.position)
+                        };
+                        match exclude_snapshot_rows(op, before, after, in_snapshot)? {
+                            Some(change) => (op, before, after) = change,
+                            None => continue,
+                        }
+                    }
+                    offset.skip_rows += 1;
+                    if rows_to_skip > 0 {
+                        rows_to_skip -= 1;
+                        continue;
+                    }
+                    messages.push(debezium_json_message(
+                        op,
+                        before,
+                        after,
+                        ts_ms,
+                        &split_id,
+                        offset.encode(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        if committed {
+            if let (Some(gtid_set), Some((uuid, gno))) = (&mut gtid_set, transaction_gtid.take()) {
+                gtid_set.add(&uuid, gno);
+                offset.gtid_set = Some(gtid_set.to_string());
+            }
+            in_transaction = false;
+            offset.skip_rows = 0;
+            rows_to_skip = 0;
+        }
+        if !in_transaction && next_position > 0 {
+            offset.position = next_position;
+        }
+        // The snapshot is passed, whose parts are no longer needed.
+        if offset.snapshot_parts.last().map_or(false, |part| {
+            (&part.position.filename, part.position.position) <= (&offset.filename, offset.position)
+        }) {
+            offset.snapshot_parts.clear();
+        }
+        if !messages.is_empty() {
+            yield messages;
+        }
+    }
+    Err(anyhow!("the binlog stream of {} is closed", split_id))?;
+}
+
+/// Starts a transaction on the consistent snapshot, and returns the binlog position of it along
+/// with the executed GTIDs. The tables are locked for a moment so that no transaction commits in
+/// between.
+async fn begin_snapshot(conn: &mut Conn) -> Result<(BinlogPosition, Option<String>)> {
+    conn.query_drop("FLUSH TABLES WITH READ LOCK").await?;
+    conn.query_drop("START TRANSACTION WITH CONSISTENT SNAPSHOT")
+        .await?;
+    let status: Option<Row> = conn.query_first("SHOW MASTER STATUS").await?;
+    conn.query_drop("UNLOCK TABLES").await?;
+
+    let status = status.ok_or_else(|| anyhow!("the binlog is not enabled on the MySQL server"))?;
+    let filename: String = status
+        .get("File")
+        .ok_or_else(|| anyhow!("binlog file is missing in the master status"))?;
+    let position: u64 = status
+        .get("Position")
+        .ok_or_else(|| anyhow!("binlog position is missing in the master status"))?;
+    let gtid_set = status
+        .get::<String, _>("Executed_Gtid_Set")
+        .map(|gtid_set| gtid_set.replace('\n', ""))
+        .filter(|gtid_set| !gtid_set.is_empty());
+    Ok((BinlogPosition { filename, position }, gtid_set))
+}
+
+/// Returns the query reading the snapshot in the order of the primary key, after the key of the
+/// last row read if any, along with its parameters. The strings are ordered in the collations of
+/// the columns, in which the keys are also compared in the binlog, so that the index of the key is
+/// used.
+fn snapshot_query(
+    properties: &MySqlCdcProperties,
+    columns: &[MySqlColumn],
+    pk_columns: &[String],
+    last_pk: Option<Vec<serde_json::Value>>,
+) -> (String, Vec<Value>) {
+    let pk_exprs = pk_columns
+        .iter()
+        .map(|name| quote_identifier(name))
+        .join(", ");
+    let mut select = format!(
+        "SELECT {} FROM {}.{}",
+        columns.iter().map(|c| quote_identifier(&c.name)).join(", "),
+        quote_identifier(&properties.database_name),
+        quote_identifier(&properties.table_name)
+    );
+    let mut params = vec![];
+    if let Some(last_pk) = last_pk {
+        select.push_str(&format!(
+            " WHERE ({}) > ({})",
+            pk_exprs,
+            last_pk.iter().map(|_| "?").join(", ")
+        ));
+        params = last_pk.iter().map(json_to_value).collect();
+    }
+    select.push_str(&format!(" ORDER BY {}", pk_exprs));
+    (select, params)
+}
+
+/// Converts a value of the primary key back to the parameter of the snapshot query.
+fn json_to_value(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::NULL,
+        serde_json::Value::Bool(v) => Value::Int(*v as i64),
+        serde_json::Value::Number(v) => match (v.as_i64(), v.as_u64()) {
+            (Some(v), _) => Value::Int(v),
+            (None, Some(v)) => Value::UInt(v),
+            _ => Value::Double(v.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(v) => Value::Bytes(v.clone().into_bytes()),
+        v => Value::Bytes(v.to_string().into_bytes()),
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+fn format_uuid(sid: &[u8; 16]) -> String {
+    let hex = sid.iter().map(|byte| format!("{:02x}", byte)).join("");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn row_values(row: Row) -> Vec<BinlogValue<'static>> {
+    row.unwrap().into_iter().map(BinlogValue::Value).collect()
+}
+
+fn row_to_json(columns: &[MySqlColumn], values: Vec<BinlogValue<'_>>) -> Result<serde_json::Value> {
+    if values.len() != columns.len() {
+        return Err(anyhow!(
+            "expect {} columns, but found {}, the table may have been altered",
+            columns.len(),
+            values.len()
+        ));
+    }
+    let row = columns
+        .iter()
+        .zip_eq(values)
+        .map(|(column, value)| Ok((column.name.clone(), value_to_json(column, value)?)))
+        .collect::<Result<serde_json::Map<_, _>>>()?;
+    Ok(row.into())
+}
+
+/// Encodes a value as the Debezium JSON parser expects, e.g. the dates and the times in text.
+fn value_to_json(column: &MySqlColumn, value: BinlogValue<'_>) -> Result<serde_json::Value> {
+    let value = match value {
+        BinlogValue::Value(value) => value,
+        BinlogValue::Jsonb(value) => {
+            let value = serde_json::Value::try_from(value)
+                .map_err(|e| anyhow!("invalid json in column {}: {:?}", column.name, e))?;
+            return Ok(value.to_string().into());
+        }
+        BinlogValue::JsonDiff(_) => {
+            return Err(anyhow!(
+                "partial json updates of column {} are not supported, \
+                 binlog_row_value_options must not be PARTIAL_JSON",
+                column.name
+            ))
+        }
+    };
+    let json = match value {
+        Value::NULL => serde_json::Value::Null,
+        Value::Int(v) => v.into(),
+        Value::UInt(v) => v.into(),
+        Value::Float(v) => v.into(),
+        Value::Double(v) => v.into(),
+        Value::Bytes(bytes) => {
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+            match column.data_type.as_str() {
+                "decimal" => text
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("invalid decimal: {}", text))?
+                    .into(),
+                // The binlog has the timestamps in seconds since the epoch, e.g. `1.5`.
+                "timestamp" => {
+                    let seconds = text
+                        .parse::<f64>()
+                        .map_err(|_| anyhow!("invalid timestamp: {}", text))?;
+                    let datetime = NaiveDateTime::from_timestamp(
+                        seconds.trunc() as i64,
+                        (seconds.fract() * 1e9).round() as u32,
+                    );
+                    format_datetime(&datetime.to_string()).into()
+                }
+                _ => text.into(),
+            }
+        }
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            if column.data_type == "date" {
+                format!("{:04}-{:02}-{:02}", year, month, day).into()
+            } else {
+                let datetime = format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
+                    year, month, day, hour, minute, second, micros
+                );
+                format_datetime(&datetime).into()
+            }
+        }
+        Value::Time(negative, days, hours, minutes, seconds, micros) => {
+            let time = format!(
+                "{}{:02}:{:02}:{:02}.{:06}",
+                if negative { "-" } else { "" },
+                days * 24 + hours as u32,
+                minutes,
+                seconds,
+                micros
+            );
+            format_datetime(&time).into()
+        }
+    };
+    Ok(json)
+}
+
+/// Trims the zero fraction of the seconds.
+fn format_datetime(datetime: &str) -> String {
+    match datetime.split_once('.') {
+        Some((seconds, fraction)) if fraction.bytes().all(|b| b == b'0') => seconds.to_string(),
+        Some((seconds, fraction)) => format!("{}.{}", seconds, fraction.trim_end_matches('0')),
+        None => datetime.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+    use serde_json::json;
+
+    use super::*;
+    use crate::source::mysql_cdc::MySqlCdcSplitEnumerator;
+    use crate::source::{ConnectorProperties, SplitEnumerator};
+
+    fn column(name: &str, data_type: &str) -> MySqlColumn {
+        MySqlColumn {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            collation: Collation::default(),
+        }
+    }
+
+    #[test]
+    fn test_value_to_json() {
+        let json = |data_type: &str, value: Value| {
+            value_to_json(&column("v", data_type), BinlogValue::Value(value)).unwrap()
+        };
+        assert_eq!(json("int", Value::Int(-1)), json!(-1));
+        assert_eq!(json("varchar", Value::NULL), json!(null));
+        assert_eq!(json("varchar", Value::Bytes(b"a".to_vec())), json!("a"));
+        assert_eq!(json("decimal", Value::Bytes(b"1.50".to_vec())), json!(1.5));
+        assert_eq!(
+            json("timestamp", Value::Bytes(b"1.5".to_vec())),
+            json!("1970-01-01 00:00:01.5")
+        );
+        assert_eq!(
+            json("date", Value::Date(2022, 1, 2, 0, 0, 0, 0)),
+            json!("2022-01-02")
+        );
+        assert_eq!(
+            json("datetime", Value::Date(2022, 1, 2, 3, 4, 5, 0)),
+            json!("2022-01-02 03:04:05")
+        );
+        assert_eq!(
+            json("time", Value::Time(true, 1, 2, 3, 4, 500)),
+            json!("-26:03:04.0005")
+        );
+    }
+
+    #[test]
+    fn test_format_uuid() {
+        assert_eq!(
+            format_uuid(&[
+                0x3e, 0x11, 0xfa, 0x47, 0x71, 0xca, 0x11, 0xe1, 0x9e, 0x33, 0xc8, 0x0a, 0xa9, 0x42,
+                0x95, 0x62
+            ]),
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562"
+        );
+    }
+
+    /// Runs against a local mysqld with the binlog enabled, e.g. the MySQL 8 docker image.
+    #[ignore]
+    #[tokio::test]
+    async fn test_mysql_cdc_source() -> Result<()> {
+        let props = ConnectorProperties::extract(convert_args!(hashmap!(
+            "connector" => "mysql-cdc",
+            "hostname" => "127.0.0.1",
+            "username" => "root",
+            "password" => "",
+            "database.name" => "test",
+            "table.name" => "t_cdc",
+        )))?;
+        let props = match props {
+            ConnectorProperties::MySqlCdc(props) => props,
+            _ => panic!("extract mysql-cdc config failed"),
+        };
+        let mut conn = props.connect().await?;
+        conn.query_drop("DROP TABLE IF EXISTS t_cdc").await?;
+        conn.query_drop("CREATE TABLE t_cdc (id INT PRIMARY KEY, v VARCHAR(10))")
+            .await?;
+        conn.query_drop("INSERT INTO t_cdc VALUES (1, 'a'), (2, 'b')")
+            .await?;
+
+        let splits = MySqlCdcSplitEnumerator::new(props.clone())
+            .await?
+            .list_splits()
+            .await?;
+        assert_eq!(
+            splits,
+            vec![MySqlCdcSplit::new("test.t_cdc".to_string(), None)]
+        );
+        let mut reader = MySqlCdcSplitReader::new(
+            props.clone(),
+            Some(vec![SplitImpl::MySqlCdc(splits[0].clone())]),
+            None,
+        )
+        .await?;
+
+        let snapshot = reader.next().await?.unwrap();
+        assert_eq!(snapshot.len(), 2);
+        assert!(MySqlCdcOffset::decode(&snapshot[1].offset)?.snapshot_done);
+
+        conn.query_drop("UPDATE t_cdc SET v = 'c' WHERE id = 1")
+            .await?;
+        conn.query_drop("DELETE FROM t_cdc WHERE id = 2").await?;
+        let mut ops = vec![];
+        while ops.len() < 2 {
+            for message in reader.next().await?.unwrap() {
+                let payload: serde_json::Value =
+                    serde_json::from_slice(message.payload.as_ref().unwrap())?;
+                ops.push(payload["payload"]["op"].as_str().unwrap().to_string());
+            }
+        }
+        assert_eq!(ops, vec!["u", "d"]);
+
+        // The source recovers after the first row of the snapshot, whose rest is read from a new
+        // snapshot having the changes of its rows, so only the update of the first row is emitted
+        // again.
+        assert!(!MySqlCdcOffset::decode(&snapshot[0].offset)?.snapshot_done);
+        let split = splits[0].copy_with_offset(snapshot[0].offset.clone())?;
+        let mut reader =
+            MySqlCdcSplitReader::new(props.clone(), Some(vec![SplitImpl::MySqlCdc(split)]), None)
+                .await?;
+        let mut ops = vec![];
+        while ops.len() < 2 {
+            for message in reader.next().await?.unwrap() {
+                let payload: serde_json::Value =
+                    serde_json::from_slice(message.payload.as_ref().unwrap())?;
+                ops.push(payload["payload"]["op"].as_str().unwrap().to_string());
+            }
+            if ops.len() == 1 {
+                conn.query_drop("INSERT INTO t_cdc VALUES (3, 'd')").await?;
+            }
+        }
+        assert_eq!(ops, vec!["u", "c"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use mysql_async::{Interval, Sid};
+use serde::{Deserialize, Serialize};
+
+use crate::source::base::SplitMetaData;
+use crate::source::SnapshotPart;
+
+/// A table captured from `MySQL`, which is read by a single reader since the binlog can't be split.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Hash)]
+pub struct MySqlCdcSplit {
+    /// The table in the form of `database.table`.
+    pub table: String,
+    /// `None` before the reader starts, in which case the snapshot is taken first.
+    pub start_offset: Option<MySqlCdcOffset>,
+}
+
+/// The position in the binlog to continue from, which is the offset of the messages.
+///
+/// A transaction is always read from its start, because the row events can't be decoded without
+/// the table map events before them. So the position is the one of the transaction being read,
+/// along with the number of its rows that have been emitted, which are skipped after recovery.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MySqlCdcOffset {
+    pub filename: String,
+    pub position: u64,
+    /// The GTIDs of the transactions that have been read, if GTID is enabled on the server, in
+    /// which case the binlog is requested by them rather than the position.
+    pub gtid_set: Option<String>,
+    pub skip_rows: u64,
+    /// The snapshot is resumed after its last row read if the source recovers before it's
+    /// completed.
+    pub snapshot_done: bool,
+    /// The parts of the snapshot, which are kept until the binlog passes the last of them.
+    #[serde(default)]
+    pub snapshot_parts: Vec<SnapshotPart<BinlogPosition>>,
+}
+
+/// A position in the binlog, which is ordered by the file and then the position in it.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BinlogPosition {
+    pub filename: String,
+    pub position: u64,
+}
+
+impl MySqlCdcOffset {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn decode(offset: &str) -> Result<Self> {
+        serde_json::from_str(offset).map_err(|e| anyhow!(e))
+    }
+}
+
+impl SplitMetaData for MySqlCdcSplit {
+    fn id(&self) -> String {
+        self.table.clone()
+    }
+
+    fn encode_to_bytes(&self) -> Bytes {
+        Bytes::from(serde_json::to_string(self).unwrap())
+    }
+
+    fn restore_from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| anyhow!(e))
+    }
+}
+
+impl MySqlCdcSplit {
+    pub fn new(table: String, start_offset: Option<MySqlCdcOffset>) -> Self {
+        Self {
+            table,
+            start_offset,
+        }
+    }
+
+    pub fn copy_with_offset(&self, start_offset: String) -> Result<Self> {
+        Ok(Self::new(
+            self.table.clone(),
+            Some(MySqlCdcOffset::decode(&start_offset)?),
+        ))
+    }
+}
+
+/// A set of GTIDs in the text form of `MySQL`, e.g. `3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7`,
+/// where the transactions of each server are in the inclusive intervals.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GtidSet {
+    intervals: BTreeMap<String, Vec<(u64, u64)>>,
+}
+
+impl GtidSet {
+    pub fn add(&mut self, uuid: &str, gno: u64) {
+        self.add_interval(uuid, gno, gno);
+    }
+
+    /// Adds the inclusive interval of the transactions of a server, merging the intervals that
+    /// overlap with or are adjacent to it.
+    pub fn add_interval(&mut self, uuid: &str, start: u64, end: u64) {
+        let intervals = self.intervals.entry(uuid.to_lowercase()).or_default();
+        let i = intervals.partition_point(|(_, e)| e + 1 < start);
+        let j = intervals.partition_point(|(s, _)| *s <= end + 1);
+        if i < j {
+            let merged = (start.min(intervals[i].0), end.max(intervals[j - 1].1));
+            intervals.splice(i..j, [merged]);
+        } else {
+            intervals.insert(i, (start, end));
+        }
+    }
+
+    /// Returns the GTIDs of each server in the text form, e.g. `uuid:1-5:7`.
+    pub fn sids(&self) -> impl Iterator<Item = String> + '_ {
+        self.intervals.iter().map(|(uuid, intervals)| {
+            let mut sid = uuid.clone();
+            for (start, end) in intervals {
+                if start == end {
+                    sid.push_str(&format!(":{}", start));
+                } else {
+                    sid.push_str(&format!(":{}-{}", start, end));
+                }
+            }
+            sid
+        })
+    }
+
+    /// Returns the SID blocks of the set to request the binlog after it, in which the intervals
+    /// are half-open.
+    pub fn binlog_sids(&self) -> Result<Vec<Sid<'static>>> {
+        self.intervals
+            .iter()
+            .map(|(uuid, intervals)| {
+                let invalid_uuid = || anyhow!("invalid server UUID in GTID: {}", uuid);
+                let hex = uuid.replace('-', "");
+                let mut sid = [0u8; 16];
+                if !hex.is_ascii() || hex.len() != sid.len() * 2 {
+                    return Err(invalid_uuid());
+                }
+                for (i, byte) in sid.iter_mut().enumerate() {
+                    *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                        .map_err(|_| invalid_uuid())?;
+                }
+                let intervals = intervals
+                    .iter()
+                    .map(|&(start, end)| Interval::new(start, end + 1))
+                    .collect();
+                Ok(Sid::new(sid).with_intervals(intervals))
+            })
+            .collect()
+    }
+}
+
+impl FromStr for GtidSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut set = Self::default();
+        // MySQL breaks the lines after the commas.
+        for sid in s.split(',').map(str::trim).filter(|sid| !sid.is_empty()) {
+            let mut parts = sid.split(':');
+            let uuid = parts.next().unwrap();
+            for interval in parts {
+                let (start, end) = interval.split_once('-').unwrap_or((interval, interval));
+                let (start, end): (u64, u64) = (
+                    start
+                        .parse()
+                        .map_err(|_| anyhow!("invalid GTID set: {}", s))?,
+                    end.parse()
+                        .map_err(|_| anyhow!("invalid GTID set: {}", s))?,
+                );
+                set.add_interval(uuid, start, end);
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl Display for GtidSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.sids().collect::<Vec<_>>().join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+
+    #[test]
+    fn test_gtid_set() {
+        let mut set: GtidSet = format!("{}:1-3:7,\n{}:1", UUID.to_uppercase(), "a")
+            .parse()
+            .unwrap();
+        assert_eq!(set.to_string(), format!("{}:1-3:7,a:1", UUID));
+
+        set.add(UUID, 5);
+        assert_eq!(set.to_string(), format!("{}:1-3:5:7,a:1", UUID));
+        set.add(UUID, 4);
+        set.add(UUID, 6);
+        set.add(UUID, 2);
+        assert_eq!(set.to_string(), format!("{}:1-7,a:1", UUID));
+        set.add("b", 1);
+        assert_eq!(set.sids().count(), 3);
+        assert!(set.binlog_sids().is_err());
+
+        let sids = format!("{}:1-3:7", UUID)
+            .parse::<GtidSet>()
+            .unwrap()
+            .binlog_sids()
+            .unwrap();
+        assert_eq!(sids.len(), 1);
+        assert_eq!(
+            sids[0].sid(),
+            [
+                0x3e, 0x11, 0xfa, 0x47, 0x71, 0xca, 0x11, 0xe1, 0x9e, 0x33, 0xc8, 0x0a, 0xa9, 0x42,
+                0x95, 0x62
+            ]
+        );
+        assert_eq!(
+            sids[0].intervals(),
+            &[Interval::new(1, 4), Interval::new(7, 8)]
+        );
+
+        assert!("a:x".parse::<GtidSet>().is_err());
+        assert_eq!("".parse::<GtidSet>().unwrap(), GtidSet::default());
+    }
+
+    #[test]
+    fn test_mysql_cdc_split() {
+        let split = MySqlCdcSplit::new("db.t".to_string(), None);
+        assert_eq!(
+            MySqlCdcSplit::restore_from_bytes(&split.encode_to_bytes()).unwrap(),
+            split
+        );
+
+        let offset = MySqlCdcOffset {
+            filename: "binlog.000001".to_string(),
+            position: 157,
+            gtid_set: None,
+            skip_rows: 2,
+            snapshot_done: true,
+            snapshot_parts: vec![SnapshotPart {
+                position: BinlogPosition {
+                    filename: "binlog.000001".to_string(),
+                    position: 257,
+                },
+                last_pk: Some("[1]".to_string()),
+            }],
+        };
+        let split = split.copy_with_offset(offset.encode()).unwrap();
+        assert_eq!(split.start_offset, Some(offset));
+    }
+}
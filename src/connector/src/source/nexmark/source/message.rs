@@ -16,7 +16,7 @@ use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 
 use crate::source::nexmark::source::event::Event;
-use crate::source::SourceMessage;
+use crate::source::{SourceMessage, SourceMeta};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NexmarkMessage {
@@ -34,6 +34,7 @@ impl From<NexmarkMessage> for SourceMessage {
                 .map(|payload| Bytes::copy_from_slice(payload)),
             offset: msg.sequence_number.clone(),
             split_id: msg.shard_id,
+            meta: SourceMeta::default(),
         }
     }
 }
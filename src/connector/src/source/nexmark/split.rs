@@ -48,11 +48,11 @@ impl NexmarkSplit {
         }
     }
 
-    pub fn copy_with_offset(&self, start_offset: String) -> Self {
-        Self::new(
+    pub fn copy_with_offset(&self, start_offset: String) -> anyhow::Result<Self> {
+        Ok(Self::new(
             self.split_index,
             self.split_num,
-            Some(start_offset.as_str().parse::<u64>().unwrap()),
-        )
+            Some(start_offset.as_str().parse::<u64>()?),
+        ))
     }
 }
@@ -0,0 +1,114 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use crate::source::postgres_cdc::{quote_identifier, PostgresCdcProperties, PostgresCdcSplit};
+use crate::source::SplitEnumerator;
+
+/// Checks the table on creation and prepares the publication and the replication slot of it, and
+/// lists the table as the only split.
+#[derive(Debug)]
+pub struct PostgresCdcSplitEnumerator {
+    table: String,
+}
+
+#[async_trait]
+impl SplitEnumerator for PostgresCdcSplitEnumerator {
+    type Properties = PostgresCdcProperties;
+    type Split = PostgresCdcSplit;
+
+    async fn new(properties: PostgresCdcProperties) -> Result<Self> {
+        let client = properties.connect().await?;
+
+        let wal_level: String = client.query_one("SHOW wal_level", &[]).await?.get(0);
+        if wal_level != "logical" {
+            return Err(anyhow!(
+                "wal_level must be logical on the PostgreSQL server, found: {}",
+                wal_level
+            ));
+        }
+
+        let replica_identity = client
+            .query_opt(
+                "SELECT c.relreplident::text FROM pg_class c \
+                 JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE n.nspname = $1 AND c.relname = $2",
+                &[&properties.schema_name, &properties.table_name],
+            )
+            .await?
+            .ok_or_else(|| anyhow!("table {} doesn't exist", properties.table()))?
+            .get::<_, String>(0);
+        if replica_identity != "f" {
+            return Err(anyhow!(
+                "table {} must have REPLICA IDENTITY FULL",
+                properties.table()
+            ));
+        }
+
+        let publication = properties.publication_name();
+        let publication_exists = client
+            .query_opt(
+                "SELECT 1 FROM pg_publication WHERE pubname = $1",
+                &[&publication],
+            )
+            .await?
+            .is_some();
+        if !publication_exists {
+            client
+                .batch_execute(&format!(
+                    "CREATE PUBLICATION {} FOR TABLE {}",
+                    quote_identifier(&publication),
+                    properties.qualified_table()
+                ))
+                .await?;
+        }
+
+        let slot = properties.slot_name();
+        let slot_plugin = client
+            .query_opt(
+                "SELECT plugin::text FROM pg_replication_slots WHERE slot_name = $1",
+                &[&slot],
+            )
+            .await?
+            .map(|row| row.get::<_, Option<String>>(0));
+        match slot_plugin {
+            None => {
+                client
+                    .execute(
+                        "SELECT pg_create_logical_replication_slot($1, 'pgoutput')",
+                        &[&slot],
+                    )
+                    .await?;
+            }
+            Some(Some(plugin)) if plugin == "pgoutput" => {}
+            Some(plugin) => {
+                return Err(anyhow!(
+                    "replication slot {} must use the pgoutput plugin, found: {:?}",
+                    slot,
+                    plugin
+                ))
+            }
+        }
+
+        Ok(Self {
+            table: properties.table(),
+        })
+    }
+
+    async fn list_splits(&mut self) -> Result<Vec<PostgresCdcSplit>> {
+        Ok(vec![PostgresCdcSplit::new(self.table.clone(), None)])
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The source capturing the changes of a `PostgreSQL` table by logical replication with the
+//! `pgoutput` plugin. The table is read from a snapshot first, and then the changes are streamed
+//! from a replication slot by the replication protocol. The changes are confirmed to the slot only
+//! after their offsets are committed in a checkpoint, so that they're retained for recovery until
+//! then. The changes are emitted in the Debezium JSON format, so the source must be created with
+//! `ROW FORMAT DEBEZIUM_JSON`. The snapshot is read in the order of the primary key, which the
+//! table must have, so that it's resumed after the last row read if the source recovers before
+//! it's completed.
+//!
+//! The server must have `wal_level = logical`, and the table must have `REPLICA IDENTITY FULL` so
+//! that the updates and the deletes carry the old rows. The publication and the slot are created
+//! if they don't exist, and the slot must be dropped manually after the source is dropped,
+//! otherwise the server keeps the WAL for it.
+
+mod enumerator;
+pub mod pgoutput;
+mod reader;
+mod replication;
+mod split;
+
+use anyhow::{anyhow, Result};
+pub use enumerator::PostgresCdcSplitEnumerator;
+pub use reader::PostgresCdcSplitReader;
+use serde::Deserialize;
+pub use split::{format_lsn, parse_lsn, PostgresCdcOffset, PostgresCdcSplit, SnapshotInfo};
+use tokio_postgres::{Client, NoTls};
+
+pub const POSTGRES_CDC_CONNECTOR: &str = "postgres-cdc";
+
+/// The microseconds between the epochs of Unix and `PostgreSQL`.
+const POSTGRES_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PostgresCdcProperties {
+    #[serde(rename = "hostname")]
+    pub hostname: String,
+    #[serde(rename = "port", default = "default_port")]
+    pub port: String,
+    #[serde(rename = "username")]
+    pub username: String,
+    #[serde(rename = "password")]
+    pub password: String,
+    #[serde(rename = "database.name")]
+    pub database_name: String,
+    #[serde(rename = "schema.name", default = "default_schema")]
+    pub schema_name: String,
+    #[serde(rename = "table.name")]
+    pub table_name: String,
+    /// `rw_<schema>_<table>` by default.
+    #[serde(rename = "slot.name")]
+    pub slot_name: Option<String>,
+    /// `rw_<schema>_<table>` by default.
+    #[serde(rename = "publication.name")]
+    pub publication_name: Option<String>,
+}
+
+fn default_port() -> String {
+    "5432".to_string()
+}
+
+fn default_schema() -> String {
+    "public".to_string()
+}
+
+impl PostgresCdcProperties {
+    /// The table in the form of `schema.table`, which is the id of the split.
+    pub fn table(&self) -> String {
+        format!("{}.{}", self.schema_name, self.table_name)
+    }
+
+    /// The table quoted to be used in the statements.
+    pub fn qualified_table(&self) -> String {
+        format!(
+            "{}.{}",
+            quote_identifier(&self.schema_name),
+            quote_identifier(&self.table_name)
+        )
+    }
+
+    pub fn slot_name(&self) -> String {
+        self.slot_name
+            .clone()
+            .unwrap_or_else(|| self.default_object_name())
+    }
+
+    pub fn publication_name(&self) -> String {
+        self.publication_name
+            .clone()
+            .unwrap_or_else(|| self.default_object_name())
+    }
+
+    /// The names of the slots may only contain lower case letters, numbers and underscores.
+    fn default_object_name(&self) -> String {
+        format!("rw_{}_{}", self.schema_name, self.table_name)
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    pub async fn connect(&self) -> Result<Client> {
+        let port = self
+            .port
+            .parse()
+            .map_err(|_| anyhow!("invalid port: {}", self.port))?;
+        let (client, connection) = tokio_postgres::Config::new()
+            .host(&self.hostname)
+            .port(port)
+            .user(&self.username)
+            .password(&self.password)
+            .dbname(&self.database_name)
+            .connect(NoTls)
+            .await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                log::error!("postgres cdc connection error: {}", e);
+            }
+        });
+        Ok(client)
+    }
+}
+
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+
+    use crate::source::ConnectorProperties;
+
+    #[test]
+    fn test_extract_postgres_cdc_config() {
+        let props = ConnectorProperties::extract(convert_args!(hashmap!(
+            "connector" => "postgres-cdc",
+            "hostname" => "localhost",
+            "username" => "postgres",
+            "password" => "",
+            "database.name" => "dev",
+            "table.name" => "My-Table",
+        )))
+        .unwrap();
+        let props = match props {
+            ConnectorProperties::PostgresCdc(props) => props,
+            _ => panic!("extract postgres-cdc config failed"),
+        };
+        assert_eq!(props.port, "5432");
+        assert_eq!(props.table(), "public.My-Table");
+        assert_eq!(props.qualified_table(), "\"public\".\"My-Table\"");
+        assert_eq!(props.slot_name(), "rw_public_my_table");
+        assert_eq!(props.publication_name(), "rw_public_my_table");
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The decoder of the messages of the `pgoutput` plugin in the protocol version 1, see
+//! <https://www.postgresql.org/docs/current/protocol-logicalrep-message-formats.html>.
+
+use anyhow::{anyhow, Result};
+use bytes::Buf;
+
+/// The messages of the logical replication. The ones that are not needed by the source, e.g. the
+/// origins and the types, are left undecoded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PgOutputMessage {
+    Begin {
+        /// The LSN of the commit of the transaction.
+        final_lsn: u64,
+        /// The microseconds since 2000-01-01.
+        commit_ts: i64,
+        xid: u32,
+    },
+    Commit {
+        commit_lsn: u64,
+        end_lsn: u64,
+        commit_ts: i64,
+    },
+    /// Describes a table before its first change in the session, and again after it's altered.
+    Relation(Relation),
+    Insert {
+        relation_id: u32,
+        new: TupleData,
+    },
+    Update {
+        relation_id: u32,
+        /// The old row, or its key, if the replica identity of the table has it.
+        old: Option<TupleData>,
+        new: TupleData,
+    },
+    Delete {
+        relation_id: u32,
+        old: TupleData,
+    },
+    Truncate {
+        relation_ids: Vec<u32>,
+    },
+    Other(u8),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relation {
+    pub id: u32,
+    pub namespace: String,
+    pub name: String,
+    pub columns: Vec<RelationColumn>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelationColumn {
+    pub name: String,
+    pub type_oid: u32,
+}
+
+pub type TupleData = Vec<TupleValue>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TupleValue {
+    Null,
+    /// A TOASTed value that's not changed by the update, which is not sent again.
+    UnchangedToast,
+    Text(String),
+}
+
+impl PgOutputMessage {
+    pub fn decode(mut buf: &[u8]) -> Result<Self> {
+        let buf = &mut buf;
+        let message = match get_u8(buf)? {
+            b'B' => Self::Begin {
+                final_lsn: get_u64(buf)?,
+                commit_ts: get_u64(buf)? as i64,
+                xid: get_u32(buf)?,
+            },
+            b'C' => {
+                // The flags are unused.
+                get_u8(buf)?;
+                Self::Commit {
+                    commit_lsn: get_u64(buf)?,
+                    end_lsn: get_u64(buf)?,
+                    commit_ts: get_u64(buf)? as i64,
+                }
+            }
+            b'R' => {
+                let id = get_u32(buf)?;
+                let namespace = get_string(buf)?;
+                let name = get_string(buf)?;
+                // The replica identity.
+                get_u8(buf)?;
+                let num_columns = get_u16(buf)?;
+                let columns = (0..num_columns)
+                    .map(|_| -> Result<RelationColumn> {
+                        // The flags, where 1 marks the columns of the key.
+                        get_u8(buf)?;
+                        let name = get_string(buf)?;
+                        let type_oid = get_u32(buf)?;
+                        // The type modifier.
+                        get_u32(buf)?;
+                        Ok(RelationColumn { name, type_oid })
+                    })
+                    .collect::<Result<_>>()?;
+                Self::Relation(Relation {
+                    id,
+                    namespace,
+                    name,
+                    columns,
+                })
+            }
+            b'I' => {
+                let relation_id = get_u32(buf)?;
+                expect_tag(buf, b'N')?;
+                Self::Insert {
+                    relation_id,
+                    new: get_tuple(buf)?,
+                }
+            }
+            b'U' => {
+                let relation_id = get_u32(buf)?;
+                let old = match get_u8(buf)? {
+                    b'K' | b'O' => {
+                        let old = get_tuple(buf)?;
+                        expect_tag(buf, b'N')?;
+                        Some(old)
+                    }
+                    b'N' => None,
+                    tag => return Err(anyhow!("unexpected tag in update: {}", tag as char)),
+                };
+                Self::Update {
+                    relation_id,
+                    old,
+                    new: get_tuple(buf)?,
+                }
+            }
+            b'D' => {
+                let relation_id = get_u32(buf)?;
+                match get_u8(buf)? {
+                    b'K' | b'O' => {}
+                    tag => return Err(anyhow!("unexpected tag in delete: {}", tag as char)),
+                }
+                Self::Delete {
+                    relation_id,
+                    old: get_tuple(buf)?,
+                }
+            }
+            b'T' => {
+                let num_relations = get_u32(buf)?;
+                // The options of `CASCADE` and `RESTART IDENTITY`.
+                get_u8(buf)?;
+                let relation_ids = (0..num_relations)
+                    .map(|_| get_u32(buf))
+                    .collect::<Result<_>>()?;
+                Self::Truncate { relation_ids }
+            }
+            tag => Self::Other(tag),
+        };
+        Ok(message)
+    }
+}
+
+fn ensure_remaining(buf: &[u8], len: usize) -> Result<()> {
+    if buf.remaining() < len {
+        return Err(anyhow!("pgoutput message is truncated"));
+    }
+    Ok(())
+}
+
+fn get_u8(buf: &mut &[u8]) -> Result<u8> {
+    ensure_remaining(buf, 1)?;
+    Ok(buf.get_u8())
+}
+
+fn get_u16(buf: &mut &[u8]) -> Result<u16> {
+    ensure_remaining(buf, 2)?;
+    Ok(buf.get_u16())
+}
+
+fn get_u32(buf: &mut &[u8]) -> Result<u32> {
+    ensure_remaining(buf, 4)?;
+    Ok(buf.get_u32())
+}
+
+fn get_u64(buf: &mut &[u8]) -> Result<u64> {
+    ensure_remaining(buf, 8)?;
+    Ok(buf.get_u64())
+}
+
+/// Reads a null-terminated string.
+fn get_string(buf: &mut &[u8]) -> Result<String> {
+    let len = buf
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or_else(|| anyhow!("pgoutput message is truncated"))?;
+    let string = String::from_utf8_lossy(&buf[..len]).into_owned();
+    buf.advance(len + 1);
+    Ok(string)
+}
+
+fn expect_tag(buf: &mut &[u8], expected: u8) -> Result<()> {
+    match get_u8(buf)? {
+        tag if tag == expected => Ok(()),
+        tag => Err(anyhow!(
+            "expect tag {} in pgoutput message, got {}",
+            expected as char,
+            tag as char
+        )),
+    }
+}
+
+fn get_tuple(buf: &mut &[u8]) -> Result<TupleData> {
+    let num_columns = get_u16(buf)?;
+    (0..num_columns)
+        .map(|_| -> Result<TupleValue> {
+            let value = match get_u8(buf)? {
+                b'n' => TupleValue::Null,
+                b'u' => TupleValue::UnchangedToast,
+                b't' => {
+                    let len = get_u32(buf)? as usize;
+                    ensure_remaining(buf, len)?;
+                    let text = String::from_utf8_lossy(&buf[..len]).into_owned();
+                    buf.advance(len);
+                    TupleValue::Text(text)
+                }
+                tag => return Err(anyhow!("unexpected tag in tuple: {}", tag as char)),
+            };
+            Ok(value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+
+    fn put_tuple(buf: &mut Vec<u8>, values: &[Option<&str>]) {
+        buf.put_u16(values.len() as u16);
+        for value in values {
+            match value {
+                Some(text) => {
+                    buf.put_u8(b't');
+                    buf.put_u32(text.len() as u32);
+                    buf.put_slice(text.as_bytes());
+                }
+                None => buf.put_u8(b'n'),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_transaction() {
+        let mut buf = vec![b'B'];
+        buf.put_u64(0x16_B374_D848);
+        buf.put_u64(1000);
+        buf.put_u32(42);
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Begin {
+                final_lsn: 0x16_B374_D848,
+                commit_ts: 1000,
+                xid: 42
+            }
+        );
+
+        let mut buf = vec![b'C', 0];
+        buf.put_u64(1);
+        buf.put_u64(2);
+        buf.put_u64(3);
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Commit {
+                commit_lsn: 1,
+                end_lsn: 2,
+                commit_ts: 3
+            }
+        );
+        assert!(PgOutputMessage::decode(&buf[..10]).is_err());
+    }
+
+    #[test]
+    fn test_decode_changes() {
+        let mut buf = vec![b'R'];
+        buf.put_u32(16384);
+        buf.put_slice(b"public\0t\0");
+        buf.put_u8(b'f');
+        buf.put_u16(2);
+        for (name, type_oid) in [("id", 23), ("v", 25)] {
+            buf.put_u8(1);
+            buf.put_slice(name.as_bytes());
+            buf.put_u8(0);
+            buf.put_u32(type_oid);
+            buf.put_i32(-1);
+        }
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Relation(Relation {
+                id: 16384,
+                namespace: "public".to_string(),
+                name: "t".to_string(),
+                columns: vec![
+                    RelationColumn {
+                        name: "id".to_string(),
+                        type_oid: 23
+                    },
+                    RelationColumn {
+                        name: "v".to_string(),
+                        type_oid: 25
+                    }
+                ]
+            })
+        );
+
+        let mut buf = vec![b'I'];
+        buf.put_u32(16384);
+        buf.put_u8(b'N');
+        put_tuple(&mut buf, &[Some("1"), None]);
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Insert {
+                relation_id: 16384,
+                new: vec![TupleValue::Text("1".to_string()), TupleValue::Null]
+            }
+        );
+
+        let mut buf = vec![b'U'];
+        buf.put_u32(16384);
+        buf.put_u8(b'O');
+        put_tuple(&mut buf, &[Some("1"), Some("a")]);
+        buf.put_u8(b'N');
+        buf.put_u16(2);
+        buf.put_u8(b't');
+        buf.put_u32(1);
+        buf.put_u8(b'1');
+        buf.put_u8(b'u');
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Update {
+                relation_id: 16384,
+                old: Some(vec![
+                    TupleValue::Text("1".to_string()),
+                    TupleValue::Text("a".to_string())
+                ]),
+                new: vec![
+                    TupleValue::Text("1".to_string()),
+                    TupleValue::UnchangedToast
+                ]
+            }
+        );
+
+        let mut buf = vec![b'D'];
+        buf.put_u32(16384);
+        buf.put_u8(b'K');
+        put_tuple(&mut buf, &[Some("1"), None]);
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Delete {
+                relation_id: 16384,
+                old: vec![TupleValue::Text("1".to_string()), TupleValue::Null]
+            }
+        );
+
+        let mut buf = vec![b'T'];
+        buf.put_u32(1);
+        buf.put_u8(0);
+        buf.put_u32(16384);
+        assert_eq!(
+            PgOutputMessage::decode(&buf).unwrap(),
+            PgOutputMessage::Truncate {
+                relation_ids: vec![16384]
+            }
+        );
+        assert_eq!(
+            PgOutputMessage::decode(b"Y").unwrap(),
+            PgOutputMessage::Other(b'Y')
+        );
+    }
+}
@@ -0,0 +1,675 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::{select, Either};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio_postgres::Client;
+use tokio_retry::strategy::ExponentialBackoff;
+use tokio_retry::Retry;
+
+use crate::source::cdc::{
+    debezium_json_message, decode_pk, exclude_snapshot_rows, row_pk, snapshot_part_of,
+    SnapshotBatcher, SNAPSHOT_BATCH_SIZE,
+};
+use crate::source::postgres_cdc::pgoutput::{PgOutputMessage, Relation, TupleValue};
+use crate::source::postgres_cdc::replication::{ReplicationMessage, ReplicationStream};
+use crate::source::postgres_cdc::{
+    parse_lsn, quote_identifier, PostgresCdcOffset, PostgresCdcProperties, PostgresCdcSplit,
+    SnapshotInfo, POSTGRES_EPOCH_MICROS,
+};
+use crate::source::{
+    Column, ConnectorState, OffsetCommitter, SnapshotPart, SourceMessage, SplitImpl, SplitMetaData,
+    SplitReader,
+};
+
+/// The maximum number of changes emitted at a time, before the transaction is committed.
+const CHANGE_BATCH_SIZE: usize = 1024;
+const START_REPLICATION_RETRIES: usize = 5;
+
+const BOOL_OID: u32 = 16;
+const INT8_OID: u32 = 20;
+const INT2_OID: u32 = 21;
+const INT4_OID: u32 = 23;
+const OID_OID: u32 = 26;
+const FLOAT4_OID: u32 = 700;
+const FLOAT8_OID: u32 = 701;
+const TIMESTAMPTZ_OID: u32 = 1184;
+const NUMERIC_OID: u32 = 1700;
+
+/// A column of the primary key, in the order of which the snapshot is read.
+struct PkColumn {
+    name: String,
+    /// The type to cast the key of the last row read to, e.g. `character varying(10)`.
+    type_name: String,
+    /// The strings are ordered in the `C` collation, i.e. by their bytes.
+    collatable: bool,
+}
+
+pub struct PostgresCdcSplitReader {
+    stream: BoxStream<'static, Result<Vec<SourceMessage>>>,
+    offset_committer: OffsetCommitter,
+}
+
+impl PostgresCdcSplitReader {
+    /// Returns the sender of the committed offsets, to which the replication slot is advanced.
+    pub fn offset_committer(&self) -> OffsetCommitter {
+        self.offset_committer.clone()
+    }
+}
+
+#[async_trait]
+impl SplitReader for PostgresCdcSplitReader {
+    type Properties = PostgresCdcProperties;
+
+    async fn new(
+        properties: PostgresCdcProperties,
+        state: ConnectorState,
+        _columns: Option<Vec<Column>>,
+    ) -> Result<Self> {
+        let split = match state.and_then(|splits| splits.into_iter().next()) {
+            Some(SplitImpl::PostgresCdc(split)) => split,
+            split => return Err(anyhow!("expect PostgresCdcSplit, got {:?}", split)),
+        };
+        let client = properties.connect().await?;
+        // The values are decoded in the session, so the timestamps are in UTC as the snapshot.
+        client.batch_execute("SET TIME ZONE 'UTC'").await?;
+        let columns: Vec<(String, u32)> = client
+            .query(
+                "SELECT attname::text, atttypid FROM pg_attribute \
+                 WHERE attrelid = $1::text::regclass AND attnum > 0 AND NOT attisdropped \
+                 ORDER BY attnum",
+                &[&properties.qualified_table()],
+            )
+            .await?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect();
+        let pk_columns: Vec<PkColumn> = client
+            .query(
+                "SELECT a.attname::text, format_type(a.atttypid, a.atttypmod), a.attcollation <> 0 \
+                 FROM pg_index i JOIN pg_attribute a \
+                 ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey) \
+                 WHERE i.indrelid = $1::text::regclass AND i.indisprimary \
+                 ORDER BY array_position(i.indkey::int2[], a.attnum)",
+                &[&properties.qualified_table()],
+            )
+            .await?
+            .iter()
+            .map(|row| PkColumn {
+                name: row.get(0),
+                type_name: row.get(1),
+                collatable: row.get(2),
+            })
+            .collect();
+        if pk_columns.is_empty() {
+            return Err(anyhow!("table {} has no primary key", split.table));
+        }
+        log::info!("launch postgres cdc reader with split: {:?}", split);
+
+        let (offset_committer, committed_offsets) = unbounded_channel();
+        Ok(Self {
+            stream: read_table(
+                properties,
+                split,
+                client,
+                columns,
+                pk_columns,
+                committed_offsets,
+            )
+            .boxed(),
+            offset_committer,
+        })
+    }
+
+    async fn next(&mut self) -> Result<Option<Vec<SourceMessage>>> {
+        self.stream.next().await.transpose()
+    }
+}
+
+/// The transaction being decoded from the slot.
+struct Transaction {
+    final_lsn: u64,
+    xid: u32,
+    ts_ms: i64,
+    /// The ones committed before recovery are skipped.
+    skip: bool,
+    rows: u64,
+}
+
+/// Reads the snapshot of the table if it's not completed, and then the changes from the slot.
+#[try_stream(ok = Vec<SourceMessage>, error = anyhow::Error)]
+async fn read_table(
+    properties: PostgresCdcProperties,
+    split: PostgresCdcSplit,
+    client: Client,
+    columns: Vec<(String, u32)>,
+    pk_columns: Vec<PkColumn>,
+    mut committed_offsets: UnboundedReceiver<String>,
+) {
+    let split_id = split.id();
+    let slot = properties.slot_name();
+    let publication = properties.publication_name();
+    let pk_names = pk_columns.iter().map(|c| c.name.clone()).collect_vec();
+    let mut offset = match split.start_offset {
+        Some(offset) if offset.snapshot_done => offset,
+        start_offset => {
+            client
+                .batch_execute("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")
+                .await?;
+            let row = client
+                .query_one(
+                    "SELECT pg_current_snapshot()::text, pg_current_wal_lsn()::text",
+                    &[],
+                )
+                .await?;
+            let snapshot = SnapshotInfo::parse(row.get(0), parse_lsn(row.get(1))?)?;
+            let mut offset = start_offset.unwrap_or_default();
+            offset.snapshot_parts.retain(|part| part.last_pk.is_some());
+            let last_pk = offset
+                .snapshot_parts
+                .last()
+                .map(|part| decode_pk(part.last_pk.as_deref()))
+                .transpose()?;
+            offset.snapshot_parts.push(SnapshotPart::new(snapshot));
+
+            let (select, params) = snapshot_query(&properties, &columns, &pk_columns, last_pk);
+            let mut batcher = SnapshotBatcher::new(&split_id, SNAPSHOT_BATCH_SIZE);
+            #[for_await]
+            for row in client.query_raw(select.as_str(), params).await? {
+                let row = row?;
+                let after: serde_json::Value = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (name, type_oid))| {
+                        let value = row
+                            .get::<_, Option<String>>(i)
+                            .map_or(serde_json::Value::Null, |text| {
+                                text_to_json(*type_oid, text)
+                            });
+                        (name.clone(), value)
+                    })
+                    .collect::<serde_json::Map<_, _>>()
+                    .into();
+                offset.snapshot_parts.last_mut().unwrap().last_pk =
+                    Some(serde_json::to_string(&row_pk(&after, &pk_names))?);
+                if let Some(batch) = batcher.push(after, offset.encode()) {
+                    yield batch;
+                }
+            }
+            client.batch_execute("COMMIT").await?;
+
+            offset.snapshot_done = true;
+            if let Some(batch) = batcher.finish(offset.encode()) {
+                yield batch;
+            }
+            offset
+        }
+    };
+
+    // The slot may be still active for the reader before recovery, until its connection is closed.
+    let start_lsn = offset.lsn;
+    let mut replication = Retry::spawn(
+        ExponentialBackoff::from_millis(100).take(START_REPLICATION_RETRIES),
+        || ReplicationStream::start(&properties, &slot, &publication, start_lsn),
+    )
+    .await?;
+    // The offset recovered is committed, so the slot can be advanced to it at once.
+    let mut committed_lsn = offset.lsn;
+    let mut confirmed_lsn = 0;
+
+    // The rows of the first transaction that have been emitted before recovery.
+    let mut rows_to_skip = offset.skip_rows;
+    offset.skip_rows = 0;
+    let mut relations: HashMap<u32, Relation> = HashMap::new();
+    let mut messages: Vec<SourceMessage> = vec![];
+    let mut transaction: Option<Transaction> = None;
+    loop {
+        // The committed offsets are confirmed as soon as they arrive, rather than on the next
+        // change, which may not come for a long time.
+        let replication_message = match select(
+            Box::pin(committed_offsets.recv()),
+            Box::pin(replication.next()),
+        )
+        .await
+        {
+            Either::Left((committed, _)) => {
+                let committed = committed
+                    .ok_or_else(|| anyhow!("offset committer of {} is closed", split_id))?;
+                committed_lsn = committed_lsn.max(PostgresCdcOffset::decode(&committed)?.lsn);
+                None
+            }
+            Either::Right((message, _)) => Some(message?),
+        };
+        if committed_lsn > confirmed_lsn {
+            replication.send_status(committed_lsn).await?;
+            confirmed_lsn = committed_lsn;
+        }
+        let data = match replication_message {
+            Some(ReplicationMessage::XLogData(data)) => data,
+            Some(ReplicationMessage::PrimaryKeepalive { reply_requested }) => {
+                if reply_requested {
+                    replication.send_status(confirmed_lsn).await?;
+                }
+                continue;
+            }
+            None => continue,
+        };
+
+        let message = PgOutputMessage::decode(&data)?;
+        let (mut op, old, new, relation_id) = match message {
+            PgOutputMessage::Begin {
+                final_lsn,
+                commit_ts,
+                xid,
+            } => {
+                transaction = Some(Transaction {
+                    final_lsn,
+                    xid,
+                    ts_ms: (commit_ts + POSTGRES_EPOCH_MICROS) / 1000,
+                    skip: final_lsn <= offset.lsn,
+                    rows: 0,
+                });
+                continue;
+            }
+            PgOutputMessage::Commit { .. } => {
+                let transaction = transaction
+                    .take()
+                    .ok_or_else(|| anyhow!("commit without begin in slot {}", slot))?;
+                if transaction.final_lsn > offset.lsn {
+                    offset.lsn = transaction.final_lsn;
+                    offset.skip_rows = 0;
+                    rows_to_skip = 0;
+                    // The snapshot is passed, whose parts are no longer needed.
+                    if offset
+                        .snapshot_parts
+                        .last()
+                        .map_or(false, |part| offset.lsn >= part.position.lsn)
+                    {
+                        offset.snapshot_parts.clear();
+                    }
+                    // The last row of the transaction marks it as completed.
+                    if transaction.rows > 0 {
+                        if let Some(last) = messages.last_mut() {
+                            last.offset = offset.encode();
+                        }
+                    }
+                }
+                if !messages.is_empty() {
+                    yield std::mem::take(&mut messages);
+                }
+                continue;
+            }
+            PgOutputMessage::Relation(relation) => {
+                relations.insert(relation.id, relation);
+                continue;
+            }
+            PgOutputMessage::Insert { relation_id, new } => ("c", None, Some(new), relation_id),
+            PgOutputMessage::Update {
+                relation_id,
+                old,
+                new,
+            } => ("u", old, Some(new), relation_id),
+            PgOutputMessage::Delete { relation_id, old } => ("d", Some(old), None, relation_id),
+            PgOutputMessage::Truncate { .. } => {
+                log::warn!("truncate of {} is not captured", split_id);
+                continue;
+            }
+            PgOutputMessage::Other(_) => continue,
+        };
+
+        let transaction = transaction
+            .as_mut()
+            .ok_or_else(|| anyhow!("change without begin in slot {}", slot))?;
+        if transaction.skip {
+            continue;
+        }
+        let relation = relations
+            .get(&relation_id)
+            .ok_or_else(|| anyhow!("relation {} is missing in slot {}", relation_id, slot))?;
+        // The publication may have other tables.
+        if relation.namespace != properties.schema_name || relation.name != properties.table_name {
+            continue;
+        }
+        let mut before = old
+            .as_ref()
+            .map(|old| tuple_to_json(relation, old, None))
+            .transpose()?;
+        let mut after = new
+            .as_ref()
+            .map(|new| tuple_to_json(relation, new, old.as_deref()))
+            .transpose()?;
+        // The changes of the rows read in a part of the snapshot are in it if the transaction is
+        // visible to the part.
+        if !offset.snapshot_parts.is_empty() {
+            let in_snapshot = |row: &serde_json::Value| -> Result<bool> {
+                let part = snapshot_part_of(&offset.snapshot_parts, &row_pk(row, &pk_names), &[])?;
+                Ok(transaction.final_lsn < part.position.lsn
+                    && part.position.is_visible(transaction.xid))
+            };
+            match exclude_snapshot_rows(op, before, after, in_snapshot)? {
+                Some(change) => (op, before, after) = change,
+                None => continue,
+            }
+        }
+        transaction.rows += 1;
+        if rows_to_skip > 0 {
+            rows_to_skip -= 1;
+            continue;
+        }
+        offset.skip_rows = transaction.rows;
+        messages.push(debezium_json_message(
+            op,
+            before,
+            after,
+            transaction.ts_ms,
+            &split_id,
+            offset.encode(),
+        ));
+        // A large transaction is emitted in batches, which are resumed by `skip_rows`.
+        if messages.len() >= CHANGE_BATCH_SIZE {
+            yield std::mem::take(&mut messages);
+        }
+    }
+}
+
+/// Returns the query reading the snapshot in the order of the primary key, after the key of the
+/// last row read if any, along with its parameters. The strings are ordered in the `C` collation,
+/// which is the order the keys are compared in the slot.
+fn snapshot_query(
+    properties: &PostgresCdcProperties,
+    columns: &[(String, u32)],
+    pk_columns: &[PkColumn],
+    last_pk: Option<Vec<serde_json::Value>>,
+) -> (String, Vec<String>) {
+    let collate = |column: &PkColumn| {
+        if column.collatable {
+            " COLLATE \"C\""
+        } else {
+            ""
+        }
+    };
+    let pk_exprs = pk_columns
+        .iter()
+        .map(|c| format!("{}{}", quote_identifier(&c.name), collate(c)))
+        .join(", ");
+    let mut select = format!(
+        "SELECT {} FROM {}",
+        columns
+            .iter()
+            .map(|(name, _)| format!("{}::text", quote_identifier(name)))
+            .join(", "),
+        properties.qualified_table()
+    );
+    let mut params = vec![];
+    if let Some(last_pk) = last_pk {
+        select.push_str(&format!(
+            " WHERE ({}) > ({})",
+            pk_exprs,
+            pk_columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("${}::text::{}{}", i + 1, c.type_name, collate(c)))
+                .join(", ")
+        ));
+        params = last_pk
+            .into_iter()
+            .map(|value| match value {
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            })
+            .collect();
+    }
+    select.push_str(&format!(" ORDER BY {}", pk_exprs));
+    (select, params)
+}
+
+/// Encodes a row as the Debezium JSON parser expects. The unchanged `TOASTed` values of an update
+/// are taken from the old row.
+fn tuple_to_json(
+    relation: &Relation,
+    tuple: &[TupleValue],
+    old: Option<&[TupleValue]>,
+) -> Result<serde_json::Value> {
+    if tuple.len() != relation.columns.len() {
+        return Err(anyhow!(
+            "expect {} columns, but found {}",
+            relation.columns.len(),
+            tuple.len()
+        ));
+    }
+    let row = relation
+        .columns
+        .iter()
+        .zip_eq(tuple)
+        .enumerate()
+        .map(|(i, (column, value))| {
+            let value = match value {
+                TupleValue::UnchangedToast => old.and_then(|old| old.get(i)).unwrap_or(value),
+                value => value,
+            };
+            let json = match value {
+                TupleValue::Text(text) => text_to_json(column.type_oid, text.clone()),
+                TupleValue::Null | TupleValue::UnchangedToast => serde_json::Value::Null,
+            };
+            (column.name.clone(), json)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    Ok(row.into())
+}
+
+/// Encodes a value in the text form of `PostgreSQL` by its type. The numbers are kept in text if
+/// they don't fit, e.g. `NaN`, for which the parser reports the error.
+fn text_to_json(type_oid: u32, text: String) -> serde_json::Value {
+    match type_oid {
+        BOOL_OID => (text == "t").into(),
+        INT2_OID | INT4_OID | INT8_OID | OID_OID => match text.parse::<i64>() {
+            Ok(v) => v.into(),
+            Err(_) => text.into(),
+        },
+        FLOAT4_OID | FLOAT8_OID | NUMERIC_OID => {
+            match text
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+            {
+                Some(v) => v.into(),
+                None => text.into(),
+            }
+        }
+        // The session is in UTC, e.g. `2022-01-02 03:04:05+00`.
+        TIMESTAMPTZ_OID => text.trim_end_matches("+00").into(),
+        _ => text.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+    use serde_json::json;
+
+    use super::*;
+    use crate::source::postgres_cdc::pgoutput::RelationColumn;
+    use crate::source::postgres_cdc::PostgresCdcSplitEnumerator;
+    use crate::source::{ConnectorProperties, SplitEnumerator};
+
+    #[test]
+    fn test_text_to_json() {
+        let json = |type_oid, text: &str| text_to_json(type_oid, text.to_string());
+        assert_eq!(json(BOOL_OID, "t"), json!(true));
+        assert_eq!(json(BOOL_OID, "f"), json!(false));
+        assert_eq!(json(INT8_OID, "-1"), json!(-1));
+        assert_eq!(json(NUMERIC_OID, "1.50"), json!(1.5));
+        assert_eq!(json(NUMERIC_OID, "NaN"), json!("NaN"));
+        assert_eq!(
+            json(TIMESTAMPTZ_OID, "2022-01-02 03:04:05.5+00"),
+            json!("2022-01-02 03:04:05.5")
+        );
+        assert_eq!(json(25, "a"), json!("a"));
+    }
+
+    #[test]
+    fn test_tuple_to_json() {
+        let relation = Relation {
+            id: 1,
+            namespace: "public".to_string(),
+            name: "t".to_string(),
+            columns: vec![
+                RelationColumn {
+                    name: "id".to_string(),
+                    type_oid: INT4_OID,
+                },
+                RelationColumn {
+                    name: "v".to_string(),
+                    type_oid: 25,
+                },
+            ],
+        };
+        let old = vec![
+            TupleValue::Text("1".to_string()),
+            TupleValue::Text("a".to_string()),
+        ];
+        let new = vec![
+            TupleValue::Text("2".to_string()),
+            TupleValue::UnchangedToast,
+        ];
+        assert_eq!(
+            tuple_to_json(&relation, &new, Some(old.as_slice())).unwrap(),
+            json!({"id": 2, "v": "a"})
+        );
+        assert_eq!(
+            tuple_to_json(&relation, &[TupleValue::Null, TupleValue::Null], None).unwrap(),
+            json!({"id": null, "v": null})
+        );
+        assert!(tuple_to_json(&relation, &[TupleValue::Null], None).is_err());
+    }
+
+    /// Runs against a local postgres with `wal_level = logical`.
+    #[ignore]
+    #[tokio::test]
+    async fn test_postgres_cdc_source() -> Result<()> {
+        let props = ConnectorProperties::extract(convert_args!(hashmap!(
+            "connector" => "postgres-cdc",
+            "hostname" => "127.0.0.1",
+            "username" => "postgres",
+            "password" => "postgres",
+            "database.name" => "postgres",
+            "table.name" => "t_cdc",
+        )))?;
+        let props = match props {
+            ConnectorProperties::PostgresCdc(props) => props,
+            _ => panic!("extract postgres-cdc config failed"),
+        };
+        let client = props.connect().await?;
+        client
+            .batch_execute(
+                "SELECT pg_drop_replication_slot(slot_name) FROM pg_replication_slots \
+                 WHERE slot_name = 'rw_public_t_cdc';
+                 DROP PUBLICATION IF EXISTS rw_public_t_cdc;
+                 DROP TABLE IF EXISTS t_cdc;
+                 CREATE TABLE t_cdc (id INT PRIMARY KEY, v VARCHAR(10));
+                 ALTER TABLE t_cdc REPLICA IDENTITY FULL;
+                 INSERT INTO t_cdc VALUES (1, 'a'), (2, 'b');",
+            )
+            .await?;
+
+        let splits = PostgresCdcSplitEnumerator::new(props.clone())
+            .await?
+            .list_splits()
+            .await?;
+        assert_eq!(
+            splits,
+            vec![PostgresCdcSplit::new("public.t_cdc".to_string(), None)]
+        );
+        let mut reader = PostgresCdcSplitReader::new(
+            props.clone(),
+            Some(vec![SplitImpl::PostgresCdc(splits[0].clone())]),
+            None,
+        )
+        .await?;
+
+        let snapshot = reader.next().await?.unwrap();
+        assert_eq!(snapshot.len(), 2);
+        assert!(PostgresCdcOffset::decode(&snapshot[1].offset)?.snapshot_done);
+
+        client
+            .batch_execute(
+                "UPDATE t_cdc SET v = 'c' WHERE id = 1;
+                 DELETE FROM t_cdc WHERE id = 2;",
+            )
+            .await?;
+        let mut ops = vec![];
+        let mut last_offset = String::new();
+        while ops.len() < 2 {
+            for message in reader.next().await?.unwrap() {
+                let payload: serde_json::Value =
+                    serde_json::from_slice(message.payload.as_ref().unwrap())?;
+                ops.push(payload["payload"]["op"].as_str().unwrap().to_string());
+                last_offset = message.offset;
+            }
+        }
+        assert_eq!(ops, vec!["u", "d"]);
+
+        // The slot is only used by one reader at a time.
+        drop(reader);
+        // The source recovers after the first row of the snapshot, whose rest is read from a new
+        // snapshot having the changes of its rows, so only the update of the first row is emitted
+        // again.
+        assert!(!PostgresCdcOffset::decode(&snapshot[0].offset)?.snapshot_done);
+        let split = splits[0].copy_with_offset(snapshot[0].offset.clone())?;
+        let mut recovered = PostgresCdcSplitReader::new(
+            props.clone(),
+            Some(vec![SplitImpl::PostgresCdc(split)]),
+            None,
+        )
+        .await?;
+        let ops = recovered
+            .next()
+            .await?
+            .unwrap()
+            .iter()
+            .map(|message| {
+                let payload: serde_json::Value =
+                    serde_json::from_slice(message.payload.as_ref().unwrap()).unwrap();
+                payload["payload"]["op"].as_str().unwrap().to_string()
+            })
+            .collect_vec();
+        assert_eq!(ops, vec!["u"]);
+
+        // The slot is confirmed once the offset is committed.
+        let lsn = PostgresCdcOffset::decode(&last_offset)?.lsn;
+        recovered.offset_committer().send(last_offset)?;
+        client
+            .batch_execute("INSERT INTO t_cdc VALUES (3, 'd')")
+            .await?;
+        assert_eq!(recovered.next().await?.unwrap().len(), 1);
+        let confirmed: String = client
+            .query_one(
+                "SELECT confirmed_flush_lsn::text FROM pg_replication_slots \
+                 WHERE slot_name = 'rw_public_t_cdc'",
+                &[],
+            )
+            .await?
+            .get(0);
+        assert!(parse_lsn(&confirmed)? >= lsn);
+        Ok(())
+    }
+}
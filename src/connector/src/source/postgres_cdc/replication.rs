@@ -0,0 +1,307 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A client of the [streaming replication protocol](https://www.postgresql.org/docs/current/protocol-replication.html)
+//! of `PostgreSQL`, which `tokio-postgres` doesn't support. It only streams the changes of a
+//! logical replication slot, and confirms the changes consumed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use postgres_protocol::authentication::md5_hash;
+use postgres_protocol::authentication::sasl::{ChannelBinding, ScramSha256, SCRAM_SHA_256};
+use postgres_protocol::message::backend::{Header, Message};
+use postgres_protocol::message::frontend;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::source::postgres_cdc::{
+    format_lsn, quote_identifier, PostgresCdcProperties, POSTGRES_EPOCH_MICROS,
+};
+
+/// The tag of `CopyBothResponse`, which `postgres-protocol` doesn't parse.
+const COPY_BOTH_RESPONSE_TAG: u8 = b'W';
+const XLOG_DATA_TAG: u8 = b'w';
+const PRIMARY_KEEPALIVE_TAG: u8 = b'k';
+const STANDBY_STATUS_UPDATE_TAG: u8 = b'r';
+
+#[derive(Debug, PartialEq)]
+pub enum ReplicationMessage {
+    /// A message of the output plugin, e.g. `pgoutput`.
+    XLogData(Bytes),
+    /// Sent by the server periodically. The client must reply with its status if
+    /// `reply_requested`, otherwise it's disconnected on `wal_sender_timeout`.
+    PrimaryKeepalive { reply_requested: bool },
+}
+
+impl ReplicationMessage {
+    fn decode(mut data: Bytes) -> Result<Self> {
+        if data.is_empty() {
+            return Err(anyhow!("empty replication message"));
+        }
+        match data.get_u8() {
+            // The start and the end of the WAL, and the time of the server.
+            XLOG_DATA_TAG if data.len() >= 24 => {
+                data.advance(24);
+                Ok(ReplicationMessage::XLogData(data))
+            }
+            // The end of the WAL, and the time of the server.
+            PRIMARY_KEEPALIVE_TAG if data.len() >= 17 => {
+                data.advance(16);
+                Ok(ReplicationMessage::PrimaryKeepalive {
+                    reply_requested: data.get_u8() == 1,
+                })
+            }
+            tag => Err(anyhow!("unexpected replication message {:?}", tag as char)),
+        }
+    }
+}
+
+/// The body of a standby status update, which confirms that the changes before `lsn` have been
+/// written, flushed and applied, so that the slot may be advanced to `lsn`.
+fn standby_status_update(lsn: u64) -> Bytes {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as i64;
+    let mut buf = BytesMut::with_capacity(34);
+    buf.put_u8(STANDBY_STATUS_UPDATE_TAG);
+    buf.put_u64(lsn);
+    buf.put_u64(lsn);
+    buf.put_u64(lsn);
+    buf.put_i64(now - POSTGRES_EPOCH_MICROS);
+    buf.put_u8(0);
+    buf.freeze()
+}
+
+/// A backend message, or `None` for `CopyBothResponse`.
+type BackendMessage = Option<Message>;
+
+pub struct ReplicationStream {
+    stream: TcpStream,
+    read_buf: BytesMut,
+    write_buf: BytesMut,
+}
+
+impl ReplicationStream {
+    /// Connects to the database in the replication mode, and starts streaming the changes of the
+    /// publication from the slot. The changes confirmed by the slot are skipped if `start_lsn` is
+    /// before them.
+    pub async fn start(
+        properties: &PostgresCdcProperties,
+        slot: &str,
+        publication: &str,
+        start_lsn: u64,
+    ) -> Result<Self> {
+        let stream =
+            TcpStream::connect(format!("{}:{}", properties.hostname, properties.port)).await?;
+        let mut replication = Self {
+            stream,
+            read_buf: BytesMut::new(),
+            write_buf: BytesMut::new(),
+        };
+        replication.authenticate(properties).await?;
+
+        frontend::query(
+            &format!(
+                "START_REPLICATION SLOT {} LOGICAL {} (\"proto_version\" '1', \"publication_names\" '{}')",
+                quote_identifier(slot),
+                format_lsn(start_lsn),
+                publication.replace('\'', "''")
+            ),
+            &mut replication.write_buf,
+        )?;
+        replication.flush().await?;
+        loop {
+            match replication.read_message().await? {
+                None => return Ok(replication),
+                Some(Message::NoticeResponse(_)) => {}
+                Some(message) => return Err(unexpected(message)),
+            }
+        }
+    }
+
+    /// Sends the startup message, and authenticates by the password until the server is ready.
+    async fn authenticate(&mut self, properties: &PostgresCdcProperties) -> Result<()> {
+        frontend::startup_message(
+            [
+                ("user", properties.username.as_str()),
+                ("database", properties.database_name.as_str()),
+                ("replication", "database"),
+            ],
+            &mut self.write_buf,
+        )?;
+        self.flush().await?;
+
+        let password = properties.password.as_bytes();
+        let mut scram = None;
+        loop {
+            match self.read_message().await? {
+                Some(Message::AuthenticationOk) => {}
+                Some(Message::AuthenticationCleartextPassword) => {
+                    frontend::password_message(password, &mut self.write_buf)?;
+                    self.flush().await?;
+                }
+                Some(Message::AuthenticationMd5Password(body)) => {
+                    let hash = md5_hash(properties.username.as_bytes(), password, body.salt());
+                    frontend::password_message(hash.as_bytes(), &mut self.write_buf)?;
+                    self.flush().await?;
+                }
+                Some(Message::AuthenticationSasl(body)) => {
+                    let mut mechanisms = body.mechanisms();
+                    let mut supported = false;
+                    while let Some(mechanism) =
+                        fallible_iterator::FallibleIterator::next(&mut mechanisms)?
+                    {
+                        supported |= mechanism == SCRAM_SHA_256;
+                    }
+                    if !supported {
+                        return Err(anyhow!("unsupported SASL mechanisms"));
+                    }
+                    let state = ScramSha256::new(password, ChannelBinding::unsupported());
+                    frontend::sasl_initial_response(
+                        SCRAM_SHA_256,
+                        state.message(),
+                        &mut self.write_buf,
+                    )?;
+                    self.flush().await?;
+                    scram = Some(state);
+                }
+                Some(Message::AuthenticationSaslContinue(body)) => {
+                    let state = scram
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("SASL continue without SASL"))?;
+                    state.update(body.data())?;
+                    frontend::sasl_response(state.message(), &mut self.write_buf)?;
+                    self.flush().await?;
+                }
+                Some(Message::AuthenticationSaslFinal(body)) => {
+                    scram
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("SASL final without SASL"))?
+                        .finish(body.data())?;
+                }
+                Some(
+                    Message::ParameterStatus(_)
+                    | Message::BackendKeyData(_)
+                    | Message::NoticeResponse(_),
+                ) => {}
+                Some(Message::ReadyForQuery(_)) => return Ok(()),
+                Some(message) => return Err(unexpected(message)),
+                None => return Err(anyhow!("unexpected CopyBothResponse")),
+            }
+        }
+    }
+
+    /// Returns the next message of the stream. It's cancel safe, so that it can be selected.
+    pub async fn next(&mut self) -> Result<ReplicationMessage> {
+        loop {
+            match self.read_message().await? {
+                Some(Message::CopyData(body)) => {
+                    return ReplicationMessage::decode(body.into_bytes());
+                }
+                Some(Message::NoticeResponse(_)) => {}
+                Some(message) => return Err(unexpected(message)),
+                None => return Err(anyhow!("unexpected CopyBothResponse")),
+            }
+        }
+    }
+
+    /// Confirms that the changes before `lsn` have been consumed, to which the slot is advanced.
+    pub async fn send_status(&mut self, lsn: u64) -> Result<()> {
+        frontend::CopyData::new(standby_status_update(lsn))?.write(&mut self.write_buf);
+        self.flush().await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.stream.write_all(&self.write_buf).await?;
+        self.write_buf.clear();
+        Ok(())
+    }
+
+    async fn read_message(&mut self) -> Result<BackendMessage> {
+        loop {
+            if let Some(header) = Header::parse(&self.read_buf)? {
+                let len = header.len() as usize + 1;
+                if header.tag() == COPY_BOTH_RESPONSE_TAG && self.read_buf.len() >= len {
+                    self.read_buf.advance(len);
+                    return Ok(None);
+                }
+                if let Some(message) = Message::parse(&mut self.read_buf)? {
+                    return Ok(Some(message));
+                }
+            }
+            if self.stream.read_buf(&mut self.read_buf).await? == 0 {
+                return Err(anyhow!("replication connection closed"));
+            }
+        }
+    }
+}
+
+fn unexpected(message: Message) -> anyhow::Error {
+    match message {
+        Message::ErrorResponse(body) => {
+            let mut fields = body.fields();
+            let mut error = String::new();
+            while let Ok(Some(field)) = fallible_iterator::FallibleIterator::next(&mut fields) {
+                // The message, e.g. `replication slot "s" does not exist`.
+                if field.type_() == b'M' {
+                    error = field.value().to_string();
+                }
+            }
+            anyhow!("replication error: {}", error)
+        }
+        _ => anyhow!("unexpected message in replication"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_replication_message() {
+        let mut xlog = vec![XLOG_DATA_TAG];
+        xlog.extend([0; 24]);
+        xlog.extend(b"B");
+        assert_eq!(
+            ReplicationMessage::decode(xlog.into()).unwrap(),
+            ReplicationMessage::XLogData(Bytes::from_static(b"B"))
+        );
+
+        let mut keepalive = vec![PRIMARY_KEEPALIVE_TAG];
+        keepalive.extend([0; 16]);
+        keepalive.push(1);
+        assert_eq!(
+            ReplicationMessage::decode(keepalive.into()).unwrap(),
+            ReplicationMessage::PrimaryKeepalive {
+                reply_requested: true
+            }
+        );
+
+        assert!(ReplicationMessage::decode(Bytes::from_static(b"w")).is_err());
+        assert!(ReplicationMessage::decode(Bytes::from_static(b"x")).is_err());
+    }
+
+    #[test]
+    fn test_standby_status_update() {
+        let mut update = standby_status_update(0x1_0000_0002);
+        assert_eq!(update.len(), 34);
+        assert_eq!(update.get_u8(), STANDBY_STATUS_UPDATE_TAG);
+        for _ in 0..3 {
+            assert_eq!(update.get_u64(), 0x1_0000_0002);
+        }
+    }
+}
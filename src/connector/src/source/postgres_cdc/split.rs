@@ -0,0 +1,213 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::source::base::SplitMetaData;
+use crate::source::SnapshotPart;
+
+/// A table captured from `PostgreSQL`, which is read by a single reader from its replication slot.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Hash)]
+pub struct PostgresCdcSplit {
+    /// The table in the form of `schema.table`.
+    pub table: String,
+    /// `None` before the reader starts, in which case the snapshot is taken first.
+    pub start_offset: Option<PostgresCdcOffset>,
+}
+
+/// The position in the replication slot to continue from, which is the offset of the messages.
+///
+/// The changes are decoded a transaction at a time, so the position is the commit LSN of the last
+/// transaction that has been emitted, along with the number of rows of the next one that have been
+/// emitted, which are skipped after recovery.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PostgresCdcOffset {
+    pub lsn: u64,
+    pub skip_rows: u64,
+    /// The snapshot is resumed after its last row read if the source recovers before it's
+    /// completed.
+    pub snapshot_done: bool,
+    /// The parts of the snapshot the table was read from, which are kept until the slot passes
+    /// the last of them.
+    #[serde(default)]
+    pub snapshot_parts: Vec<SnapshotPart<SnapshotInfo>>,
+}
+
+impl PostgresCdcOffset {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn decode(offset: &str) -> Result<Self> {
+        serde_json::from_str(offset).map_err(|e| anyhow!(e))
+    }
+}
+
+/// The transactions visible to the snapshot, whose changes are already in it and must not be
+/// emitted again when they're decoded from the slot.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SnapshotInfo {
+    /// The transactions before it have all completed.
+    pub xmin: u64,
+    /// The transactions from it have not started.
+    pub xmax: u64,
+    /// The transactions in progress between `xmin` and `xmax`.
+    pub xip: Vec<u64>,
+    /// The WAL position when the snapshot was taken, before which the visible transactions
+    /// have all committed.
+    pub lsn: u64,
+}
+
+impl SnapshotInfo {
+    /// Parses the result of `pg_current_snapshot()`, e.g. `10:20:10,14,15`.
+    pub fn parse(snapshot: &str, lsn: u64) -> Result<Self> {
+        let invalid = || anyhow!("invalid snapshot: {}", snapshot);
+        let mut parts = snapshot.split(':');
+        let mut next_xid = || -> Result<u64> {
+            parts
+                .next()
+                .and_then(|xid| xid.parse().ok())
+                .ok_or_else(invalid)
+        };
+        let xmin = next_xid()?;
+        let xmax = next_xid()?;
+        let xip = parts
+            .next()
+            .ok_or_else(invalid)?
+            .split(',')
+            .filter(|xid| !xid.is_empty())
+            .map(|xid| xid.parse().map_err(|_| invalid()))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            xmin,
+            xmax,
+            xip,
+            lsn,
+        })
+    }
+
+    /// Returns whether the transaction is visible to the snapshot. The xid in the WAL is 32-bit,
+    /// so it's extended with the epoch of `xmax`, before which it must have started.
+    pub fn is_visible(&self, xid: u32) -> bool {
+        let mut full_xid = (self.xmax & !0xFFFF_FFFF) | xid as u64;
+        if full_xid > self.xmax {
+            match full_xid.checked_sub(1 << 32) {
+                Some(xid) => full_xid = xid,
+                None => return false,
+            }
+        }
+        full_xid < self.xmin || (full_xid < self.xmax && !self.xip.contains(&full_xid))
+    }
+}
+
+/// Parses an LSN in the text form of `PostgreSQL`, e.g. `16/B374D848`.
+pub fn parse_lsn(lsn: &str) -> Result<u64> {
+    let (high, low) = lsn
+        .split_once('/')
+        .ok_or_else(|| anyhow!("invalid lsn: {}", lsn))?;
+    let high = u32::from_str_radix(high, 16).map_err(|_| anyhow!("invalid lsn: {}", lsn))?;
+    let low = u32::from_str_radix(low, 16).map_err(|_| anyhow!("invalid lsn: {}", lsn))?;
+    Ok((high as u64) << 32 | low as u64)
+}
+
+pub fn format_lsn(lsn: u64) -> String {
+    format!("{:X}/{:X}", lsn >> 32, lsn & 0xFFFF_FFFF)
+}
+
+impl SplitMetaData for PostgresCdcSplit {
+    fn id(&self) -> String {
+        self.table.clone()
+    }
+
+    fn encode_to_bytes(&self) -> Bytes {
+        Bytes::from(serde_json::to_string(self).unwrap())
+    }
+
+    fn restore_from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| anyhow!(e))
+    }
+}
+
+impl PostgresCdcSplit {
+    pub fn new(table: String, start_offset: Option<PostgresCdcOffset>) -> Self {
+        Self {
+            table,
+            start_offset,
+        }
+    }
+
+    pub fn copy_with_offset(&self, start_offset: String) -> Result<Self> {
+        Ok(Self::new(
+            self.table.clone(),
+            Some(PostgresCdcOffset::decode(&start_offset)?),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lsn() {
+        assert_eq!(parse_lsn("16/B374D848").unwrap(), 0x16_B374_D848);
+        assert_eq!(format_lsn(0x16_B374_D848), "16/B374D848");
+        assert_eq!(format_lsn(0), "0/0");
+        assert!(parse_lsn("16").is_err());
+        assert!(parse_lsn("x/1").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_visibility() {
+        let snapshot = SnapshotInfo::parse("10:20:10,14", 0).unwrap();
+        assert_eq!(snapshot.xip, vec![10, 14]);
+        assert!(snapshot.is_visible(9));
+        assert!(!snapshot.is_visible(10));
+        assert!(snapshot.is_visible(11));
+        assert!(!snapshot.is_visible(14));
+        assert!(!snapshot.is_visible(20));
+        assert!(!snapshot.is_visible(21));
+        assert!(SnapshotInfo::parse("5:5:", 0).unwrap().xip.is_empty());
+        assert!(SnapshotInfo::parse("5:5", 0).is_err());
+
+        // The xids wrap around in the WAL.
+        let snapshot = SnapshotInfo::parse("4294967290:4294967300:", 0).unwrap();
+        assert!(snapshot.is_visible(4294967295));
+        assert!(snapshot.is_visible(3));
+        assert!(!snapshot.is_visible(4));
+    }
+
+    #[test]
+    fn test_postgres_cdc_split() {
+        let split = PostgresCdcSplit::new("public.t".to_string(), None);
+        assert_eq!(
+            PostgresCdcSplit::restore_from_bytes(&split.encode_to_bytes()).unwrap(),
+            split
+        );
+
+        let offset = PostgresCdcOffset {
+            lsn: 0x16_B374_D848,
+            skip_rows: 2,
+            snapshot_done: true,
+            snapshot_parts: vec![SnapshotPart {
+                position: SnapshotInfo::parse("10:20:14", 0x16_0000_0000).unwrap(),
+                last_pk: Some("[1]".to_string()),
+            }],
+        };
+        let split = split.copy_with_offset(offset.encode()).unwrap();
+        assert_eq!(split.start_offset, Some(offset));
+    }
+}
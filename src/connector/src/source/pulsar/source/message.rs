@@ -14,11 +14,26 @@
 
 use pulsar::consumer::Message;
 
-use crate::source::SourceMessage;
+use crate::source::{SourceMessage, SourceMeta};
 
 impl From<Message<Vec<u8>>> for SourceMessage {
     fn from(msg: Message<Vec<u8>>) -> Self {
         let message_id = msg.message_id.id;
+        let metadata = &msg.payload.metadata;
+        let meta = SourceMeta {
+            key: metadata
+                .partition_key
+                .as_ref()
+                .map(|key| bytes::Bytes::copy_from_slice(key.as_bytes())),
+            // The event time is set by the producer, otherwise the publish time is used.
+            timestamp: Some(metadata.event_time.unwrap_or(metadata.publish_time) as i64),
+            headers: metadata
+                .properties
+                .iter()
+                .map(|kv| (kv.key.clone(), bytes::Bytes::from(kv.value.clone())))
+                .collect(),
+            ..Default::default()
+        };
 
         SourceMessage {
             payload: Some(bytes::Bytes::from(msg.payload.data)),
@@ -30,6 +45,7 @@ impl From<Message<Vec<u8>>> for SourceMessage {
                 message_id.batch_index.unwrap_or(-1)
             ),
             split_id: msg.topic,
+            meta,
         }
     }
 }
@@ -27,16 +27,16 @@ pub struct PulsarSplit {
 }
 
 impl PulsarSplit {
-    pub fn copy_with_offset(&self, start_offset: String) -> Self {
+    pub fn copy_with_offset(&self, start_offset: String) -> anyhow::Result<Self> {
         let start_offset = if start_offset.is_empty() {
             PulsarEnumeratorOffset::Earliest
         } else {
             PulsarEnumeratorOffset::MessageId(start_offset)
         };
-        Self {
+        Ok(Self {
             topic: self.topic.clone(),
             start_offset,
-        }
+        })
     }
 }
 
@@ -35,3 +35,18 @@ pub async fn resume() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+pub async fn throttle(source_id: u32, rate_limit: u32) -> anyhow::Result<()> {
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    meta_client.throttle(source_id, rate_limit).await?;
+
+    if rate_limit == 0 {
+        println!("Unthrottled source {}", source_id);
+    } else {
+        println!("Throttled source {} to {} rows/s", source_id, rate_limit);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use comfy_table::{Attribute, Cell, Row, Table};
+use risingwave_common::catalog::TableId;
+use risingwave_connector::source::kafka::KafkaSplitEnumerator;
+use risingwave_connector::source::{
+    ConnectorProperties, SplitEnumerator, SplitImpl, SplitMetaData,
+};
+use risingwave_pb::meta::GetClusterInfoResponse;
+use risingwave_pb::stream_plan::source_node::SourceType;
+use risingwave_pb::stream_plan::stream_node::NodeBody;
+use risingwave_pb::stream_plan::StreamNode;
+use risingwave_storage::store::ReadOptions;
+use risingwave_storage::Keyspace;
+
+use crate::common::HummockServiceOpts;
+
+fn fetch_source_id(stream_node: &StreamNode) -> Option<u32> {
+    if let Some(NodeBody::Source(s)) = stream_node.node_body.as_ref() {
+        if s.source_type == SourceType::Source as i32 {
+            return Some(s.table_id);
+        }
+    }
+    stream_node.input.iter().find_map(fetch_source_id)
+}
+
+/// Prints the splits assigned to the actors of the sources, with the offsets committed in the
+/// latest checkpoint. The lag, i.e. the number of messages not consumed yet, is shown for Kafka.
+pub async fn source_split_info() -> anyhow::Result<()> {
+    let mut hummock_opts = HummockServiceOpts::from_env()?;
+    let (meta_client, hummock) = hummock_opts.create_hummock_store().await?;
+
+    let GetClusterInfoResponse {
+        worker_nodes: _,
+        table_fragments,
+        actor_splits,
+        stream_source_infos,
+    } = meta_client.get_cluster_info().await?;
+
+    // Source ID -> [Actor ID]
+    let mut sources = BTreeMap::new();
+    for table_fragment in &table_fragments {
+        for fragment in table_fragment.fragments.values() {
+            for actor in &fragment.actors {
+                if let Some(source_id) = actor.nodes.as_ref().and_then(fetch_source_id) {
+                    sources
+                        .entry(source_id)
+                        .or_insert_with(Vec::new)
+                        .push(actor.actor_id);
+                }
+            }
+        }
+    }
+
+    let mut table = Table::new();
+    table.set_header({
+        let mut row = Row::new();
+        for header in [
+            "Source",
+            "Actor",
+            "Actor Lag",
+            "Split",
+            "Offset",
+            "Split Lag",
+        ] {
+            row.add_cell(header.into());
+        }
+        row
+    });
+
+    for (source_id, mut actor_ids) in sources {
+        actor_ids.sort_unstable();
+        let keyspace = Keyspace::table_root(hummock.clone(), &TableId::new(source_id));
+        let kafka_enumerator = match stream_source_infos
+            .get(&source_id)
+            .map(|info| ConnectorProperties::extract(info.properties.clone()))
+            .transpose()?
+        {
+            Some(ConnectorProperties::Kafka(props)) => {
+                Some(KafkaSplitEnumerator::new(props).await?)
+            }
+            _ => None,
+        };
+
+        let mut first_actor = true;
+        for actor_id in actor_ids {
+            let mut splits = vec![];
+            for split in actor_splits
+                .get(&actor_id)
+                .map(|splits| splits.splits.as_slice())
+                .unwrap_or_default()
+            {
+                let split = SplitImpl::try_from(split)?;
+                // The offset of the state is the last consumed one, while the one assigned by the
+                // meta is the next to be consumed.
+                let state = keyspace
+                    .get(
+                        split.id(),
+                        ReadOptions {
+                            epoch: u64::MAX,
+                            table_id: Some(keyspace.table_id()),
+                            retention_seconds: None,
+                        },
+                    )
+                    .await?;
+                let (split, consumed) = match state {
+                    Some(state) => (SplitImpl::restore_from_bytes(&state)?, true),
+                    None => (split, false),
+                };
+                splits.push((split, consumed));
+            }
+
+            let kafka_partitions: Vec<_> = splits
+                .iter()
+                .filter_map(|(split, _)| split.as_kafka())
+                .map(|split| split.get_topic_and_partition().1)
+                .collect();
+            let high_watermarks = match &kafka_enumerator {
+                Some(enumerator) => enumerator.fetch_high_watermarks(&kafka_partitions)?,
+                None => HashMap::new(),
+            };
+
+            // Split ID, Offset, Lag
+            let mut rows: Vec<_> = splits
+                .iter()
+                .map(|(split, consumed)| match split {
+                    SplitImpl::Kafka(kafka_split) => {
+                        let partition = kafka_split.get_topic_and_partition().1;
+                        let offset = kafka_split.start_offset();
+                        let next_offset = offset.map(|offset| offset + *consumed as i64);
+                        let lag = high_watermarks
+                            .get(&partition)
+                            .zip(next_offset)
+                            .map(|(high, next)| (high - next).max(0));
+                        (split.id(), offset, lag)
+                    }
+                    _ => (split.id(), None, None),
+                })
+                .collect();
+            let actor_lag = if rows.is_empty() {
+                rows.push(("-".to_string(), None, None));
+                None
+            } else {
+                rows.iter().map(|(_, _, lag)| *lag).sum::<Option<i64>>()
+            };
+
+            let display = |value: Option<i64>| match value {
+                Some(value) => Cell::new(value),
+                None => Cell::new("-"),
+            };
+
+            let mut first_split = true;
+            for (split_id, offset, lag) in rows {
+                let mut row = Row::new();
+                row.add_cell(if first_actor {
+                    first_actor = false;
+                    Cell::new(source_id).add_attribute(Attribute::Bold)
+                } else {
+                    "".into()
+                });
+                if first_split {
+                    first_split = false;
+                    row.add_cell(actor_id.into());
+                    row.add_cell(display(actor_lag));
+                } else {
+                    row.add_cell("".into());
+                    row.add_cell("".into());
+                }
+                row.add_cell(split_id.into());
+                row.add_cell(display(offset));
+                row.add_cell(display(lag));
+                table.add_row(row);
+            }
+        }
+    }
+
+    println!("{table}");
+
+    hummock_opts.shutdown().await;
+    Ok(())
+}
@@ -102,6 +102,15 @@ enum MetaCommands {
     Resume,
     /// get cluster info
     ClusterInfo,
+    /// get the splits of the sources assigned to actors, with their offsets and lags
+    SourceSplitInfo,
+    /// change the rows per second read from a source, where 0 removes the limit
+    Throttle {
+        #[clap(long = "source-id")]
+        source_id: u32,
+        #[clap(long = "rate-limit")]
+        rate_limit: u32,
+    },
 }
 
 #[derive(Subcommand)]
@@ -139,6 +148,13 @@ pub async fn start(opts: CliOpts) -> Result<()> {
         Commands::Meta(MetaCommands::Pause) => cmd_impl::meta::pause().await?,
         Commands::Meta(MetaCommands::Resume) => cmd_impl::meta::resume().await?,
         Commands::Meta(MetaCommands::ClusterInfo) => cmd_impl::meta::cluster_info().await?,
+        Commands::Meta(MetaCommands::SourceSplitInfo) => {
+            cmd_impl::meta::source_split_info().await?
+        }
+        Commands::Meta(MetaCommands::Throttle {
+            source_id,
+            rate_limit,
+        }) => cmd_impl::meta::throttle(source_id, rate_limit).await?,
         Commands::Stream(StreamCommands::Trace { actor_id }) => {
             cmd_impl::stream::trace(actor_id).await?
         }
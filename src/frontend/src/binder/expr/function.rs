@@ -18,15 +18,18 @@ use std::str::FromStr;
 use itertools::Itertools;
 use risingwave_common::catalog::DEFAULT_SCHEMA_NAME;
 use risingwave_common::error::{ErrorCode, Result};
-use risingwave_common::types::DataType;
+use risingwave_common::types::{DataType, ScalarImpl};
 use risingwave_expr::expr::AggKind;
-use risingwave_sqlparser::ast::{Function, FunctionArg, FunctionArgExpr};
+use risingwave_sqlparser::ast::{
+    Function, FunctionArg, FunctionArgExpr, OrderByExpr, WindowFrame, WindowFrameBound,
+    WindowFrameUnits, WindowSpec,
+};
 
 use crate::binder::bind_context::Clause;
 use crate::binder::Binder;
 use crate::expr::{
-    AggCall, AggOrderBy, AggOrderByExpr, Expr, ExprImpl, ExprType, FunctionCall, Literal,
-    TableFunction, TableFunctionType,
+    AggCall, AggOrderBy, AggOrderByExpr, Expr, ExprImpl, ExprType, Frame, FrameBound, FrameUnits,
+    FunctionCall, Literal, TableFunction, TableFunctionType, WindowFunction, WindowFunctionType,
 };
 use crate::optimizer::property::Direction;
 use crate::utils::Condition;
@@ -43,14 +46,6 @@ impl Binder {
             .into());
         };
 
-        if f.over.is_some() {
-            return Err(ErrorCode::NotImplemented(
-                format!("over window function: {}", f.name),
-                3646.into(),
-            )
-            .into());
-        }
-
         // agg calls
         let agg_kind = match function_name.as_str() {
             "count" => Some(AggKind::Count),
@@ -63,6 +58,21 @@ impl Binder {
             "approx_count_distinct" => Some(AggKind::ApproxCountDistinct),
             _ => None,
         };
+
+        // window function calls
+        if f.over.is_some() {
+            let function_type = match agg_kind {
+                Some(kind) => WindowFunctionType::Aggregate(kind),
+                None => WindowFunctionType::from_str(&function_name).map_err(|_| {
+                    ErrorCode::NotImplemented(
+                        format!("unsupported window function: {}", function_name),
+                        3646.into(),
+                    )
+                })?,
+            };
+            return self.bind_window_function(f, function_type);
+        }
+
         if let Some(kind) = agg_kind {
             return self.bind_agg(f, kind);
         }
@@ -184,6 +194,12 @@ impl Binder {
             .map(|arg| self.bind_function_arg(arg))
             .flatten_ok()
             .try_collect()?;
+        if inputs.iter().any(|input| input.has_window_function()) {
+            return Err(ErrorCode::InvalidInputSyntax(
+                "aggregate function calls cannot contain window function calls".to_string(),
+            )
+            .into());
+        }
         if f.distinct {
             match &kind {
                 AggKind::Count if inputs.is_empty() => {
@@ -250,8 +266,15 @@ impl Binder {
             )
             .into());
         }
-        let order_by = AggOrderBy::new(
-            f.order_by
+        let order_by = self.bind_agg_order_by(f.order_by)?;
+        Ok(ExprImpl::AggCall(Box::new(AggCall::new(
+            kind, inputs, f.distinct, order_by, filter,
+        )?)))
+    }
+
+    fn bind_agg_order_by(&mut self, order_by: Vec<OrderByExpr>) -> Result<AggOrderBy> {
+        Ok(AggOrderBy::new(
+            order_by
                 .into_iter()
                 .map(|e| -> Result<AggOrderByExpr> {
                     let expr = self.bind_expr(e.expr)?;
@@ -271,10 +294,156 @@ impl Binder {
                     })
                 })
                 .try_collect()?,
-        );
-        Ok(ExprImpl::AggCall(Box::new(AggCall::new(
-            kind, inputs, f.distinct, order_by, filter,
-        )?)))
+        ))
+    }
+
+    fn bind_window_function(
+        &mut self,
+        f: Function,
+        function_type: WindowFunctionType,
+    ) -> Result<ExprImpl> {
+        self.ensure_window_function_allowed()?;
+        if f.distinct || !f.order_by.is_empty() || f.filter.is_some() {
+            return Err(ErrorCode::NotImplemented(
+                format!(
+                    "DISTINCT, ORDER BY or FILTER in window function `{}`",
+                    function_type
+                ),
+                None.into(),
+            )
+            .into());
+        }
+        let WindowSpec {
+            partition_by,
+            order_by,
+            window_frame,
+        } = f.over.unwrap();
+
+        let mut inputs: Vec<ExprImpl> = f
+            .args
+            .into_iter()
+            .map(|arg| self.bind_function_arg(arg))
+            .flatten_ok()
+            .try_collect()?;
+        let partition_by: Vec<ExprImpl> = partition_by
+            .into_iter()
+            .map(|expr| self.bind_expr(expr))
+            .try_collect()?;
+        let order_by = self.bind_agg_order_by(order_by)?;
+
+        let nested = inputs
+            .iter()
+            .chain(partition_by.iter())
+            .chain(order_by.sort_exprs.iter().map(|e| &e.expr))
+            .any(|expr| expr.has_window_function());
+        if nested {
+            return Err(ErrorCode::InvalidInputSyntax(
+                "window function calls cannot be nested".to_string(),
+            )
+            .into());
+        }
+
+        let frame = match function_type {
+            // `lag` and `lead` are evaluated over the single row at the offset, regardless of the
+            // frame clause.
+            WindowFunctionType::Lag | WindowFunctionType::Lead => {
+                let offset = match inputs.len() {
+                    1 => 1,
+                    2 => Self::bind_window_offset(inputs.pop().unwrap())?,
+                    _ => {
+                        return Err(ErrorCode::NotImplemented(
+                            format!("{} with more than 2 arguments", function_type),
+                            None.into(),
+                        )
+                        .into())
+                    }
+                };
+                if function_type == WindowFunctionType::Lag {
+                    Frame::single_row(-offset)
+                } else {
+                    Frame::single_row(offset)
+                }
+            }
+            _ if function_type.is_framed() => match window_frame {
+                Some(window_frame) => Self::bind_window_frame(window_frame)?,
+                None => Frame::default(),
+            },
+            _ => Frame::default(),
+        };
+
+        Ok(WindowFunction::new(function_type, inputs, partition_by, order_by, frame)?.into())
+    }
+
+    /// Bind the offset argument of `lag` and `lead`, which must be a non-negative integer constant.
+    fn bind_window_offset(offset: ExprImpl) -> Result<isize> {
+        let invalid = || {
+            ErrorCode::InvalidInputSyntax(
+                "the offset of lag and lead must be a non-negative integer constant".to_string(),
+            )
+        };
+        if !offset.is_const() {
+            return Err(invalid().into());
+        }
+        let offset = match offset.cast_implicit(DataType::Int64)?.eval_row_const()? {
+            Some(ScalarImpl::Int64(offset)) if offset >= 0 => offset,
+            _ => return Err(invalid().into()),
+        };
+        Ok(offset as isize)
+    }
+
+    fn bind_window_frame(window_frame: WindowFrame) -> Result<Frame> {
+        let units = match window_frame.units {
+            WindowFrameUnits::Rows => FrameUnits::Rows,
+            WindowFrameUnits::Range => FrameUnits::Range,
+            WindowFrameUnits::Groups => {
+                return Err(
+                    ErrorCode::NotImplemented("GROUPS frame".to_string(), None.into()).into(),
+                )
+            }
+        };
+        let bind_bound = |bound: WindowFrameBound| -> Result<FrameBound> {
+            let bound = match bound {
+                WindowFrameBound::CurrentRow => FrameBound::CurrentRow,
+                WindowFrameBound::Preceding(None) => FrameBound::UnboundedPreceding,
+                WindowFrameBound::Following(None) => FrameBound::UnboundedFollowing,
+                WindowFrameBound::Preceding(Some(n)) => FrameBound::Preceding(n as usize),
+                WindowFrameBound::Following(Some(n)) => FrameBound::Following(n as usize),
+            };
+            if units == FrameUnits::Range
+                && matches!(bound, FrameBound::Preceding(_) | FrameBound::Following(_))
+            {
+                return Err(ErrorCode::NotImplemented(
+                    "RANGE frame with offset PRECEDING or FOLLOWING".to_string(),
+                    None.into(),
+                )
+                .into());
+            }
+            Ok(bound)
+        };
+        let start = bind_bound(window_frame.start_bound)?;
+        let end = match window_frame.end_bound {
+            Some(end_bound) => bind_bound(end_bound)?,
+            None => FrameBound::CurrentRow,
+        };
+
+        let invalid = |msg: &str| -> Result<Frame> {
+            Err(ErrorCode::InvalidInputSyntax(msg.to_string()).into())
+        };
+        match (start, end) {
+            (FrameBound::UnboundedFollowing, _) => {
+                invalid("frame start cannot be UNBOUNDED FOLLOWING")
+            }
+            (_, FrameBound::UnboundedPreceding) => {
+                invalid("frame end cannot be UNBOUNDED PRECEDING")
+            }
+            (FrameBound::CurrentRow, FrameBound::Preceding(_)) => {
+                invalid("frame starting from current row cannot have preceding rows")
+            }
+            (FrameBound::Following(_), FrameBound::Preceding(_) | FrameBound::CurrentRow) => {
+                invalid("frame starting from following row cannot have preceding rows")
+            }
+            _ => Ok(Frame { units, start, end }),
+        }
     }
 
     fn rewrite_concat_to_concat_ws(inputs: Vec<ExprImpl>) -> Result<Vec<ExprImpl>> {
@@ -333,6 +502,17 @@ impl Binder {
         Ok(())
     }
 
+    fn ensure_window_function_allowed(&self) -> Result<()> {
+        if let Some(clause) = self.context.clause {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "window functions are not allowed in {}",
+                clause
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
     fn ensure_table_function_allowed(&self) -> Result<()> {
         if let Some(clause) = self.context.clause {
             if clause == Clause::Values || clause == Clause::Where {
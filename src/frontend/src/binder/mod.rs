@@ -42,7 +42,7 @@ pub use relation::{
 };
 use risingwave_common::error::ErrorCode;
 pub use select::BoundSelect;
-pub use set_expr::BoundSetExpr;
+pub use set_expr::{BoundSetExpr, BoundSetOperation};
 pub use statement::BoundStatement;
 pub use update::BoundUpdate;
 pub use values::BoundValues;
@@ -21,7 +21,7 @@ use risingwave_common::error::{internal_error, ErrorCode, Result, RwError};
 use risingwave_sqlparser::ast::{FunctionArg, Ident, ObjectName, TableAlias, TableFactor};
 
 use super::bind_context::ColumnBinding;
-use crate::binder::{Binder, BoundSetExpr};
+use crate::binder::Binder;
 use crate::expr::{Expr, ExprImpl, TableFunction, TableFunctionType};
 
 mod join;
@@ -53,17 +53,13 @@ impl Relation {
     pub fn contains_sys_table(&self) -> bool {
         match self {
             Relation::SystemTable(_) => true,
-            Relation::Subquery(s) => {
-                if let BoundSetExpr::Select(select) = &s.query.body
-                    && let Some(relation) = &select.from {
-                    relation.contains_sys_table()
-                } else {
-                    false
-                }
-            },
-            Relation::Join(j) => {
-                j.left.contains_sys_table() || j.right.contains_sys_table()
-            },
+            Relation::Subquery(s) => s.query.body.selects().into_iter().any(|select| {
+                select
+                    .from
+                    .as_ref()
+                    .map_or(false, |relation| relation.contains_sys_table())
+            }),
+            Relation::Join(j) => j.left.contains_sys_table() || j.right.contains_sys_table(),
             _ => false,
         }
     }
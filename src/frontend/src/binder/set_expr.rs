@@ -12,12 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risingwave_common::catalog::Schema;
+use itertools::Itertools;
+use risingwave_common::catalog::{Field, Schema};
 use risingwave_common::error::{ErrorCode, Result};
-use risingwave_sqlparser::ast::SetExpr;
+use risingwave_sqlparser::ast::{SetExpr, SetOperator};
 
-use crate::binder::{Binder, BoundSelect, BoundValues};
-use crate::expr::CorrelatedId;
+use crate::binder::{BindContext, Binder, BoundSelect, BoundValues};
+use crate::expr::{align_types, CorrelatedId, ExprImpl, InputRef};
 
 /// Part of a validated query, without order or limit clause. It may be composed of smaller
 /// `BoundSetExpr`s via set operators (e.g. union).
@@ -25,6 +26,42 @@ use crate::expr::CorrelatedId;
 pub enum BoundSetExpr {
     Select(Box<BoundSelect>),
     Values(Box<BoundValues>),
+    SetOperation {
+        op: BoundSetOperation,
+        all: bool,
+        left: Box<BoundSetExpr>,
+        right: Box<BoundSetExpr>,
+        /// The output schema. Column names come from the left side, and each column type is the
+        /// common type of the two sides.
+        schema: Schema,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundSetOperation {
+    Union,
+    Except,
+    Intersect,
+}
+
+impl From<SetOperator> for BoundSetOperation {
+    fn from(value: SetOperator) -> Self {
+        match value {
+            SetOperator::Union => BoundSetOperation::Union,
+            SetOperator::Except => BoundSetOperation::Except,
+            SetOperator::Intersect => BoundSetOperation::Intersect,
+        }
+    }
+}
+
+impl std::fmt::Display for BoundSetOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BoundSetOperation::Union => "UNION",
+            BoundSetOperation::Except => "EXCEPT",
+            BoundSetOperation::Intersect => "INTERSECT",
+        })
+    }
 }
 
 impl BoundSetExpr {
@@ -34,6 +71,33 @@ impl BoundSetExpr {
         match self {
             BoundSetExpr::Select(s) => s.schema(),
             BoundSetExpr::Values(v) => v.schema(),
+            BoundSetExpr::SetOperation { schema, .. } => schema,
+        }
+    }
+
+    /// All the [`BoundSelect`]s in this [`BoundSetExpr`], from left to right.
+    pub fn selects(&self) -> Vec<&BoundSelect> {
+        match self {
+            BoundSetExpr::Select(s) => vec![s.as_ref()],
+            BoundSetExpr::Values(_) => vec![],
+            BoundSetExpr::SetOperation { left, right, .. } => {
+                let mut selects = left.selects();
+                selects.extend(right.selects());
+                selects
+            }
+        }
+    }
+
+    /// The mutable version of [`BoundSetExpr::selects`].
+    pub fn selects_mut(&mut self) -> Vec<&mut BoundSelect> {
+        match self {
+            BoundSetExpr::Select(s) => vec![s.as_mut()],
+            BoundSetExpr::Values(_) => vec![],
+            BoundSetExpr::SetOperation { left, right, .. } => {
+                let mut selects = left.selects_mut();
+                selects.extend(right.selects_mut());
+                selects
+            }
         }
     }
 
@@ -41,6 +105,9 @@ impl BoundSetExpr {
         match self {
             BoundSetExpr::Select(s) => s.is_correlated(),
             BoundSetExpr::Values(_) => false,
+            BoundSetExpr::SetOperation { left, right, .. } => {
+                left.is_correlated() || right.is_correlated()
+            }
         }
     }
 
@@ -53,6 +120,13 @@ impl BoundSetExpr {
                 s.collect_correlated_indices_by_depth_and_assign_id(correlated_id)
             }
             BoundSetExpr::Values(_) => vec![],
+            BoundSetExpr::SetOperation { left, right, .. } => {
+                let mut correlated_indices =
+                    left.collect_correlated_indices_by_depth_and_assign_id(correlated_id);
+                correlated_indices
+                    .extend(right.collect_correlated_indices_by_depth_and_assign_id(correlated_id));
+                correlated_indices
+            }
         }
     }
 }
@@ -67,6 +141,12 @@ impl Binder {
                 3584.into(),
             )
             .into()),
+            SetExpr::SetOperation {
+                op,
+                all,
+                left,
+                right,
+            } => self.bind_set_operation(op.into(), all, *left, *right),
             _ => Err(ErrorCode::NotImplemented(
                 format!("set expr: {:}", set_expr),
                 None.into(),
@@ -74,4 +154,75 @@ impl Binder {
             .into()),
         }
     }
+
+    /// Bind a set operation, e.g. `left UNION ALL right`.
+    ///
+    /// Each side is bound in its own [`BindContext`] so that the relations in the `FROM` clause of
+    /// one side are invisible to the other side. Note that we don't push a new subquery context
+    /// here, so the depth of correlated input refs is not affected.
+    fn bind_set_operation(
+        &mut self,
+        op: BoundSetOperation,
+        all: bool,
+        left: SetExpr,
+        right: SetExpr,
+    ) -> Result<BoundSetExpr> {
+        if matches!(op, BoundSetOperation::Except | BoundSetOperation::Intersect) && all {
+            return Err(ErrorCode::NotImplemented(format!("{} ALL", op), None.into()).into());
+        }
+
+        let context = std::mem::take(&mut self.context);
+        let left = self.bind_set_expr(left);
+        self.context = BindContext::new();
+        let right = self.bind_set_expr(right);
+        self.context = context;
+        let (left, right) = (left?, right?);
+
+        if left.is_correlated() || right.is_correlated() {
+            return Err(ErrorCode::NotImplemented(
+                format!("correlated subquery in {}", op),
+                None.into(),
+            )
+            .into());
+        }
+
+        let left_schema = left.schema();
+        let right_schema = right.schema();
+        if left_schema.len() != right_schema.len() {
+            return Err(ErrorCode::BindError(format!(
+                "each {} query must have the same number of columns",
+                op
+            ))
+            .into());
+        }
+
+        let fields = left_schema
+            .fields()
+            .iter()
+            .zip_eq(right_schema.fields())
+            .map(|(left_field, right_field)| {
+                let mut exprs: [ExprImpl; 2] = [
+                    InputRef::new(0, left_field.data_type()).into(),
+                    InputRef::new(0, right_field.data_type()).into(),
+                ];
+                let data_type = align_types(exprs.iter_mut()).map_err(|_| {
+                    ErrorCode::BindError(format!(
+                        "{} types {:?} and {:?} cannot be matched",
+                        op,
+                        left_field.data_type(),
+                        right_field.data_type()
+                    ))
+                })?;
+                Ok(Field::with_name(data_type, left_field.name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(BoundSetExpr::SetOperation {
+            op,
+            all,
+            left: Box::new(left),
+            right: Box::new(right),
+            schema: Schema::new(fields),
+        })
+    }
 }
@@ -28,7 +28,7 @@ use risingwave_rpc_client::MetaClient;
 use tokio::sync::watch::Receiver;
 
 use super::root_catalog::Catalog;
-use super::DatabaseId;
+use super::{DatabaseId, TableStatistics};
 use crate::user::UserId;
 
 pub type CatalogReadGuard = ArcRwLockReadGuard<RawRwLock, Catalog>;
@@ -97,6 +97,12 @@ pub trait CatalogWriter: Send + Sync {
     async fn drop_schema(&self, schema_id: u32) -> Result<()>;
 
     async fn drop_index(&self, index_id: IndexId) -> Result<()>;
+
+    async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: TableStatistics,
+    ) -> Result<()>;
 }
 
 #[derive(Clone)]
@@ -220,6 +226,18 @@ impl CatalogWriter for CatalogWriterImpl {
         let version = self.meta_client.drop_database(database_id).await?;
         self.wait_version(version).await
     }
+
+    async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: TableStatistics,
+    ) -> Result<()> {
+        let version = self
+            .meta_client
+            .update_table_statistics(table_id.table_id, statistics.to_prost())
+            .await?;
+        self.wait_version(version).await
+    }
 }
 
 impl CatalogWriterImpl {
@@ -28,9 +28,11 @@ pub(crate) mod sink_catalog;
 pub(crate) mod source_catalog;
 pub(crate) mod system_catalog;
 pub(crate) mod table_catalog;
+pub(crate) mod table_statistics;
 
 pub use index_catalog::IndexCatalog;
 pub use table_catalog::TableCatalog;
+pub use table_statistics::TableStatistics;
 
 pub(crate) type SourceId = u32;
 pub(crate) type SinkId = u32;
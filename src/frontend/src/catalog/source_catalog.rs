@@ -18,6 +18,8 @@ use itertools::Itertools;
 use risingwave_pb::catalog::source::Info;
 use risingwave_pb::catalog::Source as ProstSource;
 use risingwave_pb::stream_plan::source_node::SourceType;
+use risingwave_source::dead_letter::SourceErrorPolicy;
+use risingwave_source::rate_limit::rate_limit_from_properties;
 
 use super::column_catalog::ColumnCatalog;
 use super::{ColumnId, SourceId, TABLE_SOURCE_PK_COLID};
@@ -40,6 +42,9 @@ pub struct SourceCatalog {
     pub source_type: SourceType,
     pub append_only: bool,
     pub owner: u32,
+    pub error_policy: SourceErrorPolicy,
+    /// The rows per second to read from the source by default.
+    pub rate_limit: Option<u32>,
 }
 
 impl SourceCatalog {
@@ -92,6 +97,9 @@ impl From<&ProstSource> for SourceCatalog {
 
         let append_only = check_append_only(&with_options);
         let owner = prost.owner;
+        // The policy has been checked when the source is created.
+        let error_policy = SourceErrorPolicy::from_properties(&with_options).unwrap_or_default();
+        let rate_limit = rate_limit_from_properties(&with_options).unwrap_or_default();
 
         Self {
             id,
@@ -101,6 +109,8 @@ impl From<&ProstSource> for SourceCatalog {
             source_type,
             append_only,
             owner,
+            error_policy,
+            rate_limit,
         }
     }
 }
@@ -23,7 +23,7 @@ use risingwave_pb::catalog::table::OptionalAssociatedSourceId;
 use risingwave_pb::catalog::Table as ProstTable;
 
 use super::column_catalog::ColumnCatalog;
-use super::{DatabaseId, SchemaId};
+use super::{DatabaseId, SchemaId, TableStatistics};
 use crate::catalog::TableId;
 use crate::optimizer::property::FieldOrder;
 
@@ -90,6 +90,9 @@ pub struct TableCatalog {
     pub properties: HashMap<String, String>,
 
     pub read_pattern_prefix_column: u32,
+
+    /// Collected by `ANALYZE`, `None` if the table has never been analyzed.
+    pub statistics: Option<TableStatistics>,
 }
 
 impl TableCatalog {
@@ -172,6 +175,7 @@ impl TableCatalog {
             mapping: None,
             properties: self.properties.clone(),
             read_pattern_prefix_column: self.read_pattern_prefix_column,
+            statistics: self.statistics.as_ref().map(TableStatistics::to_prost),
         }
     }
 }
@@ -228,6 +232,7 @@ impl From<ProstTable> for TableCatalog {
             vnode_mapping: Some(vnode_mapping),
             properties: tb.properties,
             read_pattern_prefix_column: tb.read_pattern_prefix_column,
+            statistics: tb.statistics.as_ref().map(TableStatistics::from),
         }
     }
 }
@@ -318,6 +323,7 @@ mod tests {
                 String::from("300"),
             )]),
             read_pattern_prefix_column: 0,
+            statistics: None,
         }
         .into();
 
@@ -372,6 +378,7 @@ mod tests {
                     String::from("300")
                 )]),
                 read_pattern_prefix_column: 0,
+                statistics: None,
             }
         );
     }
@@ -0,0 +1,141 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use risingwave_pb::catalog::{
+    ColumnStatistics as ProstColumnStatistics, Histogram as ProstHistogram,
+    TableStatistics as ProstTableStatistics,
+};
+
+use super::ColumnId;
+
+/// The statistics of a table collected by `ANALYZE`, to estimate the cardinality of the plans.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableStatistics {
+    pub row_count: u64,
+    pub columns: HashMap<ColumnId, ColumnStatistics>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnStatistics {
+    pub null_count: u64,
+    /// The number of distinct non-null values, estimated by HyperLogLog.
+    pub distinct_count: u64,
+    /// Only for the numeric columns.
+    pub histogram: Option<Histogram>,
+}
+
+/// An equi-width histogram of the non-null values of a column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    /// The number of values in each of the buckets evenly dividing `[min, max]`.
+    pub bucket_counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// The fraction of the values less than `value`, assuming that the values in a bucket are
+    /// evenly distributed.
+    pub fn fraction_below(&self, value: f64) -> f64 {
+        let total: u64 = self.bucket_counts.iter().sum();
+        if total == 0 || value <= self.min {
+            return 0.0;
+        }
+        if value > self.max {
+            return 1.0;
+        }
+        let width = (self.max - self.min) / self.bucket_counts.len() as f64;
+        let position = (value - self.min) / width;
+        let bucket = (position as usize).min(self.bucket_counts.len() - 1);
+        let below: u64 = self.bucket_counts[..bucket].iter().sum();
+        let partial = self.bucket_counts[bucket] as f64 * (position - bucket as f64).min(1.0);
+        (below as f64 + partial) / total as f64
+    }
+}
+
+impl TableStatistics {
+    pub fn to_prost(&self) -> ProstTableStatistics {
+        ProstTableStatistics {
+            row_count: self.row_count,
+            columns: self
+                .columns
+                .iter()
+                .map(|(column_id, column)| {
+                    (
+                        column_id.get_id(),
+                        ProstColumnStatistics {
+                            null_count: column.null_count,
+                            distinct_count: column.distinct_count,
+                            histogram: column.histogram.as_ref().map(|histogram| ProstHistogram {
+                                min: histogram.min,
+                                max: histogram.max,
+                                bucket_counts: histogram.bucket_counts.clone(),
+                            }),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&ProstTableStatistics> for TableStatistics {
+    fn from(prost: &ProstTableStatistics) -> Self {
+        Self {
+            row_count: prost.row_count,
+            columns: prost
+                .columns
+                .iter()
+                .map(|(&column_id, column)| {
+                    (
+                        ColumnId::new(column_id),
+                        ColumnStatistics {
+                            null_count: column.null_count,
+                            distinct_count: column.distinct_count,
+                            histogram: column.histogram.as_ref().map(|histogram| Histogram {
+                                min: histogram.min,
+                                max: histogram.max,
+                                bucket_counts: histogram.bucket_counts.clone(),
+                            }),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_fraction_below() {
+        let histogram = Histogram {
+            min: 0.0,
+            max: 100.0,
+            bucket_counts: vec![10, 30, 0, 60],
+        };
+        assert_eq!(histogram.fraction_below(-1.0), 0.0);
+        assert_eq!(histogram.fraction_below(0.0), 0.0);
+        assert_eq!(histogram.fraction_below(25.0), 0.1);
+        assert_eq!(histogram.fraction_below(50.0), 0.4);
+        assert_eq!(histogram.fraction_below(60.0), 0.4);
+        assert_eq!(histogram.fraction_below(87.5), 0.7);
+        assert_eq!(histogram.fraction_below(100.0), 1.0);
+        assert_eq!(histogram.fraction_below(101.0), 1.0);
+    }
+}
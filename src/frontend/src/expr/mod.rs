@@ -28,6 +28,7 @@ mod input_ref;
 mod literal;
 mod subquery;
 mod table_function;
+mod window_function;
 
 mod expr_mutator;
 mod expr_rewriter;
@@ -42,6 +43,7 @@ pub use input_ref::{input_ref_to_column_indices, InputRef, InputRefDisplay};
 pub use literal::Literal;
 pub use subquery::{Subquery, SubqueryKind};
 pub use table_function::{TableFunction, TableFunctionType};
+pub use window_function::{Frame, FrameBound, FrameUnits, WindowFunction, WindowFunctionType};
 
 pub type ExprType = risingwave_pb::expr::expr_node::Type;
 
@@ -72,6 +74,7 @@ pub enum ExprImpl {
     AggCall(Box<AggCall>),
     Subquery(Box<Subquery>),
     TableFunction(Box<TableFunction>),
+    WindowFunction(Box<WindowFunction>),
 }
 
 impl ExprImpl {
@@ -209,7 +212,7 @@ macro_rules! impl_has_variant {
     };
 }
 
-impl_has_variant! {InputRef, Literal, FunctionCall, AggCall, Subquery, TableFunction}
+impl_has_variant! {InputRef, Literal, FunctionCall, AggCall, Subquery, TableFunction, WindowFunction}
 
 impl ExprImpl {
     /// Used to check whether the expression has [`CorrelatedInputRef`].
@@ -228,17 +231,14 @@ impl ExprImpl {
             }
 
             fn visit_subquery(&mut self, subquery: &Subquery) {
-                use crate::binder::BoundSetExpr;
-
                 self.depth += 1;
-                match &subquery.query.body {
-                    BoundSetExpr::Select(select) => select
+                for select in subquery.query.body.selects() {
+                    select
                         .select_items
                         .iter()
                         .chain(select.group_by.iter())
                         .chain(select.where_clause.iter())
-                        .for_each(|expr| self.visit_expr(expr)),
-                    BoundSetExpr::Values(_) => {}
+                        .for_each(|expr| self.visit_expr(expr));
                 }
                 self.depth -= 1;
             }
@@ -266,15 +266,13 @@ impl ExprImpl {
             }
 
             fn visit_subquery(&mut self, subquery: &Subquery) {
-                use crate::binder::BoundSetExpr;
-                match &subquery.query.body {
-                    BoundSetExpr::Select(select) => select
+                for select in subquery.query.body.selects() {
+                    select
                         .select_items
                         .iter()
                         .chain(select.group_by.iter())
                         .chain(select.where_clause.iter())
-                        .for_each(|expr| self.visit_expr(expr)),
-                    BoundSetExpr::Values(_) => {}
+                        .for_each(|expr| self.visit_expr(expr));
                 }
             }
         }
@@ -311,17 +309,14 @@ impl ExprImpl {
             }
 
             fn visit_subquery(&mut self, subquery: &mut Subquery) {
-                use crate::binder::BoundSetExpr;
-
                 self.depth += 1;
-                match &mut subquery.query.body {
-                    BoundSetExpr::Select(select) => select
+                for select in subquery.query.body.selects_mut() {
+                    select
                         .select_items
                         .iter_mut()
                         .chain(select.group_by.iter_mut())
                         .chain(select.where_clause.iter_mut())
-                        .for_each(|expr| self.visit_expr(expr)),
-                    BoundSetExpr::Values(_) => {}
+                        .for_each(|expr| self.visit_expr(expr));
                 }
                 self.depth -= 1;
             }
@@ -497,6 +492,7 @@ impl Expr for ExprImpl {
             ExprImpl::Subquery(expr) => expr.return_type(),
             ExprImpl::CorrelatedInputRef(expr) => expr.return_type(),
             ExprImpl::TableFunction(expr) => expr.return_type(),
+            ExprImpl::WindowFunction(expr) => expr.return_type(),
         }
     }
 
@@ -511,6 +507,9 @@ impl Expr for ExprImpl {
             ExprImpl::TableFunction(_e) => {
                 unreachable!("Table function should not be converted to ExprNode")
             }
+            ExprImpl::WindowFunction(_e) => {
+                unreachable!("Window function should not be converted to ExprNode")
+            }
         }
     }
 }
@@ -557,6 +556,12 @@ impl From<TableFunction> for ExprImpl {
     }
 }
 
+impl From<WindowFunction> for ExprImpl {
+    fn from(wf: WindowFunction) -> Self {
+        ExprImpl::WindowFunction(Box::new(wf))
+    }
+}
+
 impl From<Condition> for ExprImpl {
     fn from(c: Condition) -> Self {
         merge_expr_by_binary(
@@ -583,6 +588,7 @@ impl std::fmt::Debug for ExprImpl {
                     f.debug_tuple("CorrelatedInputRef").field(arg0).finish()
                 }
                 Self::TableFunction(arg0) => f.debug_tuple("TableFunction").field(arg0).finish(),
+                Self::WindowFunction(arg0) => f.debug_tuple("WindowFunction").field(arg0).finish(),
             };
         }
         match self {
@@ -593,6 +599,7 @@ impl std::fmt::Debug for ExprImpl {
             Self::Subquery(x) => write!(f, "{:?}", x),
             Self::CorrelatedInputRef(x) => write!(f, "{:?}", x),
             Self::TableFunction(x) => write!(f, "{:?}", x),
+            Self::WindowFunction(x) => write!(f, "{:?}", x),
         }
     }
 }
@@ -630,6 +637,7 @@ impl std::fmt::Debug for ExprDisplay<'_> {
                 // TODO: TableFunctionCallVerboseDisplay
                 write!(f, "{:?}", x)
             }
+            ExprImpl::WindowFunction(x) => write!(f, "{:?}", x),
         }
     }
 }
@@ -0,0 +1,318 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+
+use itertools::Itertools;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_common::types::DataType;
+use risingwave_expr::expr::AggKind;
+use risingwave_pb::expr::window_frame::{
+    Bound as ProstBound, BoundType as ProstBoundType, Type as ProstFrameType,
+};
+use risingwave_pb::expr::window_function::{GeneralType, Type as ProstWindowFunctionType};
+use risingwave_pb::expr::WindowFrame as ProstWindowFrame;
+
+use super::{AggCall, AggOrderBy, Expr, ExprImpl, ExprRewriter};
+
+/// The kind of a window function.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WindowFunctionType {
+    RowNumber,
+    Rank,
+    DenseRank,
+    Lag,
+    Lead,
+    FirstValue,
+    LastValue,
+    /// An aggregate function used as a window function, e.g. `sum(v) over (...)`.
+    Aggregate(AggKind),
+}
+
+impl WindowFunctionType {
+    /// Whether the function takes the frame into account. The others are computed over the whole
+    /// partition regardless of the frame.
+    pub fn is_framed(&self) -> bool {
+        matches!(
+            self,
+            WindowFunctionType::Lag
+                | WindowFunctionType::Lead
+                | WindowFunctionType::FirstValue
+                | WindowFunctionType::LastValue
+                | WindowFunctionType::Aggregate(_)
+        )
+    }
+
+    pub fn to_protobuf(&self) -> ProstWindowFunctionType {
+        let general = match self {
+            WindowFunctionType::RowNumber => GeneralType::RowNumber,
+            WindowFunctionType::Rank => GeneralType::Rank,
+            WindowFunctionType::DenseRank => GeneralType::DenseRank,
+            WindowFunctionType::Lag => GeneralType::Lag,
+            WindowFunctionType::Lead => GeneralType::Lead,
+            WindowFunctionType::FirstValue => GeneralType::FirstValue,
+            WindowFunctionType::LastValue => GeneralType::LastValue,
+            WindowFunctionType::Aggregate(agg_kind) => {
+                return ProstWindowFunctionType::Aggregate(agg_kind.to_prost() as i32)
+            }
+        };
+        ProstWindowFunctionType::General(general as i32)
+    }
+}
+
+impl FromStr for WindowFunctionType {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "row_number" => Ok(WindowFunctionType::RowNumber),
+            "rank" => Ok(WindowFunctionType::Rank),
+            "dense_rank" => Ok(WindowFunctionType::DenseRank),
+            "lag" => Ok(WindowFunctionType::Lag),
+            "lead" => Ok(WindowFunctionType::Lead),
+            "first_value" => Ok(WindowFunctionType::FirstValue),
+            "last_value" => Ok(WindowFunctionType::LastValue),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for WindowFunctionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowFunctionType::RowNumber => write!(f, "row_number"),
+            WindowFunctionType::Rank => write!(f, "rank"),
+            WindowFunctionType::DenseRank => write!(f, "dense_rank"),
+            WindowFunctionType::Lag => write!(f, "lag"),
+            WindowFunctionType::Lead => write!(f, "lead"),
+            WindowFunctionType::FirstValue => write!(f, "first_value"),
+            WindowFunctionType::LastValue => write!(f, "last_value"),
+            WindowFunctionType::Aggregate(agg_kind) => write!(f, "{}", agg_kind),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameUnits {
+    Rows,
+    Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+impl FrameBound {
+    fn to_protobuf(self) -> ProstBound {
+        let (bound_type, offset) = match self {
+            FrameBound::UnboundedPreceding => (ProstBoundType::UnboundedPreceding, 0),
+            FrameBound::Preceding(offset) => (ProstBoundType::Preceding, offset),
+            FrameBound::CurrentRow => (ProstBoundType::CurrentRow, 0),
+            FrameBound::Following(offset) => (ProstBoundType::Following, offset),
+            FrameBound::UnboundedFollowing => (ProstBoundType::UnboundedFollowing, 0),
+        };
+        ProstBound {
+            r#type: bound_type as i32,
+            offset: offset as u64,
+        }
+    }
+}
+
+impl fmt::Display for FrameBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameBound::UnboundedPreceding => write!(f, "UNBOUNDED PRECEDING"),
+            FrameBound::Preceding(n) => write!(f, "{} PRECEDING", n),
+            FrameBound::CurrentRow => write!(f, "CURRENT ROW"),
+            FrameBound::Following(n) => write!(f, "{} FOLLOWING", n),
+            FrameBound::UnboundedFollowing => write!(f, "UNBOUNDED FOLLOWING"),
+        }
+    }
+}
+
+/// The frame of a window function. The rows of the frame are `[start, end]` of the partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+impl Default for Frame {
+    /// `RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW`, which covers the whole partition when
+    /// there's no `ORDER BY`, since all the rows are peers of the current row.
+    fn default() -> Self {
+        Self {
+            units: FrameUnits::Range,
+            start: FrameBound::UnboundedPreceding,
+            end: FrameBound::CurrentRow,
+        }
+    }
+}
+
+impl Frame {
+    /// A frame containing the single row `offset` rows before (negative) or after (positive) the
+    /// current row. It's used to evaluate `lag` and `lead`.
+    pub fn single_row(offset: isize) -> Self {
+        let bound = match offset {
+            0 => FrameBound::CurrentRow,
+            n if n < 0 => FrameBound::Preceding(n.unsigned_abs()),
+            n => FrameBound::Following(n as usize),
+        };
+        Self {
+            units: FrameUnits::Rows,
+            start: bound,
+            end: bound,
+        }
+    }
+
+    pub fn to_protobuf(self) -> ProstWindowFrame {
+        let frame_type = match self.units {
+            FrameUnits::Rows => ProstFrameType::Rows,
+            FrameUnits::Range => ProstFrameType::Range,
+        };
+        ProstWindowFrame {
+            r#type: frame_type as i32,
+            start: Some(self.start.to_protobuf()),
+            end: Some(self.end.to_protobuf()),
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = match self.units {
+            FrameUnits::Rows => "ROWS",
+            FrameUnits::Range => "RANGE",
+        };
+        write!(f, "{} BETWEEN {} AND {}", units, self.start, self.end)
+    }
+}
+
+/// A window function call, i.e. a function with an `OVER` clause. It's planned as a
+/// [`LogicalOverAgg`](crate::optimizer::plan_node::LogicalOverAgg) and then replaced by an
+/// [`InputRef`](super::InputRef) to its output.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct WindowFunction {
+    pub args: Vec<ExprImpl>,
+    pub return_type: DataType,
+    pub function_type: WindowFunctionType,
+    pub partition_by: Vec<ExprImpl>,
+    pub order_by: AggOrderBy,
+    pub frame: Frame,
+}
+
+impl WindowFunction {
+    /// Create a `WindowFunction` expr with the return type inferred from `function_type` and types
+    /// of `args`.
+    pub fn new(
+        function_type: WindowFunctionType,
+        args: Vec<ExprImpl>,
+        partition_by: Vec<ExprImpl>,
+        order_by: AggOrderBy,
+        frame: Frame,
+    ) -> Result<Self> {
+        let return_type = match (&function_type, &args[..]) {
+            (
+                WindowFunctionType::RowNumber
+                | WindowFunctionType::Rank
+                | WindowFunctionType::DenseRank,
+                [],
+            ) => DataType::Int64,
+            (
+                WindowFunctionType::Lag
+                | WindowFunctionType::Lead
+                | WindowFunctionType::FirstValue
+                | WindowFunctionType::LastValue,
+                [arg],
+            ) => arg.return_type(),
+            (WindowFunctionType::Aggregate(agg_kind), args) => AggCall::infer_return_type(
+                agg_kind,
+                &args.iter().map(|arg| arg.return_type()).collect_vec(),
+            )?,
+            _ => {
+                return Err(ErrorCode::InvalidInputSyntax(format!(
+                    "Invalid window function: {}({})",
+                    function_type,
+                    args.iter()
+                        .map(|arg| format!("{:?}", arg.return_type()))
+                        .join(", ")
+                ))
+                .into())
+            }
+        };
+        Ok(Self {
+            args,
+            return_type,
+            function_type,
+            partition_by,
+            order_by,
+            frame,
+        })
+    }
+
+    pub fn rewrite(self, rewriter: &mut (impl ExprRewriter + ?Sized)) -> Self {
+        Self {
+            args: self
+                .args
+                .into_iter()
+                .map(|e| rewriter.rewrite_expr(e))
+                .collect(),
+            partition_by: self
+                .partition_by
+                .into_iter()
+                .map(|e| rewriter.rewrite_expr(e))
+                .collect(),
+            order_by: self.order_by.rewrite_expr(rewriter),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Debug for WindowFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            f.debug_struct("WindowFunction")
+                .field("function_type", &self.function_type)
+                .field("return_type", &self.return_type)
+                .field("args", &self.args)
+                .field("partition_by", &self.partition_by)
+                .field("order_by", &self.order_by)
+                .field("frame", &self.frame)
+                .finish()
+        } else {
+            let mut builder = f.debug_tuple(&self.function_type.to_string());
+            self.args.iter().for_each(|child| {
+                builder.field(child);
+            });
+            builder.finish()
+        }
+    }
+}
+
+impl Expr for WindowFunction {
+    fn return_type(&self) -> DataType {
+        self.return_type.clone()
+    }
+
+    fn to_expr_proto(&self) -> risingwave_pb::expr::ExprNode {
+        unreachable!("Window function should not be converted to ExprNode")
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures_async_stream::for_await;
+use itertools::Itertools;
+use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::array::Row;
+use risingwave_common::error::ErrorCode::{InternalError, PermissionDenied};
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_sqlparser::ast::ObjectName;
+use risingwave_sqlparser::parser::Parser;
+
+use super::privilege::check_super_user;
+use crate::binder::Binder;
+use crate::catalog::table_statistics::{ColumnStatistics, Histogram};
+use crate::catalog::{ColumnId, TableStatistics};
+use crate::handler::query::local_execute;
+use crate::session::{OptimizerContext, SessionImpl};
+
+/// The number of buckets of the histograms of the numeric columns.
+const HISTOGRAM_BUCKETS: usize = 16;
+
+/// A column to collect the statistics of.
+struct AnalyzedColumn {
+    id: ColumnId,
+    /// Quoted to be used in the queries.
+    name: String,
+    is_numeric: bool,
+}
+
+/// Collects the row count of the table, and the null count, the approximate distinct count and
+/// the histogram of its columns, by scanning the table with two queries in local mode. The
+/// statistics are stored in the catalog and used by the optimizer to order the joins.
+///
+/// Each query scans and aggregates the whole table in local mode, so analyzing a large table is
+/// costly.
+/// The second one, which counts the rows in the buckets of the histograms, is skipped if no
+/// numeric column has distinct values.
+pub async fn handle_analyze(
+    context: OptimizerContext,
+    table_name: ObjectName,
+) -> Result<PgResponse> {
+    let session = context.session_ctx;
+    let (schema_name, table_name) = Binder::resolve_table_name(table_name)?;
+
+    let (table_id, columns) = {
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let table =
+            catalog_reader.get_table_by_name(session.database(), &schema_name, &table_name)?;
+        let schema_owner = catalog_reader
+            .get_schema_by_name(session.database(), &schema_name)?
+            .owner();
+        if session.user_id() != table.owner
+            && session.user_id() != schema_owner
+            && !check_super_user(&session)
+        {
+            return Err(PermissionDenied("Do not have the privilege".to_string()).into());
+        }
+        let columns = table
+            .columns()
+            .iter()
+            .filter(|column| {
+                !column.is_hidden()
+                    && !matches!(
+                        column.data_type(),
+                        DataType::Struct { .. } | DataType::List { .. }
+                    )
+            })
+            .map(|column| AnalyzedColumn {
+                id: column.column_id(),
+                name: quote_ident(column.name()),
+                is_numeric: column.data_type().is_numeric(),
+            })
+            .collect_vec();
+        (table.id, columns)
+    };
+    let from = format!("{}.{}", quote_ident(&schema_name), quote_ident(&table_name));
+
+    // count(*), then for each column: count, approx_count_distinct, and min, max if numeric.
+    let mut select_items = vec!["count(*)".to_string()];
+    for column in &columns {
+        select_items.push(format!("count({})", column.name));
+        select_items.push(format!("approx_count_distinct({})", column.name));
+        if column.is_numeric {
+            select_items.push(format!("CAST(min({}) AS DOUBLE PRECISION)", column.name));
+            select_items.push(format!("CAST(max({}) AS DOUBLE PRECISION)", column.name));
+        }
+    }
+    let row = query_one_row(
+        session.clone(),
+        format!("SELECT {} FROM {}", select_items.join(", "), from),
+    )
+    .await?;
+
+    let mut values = row.0.into_iter();
+    let row_count = get_count(values.next());
+    let mut statistics = TableStatistics {
+        row_count,
+        columns: HashMap::new(),
+    };
+    // The columns having distinct values, with the min and max of them.
+    let mut ranges = vec![];
+    for column in &columns {
+        let non_null_count = get_count(values.next());
+        let distinct_count = get_count(values.next());
+        let mut histogram = None;
+        if column.is_numeric {
+            let min = get_float(values.next());
+            let max = get_float(values.next());
+            if let (Some(min), Some(max)) = (min, max) {
+                if min < max {
+                    ranges.push((column, min, max));
+                } else {
+                    histogram = Some(Histogram {
+                        min,
+                        max,
+                        bucket_counts: vec![non_null_count],
+                    });
+                }
+            }
+        }
+        statistics.columns.insert(
+            column.id,
+            ColumnStatistics {
+                null_count: row_count - non_null_count,
+                distinct_count,
+                histogram,
+            },
+        );
+    }
+
+    if !ranges.is_empty() {
+        let mut select_items = vec![];
+        for (column, min, max) in &ranges {
+            for bucket in 0..HISTOGRAM_BUCKETS {
+                let lower = bucket_bound(*min, *max, bucket);
+                let condition = if bucket + 1 == HISTOGRAM_BUCKETS {
+                    format!("{} >= {}", column.name, lower)
+                } else {
+                    let upper = bucket_bound(*min, *max, bucket + 1);
+                    format!("{0} >= {1} AND {0} < {2}", column.name, lower, upper)
+                };
+                select_items.push(format!("count(CASE WHEN {} THEN 1 END)", condition));
+            }
+        }
+        let row = query_one_row(
+            session.clone(),
+            format!("SELECT {} FROM {}", select_items.join(", "), from),
+        )
+        .await?;
+
+        let mut values = row.0.into_iter();
+        for (column, min, max) in ranges {
+            let bucket_counts = (0..HISTOGRAM_BUCKETS)
+                .map(|_| get_count(values.next()))
+                .collect();
+            statistics.columns.get_mut(&column.id).unwrap().histogram = Some(Histogram {
+                min,
+                max,
+                bucket_counts,
+            });
+        }
+    }
+
+    let catalog_writer = session.env().catalog_writer();
+    catalog_writer
+        .update_table_statistics(table_id, statistics)
+        .await?;
+
+    Ok(PgResponse::empty_result(StatementType::ANALYZE))
+}
+
+/// Runs the query in local mode, where the aggregations are done in a single phase so that
+/// `approx_count_distinct` is not summed up across the partial results.
+async fn query_one_row(session: Arc<SessionImpl>, sql: String) -> Result<Row> {
+    let context = OptimizerContext::new(session.clone(), Arc::from(sql.as_str()));
+    let stmt = Parser::parse_sql(&sql)
+        .map_err(|e| RwError::from(InternalError(e.to_string())))?
+        .into_iter()
+        .exactly_one()
+        .map_err(|_| RwError::from(InternalError("expect one statement".to_string())))?;
+    let bound = Binder::new(&session).bind(stmt)?;
+    let (data_stream, _) = local_execute(context, bound)?;
+
+    let mut rows = vec![];
+    #[for_await]
+    for chunk in data_stream {
+        rows.extend(chunk?.rows().map(|row| row.to_owned_row()));
+    }
+    rows.into_iter().exactly_one().map_err(|_| {
+        RwError::from(InternalError(
+            "expect one row from the aggregation".to_string(),
+        ))
+    })
+}
+
+/// The bound of the bucket as a literal, which is cast from a string to keep the precision.
+fn bucket_bound(min: f64, max: f64, bucket: usize) -> String {
+    let bound = min + (max - min) * bucket as f64 / HISTOGRAM_BUCKETS as f64;
+    format!("CAST('{}' AS DOUBLE PRECISION)", bound)
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn get_count(value: Option<Option<ScalarImpl>>) -> u64 {
+    match value.flatten() {
+        Some(ScalarImpl::Int64(count)) => count as u64,
+        _ => 0,
+    }
+}
+
+fn get_float(value: Option<Option<ScalarImpl>>) -> Option<f64> {
+    match value.flatten() {
+        Some(ScalarImpl::Float64(value)) => Some(value.into_inner()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_bound() {
+        assert_eq!(
+            bucket_bound(0.0, 160.0, 3),
+            "CAST('30' AS DOUBLE PRECISION)"
+        );
+        assert_eq!(quote_ident("a\"b"), "\"a\"\"b\"");
+    }
+}
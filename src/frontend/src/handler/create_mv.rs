@@ -16,10 +16,11 @@ use pgwire::pg_response::{PgResponse, StatementType};
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_pb::catalog::Table as ProstTable;
 use risingwave_pb::user::grant_privilege::{Action, Object};
+use risingwave_source::rate_limit::rate_limit_from_properties;
 use risingwave_sqlparser::ast::{ObjectName, Query};
 
 use super::privilege::{check_privileges, resolve_relation_privileges};
-use crate::binder::{Binder, BoundSetExpr};
+use crate::binder::Binder;
 use crate::catalog::check_schema_writable;
 use crate::handler::privilege::ObjectCheckItem;
 use crate::optimizer::property::RequiredDist;
@@ -61,7 +62,7 @@ pub fn gen_create_mv_plan(
         binder.bind_query(*query)?
     };
 
-    if let BoundSetExpr::Select(select) = &bound.body {
+    for select in bound.body.selects() {
         // `InputRef`'s alias will be implicitly assigned in `bind_project`.
         // For other expressions, we require the user to explicitly assign an alias.
         if select.aliases.iter().any(Option::is_none) {
@@ -100,6 +101,7 @@ pub async fn handle_create_mv(
     query: Box<Query>,
 ) -> Result<PgResponse> {
     let session = context.session_ctx.clone();
+    rate_limit_from_properties(&context.with_properties)?;
 
     let (table, graph) = {
         let (plan, table) = gen_create_mv_plan(&session, context.into(), query, name)?;
@@ -15,8 +15,12 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use itertools::Itertools;
 use pgwire::pg_response::{PgResponse, StatementType};
-use risingwave_common::error::Result;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_connector::sink::{
+    SinkConfig, SinkImpl, SINK_FORMAT_APPEND_ONLY, SINK_FORMAT_OPTION,
+};
 use risingwave_pb::catalog::Sink as ProstSink;
 use risingwave_pb::user::grant_privilege::{Action, Object};
 use risingwave_sqlparser::ast::CreateSinkStatement;
@@ -81,7 +85,7 @@ pub fn gen_sink_plan(
         )?
     };
 
-    let (associated_table_id, associated_table_name, associated_table_desc) = {
+    let (associated_table_id, associated_table_name, associated_table_desc, hidden_column_indices) = {
         let catalog_reader = session.env().catalog_reader().read_guard();
         let table = catalog_reader.get_table_by_name(
             session.database(),
@@ -92,6 +96,11 @@ pub fn gen_sink_plan(
             table.id().table_id,
             table.name().to_string(),
             table.table_desc(),
+            table
+                .columns()
+                .iter()
+                .positions(|column| column.is_hidden)
+                .collect_vec(),
         )
     };
 
@@ -113,7 +122,18 @@ pub fn gen_sink_plan(
     ))
     .into();
 
-    let plan: PlanRef = StreamSink::new(scan_node, with_properties).into();
+    let plan: PlanRef =
+        StreamSink::new(scan_node, with_properties.clone(), hidden_column_indices).into();
+
+    if with_properties.get(SINK_FORMAT_OPTION).map(String::as_str) == Some(SINK_FORMAT_APPEND_ONLY)
+        && !plan.append_only()
+    {
+        return Err(ErrorCode::InvalidInputSyntax(format!(
+            "{} is not append-only, and can only be sinked with format = 'upsert' or 'debezium'",
+            stmt.materialized_view
+        ))
+        .into());
+    }
 
     let ctx = plan.ctx();
     let explain_trace = ctx.is_explain_trace();
@@ -131,16 +151,41 @@ pub async fn handle_create_sink(
 ) -> Result<PgResponse> {
     let session = context.session_ctx.clone();
 
-    let (sink, graph) = {
+    let (sink, graph, schema, hidden_column_indices) = {
         let (plan, sink) = gen_sink_plan(&session, context.into(), stmt)?;
+        let schema = plan.schema().clone();
+        let hidden_column_indices = plan
+            .as_stream_sink()
+            .unwrap()
+            .hidden_column_indices()
+            .to_vec();
 
-        (sink, StreamFragmenterV2::build_graph(plan))
+        (
+            sink,
+            StreamFragmenterV2::build_graph(plan),
+            schema,
+            hidden_column_indices,
+        )
     };
 
+    // Reject the invalid sinks early, instead of failing the sink executors.
+    let sink_config = SinkConfig::from_hashmap(sink.properties.clone())?;
+    SinkImpl::validate(&sink_config, &schema, &hidden_column_indices).await?;
+
     let catalog_writer = session.env().catalog_writer();
     catalog_writer.create_sink(sink, graph).await?;
 
-    Ok(PgResponse::empty_result(StatementType::CREATE_SINK))
+    if sink_config.support_two_phase_commit() {
+        Ok(PgResponse::empty_result(StatementType::CREATE_SINK))
+    } else {
+        Ok(PgResponse::empty_result_with_notice(
+            StatementType::CREATE_SINK,
+            format!(
+                "the {} sink is not exactly-once, and may write the rows again after a recovery",
+                sink_config.get_connector()
+            ),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -161,7 +206,7 @@ pub mod tests {
         let frontend = LocalFrontend::new(Default::default()).await;
         frontend.run_sql(sql).await.unwrap();
 
-        let sql = "create materialized view mv1 as select t1.country from t1;";
+        let sql = "create materialized view mv1 as select t1.zipcode from t1;";
         frontend.run_sql(sql).await.unwrap();
 
         let sql = r#"CREATE SINK snk1 FROM mv1
@@ -197,4 +242,25 @@ pub mod tests {
             .clone();
         assert_eq!(sink.name, "snk1");
     }
+
+    #[tokio::test]
+    async fn test_create_append_only_sink_on_updatable_mv() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 int);")
+            .await
+            .unwrap();
+        frontend
+            .run_sql(
+                "create materialized view mv as select v1, count(*) as cnt from t group by v1;",
+            )
+            .await
+            .unwrap();
+
+        let sql = r#"CREATE SINK snk FROM mv
+                    WITH (connector = 'kafka', kafka.brokers = '127.0.0.1:9092',
+                        kafka.topic = 'sink', format = 'append_only');"#;
+        let err = frontend.run_sql(sql).await.unwrap_err();
+        assert!(err.to_string().contains("not append-only"), "{}", err);
+    }
 }
@@ -12,15 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use itertools::Itertools;
 use pgwire::pg_response::{PgResponse, StatementType};
-use risingwave_common::error::Result;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_connector::source::{MYSQL_CDC_CONNECTOR, POSTGRES_CDC_CONNECTOR};
 use risingwave_pb::catalog::source::Info;
 use risingwave_pb::catalog::{Source as ProstSource, StreamSourceInfo};
-use risingwave_pb::plan_common::{ColumnCatalog as ProstColumnCatalog, RowFormatType};
+use risingwave_pb::plan_common::{
+    ColumnCatalog as ProstColumnCatalog, ColumnDesc as ProstColumnDesc, CsvInfo as ProstCsvInfo,
+    RowFormatType,
+};
 use risingwave_pb::user::grant_privilege::{Action, Object};
-use risingwave_source::ProtobufParser;
-use risingwave_sqlparser::ast::{CreateSourceStatement, ObjectName, ProtobufSchema, SourceSchema};
+use risingwave_source::dead_letter::SourceErrorPolicy;
+use risingwave_source::meta_column::SourceMetaColumn;
+use risingwave_source::parser::schema_registry::SCHEMA_REGISTRY_KEY;
+use risingwave_source::rate_limit::rate_limit_from_properties;
+use risingwave_source::{AvroParser, ProtobufParser};
+use risingwave_sqlparser::ast::{
+    AstString, AvroSchema, CreateSourceStatement, ObjectName, ProtobufSchema, SourceSchema,
+};
 
 use super::create_table::{bind_sql_columns, gen_materialized_source_plan};
 use super::privilege::check_privileges;
@@ -66,18 +79,74 @@ pub(crate) fn make_prost_source(
     })
 }
 
-/// Map a protobuf schema to a relational schema.
-fn extract_protobuf_table_schema(schema: &ProtobufSchema) -> Result<Vec<ProstColumnCatalog>> {
-    let parser = ProtobufParser::new(&schema.row_schema_location.0, &schema.message_name.0)?;
-    let column_descs = parser.map_to_columns()?;
+/// Returns the location of the schema file, which may be omitted if the schema is fetched from the
+/// schema registry instead.
+fn schema_location<'a>(
+    location: &'a Option<AstString>,
+    properties: &HashMap<String, String>,
+) -> Result<&'a str> {
+    match location {
+        Some(location) => Ok(&location.0),
+        None if properties.contains_key(SCHEMA_REGISTRY_KEY) => Ok(""),
+        None => Err(RwError::from(ProtocolError(format!(
+            "either ROW SCHEMA LOCATION or '{}' must be specified",
+            SCHEMA_REGISTRY_KEY
+        )))),
+    }
+}
 
-    Ok(column_descs
+fn to_column_catalogs(column_descs: Vec<ProstColumnDesc>) -> Vec<ProstColumnCatalog> {
+    column_descs
         .into_iter()
         .map(|col| ProstColumnCatalog {
             column_desc: Some(col),
             is_hidden: false,
         })
-        .collect_vec())
+        .collect_vec()
+}
+
+/// Map a protobuf schema to a relational schema.
+async fn extract_protobuf_table_schema(
+    schema: &ProtobufSchema,
+    properties: &HashMap<String, String>,
+) -> Result<Vec<ProstColumnCatalog>> {
+    let parser = if properties.contains_key(SCHEMA_REGISTRY_KEY) {
+        ProtobufParser::new_with_schema_registry(properties, &schema.message_name.0).await?
+    } else {
+        ProtobufParser::new(
+            schema_location(&schema.row_schema_location, properties)?,
+            &schema.message_name.0,
+        )?
+    };
+    Ok(to_column_catalogs(parser.map_to_columns()?))
+}
+
+/// Map an avro schema to a relational schema, where the latest schema of the subject is used if
+/// the schema registry is specified.
+async fn extract_avro_table_schema(
+    schema: &AvroSchema,
+    properties: &HashMap<String, String>,
+) -> Result<Vec<ProstColumnCatalog>> {
+    let parser = if properties.contains_key(SCHEMA_REGISTRY_KEY) {
+        AvroParser::new_with_schema_registry(properties).await?
+    } else {
+        AvroParser::new(
+            schema_location(&schema.row_schema_location, properties)?,
+            properties.clone(),
+        )
+        .await?
+    };
+    Ok(to_column_catalogs(parser.map_to_columns()?))
+}
+
+/// Checks the names and types of the metadata columns, which are filled from the metadata of the
+/// messages instead of the payloads.
+fn check_source_meta_columns(columns: &[ProstColumnCatalog]) -> Result<()> {
+    for column in columns {
+        let desc = column.column_desc.as_ref().unwrap();
+        SourceMetaColumn::check_column(&desc.name, &desc.column_type.as_ref().unwrap().into())?;
+    }
+    Ok(())
 }
 
 pub async fn handle_create_source(
@@ -86,18 +155,70 @@ pub async fn handle_create_source(
     stmt: CreateSourceStatement,
 ) -> Result<PgResponse> {
     let with_properties = handle_with_properties("create_source", stmt.with_properties.0)?;
+    // Reject an unknown error policy or rate limit early rather than on the compute nodes.
+    SourceErrorPolicy::from_properties(&with_properties)?;
+    rate_limit_from_properties(&with_properties)?;
+    // The CDC connectors emit the changes in the Debezium JSON format.
+    if let Some(connector) = with_properties.get("connector") {
+        if [MYSQL_CDC_CONNECTOR, POSTGRES_CDC_CONNECTOR]
+            .iter()
+            .any(|cdc| connector.eq_ignore_ascii_case(cdc))
+            && !matches!(stmt.source_schema, SourceSchema::DebeziumJson)
+        {
+            return Err(RwError::from(ProtocolError(format!(
+                "connector '{}' requires ROW FORMAT DEBEZIUM_JSON",
+                connector.to_lowercase()
+            ))));
+        }
+    }
 
     let source = match &stmt.source_schema {
         SourceSchema::Protobuf(protobuf_schema) => {
             let mut columns = vec![ColumnCatalog::row_id_column().to_protobuf()];
-            columns.extend(extract_protobuf_table_schema(protobuf_schema)?.into_iter());
+            columns.extend(
+                extract_protobuf_table_schema(protobuf_schema, &with_properties)
+                    .await?
+                    .into_iter(),
+            );
             StreamSourceInfo {
                 properties: with_properties.clone(),
                 row_format: RowFormatType::Protobuf as i32,
-                row_schema_location: protobuf_schema.row_schema_location.0.clone(),
+                row_schema_location: schema_location(
+                    &protobuf_schema.row_schema_location,
+                    &with_properties,
+                )?
+                .to_string(),
+                row_id_index: 0,
+                columns,
+                pk_column_ids: vec![0],
+                csv_info: None,
+            }
+        }
+        SourceSchema::Avro(avro_schema) => {
+            // The columns are inferred from the avro schema unless they're given explicitly.
+            let columns = if stmt.columns.is_empty() {
+                let mut columns = vec![ColumnCatalog::row_id_column().to_protobuf()];
+                columns.extend(
+                    extract_avro_table_schema(avro_schema, &with_properties)
+                        .await?
+                        .into_iter(),
+                );
+                columns
+            } else {
+                bind_sql_columns(stmt.columns)?
+            };
+            StreamSourceInfo {
+                properties: with_properties.clone(),
+                row_format: RowFormatType::Avro as i32,
+                row_schema_location: schema_location(
+                    &avro_schema.row_schema_location,
+                    &with_properties,
+                )?
+                .to_string(),
                 row_id_index: 0,
                 columns,
                 pk_column_ids: vec![0],
+                csv_info: None,
             }
         }
         SourceSchema::Json => StreamSourceInfo {
@@ -107,6 +228,7 @@ pub async fn handle_create_source(
             row_id_index: 0,
             columns: bind_sql_columns(stmt.columns)?,
             pk_column_ids: vec![0],
+            csv_info: None,
         },
         SourceSchema::DebeziumJson => StreamSourceInfo {
             properties: with_properties.clone(),
@@ -115,8 +237,58 @@ pub async fn handle_create_source(
             row_id_index: 0,
             columns: bind_sql_columns(stmt.columns)?,
             pk_column_ids: vec![0],
+            csv_info: None,
+        },
+        SourceSchema::DebeziumAvro => {
+            if !with_properties.contains_key(SCHEMA_REGISTRY_KEY) {
+                return Err(RwError::from(ProtocolError(format!(
+                    "'{}' must be specified for ROW FORMAT DEBEZIUM_AVRO",
+                    SCHEMA_REGISTRY_KEY
+                ))));
+            }
+            StreamSourceInfo {
+                properties: with_properties.clone(),
+                row_format: RowFormatType::DebeziumAvro as i32,
+                row_schema_location: "".to_string(),
+                row_id_index: 0,
+                columns: bind_sql_columns(stmt.columns)?,
+                pk_column_ids: vec![0],
+                csv_info: None,
+            }
+        }
+        SourceSchema::Maxwell => StreamSourceInfo {
+            properties: with_properties.clone(),
+            row_format: RowFormatType::Maxwell as i32,
+            row_schema_location: "".to_string(),
+            row_id_index: 0,
+            columns: bind_sql_columns(stmt.columns)?,
+            pk_column_ids: vec![0],
+            csv_info: None,
+        },
+        SourceSchema::CanalJson => StreamSourceInfo {
+            properties: with_properties.clone(),
+            row_format: RowFormatType::CanalJson as i32,
+            row_schema_location: "".to_string(),
+            row_id_index: 0,
+            columns: bind_sql_columns(stmt.columns)?,
+            pk_column_ids: vec![0],
+            csv_info: None,
+        },
+        SourceSchema::Csv(csv_info) => StreamSourceInfo {
+            properties: with_properties.clone(),
+            row_format: RowFormatType::Csv as i32,
+            row_schema_location: "".to_string(),
+            row_id_index: 0,
+            columns: bind_sql_columns(stmt.columns)?,
+            pk_column_ids: vec![0],
+            csv_info: Some(ProstCsvInfo {
+                delimiter: csv_info.delimiter as u32,
+                quote: csv_info.quote as u32,
+                has_header: csv_info.has_header,
+            }),
         },
     };
+    check_source_meta_columns(&source.columns)?;
 
     let session = context.session_ctx.clone();
     let source = make_prost_source(&session, stmt.source_name, Info::StreamSource(source))?;
@@ -0,0 +1,258 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use pgwire::pg_field_descriptor::{PgFieldDescriptor, TypeOid};
+use pgwire::pg_response::{PgResponse, StatementType};
+use pgwire::types::Row;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_pb::stream_service::actor_stats_response::ActorStats;
+use risingwave_pb::stream_service::ActorStatsRequest;
+use risingwave_sqlparser::ast::{ObjectName, Statement};
+
+use crate::binder::Binder;
+use crate::handler::util::force_local_mode;
+use crate::optimizer::plan_node::PlanRef;
+use crate::planner::Planner;
+use crate::scheduler::{
+    BatchPlanFragmenter, ExecutionContext, ExecutionContextRef, QueryExecutorStats,
+};
+use crate::session::{OptimizerContext, SessionImpl};
+
+/// How long the throughput of the actors is measured for.
+const ACTOR_STATS_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for the statistics of the tasks after the results are all received. The
+/// statistics of the tasks not reported by then are left out.
+const EXECUTOR_STATS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs the query in the distributed mode, and shows its plan with the runtime statistics of the
+/// operators reported by the tasks.
+pub(super) async fn handle_explain_analyze(
+    context: OptimizerContext,
+    stmt: Statement,
+    verbose: bool,
+) -> Result<PgResponse> {
+    if !matches!(stmt, Statement::Query(_)) {
+        return Err(
+            ErrorCode::NotImplemented(format!("EXPLAIN ANALYZE {}", stmt), None.into()).into(),
+        );
+    }
+    let session = context.session_ctx.clone();
+    context.explain_verbose.store(verbose, Ordering::Release);
+
+    // Subblock to make sure PlanRef (an Rc) is dropped before `await` below.
+    let (query, lines) = {
+        let bound = {
+            let mut binder = Binder::new(&session);
+            binder.bind(stmt)?
+        };
+        if force_local_mode(&bound) {
+            return Err(ErrorCode::NotImplemented(
+                "EXPLAIN ANALYZE of queries on system tables".to_string(),
+                None.into(),
+            )
+            .into());
+        }
+        let plan = Planner::new(context.into())
+            .plan(bound)?
+            .gen_batch_query_plan()?;
+
+        let mut lines = vec![];
+        explain_lines(&plan, 0, &mut lines);
+
+        let plan_fragmenter = BatchPlanFragmenter::new(session.env().worker_node_manager_ref());
+        (plan_fragmenter.split(plan)?, lines)
+    };
+
+    let executor_stats = Arc::new(QueryExecutorStats::new(query.stage_graph.stages.len()));
+    let execution_context: ExecutionContextRef = ExecutionContext::new(session.clone())
+        .with_executor_stats(executor_stats.clone())
+        .into();
+    let query_manager = session.env().query_manager().clone();
+
+    let start = Instant::now();
+    let mut data_stream = Box::pin(query_manager.schedule(execution_context, query).await?);
+    // The results are discarded, as in PostgreSQL.
+    while let Some(chunk) = data_stream.next().await {
+        chunk?;
+    }
+    let execution_time = start.elapsed();
+    drop(data_stream);
+    if tokio::time::timeout(EXECUTOR_STATS_TIMEOUT, executor_stats.wait_finished())
+        .await
+        .is_err()
+    {
+        tracing::warn!("timed out waiting for the executor stats of EXPLAIN ANALYZE");
+    }
+
+    let mut rows = lines
+        .into_iter()
+        .map(
+            |(line, operator_id)| match executor_stats.get(operator_id) {
+                Some(stats) => format!("{} ({})", line, stats),
+                None => line,
+            },
+        )
+        .collect::<Vec<_>>();
+    rows.push(format!(
+        "Execution time: {:.3} ms",
+        execution_time.as_secs_f64() * 1000.0
+    ));
+    Ok(query_plan_response(rows))
+}
+
+/// Shows the throughput and backpressure of the actors of the materialized view, sampled from the
+/// streaming metrics of the compute nodes.
+pub(super) async fn handle_explain_analyze_mv(
+    context: OptimizerContext,
+    name: ObjectName,
+) -> Result<PgResponse> {
+    let session = context.session_ctx;
+    let (schema_name, table_name) = Binder::resolve_table_name(name)?;
+    let table_id = {
+        let reader = session.env().catalog_reader().read_guard();
+        let table = reader.get_table_by_name(session.database(), &schema_name, &table_name)?;
+        table.id().table_id
+    };
+
+    let mut table_fragments = session
+        .env()
+        .meta_client()
+        .list_table_fragments(&[table_id])
+        .await?;
+    let mut fragments = table_fragments
+        .remove(&table_id)
+        .map(|info| info.fragments)
+        .unwrap_or_default();
+    fragments.sort_by_key(|fragment| fragment.id);
+    let actor_ids = fragments
+        .iter()
+        .flat_map(|fragment| fragment.actors.iter().map(|actor| actor.id))
+        .collect::<Vec<_>>();
+
+    let before = collect_actor_stats(&session, &actor_ids).await?;
+    let start = Instant::now();
+    tokio::time::sleep(ACTOR_STATS_SAMPLE_INTERVAL).await;
+    let after = collect_actor_stats(&session, &actor_ids).await?;
+    let interval = start.elapsed();
+
+    let mut rows = vec![];
+    for fragment in fragments {
+        rows.push(format!("Fragment {}", fragment.id));
+        let mut actor_ids = fragment
+            .actors
+            .iter()
+            .map(|actor| actor.id)
+            .collect::<Vec<_>>();
+        actor_ids.sort_unstable();
+        for actor_id in actor_ids {
+            let stats = match (before.get(&actor_id), after.get(&actor_id)) {
+                (Some(before), Some(after)) => explain_actor_stats(before, after, interval),
+                _ => "not running".to_string(),
+            };
+            rows.push(format!("  Actor {} ({})", actor_id, stats));
+        }
+    }
+    Ok(query_plan_response(rows))
+}
+
+/// The lines of `EXPLAIN` of the plan, each with the id of its plan node.
+fn explain_lines(plan: &PlanRef, level: usize, lines: &mut Vec<(String, i32)>) {
+    lines.push((format!("{}{}", " ".repeat(level * 2), plan), plan.id().0));
+    for input in plan.inputs() {
+        explain_lines(&input, level + 1, lines);
+    }
+}
+
+/// Collects the statistics of the actors from all compute nodes.
+async fn collect_actor_stats(
+    session: &SessionImpl,
+    actor_ids: &[u32],
+) -> Result<HashMap<u32, ActorStats>> {
+    let mut actor_stats = HashMap::new();
+    for worker in session.env().worker_node_manager().list_worker_nodes() {
+        let client = session.env().stream_client_pool().get(&worker).await?;
+        let response = client
+            .actor_stats(ActorStatsRequest {
+                actor_ids: actor_ids.to_vec(),
+            })
+            .await?;
+        actor_stats.extend(response.actor_stats);
+    }
+    Ok(actor_stats)
+}
+
+/// The throughput of the actor and the ratio of the time blocked by the downstream between two
+/// samples of its statistics.
+fn explain_actor_stats(before: &ActorStats, after: &ActorStats, interval: Duration) -> String {
+    let secs = interval.as_secs_f64();
+    let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / secs;
+    let blocking_secs = rate(
+        before.output_blocking_duration_ns,
+        after.output_blocking_duration_ns,
+    ) / 1e9;
+    format!(
+        "input: {:.1} rows/s, output: {:.1} rows/s, backpressure: {:.1}%, total output rows: {}",
+        rate(before.input_rows, after.input_rows),
+        rate(before.output_rows, after.output_rows),
+        (blocking_secs * 100.0).min(100.0),
+        after.output_rows
+    )
+}
+
+fn query_plan_response(rows: Vec<String>) -> PgResponse {
+    let rows = rows
+        .into_iter()
+        .map(|s| Row::new(vec![Some(s.into())]))
+        .collect::<Vec<_>>();
+    PgResponse::new(
+        StatementType::EXPLAIN,
+        rows.len() as i32,
+        rows,
+        vec![PgFieldDescriptor::new(
+            "QUERY PLAN".to_owned(),
+            TypeOid::Varchar,
+        )],
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_actor_stats() {
+        let before = ActorStats {
+            input_rows: 1000,
+            output_rows: 500,
+            output_blocking_duration_ns: 1_000_000_000,
+        };
+        let after = ActorStats {
+            input_rows: 3000,
+            output_rows: 1500,
+            output_blocking_duration_ns: 1_500_000_000,
+        };
+        assert_eq!(
+            explain_actor_stats(&before, &after, Duration::from_secs(2)),
+            "input: 1000.0 rows/s, output: 500.0 rows/s, backpressure: 25.0%, total output rows: 1500"
+        );
+    }
+}
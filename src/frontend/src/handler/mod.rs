@@ -23,6 +23,7 @@ use self::util::handle_with_properties;
 use crate::session::{OptimizerContext, SessionImpl};
 
 pub mod alter_user;
+mod analyze;
 mod create_database;
 pub mod create_index;
 pub mod create_mv;
@@ -42,6 +43,7 @@ pub mod drop_source;
 pub mod drop_table;
 pub mod drop_user;
 mod explain;
+mod explain_analyze;
 mod flush;
 pub mod handle_privilege;
 pub mod privilege;
@@ -60,6 +62,7 @@ pub async fn handle(
     match stmt {
         Statement::Explain {
             statement,
+            analyze,
             verbose,
             trace,
             ..
@@ -74,7 +77,14 @@ pub async fn handle(
                 _ => {}
             }
 
-            explain::handle_explain(context, *statement, verbose, trace)
+            if analyze {
+                explain_analyze::handle_explain_analyze(context, *statement, verbose).await
+            } else {
+                explain::handle_explain(context, *statement, verbose, trace)
+            }
+        }
+        Statement::ExplainAnalyzeMaterializedView { name } => {
+            explain_analyze::handle_explain_analyze_mv(context, name).await
         }
         Statement::CreateSource {
             is_materialized,
@@ -154,6 +164,7 @@ pub async fn handle(
             create_mv::handle_create_mv(context, name, query).await
         }
         Statement::Flush => flush::handle_flush(context).await,
+        Statement::Analyze { table_name } => analyze::handle_analyze(context, table_name).await,
         Statement::SetVariable {
             local: _,
             variable,
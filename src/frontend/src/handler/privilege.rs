@@ -60,7 +60,7 @@ pub(crate) fn resolve_relation_privileges(
             objects.push(item);
         }
         Relation::Subquery(query) => {
-            if let crate::binder::BoundSetExpr::Select(select) = &query.query.body {
+            for select in query.query.body.selects() {
                 if let Some(sub_relation) = &select.from {
                     resolve_relation_privileges(sub_relation, action, objects);
                 }
@@ -88,7 +88,7 @@ pub(crate) fn resolve_privileges(stmt: &BoundStatement) -> Vec<ObjectCheckItem>
                 object: ProstObject::TableId(insert.table_source.source_id.table_id),
             };
             objects.push(object);
-            if let crate::binder::BoundSetExpr::Select(select) = &insert.source.body {
+            for select in insert.source.body.selects() {
                 if let Some(sub_relation) = &select.from {
                     resolve_relation_privileges(sub_relation, ProstAction::Select, &mut objects);
                 }
@@ -111,7 +111,7 @@ pub(crate) fn resolve_privileges(stmt: &BoundStatement) -> Vec<ObjectCheckItem>
             objects.push(object);
         }
         BoundStatement::Query(ref query) => {
-            if let crate::binder::BoundSetExpr::Select(select) = &query.body {
+            for select in query.body.selects() {
                 if let Some(sub_relation) = &select.from {
                     resolve_relation_privileges(sub_relation, ProstAction::Select, &mut objects);
                 }
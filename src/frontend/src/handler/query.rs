@@ -12,11 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use futures::future;
 use futures_async_stream::for_await;
 use pgwire::pg_field_descriptor::PgFieldDescriptor;
 use pgwire::pg_response::{PgResponse, StatementType};
 use risingwave_batch::executor::BoxedDataChunkStream;
-use risingwave_common::error::Result;
+use risingwave_common::error::{Result, RwError};
 use risingwave_common::session_config::QueryMode;
 use risingwave_sqlparser::ast::Statement;
 use tracing::debug;
@@ -25,7 +26,7 @@ use crate::binder::{Binder, BoundStatement};
 use crate::handler::util::{force_local_mode, to_pg_field, to_pg_rows};
 use crate::planner::Planner;
 use crate::scheduler::{
-    BatchPlanFragmenter, ExecutionContext, ExecutionContextRef, LocalQueryExecution,
+    BatchPlanFragmenter, ExecutionContext, ExecutionContextRef, LocalQueryExecution, SchedulerError,
 };
 use crate::session::OptimizerContext;
 
@@ -50,16 +51,37 @@ pub async fn handle_query(
     };
     debug!("query_mode:{:?}", query_mode);
 
-    let (data_stream, pg_descs) = match query_mode {
-        QueryMode::Local => local_execute(context, bound)?,
-        QueryMode::Distributed => distribute_execute(context, bound).await?,
+    // Cancelled by the cancel request of the session, or by the statement timeout. Dropping the
+    // execution stops the local one, while the distributed one is aborted by the session.
+    let cancelled = session.reset_cancel_query_flag();
+    let statement_timeout = session.config().get_statement_timeout();
+    let execution = async move {
+        let (data_stream, pg_descs) = match query_mode {
+            QueryMode::Local => local_execute(context, bound)?,
+            QueryMode::Distributed => distribute_execute(context, bound).await?,
+        };
+
+        let mut rows = vec![];
+        #[for_await]
+        for chunk in data_stream {
+            rows.extend(to_pg_rows(chunk?, format));
+        }
+        Ok::<_, RwError>((rows, pg_descs))
+    };
+    let timeout = async {
+        match statement_timeout {
+            Some(statement_timeout) => tokio::time::sleep(statement_timeout).await,
+            None => future::pending().await,
+        }
+    };
+    let (rows, pg_descs) = tokio::select! {
+        result = execution => result?,
+        Ok(()) = cancelled => return Err(SchedulerError::QueryCancelled.into()),
+        _ = timeout => {
+            session.cancel_current_query();
+            return Err(SchedulerError::StatementTimeout.into());
+        }
     };
-
-    let mut rows = vec![];
-    #[for_await]
-    for chunk in data_stream {
-        rows.extend(to_pg_rows(chunk?, format));
-    }
 
     let rows_count = match stmt_type {
         StatementType::SELECT => rows.len() as i32,
@@ -115,7 +137,7 @@ async fn distribute_execute(
     ))
 }
 
-fn local_execute(
+pub(super) fn local_execute(
     context: OptimizerContext,
     stmt: BoundStatement,
 ) -> Result<(BoxedDataChunkStream, Vec<PgFieldDescriptor>)> {
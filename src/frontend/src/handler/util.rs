@@ -25,7 +25,7 @@ use risingwave_common::error::{Result, RwError};
 use risingwave_common::types::{DataType, ScalarRefImpl};
 use risingwave_sqlparser::ast::{SqlOption, Value};
 
-use crate::binder::{BoundSetExpr, BoundStatement};
+use crate::binder::BoundStatement;
 
 /// Format scalars according to postgres convention.
 fn pg_value_format(d: ScalarRefImpl, format: bool) -> Bytes {
@@ -120,13 +120,15 @@ pub fn handle_with_properties(
 /// Check whether need to force query mode to local.
 pub fn force_local_mode(bound: &BoundStatement) -> bool {
     if let BoundStatement::Query(query) = bound {
-        if let BoundSetExpr::Select(select) = &query.body
-            && let Some(relation) = &select.from
-            && relation.contains_sys_table() {
-            return true;
-        }
+        query.body.selects().into_iter().any(|select| {
+            select
+                .from
+                .as_ref()
+                .map_or(false, |relation| relation.contains_sys_table())
+        })
+    } else {
+        false
     }
-    false
 }
 
 #[cfg(test)]
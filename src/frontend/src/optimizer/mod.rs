@@ -19,6 +19,7 @@ pub mod property;
 
 mod delta_join_solver;
 mod heuristic;
+mod plan_cardinality_estimator;
 mod plan_correlated_id_finder;
 mod plan_rewriter;
 mod plan_visitor;
@@ -282,10 +283,22 @@ impl PlanRoot {
         plan
     }
 
+    /// Apply logical optimization to the plan, and choose the indexes to scan for batch queries.
+    fn gen_optimized_batch_logical_plan(&self) -> PlanRef {
+        let plan = self.gen_optimized_logical_plan();
+
+        self.optimize_by_rules(
+            plan,
+            "Index Selection".to_string(),
+            vec![IndexSelectionRule::create()],
+            ApplyOrder::TopDown,
+        )
+    }
+
     /// Optimize and generate a batch query plan for distributed execution.
     pub fn gen_batch_query_plan(&self) -> Result<PlanRef> {
         // Logical optimization
-        let mut plan = self.gen_optimized_logical_plan();
+        let mut plan = self.gen_optimized_batch_logical_plan();
 
         // Convert to physical plan node
         plan = plan.to_batch_with_order_required(&self.required_order)?;
@@ -312,7 +325,7 @@ impl PlanRoot {
     /// Optimize and generate a batch query plan for local execution.
     pub fn gen_batch_local_plan(&self) -> Result<PlanRef> {
         // Logical optimization
-        let mut plan = self.gen_optimized_logical_plan();
+        let mut plan = self.gen_optimized_batch_logical_plan();
 
         // Convert to physical plan node
         plan = plan.to_batch_with_order_required(&self.required_order)?;
@@ -348,6 +361,13 @@ impl PlanRoot {
         let mut plan = match self.plan.convention() {
             Convention::Logical => {
                 let plan = self.gen_optimized_logical_plan();
+                // Only streaming has the group TopN operator.
+                let plan = self.optimize_by_rules(
+                    plan,
+                    "Convert Over Aggregation to TopN".to_string(),
+                    vec![OverAggToTopNRule::create()],
+                    ApplyOrder::TopDown,
+                );
                 let (plan, out_col_change) = plan.logical_rewrite_for_stream()?;
                 self.required_dist =
                     out_col_change.rewrite_required_distribution(&self.required_dist);
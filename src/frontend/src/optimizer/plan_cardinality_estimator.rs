@@ -0,0 +1,382 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimates the number of rows of the logical plans from the statistics collected by `ANALYZE`.
+
+use itertools::Itertools;
+use num_traits::ToPrimitive;
+use risingwave_common::types::ScalarImpl;
+use risingwave_pb::plan_common::JoinType;
+
+use crate::expr::{ExprImpl, ExprType};
+use crate::optimizer::plan_node::{
+    LogicalAgg, LogicalFilter, LogicalJoin, LogicalLimit, LogicalMultiJoin, LogicalProject,
+    LogicalScan, LogicalTopN, LogicalUnion, LogicalValues, PlanTreeNode, PlanTreeNodeBinary,
+    PlanTreeNodeUnary,
+};
+use crate::optimizer::plan_visitor::PlanVisitor;
+use crate::optimizer::PlanRef;
+
+/// The selectivity of an equality on a column without statistics, the same as `PostgreSQL`.
+const DEFAULT_EQ_SELECTIVITY: f64 = 0.005;
+/// The selectivity of the other predicates, the same as the inequalities in `PostgreSQL`.
+const DEFAULT_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// The estimated cardinality of a plan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    pub rows: f64,
+    /// The number of distinct values of each output column, `None` if unknown.
+    pub distinct_counts: Vec<Option<f64>>,
+}
+
+impl Estimate {
+    pub fn new(rows: f64, distinct_counts: Vec<Option<f64>>) -> Self {
+        let distinct_counts = distinct_counts
+            .into_iter()
+            .map(|distinct_count| distinct_count.map(|distinct_count| distinct_count.min(rows)))
+            .collect();
+        Self {
+            rows,
+            distinct_counts,
+        }
+    }
+
+    pub fn distinct_count(&self, col_idx: usize) -> Option<f64> {
+        self.distinct_counts.get(col_idx).copied().flatten()
+    }
+}
+
+/// The fraction of the rows of `input` satisfying the conjunction.
+pub fn selectivity(expr: &ExprImpl, input: &Estimate) -> f64 {
+    let eq_selectivity = |col_idx: usize| match input.distinct_count(col_idx) {
+        Some(distinct_count) => 1.0 / distinct_count.max(1.0),
+        None => DEFAULT_EQ_SELECTIVITY,
+    };
+    if let Some((left, right)) = expr.as_eq_cond() {
+        match (
+            input.distinct_count(left.index()),
+            input.distinct_count(right.index()),
+        ) {
+            (Some(left), Some(right)) => 1.0 / left.max(right).max(1.0),
+            (Some(distinct_count), None) | (None, Some(distinct_count)) => {
+                1.0 / distinct_count.max(1.0)
+            }
+            (None, None) => DEFAULT_EQ_SELECTIVITY,
+        }
+    } else if let Some((input_ref, _)) = expr.as_eq_const() {
+        eq_selectivity(input_ref.index())
+    } else if let Some((input_ref, list)) = expr.as_in_const_list() {
+        (eq_selectivity(input_ref.index()) * list.len() as f64).min(1.0)
+    } else {
+        DEFAULT_SELECTIVITY
+    }
+}
+
+/// Estimates the cardinality of the logical plans bottom-up. The estimate is `None` if any of the
+/// leaves can't be estimated, e.g. the sources and the tables never analyzed.
+#[derive(Default)]
+pub struct PlanCardinalityEstimator {}
+
+impl PlanCardinalityEstimator {
+    pub fn estimate(plan: PlanRef) -> Option<Estimate> {
+        Self::default().visit_input(plan)
+    }
+
+    /// Visits the input, dropping the distinct counts if they don't line up with its schema, which
+    /// happens to the plan nodes passing through the estimate of their left-most input.
+    fn visit_input(&mut self, input: PlanRef) -> Option<Estimate> {
+        let len = input.schema().len();
+        let mut estimate = self.visit(input)?;
+        if estimate.distinct_counts.len() != len {
+            estimate.distinct_counts = vec![None; len];
+        }
+        Some(estimate)
+    }
+}
+
+impl PlanVisitor<Option<Estimate>> for PlanCardinalityEstimator {
+    fn visit_logical_scan(&mut self, scan: &LogicalScan) -> Option<Estimate> {
+        let statistics = scan.statistics()?;
+        let column_statistics = scan
+            .table_desc()
+            .columns
+            .iter()
+            .map(|column| statistics.columns.get(&column.column_id))
+            .collect_vec();
+        let row_count = statistics.row_count as f64;
+        let table = Estimate::new(
+            row_count,
+            column_statistics
+                .iter()
+                .map(|column| column.map(|column| column.distinct_count as f64))
+                .collect(),
+        );
+
+        let mut rows = row_count;
+        for expr in &scan.predicate().conjunctions {
+            // The ranges on the numeric columns are estimated by their histograms.
+            let range_selectivity =
+                expr.as_comparison_const()
+                    .and_then(|(input_ref, ty, value)| {
+                        let column = column_statistics[input_ref.index()]?;
+                        let histogram = column.histogram.as_ref()?;
+                        let below = histogram.fraction_below(const_to_f64(&value)?);
+                        let fraction = match ty {
+                            ExprType::LessThan | ExprType::LessThanOrEqual => below,
+                            _ => 1.0 - below,
+                        };
+                        let non_null_fraction = if statistics.row_count == 0 {
+                            0.0
+                        } else {
+                            1.0 - column.null_count as f64 / row_count
+                        };
+                        Some(fraction * non_null_fraction)
+                    });
+            rows *= range_selectivity.unwrap_or_else(|| selectivity(expr, &table));
+        }
+
+        Some(Estimate::new(
+            rows,
+            scan.output_col_idx()
+                .iter()
+                .map(|&col_idx| table.distinct_count(col_idx))
+                .collect(),
+        ))
+    }
+
+    fn visit_logical_values(&mut self, values: &LogicalValues) -> Option<Estimate> {
+        Some(Estimate::new(
+            values.rows().len() as f64,
+            vec![None; values.schema().len()],
+        ))
+    }
+
+    fn visit_logical_filter(&mut self, filter: &LogicalFilter) -> Option<Estimate> {
+        let input = self.visit_input(filter.input())?;
+        let rows = filter
+            .predicate()
+            .conjunctions
+            .iter()
+            .fold(input.rows, |rows, expr| rows * selectivity(expr, &input));
+        Some(Estimate::new(rows, input.distinct_counts))
+    }
+
+    fn visit_logical_project(&mut self, project: &LogicalProject) -> Option<Estimate> {
+        let input = self.visit_input(project.input())?;
+        let distinct_counts = project
+            .exprs()
+            .iter()
+            .map(|expr| match expr {
+                ExprImpl::InputRef(input_ref) => input.distinct_count(input_ref.index()),
+                ExprImpl::Literal(_) => Some(1.0),
+                _ => None,
+            })
+            .collect();
+        Some(Estimate::new(input.rows, distinct_counts))
+    }
+
+    fn visit_logical_join(&mut self, join: &LogicalJoin) -> Option<Estimate> {
+        let left = self.visit_input(join.left())?;
+        let right = self.visit_input(join.right())?;
+        let cross = Estimate::new(
+            left.rows * right.rows,
+            left.distinct_counts
+                .iter()
+                .chain(right.distinct_counts.iter())
+                .copied()
+                .collect(),
+        );
+        let matched = join
+            .on()
+            .conjunctions
+            .iter()
+            .fold(cross.rows, |rows, expr| rows * selectivity(expr, &cross));
+
+        let (rows, internal_distinct_counts) = match join.join_type() {
+            JoinType::Inner => (matched, cross.distinct_counts),
+            JoinType::LeftOuter => (matched.max(left.rows), cross.distinct_counts),
+            JoinType::RightOuter => (matched.max(right.rows), cross.distinct_counts),
+            JoinType::FullOuter => (
+                matched.max(left.rows).max(right.rows),
+                cross.distinct_counts,
+            ),
+            JoinType::LeftSemi => (matched.min(left.rows), left.distinct_counts),
+            JoinType::LeftAnti => (left.rows, left.distinct_counts),
+            JoinType::RightSemi => (matched.min(right.rows), right.distinct_counts),
+            JoinType::RightAnti => (right.rows, right.distinct_counts),
+            JoinType::Unspecified => unreachable!(),
+        };
+        Some(Estimate::new(
+            rows,
+            join.output_indices()
+                .iter()
+                .map(|&i| internal_distinct_counts[i])
+                .collect(),
+        ))
+    }
+
+    fn visit_logical_multi_join(&mut self, _multi_join: &LogicalMultiJoin) -> Option<Estimate> {
+        None
+    }
+
+    fn visit_logical_agg(&mut self, agg: &LogicalAgg) -> Option<Estimate> {
+        let input = self.visit_input(agg.input())?;
+        // The number of groups is at most the product of the distinct counts of the group keys.
+        let rows = if agg.group_key().is_empty() {
+            1.0
+        } else {
+            agg.group_key()
+                .iter()
+                .map(|&col_idx| input.distinct_count(col_idx))
+                .product::<Option<f64>>()
+                .map_or(input.rows, |groups| groups.min(input.rows))
+        };
+        let distinct_counts = agg
+            .group_key()
+            .iter()
+            .map(|&col_idx| input.distinct_count(col_idx))
+            .chain(agg.agg_calls().iter().map(|_| None))
+            .collect();
+        Some(Estimate::new(rows, distinct_counts))
+    }
+
+    fn visit_logical_limit(&mut self, limit: &LogicalLimit) -> Option<Estimate> {
+        let input = self.visit_input(limit.input())?;
+        let rows = (input.rows - limit.offset() as f64)
+            .max(0.0)
+            .min(limit.limit() as f64);
+        Some(Estimate::new(rows, input.distinct_counts))
+    }
+
+    fn visit_logical_top_n(&mut self, top_n: &LogicalTopN) -> Option<Estimate> {
+        let input = self.visit_input(top_n.input())?;
+        if !top_n.group_key().is_empty() {
+            return Some(input);
+        }
+        let rows = (input.rows - top_n.offset() as f64)
+            .max(0.0)
+            .min(top_n.limit() as f64);
+        Some(Estimate::new(rows, input.distinct_counts))
+    }
+
+    fn visit_logical_union(&mut self, union: &LogicalUnion) -> Option<Estimate> {
+        let mut rows = 0.0;
+        for input in union.inputs() {
+            rows += self.visit_input(input)?.rows;
+        }
+        Some(Estimate::new(rows, vec![None; union.schema().len()]))
+    }
+}
+
+fn const_to_f64(expr: &ExprImpl) -> Option<f64> {
+    match expr.eval_row_const().ok()?? {
+        ScalarImpl::Int16(v) => Some(v as f64),
+        ScalarImpl::Int32(v) => Some(v as f64),
+        ScalarImpl::Int64(v) => Some(v as f64),
+        ScalarImpl::Float32(v) => Some(v.into_inner() as f64),
+        ScalarImpl::Float64(v) => Some(v.into_inner()),
+        ScalarImpl::Decimal(v) => v.to_f64(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use risingwave_common::catalog::{ColumnDesc, TableDesc, TableId};
+    use risingwave_common::types::DataType;
+
+    use super::*;
+    use crate::catalog::table_statistics::{ColumnStatistics, Histogram};
+    use crate::catalog::{ColumnId, TableStatistics};
+    use crate::expr::{FunctionCall, InputRef, Literal};
+    use crate::optimizer::plan_node::PredicatePushdown;
+    use crate::session::OptimizerContext;
+    use crate::utils::Condition;
+
+    #[tokio::test]
+    async fn test_estimate_scan_with_predicate() {
+        let ctx = OptimizerContext::mock().await;
+        let table_desc = TableDesc {
+            table_id: TableId::new(1),
+            columns: vec![
+                ColumnDesc::unnamed(ColumnId::new(0), DataType::Int32),
+                ColumnDesc::unnamed(ColumnId::new(1), DataType::Int32),
+            ],
+            ..Default::default()
+        };
+        let statistics = TableStatistics {
+            row_count: 1000,
+            columns: HashMap::from([
+                (
+                    ColumnId::new(0),
+                    ColumnStatistics {
+                        null_count: 0,
+                        distinct_count: 1000,
+                        histogram: Some(Histogram {
+                            min: 0.0,
+                            max: 100.0,
+                            bucket_counts: vec![250, 250, 250, 250],
+                        }),
+                    },
+                ),
+                (
+                    ColumnId::new(1),
+                    ColumnStatistics {
+                        null_count: 0,
+                        distinct_count: 10,
+                        histogram: None,
+                    },
+                ),
+            ]),
+        };
+        let scan = LogicalScan::create("t".to_string(), false, Rc::new(table_desc), vec![], ctx)
+            .with_statistics(Some(Rc::new(statistics)));
+
+        let estimate = PlanCardinalityEstimator::estimate(scan.clone().into()).unwrap();
+        assert_eq!(estimate.rows, 1000.0);
+        assert_eq!(estimate.distinct_counts, vec![Some(1000.0), Some(10.0)]);
+
+        // v0 < 25 AND v1 = 1
+        let predicate = Condition::with_expr(
+            FunctionCall::new(
+                ExprType::LessThan,
+                vec![
+                    InputRef::new(0, DataType::Int32).into(),
+                    Literal::new(Some(ScalarImpl::Int32(25)), DataType::Int32).into(),
+                ],
+            )
+            .unwrap()
+            .into(),
+        )
+        .and(Condition::with_expr(
+            FunctionCall::new(
+                ExprType::Equal,
+                vec![
+                    InputRef::new(1, DataType::Int32).into(),
+                    Literal::new(Some(ScalarImpl::Int32(1)), DataType::Int32).into(),
+                ],
+            )
+            .unwrap()
+            .into(),
+        ));
+        let estimate =
+            PlanCardinalityEstimator::estimate(scan.predicate_pushdown(predicate)).unwrap();
+        assert_eq!(estimate.rows, 25.0);
+        assert_eq!(estimate.distinct_counts, vec![Some(25.0), Some(10.0)]);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use risingwave_common::error::Result;
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+use risingwave_pb::batch_plan::OverAggNode;
+
+use super::logical_over_agg::PlanWindowFunction;
+use super::{
+    LogicalOverAgg, PlanBase, PlanRef, PlanTreeNodeUnary, ToBatchProst, ToDistributedBatch,
+};
+use crate::optimizer::plan_node::ToLocalBatch;
+use crate::optimizer::property::{Order, RequiredDist};
+
+/// `BatchOverAgg` computes window functions over its input, which is sorted by the partition
+/// columns and then the `ORDER BY` of the window.
+#[derive(Debug, Clone)]
+pub struct BatchOverAgg {
+    pub base: PlanBase,
+    logical: LogicalOverAgg,
+}
+
+impl BatchOverAgg {
+    pub fn new(logical: LogicalOverAgg) -> Self {
+        let ctx = logical.base.ctx.clone();
+        // The input columns are output as is, so the distribution of the input is kept.
+        let dist = logical.input().distribution().clone();
+        let base = PlanBase::new_batch(ctx, logical.schema().clone(), dist, Order::any());
+        BatchOverAgg { base, logical }
+    }
+
+    pub fn window_functions(&self) -> &[PlanWindowFunction] {
+        self.logical.window_functions()
+    }
+
+    pub fn partition_by(&self) -> &[usize] {
+        self.logical.partition_by()
+    }
+}
+
+impl fmt::Display for BatchOverAgg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.logical.fmt_with_name(f, "BatchOverAgg")
+    }
+}
+
+impl PlanTreeNodeUnary for BatchOverAgg {
+    fn input(&self) -> PlanRef {
+        self.logical.input()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(self.logical.clone_with_input(input))
+    }
+}
+impl_plan_tree_node_for_unary! { BatchOverAgg }
+
+impl ToDistributedBatch for BatchOverAgg {
+    fn to_distributed(&self) -> Result<PlanRef> {
+        let required_dist = if self.partition_by().is_empty() {
+            RequiredDist::single()
+        } else {
+            RequiredDist::shard_by_key(self.input().schema().len(), self.partition_by())
+        };
+        let new_input = self
+            .input()
+            .to_distributed_with_required(&self.logical.required_input_order(), &required_dist)?;
+        Ok(self.clone_with_input(new_input).into())
+    }
+}
+
+impl ToBatchProst for BatchOverAgg {
+    fn to_batch_prost_body(&self) -> NodeBody {
+        NodeBody::OverAgg(OverAggNode {
+            partition_by: self
+                .partition_by()
+                .iter()
+                .map(|index| *index as u32)
+                .collect(),
+            order_by: self.logical.order_by().to_protobuf(self.input().schema()),
+            window_functions: self
+                .window_functions()
+                .iter()
+                .map(PlanWindowFunction::to_protobuf)
+                .collect(),
+        })
+    }
+}
+
+impl ToLocalBatch for BatchOverAgg {
+    fn to_local(&self) -> Result<PlanRef> {
+        let required_order = self.logical.required_input_order();
+        let new_input = self.input().to_local_with_order_required(&required_order)?;
+
+        let new_input =
+            RequiredDist::single().enforce_if_not_satisfies(new_input, &required_order)?;
+
+        Ok(self.clone_with_input(new_input).into())
+    }
+}
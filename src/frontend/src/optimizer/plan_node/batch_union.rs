@@ -0,0 +1,102 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use itertools::Itertools;
+use risingwave_common::error::Result;
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+use risingwave_pb::batch_plan::UnionNode;
+
+use super::{PlanRef, ToBatchProst, ToDistributedBatch};
+use crate::optimizer::plan_node::{LogicalUnion, PlanBase, PlanTreeNode, ToLocalBatch};
+use crate::optimizer::property::{Distribution, Order, RequiredDist};
+
+/// `BatchUnion` implements [`super::LogicalUnion`]
+#[derive(Debug, Clone)]
+pub struct BatchUnion {
+    pub base: PlanBase,
+    logical: LogicalUnion,
+}
+
+impl BatchUnion {
+    pub fn new(logical: LogicalUnion) -> Self {
+        let ctx = logical.base.ctx.clone();
+        let dist = if logical
+            .inputs()
+            .iter()
+            .all(|input| *input.distribution() == Distribution::Single)
+        {
+            Distribution::Single
+        } else {
+            Distribution::SomeShard
+        };
+
+        let base = PlanBase::new_batch(ctx, logical.schema().clone(), dist, Order::any());
+        BatchUnion { base, logical }
+    }
+
+    /// Make every input have a single distribution. The inputs may read different tables, so
+    /// they can't be put in the same stage.
+    fn inputs_to_single(inputs: Vec<PlanRef>) -> Result<Vec<PlanRef>> {
+        inputs
+            .into_iter()
+            .map(|input| RequiredDist::single().enforce_if_not_satisfies(input, &Order::any()))
+            .try_collect()
+    }
+}
+
+impl fmt::Display for BatchUnion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.logical.fmt_with_name(f, "BatchUnion")
+    }
+}
+
+impl PlanTreeNode for BatchUnion {
+    fn inputs(&self) -> smallvec::SmallVec<[crate::optimizer::PlanRef; 2]> {
+        self.logical.inputs()
+    }
+
+    fn clone_with_inputs(&self, inputs: &[crate::optimizer::PlanRef]) -> PlanRef {
+        Self::new(LogicalUnion::new(inputs.to_vec())).into()
+    }
+}
+
+impl ToDistributedBatch for BatchUnion {
+    fn to_distributed(&self) -> Result<PlanRef> {
+        let new_inputs = self
+            .inputs()
+            .into_iter()
+            .map(|input| input.to_distributed())
+            .try_collect()?;
+        Ok(self.clone_with_inputs(&Self::inputs_to_single(new_inputs)?))
+    }
+}
+
+impl ToBatchProst for BatchUnion {
+    fn to_batch_prost_body(&self) -> NodeBody {
+        NodeBody::Union(UnionNode {})
+    }
+}
+
+impl ToLocalBatch for BatchUnion {
+    fn to_local(&self) -> Result<PlanRef> {
+        let new_inputs = self
+            .inputs()
+            .into_iter()
+            .map(|input| input.to_local())
+            .try_collect()?;
+        Ok(self.clone_with_inputs(&Self::inputs_to_single(new_inputs)?))
+    }
+}
@@ -27,6 +27,7 @@ use super::{
     ToBatch, ToStream,
 };
 use crate::expr::{ExprImpl, ExprType};
+use crate::optimizer::plan_cardinality_estimator::PlanCardinalityEstimator;
 use crate::optimizer::plan_node::utils::IndicesDisplay;
 use crate::optimizer::plan_node::{
     BatchFilter, BatchHashJoin, BatchLookupJoin, BatchNestedLoopJoin, EqJoinPredicate,
@@ -408,6 +409,54 @@ impl LogicalJoin {
         )
     }
 
+    /// Swaps the left and right inputs, with the same output.
+    pub fn swap_inputs(&self) -> Self {
+        let left_len = self.left.schema().len();
+        let right_len = self.right.schema().len();
+        let mut mapping = ColIndexMapping::new(
+            (0..left_len)
+                .map(|i| Some(i + right_len))
+                .chain((0..right_len).map(Some))
+                .collect(),
+        );
+        let (join_type, output_indices) = match self.join_type {
+            JoinType::Inner | JoinType::FullOuter => (self.join_type, None),
+            JoinType::LeftOuter => (JoinType::RightOuter, None),
+            JoinType::RightOuter => (JoinType::LeftOuter, None),
+            // The output of the semi and anti joins is only one side.
+            JoinType::LeftSemi => (JoinType::RightSemi, Some(self.output_indices.clone())),
+            JoinType::RightSemi => (JoinType::LeftSemi, Some(self.output_indices.clone())),
+            JoinType::LeftAnti => (JoinType::RightAnti, Some(self.output_indices.clone())),
+            JoinType::RightAnti => (JoinType::LeftAnti, Some(self.output_indices.clone())),
+            JoinType::Unspecified => unreachable!(),
+        };
+        let output_indices = output_indices.unwrap_or_else(|| {
+            self.output_indices
+                .iter()
+                .map(|&i| mapping.map(i))
+                .collect()
+        });
+        Self::new_with_output_indices(
+            self.right.clone(),
+            self.left.clone(),
+            join_type,
+            self.on.clone().rewrite_expr(&mut mapping),
+            output_indices,
+        )
+    }
+
+    /// Whether the right input, on which the hash join builds the hash table, is estimated to have
+    /// more rows than the left one.
+    fn is_right_larger(&self) -> bool {
+        match (
+            PlanCardinalityEstimator::estimate(self.left.clone()),
+            PlanCardinalityEstimator::estimate(self.right.clone()),
+        ) {
+            (Some(left), Some(right)) => right.rows > left.rows,
+            _ => false,
+        }
+    }
+
     pub fn is_left_join(&self) -> bool {
         matches!(self.join_type(), JoinType::LeftSemi | JoinType::LeftAnti)
     }
@@ -820,6 +869,17 @@ impl ToBatch for LogicalJoin {
             self.on.clone(),
         );
 
+        let lookup_join_enabled = {
+            let config = self.base.ctx.inner().session_ctx.config();
+            config.get_batch_enable_lookup_join() && config.get_query_mode() == QueryMode::Local
+        };
+
+        // The hash join builds the hash table on the right input, which should be the smaller one.
+        // It's kept for the lookup join, which requires a table on the right.
+        if predicate.has_eq() && !lookup_join_enabled && self.is_right_larger() {
+            return self.swap_inputs().to_batch();
+        }
+
         let left = self.left().to_batch()?;
         let right = self.right().to_batch()?;
         let logical_join = self.clone_with_left_right(left, right);
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Ordering;
 use std::fmt;
 
 use itertools::Itertools;
@@ -24,6 +25,9 @@ use super::{
     PlanTreeNodeBinary, PlanTreeNodeUnary, PredicatePushdown, ToBatch, ToStream,
 };
 use crate::expr::{ExprImpl, ExprRewriter};
+use crate::optimizer::plan_cardinality_estimator::{
+    selectivity, Estimate, PlanCardinalityEstimator,
+};
 use crate::optimizer::plan_node::PlanTreeNode;
 use crate::optimizer::property::FunctionalDependencySet;
 use crate::utils::{ColIndexMapping, Condition, ConditionDisplay, ConnectedComponentLabeller};
@@ -478,6 +482,135 @@ impl LogicalMultiJoin {
         Ok(join_ordering)
     }
 
+    /// Orders the joins greedily by the cardinality estimated from the statistics of the tables.
+    /// It starts with the two inputs joined into the fewest rows, then keeps joining the input
+    /// connected by eq join conditions which produces the fewest rows, and only adds a cross join
+    /// if no input is connected.
+    ///
+    /// Returns `None` if the cardinality of any input is unknown, e.g. the table is never analyzed,
+    /// or there's no eq join condition at all.
+    pub(crate) fn cost_based_ordering(&self) -> Option<Vec<usize>> {
+        let estimates = self
+            .inputs
+            .iter()
+            .map(|input| PlanCardinalityEstimator::estimate(input.clone()))
+            .collect::<Option<Vec<_>>>()?;
+        let mut rows = estimates.iter().map(|estimate| estimate.rows).collect_vec();
+
+        // (input_idx, col_idx) of each column of the inputs
+        let col_to_input = self
+            .input_col_nums()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(input_idx, col_num)| (0..col_num).map(move |col_idx| (input_idx, col_idx)))
+            .collect_vec();
+        // The distinct counts of all the columns, to estimate the selectivity of the conditions.
+        let all_columns = Estimate {
+            rows: 0.0,
+            distinct_counts: estimates
+                .iter()
+                .flat_map(|estimate| estimate.distinct_counts.iter().copied())
+                .collect(),
+        };
+
+        let mut eq_edges = vec![];
+        for expr in &self.on.conjunctions {
+            if let Some((left, right)) = expr.as_eq_cond() {
+                let (left, right) = (col_to_input[left.index()], col_to_input[right.index()]);
+                if left.0 != right.0 {
+                    eq_edges.push((left, right));
+                    continue;
+                }
+            }
+            // The conditions on a single input are applied to it before joining.
+            let input_indices = expr
+                .collect_input_refs(col_to_input.len())
+                .ones()
+                .map(|col_idx| col_to_input[col_idx].0)
+                .dedup()
+                .collect_vec();
+            if let [input_idx] = input_indices[..] {
+                rows[input_idx] *= selectivity(expr, &all_columns);
+            }
+        }
+        if eq_edges.is_empty() {
+            return None;
+        }
+
+        // The rows of joining the `right` input to the `left` ones, which produce `left_rows`.
+        let join_rows = |left: &[usize], left_rows: f64, right: usize| {
+            let distinct_count = |(input_idx, col_idx): (usize, usize), rows: f64| {
+                estimates[input_idx].distinct_counts[col_idx]
+                    .unwrap_or(rows)
+                    .min(rows)
+                    .max(1.0)
+            };
+            let mut join_rows = left_rows * rows[right];
+            for &(a, b) in &eq_edges {
+                let (left_col, right_col) = if left.contains(&a.0) && b.0 == right {
+                    (a, b)
+                } else if left.contains(&b.0) && a.0 == right {
+                    (b, a)
+                } else {
+                    continue;
+                };
+                join_rows /=
+                    distinct_count(left_col, left_rows).max(distinct_count(right_col, rows[right]));
+            }
+            join_rows
+        };
+        let is_connected = |left: &[usize], right: usize| {
+            eq_edges.iter().any(|&(a, b)| {
+                (left.contains(&a.0) && b.0 == right) || (left.contains(&b.0) && a.0 == right)
+            })
+        };
+        let cmp_rows = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+
+        // Start with the larger input on the left, which is the probe side of the hash join.
+        let (first, second) = eq_edges
+            .iter()
+            .map(|&(a, b)| {
+                if rows[a.0] >= rows[b.0] {
+                    (a.0, b.0)
+                } else {
+                    (b.0, a.0)
+                }
+            })
+            .min_by(|&(a1, b1), &(a2, b2)| {
+                cmp_rows(
+                    &join_rows(&[a1], rows[a1], b1),
+                    &join_rows(&[a2], rows[a2], b2),
+                )
+            })?;
+        let mut join_ordering = vec![first, second];
+        let mut current_rows = join_rows(&[first], rows[first], second);
+        while join_ordering.len() < self.inputs.len() {
+            let remaining = (0..self.inputs.len())
+                .filter(|input_idx| !join_ordering.contains(input_idx))
+                .collect_vec();
+            let next = remaining
+                .iter()
+                .copied()
+                .filter(|&input_idx| is_connected(&join_ordering, input_idx))
+                .min_by(|&a, &b| {
+                    cmp_rows(
+                        &join_rows(&join_ordering, current_rows, a),
+                        &join_rows(&join_ordering, current_rows, b),
+                    )
+                })
+                .or_else(|| {
+                    remaining
+                        .iter()
+                        .copied()
+                        .min_by(|&a, &b| cmp_rows(&rows[a], &rows[b]))
+                })
+                .unwrap();
+            current_rows = join_rows(&join_ordering, current_rows, next);
+            join_ordering.push(next);
+        }
+        Some(join_ordering)
+    }
+
     pub(crate) fn input_col_nums(&self) -> Vec<usize> {
         self.inputs.iter().map(|i| i.schema().len()).collect()
     }
@@ -0,0 +1,554 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use fixedbitset::FixedBitSet;
+use itertools::Itertools;
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_common::types::DataType;
+use risingwave_expr::expr::AggKind;
+use risingwave_pb::expr::WindowFunction as ProstWindowFunction;
+
+use super::{
+    gen_filter_and_pushdown, BatchOverAgg, ColPrunable, LogicalProject, LogicalProjectBuilder,
+    PlanBase, PlanRef, PlanTreeNodeUnary, PredicatePushdown, ToBatch, ToStream,
+};
+use crate::expr::{
+    Expr, ExprImpl, ExprRewriter, ExprType, ExprVisitor, Frame, FunctionCall, InputRef,
+    InputRefDisplay, WindowFunction, WindowFunctionType,
+};
+use crate::optimizer::plan_node::utils::IndicesDisplay;
+use crate::optimizer::property::{FieldOrder, Order, OrderDisplay};
+use crate::utils::{ColIndexMapping, Condition};
+
+/// A window function call in [`LogicalOverAgg`], whose arguments are columns of the input.
+#[derive(Clone, Debug)]
+pub struct PlanWindowFunction {
+    pub function_type: WindowFunctionType,
+    pub return_type: DataType,
+    pub args: Vec<InputRef>,
+    pub frame: Frame,
+}
+
+impl PlanWindowFunction {
+    pub fn to_protobuf(&self) -> ProstWindowFunction {
+        ProstWindowFunction {
+            r#type: Some(self.function_type.to_protobuf()),
+            args: self.args.iter().map(InputRef::to_agg_arg_proto).collect(),
+            return_type: Some(self.return_type.to_protobuf()),
+            frame: Some(self.frame.to_protobuf()),
+        }
+    }
+}
+
+pub struct PlanWindowFunctionDisplay<'a> {
+    pub window_function: &'a PlanWindowFunction,
+    pub input_schema: &'a Schema,
+}
+
+impl fmt::Debug for PlanWindowFunctionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let that = self.window_function;
+        write!(
+            f,
+            "{}({})",
+            that.function_type,
+            that.args
+                .iter()
+                .map(|input_ref| format!(
+                    "{}",
+                    InputRefDisplay {
+                        input_ref,
+                        input_schema: self.input_schema
+                    }
+                ))
+                .join(", ")
+        )?;
+        if that.function_type.is_framed() {
+            write!(f, " {}", that.frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// `LogicalOverAgg` computes window functions over the partitions of its input. All the window
+/// functions of an `OverAgg` share the same `PARTITION BY` and `ORDER BY`.
+///
+/// The output schema is the input columns followed by the results of the window functions.
+#[derive(Debug, Clone)]
+pub struct LogicalOverAgg {
+    pub base: PlanBase,
+    window_functions: Vec<PlanWindowFunction>,
+    partition_by: Vec<usize>,
+    order_by: Order,
+    input: PlanRef,
+}
+
+impl LogicalOverAgg {
+    pub fn new(
+        window_functions: Vec<PlanWindowFunction>,
+        partition_by: Vec<usize>,
+        order_by: Order,
+        input: PlanRef,
+    ) -> Self {
+        let ctx = input.ctx();
+        let schema: Schema = input
+            .schema()
+            .fields()
+            .iter()
+            .cloned()
+            .chain(window_functions.iter().map(|window_function| {
+                Field::with_name(
+                    window_function.return_type.clone(),
+                    window_function.function_type.to_string(),
+                )
+            }))
+            .collect();
+        let pk_indices = input.logical_pk().to_vec();
+        let functional_dependency =
+            ColIndexMapping::identity_or_none(input.schema().len(), schema.len())
+                .rewrite_functional_dependency_set(input.functional_dependency().clone());
+        let base = PlanBase::new_logical(ctx, schema, pk_indices, functional_dependency);
+        Self {
+            base,
+            window_functions,
+            partition_by,
+            order_by,
+            input,
+        }
+    }
+
+    /// Plan the window functions in `select_exprs` on top of `input`, and rewrite them to
+    /// [`InputRef`]s to the outputs of the [`LogicalOverAgg`]s.
+    ///
+    /// The window functions are grouped by their `PARTITION BY` and `ORDER BY`, and each group is
+    /// planned as one [`LogicalOverAgg`]. `avg` is rewritten to `sum / count` in the same way as
+    /// [`LogicalAgg`](super::LogicalAgg) does.
+    pub fn create(
+        input: PlanRef,
+        mut select_exprs: Vec<ExprImpl>,
+    ) -> Result<(PlanRef, Vec<ExprImpl>)> {
+        select_exprs = select_exprs
+            .into_iter()
+            .map(|expr| AvgRewriter {}.rewrite_expr(expr))
+            .collect();
+
+        let mut collector = WindowFunctionCollector::default();
+        select_exprs
+            .iter()
+            .for_each(|expr| collector.visit_expr(expr));
+        let window_functions = collector.window_functions;
+        for window_function in &window_functions {
+            let has_subquery = window_function
+                .args
+                .iter()
+                .chain(window_function.partition_by.iter())
+                .chain(window_function.order_by.sort_exprs.iter().map(|e| &e.expr))
+                .any(|expr| expr.has_subquery());
+            if has_subquery {
+                return Err(ErrorCode::NotImplemented(
+                    "subquery in window function".to_string(),
+                    None.into(),
+                )
+                .into());
+            }
+        }
+
+        // Evaluate the arguments, `PARTITION BY` and `ORDER BY` of all the window functions after
+        // the input columns.
+        let mut input_proj_builder = LogicalProjectBuilder::default();
+        for (i, field) in input.schema().fields().iter().enumerate() {
+            input_proj_builder.add_expr(&InputRef::new(i, field.data_type()).into());
+        }
+        // The window functions grouped by their `PARTITION BY` and `ORDER BY`.
+        type GroupKey = (Vec<usize>, Vec<FieldOrder>);
+        let mut groups: Vec<(GroupKey, Vec<PlanWindowFunction>)> = vec![];
+        let mut window_function_group = vec![];
+        for window_function in &window_functions {
+            let partition_by = window_function
+                .partition_by
+                .iter()
+                .map(|expr| input_proj_builder.add_expr(expr))
+                .collect_vec();
+            let order_by = window_function
+                .order_by
+                .sort_exprs
+                .iter()
+                .map(|e| FieldOrder {
+                    index: input_proj_builder.add_expr(&e.expr),
+                    direct: e.direction,
+                })
+                .collect_vec();
+            let args = window_function
+                .args
+                .iter()
+                .map(|expr| InputRef::new(input_proj_builder.add_expr(expr), expr.return_type()))
+                .collect_vec();
+            let plan_window_function = PlanWindowFunction {
+                function_type: window_function.function_type.clone(),
+                return_type: window_function.return_type(),
+                args,
+                frame: window_function.frame,
+            };
+
+            let key = (partition_by, order_by);
+            let group_idx = match groups.iter().position(|(k, _)| k == &key) {
+                Some(group_idx) => group_idx,
+                None => {
+                    groups.push((key, vec![]));
+                    groups.len() - 1
+                }
+            };
+            groups[group_idx].1.push(plan_window_function);
+            window_function_group.push((group_idx, groups[group_idx].1.len() - 1));
+        }
+
+        let mut root: PlanRef = input_proj_builder.build(input).into();
+        let mut group_offsets = vec![];
+        for ((partition_by, order_by), plan_window_functions) in groups {
+            group_offsets.push(root.schema().len());
+            root = Self::new(
+                plan_window_functions,
+                partition_by,
+                Order::new(order_by),
+                root,
+            )
+            .into();
+        }
+
+        let output_indices = window_functions
+            .into_iter()
+            .zip_eq(window_function_group)
+            .map(|(window_function, (group_idx, idx))| {
+                (window_function, group_offsets[group_idx] + idx)
+            })
+            .collect();
+        let mut rewriter = WindowFunctionRewriter { output_indices };
+        let select_exprs = select_exprs
+            .into_iter()
+            .map(|expr| rewriter.rewrite_expr(expr))
+            .collect_vec();
+
+        Ok((root, select_exprs))
+    }
+
+    pub fn window_functions(&self) -> &[PlanWindowFunction] {
+        self.window_functions.as_ref()
+    }
+
+    pub fn partition_by(&self) -> &[usize] {
+        self.partition_by.as_ref()
+    }
+
+    pub fn order_by(&self) -> &Order {
+        &self.order_by
+    }
+
+    /// The order the input must be sorted by: the partition columns and then the `ORDER BY`.
+    pub fn required_input_order(&self) -> Order {
+        let mut field_order = self
+            .partition_by
+            .iter()
+            .map(|&index| FieldOrder::ascending(index))
+            .collect_vec();
+        for order in &self.order_by.field_order {
+            if !self.partition_by.contains(&order.index) {
+                field_order.push(order.clone());
+            }
+        }
+        Order::new(field_order)
+    }
+
+    pub(super) fn fmt_with_name(&self, f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+        let input_schema = self.input.schema();
+        write!(
+            f,
+            "{} {{ window_functions: [{}], partition_by: {}, order_by: {} }}",
+            name,
+            self.window_functions
+                .iter()
+                .map(|window_function| format!(
+                    "{:?}",
+                    PlanWindowFunctionDisplay {
+                        window_function,
+                        input_schema
+                    }
+                ))
+                .join(", "),
+            IndicesDisplay {
+                indices: &self.partition_by,
+                input_schema
+            },
+            OrderDisplay {
+                order: &self.order_by,
+                input_schema
+            },
+        )
+    }
+}
+
+/// Rewrites `avg(x) over w` to `sum(x) over w / count(x) over w`, since the executor can not
+/// compute `avg` directly.
+struct AvgRewriter {}
+
+impl ExprRewriter for AvgRewriter {
+    fn rewrite_window_function(&mut self, window_func: WindowFunction) -> ExprImpl {
+        let return_type = window_func.return_type();
+        if window_func.function_type != WindowFunctionType::Aggregate(AggKind::Avg) {
+            return window_func.into();
+        }
+        let with_kind = |agg_kind| {
+            WindowFunction::new(
+                WindowFunctionType::Aggregate(agg_kind),
+                window_func.args.clone(),
+                window_func.partition_by.clone(),
+                window_func.order_by.clone(),
+                window_func.frame,
+            )
+            .unwrap()
+        };
+        let sum = ExprImpl::from(with_kind(AggKind::Sum))
+            .cast_implicit(return_type)
+            .unwrap();
+        let count = with_kind(AggKind::Count).into();
+        FunctionCall::new(ExprType::Divide, vec![sum, count])
+            .unwrap()
+            .into()
+    }
+}
+
+/// Collects the distinct window functions in the order of their appearance.
+#[derive(Default)]
+struct WindowFunctionCollector {
+    window_functions: Vec<WindowFunction>,
+}
+
+impl ExprVisitor for WindowFunctionCollector {
+    fn visit_window_function(&mut self, window_func: &WindowFunction) {
+        if !self.window_functions.contains(window_func) {
+            self.window_functions.push(window_func.clone());
+        }
+    }
+}
+
+/// Replaces window functions with the [`InputRef`]s to their results.
+struct WindowFunctionRewriter {
+    output_indices: HashMap<WindowFunction, usize>,
+}
+
+impl ExprRewriter for WindowFunctionRewriter {
+    fn rewrite_window_function(&mut self, window_func: WindowFunction) -> ExprImpl {
+        InputRef::new(self.output_indices[&window_func], window_func.return_type()).into()
+    }
+}
+
+impl PlanTreeNodeUnary for LogicalOverAgg {
+    fn input(&self) -> PlanRef {
+        self.input.clone()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(
+            self.window_functions.clone(),
+            self.partition_by.clone(),
+            self.order_by.clone(),
+            input,
+        )
+    }
+}
+
+impl_plan_tree_node_for_unary! { LogicalOverAgg }
+
+impl fmt::Display for LogicalOverAgg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_name(f, "LogicalOverAgg")
+    }
+}
+
+impl ColPrunable for LogicalOverAgg {
+    fn prune_col(&self, required_cols: &[usize]) -> PlanRef {
+        let input_len = self.input.schema().len();
+        let kept_window_functions = required_cols
+            .iter()
+            .filter(|&&i| i >= input_len)
+            .map(|&i| i - input_len)
+            .sorted()
+            .dedup()
+            .collect_vec();
+        if kept_window_functions.is_empty() {
+            // None of the window functions is required.
+            return self.input.prune_col(required_cols);
+        }
+
+        let input_required_cols = {
+            let mut tmp = FixedBitSet::with_capacity(input_len);
+            tmp.extend(required_cols.iter().copied().filter(|&i| i < input_len));
+            tmp.extend(self.partition_by.iter().copied());
+            tmp.extend(self.order_by.field_order.iter().map(|o| o.index));
+            for &i in &kept_window_functions {
+                tmp.extend(self.window_functions[i].args.iter().map(|arg| arg.index()));
+            }
+            tmp.ones().collect_vec()
+        };
+        let mapping = ColIndexMapping::with_remaining_columns(&input_required_cols, input_len);
+        let new_input = self.input.prune_col(&input_required_cols);
+
+        let window_functions = kept_window_functions
+            .iter()
+            .map(|&i| {
+                let mut window_function = self.window_functions[i].clone();
+                for arg in &mut window_function.args {
+                    arg.index = mapping.map(arg.index);
+                }
+                window_function
+            })
+            .collect();
+        let partition_by = self.partition_by.iter().map(|&i| mapping.map(i)).collect();
+        let order_by = Order::new(
+            self.order_by
+                .field_order
+                .iter()
+                .map(|o| FieldOrder {
+                    index: mapping.map(o.index),
+                    direct: o.direct,
+                })
+                .collect(),
+        );
+        let new_over_agg = Self::new(window_functions, partition_by, order_by, new_input);
+
+        let new_input_len = input_required_cols.len();
+        let out_col_idx = required_cols
+            .iter()
+            .map(|&i| {
+                if i < input_len {
+                    mapping.map(i)
+                } else {
+                    let pos = kept_window_functions
+                        .iter()
+                        .position(|&j| j == i - input_len)
+                        .unwrap();
+                    new_input_len + pos
+                }
+            })
+            .collect_vec();
+        if out_col_idx
+            .iter()
+            .copied()
+            .eq(0..new_over_agg.schema().len())
+        {
+            new_over_agg.into()
+        } else {
+            LogicalProject::with_out_col_idx(new_over_agg.into(), out_col_idx.into_iter()).into()
+        }
+    }
+}
+
+impl PredicatePushdown for LogicalOverAgg {
+    fn predicate_pushdown(&self, predicate: Condition) -> PlanRef {
+        gen_filter_and_pushdown(self, predicate, Condition::true_cond())
+    }
+}
+
+impl ToBatch for LogicalOverAgg {
+    fn to_batch(&self) -> Result<PlanRef> {
+        let new_input = self
+            .input()
+            .to_batch_with_order_required(&self.required_input_order())?;
+        let new_logical = self.clone_with_input(new_input);
+        Ok(BatchOverAgg::new(new_logical).into())
+    }
+}
+
+impl LogicalOverAgg {
+    fn stream_not_supported() -> RwError {
+        ErrorCode::NotImplemented(
+            "window function in streaming query, except `row_number() OVER (PARTITION BY ..)` \
+             filtered by a constant upper bound"
+                .to_string(),
+            None.into(),
+        )
+        .into()
+    }
+}
+
+impl ToStream for LogicalOverAgg {
+    fn to_stream(&self) -> Result<PlanRef> {
+        Err(Self::stream_not_supported())
+    }
+
+    fn logical_rewrite_for_stream(&self) -> Result<(PlanRef, ColIndexMapping)> {
+        Err(Self::stream_not_supported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{Field, Schema};
+    use risingwave_common::types::DataType;
+
+    use super::*;
+    use crate::expr::AggOrderBy;
+    use crate::optimizer::plan_node::LogicalValues;
+    use crate::session::OptimizerContext;
+
+    #[tokio::test]
+    async fn test_create_and_prune_over_agg() {
+        let ctx = OptimizerContext::mock().await;
+        let fields: Vec<Field> = vec![
+            Field::with_name(DataType::Int32, "v1"),
+            Field::with_name(DataType::Int32, "v2"),
+            Field::with_name(DataType::Int32, "v3"),
+        ];
+        let values = LogicalValues::new(vec![], Schema::new(fields), ctx);
+        let v = |i| ExprImpl::from(InputRef::new(i, DataType::Int32));
+
+        // select v1, row_number() over (partition by v2), sum(v3) over (partition by v2) from t
+        let row_number = WindowFunction::new(
+            WindowFunctionType::RowNumber,
+            vec![],
+            vec![v(1)],
+            AggOrderBy::any(),
+            Frame::default(),
+        )
+        .unwrap();
+        let sum = WindowFunction::new(
+            WindowFunctionType::Aggregate(AggKind::Sum),
+            vec![v(2)],
+            vec![v(1)],
+            AggOrderBy::any(),
+            Frame::default(),
+        )
+        .unwrap();
+        let (plan, select_exprs) =
+            LogicalOverAgg::create(values.into(), vec![v(0), row_number.into(), sum.into()])
+                .unwrap();
+
+        let over_agg = plan.as_logical_over_agg().unwrap();
+        assert_eq!(over_agg.window_functions().len(), 2);
+        assert_eq!(over_agg.partition_by(), &[1]);
+        assert_eq!(select_exprs[1].as_input_ref().unwrap().index(), 3);
+        assert_eq!(select_exprs[2].as_input_ref().unwrap().index(), 4);
+
+        // Only `row_number` is required, so `v3` and `sum` are pruned.
+        let pruned = plan.prune_col(&[0, 1, 3]);
+        let over_agg = pruned.as_logical_over_agg().unwrap();
+        assert_eq!(over_agg.window_functions().len(), 1);
+        assert_eq!(over_agg.partition_by(), &[1]);
+        assert_eq!(over_agg.schema().len(), 3);
+        assert_eq!(over_agg.input().schema().len(), 2);
+    }
+}
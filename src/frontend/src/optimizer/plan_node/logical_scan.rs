@@ -25,7 +25,7 @@ use super::{
     BatchFilter, BatchProject, ColPrunable, PlanBase, PlanRef, PredicatePushdown, StreamTableScan,
     ToBatch, ToStream,
 };
-use crate::catalog::{ColumnId, IndexCatalog};
+use crate::catalog::{ColumnId, IndexCatalog, TableStatistics};
 use crate::expr::{CollectInputRef, ExprImpl, InputRef};
 use crate::optimizer::plan_node::{BatchSeqScan, LogicalFilter, LogicalProject, LogicalValues};
 use crate::optimizer::property::FunctionalDependencySet;
@@ -45,18 +45,22 @@ pub struct LogicalScan {
     table_desc: Rc<TableDesc>,
     // Descriptors of all indexes on this table
     indexes: Vec<Rc<IndexCatalog>>,
+    /// Collected by `ANALYZE` to estimate the cardinality, `None` if not analyzed yet.
+    statistics: Option<Rc<TableStatistics>>,
     /// The pushed down predicates. It refers to column indexes of the table.
     predicate: Condition,
 }
 
 impl LogicalScan {
     /// Create a `LogicalScan` node. Used internally by optimizer.
+    #[allow(clippy::too_many_arguments)]
     fn new(
         table_name: String, // explain-only
         is_sys_table: bool,
         output_col_idx: Vec<usize>, // the column index in the table
         table_desc: Rc<TableDesc>,
         indexes: Vec<Rc<IndexCatalog>>,
+        statistics: Option<Rc<TableStatistics>>,
         ctx: OptimizerContextRef,
         predicate: Condition, // refers to column indexes of the table
     ) -> Self {
@@ -115,6 +119,7 @@ impl LogicalScan {
             output_col_idx,
             table_desc,
             indexes,
+            statistics,
             predicate,
         }
     }
@@ -133,11 +138,18 @@ impl LogicalScan {
             (0..table_desc.columns.len()).into_iter().collect(),
             table_desc,
             indexes,
+            None,
             ctx,
             Condition::true_cond(),
         )
     }
 
+    /// Attaches the statistics of the table, used by the planner for the base tables.
+    pub fn with_statistics(mut self, statistics: Option<Rc<TableStatistics>>) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
     pub(super) fn column_names(&self) -> Vec<String> {
         self.output_col_idx
             .iter()
@@ -223,6 +235,11 @@ impl LogicalScan {
         &self.predicate
     }
 
+    /// Get the statistics of the table, `None` if it's not analyzed.
+    pub fn statistics(&self) -> Option<&TableStatistics> {
+        self.statistics.as_deref()
+    }
+
     /// The mapped distribution key of the scan operator.
     ///
     /// The column indices in it is the position in the `required_col_idx`, instead of the position
@@ -266,11 +283,59 @@ impl LogicalScan {
             new_required_col_idx,
             index_table_desc,
             vec![],
+            None,
             self.ctx(),
             self.predicate.clone(),
         )
     }
 
+    /// Scans the index instead of the table, outputting the columns of `output_col_idx` that
+    /// satisfy the `predicate`. Both refer to the columns of the table, which must be covered by
+    /// the index.
+    pub fn to_index_scan_with(
+        &self,
+        index: &IndexCatalog,
+        output_col_idx: &[usize],
+        predicate: Condition,
+    ) -> LogicalScan {
+        let primary_to_secondary_mapping = index.primary_to_secondary_mapping();
+        let mut mapping = ColIndexMapping::with_target_size(
+            (0..self.table_desc.columns.len())
+                .map(|col_idx| primary_to_secondary_mapping.get(&col_idx).copied())
+                .collect(),
+            index.index_table.columns.len(),
+        );
+
+        Self::new(
+            index.index_table.name.clone(),
+            false,
+            output_col_idx
+                .iter()
+                .map(|&col_idx| mapping.map(col_idx))
+                .collect(),
+            index.index_table.table_desc().into(),
+            vec![],
+            None,
+            self.ctx(),
+            predicate.rewrite_expr(&mut mapping),
+        )
+    }
+
+    /// Scans the table without the predicate, to look up the rows found in an index by the
+    /// primary key.
+    pub fn to_lookup_scan(&self, output_col_idx: Vec<usize>) -> LogicalScan {
+        Self::new(
+            self.table_name.clone(),
+            self.is_sys_table,
+            output_col_idx,
+            self.table_desc.clone(),
+            vec![],
+            self.statistics.clone(),
+            self.ctx(),
+            Condition::true_cond(),
+        )
+    }
+
     /// a vec of `InputRef` corresponding to `output_col_idx`, which can represent a pulled project.
     fn output_idx_to_input_ref(&self) -> Vec<ExprImpl> {
         let output_idx = self
@@ -302,6 +367,7 @@ impl LogicalScan {
             self.required_col_idx.clone(),
             self.table_desc.clone(),
             self.indexes.clone(),
+            self.statistics.clone(),
             self.ctx(),
             Condition::true_cond(),
         );
@@ -320,6 +386,7 @@ impl LogicalScan {
             self.required_col_idx.clone(),
             self.table_desc.clone(),
             self.indexes.clone(),
+            self.statistics.clone(),
             self.base.ctx.clone(),
             predicate,
         )
@@ -332,6 +399,7 @@ impl LogicalScan {
             output_col_idx,
             self.table_desc.clone(),
             self.indexes.clone(),
+            self.statistics.clone(),
             self.base.ctx.clone(),
             self.predicate.clone(),
         )
@@ -340,6 +408,10 @@ impl LogicalScan {
     pub fn output_col_idx(&self) -> &Vec<usize> {
         &self.output_col_idx
     }
+
+    pub fn required_col_idx(&self) -> &Vec<usize> {
+        &self.required_col_idx
+    }
 }
 
 impl_plan_tree_node_for_leaf! {LogicalScan}
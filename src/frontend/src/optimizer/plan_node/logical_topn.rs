@@ -16,19 +16,26 @@ use std::fmt;
 
 use fixedbitset::FixedBitSet;
 use itertools::Itertools;
+use risingwave_common::catalog::Field;
 use risingwave_common::error::ErrorCode::InternalError;
-use risingwave_common::error::{Result, RwError};
+use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_common::types::DataType;
 
 use super::{
     gen_filter_and_pushdown, ColPrunable, PlanBase, PlanRef, PlanTreeNodeUnary, PredicatePushdown,
     ToBatch, ToStream,
 };
-use crate::optimizer::plan_node::{BatchTopN, LogicalProject, StreamTopN};
+use crate::optimizer::plan_node::utils::IndicesDisplay;
+use crate::optimizer::plan_node::{BatchTopN, LogicalProject, StreamGroupTopN, StreamTopN};
 use crate::optimizer::property::{FieldOrder, Order, OrderDisplay, RequiredDist};
 use crate::planner::LIMIT_ALL_COUNT;
 use crate::utils::{ColIndexMapping, Condition};
 
 /// `LogicalTopN` sorts the input data and fetches up to `limit` rows from `offset`
+///
+/// If `group_key` is not empty, the rows are fetched for each group separately. If `output_rank` is
+/// set, the row number of each row in its group, starting from 1, is output after the input
+/// columns.
 #[derive(Debug, Clone)]
 pub struct LogicalTopN {
     pub base: PlanBase,
@@ -36,14 +43,34 @@ pub struct LogicalTopN {
     limit: usize,
     offset: usize,
     order: Order,
+    group_key: Vec<usize>,
+    output_rank: bool,
 }
 
 impl LogicalTopN {
     pub fn new(input: PlanRef, limit: usize, offset: usize, order: Order) -> Self {
+        Self::with_group(input, limit, offset, order, vec![], false)
+    }
+
+    pub fn with_group(
+        input: PlanRef,
+        limit: usize,
+        offset: usize,
+        order: Order,
+        group_key: Vec<usize>,
+        output_rank: bool,
+    ) -> Self {
         let ctx = input.ctx();
-        let schema = input.schema().clone();
+        let mut schema = input.schema().clone();
+        if output_rank {
+            schema
+                .fields
+                .push(Field::with_name(DataType::Int64, "row_number"));
+        }
         let pk_indices = input.logical_pk().to_vec();
-        let functional_dependency = input.functional_dependency().clone();
+        let functional_dependency =
+            ColIndexMapping::identity_or_none(input.schema().len(), schema.len())
+                .rewrite_functional_dependency_set(input.functional_dependency().clone());
         let base = PlanBase::new_logical(ctx, schema, pk_indices, functional_dependency);
         LogicalTopN {
             base,
@@ -51,6 +78,8 @@ impl LogicalTopN {
             limit,
             offset,
             order,
+            group_key,
+            output_rank,
         }
     }
 
@@ -67,6 +96,14 @@ impl LogicalTopN {
         self.offset
     }
 
+    pub fn group_key(&self) -> &[usize] {
+        &self.group_key
+    }
+
+    pub fn output_rank(&self) -> bool {
+        self.output_rank
+    }
+
     /// `topn_order` returns the order of the Top-N operator. This naming is because `order()`
     /// already exists and it was designed to return the operator's physical property order.
     ///
@@ -93,8 +130,20 @@ impl LogicalTopN {
         );
         builder
             .field("limit", &format_args!("{}", self.limit()))
-            .field("offset", &format_args!("{}", self.offset()))
-            .finish()
+            .field("offset", &format_args!("{}", self.offset()));
+        if !self.group_key.is_empty() {
+            builder.field(
+                "group_key",
+                &IndicesDisplay {
+                    indices: &self.group_key,
+                    input_schema,
+                },
+            );
+        }
+        if self.output_rank {
+            builder.field("output_rank", &self.output_rank);
+        }
+        builder.finish()
     }
 }
 
@@ -104,7 +153,14 @@ impl PlanTreeNodeUnary for LogicalTopN {
     }
 
     fn clone_with_input(&self, input: PlanRef) -> Self {
-        Self::new(input, self.limit, self.offset, self.order.clone())
+        Self::with_group(
+            input,
+            self.limit,
+            self.offset,
+            self.order.clone(),
+            self.group_key.clone(),
+            self.output_rank,
+        )
     }
 
     #[must_use]
@@ -113,17 +169,28 @@ impl PlanTreeNodeUnary for LogicalTopN {
         input: PlanRef,
         input_col_change: ColIndexMapping,
     ) -> (Self, ColIndexMapping) {
-        (
-            Self::new(
-                input,
-                self.limit,
-                self.offset,
-                input_col_change
-                    .rewrite_required_order(&self.order)
-                    .unwrap(),
-            ),
-            input_col_change,
-        )
+        let top_n = Self::with_group(
+            input,
+            self.limit,
+            self.offset,
+            input_col_change
+                .rewrite_required_order(&self.order)
+                .unwrap(),
+            self.group_key
+                .iter()
+                .map(|&idx| input_col_change.map(idx))
+                .collect(),
+            self.output_rank,
+        );
+        let out_col_change = if self.output_rank {
+            // The row number follows the input columns.
+            let (mut map, target_size) = input_col_change.into_parts();
+            map.push(Some(target_size));
+            ColIndexMapping::with_target_size(map, target_size + 1)
+        } else {
+            input_col_change
+        };
+        (top_n, out_col_change)
     }
 }
 impl_plan_tree_node_for_unary! {LogicalTopN}
@@ -135,13 +202,18 @@ impl fmt::Display for LogicalTopN {
 
 impl ColPrunable for LogicalTopN {
     fn prune_col(&self, required_cols: &[usize]) -> PlanRef {
-        let input_required_bitset = FixedBitSet::from_iter(required_cols.iter().copied());
+        let input_len = self.input().schema().len();
+        // The row number is output only if it's required.
+        let output_rank = self.output_rank && required_cols.contains(&input_len);
+        let input_required_bitset =
+            FixedBitSet::from_iter(required_cols.iter().copied().filter(|&idx| idx < input_len));
         let order_required_cols = {
-            let mut order_required_cols = FixedBitSet::with_capacity(self.input().schema().len());
+            let mut order_required_cols = FixedBitSet::with_capacity(input_len);
             self.order
                 .field_order
                 .iter()
                 .for_each(|fo| order_required_cols.insert(fo.index));
+            order_required_cols.extend(self.group_key.iter().copied());
             order_required_cols
         };
 
@@ -150,10 +222,7 @@ impl ColPrunable for LogicalTopN {
             tmp.union_with(&input_required_bitset);
             tmp.ones().collect_vec()
         };
-        let mapping = ColIndexMapping::with_remaining_columns(
-            &input_required_cols,
-            self.input().schema().len(),
-        );
+        let mapping = ColIndexMapping::with_remaining_columns(&input_required_cols, input_len);
         let new_order = Order {
             field_order: self
                 .order
@@ -165,16 +234,36 @@ impl ColPrunable for LogicalTopN {
                 })
                 .collect(),
         };
+        let new_group_key = self.group_key.iter().map(|&idx| mapping.map(idx)).collect();
         let new_input = self.input.prune_col(&input_required_cols);
-        let top_n = Self::new(new_input, self.limit, self.offset, new_order).into();
+        let new_input_len = input_required_cols.len();
+        let top_n = Self::with_group(
+            new_input,
+            self.limit,
+            self.offset,
+            new_order,
+            new_group_key,
+            output_rank,
+        )
+        .into();
 
-        if input_required_cols == required_cols {
+        let output_required_cols = required_cols
+            .iter()
+            .map(|&idx| {
+                if idx < input_len {
+                    mapping.map(idx)
+                } else {
+                    new_input_len
+                }
+            })
+            .collect_vec();
+        if output_required_cols
+            .iter()
+            .copied()
+            .eq(0..new_input_len + output_rank as usize)
+        {
             top_n
         } else {
-            let output_required_cols = required_cols
-                .iter()
-                .map(|&idx| mapping.map(idx))
-                .collect_vec();
             let src_size = top_n.schema().len();
             LogicalProject::with_mapping(
                 top_n,
@@ -197,6 +286,13 @@ impl ToBatch for LogicalTopN {
     }
 
     fn to_batch_with_order_required(&self, required_order: &Order) -> Result<PlanRef> {
+        if !self.group_key.is_empty() {
+            return Err(ErrorCode::NotImplemented(
+                "group TopN in batch query".to_string(),
+                None.into(),
+            )
+            .into());
+        }
         let new_input = self.input().to_batch()?;
         let new_logical = self.clone_with_input(new_input);
         let ret = BatchTopN::new(new_logical).into();
@@ -211,16 +307,27 @@ impl ToBatch for LogicalTopN {
 
 impl ToStream for LogicalTopN {
     fn to_stream(&self) -> Result<PlanRef> {
-        // Unlike `BatchTopN`, `StreamTopN` cannot guarantee the output order
-        let input = self
-            .input()
-            .to_stream_with_dist_required(&RequiredDist::single())?;
-
         if self.offset() != 0 && self.limit == LIMIT_ALL_COUNT {
             return Err(RwError::from(InternalError(
                 "Doesn't support OFFSET without LIMIT".to_string(),
             )));
         }
+
+        if !self.group_key.is_empty() {
+            // Each group is handled by one parallel unit.
+            let input = self
+                .input()
+                .to_stream_with_dist_required(&RequiredDist::shard_by_key(
+                    self.input().schema().len(),
+                    &self.group_key,
+                ))?;
+            return Ok(StreamGroupTopN::new(self.clone_with_input(input)).into());
+        }
+
+        // Unlike `BatchTopN`, `StreamTopN` cannot guarantee the output order
+        let input = self
+            .input()
+            .to_stream_with_dist_required(&RequiredDist::single())?;
         Ok(StreamTopN::new(self.clone_with_input(input)).into())
     }
 
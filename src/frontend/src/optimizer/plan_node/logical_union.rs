@@ -0,0 +1,280 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use itertools::Itertools;
+use risingwave_common::error::Result;
+use risingwave_common::types::{DataType, ScalarImpl};
+
+use super::{
+    BatchUnion, ColPrunable, LogicalProject, PlanBase, PlanRef, PlanTreeNode, PredicatePushdown,
+    StreamExchange, StreamUnion, ToBatch, ToStream,
+};
+use crate::expr::{ExprImpl, InputRef, Literal};
+use crate::optimizer::property::{Distribution, FunctionalDependencySet};
+use crate::utils::{ColIndexMapping, Condition};
+
+/// `LogicalUnion` returns all the rows of its inputs, without removing duplicates (i.e. `UNION
+/// ALL`). All the inputs must have the same data types, and the output schema follows the first
+/// input.
+///
+/// The other set operations (`UNION`, `INTERSECT` and `EXCEPT`) are planned as a `LogicalUnion`
+/// followed by a [`LogicalAgg`](super::LogicalAgg).
+#[derive(Debug, Clone)]
+pub struct LogicalUnion {
+    pub base: PlanBase,
+    inputs: Vec<PlanRef>,
+    /// The column which tells which input a row comes from. It's only added when rewriting for
+    /// stream, so that rows from different inputs never share the same pk.
+    source_col: Option<usize>,
+}
+
+impl LogicalUnion {
+    pub fn new(inputs: Vec<PlanRef>) -> Self {
+        Self::new_with_source_col(inputs, None)
+    }
+
+    pub fn create(inputs: Vec<PlanRef>) -> PlanRef {
+        Self::new(inputs).into()
+    }
+
+    pub fn new_with_source_col(inputs: Vec<PlanRef>, source_col: Option<usize>) -> Self {
+        assert!(!inputs.is_empty());
+        let data_types = inputs[0].schema().data_types();
+        assert!(inputs
+            .iter()
+            .all(|input| input.schema().data_types() == data_types));
+
+        let ctx = inputs[0].ctx();
+        let schema = inputs[0].schema().clone();
+        // Without the source column, the pk of the inputs may be duplicated in the output, so
+        // there's no pk at all.
+        let pk_indices = match source_col {
+            Some(source_col) => inputs
+                .iter()
+                .flat_map(|input| input.logical_pk().iter().copied())
+                .chain(std::iter::once(source_col))
+                .sorted()
+                .dedup()
+                .collect(),
+            None => vec![],
+        };
+        let functional_dependency = FunctionalDependencySet::new(schema.len());
+        let base = PlanBase::new_logical(ctx, schema, pk_indices, functional_dependency);
+        LogicalUnion {
+            base,
+            inputs,
+            source_col,
+        }
+    }
+
+    pub fn source_col(&self) -> Option<usize> {
+        self.source_col
+    }
+
+    pub(super) fn fmt_with_name(&self, f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+        write!(f, "{} {{ all: true }}", name)
+    }
+}
+
+impl PlanTreeNode for LogicalUnion {
+    fn inputs(&self) -> smallvec::SmallVec<[crate::optimizer::PlanRef; 2]> {
+        let mut vec = smallvec::SmallVec::new();
+        vec.extend(self.inputs.clone().into_iter());
+        vec
+    }
+
+    fn clone_with_inputs(&self, inputs: &[crate::optimizer::PlanRef]) -> PlanRef {
+        Self::new_with_source_col(inputs.to_vec(), self.source_col).into()
+    }
+}
+
+impl fmt::Display for LogicalUnion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_name(f, "LogicalUnion")
+    }
+}
+
+impl ColPrunable for LogicalUnion {
+    fn prune_col(&self, required_cols: &[usize]) -> PlanRef {
+        let new_inputs = self
+            .inputs
+            .iter()
+            .map(|input| input.prune_col(required_cols))
+            .collect_vec();
+        let source_col = self
+            .source_col
+            .and_then(|source_col| required_cols.iter().position(|&i| i == source_col));
+        Self::new_with_source_col(new_inputs, source_col).into()
+    }
+}
+
+impl PredicatePushdown for LogicalUnion {
+    fn predicate_pushdown(&self, predicate: Condition) -> PlanRef {
+        // All the inputs have the same schema as the union, so the predicate can be pushed to
+        // each of them as is.
+        let new_inputs = self
+            .inputs
+            .iter()
+            .map(|input| input.predicate_pushdown(predicate.clone()))
+            .collect_vec();
+        Self::new_with_source_col(new_inputs, self.source_col).into()
+    }
+}
+
+impl ToBatch for LogicalUnion {
+    fn to_batch(&self) -> Result<PlanRef> {
+        let new_inputs = self
+            .inputs
+            .iter()
+            .map(|input| input.to_batch())
+            .try_collect()?;
+        let new_logical = Self::new_with_source_col(new_inputs, self.source_col);
+        Ok(BatchUnion::new(new_logical).into())
+    }
+}
+
+impl ToStream for LogicalUnion {
+    /// Rewrite each input to make the output of the union have a pk.
+    ///
+    /// Each input is projected to `[original columns, pk slots, source column]`. There is one pk
+    /// slot for every pk column of every input that is not in the original columns. An input fills
+    /// its own slots and leaves the slots of other inputs as `NULL`. The source column is the
+    /// index of the input, so that the pk slots together with it form the pk of the union.
+    fn logical_rewrite_for_stream(&self) -> Result<(PlanRef, ColIndexMapping)> {
+        let original_len = self.schema().len();
+        let rewritten_inputs: Vec<(PlanRef, ColIndexMapping)> = self
+            .inputs
+            .iter()
+            .map(|input| input.logical_rewrite_for_stream())
+            .try_collect()?;
+
+        // For each input, the positions of its pk columns in the new schema.
+        let mut slot_types: Vec<DataType> = vec![];
+        let mut input_pk_positions = vec![];
+        for (input, col_change) in &rewritten_inputs {
+            // Maps the columns of the rewritten input back to the original columns.
+            let reverse = col_change.inverse();
+            let positions = input
+                .logical_pk()
+                .iter()
+                .map(|&pk_col| match reverse.try_map(pk_col) {
+                    Some(original_col) if original_col < original_len => (pk_col, original_col),
+                    _ => {
+                        slot_types.push(input.schema().fields()[pk_col].data_type());
+                        (pk_col, original_len + slot_types.len() - 1)
+                    }
+                })
+                .collect_vec();
+            input_pk_positions.push(positions);
+        }
+
+        let source_col = original_len + slot_types.len();
+        let new_inputs = rewritten_inputs
+            .iter()
+            .zip_eq(input_pk_positions)
+            .enumerate()
+            .map(|(source, ((input, col_change), pk_positions))| {
+                let input_schema = input.schema();
+                let mut exprs: Vec<ExprImpl> = (0..original_len)
+                    .map(|i| {
+                        let index = col_change.map(i);
+                        InputRef::new(index, input_schema.fields()[index].data_type()).into()
+                    })
+                    .collect();
+                exprs.extend(
+                    slot_types
+                        .iter()
+                        .map(|data_type| Literal::new(None, data_type.clone()).into()),
+                );
+                for (pk_col, position) in pk_positions {
+                    if position >= original_len {
+                        exprs[position] =
+                            InputRef::new(pk_col, input_schema.fields()[pk_col].data_type()).into();
+                    }
+                }
+                exprs.push(
+                    Literal::new(Some(ScalarImpl::Int32(source as i32)), DataType::Int32).into(),
+                );
+                LogicalProject::create(input.clone(), exprs)
+            })
+            .collect_vec();
+
+        let new_union = Self::new_with_source_col(new_inputs, Some(source_col));
+        let out_col_change = ColIndexMapping::with_target_size(
+            (0..original_len).map(Some).collect(),
+            source_col + 1,
+        );
+        Ok((new_union.into(), out_col_change))
+    }
+
+    fn to_stream(&self) -> Result<PlanRef> {
+        // Shuffle each input by its own pk, so that the inputs will be in different fragments and
+        // the union can be scheduled independently of them. An input without pk, e.g. a simple
+        // aggregation, can't be shuffled by it, and is gathered to a single actor instead.
+        let new_inputs = self
+            .inputs
+            .iter()
+            .map(|input| {
+                let input = input.to_stream()?;
+                let dist = if input.logical_pk().is_empty() {
+                    Distribution::Single
+                } else {
+                    Distribution::HashShard(input.logical_pk().to_vec())
+                };
+                Ok(StreamExchange::new(input, dist).into())
+            })
+            .collect::<Result<Vec<PlanRef>>>()?;
+        let new_logical = Self::new_with_source_col(new_inputs, self.source_col);
+        Ok(StreamUnion::new(new_logical).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{Field, Schema};
+    use risingwave_common::types::DataType;
+
+    use super::*;
+    use crate::optimizer::plan_node::LogicalValues;
+    use crate::session::OptimizerContext;
+
+    #[tokio::test]
+    async fn test_prune_union() {
+        let ctx = OptimizerContext::mock().await;
+        let fields: Vec<Field> = vec![
+            Field::with_name(DataType::Int32, "v1"),
+            Field::with_name(DataType::Int32, "v2"),
+            Field::with_name(DataType::Int32, "v3"),
+        ];
+        let left = LogicalValues::new(vec![], Schema::new(fields.clone()), ctx.clone());
+        let right = LogicalValues::new(vec![], Schema::new(fields), ctx);
+        let union = LogicalUnion::create(vec![left.into(), right.into()]);
+
+        let required_cols = vec![2, 0];
+        let plan = union.prune_col(&required_cols);
+
+        let union = plan.as_logical_union().unwrap();
+        let expected_schema = vec![
+            Field::with_name(DataType::Int32, "v3"),
+            Field::with_name(DataType::Int32, "v1"),
+        ];
+        assert_eq!(expected_schema, union.base.schema.fields().to_owned());
+        for input in union.inputs() {
+            let values = input.as_logical_values().unwrap();
+            assert_eq!(expected_schema, values.base.schema.fields().to_owned());
+        }
+    }
+}
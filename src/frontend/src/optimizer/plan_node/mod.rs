@@ -148,6 +148,7 @@ impl dyn PlanNode {
             } else {
                 "".into()
             },
+            operator_id: self.id().0,
             node_body,
         }
     }
@@ -210,6 +211,7 @@ mod batch_insert;
 mod batch_limit;
 mod batch_lookup_join;
 mod batch_nested_loop_join;
+mod batch_over_agg;
 mod batch_project;
 mod batch_project_set;
 mod batch_seq_scan;
@@ -217,6 +219,7 @@ mod batch_simple_agg;
 mod batch_sort;
 mod batch_table_function;
 mod batch_topn;
+mod batch_union;
 mod batch_update;
 mod batch_values;
 mod logical_agg;
@@ -229,12 +232,14 @@ mod logical_insert;
 mod logical_join;
 mod logical_limit;
 mod logical_multi_join;
+mod logical_over_agg;
 mod logical_project;
 mod logical_project_set;
 mod logical_scan;
 mod logical_source;
 mod logical_table_function;
 mod logical_topn;
+mod logical_union;
 mod logical_update;
 mod logical_values;
 mod stream_delta_join;
@@ -243,6 +248,7 @@ mod stream_exchange;
 mod stream_expand;
 mod stream_filter;
 mod stream_global_simple_agg;
+mod stream_group_topn;
 mod stream_hash_agg;
 mod stream_hash_join;
 mod stream_hop_window;
@@ -255,6 +261,7 @@ mod stream_sink;
 mod stream_source;
 mod stream_table_scan;
 mod stream_topn;
+mod stream_union;
 
 pub mod utils;
 
@@ -269,6 +276,7 @@ pub use batch_insert::BatchInsert;
 pub use batch_limit::BatchLimit;
 pub use batch_lookup_join::BatchLookupJoin;
 pub use batch_nested_loop_join::BatchNestedLoopJoin;
+pub use batch_over_agg::BatchOverAgg;
 pub use batch_project::BatchProject;
 pub use batch_project_set::BatchProjectSet;
 pub use batch_seq_scan::BatchSeqScan;
@@ -276,6 +284,7 @@ pub use batch_simple_agg::BatchSimpleAgg;
 pub use batch_sort::BatchSort;
 pub use batch_table_function::BatchTableFunction;
 pub use batch_topn::BatchTopN;
+pub use batch_union::BatchUnion;
 pub use batch_update::BatchUpdate;
 pub use batch_values::BatchValues;
 pub use logical_agg::{LogicalAgg, PlanAggCall, PlanAggCallDisplay};
@@ -288,12 +297,14 @@ pub use logical_insert::LogicalInsert;
 pub use logical_join::LogicalJoin;
 pub use logical_limit::LogicalLimit;
 pub use logical_multi_join::{LogicalMultiJoin, LogicalMultiJoinBuilder};
+pub use logical_over_agg::{LogicalOverAgg, PlanWindowFunction, PlanWindowFunctionDisplay};
 pub use logical_project::{LogicalProject, LogicalProjectBuilder};
 pub use logical_project_set::LogicalProjectSet;
 pub use logical_scan::LogicalScan;
 pub use logical_source::LogicalSource;
 pub use logical_table_function::LogicalTableFunction;
 pub use logical_topn::LogicalTopN;
+pub use logical_union::LogicalUnion;
 pub use logical_update::LogicalUpdate;
 pub use logical_values::LogicalValues;
 pub use stream_delta_join::StreamDeltaJoin;
@@ -302,6 +313,7 @@ pub use stream_exchange::StreamExchange;
 pub use stream_expand::StreamExpand;
 pub use stream_filter::StreamFilter;
 pub use stream_global_simple_agg::StreamGlobalSimpleAgg;
+pub use stream_group_topn::StreamGroupTopN;
 pub use stream_hash_agg::StreamHashAgg;
 pub use stream_hash_join::StreamHashJoin;
 pub use stream_hop_window::StreamHopWindow;
@@ -314,6 +326,7 @@ pub use stream_sink::StreamSink;
 pub use stream_source::StreamSource;
 pub use stream_table_scan::StreamTableScan;
 pub use stream_topn::StreamTopN;
+pub use stream_union::StreamUnion;
 
 use crate::session::OptimizerContextRef;
 
@@ -352,6 +365,8 @@ macro_rules! for_all_plan_nodes {
             , { Logical, MultiJoin }
             , { Logical, Expand }
             , { Logical, ProjectSet }
+            , { Logical, Union }
+            , { Logical, OverAgg }
             // , { Logical, Sort } we don't need a LogicalSort, just require the Order
             , { Batch, SimpleAgg }
             , { Batch, HashAgg }
@@ -373,6 +388,8 @@ macro_rules! for_all_plan_nodes {
             , { Batch, Expand }
             , { Batch, LookupJoin }
             , { Batch, ProjectSet }
+            , { Batch, Union }
+            , { Batch, OverAgg }
             , { Stream, Project }
             , { Stream, Filter }
             , { Stream, TableScan }
@@ -391,6 +408,8 @@ macro_rules! for_all_plan_nodes {
             , { Stream, Expand }
             , { Stream, DynamicFilter }
             , { Stream, ProjectSet }
+            , { Stream, Union }
+            , { Stream, GroupTopN }
         }
     };
 }
@@ -419,6 +438,8 @@ macro_rules! for_logical_plan_nodes {
             , { Logical, MultiJoin }
             , { Logical, Expand }
             , { Logical, ProjectSet }
+            , { Logical, Union }
+            , { Logical, OverAgg }
             // , { Logical, Sort} not sure if we will support Order by clause in subquery/view/MV
             // if we dont support that, we don't need LogicalSort, just require the Order at the top of query
         }
@@ -451,6 +472,8 @@ macro_rules! for_batch_plan_nodes {
             , { Batch, Expand }
             , { Batch, LookupJoin }
             , { Batch, ProjectSet }
+            , { Batch, Union }
+            , { Batch, OverAgg }
         }
     };
 }
@@ -479,6 +502,8 @@ macro_rules! for_stream_plan_nodes {
             , { Stream, Expand }
             , { Stream, DynamicFilter }
             , { Stream, ProjectSet }
+            , { Stream, Union }
+            , { Stream, GroupTopN }
         }
     };
 }
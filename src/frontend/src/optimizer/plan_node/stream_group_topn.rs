@@ -0,0 +1,156 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use risingwave_common::catalog::{DatabaseId, SchemaId};
+use risingwave_common::config::constant::hummock::PROPERTIES_RETAINTION_SECOND_KEY;
+use risingwave_common::util::sort_util::OrderType;
+use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
+
+use super::utils::TableCatalogBuilder;
+use super::{LogicalTopN, PlanBase, PlanRef, PlanTreeNodeUnary, ToStreamProst};
+use crate::catalog::TableCatalog;
+use crate::optimizer::property::FieldOrder;
+
+/// `StreamGroupTopN` implements [`super::LogicalTopN`] with a group key, and finds the top N
+/// elements of each group. The input must be distributed by the group key.
+#[derive(Debug, Clone)]
+pub struct StreamGroupTopN {
+    pub base: PlanBase,
+    logical: LogicalTopN,
+}
+
+impl StreamGroupTopN {
+    pub fn new(logical: LogicalTopN) -> Self {
+        assert!(!logical.group_key().is_empty());
+        let ctx = logical.base.ctx.clone();
+        let input = logical.input();
+        let base = PlanBase::new_stream(
+            ctx,
+            logical.schema().clone(),
+            input.logical_pk().to_vec(),
+            input.distribution().clone(),
+            false,
+        );
+        StreamGroupTopN { base, logical }
+    }
+
+    /// The internal table stores the input rows, ordered by the group key, the order of `TopN`
+    /// and the input pk, which is the order the executor scans each group in.
+    fn infer_internal_table_catalog(&self) -> TableCatalog {
+        let input = self.input();
+        let base = input.plan_base();
+        let schema = &base.schema;
+
+        let append_only = input.append_only();
+        let dist_keys = base.dist.dist_column_indices().to_vec();
+
+        let mut internal_table_catalog_builder = TableCatalogBuilder::new();
+
+        schema.fields().iter().for_each(|field| {
+            internal_table_catalog_builder.add_column(field);
+        });
+
+        let mut order_cols = vec![];
+        for &idx in self.logical.group_key() {
+            order_cols.push((idx, OrderType::Ascending));
+        }
+        for field_order in &self.logical.topn_order().field_order {
+            let order_pair = field_order.to_order_pair();
+            if !order_cols
+                .iter()
+                .any(|(idx, _)| *idx == order_pair.column_idx)
+            {
+                order_cols.push((order_pair.column_idx, order_pair.order_type));
+            }
+        }
+        for &idx in &base.logical_pk {
+            if !order_cols.iter().any(|(i, _)| *i == idx) {
+                order_cols.push((idx, OrderType::Ascending));
+            }
+        }
+        order_cols.into_iter().for_each(|(idx, order_type)| {
+            internal_table_catalog_builder.add_order_column(idx, order_type)
+        });
+
+        if !base.ctx.inner().with_properties.is_empty() {
+            let properties: HashMap<_, _> = base
+                .ctx
+                .inner()
+                .with_properties
+                .iter()
+                .filter(|(key, _)| key.as_str() == PROPERTIES_RETAINTION_SECOND_KEY)
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+
+            if !properties.is_empty() {
+                internal_table_catalog_builder.add_properties(properties);
+            }
+        }
+
+        internal_table_catalog_builder.build(dist_keys, append_only)
+    }
+}
+
+impl fmt::Display for StreamGroupTopN {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.logical.fmt_with_name(f, "StreamGroupTopN")
+    }
+}
+
+impl PlanTreeNodeUnary for StreamGroupTopN {
+    fn input(&self) -> PlanRef {
+        self.logical.input()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(self.logical.clone_with_input(input))
+    }
+}
+
+impl_plan_tree_node_for_unary! { StreamGroupTopN }
+
+impl ToStreamProst for StreamGroupTopN {
+    fn to_stream_prost_body(&self) -> ProstStreamNode {
+        use risingwave_pb::stream_plan::*;
+        let column_orders = self
+            .logical
+            .topn_order()
+            .field_order
+            .iter()
+            .map(FieldOrder::to_protobuf)
+            .collect();
+        let group_key = self
+            .logical
+            .group_key()
+            .iter()
+            .map(|idx| *idx as u32)
+            .collect::<Vec<_>>();
+
+        ProstStreamNode::GroupTopN(GroupTopNNode {
+            column_orders,
+            limit: self.logical.limit() as u64,
+            offset: self.logical.offset() as u64,
+            distribution_key: group_key.clone(),
+            group_key,
+            table: Some(self.infer_internal_table_catalog().to_prost(
+                SchemaId::placeholder() as u32,
+                DatabaseId::placeholder() as u32,
+            )),
+            output_rank: self.logical.output_rank(),
+        })
+    }
+}
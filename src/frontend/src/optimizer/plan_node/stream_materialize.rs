@@ -179,6 +179,7 @@ impl StreamMaterialize {
             vnode_mapping: None,
             properties,
             read_pattern_prefix_column: 0,
+            statistics: None,
         };
 
         Ok(Self { base, input, table })
@@ -27,6 +27,8 @@ pub struct StreamSink {
     pub base: PlanBase,
     input: PlanRef,
     properties: HashMap<String, String>,
+    /// The columns of the input hidden from the users.
+    hidden_column_indices: Vec<usize>,
 }
 
 impl StreamSink {
@@ -46,14 +48,23 @@ impl StreamSink {
     }
 
     #[must_use]
-    pub fn new(input: PlanRef, properties: HashMap<String, String>) -> Self {
+    pub fn new(
+        input: PlanRef,
+        properties: HashMap<String, String>,
+        hidden_column_indices: Vec<usize>,
+    ) -> Self {
         let base = Self::derive_plan_base(&input).unwrap();
         Self {
             base,
             input,
             properties,
+            hidden_column_indices,
         }
     }
+
+    pub fn hidden_column_indices(&self) -> &[usize] {
+        &self.hidden_column_indices
+    }
 }
 
 impl PlanTreeNodeUnary for StreamSink {
@@ -62,7 +73,11 @@ impl PlanTreeNodeUnary for StreamSink {
     }
 
     fn clone_with_input(&self, input: PlanRef) -> Self {
-        Self::new(input, self.properties.clone())
+        Self::new(
+            input,
+            self.properties.clone(),
+            self.hidden_column_indices.clone(),
+        )
         // TODO(nanderstabel): Add assertions (assert_eq!)
     }
 }
@@ -88,6 +103,12 @@ impl ToStreamProst for StreamSink {
             table_id: table_desc.table_id.table_id(),
             column_ids: vec![], // TODO(nanderstabel): fix empty Vector
             properties: self.properties.clone(),
+            state_table_id: 0, // will be assigned by the fragmenter
+            hidden_column_indices: self
+                .hidden_column_indices
+                .iter()
+                .map(|&i| i as u32)
+                .collect(),
         })
     }
 }
@@ -14,10 +14,16 @@
 
 use std::fmt;
 
+use risingwave_common::catalog::{DatabaseId, Field, SchemaId};
+use risingwave_common::util::sort_util::OrderType;
 use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
 use risingwave_pb::stream_plan::SourceNode;
+use risingwave_source::dead_letter::{DeadLetter, SourceErrorPolicy};
+use risingwave_source::rate_limit::rate_limit_from_properties;
 
+use super::utils::TableCatalogBuilder;
 use super::{LogicalSource, PlanBase, ToStreamProst};
+use crate::catalog::TableCatalog;
 use crate::optimizer::property::Distribution;
 
 /// [`StreamSource`] represents a table/connector source at the very beginning of the graph.
@@ -46,6 +52,32 @@ impl StreamSource {
             .map(|f| f.name.clone())
             .collect()
     }
+
+    /// The internal table keeping the messages that failed to be parsed, if the error policy of
+    /// the source is `dead_letter`.
+    fn infer_dead_letter_table_catalog(&self) -> Option<TableCatalog> {
+        if self.logical.source_catalog.error_policy != SourceErrorPolicy::DeadLetter {
+            return None;
+        }
+        let mut builder = TableCatalogBuilder::new();
+        for (name, data_type) in DeadLetter::table_columns() {
+            builder.add_column(&Field::with_name(data_type, name));
+        }
+        for idx in DeadLetter::PK_INDICES {
+            builder.add_order_column(idx, OrderType::Ascending);
+        }
+        // The dead letters are written by each actor of the source without any shuffle. A message
+        // delivered again after recovery overwrites the row written before.
+        Some(builder.build(vec![], false))
+    }
+
+    /// The rate limit in the WITH clause of the streaming job overrides the one of the source.
+    fn rate_limit(&self) -> Option<u32> {
+        rate_limit_from_properties(&self.base.ctx.inner().with_properties)
+            .ok()
+            .flatten()
+            .or(self.logical.source_catalog.rate_limit)
+    }
 }
 
 impl_plan_tree_node_for_leaf! { StreamSource }
@@ -75,6 +107,13 @@ impl ToStreamProst for StreamSource {
                 .map(|c| c.column_id().into())
                 .collect(),
             source_type: self.logical.source_catalog.source_type as i32,
+            dead_letter_table: self.infer_dead_letter_table_catalog().map(|table| {
+                table.to_prost(
+                    SchemaId::placeholder() as u32,
+                    DatabaseId::placeholder() as u32,
+                )
+            }),
+            rate_limit: self.rate_limit().unwrap_or(0),
         })
     }
 }
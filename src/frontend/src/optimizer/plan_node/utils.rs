@@ -104,6 +104,7 @@ impl TableCatalogBuilder {
             vnode_mapping: None,
             properties: self.properties,
             read_pattern_prefix_column: 0,
+            statistics: None,
         }
     }
 
@@ -0,0 +1,196 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Bound;
+
+use fixedbitset::FixedBitSet;
+use itertools::Itertools;
+use risingwave_common::session_config::QueryMode;
+use risingwave_pb::plan_common::JoinType;
+
+use super::super::plan_node::*;
+use super::{BoxedRule, Rule};
+use crate::expr::{ExprImpl, ExprType, FunctionCall, InputRef};
+use crate::utils::{ColIndexMapping, Condition};
+
+/// Scans a secondary index instead of the table in batch queries, when the predicate on the order
+/// key of the index narrows down the scan more than the one on the primary key.
+///
+/// If the index doesn't cover all the columns required, the rows found in the index look up the
+/// other columns in the table by the primary key. This is only done when the lookup join is
+/// enabled in local mode, since a hash join would scan the whole table anyway.
+pub struct IndexSelectionRule {}
+
+impl Rule for IndexSelectionRule {
+    fn apply(&self, plan: PlanRef) -> Option<PlanRef> {
+        let scan = plan.as_logical_scan()?;
+        if scan.is_sys_table() || scan.indexes().is_empty() || scan.predicate().always_true() {
+            return None;
+        }
+
+        let table_desc = scan.table_desc();
+        let num_cols = table_desc.columns.len();
+        let primary_key = table_desc.order_column_indices();
+        let ctx = plan.ctx();
+        let config = ctx.inner().session_ctx.config();
+        let enable_lookup =
+            config.get_batch_enable_lookup_join() && config.get_query_mode() == QueryMode::Local;
+
+        let mut best_prefix = scan_range_prefix(scan.predicate().clone(), &primary_key, num_cols)?;
+        let mut best_index = None;
+        for index in scan.indexes() {
+            let mut covered_cols = FixedBitSet::with_capacity(num_cols);
+            covered_cols.extend(index.index_item.iter().map(|input_ref| input_ref.index));
+            if !primary_key.iter().all(|&col_idx| covered_cols[col_idx]) {
+                continue;
+            }
+            let full_covering = scan
+                .required_col_idx()
+                .iter()
+                .all(|&col_idx| covered_cols[col_idx]);
+            if !full_covering && !enable_lookup {
+                continue;
+            }
+
+            // The conjunctions that can be evaluated on the index.
+            let (index_conjunctions, other_conjunctions): (Vec<_>, Vec<_>) = scan
+                .predicate()
+                .conjunctions
+                .iter()
+                .cloned()
+                .partition(|expr| expr.collect_input_refs(num_cols).is_subset(&covered_cols));
+            let index_predicate = Condition {
+                conjunctions: index_conjunctions,
+            };
+            let other_predicate = Condition {
+                conjunctions: other_conjunctions,
+            };
+
+            // Outputs the primary key to look up the table if the index is not covering.
+            let index_scan = if full_covering {
+                scan.to_index_scan_with(index, scan.output_col_idx(), index_predicate)
+            } else {
+                scan.to_index_scan_with(index, &primary_key, index_predicate)
+            };
+            let index_order_key = index_scan.table_desc().order_column_indices();
+            let prefix = match scan_range_prefix(
+                index_scan.predicate().clone(),
+                &index_order_key,
+                index_scan.table_desc().columns.len(),
+            ) {
+                Some(prefix) => prefix,
+                None => continue,
+            };
+            if prefix > best_prefix {
+                best_prefix = prefix;
+                best_index = Some((index_scan, full_covering, other_predicate));
+            }
+        }
+
+        let (index_scan, full_covering, other_predicate) = best_index?;
+        if full_covering {
+            return Some(index_scan.into());
+        }
+
+        // Look up the other columns required in the table, with the primary key first.
+        let lookup_col_idx = primary_key
+            .iter()
+            .copied()
+            .chain(
+                scan.required_col_idx()
+                    .iter()
+                    .copied()
+                    .filter(|col_idx| !primary_key.contains(col_idx)),
+            )
+            .collect_vec();
+        let lookup_scan = scan.to_lookup_scan(lookup_col_idx.clone());
+
+        let left_len = primary_key.len();
+        let mut mapping = ColIndexMapping::with_target_size(
+            (0..num_cols)
+                .map(|col_idx| {
+                    lookup_col_idx
+                        .iter()
+                        .position(|&i| i == col_idx)
+                        .map(|pos| left_len + pos)
+                })
+                .collect(),
+            left_len + lookup_col_idx.len(),
+        );
+        let eq_conjunctions = primary_key.iter().enumerate().map(|(i, &col_idx)| {
+            let data_type = table_desc.columns[col_idx].data_type.clone();
+            let eq: ExprImpl = FunctionCall::new(
+                ExprType::Equal,
+                vec![
+                    InputRef::new(i, data_type.clone()).into(),
+                    InputRef::new(left_len + i, data_type).into(),
+                ],
+            )
+            .unwrap()
+            .into();
+            eq
+        });
+        let on = Condition {
+            conjunctions: eq_conjunctions
+                .chain(other_predicate.rewrite_expr(&mut mapping).conjunctions)
+                .collect(),
+        };
+        let output_indices = scan
+            .output_col_idx()
+            .iter()
+            .map(|&col_idx| mapping.map(col_idx))
+            .collect();
+
+        Some(
+            LogicalJoin::new_with_output_indices(
+                index_scan.into(),
+                lookup_scan.into(),
+                JoinType::Inner,
+                on,
+                output_indices,
+            )
+            .into(),
+        )
+    }
+}
+
+/// How far the scan ranges built from the predicate narrow down a scan in the given order: the
+/// number of leading columns fixed to values, then the number of bounds on the next column.
+/// Returns `None` if the scan ranges can't be built, leaving the error to the conversion to batch.
+fn scan_range_prefix(
+    predicate: Condition,
+    order_column_ids: &[usize],
+    num_cols: usize,
+) -> Option<(usize, usize)> {
+    let (scan_ranges, _) = predicate
+        .split_to_scan_ranges(order_column_ids, num_cols)
+        .ok()?;
+    Some(match scan_ranges.first() {
+        Some(scan_range) => {
+            let (lower, upper) = &scan_range.range;
+            let bounds = [lower, upper]
+                .into_iter()
+                .filter(|bound| !matches!(bound, Bound::Unbounded))
+                .count();
+            (scan_range.eq_conds.len(), bounds)
+        }
+        None => (0, 0),
+    })
+}
+
+impl IndexSelectionRule {
+    pub fn create() -> BoxedRule {
+        Box::new(Self {})
+    }
+}
@@ -39,6 +39,8 @@ mod pull_up_correlated_predicate;
 pub use pull_up_correlated_predicate::*;
 mod index_delta_join;
 pub use index_delta_join::*;
+mod index_selection;
+pub use index_selection::*;
 mod reorder_multijoin;
 pub use reorder_multijoin::*;
 mod apply_agg;
@@ -59,6 +61,8 @@ mod push_calculation_of_join;
 pub use apply_join::*;
 pub use distinct_agg::*;
 pub use push_calculation_of_join::*;
+mod over_agg_to_topn;
+pub use over_agg_to_topn::*;
 
 #[macro_export]
 macro_rules! for_all_rules {
@@ -72,7 +76,9 @@ macro_rules! for_all_rules {
             ,{ApplyJoinRule}
             ,{DistinctAggRule}
             ,{IndexDeltaJoinRule}
+            ,{IndexSelectionRule}
             ,{MergeMultiJoinRule}
+            ,{OverAggToTopNRule}
             ,{ProjectEliminateRule}
             ,{ProjectJoinRule}
             ,{ProjectMergeRule}
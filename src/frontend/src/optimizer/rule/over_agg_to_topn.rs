@@ -0,0 +1,136 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::types::ScalarImpl;
+
+use super::super::plan_node::*;
+use super::{BoxedRule, Rule};
+use crate::expr::{ExprImpl, ExprType, WindowFunctionType};
+use crate::utils::Condition;
+
+/// Transforms the following pattern into a group [`LogicalTopN`]:
+///
+/// ```sql
+/// SELECT .. FROM
+///   (SELECT .., row_number() OVER (PARTITION BY .. ORDER BY ..) AS rn FROM ..)
+/// WHERE rn [ < | <= | > | >= | = ] ..;
+/// ```
+///
+/// If the row number is selected, or there's no `Project` above the `Filter`, the `TopN` outputs it
+/// after the input columns, in the same place as the `OverAgg` does.
+pub struct OverAggToTopNRule {}
+impl Rule for OverAggToTopNRule {
+    fn apply(&self, plan: PlanRef) -> Option<PlanRef> {
+        // The `Project` is removed if it selects all the columns.
+        let (project, plan) = match plan.as_logical_project() {
+            Some(project) => (Some(project), project.input()),
+            None => (None, plan.clone()),
+        };
+        let filter = plan.as_logical_filter()?;
+        let plan = filter.input();
+        let over_agg = plan.as_logical_over_agg()?;
+
+        let [window_function] = over_agg.window_functions() else {
+            return None;
+        };
+        if window_function.function_type != WindowFunctionType::RowNumber
+            || over_agg.partition_by().is_empty()
+        {
+            return None;
+        }
+        let rank_col = over_agg.input().schema().len();
+        let schema_len = over_agg.schema().len();
+        let output_rank = project.map_or(true, |project| {
+            project
+                .exprs()
+                .iter()
+                .any(|expr| expr.collect_input_refs(schema_len).contains(rank_col))
+        });
+
+        // The row numbers are 1-based, and the range is `[lower, upper]`.
+        let mut lower = 1;
+        let mut upper = None;
+        let mut other_conds = vec![];
+        for cond in &filter.predicate().conjunctions {
+            if !cond.collect_input_refs(schema_len).contains(rank_col) {
+                other_conds.push(cond.clone());
+                continue;
+            }
+            let (input_ref, cmp, value) = match cond.as_comparison_const() {
+                Some(comparison) => comparison,
+                None => {
+                    let (input_ref, value) = cond.as_eq_const()?;
+                    (input_ref, ExprType::Equal, value)
+                }
+            };
+            debug_assert_eq!(input_ref.index(), rank_col);
+            let value = eval_i64(&value)?;
+            let (new_lower, new_upper) = match cmp {
+                // The rule is not applied if the bound overflows.
+                ExprType::LessThan => (None, Some(value.checked_sub(1)?)),
+                ExprType::LessThanOrEqual => (None, Some(value)),
+                ExprType::GreaterThan => (Some(value.checked_add(1)?), None),
+                ExprType::GreaterThanOrEqual => (Some(value), None),
+                ExprType::Equal => (Some(value), Some(value)),
+                _ => unreachable!(),
+            };
+            if let Some(new_lower) = new_lower {
+                lower = lower.max(new_lower);
+            }
+            if let Some(new_upper) = new_upper {
+                upper = Some(upper.map_or(new_upper, |upper: i64| upper.min(new_upper)));
+            }
+        }
+        // An unbounded or empty range can not be computed by `TopN`.
+        let upper = upper?;
+        if upper < lower {
+            return None;
+        }
+
+        let top_n = LogicalTopN::with_group(
+            over_agg.input(),
+            (upper - lower + 1) as usize,
+            (lower - 1) as usize,
+            over_agg.order_by().clone(),
+            over_agg.partition_by().to_vec(),
+            output_rank,
+        );
+        let filter = LogicalFilter::create(
+            top_n.into(),
+            Condition {
+                conjunctions: other_conds,
+            },
+        );
+        match project {
+            Some(project) => Some(LogicalProject::new(filter, project.exprs().clone()).into()),
+            None => Some(filter),
+        }
+    }
+}
+
+/// Evaluate a constant expression as an `i64`.
+fn eval_i64(expr: &ExprImpl) -> Option<i64> {
+    match expr.eval_row_const().ok()?? {
+        ScalarImpl::Int16(v) => Some(v as i64),
+        ScalarImpl::Int32(v) => Some(v as i64),
+        ScalarImpl::Int64(v) => Some(v),
+        _ => None,
+    }
+}
+
+impl OverAggToTopNRule {
+    pub fn create() -> BoxedRule {
+        Box::new(OverAggToTopNRule {})
+    }
+}
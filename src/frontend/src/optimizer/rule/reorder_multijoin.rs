@@ -16,14 +16,18 @@ use super::super::plan_node::*;
 use super::Rule;
 use crate::optimizer::rule::BoxedRule;
 
-/// Reorders a multi join into a left deep join via the heuristic ordering
+/// Reorders a multi join into a left deep join, via the ordering by the estimated cardinality if
+/// the tables are analyzed, otherwise via the heuristic ordering.
 pub struct ReorderMultiJoinRule {}
 
 impl Rule for ReorderMultiJoinRule {
     fn apply(&self, plan: PlanRef) -> Option<PlanRef> {
         let join = plan.as_logical_multi_join()?;
         // check if join is inner and can be merged into multijoin
-        let join_ordering = join.heuristic_ordering().ok()?; // maybe panic here instead?
+        let join_ordering = match join.cost_based_ordering() {
+            Some(join_ordering) => join_ordering,
+            None => join.heuristic_ordering().ok()?, // maybe panic here instead?
+        };
         let left_deep_join = join.as_reordered_left_deep_join(&join_ordering);
         Some(left_deep_join)
     }
@@ -39,12 +43,12 @@ impl ReorderMultiJoinRule {
 mod tests {
     use itertools::Itertools;
     use risingwave_common::catalog::{Field, Schema};
-    use risingwave_common::types::DataType;
+    use risingwave_common::types::{DataType, ScalarImpl};
     use risingwave_pb::expr::expr_node::Type;
     use risingwave_pb::plan_common::JoinType;
 
     use super::*;
-    use crate::expr::{ExprImpl, FunctionCall, InputRef};
+    use crate::expr::{ExprImpl, FunctionCall, InputRef, Literal};
     use crate::session::OptimizerContext;
     use crate::utils::Condition;
 
@@ -132,4 +136,78 @@ mod tests {
 
         assert_eq!(multi_join.heuristic_ordering().unwrap(), vec![0, 2, 1]);
     }
+
+    #[tokio::test]
+    async fn test_cost_based_join_reorder_from_multijoin() {
+        // Converts a join graph
+        // C-A-B
+        //
+        // where A has 100 rows, B has 10 rows and C has 1000 rows, with initial ordering:
+        //
+        //      inner
+        //     /   |
+        //  inner  B
+        //  / |
+        // A  C
+        //
+        // to:
+        //
+        //     inner
+        //     /   |
+        //  inner  C
+        //  / |
+        // A  B
+        //
+        // as A joined with B is estimated to have 10 rows, and 100 rows with C.
+
+        let ty = DataType::Int32;
+        let ctx = OptimizerContext::mock().await;
+        let fields: Vec<Field> = (1..10)
+            .map(|i| Field::with_name(ty.clone(), format!("v{}", i)))
+            .collect();
+        let values = |fields: &[Field], row_count: usize| {
+            let row = (0..fields.len())
+                .map(|i| Literal::new(Some(ScalarImpl::Int32(i as i32)), ty.clone()).into())
+                .collect::<Vec<ExprImpl>>();
+            LogicalValues::new(
+                vec![row; row_count],
+                Schema {
+                    fields: fields.to_vec(),
+                },
+                ctx.clone(),
+            )
+        };
+        let relation_a = values(&fields[0..3], 100);
+        let relation_c = values(&fields[3..6], 1000);
+        let relation_b = values(&fields[6..9], 10);
+        let eq = |left: usize, right: usize| -> ExprImpl {
+            FunctionCall::new(
+                Type::Equal,
+                vec![
+                    InputRef::new(left, ty.clone()).into(),
+                    InputRef::new(right, ty.clone()).into(),
+                ],
+            )
+            .unwrap()
+            .into()
+        };
+
+        let join_type = JoinType::Inner;
+        let join_0 = LogicalJoin::new(
+            relation_a.into(),
+            relation_c.into(),
+            join_type,
+            Condition::with_expr(eq(1, 3)),
+        );
+        let join_1 = LogicalJoin::new(
+            join_0.into(),
+            relation_b.into(),
+            join_type,
+            Condition::with_expr(eq(0, 6)),
+        );
+        let multi_join = LogicalMultiJoinBuilder::new(join_1.into()).build();
+
+        assert_eq!(multi_join.heuristic_ordering().unwrap(), vec![0, 1, 2]);
+        assert_eq!(multi_join.cost_based_ordering().unwrap(), vec![0, 2, 1]);
+    }
 }
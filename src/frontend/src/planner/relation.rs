@@ -66,6 +66,7 @@ impl Planner {
                 .collect(),
             self.ctx(),
         )
+        .with_statistics(base_table.table_catalog.statistics.map(Rc::new))
         .into())
     }
 
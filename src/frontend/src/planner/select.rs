@@ -25,8 +25,8 @@ use crate::expr::{
 };
 pub use crate::optimizer::plan_node::LogicalFilter;
 use crate::optimizer::plan_node::{
-    LogicalAgg, LogicalApply, LogicalJoin, LogicalProject, LogicalProjectSet, LogicalValues,
-    PlanAggCall, PlanRef,
+    LogicalAgg, LogicalApply, LogicalJoin, LogicalOverAgg, LogicalProject, LogicalProjectSet,
+    LogicalValues, PlanAggCall, PlanRef,
 };
 use crate::planner::Planner;
 use crate::utils::Condition;
@@ -74,6 +74,10 @@ impl Planner {
             root = self.plan_where(root, having)?;
         }
 
+        if select_items.iter().any(|e| e.has_window_function()) {
+            (root, select_items) = LogicalOverAgg::create(root, select_items)?;
+        }
+
         if select_items.iter().any(|e| e.has_subquery()) {
             (root, select_items) = self.substitute_subqueries(root, select_items)?;
         }
@@ -12,12 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risingwave_common::error::Result;
+use itertools::Itertools;
+use risingwave_common::catalog::Schema;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_common::types::DataType;
 
-use crate::binder::BoundSetExpr;
-use crate::expr::ExprImpl;
-use crate::optimizer::plan_node::PlanRef;
+use crate::binder::{BoundSetExpr, BoundSetOperation};
+use crate::expr::{ExprImpl, ExprType, FunctionCall, InputRef};
+use crate::optimizer::plan_node::{
+    LogicalAgg, LogicalFilter, LogicalProject, LogicalUnion, PlanAggCall, PlanRef,
+};
 use crate::planner::Planner;
+use crate::utils::Condition;
 
 impl Planner {
     pub(super) fn plan_set_expr(
@@ -28,6 +34,150 @@ impl Planner {
         match set_expr {
             BoundSetExpr::Select(s) => self.plan_select(*s, extra_order_exprs),
             BoundSetExpr::Values(v) => self.plan_values(*v),
+            BoundSetExpr::SetOperation {
+                op,
+                all,
+                left,
+                right,
+                schema,
+            } => {
+                if !extra_order_exprs.is_empty() {
+                    return Err(ErrorCode::InvalidInputSyntax(format!(
+                        "ORDER BY on a {} result must be on one of the result columns",
+                        op
+                    ))
+                    .into());
+                }
+                self.plan_set_operation(op, all, *left, *right, schema)
+            }
         }
     }
+
+    /// Plan a set operation.
+    ///
+    /// - `UNION ALL` is planned as a [`LogicalUnion`].
+    /// - `UNION` is planned as a [`LogicalAgg`] grouping by all the columns on top of the union.
+    /// - `INTERSECT` and `EXCEPT` append a column telling which side a row comes from to each side,
+    ///   and count the rows of each side for every distinct row after the union. Then a
+    ///   [`LogicalFilter`] keeps the rows that satisfy the set operation.
+    fn plan_set_operation(
+        &mut self,
+        op: BoundSetOperation,
+        all: bool,
+        left: BoundSetExpr,
+        right: BoundSetExpr,
+        schema: Schema,
+    ) -> Result<PlanRef> {
+        let data_types = schema.data_types();
+        let left = self.plan_set_operation_input(left, &data_types)?;
+        let right = self.plan_set_operation_input(right, &data_types)?;
+        let column_num = data_types.len();
+
+        match op {
+            BoundSetOperation::Union => {
+                let union = LogicalUnion::create(vec![left, right]);
+                if all {
+                    Ok(union)
+                } else {
+                    let group_key = (0..column_num).collect();
+                    Ok(LogicalAgg::new(vec![], group_key, union).into())
+                }
+            }
+            BoundSetOperation::Intersect | BoundSetOperation::Except => {
+                assert!(
+                    !all,
+                    "INTERSECT ALL and EXCEPT ALL should be rejected by binder"
+                );
+                let left = Self::append_side_column(left, 0);
+                let right = Self::append_side_column(right, 1);
+                let union = LogicalUnion::create(vec![left, right]);
+
+                // `count(*) filter (where side = 0)` and `count(*) filter (where side = 1)`
+                let side = InputRef::new(column_num, DataType::Int32);
+                let count_side = |value| -> Result<PlanAggCall> {
+                    let filter = FunctionCall::new(
+                        ExprType::Equal,
+                        vec![side.clone().into(), ExprImpl::literal_int(value)],
+                    )?;
+                    Ok(PlanAggCall::count_star()
+                        .with_condition(Condition::with_expr(filter.into())))
+                };
+                let agg_calls = vec![count_side(0)?, count_side(1)?];
+                let group_key = (0..column_num).collect();
+                let agg = LogicalAgg::new(agg_calls, group_key, union);
+
+                // A group always has at least one row, so for `EXCEPT` we only need to check that
+                // none of them comes from the right side.
+                let left_count = InputRef::new(column_num, DataType::Int64);
+                let right_count = InputRef::new(column_num + 1, DataType::Int64);
+                let predicate: ExprImpl = match op {
+                    BoundSetOperation::Intersect => FunctionCall::new(
+                        ExprType::And,
+                        vec![
+                            FunctionCall::new(
+                                ExprType::GreaterThan,
+                                vec![left_count.into(), ExprImpl::literal_int(0)],
+                            )?
+                            .into(),
+                            FunctionCall::new(
+                                ExprType::GreaterThan,
+                                vec![right_count.into(), ExprImpl::literal_int(0)],
+                            )?
+                            .into(),
+                        ],
+                    )?
+                    .into(),
+                    BoundSetOperation::Except => FunctionCall::new(
+                        ExprType::Equal,
+                        vec![right_count.into(), ExprImpl::literal_int(0)],
+                    )?
+                    .into(),
+                    BoundSetOperation::Union => unreachable!(),
+                };
+                let filter = LogicalFilter::create_with_expr(agg.into(), predicate);
+                Ok(LogicalProject::with_out_col_idx(filter, 0..column_num).into())
+            }
+        }
+    }
+
+    /// Plan one side of a set operation, and add a [`LogicalProject`] to cast its output columns
+    /// to `data_types` if needed.
+    fn plan_set_operation_input(
+        &mut self,
+        set_expr: BoundSetExpr,
+        data_types: &[DataType],
+    ) -> Result<PlanRef> {
+        let mut plan = self.plan_set_expr(set_expr, vec![])?;
+        if plan.as_logical_project_set().is_some() {
+            // Do not output projected_row_id hidden column.
+            plan = LogicalProject::with_out_col_idx(plan.clone(), 1..plan.schema().len()).into();
+        }
+        if plan.schema().data_types() == data_types {
+            return Ok(plan);
+        }
+        let exprs = plan
+            .schema()
+            .fields()
+            .iter()
+            .zip_eq(data_types)
+            .enumerate()
+            .map(|(i, (field, data_type))| {
+                ExprImpl::from(InputRef::new(i, field.data_type())).cast_implicit(data_type.clone())
+            })
+            .try_collect()?;
+        Ok(LogicalProject::create(plan, exprs))
+    }
+
+    /// Append a constant column `side` of type `Int32` to the output of `plan`.
+    fn append_side_column(plan: PlanRef, side: i32) -> PlanRef {
+        let exprs = plan
+            .schema()
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| InputRef::new(i, field.data_type()).into())
+            .chain(std::iter::once(ExprImpl::literal_int(side)))
+            .collect();
+        LogicalProject::create(plan, exprs)
+    }
 }
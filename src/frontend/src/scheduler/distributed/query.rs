@@ -17,6 +17,7 @@ use std::mem;
 use std::sync::Arc;
 
 use anyhow::anyhow;
+use pgwire::pg_server::SessionId;
 use risingwave_common::bail;
 use risingwave_pb::batch_plan::{TaskId as TaskIdProst, TaskOutputId as TaskOutputIdProst};
 use risingwave_rpc_client::ComputeClientPoolRef;
@@ -31,7 +32,9 @@ use crate::scheduler::distributed::StageEvent::Scheduled;
 use crate::scheduler::distributed::StageExecution;
 use crate::scheduler::plan_fragmenter::{Query, StageId, ROOT_TASK_ID, ROOT_TASK_OUTPUT_ID};
 use crate::scheduler::worker_node_manager::WorkerNodeManagerRef;
-use crate::scheduler::{HummockSnapshotManagerRef, SchedulerError, SchedulerResult};
+use crate::scheduler::{
+    HummockSnapshotManagerRef, QueryExecutorStatsRef, SchedulerError, SchedulerResult,
+};
 
 /// Message sent to a `QueryRunner` to control its execution.
 #[derive(Debug)]
@@ -59,7 +62,7 @@ enum QueryState {
 
     /// Running
     Running {
-        _msg_sender: Sender<QueryMessage>,
+        msg_sender: Sender<QueryMessage>,
         _task_handle: JoinHandle<SchedulerResult<()>>,
     },
 
@@ -74,6 +77,8 @@ pub struct QueryExecution {
     query: Arc<Query>,
     state: Arc<RwLock<QueryState>>,
     _stage_executions: Arc<HashMap<StageId, Arc<StageExecution>>>,
+    /// The session that runs the query, used to cancel the query on the request of the session.
+    pub session_id: SessionId,
 }
 
 struct QueryRunner {
@@ -100,6 +105,8 @@ impl QueryExecution {
         worker_node_manager: WorkerNodeManagerRef,
         hummock_snapshot_manager: HummockSnapshotManagerRef,
         compute_client_pool: ComputeClientPoolRef,
+        session_id: SessionId,
+        executor_stats: Option<QueryExecutorStatsRef>,
     ) -> Self {
         let query = Arc::new(query);
         let (sender, receiver) = channel(100);
@@ -123,6 +130,7 @@ impl QueryExecution {
                     sender.clone(),
                     children_stages,
                     compute_client_pool.clone(),
+                    executor_stats.clone(),
                 ));
                 stage_executions.insert(stage_id, stage_exec);
             }
@@ -153,6 +161,7 @@ impl QueryExecution {
             query,
             state: Arc::new(RwLock::new(state)),
             _stage_executions: stage_executions,
+            session_id,
         }
     }
 
@@ -175,6 +184,14 @@ impl QueryExecution {
                     })
                 });
 
+                *state = QueryState::Running {
+                    msg_sender,
+                    _task_handle: task_handle,
+                };
+                // Released before waiting for the root stage, so that the query can be aborted
+                // while its stages are being scheduled.
+                drop(state);
+
                 let root_stage = root_stage_receiver
                     .await
                     .map_err(|e| anyhow!("Starting query execution failed: {:?}", e))??;
@@ -185,11 +202,6 @@ impl QueryExecution {
                     self.query.query_id
                 );
 
-                *state = QueryState::Running {
-                    _msg_sender: msg_sender,
-                    _task_handle: task_handle,
-                };
-
                 Ok(root_stage)
             }
             s => {
@@ -201,9 +213,25 @@ impl QueryExecution {
     }
 
     /// Cancel execution of this query.
-    #[expect(clippy::unused_async)]
-    pub async fn abort(&mut self) -> SchedulerResult<()> {
-        todo!()
+    pub async fn abort(&self) -> SchedulerResult<()> {
+        let mut state = self.state.write().await;
+        match &*state {
+            QueryState::Pending { .. } => {
+                *state = QueryState::Failed;
+                Ok(())
+            }
+            QueryState::Running { msg_sender, .. } => {
+                msg_sender.send(QueryMessage::Stop).await.map_err(|e| {
+                    anyhow!(
+                        "Failed to abort query {:?}, reason: {:?}",
+                        self.query.query_id,
+                        e
+                    )
+                    .into()
+                })
+            }
+            QueryState::Failed | QueryState::Completed => Ok(()),
+        }
     }
 }
 
@@ -285,7 +313,18 @@ impl QueryRunner {
                             );
                         }
                     }
-                    // TODO: We should can cancel all scheduled stages here.
+                    self.stop_stages().await;
+                    return Ok(());
+                }
+                QueryMessage::Stop => {
+                    info!("Query {:?} is aborted.", self.query.query_id);
+                    self.stop_stages().await;
+                    if let Some(sender) = mem::take(&mut self.root_stage_sender) {
+                        if let Err(e) = sender.send(Err(SchedulerError::QueryCancelled)) {
+                            warn!("Query execution dropped: {:?}", e);
+                        }
+                    }
+                    return Ok(());
                 }
                 rest => {
                     return Err(SchedulerError::NotImplemented(
@@ -300,6 +339,18 @@ impl QueryRunner {
         Ok(())
     }
 
+    /// Stops all the stages and aborts their tasks.
+    async fn stop_stages(&self) {
+        for (stage_id, stage_execution) in self.stage_executions.iter() {
+            if let Err(e) = stage_execution.stop().await {
+                warn!(
+                    "Failed to stop stage {:?}-{:?}, reason: {:?}",
+                    self.query.query_id, stage_id, e
+                );
+            }
+        }
+    }
+
     #[expect(clippy::unused_async)]
     async fn send_root_stage_info(&mut self) {
         let root_task_status = self.stage_executions[&self.query.root_stage_id()]
@@ -347,7 +398,7 @@ impl QueryRunner {
 }
 
 #[cfg(test)]
-mod tests {
+pub(super) mod tests {
     use std::rc::Rc;
     use std::sync::Arc;
 
@@ -384,13 +435,15 @@ mod tests {
                 MockFrontendMetaClient {},
             ))),
             compute_client_pool,
+            (0, 0),
+            None,
         );
         let err = query_execution.start().await;
         println!("err: {:?}", err);
         // assert!(query_execution.start().await.is_err());
     }
 
-    async fn create_query() -> Query {
+    pub(crate) async fn create_query() -> Query {
         // Construct a Hash Join with Exchange node.
         // Logical plan:
         //
@@ -12,11 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
 
 use futures::StreamExt;
 use futures_async_stream::{for_await, try_stream};
-use log::debug;
+use log::{debug, warn};
+use pgwire::pg_server::SessionId;
 use rand::seq::SliceRandom;
 use risingwave_common::array::DataChunk;
 use risingwave_common::error::RwError;
@@ -52,6 +55,35 @@ pub struct QueryManager {
     worker_node_manager: WorkerNodeManagerRef,
     hummock_snapshot_manager: HummockSnapshotManagerRef,
     compute_client_pool: ComputeClientPoolRef,
+    /// The running queries, which are removed when their results are dropped.
+    query_executions_map: Arc<Mutex<HashMap<QueryId, Arc<QueryExecution>>>>,
+}
+
+/// Removes the query from the running ones of the [`QueryManager`] on drop, and aborts it if its
+/// results are not fully fetched, e.g. the query is cancelled or timed out.
+struct QueryExecutionGuard {
+    query_id: QueryId,
+    query_execution: Arc<QueryExecution>,
+    query_executions_map: Arc<Mutex<HashMap<QueryId, Arc<QueryExecution>>>>,
+    finished: bool,
+}
+
+impl Drop for QueryExecutionGuard {
+    fn drop(&mut self) {
+        self.query_executions_map
+            .lock()
+            .unwrap()
+            .remove(&self.query_id);
+        if !self.finished {
+            let query_id = self.query_id.clone();
+            let query_execution = self.query_execution.clone();
+            tokio::spawn(async move {
+                if let Err(e) = query_execution.abort().await {
+                    warn!("Failed to abort query {:?}, reason: {:?}", query_id, e);
+                }
+            });
+        }
+    }
 }
 
 impl QueryManager {
@@ -64,6 +96,26 @@ impl QueryManager {
             worker_node_manager,
             hummock_snapshot_manager,
             compute_client_pool,
+            query_executions_map: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Aborts the running queries of the session.
+    pub fn cancel_queries_in_session(&self, session_id: SessionId) {
+        let query_executions = self
+            .query_executions_map
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, query_execution)| query_execution.session_id == session_id)
+            .map(|(query_id, query_execution)| (query_id.clone(), query_execution.clone()))
+            .collect::<Vec<_>>();
+        for (query_id, query_execution) in query_executions {
+            tokio::spawn(async move {
+                if let Err(e) = query_execution.abort().await {
+                    warn!("Failed to abort query {:?}, reason: {:?}", query_id, e);
+                }
+            });
         }
     }
 
@@ -124,6 +176,7 @@ impl QueryManager {
                 mode: DistributionMode::Single as i32,
                 ..Default::default()
             }),
+            collect_executor_stats: false,
         };
         let creat_task_resp = compute_client
             .create_task(task_id.clone(), plan, epoch)
@@ -151,7 +204,7 @@ impl QueryManager {
 
     pub async fn schedule(
         &self,
-        _context: ExecutionContextRef,
+        context: ExecutionContextRef,
         query: Query,
     ) -> SchedulerResult<impl DataChunkStream> {
         let query_id = query.query_id().clone();
@@ -160,13 +213,25 @@ impl QueryManager {
             .get_epoch(query_id.clone())
             .await?;
 
-        let query_execution = QueryExecution::new(
+        let query_execution = Arc::new(QueryExecution::new(
             query,
             epoch,
             self.worker_node_manager.clone(),
             self.hummock_snapshot_manager.clone(),
             self.compute_client_pool.clone(),
-        );
+            context.session().id(),
+            context.executor_stats().cloned(),
+        ));
+        self.query_executions_map
+            .lock()
+            .unwrap()
+            .insert(query_id.clone(), query_execution.clone());
+        let guard = QueryExecutionGuard {
+            query_id: query_id.clone(),
+            query_execution: query_execution.clone(),
+            query_executions_map: self.query_executions_map.clone(),
+            finished: false,
+        };
 
         let query_result_fetcher = match query_execution.start().await {
             Ok(query_result_fetcher) => query_result_fetcher,
@@ -178,7 +243,7 @@ impl QueryManager {
             }
         };
 
-        Ok(query_result_fetcher.run())
+        Ok(query_result_fetcher.run_with_guard(guard))
     }
 }
 
@@ -214,6 +279,16 @@ impl QueryResultFetcher {
             yield DataChunk::from_protobuf(response?.get_record_batch()?)?;
         }
     }
+
+    /// Keeps the query as a running one until the results are dropped.
+    #[try_stream(ok = DataChunk, error = RwError)]
+    async fn run_with_guard(self, mut guard: QueryExecutionGuard) {
+        #[for_await]
+        for chunk in self.run() {
+            yield chunk?;
+        }
+        guard.finished = true;
+    }
 }
 
 impl Debug for QueryResultFetcher {
@@ -225,3 +300,53 @@ impl Debug for QueryResultFetcher {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use risingwave_rpc_client::ComputeClientPool;
+
+    use super::QueryExecutionGuard;
+    use crate::scheduler::distributed::query::tests::create_query;
+    use crate::scheduler::distributed::QueryExecution;
+    use crate::scheduler::worker_node_manager::WorkerNodeManager;
+    use crate::scheduler::HummockSnapshotManager;
+    use crate::test_utils::MockFrontendMetaClient;
+
+    #[tokio::test]
+    async fn test_abort_query_on_results_dropped() {
+        let query = create_query().await;
+        let query_id = query.query_id().clone();
+        let query_execution = Arc::new(QueryExecution::new(
+            query,
+            100,
+            Arc::new(WorkerNodeManager::mock(vec![])),
+            Arc::new(HummockSnapshotManager::new(Arc::new(
+                MockFrontendMetaClient {},
+            ))),
+            Arc::new(ComputeClientPool::new(1024)),
+            (0, 0),
+            None,
+        ));
+        let query_executions_map = Arc::new(Mutex::new(HashMap::new()));
+        query_executions_map
+            .lock()
+            .unwrap()
+            .insert(query_id.clone(), query_execution.clone());
+
+        // The results are dropped before they are fully fetched, e.g. on statement timeout.
+        drop(QueryExecutionGuard {
+            query_id,
+            query_execution: query_execution.clone(),
+            query_executions_map: query_executions_map.clone(),
+            finished: false,
+        });
+        assert!(query_executions_map.lock().unwrap().is_empty());
+
+        // Wait for the abort spawned by the guard, after which the query can no longer start.
+        tokio::task::yield_now().await;
+        assert!(query_execution.start().await.is_err());
+    }
+}
@@ -18,6 +18,7 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use arc_swap::ArcSwap;
+use futures::future::join_all;
 use futures::{stream, StreamExt};
 use futures_async_stream::for_await;
 use itertools::Itertools;
@@ -35,7 +36,7 @@ use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use tonic::Streaming;
-use tracing::error;
+use tracing::{error, warn};
 use uuid::Uuid;
 use StageEvent::Failed;
 
@@ -47,7 +48,7 @@ use crate::scheduler::plan_fragmenter::{
 };
 use crate::scheduler::worker_node_manager::WorkerNodeManagerRef;
 use crate::scheduler::SchedulerError::{Internal, RpcError};
-use crate::scheduler::{SchedulerError, SchedulerResult};
+use crate::scheduler::{QueryExecutorStatsRef, SchedulerError, SchedulerResult};
 
 const TASK_SCHEDULING_PARALLELISM: usize = 10;
 
@@ -105,6 +106,8 @@ pub struct StageExecution {
     /// We use `Vec` here since children's size is usually small.
     children: Vec<Arc<StageExecution>>,
     compute_client_pool: ComputeClientPoolRef,
+    /// Set for `EXPLAIN ANALYZE`.
+    executor_stats: Option<QueryExecutorStatsRef>,
 }
 
 struct StageRunner {
@@ -118,6 +121,7 @@ struct StageRunner {
     msg_sender: Sender<QueryMessage>,
     children: Vec<Arc<StageExecution>>,
     compute_client_pool: ComputeClientPoolRef,
+    executor_stats: Option<QueryExecutorStatsRef>,
 }
 
 impl TaskStatusHolder {
@@ -145,6 +149,7 @@ impl StageExecution {
         msg_sender: Sender<QueryMessage>,
         children: Vec<Arc<StageExecution>>,
         compute_client_pool: ComputeClientPoolRef,
+        executor_stats: Option<QueryExecutorStatsRef>,
     ) -> Self {
         let tasks = (0..stage.parallelism)
             .into_iter()
@@ -159,6 +164,7 @@ impl StageExecution {
             msg_sender,
             children,
             compute_client_pool,
+            executor_stats,
         }
     }
 
@@ -178,6 +184,7 @@ impl StageExecution {
                     children: self.children.clone(),
                     state: self.state.clone(),
                     compute_client_pool: self.compute_client_pool.clone(),
+                    executor_stats: self.executor_stats.clone(),
                 };
                 let handle = spawn(async move {
                     if let Err(e) = runner.run().await {
@@ -205,9 +212,24 @@ impl StageExecution {
         }
     }
 
-    #[expect(clippy::unused_async)]
+    /// Stops scheduling the stage, and aborts its tasks that have been scheduled.
     pub async fn stop(&self) -> SchedulerResult<()> {
-        todo!()
+        {
+            let mut s = self.state.write().await;
+            match mem::replace(&mut *s, StageState::Failed) {
+                StageState::Pending => {}
+                StageState::Started { handle, .. }
+                | StageState::Running {
+                    _handle: handle, ..
+                } => handle.abort(),
+                state @ (StageState::Completed | StageState::Failed) => {
+                    *s = state;
+                    return Ok(());
+                }
+            }
+        }
+        abort_tasks(&self.stage, &self.tasks, &self.compute_client_pool).await;
+        Ok(())
     }
 
     pub async fn is_scheduled(&self) -> bool {
@@ -248,14 +270,45 @@ impl StageExecution {
     }
 }
 
+/// Aborts the tasks of the stage that have been scheduled to the compute nodes.
+async fn abort_tasks(
+    stage: &QueryStageRef,
+    tasks: &HashMap<TaskId, TaskStatusHolder>,
+    compute_client_pool: &ComputeClientPoolRef,
+) {
+    let futures = tasks.iter().filter_map(|(task_id, status_holder)| {
+        let location = status_holder.get_status().location.clone()?;
+        let task_id = TaskIdProst {
+            query_id: stage.query_id.id.clone(),
+            stage_id: stage.id,
+            task_id: *task_id,
+        };
+        Some(async move {
+            let result = match compute_client_pool.get_by_addr((&location).into()).await {
+                Ok(client) => client.abort(task_id.clone()).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Failed to abort task {:?}, reason: {:?}", task_id, e);
+            }
+        })
+    });
+    join_all(futures).await;
+}
+
 impl StageRunner {
     async fn run(self) -> SchedulerResult<()> {
-        if let Err(e) = self.schedule_tasks().await {
+        let result = self.schedule_tasks().await;
+        // Don't keep `EXPLAIN ANALYZE` waiting for the stage, even if it failed.
+        if let Some(executor_stats) = &self.executor_stats {
+            executor_stats.finish_stage();
+        }
+        if let Err(e) = result {
             error!(
                 "Stage {:?}-{:?} failed to schedule tasks, error: {:?}",
                 self.stage.query_id, self.stage.id, e
             );
-            // TODO: We should cancel all scheduled tasks
+            abort_tasks(&self.stage, &self.tasks, &self.compute_client_pool).await;
             self.send_event(QueryMessage::Stage(Failed {
                 id: self.stage.id,
                 reason: e,
@@ -337,6 +390,9 @@ impl StageRunner {
             // The status can be Running, Finished, Failed etc. This stream contains status from
             // different tasks.
             let status = status_res.map_err(|e| RpcError(e.into()))?;
+            if let Some(executor_stats) = &self.executor_stats {
+                executor_stats.record(&status.task_info.as_ref().unwrap().executor_stats);
+            }
             use risingwave_pb::task_service::task_info::TaskStatus as TaskStatusProst;
             if TaskStatusProst::from_i32(status.task_info.as_ref().unwrap().task_status)
                 == Some(TaskStatusProst::Running)
@@ -375,6 +431,8 @@ impl StageRunner {
                         _handle: handle,
                     };
                 }
+                // The stage is stopped before this runner is aborted.
+                StageState::Failed => return Ok(()),
                 _ => unreachable!(),
             }
         }
@@ -424,6 +482,7 @@ impl StageRunner {
         PlanFragment {
             root: Some(plan_node_prost),
             exchange_info: Some(exchange_info),
+            collect_executor_stats: self.executor_stats.is_some(),
         }
     }
 
@@ -451,6 +510,7 @@ impl StageRunner {
                             children: vec![],
                             // TODO: Generate meaningful identify
                             identity: Uuid::new_v4().to_string(),
+                            operator_id: execution_plan_node.plan_node_id.0,
                             node_body: Some(NodeBody::Exchange(ExchangeNode {
                                 sources: exchange_sources,
                                 input_schema: execution_plan_node.schema.clone(),
@@ -462,6 +522,7 @@ impl StageRunner {
                             children: vec![],
                             // TODO: Generate meaningful identify
                             identity: Uuid::new_v4().to_string(),
+                            operator_id: execution_plan_node.plan_node_id.0,
                             node_body: Some(NodeBody::MergeSortExchange(MergeSortExchangeNode {
                                 exchange: Some(ExchangeNode {
                                     sources: exchange_sources,
@@ -486,6 +547,7 @@ impl StageRunner {
                     children: vec![],
                     // TODO: Generate meaningful identify
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: execution_plan_node.plan_node_id.0,
                     node_body: Some(NodeBody::RowSeqScan(scan_node)),
                 }
             }
@@ -500,6 +562,7 @@ impl StageRunner {
                     children,
                     // TODO: Generate meaningful identify
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: execution_plan_node.plan_node_id.0,
                     node_body: Some(execution_plan_node.node.clone()),
                 }
             }
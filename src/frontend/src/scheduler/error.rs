@@ -32,6 +32,12 @@ pub enum SchedulerError {
     #[error("Empty workers found")]
     EmptyWorkerNodes,
 
+    #[error("canceling statement due to user request")]
+    QueryCancelled,
+
+    #[error("canceling statement due to statement timeout")]
+    StatementTimeout,
+
     #[error(transparent)]
     Internal(#[from] anyhow::Error),
 }
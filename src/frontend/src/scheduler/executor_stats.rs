@@ -0,0 +1,142 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use risingwave_pb::task_service::ExecutorStats as ProstExecutorStats;
+use tokio::sync::Notify;
+
+/// The runtime statistics of an operator, summed up over the tasks running it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OperatorStats {
+    pub output_rows: u64,
+    pub output_chunks: u64,
+    /// The longest time spent in the operator and its inputs by a task.
+    pub elapsed: Duration,
+    pub peak_memory_bytes: u64,
+}
+
+impl OperatorStats {
+    fn merge(&mut self, stats: &ProstExecutorStats) {
+        self.output_rows += stats.output_rows;
+        self.output_chunks += stats.output_chunks;
+        self.elapsed = self.elapsed.max(Duration::from_nanos(stats.elapsed_ns));
+        self.peak_memory_bytes += stats.peak_memory_bytes;
+    }
+}
+
+impl Display for OperatorStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "actual rows: {}, chunks: {}, time: {:.3} ms, peak memory: {} B",
+            self.output_rows,
+            self.output_chunks,
+            self.elapsed.as_secs_f64() * 1000.0,
+            self.peak_memory_bytes
+        )
+    }
+}
+
+pub type QueryExecutorStatsRef = Arc<QueryExecutorStats>;
+
+/// The runtime statistics of the operators of a distributed query for `EXPLAIN ANALYZE`, which are
+/// reported by the tasks when they finish.
+pub struct QueryExecutorStats {
+    /// By the id of the plan node of the operator.
+    operators: Mutex<HashMap<i32, OperatorStats>>,
+    /// The stages whose tasks are not all done.
+    running_stages: AtomicUsize,
+    finished: Notify,
+}
+
+impl QueryExecutorStats {
+    pub fn new(stage_count: usize) -> Self {
+        Self {
+            operators: Mutex::new(HashMap::new()),
+            running_stages: AtomicUsize::new(stage_count),
+            finished: Notify::new(),
+        }
+    }
+
+    pub fn record(&self, stats: &[ProstExecutorStats]) {
+        let mut operators = self.operators.lock();
+        for stats in stats {
+            operators.entry(stats.operator_id).or_default().merge(stats);
+        }
+    }
+
+    /// Called when the status streams of all tasks of a stage end.
+    pub fn finish_stage(&self) {
+        if self.running_stages.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Stores a permit if the query is not waited yet.
+            self.finished.notify_one();
+        }
+    }
+
+    /// Waits until all stages have reported their statistics.
+    pub async fn wait_finished(&self) {
+        if self.running_stages.load(Ordering::Acquire) > 0 {
+            self.finished.notified().await;
+        }
+    }
+
+    pub fn get(&self, operator_id: i32) -> Option<OperatorStats> {
+        self.operators.lock().get(&operator_id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_query_executor_stats() {
+        let stats = QueryExecutorStats::new(2);
+        let task_stats = |rows, elapsed_ns, peak_memory_bytes| ProstExecutorStats {
+            operator_id: 1,
+            identity: "HashAggExecutor".to_string(),
+            output_rows: rows,
+            output_chunks: 1,
+            elapsed_ns,
+            peak_memory_bytes,
+        };
+        stats.record(&[task_stats(10, 2_000_000, 100)]);
+        stats.finish_stage();
+        stats.record(&[task_stats(5, 1_000_000, 50)]);
+        stats.finish_stage();
+        stats.wait_finished().await;
+
+        let operator = stats.get(1).unwrap();
+        assert_eq!(
+            operator,
+            OperatorStats {
+                output_rows: 15,
+                output_chunks: 2,
+                elapsed: Duration::from_millis(2),
+                peak_memory_bytes: 150,
+            }
+        );
+        assert_eq!(
+            operator.to_string(),
+            "actual rows: 15, chunks: 2, time: 2.000 ms, peak memory: 150 B"
+        );
+        assert!(stats.get(2).is_none());
+    }
+}
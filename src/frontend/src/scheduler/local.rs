@@ -151,6 +151,7 @@ impl LocalQueryExecution {
             // to really get the output of computation, which is single distribution
             // but we do not need to explicitly specify this.
             exchange_info: None,
+            collect_executor_stats: false,
         })
     }
 
@@ -207,6 +208,7 @@ impl LocalQueryExecution {
                                 mode: DistributionMode::Single as i32,
                                 ..Default::default()
                             }),
+                            collect_executor_stats: false,
                         };
                         let local_execute_plan =  LocalExecutePlan {
                             plan: Some(second_stage_plan_fragment),
@@ -237,6 +239,7 @@ impl LocalQueryExecution {
                             mode: DistributionMode::Single as i32,
                             ..Default::default()
                         }),
+                        collect_executor_stats: false,
                     };
 
                     let local_execute_plan = LocalExecutePlan {
@@ -277,6 +280,7 @@ impl LocalQueryExecution {
                     /// there is no children any more.
                     children: vec![],
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: execution_plan_node.plan_node_id.0,
                     node_body: Some(node_body),
                 })
             }
@@ -297,6 +301,7 @@ impl LocalQueryExecution {
                     children: vec![],
                     // TODO: Generate meaningful identify
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: execution_plan_node.plan_node_id.0,
                     node_body: Some(node_body),
                 })
             }
@@ -317,6 +322,7 @@ impl LocalQueryExecution {
                 Ok(PlanNodeProst {
                     children: vec![left_child],
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: execution_plan_node.plan_node_id.0,
                     node_body: Some(node_body),
                 })
             }
@@ -331,6 +337,7 @@ impl LocalQueryExecution {
                     children,
                     // TODO: Generate meaningful identify
                     identity: Uuid::new_v4().to_string(),
+                    operator_id: execution_plan_node.plan_node_id.0,
                     node_body: Some(execution_plan_node.node.clone()),
                 })
             }
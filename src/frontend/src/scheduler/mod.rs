@@ -24,6 +24,8 @@ use crate::session::SessionImpl;
 
 mod distributed;
 pub use distributed::QueryManager;
+mod executor_stats;
+pub use executor_stats::*;
 mod hummock_snapshot_manager;
 pub use hummock_snapshot_manager::*;
 mod plan_fragmenter;
@@ -42,16 +44,31 @@ pub trait DataChunkStream = Stream<Item = Result<DataChunk>>;
 /// Context for mpp query execution.
 pub struct ExecutionContext {
     session: Arc<SessionImpl>,
+    /// Set to collect the runtime statistics of the operators for `EXPLAIN ANALYZE`.
+    executor_stats: Option<QueryExecutorStatsRef>,
 }
 
 pub type ExecutionContextRef = Arc<ExecutionContext>;
 
 impl ExecutionContext {
     pub fn new(session: Arc<SessionImpl>) -> Self {
-        Self { session }
+        Self {
+            session,
+            executor_stats: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_executor_stats(mut self, executor_stats: QueryExecutorStatsRef) -> Self {
+        self.executor_stats = Some(executor_stats);
+        self
     }
 
     pub fn session(&self) -> &SessionImpl {
         &self.session
     }
+
+    pub fn executor_stats(&self) -> Option<&QueryExecutorStatsRef> {
+        self.executor_stats.as_ref()
+    }
 }
@@ -15,7 +15,9 @@
 use std::sync::Arc;
 
 use risingwave_batch::executor::BatchMetrics;
-use risingwave_batch::task::{BatchTaskContext, TaskOutput, TaskOutputId};
+use risingwave_batch::task::{
+    BatchTaskContext, MemoryContext, MemoryContextRef, TaskId, TaskOutput, TaskOutputId,
+};
 use risingwave_common::catalog::SysCatalogReaderRef;
 use risingwave_common::error::Result;
 use risingwave_common::util::addr::{is_local_address, HostAddr};
@@ -67,4 +69,9 @@ impl BatchTaskContext for FrontendBatchTaskContext {
     fn stats(&self) -> Arc<BatchMetrics> {
         todo!()
     }
+
+    /// The local execution in frontend is not bounded, as it serves the small point queries.
+    fn memory_context(&self, _task_id: &TaskId) -> MemoryContextRef {
+        MemoryContext::unlimited()
+    }
 }
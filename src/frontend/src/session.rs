@@ -24,7 +24,7 @@ use std::time::Duration;
 use parking_lot::{RwLock, RwLockReadGuard};
 use pgwire::pg_field_descriptor::{PgFieldDescriptor, TypeOid};
 use pgwire::pg_response::PgResponse;
-use pgwire::pg_server::{BoxedError, Session, SessionManager, UserAuthenticator};
+use pgwire::pg_server::{BoxedError, Session, SessionId, SessionManager, UserAuthenticator};
 use rand::RngCore;
 #[cfg(test)]
 use risingwave_common::catalog::{
@@ -37,11 +37,11 @@ use risingwave_common::util::addr::HostAddr;
 use risingwave_common_service::observer_manager::ObserverManager;
 use risingwave_pb::common::WorkerType;
 use risingwave_pb::user::auth_info::EncryptionType;
-use risingwave_rpc_client::{ComputeClientPool, MetaClient};
+use risingwave_rpc_client::{ComputeClientPool, MetaClient, StreamClientPool, StreamClientPoolRef};
 use risingwave_sqlparser::ast::{ShowObject, Statement};
 use risingwave_sqlparser::parser::Parser;
 use tokio::sync::oneshot::Sender;
-use tokio::sync::watch;
+use tokio::sync::{oneshot, watch};
 use tokio::task::JoinHandle;
 
 use crate::binder::Binder;
@@ -200,6 +200,7 @@ pub struct FrontendEnv {
     query_manager: QueryManager,
     hummock_snapshot_manager: HummockSnapshotManagerRef,
     server_addr: HostAddr,
+    stream_client_pool: StreamClientPoolRef,
 }
 
 impl FrontendEnv {
@@ -239,6 +240,7 @@ impl FrontendEnv {
             query_manager,
             hummock_snapshot_manager,
             server_addr,
+            stream_client_pool: Arc::new(StreamClientPool::new(u64::MAX)),
         }
     }
 
@@ -324,6 +326,7 @@ impl FrontendEnv {
                 query_manager,
                 hummock_snapshot_manager,
                 server_addr: frontend_address,
+                stream_client_pool: Arc::new(StreamClientPool::new(u64::MAX)),
             },
             observer_join_handle,
             heartbeat_join_handle,
@@ -375,6 +378,10 @@ impl FrontendEnv {
         &self.query_manager
     }
 
+    pub fn stream_client_pool(&self) -> &StreamClientPoolRef {
+        &self.stream_client_pool
+    }
+
     pub fn hummock_snapshot_manager(&self) -> &HummockSnapshotManagerRef {
         &self.hummock_snapshot_manager
     }
@@ -407,6 +414,10 @@ pub struct SessionImpl {
     user_authenticator: UserAuthenticator,
     /// Stores the value of configurations.
     config_map: RwLock<ConfigMap>,
+    /// Identifies the session in the cancel requests.
+    id: SessionId,
+    /// Notifies the running query of the session to be cancelled.
+    current_query_cancel_flag: Mutex<Option<Sender<()>>>,
 }
 
 impl SessionImpl {
@@ -414,12 +425,15 @@ impl SessionImpl {
         env: FrontendEnv,
         auth_context: Arc<AuthContext>,
         user_authenticator: UserAuthenticator,
+        id: SessionId,
     ) -> Self {
         Self {
             env,
             auth_context,
             user_authenticator,
             config_map: RwLock::new(Default::default()),
+            id,
+            current_query_cancel_flag: Mutex::new(None),
         }
     }
 
@@ -434,6 +448,8 @@ impl SessionImpl {
             )),
             user_authenticator: UserAuthenticator::None,
             config_map: Default::default(),
+            id: (0, 0),
+            current_query_cancel_flag: Mutex::new(None),
         }
     }
 
@@ -464,10 +480,37 @@ impl SessionImpl {
     pub fn set_config(&self, key: &str, value: &str) -> Result<()> {
         self.config_map.write().set(key, value)
     }
+
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    /// Called before running a query, and the returned receiver is notified when the query is
+    /// cancelled.
+    pub fn reset_cancel_query_flag(&self) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        *self.current_query_cancel_flag.lock().unwrap() = Some(sender);
+        receiver
+    }
+
+    /// Cancels the running query of the session, including the distributed tasks of it on the
+    /// compute nodes.
+    pub fn cancel_current_query(&self) {
+        // Aborts the query before waking up its handler, which drops the results and removes the
+        // query from the query manager.
+        self.env.query_manager().cancel_queries_in_session(self.id);
+        if let Some(sender) = self.current_query_cancel_flag.lock().unwrap().take() {
+            // The query may have finished.
+            let _ = sender.send(());
+        }
+    }
 }
 
 pub struct SessionManagerImpl {
     env: FrontendEnv,
+    /// The sessions of the open connections, to look up by the cancel requests.
+    sessions_map: Mutex<HashMap<SessionId, Arc<SessionImpl>>>,
+    next_process_id: AtomicI32,
     _observer_join_handle: JoinHandle<()>,
     _heartbeat_join_handle: JoinHandle<()>,
     _heartbeat_shutdown_sender: Sender<()>,
@@ -523,7 +566,13 @@ impl SessionManager for SessionManagerImpl {
                 }
             };
 
-            Ok(SessionImpl::new(
+            // The secret key is random, so that other clients can't cancel the queries of the
+            // session by guessing its key.
+            let session_id = (
+                self.next_process_id.fetch_add(1, Ordering::Relaxed),
+                rand::thread_rng().next_u32() as i32,
+            );
+            let session: Arc<SessionImpl> = SessionImpl::new(
                 self.env.clone(),
                 Arc::new(AuthContext::new(
                     database.to_string(),
@@ -531,8 +580,14 @@ impl SessionManager for SessionManagerImpl {
                     user.id,
                 )),
                 user_authenticator,
+                session_id,
             )
-            .into())
+            .into();
+            self.sessions_map
+                .lock()
+                .unwrap()
+                .insert(session_id, session.clone());
+            Ok(session)
         } else {
             Err(Box::new(Error::new(
                 ErrorKind::InvalidInput,
@@ -540,6 +595,18 @@ impl SessionManager for SessionManagerImpl {
             )))
         }
     }
+
+    fn cancel_queries_in_session(&self, session_id: SessionId) {
+        let session = self.sessions_map.lock().unwrap().get(&session_id).cloned();
+        match session {
+            Some(session) => session.cancel_current_query(),
+            None => tracing::info!("Session {:?} to cancel doesn't exist", session_id),
+        }
+    }
+
+    fn end_session(&self, session: &Self::Session) {
+        self.sessions_map.lock().unwrap().remove(&session.id());
+    }
 }
 
 impl SessionManagerImpl {
@@ -548,6 +615,8 @@ impl SessionManagerImpl {
             FrontendEnv::init(opts).await?;
         Ok(Self {
             env,
+            sessions_map: Mutex::new(HashMap::new()),
+            next_process_id: AtomicI32::new(1),
             _observer_join_handle: join_handle,
             _heartbeat_join_handle: heartbeat_join_handle,
             _heartbeat_shutdown_sender: heartbeat_shutdown_sender,
@@ -658,6 +727,10 @@ impl Session for SessionImpl {
     fn user_authenticator(&self) -> &UserAuthenticator {
         &self.user_authenticator
     }
+
+    fn id(&self) -> SessionId {
+        self.id
+    }
 }
 
 /// Returns row description of the statement
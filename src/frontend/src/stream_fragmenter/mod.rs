@@ -343,6 +343,12 @@ impl StreamFragmenter {
                 append_only_top_n_node.table_id_h = state.gen_table_id();
             }
 
+            NodeBody::GroupTopN(group_top_n_node) => {
+                if let Some(table) = &mut group_top_n_node.table {
+                    table.id = state.gen_table_id();
+                }
+            }
+
             NodeBody::DynamicFilter(dynamic_filter_node) => {
                 if let Some(left_table) = &mut dynamic_filter_node.left_table {
                     left_table.id = state.gen_table_id();
@@ -352,6 +358,16 @@ impl StreamFragmenter {
                 }
             }
 
+            NodeBody::Sink(sink_node) => {
+                sink_node.state_table_id = state.gen_table_id();
+            }
+
+            NodeBody::Source(source_node) => {
+                if let Some(dead_letter_table) = &mut source_node.dead_letter_table {
+                    dead_letter_table.id = state.gen_table_id();
+                }
+            }
+
             _ => {}
         }
     }
@@ -19,7 +19,7 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 use pgwire::pg_response::PgResponse;
-use pgwire::pg_server::{BoxedError, Session, SessionManager, UserAuthenticator};
+use pgwire::pg_server::{BoxedError, Session, SessionId, SessionManager, UserAuthenticator};
 use risingwave_common::catalog::{
     IndexId, TableId, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, DEFAULT_SUPER_USER,
     DEFAULT_SUPER_USER_ID, NON_RESERVED_USER_ID, PG_CATALOG_SCHEMA_NAME,
@@ -43,7 +43,7 @@ use tempfile::{Builder, NamedTempFile};
 use crate::binder::Binder;
 use crate::catalog::catalog_service::CatalogWriter;
 use crate::catalog::root_catalog::Catalog;
-use crate::catalog::{DatabaseId, SchemaId};
+use crate::catalog::{DatabaseId, SchemaId, TableStatistics};
 use crate::meta_client::FrontendMetaClient;
 use crate::optimizer::PlanRef;
 use crate::planner::Planner;
@@ -69,6 +69,10 @@ impl SessionManager for LocalFrontend {
     ) -> std::result::Result<Arc<Self::Session>, BoxedError> {
         Ok(self.session_ref())
     }
+
+    fn cancel_queries_in_session(&self, _session_id: SessionId) {}
+
+    fn end_session(&self, _session: &Self::Session) {}
 }
 
 impl LocalFrontend {
@@ -138,6 +142,8 @@ impl LocalFrontend {
                 DEFAULT_SUPER_USER_ID,
             )),
             UserAuthenticator::None,
+            // Local Frontend use a non-sense id.
+            (0, 0),
         ))
     }
 
@@ -151,6 +157,8 @@ impl LocalFrontend {
             self.env.clone(),
             Arc::new(AuthContext::new(database, user_name, user_id)),
             UserAuthenticator::None,
+            // Local Frontend use a non-sense id.
+            (0, 0),
         ))
     }
 }
@@ -330,6 +338,31 @@ impl CatalogWriter for MockCatalogWriter {
         self.catalog.write().drop_schema(database_id, schema_id);
         Ok(())
     }
+
+    async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: TableStatistics,
+    ) -> Result<()> {
+        let &schema_id = self
+            .table_id_to_schema_id
+            .read()
+            .get(&table_id.table_id)
+            .unwrap();
+        let database_id = self.get_database_id_by_schema(schema_id);
+        let mut table = {
+            let catalog_reader = self.catalog.read();
+            let schema_catalog = catalog_reader
+                .get_schema_by_id(&database_id, &schema_id)
+                .unwrap();
+            schema_catalog.get_table_by_id(&table_id).unwrap().clone()
+        };
+        table.statistics = Some(statistics);
+        self.catalog
+            .write()
+            .update_table(&table.to_prost(schema_id, database_id));
+        Ok(())
+    }
 }
 
 impl MockCatalogWriter {
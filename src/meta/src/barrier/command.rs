@@ -27,7 +27,7 @@ use risingwave_pb::stream_plan::update_mutation::{
 };
 use risingwave_pb::stream_plan::{
     ActorMapping, AddMutation, Dispatcher, PauseMutation, ResumeMutation, StopMutation,
-    UpdateMutation,
+    ThrottleMutation, UpdateMutation,
 };
 use risingwave_pb::stream_service::DropActorsRequest;
 use risingwave_rpc_client::StreamClientPoolRef;
@@ -113,6 +113,13 @@ impl Command {
         Self::Plain(Some(Mutation::Resume(ResumeMutation {})))
     }
 
+    /// Changes the rows per second of the sources of the actors, where 0 removes the limit.
+    pub fn throttle(actor_throttle: HashMap<ActorId, u32>) -> Self {
+        Self::Plain(Some(Mutation::Throttle(ThrottleMutation {
+            actor_throttle,
+        })))
+    }
+
     /// Changes to the actors to be sent or collected after this command is committed.
     pub fn changes(&self) -> CommandChanges {
         match self {
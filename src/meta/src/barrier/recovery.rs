@@ -114,6 +114,11 @@ where
             let prev_epoch = new_epoch;
             new_epoch = prev_epoch.next();
             // checkpoint, used as init barrier to initialize all executors.
+            //
+            // The meta doesn't track the prepared sink transactions. They're persisted in the
+            // state tables of the sinks along with the checkpoints, so each sink executor resolves
+            // its own on this barrier: the transactions of the checkpointed epochs are committed,
+            // and the other transactions prepared before the failure are aborted.
             let command_ctx = Arc::new(CommandContext::new(
                 self.fragment_manager.clone(),
                 self.env.stream_client_pool_ref(),
@@ -29,7 +29,7 @@ use tokio::sync::RwLock;
 
 use crate::barrier::Reschedule;
 use crate::manager::cluster::WorkerId;
-use crate::manager::{HashMappingManagerRef, MetaSrvEnv};
+use crate::manager::{HashMappingManagerRef, MetaSrvEnv, SourceId};
 use crate::model::{ActorId, FragmentId, MetadataModel, TableFragments, Transactional};
 use crate::storage::{MetaStore, Transaction};
 use crate::stream::record_table_vnode_mappings;
@@ -117,6 +117,36 @@ where
         Ok(())
     }
 
+    /// Sets the rate limit of the source in the actors reading it, so that it's kept after the
+    /// actors are rebuilt in recovery, and returns the actors.
+    pub async fn update_source_rate_limit(
+        &self,
+        source_id: SourceId,
+        rate_limit: u32,
+    ) -> MetaResult<Vec<ActorId>> {
+        let map = &mut self.core.write().await.table_fragments;
+
+        let mut transaction = Transaction::default();
+        let mut updated_table_fragments = vec![];
+        let mut actor_ids = vec![];
+        for table_fragment in map.values() {
+            let mut table_fragment = table_fragment.clone();
+            let source_actor_ids = table_fragment.update_source_rate_limit(source_id, rate_limit);
+            if !source_actor_ids.is_empty() {
+                table_fragment.upsert_in_transaction(&mut transaction)?;
+                actor_ids.extend(source_actor_ids);
+                updated_table_fragments.push(table_fragment);
+            }
+        }
+
+        self.meta_store.txn(transaction).await?;
+        for table_fragment in updated_table_fragments {
+            map.insert(table_fragment.table_id(), table_fragment);
+        }
+
+        Ok(actor_ids)
+    }
+
     pub async fn select_table_fragments_by_table_id(
         &self,
         table_id: &TableId,
@@ -31,7 +31,7 @@ use risingwave_common::catalog::{
 use risingwave_common::types::ParallelUnitId;
 use risingwave_common::{bail, ensure};
 use risingwave_pb::catalog::table::OptionalAssociatedSourceId;
-use risingwave_pb::catalog::{Database, Index, Schema, Sink, Source, Table};
+use risingwave_pb::catalog::{Database, Index, Schema, Sink, Source, Table, TableStatistics};
 use risingwave_pb::common::ParallelUnit;
 use risingwave_pb::meta::subscribe_response::{Info, Operation};
 use risingwave_pb::user::grant_privilege::{ActionWithGrantOption, Object};
@@ -556,6 +556,28 @@ where
         Ok(())
     }
 
+    /// Replaces the statistics of the table collected by `ANALYZE`.
+    pub async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: TableStatistics,
+    ) -> MetaResult<NotificationVersion> {
+        let _core = self.core.lock().await;
+        let table = Table::select(self.env.meta_store(), &table_id).await?;
+        if let Some(mut table) = table {
+            table.statistics = Some(statistics);
+            table.insert(self.env.meta_store()).await?;
+
+            let version = self
+                .broadcast_info_op(Operation::Update, Info::Table(table))
+                .await;
+
+            Ok(version)
+        } else {
+            bail!("table doesn't exist");
+        }
+    }
+
     pub async fn drop_source(&self, source_id: SourceId) -> MetaResult<NotificationVersion> {
         let core = &mut self.core.lock().await.database;
         let source = Source::select(self.env.meta_store(), &source_id).await?;
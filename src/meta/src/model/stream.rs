@@ -189,6 +189,43 @@ impl TableFragments {
         None
     }
 
+    /// Sets the rate limit of the source in the stream nodes, and returns the actors reading it.
+    pub fn update_source_rate_limit(
+        &mut self,
+        source_id: SourceId,
+        rate_limit: u32,
+    ) -> Vec<ActorId> {
+        let mut actor_ids = vec![];
+        for fragment in self.fragments.values_mut() {
+            for actor in &mut fragment.actors {
+                if let Some(node) = actor.nodes.as_mut() {
+                    if Self::update_stream_source_rate_limit(node, source_id, rate_limit) {
+                        actor_ids.push(actor.actor_id);
+                    }
+                }
+            }
+        }
+        actor_ids
+    }
+
+    fn update_stream_source_rate_limit(
+        stream_node: &mut StreamNode,
+        source_id: SourceId,
+        rate_limit: u32,
+    ) -> bool {
+        let mut found = false;
+        if let Some(NodeBody::Source(s)) = stream_node.node_body.as_mut() {
+            if s.source_type == SourceType::Source as i32 && s.table_id == source_id {
+                s.rate_limit = rate_limit;
+                found = true;
+            }
+        }
+        for child in &mut stream_node.input {
+            found |= Self::update_stream_source_rate_limit(child, source_id, rate_limit);
+        }
+        found
+    }
+
     /// Returns actors that contains Chain node.
     pub fn chain_actor_ids(&self) -> Vec<ActorId> {
         self.fragments
@@ -377,6 +377,25 @@ where
         }))
     }
 
+    async fn update_table_statistics(
+        &self,
+        request: Request<UpdateTableStatisticsRequest>,
+    ) -> Result<Response<UpdateTableStatisticsResponse>, Status> {
+        self.env.idle_manager().record_activity();
+
+        let req = request.into_inner();
+        let statistics = req.get_statistics().map_err(meta_error_to_tonic)?.clone();
+        let version = self
+            .catalog_manager
+            .update_table_statistics(req.table_id, statistics)
+            .await?;
+
+        Ok(Response::new(UpdateTableStatisticsResponse {
+            status: None,
+            version,
+        }))
+    }
+
     async fn create_materialized_source(
         &self,
         request: Request<CreateMaterializedSourceRequest>,
@@ -20,7 +20,7 @@ use risingwave_pb::common::WorkerType;
 use risingwave_pb::meta::scale_service_server::ScaleService;
 use risingwave_pb::meta::{
     GetClusterInfoRequest, GetClusterInfoResponse, PauseRequest, PauseResponse, ResumeRequest,
-    ResumeResponse,
+    ResumeResponse, ThrottleRequest, ThrottleResponse,
 };
 use risingwave_pb::source::{ConnectorSplit, ConnectorSplits};
 use tokio::sync::RwLock;
@@ -132,4 +132,35 @@ where
             stream_source_infos,
         }))
     }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn throttle(
+        &self,
+        request: Request<ThrottleRequest>,
+    ) -> Result<Response<ThrottleResponse>, Status> {
+        let ThrottleRequest {
+            source_id,
+            rate_limit,
+        } = request.into_inner();
+        let _ddl_lock = self.ddl_lock.write().await;
+        let actor_ids = self
+            .fragment_manager
+            .update_source_rate_limit(source_id, rate_limit)
+            .await?;
+        if actor_ids.is_empty() {
+            return Err(Status::invalid_argument(format!(
+                "source {} is not read by any streaming job",
+                source_id
+            )));
+        }
+        self.barrier_manager
+            .run_command(Command::throttle(
+                actor_ids
+                    .into_iter()
+                    .map(|actor_id| (actor_id, rate_limit))
+                    .collect(),
+            ))
+            .await?;
+        Ok(Response::new(ThrottleResponse {}))
+    }
 }
@@ -75,6 +75,11 @@ pub fn record_table_vnode_mappings(
             hash_mapping_manager
                 .set_fragment_state_table(fragment_id, node.right_table.as_ref().unwrap().id);
         }
+        NodeBody::Source(node) => {
+            if let Some(table) = &node.dead_letter_table {
+                hash_mapping_manager.set_fragment_state_table(fragment_id, table.id);
+            }
+        }
         _ => {}
     }
     let input_nodes = stream_node.get_input();
@@ -13,8 +13,9 @@
 // limitations under the License.
 
 use std::borrow::BorrowMut;
+use std::cmp::Reverse;
 use std::collections::hash_map::Entry;
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -359,51 +360,62 @@ pub(crate) fn fetch_source_fragments(
     }
 }
 
-// todo use min heap to optimize
+/// Returns the new assignment of the actors whose splits change, or `None` if nothing changes.
+///
+/// The splits that are no longer discovered are dropped, e.g. the Kinesis shards that are closed
+/// and have expired, which are listed until then so that they're fully consumed. The newly
+/// discovered splits are assigned one by one to the actor with the fewest splits. The existing
+/// splits are never moved, since the actors have their states.
 fn diff_splits(
-    mut prev_actor_splits: HashMap<ActorId, Vec<SplitImpl>>,
+    prev_actor_splits: HashMap<ActorId, Vec<SplitImpl>>,
     discovered_splits: &BTreeMap<String, SplitImpl>,
 ) -> Option<HashMap<ActorId, Vec<SplitImpl>>> {
+    if prev_actor_splits.is_empty() {
+        return None;
+    }
+
     let prev_split_ids: HashSet<_> = prev_actor_splits
         .values()
         .flat_map(|splits| splits.iter().map(SplitImpl::id))
         .collect();
 
-    if discovered_splits
-        .keys()
-        .all(|split_id| prev_split_ids.contains(split_id))
-    {
-        return None;
-    }
+    let mut changed_actors = HashSet::new();
+    let mut actor_splits: BTreeMap<_, _> = prev_actor_splits
+        .into_iter()
+        .map(|(actor_id, mut splits)| {
+            let split_num = splits.len();
+            splits.retain(|split| discovered_splits.contains_key(&split.id()));
+            if splits.len() != split_num {
+                changed_actors.insert(actor_id);
+            }
+            (actor_id, splits)
+        })
+        .collect();
 
-    let mut new_discovered_splits = HashSet::new();
+    // The actors with the fewest splits come first, and the ones with smaller ids among them.
+    let mut heap: BinaryHeap<_> = actor_splits
+        .iter()
+        .map(|(&actor_id, splits)| Reverse((splits.len(), actor_id)))
+        .collect();
     for (split_id, split) in discovered_splits {
-        if !prev_split_ids.contains(split_id) {
-            new_discovered_splits.insert(split.id());
+        if prev_split_ids.contains(split_id) {
+            continue;
         }
+        let Reverse((split_num, actor_id)) = heap.pop().unwrap();
+        actor_splits.get_mut(&actor_id).unwrap().push(split.clone());
+        changed_actors.insert(actor_id);
+        heap.push(Reverse((split_num + 1, actor_id)));
     }
 
-    let mut result = HashMap::new();
-
-    let mut actors = prev_actor_splits.keys().cloned().collect_vec();
-
-    // sort actors
-    actors.sort();
-
-    let actor_len = actors.len();
-
-    for (index, split_id) in new_discovered_splits.into_iter().enumerate() {
-        let target_actor_id = actors[index % actor_len];
-        let split = discovered_splits.get(&split_id).unwrap().clone();
-
-        result
-            .entry(target_actor_id)
-            .or_insert_with(|| prev_actor_splits.remove(&target_actor_id).unwrap());
-
-        result.get_mut(&target_actor_id).unwrap().push(split);
+    if changed_actors.is_empty() {
+        return None;
     }
-
-    Some(result)
+    Some(
+        actor_splits
+            .into_iter()
+            .filter(|(actor_id, _)| changed_actors.contains(actor_id))
+            .collect(),
+    )
 }
 
 impl<S> SourceManager<S>
@@ -759,3 +771,55 @@ where
         self.core.lock().await.get_actor_splits()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use risingwave_connector::source::kafka::KafkaSplit;
+
+    use super::*;
+
+    fn kafka_split(partition: i32) -> SplitImpl {
+        SplitImpl::Kafka(KafkaSplit::new(partition, None, None, "t".to_string()))
+    }
+
+    fn discovered(partitions: impl IntoIterator<Item = i32>) -> BTreeMap<String, SplitImpl> {
+        partitions
+            .into_iter()
+            .map(kafka_split)
+            .map(|split| (split.id(), split))
+            .collect()
+    }
+
+    fn split_ids(splits: &[SplitImpl]) -> Vec<String> {
+        splits.iter().map(SplitImpl::id).collect()
+    }
+
+    #[test]
+    fn test_diff_splits() {
+        // The splits are spread over the actors on creation.
+        let empty: HashMap<_, _> = (1..=3).map(|actor_id| (actor_id, vec![])).collect();
+        let assigned = diff_splits(empty, &discovered(0..4)).unwrap();
+        assert_eq!(split_ids(&assigned[&1]), vec!["0", "3"]);
+        assert_eq!(split_ids(&assigned[&2]), vec!["1"]);
+        assert_eq!(split_ids(&assigned[&3]), vec!["2"]);
+        assert!(diff_splits(assigned.clone(), &discovered(0..4)).is_none());
+
+        // The new partitions go to the actors with the fewest splits, and only their actors
+        // change.
+        let diff = diff_splits(assigned.clone(), &discovered(0..6)).unwrap();
+        assert_eq!(diff.len(), 2);
+        assert_eq!(split_ids(&diff[&2]), vec!["1", "4"]);
+        assert_eq!(split_ids(&diff[&3]), vec!["2", "5"]);
+
+        // The splits that are gone are dropped, and the new ones fill the gaps first.
+        let diff = diff_splits(assigned, &discovered([0, 1, 2, 7])).unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(split_ids(&diff[&1]), vec!["0", "7"]);
+        let mut assigned: HashMap<_, _> = (1..=2).map(|actor_id| (actor_id, vec![])).collect();
+        assigned.insert(1, vec![kafka_split(0)]);
+        let diff = diff_splits(assigned, &discovered([])).unwrap();
+        assert_eq!(diff, HashMap::from([(1, vec![])]));
+
+        assert!(diff_splits(HashMap::new(), &discovered(0..4)).is_none());
+    }
+}
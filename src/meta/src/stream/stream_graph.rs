@@ -634,6 +634,27 @@ impl StreamGraphBuilder {
                         check_and_fill_internal_table(node.table_id_h, None);
                     }
 
+                    NodeBody::GroupTopN(node) => {
+                        if let Some(table) = &mut node.table {
+                            table.id += table_id_offset;
+                            table.schema_id = ctx.schema_id;
+                            table.database_id = ctx.database_id;
+                            table.name = generate_intertable_name_with_type(
+                                &ctx.mview_name,
+                                fragment_id.as_global_id(),
+                                table.id,
+                                "GroupTopN",
+                            );
+                            check_and_fill_internal_table(table.id, Some(table.clone()));
+                        }
+                    }
+
+                    NodeBody::Sink(node) => {
+                        node.state_table_id += table_id_offset;
+
+                        check_and_fill_internal_table(node.state_table_id, None);
+                    }
+
                     NodeBody::GlobalSimpleAgg(node) | NodeBody::LocalSimpleAgg(node) => {
                         assert_eq!(node.internal_tables.len(), node.agg_calls.len());
                         // In-place update the table id. Convert from local to global.
@@ -677,6 +698,21 @@ impl StreamGraphBuilder {
                             check_and_fill_internal_table(table.id, Some(table.clone()));
                         }
                     }
+
+                    NodeBody::Source(node) => {
+                        if let Some(table) = &mut node.dead_letter_table {
+                            table.id += table_id_offset;
+                            table.schema_id = ctx.schema_id;
+                            table.database_id = ctx.database_id;
+                            table.name = generate_intertable_name_with_type(
+                                &ctx.mview_name,
+                                fragment_id.as_global_id(),
+                                table.id,
+                                "SourceDeadLetter",
+                            );
+                            check_and_fill_internal_table(table.id, Some(table.clone()));
+                        }
+                    }
                     _ => {}
                 }
 
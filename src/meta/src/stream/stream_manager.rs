@@ -955,6 +955,13 @@ mod tests {
         ) -> std::result::Result<Response<ActorTraceResponse>, Status> {
             Ok(Response::new(ActorTraceResponse::default()))
         }
+
+        async fn actor_stats(
+            &self,
+            _request: Request<ActorStatsRequest>,
+        ) -> std::result::Result<Response<ActorStatsResponse>, Status> {
+            Ok(Response::new(ActorStatsResponse::default()))
+        }
     }
 
     struct MockServices {
@@ -134,6 +134,8 @@ fn make_stream_fragments() -> Vec<StreamFragment> {
             table_id: 1,
             column_ids: vec![1, 2, 0],
             source_type: SourceType::Table as i32,
+            dead_letter_table: None,
+            rate_limit: 0,
         })),
         pk_indices: vec![2],
         ..Default::default()
@@ -22,8 +22,8 @@ use risingwave_pb::batch_plan::{PlanFragment, TaskId, TaskOutputId};
 use risingwave_pb::task_service::exchange_service_client::ExchangeServiceClient;
 use risingwave_pb::task_service::task_service_client::TaskServiceClient;
 use risingwave_pb::task_service::{
-    CreateTaskRequest, ExecuteRequest, GetDataRequest, GetDataResponse, GetStreamRequest,
-    GetStreamResponse, TaskInfoResponse,
+    AbortTaskRequest, AbortTaskResponse, CreateTaskRequest, ExecuteRequest, GetDataRequest,
+    GetDataResponse, GetStreamRequest, GetStreamResponse, TaskInfoResponse,
 };
 use tonic::transport::{Channel, Endpoint};
 use tonic::Streaming;
@@ -115,6 +115,17 @@ impl ComputeClient {
             .into_inner())
     }
 
+    pub async fn abort(&self, task_id: TaskId) -> Result<AbortTaskResponse> {
+        Ok(self
+            .task_client
+            .to_owned()
+            .abort_task(AbortTaskRequest {
+                task_id: Some(task_id),
+            })
+            .await?
+            .into_inner())
+    }
+
     pub async fn execute(&self, req: ExecuteRequest) -> Result<Streaming<GetDataResponse>> {
         Ok(self.task_client.to_owned().execute(req).await?.into_inner())
     }
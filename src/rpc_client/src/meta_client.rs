@@ -25,7 +25,7 @@ use risingwave_hummock_sdk::{
 };
 use risingwave_pb::catalog::{
     Database as ProstDatabase, Index as ProstIndex, Schema as ProstSchema, Sink as ProstSink,
-    Source as ProstSource, Table as ProstTable,
+    Source as ProstSource, Table as ProstTable, TableStatistics,
 };
 use risingwave_pb::common::WorkerType;
 use risingwave_pb::ddl_service::ddl_service_client::DdlServiceClient;
@@ -264,6 +264,19 @@ impl MetaClient {
         Ok(resp.version)
     }
 
+    pub async fn update_table_statistics(
+        &self,
+        table_id: u32,
+        statistics: TableStatistics,
+    ) -> Result<CatalogVersion> {
+        let request = UpdateTableStatisticsRequest {
+            table_id,
+            statistics: Some(statistics),
+        };
+        let resp = self.inner.update_table_statistics(request).await?;
+        Ok(resp.version)
+    }
+
     pub async fn drop_database(&self, database_id: u32) -> Result<CatalogVersion> {
         let request = DropDatabaseRequest { database_id };
         let resp = self.inner.drop_database(request).await?;
@@ -435,6 +448,16 @@ impl MetaClient {
         let resp = self.inner.get_cluster_info(request).await?;
         Ok(resp)
     }
+
+    /// Changes the rows per second read from the source, where 0 removes the limit.
+    pub async fn throttle(&self, source_id: u32, rate_limit: u32) -> Result<()> {
+        let request = ThrottleRequest {
+            source_id,
+            rate_limit,
+        };
+        let _resp = self.inner.throttle(request).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -666,6 +689,7 @@ macro_rules! for_all_meta_rpc {
             ,{ ddl_client, drop_database, DropDatabaseRequest, DropDatabaseResponse }
             ,{ ddl_client, drop_schema, DropSchemaRequest, DropSchemaResponse }
             ,{ ddl_client, drop_index, DropIndexRequest, DropIndexResponse }
+            ,{ ddl_client, update_table_statistics, UpdateTableStatisticsRequest, UpdateTableStatisticsResponse }
             ,{ ddl_client, risectl_list_state_tables, RisectlListStateTablesRequest, RisectlListStateTablesResponse }
             ,{ hummock_client, pin_version, PinVersionRequest, PinVersionResponse }
             ,{ hummock_client, unpin_version, UnpinVersionRequest, UnpinVersionResponse }
@@ -689,6 +713,7 @@ macro_rules! for_all_meta_rpc {
             ,{ scale_client, pause, PauseRequest, PauseResponse }
             ,{ scale_client, resume, ResumeRequest, ResumeResponse }
             ,{ scale_client, get_cluster_info, GetClusterInfoRequest, GetClusterInfoResponse }
+            ,{ scale_client, throttle, ThrottleRequest, ThrottleResponse }
             ,{ notification_client, subscribe, SubscribeRequest, Streaming<SubscribeResponse> }
         }
     };
@@ -64,6 +64,7 @@ macro_rules! for_all_stream_rpc {
             ,{ 0, drop_source, DropSourceRequest, DropSourceResponse }
             ,{ 0, barrier_complete, BarrierCompleteRequest, BarrierCompleteResponse }
             ,{ 0, actor_trace, ActorTraceRequest, ActorTraceResponse }
+            ,{ 0, actor_stats, ActorStatsRequest, ActorStatsResponse }
         }
     };
 }
@@ -23,20 +23,26 @@ use risingwave_common::array::StreamChunk;
 use risingwave_common::catalog::{ColumnId, TableId};
 use risingwave_common::error::{internal_error, Result, RwError, ToRwResult};
 use risingwave_connector::source::{
-    Column, ConnectorProperties, ConnectorState, SourceMessage, SplitMetaData, SplitReaderImpl,
+    Column, ConnectorProperties, ConnectorState, OffsetCommitter, SourceMessage, SplitMetaData,
+    SplitReaderImpl,
 };
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 
 use crate::common::SourceChunkBuilder;
+use crate::dead_letter::{DeadLetter, SourceErrorPolicy};
+use crate::meta_column::SourceMetaColumn;
 use crate::monitor::SourceMetrics;
+use crate::rate_limit::RateLimiter;
 use crate::{SourceColumnDesc, SourceParserImpl, StreamChunkWithState, StreamSourceReader};
 
 #[derive(Clone, Debug)]
 pub struct SourceContext {
     pub actor_id: u32,
     pub source_id: TableId,
+    /// Shared with the executor, which changes the limit on the throttle mutations.
+    pub rate_limiter: Arc<RateLimiter>,
 }
 
 impl SourceContext {
@@ -44,8 +50,14 @@ impl SourceContext {
         SourceContext {
             actor_id,
             source_id,
+            rate_limiter: Arc::new(RateLimiter::default()),
         }
     }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
 }
 
 struct InnerConnectorSourceReader {
@@ -60,6 +72,7 @@ struct InnerConnectorSourceReader {
 struct InnerConnectorSourceReaderHandle {
     stop_tx: oneshot::Sender<()>,
     join_handle: JoinHandle<()>,
+    offset_committer: Option<OffsetCommitter>,
 }
 
 const CONNECTOR_MESSAGE_BUFFER_SIZE: usize = 512;
@@ -73,6 +86,7 @@ pub struct ConnectorSourceReader {
     pub config: ConnectorProperties,
     pub parser: Arc<SourceParserImpl>,
     pub columns: Vec<SourceColumnDesc>,
+    pub error_policy: SourceErrorPolicy,
 
     handles: Option<HashMap<String, InnerConnectorSourceReaderHandle>>,
     message_rx: Receiver<Either<Vec<SourceMessage>, RwError>>,
@@ -186,19 +200,80 @@ impl StreamSourceReader for ConnectorSourceReader {
 
         let mut events = Vec::with_capacity(batch.len());
         let mut split_offset_mapping: HashMap<String, String> = HashMap::new();
+        let mut dead_letters = vec![];
+        let meta_columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, column)| {
+                SourceMetaColumn::from_name(&column.name).map(|meta| (idx, meta))
+            })
+            .collect_vec();
 
         for msg in batch {
-            if let Some(content) = msg.payload {
-                *split_offset_mapping
-                    .entry(msg.split_id.clone())
-                    .or_insert_with(|| "".to_string()) = msg.offset.to_string();
-                match self.parser.parse(content.as_ref(), &self.columns) {
+            if let Some(content) = msg.payload.clone() {
+                match self
+                    .parser
+                    .parse(content.as_ref(), &msg.meta, &self.columns)
+                    .await
+                {
                     Err(e) => {
-                        tracing::warn!("message parsing failed {}, skipping", e.to_string());
-                        continue;
+                        self.metrics
+                            .parse_error_count
+                            .with_label_values(&[
+                                &self.context.actor_id.to_string(),
+                                &self.context.source_id.to_string(),
+                                &msg.split_id,
+                                self.error_policy.as_str(),
+                            ])
+                            .inc();
+                        match self.error_policy {
+                            // The offset of the message is not recorded, so that the source
+                            // starts from it again after recovery.
+                            SourceErrorPolicy::Fail => {
+                                return Err(internal_error(format!(
+                                    "failed to parse message at offset {} of split {}: {}",
+                                    msg.offset, msg.split_id, e
+                                )));
+                            }
+                            SourceErrorPolicy::Skip => {
+                                tracing::warn!(
+                                    "message parsing failed at offset {} of split {}: {}, skipping",
+                                    msg.offset,
+                                    msg.split_id,
+                                    e
+                                );
+                            }
+                            SourceErrorPolicy::DeadLetter => {
+                                tracing::warn!(
+                                    "message parsing failed at offset {} of split {}: {}, \
+                                     writing it to the dead letter table",
+                                    msg.offset,
+                                    msg.split_id,
+                                    e
+                                );
+                                dead_letters.push(DeadLetter {
+                                    split_id: msg.split_id.clone(),
+                                    offset: msg.offset.clone(),
+                                    payload: content,
+                                    error: e.to_string(),
+                                });
+                            }
+                        }
+                    }
+                    Ok(mut result) => {
+                        for (idx, meta) in &meta_columns {
+                            let datum = meta.to_datum(&msg, &self.columns[*idx].data_type)?;
+                            for row in &mut result.rows {
+                                row[*idx] = datum.clone();
+                            }
+                        }
+                        events.push(result);
                     }
-                    Ok(result) => events.push(result),
                 }
+                *split_offset_mapping
+                    .entry(msg.split_id)
+                    .or_insert_with(|| "".to_string()) = msg.offset;
             }
         }
         let mut ops = Vec::with_capacity(events.iter().map(|e| e.ops.len()).sum());
@@ -208,6 +283,9 @@ impl StreamSourceReader for ConnectorSourceReader {
             rows.extend(event.rows);
             ops.extend(event.ops);
         }
+        // The messages are not pulled from the inner readers while waiting, which then block on the
+        // full channel.
+        self.context.rate_limiter.wait(rows.len()).await;
         Ok(StreamChunkWithState {
             chunk: StreamChunk::new(
                 ops,
@@ -215,6 +293,7 @@ impl StreamSourceReader for ConnectorSourceReader {
                 None,
             ),
             split_offset_mapping: Some(split_offset_mapping),
+            dead_letters,
         })
     }
 }
@@ -245,6 +324,7 @@ impl ConnectorSourceReader {
                 .await?;
                 let (stop_tx, stop_rx) = oneshot::channel();
                 let sender = self.message_tx.clone();
+                let offset_committer = reader.reader.offset_committer();
                 let join_handle = tokio::spawn(async move { reader.run(stop_rx, sender).await });
 
                 if let Some(handles) = self.handles.as_mut() {
@@ -253,6 +333,7 @@ impl ConnectorSourceReader {
                         InnerConnectorSourceReaderHandle {
                             stop_tx,
                             join_handle,
+                            offset_committer,
                         },
                     );
                 }
@@ -275,6 +356,21 @@ impl ConnectorSourceReader {
             .await
             .map_err(|e| internal_error(e.to_string()))
     }
+
+    /// Returns the senders of the offsets committed in checkpoints, for the splits whose readers
+    /// acknowledge them to the external source.
+    pub fn offset_committers(&self) -> HashMap<String, OffsetCommitter> {
+        self.handles
+            .iter()
+            .flatten()
+            .filter_map(|(split_id, handle)| {
+                handle
+                    .offset_committer
+                    .clone()
+                    .map(|committer| (split_id.clone(), committer))
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -282,6 +378,7 @@ pub struct ConnectorSource {
     pub config: ConnectorProperties,
     pub columns: Vec<SourceColumnDesc>,
     pub parser: Arc<SourceParserImpl>,
+    pub error_policy: SourceErrorPolicy,
 }
 
 impl ConnectorSource {
@@ -347,6 +444,7 @@ impl ConnectorSource {
             };
             let (stop_tx, stop_rx) = oneshot::channel();
             let sender = tx.clone();
+            let offset_committer = reader.reader.offset_committer();
             let join_handle = tokio::spawn(async move { reader.run(stop_rx, sender).await });
 
             handles.insert(
@@ -354,6 +452,7 @@ impl ConnectorSource {
                 InnerConnectorSourceReaderHandle {
                     stop_tx,
                     join_handle,
+                    offset_committer,
                 },
             );
         }
@@ -364,6 +463,7 @@ impl ConnectorSource {
             message_rx: rx,
             parser: self.parser.clone(),
             columns,
+            error_policy: self.error_policy,
             message_tx: tx,
             metrics: metrics.clone(),
             context: context.clone(),
@@ -0,0 +1,152 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handling of the source messages that fail to be parsed.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use risingwave_common::array::Row;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::{DataType, ScalarImpl};
+
+use crate::meta_column::bytes_to_text;
+
+/// What to do with a message that fails to be parsed, set by `parse.error.policy` in the WITH
+/// clause of the source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SourceErrorPolicy {
+    /// Fails the source, which fails at the same message again after recovery.
+    Fail,
+    /// Drops the message after logging it.
+    #[default]
+    Skip,
+    /// Drops the message and writes it to the dead letter table of each streaming job reading the
+    /// source.
+    DeadLetter,
+}
+
+impl SourceErrorPolicy {
+    pub const KEY: &'static str = "parse.error.policy";
+
+    pub fn from_properties(properties: &HashMap<String, String>) -> Result<Self> {
+        match properties
+            .get(Self::KEY)
+            .map(|v| v.to_lowercase())
+            .as_deref()
+        {
+            None | Some("skip") => Ok(Self::Skip),
+            Some("fail") => Ok(Self::Fail),
+            Some("dead_letter") => Ok(Self::DeadLetter),
+            Some(other) => Err(RwError::from(ProtocolError(format!(
+                "{} must be one of \"fail\", \"skip\" and \"dead_letter\", found: \"{}\"",
+                Self::KEY,
+                other
+            )))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fail => "fail",
+            Self::Skip => "skip",
+            Self::DeadLetter => "dead_letter",
+        }
+    }
+}
+
+/// A message that fails to be parsed, which is kept in the dead letter table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadLetter {
+    pub split_id: String,
+    pub offset: String,
+    pub payload: Bytes,
+    pub error: String,
+}
+
+impl DeadLetter {
+    /// The indices of the primary key columns in [`DeadLetter::table_columns`].
+    pub const PK_INDICES: [usize; 2] = [0, 1];
+
+    /// The names and types of the columns of the dead letter table. The split id and the offset
+    /// are the primary key, so a message delivered again after recovery is written only once.
+    pub fn table_columns() -> [(&'static str, DataType); 4] {
+        [
+            ("split_id", DataType::Varchar),
+            ("offset", DataType::Varchar),
+            ("payload", DataType::Varchar),
+            ("error", DataType::Varchar),
+        ]
+    }
+
+    /// The row in the dead letter table, with the payload converted by [`bytes_to_text`].
+    pub fn to_row(&self) -> Row {
+        Row::new(vec![
+            Some(ScalarImpl::Utf8(self.split_id.clone())),
+            Some(ScalarImpl::Utf8(self.offset.clone())),
+            Some(ScalarImpl::Utf8(bytes_to_text(&self.payload))),
+            Some(ScalarImpl::Utf8(self.error.clone())),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+
+    use super::*;
+
+    #[test]
+    fn test_error_policy_from_properties() {
+        assert_eq!(
+            SourceErrorPolicy::from_properties(&HashMap::new()).unwrap(),
+            SourceErrorPolicy::Skip
+        );
+        assert_eq!(
+            SourceErrorPolicy::from_properties(&convert_args!(hashmap!(
+                "parse.error.policy" => "DEAD_LETTER",
+            )))
+            .unwrap(),
+            SourceErrorPolicy::DeadLetter
+        );
+        assert!(SourceErrorPolicy::from_properties(&convert_args!(hashmap!(
+            "parse.error.policy" => "retry",
+        )))
+        .is_err());
+    }
+
+    #[test]
+    fn test_dead_letter_to_row() {
+        let dead_letter = DeadLetter {
+            split_id: "0".to_string(),
+            offset: "42".to_string(),
+            payload: Bytes::from_static(b"{\"v\":"),
+            error: "EOF while parsing".to_string(),
+        };
+        assert_eq!(
+            dead_letter.to_row().0[2],
+            Some(ScalarImpl::Utf8("{\"v\":".to_string()))
+        );
+
+        let dead_letter = DeadLetter {
+            payload: Bytes::from_static(&[0x0a, 0xff]),
+            ..dead_letter
+        };
+        assert_eq!(
+            dead_letter.to_row().0[2],
+            Some(ScalarImpl::Utf8("\\x0aff".to_string()))
+        );
+    }
+}
@@ -40,9 +40,11 @@ pub use manager::*;
 pub use parser::*;
 use risingwave_common::array::StreamChunk;
 use risingwave_common::error::Result;
+use risingwave_connector::source::OffsetCommitter;
 pub use table_v2::*;
 
 use crate::connector_source::{ConnectorSource, ConnectorSourceReader};
+use crate::dead_letter::DeadLetter;
 
 pub mod parser;
 
@@ -50,7 +52,10 @@ mod manager;
 
 mod common;
 pub mod connector_source;
+pub mod dead_letter;
+pub mod meta_column;
 pub mod monitor;
+pub mod rate_limit;
 pub mod row_id;
 mod table_v2;
 
@@ -63,10 +68,15 @@ pub enum SourceFormat {
     Json,
     Protobuf,
     DebeziumJson,
+    DebeziumAvro,
     Avro,
+    Csv,
+    Maxwell,
+    CanalJson,
 }
 
 #[derive(Debug, EnumAsInner)]
+#[expect(clippy::large_enum_variant)]
 pub enum SourceImpl {
     TableV2(TableSourceV2),
     Connector(ConnectorSource),
@@ -78,6 +88,17 @@ pub enum SourceStreamReaderImpl {
     Connector(ConnectorSourceReader),
 }
 
+impl SourceStreamReaderImpl {
+    /// Returns the senders of the offsets committed in checkpoints, for the splits whose readers
+    /// acknowledge them to the external source, e.g. the replication slots of postgres.
+    pub fn offset_committers(&self) -> HashMap<String, OffsetCommitter> {
+        match self {
+            SourceStreamReaderImpl::TableV2(_) => HashMap::new(),
+            SourceStreamReaderImpl::Connector(c) => c.offset_committers(),
+        }
+    }
+}
+
 #[async_trait]
 impl StreamSourceReader for SourceStreamReaderImpl {
     async fn next(&mut self) -> Result<StreamChunkWithState> {
@@ -90,11 +111,14 @@ impl StreamSourceReader for SourceStreamReaderImpl {
 
 /// [`StreamChunkWithState`] returns stream chunk together with offset for each split. In the
 /// current design, one connector source can have multiple split reader. The keys are unique
-/// `split_id` and values are the latest offset for each split.
+/// `split_id` and values are the latest offset for each split. `dead_letters` are the messages that
+/// failed to be parsed under
+/// [`SourceErrorPolicy::DeadLetter`](crate::dead_letter::SourceErrorPolicy::DeadLetter).
 #[derive(Clone, Debug)]
 pub struct StreamChunkWithState {
     pub chunk: StreamChunk,
     pub split_offset_mapping: Option<HashMap<String, String>>,
+    pub dead_letters: Vec<DeadLetter>,
 }
 
 #[async_trait]
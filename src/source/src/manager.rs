@@ -27,7 +27,10 @@ use risingwave_connector::source::ConnectorProperties;
 use risingwave_pb::catalog::StreamSourceInfo;
 use risingwave_pb::plan_common::RowFormatType;
 
+use crate::dead_letter::SourceErrorPolicy;
+use crate::meta_column::SourceMetaColumn;
 use crate::monitor::SourceMetrics;
+use crate::parser::schema_registry::SCHEMA_REGISTRY_KEY;
 use crate::table_v2::TableSourceV2;
 use crate::{ConnectorSource, SourceFormat, SourceImpl, SourceParserImpl};
 
@@ -111,18 +114,29 @@ impl SourceManager for MemSourceManager {
             RowFormatType::Json => SourceFormat::Json,
             RowFormatType::Protobuf => SourceFormat::Protobuf,
             RowFormatType::DebeziumJson => SourceFormat::DebeziumJson,
+            RowFormatType::DebeziumAvro => SourceFormat::DebeziumAvro,
             RowFormatType::Avro => SourceFormat::Avro,
+            RowFormatType::Csv => SourceFormat::Csv,
+            RowFormatType::Maxwell => SourceFormat::Maxwell,
+            RowFormatType::CanalJson => SourceFormat::CanalJson,
             RowFormatType::RowUnspecified => unreachable!(),
         };
 
-        if format == SourceFormat::Protobuf && info.row_schema_location.is_empty() {
+        if format == SourceFormat::Protobuf
+            && info.row_schema_location.is_empty()
+            && !info.properties.contains_key(SCHEMA_REGISTRY_KEY)
+        {
             return Err(RwError::from(ProtocolError(
                 "protobuf file location not provided".to_string(),
             )));
         }
-        let source_parser_rs =
-            SourceParserImpl::create(&format, &info.properties, info.row_schema_location.as_str())
-                .await;
+        let source_parser_rs = SourceParserImpl::create(
+            &format,
+            &info.properties,
+            info.row_schema_location.as_str(),
+            info.csv_info.as_ref(),
+        )
+        .await;
         let parser = if let Ok(source_parser) = source_parser_rs {
             source_parser
         } else {
@@ -137,7 +151,9 @@ impl SourceManager for MemSourceManager {
                 let mut col = SourceColumnDesc::from(&ColumnDesc::from(
                     c.column_desc.as_ref().unwrap().clone(),
                 ));
-                col.skip_parse = idx as i32 == info.row_id_index;
+                // The metadata columns are filled from the messages rather than the payloads.
+                col.skip_parse = idx as i32 == info.row_id_index
+                    || SourceMetaColumn::from_name(&col.name).is_some();
                 col
             })
             .collect::<Vec<SourceColumnDesc>>();
@@ -149,6 +165,7 @@ impl SourceManager for MemSourceManager {
         );
         let row_id_index = info.row_id_index as usize;
 
+        let error_policy = SourceErrorPolicy::from_properties(&info.properties)?;
         let config = ConnectorProperties::extract(info.properties)
             .map_err(|e| RwError::from(ConnectorError(e.to_string())))?;
 
@@ -156,6 +173,7 @@ impl SourceManager for MemSourceManager {
             config,
             columns: columns.clone(),
             parser,
+            error_policy,
         });
 
         let desc = SourceDesc {
@@ -242,15 +260,23 @@ impl MemSourceManager {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use maplit::{convert_args, hashmap};
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::array::StreamChunk;
     use risingwave_common::catalog::{ColumnDesc, ColumnId, Field, Schema, TableId};
     use risingwave_common::error::Result;
     use risingwave_common::types::DataType;
+    use risingwave_connector::source::filesystem::FsSplit;
     use risingwave_connector::source::kinesis::config::kinesis_demo_properties;
+    use risingwave_connector::source::SplitImpl;
     use risingwave_pb::catalog::StreamSourceInfo;
-    use risingwave_pb::plan_common::ColumnCatalog;
+    use risingwave_pb::plan_common::{ColumnCatalog, CsvInfo, RowFormatType};
     use risingwave_storage::memory::MemoryStateStore;
     use risingwave_storage::Keyspace;
 
+    use crate::connector_source::{ConnectorSourceReader, SourceContext};
     use crate::*;
 
     #[tokio::test]
@@ -274,6 +300,7 @@ mod tests {
             row_id_index: 0,
             pk_column_ids: vec![0],
             columns,
+            csv_info: None,
         };
         let source_id = TableId::default();
 
@@ -285,6 +312,153 @@ mod tests {
         Ok(())
     }
 
+    /// Creates a CSV source over the local files in `dir` and reads the split of `file` from it.
+    async fn read_local_fs_csv(
+        dir: &std::path::Path,
+        file: &str,
+        extra_properties: HashMap<String, String>,
+        column_ids: &[i32],
+    ) -> Result<ConnectorSourceReader> {
+        let columns = [
+            ColumnDesc::unnamed(ColumnId::from(0), DataType::Int64),
+            ColumnDesc::unnamed(ColumnId::from(1), DataType::Int32),
+            ColumnDesc::unnamed(ColumnId::from(2), DataType::Varchar),
+            ColumnDesc {
+                name: "_rw_partition".to_string(),
+                ..ColumnDesc::unnamed(ColumnId::from(3), DataType::Varchar)
+            },
+            ColumnDesc {
+                name: "_rw_offset".to_string(),
+                ..ColumnDesc::unnamed(ColumnId::from(4), DataType::Int64)
+            },
+        ]
+        .iter()
+        .map(|c| ColumnCatalog {
+            column_desc: Some(c.to_protobuf()),
+            is_hidden: false,
+        })
+        .collect();
+        let mut properties: HashMap<String, String> = convert_args!(hashmap!(
+            "connector" => "local_fs",
+            "local_fs.root" => dir.to_str().unwrap(),
+            "match_pattern" => "*.csv",
+        ));
+        properties.extend(extra_properties);
+        let info = StreamSourceInfo {
+            properties,
+            row_format: RowFormatType::Csv as i32,
+            row_schema_location: "".to_string(),
+            row_id_index: 0,
+            pk_column_ids: vec![0],
+            columns,
+            csv_info: Some(CsvInfo {
+                delimiter: b',' as u32,
+                quote: b'"' as u32,
+                has_header: true,
+            }),
+        };
+        let source_id = TableId::default();
+
+        let mem_source_manager = MemSourceManager::default();
+        mem_source_manager.create_source(&source_id, info).await?;
+        let source_desc = mem_source_manager.get_source(&source_id)?;
+        let source = source_desc.source.as_connector().unwrap();
+
+        let size = std::fs::metadata(dir.join(file)).unwrap().len() as usize;
+        let split = FsSplit::new(file.to_string(), 0, size);
+        source
+            .stream_reader(
+                Some(vec![SplitImpl::LocalFs(split)]),
+                column_ids.iter().copied().map(ColumnId::from).collect(),
+                source_desc.metrics.clone(),
+                SourceContext::new(0, source_id),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_csv_local_fs_source() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("1.csv"), "id,name\n1,alice\n2,bob\n").unwrap();
+
+        let mut reader = read_local_fs_csv(dir.path(), "1.csv", HashMap::new(), &[1, 2]).await?;
+        let chunk = reader.next().await?;
+        assert_eq!(
+            chunk.chunk,
+            StreamChunk::from_pretty(
+                " i T
+                + 1 alice
+                + 2 bob",
+            )
+        );
+        assert_eq!(
+            chunk.split_offset_mapping,
+            Some(convert_args!(hashmap!("1.csv" => "22")))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_source_error_policy() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("1.csv"), "id,name\nx,alice\n").unwrap();
+
+        // The message is dropped, but the offset moves on.
+        let mut reader = read_local_fs_csv(dir.path(), "1.csv", HashMap::new(), &[1, 2]).await?;
+        let chunk = reader.next().await?;
+        assert_eq!(chunk.chunk.cardinality(), 0);
+        assert!(chunk.dead_letters.is_empty());
+        assert_eq!(
+            chunk.split_offset_mapping,
+            Some(convert_args!(hashmap!("1.csv" => "16")))
+        );
+
+        let mut reader = read_local_fs_csv(
+            dir.path(),
+            "1.csv",
+            convert_args!(hashmap!("parse.error.policy" => "dead_letter")),
+            &[1, 2],
+        )
+        .await?;
+        let chunk = reader.next().await?;
+        assert_eq!(chunk.chunk.cardinality(), 0);
+        assert_eq!(chunk.dead_letters.len(), 1);
+        assert_eq!(chunk.dead_letters[0].split_id, "1.csv");
+        assert_eq!(chunk.dead_letters[0].offset, "16");
+        assert_eq!(&chunk.dead_letters[0].payload[..], b"id,name\nx,alice\n");
+
+        let mut reader = read_local_fs_csv(
+            dir.path(),
+            "1.csv",
+            convert_args!(hashmap!("parse.error.policy" => "fail")),
+            &[1, 2],
+        )
+        .await?;
+        assert!(reader.next().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_source_meta_columns() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("1.csv"), "id,name\n1,alice\n").unwrap();
+
+        let mut reader =
+            read_local_fs_csv(dir.path(), "1.csv", HashMap::new(), &[1, 2, 3, 4]).await?;
+        let chunk = reader.next().await?;
+        assert_eq!(
+            chunk.chunk,
+            StreamChunk::from_pretty(
+                " i T     T     I
+                + 1 alice 1.csv 16",
+            )
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_table_source_v2() -> Result<()> {
         let table_id = TableId::default();
@@ -0,0 +1,222 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The metadata columns of sources, which are filled from the metadata of the messages rather than
+//! parsed from the payloads.
+
+use std::fmt::Write;
+
+use chrono::NaiveDateTime;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::{DataType, Datum, NaiveDateTimeWrapper, ScalarImpl};
+use risingwave_connector::source::SourceMessage;
+
+/// The prefix of the names of the metadata columns.
+pub const SOURCE_META_COLUMN_PREFIX: &str = "_rw_";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceMetaColumn {
+    /// The key of the message, e.g. the partition key of Pulsar and Kinesis.
+    Key,
+    /// The time when the message was produced, or stored by the broker.
+    Timestamp,
+    /// The id of the split, e.g. the partition of Kafka.
+    Partition,
+    Offset,
+    /// The headers of the message as a JSON object, e.g. the properties of Pulsar.
+    Headers,
+}
+
+impl SourceMetaColumn {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix(SOURCE_META_COLUMN_PREFIX)? {
+            "key" => Some(Self::Key),
+            "timestamp" | "kafka_timestamp" => Some(Self::Timestamp),
+            "partition" => Some(Self::Partition),
+            "offset" => Some(Self::Offset),
+            "headers" => Some(Self::Headers),
+            _ => None,
+        }
+    }
+
+    /// Checks the column name with the metadata column prefix and its type, returning `None` if
+    /// it's an ordinary column.
+    pub fn check_column(name: &str, data_type: &DataType) -> Result<Option<Self>> {
+        if !name.starts_with(SOURCE_META_COLUMN_PREFIX) {
+            return Ok(None);
+        }
+        let column = Self::from_name(name).ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "unknown metadata column {}, expected one of _rw_key, _rw_timestamp, \
+                 _rw_partition, _rw_offset and _rw_headers",
+                name
+            )))
+        })?;
+        let supported = match column {
+            Self::Key | Self::Headers => matches!(data_type, DataType::Varchar),
+            Self::Timestamp => matches!(
+                data_type,
+                DataType::Timestampz | DataType::Timestamp | DataType::Int64
+            ),
+            Self::Partition => matches!(
+                data_type,
+                DataType::Varchar | DataType::Int32 | DataType::Int64
+            ),
+            Self::Offset => matches!(data_type, DataType::Varchar | DataType::Int64),
+        };
+        if !supported {
+            return Err(RwError::from(ProtocolError(format!(
+                "metadata column {} can't be of type {:?}",
+                name, data_type
+            ))));
+        }
+        Ok(Some(column))
+    }
+
+    /// Returns the value of the column from the message. The timestamp is in milliseconds if the
+    /// column is a `BIGINT`.
+    pub fn to_datum(&self, message: &SourceMessage, data_type: &DataType) -> Result<Datum> {
+        let datum = match self {
+            Self::Key => message
+                .meta
+                .key
+                .as_ref()
+                .map(|key| ScalarImpl::Utf8(bytes_to_text(key))),
+            Self::Timestamp => message.meta.timestamp.map(|millis| match data_type {
+                DataType::Timestamp => ScalarImpl::NaiveDateTime(NaiveDateTimeWrapper::new(
+                    NaiveDateTime::from_timestamp(
+                        millis.div_euclid(1000),
+                        (millis.rem_euclid(1000) * 1_000_000) as u32,
+                    ),
+                )),
+                // In microseconds.
+                DataType::Timestampz => ScalarImpl::Int64(millis * 1000),
+                _ => ScalarImpl::Int64(millis),
+            }),
+            Self::Partition => Some(parse_text(&message.split_id, data_type)?),
+            Self::Offset => Some(parse_text(&message.offset, data_type)?),
+            Self::Headers => {
+                let headers = message
+                    .meta
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), bytes_to_text(value).into()))
+                    .collect::<serde_json::Map<_, _>>();
+                Some(ScalarImpl::Utf8(
+                    serde_json::Value::from(headers).to_string(),
+                ))
+            }
+        };
+        Ok(datum)
+    }
+}
+
+fn parse_text(text: &str, data_type: &DataType) -> Result<ScalarImpl> {
+    let parse_error = |e| {
+        RwError::from(ProtocolError(format!(
+            "failed to parse \"{}\": {}",
+            text, e
+        )))
+    };
+    let scalar = match data_type {
+        DataType::Int32 => ScalarImpl::Int32(text.parse().map_err(parse_error)?),
+        DataType::Int64 => ScalarImpl::Int64(text.parse().map_err(parse_error)?),
+        _ => ScalarImpl::Utf8(text.to_string()),
+    };
+    Ok(scalar)
+}
+
+/// Returns the bytes as they are if they're valid UTF-8, and otherwise in hex after `\x`, like the
+/// text output of `bytea` in `PostgreSQL`.
+pub fn bytes_to_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => {
+            let mut hex = String::with_capacity(2 + bytes.len() * 2);
+            hex.push_str("\\x");
+            for byte in bytes {
+                write!(hex, "{:02x}", byte).unwrap();
+            }
+            hex
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use risingwave_connector::source::SourceMeta;
+
+    use super::*;
+
+    #[test]
+    fn test_check_meta_column() {
+        assert_eq!(
+            SourceMetaColumn::check_column("v1", &DataType::Int32).unwrap(),
+            None
+        );
+        assert_eq!(
+            SourceMetaColumn::check_column("_rw_kafka_timestamp", &DataType::Timestampz).unwrap(),
+            Some(SourceMetaColumn::Timestamp)
+        );
+        assert!(SourceMetaColumn::check_column("_rw_topic", &DataType::Varchar).is_err());
+        assert!(SourceMetaColumn::check_column("_rw_key", &DataType::Int64).is_err());
+    }
+
+    #[test]
+    fn test_meta_column_to_datum() {
+        let message = SourceMessage {
+            payload: None,
+            offset: "42".to_string(),
+            split_id: "1".to_string(),
+            meta: SourceMeta {
+                key: Some(Bytes::from_static(&[0xff, 0x01])),
+                timestamp: Some(1_500),
+                headers: vec![("h".to_string(), Bytes::from_static(b"v"))],
+                ..Default::default()
+            },
+        };
+        let datum = |column: SourceMetaColumn, data_type: DataType| {
+            column.to_datum(&message, &data_type).unwrap()
+        };
+
+        assert_eq!(
+            datum(SourceMetaColumn::Key, DataType::Varchar),
+            Some(ScalarImpl::Utf8("\\xff01".to_string()))
+        );
+        assert_eq!(
+            datum(SourceMetaColumn::Timestamp, DataType::Timestampz),
+            Some(ScalarImpl::Int64(1_500_000))
+        );
+        assert_eq!(
+            datum(SourceMetaColumn::Timestamp, DataType::Timestamp),
+            Some(ScalarImpl::NaiveDateTime(NaiveDateTimeWrapper::new(
+                NaiveDateTime::from_timestamp(1, 500_000_000)
+            )))
+        );
+        assert_eq!(
+            datum(SourceMetaColumn::Partition, DataType::Int32),
+            Some(ScalarImpl::Int32(1))
+        );
+        assert_eq!(
+            datum(SourceMetaColumn::Offset, DataType::Varchar),
+            Some(ScalarImpl::Utf8("42".to_string()))
+        );
+        assert_eq!(
+            datum(SourceMetaColumn::Headers, DataType::Varchar),
+            Some(ScalarImpl::Utf8(r#"{"h":"v"}"#.to_string()))
+        );
+    }
+}
@@ -18,7 +18,7 @@ use std::future::Future;
 use std::path::Path;
 
 use apache_avro::types::Value;
-use apache_avro::{Reader, Schema};
+use apache_avro::{from_avro_datum, Reader, Schema};
 use chrono::{Datelike, NaiveDate};
 use num_traits::FromPrimitive;
 use risingwave_common::array::Op;
@@ -28,8 +28,13 @@ use risingwave_common::types::{
     DataType, Datum, Decimal, NaiveDateTimeWrapper, NaiveDateWrapper, ScalarImpl,
 };
 use risingwave_connector::aws_utils::{default_conn_config, s3_client, AwsConfigV2};
+use risingwave_pb::plan_common::ColumnDesc;
 use url::Url;
 
+use super::schema_registry::{
+    client_from_properties, extract_schema_id, subject_from_properties, ConfluentSchemaResolver,
+    SCHEMA_REGISTRY_KEY,
+};
 use crate::{Event, SourceColumnDesc, SourceParser};
 
 const AVRO_SCHEMA_LOCATION_S3_REGION: &str = "region";
@@ -41,6 +46,9 @@ pub fn unix_epoch_days() -> i32 {
 #[derive(Debug)]
 pub struct AvroParser {
     schema: Schema,
+    /// Set if the messages are in the Confluent wire format, in which case `schema` is the latest
+    /// one of the subject, and the messages written in the other schemas are resolved to it.
+    schema_resolver: Option<ConfluentSchemaResolver<Schema>>,
 }
 
 impl AvroParser {
@@ -72,11 +80,165 @@ impl AvroParser {
                 )))),
             };
         if let Ok(schema) = arvo_schema {
-            Ok(Self { schema })
+            Ok(Self {
+                schema,
+                schema_resolver: None,
+            })
         } else {
             Err(arvo_schema.err().unwrap())
         }
     }
+
+    /// Creates a parser of the messages in the Confluent wire format, with the schemas in the
+    /// registry set by [`SCHEMA_REGISTRY_KEY`].
+    pub async fn new_with_schema_registry(props: &HashMap<String, String>) -> Result<Self> {
+        let url = props.get(SCHEMA_REGISTRY_KEY).ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "'{}' not provided",
+                SCHEMA_REGISTRY_KEY
+            )))
+        })?;
+        let client = client_from_properties(url, props)?;
+        let subject = subject_from_properties(props)?;
+        let latest = client.get_latest_schema(&subject).await?;
+        Ok(Self {
+            schema: parse_avro_schema(&latest.schema)?,
+            schema_resolver: Some(ConfluentSchemaResolver::new(client)),
+        })
+    }
+
+    pub fn uses_schema_registry(&self) -> bool {
+        self.schema_resolver.is_some()
+    }
+
+    /// Parses a message in the Confluent wire format, fetching its schema from the registry if
+    /// it's the first message written in the schema.
+    pub async fn parse_confluent(
+        &self,
+        payload: &[u8],
+        columns: &[SourceColumnDesc],
+    ) -> Result<Event> {
+        let resolver = self
+            .schema_resolver
+            .as_ref()
+            .ok_or_else(|| RwError::from(InternalError("schema registry not set".to_string())))?;
+        let (schema_id, mut data) = extract_schema_id(payload)?;
+        let writer_schema = match resolver.get(schema_id) {
+            Some(schema) => schema,
+            None => {
+                let schema = resolver.client().get_schema_by_id(schema_id).await?;
+                resolver.insert(schema_id, parse_avro_schema(&schema.schema)?)
+            }
+        };
+        let value = from_avro_datum(&writer_schema, &mut data, Some(&self.schema))
+            .map_err(|e| RwError::from(ProtocolError(e.to_string())))?;
+        let fields = match value {
+            Value::Record(fields) => fields,
+            _ => {
+                return Err(RwError::from(ProtocolError(
+                    "avro message is not a record".to_string(),
+                )))
+            }
+        };
+        Ok(Event {
+            ops: vec![Op::Insert],
+            rows: vec![avro_record_to_datums(&fields, columns)?],
+        })
+    }
+
+    /// Maps the fields of the record schema to columns, with the ids starting from 1 after the row
+    /// id column. The optional fields, i.e. the unions of `null` and another type, are nullable
+    /// columns of the type.
+    pub fn map_to_columns(&self) -> Result<Vec<ColumnDesc>> {
+        let fields = match &self.schema {
+            Schema::Record { fields, .. } => fields,
+            _ => {
+                return Err(RwError::from(ProtocolError(
+                    "avro schema is not a record".to_string(),
+                )))
+            }
+        };
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                Ok(ColumnDesc {
+                    column_id: i as i32 + 1,
+                    name: field.name.clone(),
+                    column_type: Some(avro_type_mapping(&field.schema)?.to_protobuf()),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Maps the fields of a record to the columns by name. The fields missing from the record, e.g. the
+/// ones dropped later from the schema, are parsed as `NULL`.
+pub(crate) fn avro_record_to_datums(
+    fields: &[(String, Value)],
+    columns: &[SourceColumnDesc],
+) -> Result<Vec<Datum>> {
+    columns
+        .iter()
+        .map(|column| {
+            if column.skip_parse {
+                return Ok(None);
+            }
+            match fields.iter().find(|(name, _)| name == &column.name) {
+                None | Some((_, Value::Null)) => Ok(None),
+                Some((_, Value::Union(_, value))) if **value == Value::Null => Ok(None),
+                Some((_, Value::Union(_, value))) => {
+                    from_avro_value(column, *value.clone()).map(Some)
+                }
+                Some((_, value)) => from_avro_value(column, value.clone()).map(Some),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn parse_avro_schema(content: &str) -> Result<Schema> {
+    Schema::parse_str(content)
+        .map_err(|e| RwError::from(ProtocolError(format!("Avro schema parse error {}", e))))
+}
+
+/// Maps an Avro type to a DB column type, in line with [`from_avro_value`].
+fn avro_type_mapping(schema: &Schema) -> Result<DataType> {
+    let data_type = match schema {
+        Schema::Boolean => DataType::Boolean,
+        Schema::Int => DataType::Int32,
+        Schema::Long => DataType::Int64,
+        Schema::Float => DataType::Float32,
+        Schema::Double => DataType::Float64,
+        Schema::String => DataType::Varchar,
+        Schema::Date => DataType::Date,
+        Schema::TimestampMillis => DataType::Timestamp,
+        Schema::Union(union) => {
+            let variants = union
+                .variants()
+                .iter()
+                .filter(|s| **s != Schema::Null)
+                .collect::<Vec<_>>();
+            match variants.as_slice() {
+                [schema] => avro_type_mapping(schema)?,
+                _ => {
+                    return Err(ErrorCode::NotImplemented(
+                        "avro union of multiple types is not supported".to_string(),
+                        None.into(),
+                    )
+                    .into())
+                }
+            }
+        }
+        other => {
+            return Err(ErrorCode::NotImplemented(
+                format!("unsupported avro type: {:?}", other),
+                None.into(),
+            )
+            .into())
+        }
+    };
+    Ok(data_type)
 }
 
 macro_rules! from_avro_datetime {
@@ -345,13 +507,17 @@ mod test {
     use std::ops::Sub;
 
     use apache_avro::types::{Record, Value};
-    use apache_avro::{Codec, Schema, Writer};
+    use apache_avro::{to_avro_datum, Codec, Schema, Writer};
     use chrono::NaiveDate;
-    use risingwave_common::catalog::ColumnId;
+    use maplit::{convert_args, hashmap};
+    use risingwave_common::catalog::{ColumnDesc, ColumnId};
     use risingwave_common::error;
     use risingwave_common::error::ErrorCode::InternalError;
     use risingwave_common::error::RwError;
     use risingwave_common::types::{DataType, NaiveDateTimeWrapper, NaiveDateWrapper, ScalarImpl};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use crate::parser::avro_parser::{
         load_schema_async, read_schema_from_local, read_schema_from_s3, unix_epoch_days, AvroParser,
@@ -586,4 +752,56 @@ mod test {
         let avro_parser = avro_parser_rs.unwrap();
         println!("avro_parser = {:?}", avro_parser);
     }
+
+    #[tokio::test]
+    #[cfg_attr(madsim, ignore)] // MockServer is not supported in simulation.
+    async fn test_avro_parser_with_schema_registry() {
+        let v1 = r#"{"type":"record","name":"r","fields":[{"name":"id","type":"int"}]}"#;
+        let v2 = r#"{"type":"record","name":"r","fields":[
+            {"name":"id","type":"int"},
+            {"name":"name","type":["null","string"],"default":null}
+        ]}"#;
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/subjects/t-value/versions/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "schema": v2 })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "schema": v1 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let parser = AvroParser::new_with_schema_registry(&convert_args!(hashmap!(
+            "kafka.topic" => "t",
+            "schema.registry" => server.uri(),
+        )))
+        .await
+        .unwrap();
+        assert!(parser.uses_schema_registry());
+        let columns = parser
+            .map_to_columns()
+            .unwrap()
+            .into_iter()
+            .map(|c| SourceColumnDesc::from(&ColumnDesc::from(c)))
+            .collect::<Vec<_>>();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[1].data_type, DataType::Varchar);
+
+        // A message written in the old schema, which has no `name`.
+        let writer_schema = Schema::parse_str(v1).unwrap();
+        let mut record = Record::new(&writer_schema).unwrap();
+        record.put("id", 42);
+        let mut payload = vec![0, 0, 0, 0, 1];
+        payload.extend(to_avro_datum(&writer_schema, record).unwrap());
+
+        // The schema is fetched only once.
+        for _ in 0..2 {
+            let event = parser.parse_confluent(&payload, &columns).await.unwrap();
+            assert_eq!(event.rows, vec![vec![Some(ScalarImpl::Int32(42)), None]]);
+        }
+        assert!(parser.parse_confluent(b"{}", &columns).await.is_err());
+    }
 }
@@ -0,0 +1,213 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use risingwave_common::array::Op;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::Datum;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parser::common::{json_parse_value, str_parse_value};
+use crate::{Event, SourceColumnDesc, SourceParser};
+
+const CANAL_INSERT_OP: &str = "INSERT";
+const CANAL_UPDATE_OP: &str = "UPDATE";
+const CANAL_DELETE_OP: &str = "DELETE";
+
+/// A flat message of [Canal](https://github.com/alibaba/canal) in JSON, which carries all the
+/// rows changed by a statement, e.g. `{"data":[{"id":"1","v":"2"}],"old":[{"v":"1"}],
+/// "database":"test","table":"t","type":"UPDATE","isDdl":false,"pkNames":["id"],"ts":1}`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanalEvent {
+    #[serde(rename = "type")]
+    pub op: String,
+    #[serde(default)]
+    pub is_ddl: bool,
+    pub data: Option<Vec<BTreeMap<String, Value>>>,
+    /// The old values of the changed columns of each row in an update.
+    pub old: Option<Vec<BTreeMap<String, Value>>>,
+}
+
+#[derive(Debug)]
+pub struct CanalJsonParser {}
+
+impl CanalJsonParser {
+    /// The values are in text by default, and in JSON if Canal is configured so.
+    fn value_to_datums(
+        columns: &[SourceColumnDesc],
+        map: &BTreeMap<String, Value>,
+    ) -> Result<Vec<Datum>> {
+        columns
+            .iter()
+            .map(|column| {
+                if column.skip_parse {
+                    return Ok(None);
+                }
+                match map.get(&column.name) {
+                    Some(Value::String(v)) => str_parse_value(&column.data_type, v)
+                        .map(Some)
+                        .map_err(|e| {
+                            anyhow!("failed to parse column '{}' from canal: {}", column.name, e)
+                                .into()
+                        }),
+                    value => json_parse_value(&column.into(), value).map_err(|e| e.into()),
+                }
+            })
+            .collect::<Result<Vec<Datum>>>()
+    }
+}
+
+impl SourceParser for CanalJsonParser {
+    fn parse(&self, payload: &[u8], columns: &[SourceColumnDesc]) -> Result<Event> {
+        let event: CanalEvent = serde_json::from_slice(payload)
+            .map_err(|e| RwError::from(ProtocolError(e.to_string())))?;
+        if event.is_ddl {
+            return Ok(Event::default());
+        }
+        let data = event.data.ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "data is missing for {} event",
+                event.op
+            )))
+        })?;
+
+        let mut result = Event::default();
+        match event.op.as_str() {
+            CANAL_INSERT_OP | CANAL_DELETE_OP => {
+                let op = if event.op == CANAL_INSERT_OP {
+                    Op::Insert
+                } else {
+                    Op::Delete
+                };
+                for row in &data {
+                    result.ops.push(op);
+                    result.rows.push(Self::value_to_datums(columns, row)?);
+                }
+            }
+            CANAL_UPDATE_OP => {
+                let old = event.old.unwrap_or_default();
+                if old.len() != data.len() {
+                    return Err(RwError::from(ProtocolError(format!(
+                        "expect {} old rows in canal update, but found {}",
+                        data.len(),
+                        old.len()
+                    ))));
+                }
+                for (after, old) in data.iter().zip_eq(old) {
+                    let mut before = after.clone();
+                    before.extend(old);
+                    let before = Self::value_to_datums(columns, &before)?;
+                    let after = Self::value_to_datums(columns, after)?;
+                    // Skip the row if none of the columns is changed.
+                    if before == after {
+                        continue;
+                    }
+                    result.ops.extend([Op::UpdateDelete, Op::UpdateInsert]);
+                    result.rows.extend([before, after]);
+                }
+            }
+            _ => {
+                return Err(RwError::from(ProtocolError(format!(
+                    "unknown canal type: {}",
+                    event.op
+                ))))
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use risingwave_common::array::Op;
+    use risingwave_common::catalog::ColumnId;
+    use risingwave_common::types::{DataType, ScalarImpl};
+
+    use crate::parser::canal::json::CanalJsonParser;
+    use crate::{SourceColumnDesc, SourceParser};
+
+    fn get_test_columns() -> Vec<SourceColumnDesc> {
+        vec![
+            SourceColumnDesc {
+                name: "id".to_string(),
+                data_type: DataType::Int32,
+                column_id: ColumnId::from(0),
+                skip_parse: false,
+                fields: vec![],
+            },
+            SourceColumnDesc {
+                name: "name".to_string(),
+                data_type: DataType::Varchar,
+                column_id: ColumnId::from(1),
+                skip_parse: false,
+                fields: vec![],
+            },
+        ]
+    }
+
+    fn row(id: i32, name: &str) -> Vec<Option<ScalarImpl>> {
+        vec![
+            Some(ScalarImpl::Int32(id)),
+            Some(ScalarImpl::Utf8(name.to_string())),
+        ]
+    }
+
+    #[test]
+    fn test_canal_json_parser_insert_and_delete() {
+        let parser = CanalJsonParser {};
+        let columns = get_test_columns();
+
+        let data = r#"{"data":[{"id":"1","name":"a"},{"id":"2","name":"b"}],"database":"test","es":1,"id":1,"isDdl":false,"mysqlType":{"id":"int","name":"varchar(10)"},"old":null,"pkNames":["id"],"sql":"","sqlType":{"id":4,"name":12},"table":"t","ts":1,"type":"INSERT"}"#;
+        let result = parser.parse(data.as_bytes(), &columns).unwrap();
+        assert_eq!(result.ops, vec![Op::Insert, Op::Insert]);
+        assert_eq!(result.rows, vec![row(1, "a"), row(2, "b")]);
+
+        let data = r#"{"data":[{"id":"1","name":null}],"database":"test","isDdl":false,"old":null,"table":"t","ts":1,"type":"DELETE"}"#;
+        let result = parser.parse(data.as_bytes(), &columns).unwrap();
+        assert_eq!(result.ops, vec![Op::Delete]);
+        assert_eq!(result.rows, vec![vec![Some(ScalarImpl::Int32(1)), None]]);
+    }
+
+    #[test]
+    fn test_canal_json_parser_update() {
+        let parser = CanalJsonParser {};
+        let columns = get_test_columns();
+
+        // The name of the second row isn't changed.
+        let data = r#"{"data":[{"id":"1","name":"c","v":"1"},{"id":"2","name":"b","v":"1"}],"database":"test","isDdl":false,"old":[{"name":"a"},{"v":"0"}],"table":"t","ts":1,"type":"UPDATE"}"#;
+        let result = parser.parse(data.as_bytes(), &columns).unwrap();
+        assert_eq!(result.ops, vec![Op::UpdateDelete, Op::UpdateInsert]);
+        assert_eq!(result.rows, vec![row(1, "a"), row(1, "c")]);
+
+        let data = r#"{"data":[{"id":"1","name":"c"}],"database":"test","isDdl":false,"old":[],"table":"t","ts":1,"type":"UPDATE"}"#;
+        assert!(parser.parse(data.as_bytes(), &columns).is_err());
+    }
+
+    #[test]
+    fn test_canal_json_parser_ddl() {
+        let parser = CanalJsonParser {};
+        let columns = get_test_columns();
+
+        let data = r#"{"data":null,"database":"test","isDdl":true,"old":null,"sql":"ALTER TABLE t ADD v int","table":"t","ts":1,"type":"ALTER"}"#;
+        let result = parser.parse(data.as_bytes(), &columns).unwrap();
+        assert!(result.ops.is_empty());
+    }
+}
@@ -17,7 +17,9 @@ use num_traits::FromPrimitive;
 use risingwave_common::array::StructValue;
 use risingwave_common::catalog::ColumnDesc;
 use risingwave_common::types::{DataType, Datum, Decimal, ScalarImpl};
-use risingwave_expr::vector_op::cast::{str_to_date, str_to_time, str_to_timestamp};
+use risingwave_expr::vector_op::cast::{
+    str_parse, str_to_bool, str_to_date, str_to_time, str_to_timestamp, str_to_timestampz,
+};
 use serde_json::Value;
 
 macro_rules! ensure_float {
@@ -79,6 +81,26 @@ fn do_parse_json_value(column: &ColumnDesc, v: &Value) -> Result<ScalarImpl> {
     Ok(v)
 }
 
+/// Parses a value from its text, e.g. the fields of CSV and the values of Canal.
+pub(crate) fn str_parse_value(data_type: &DataType, v: &str) -> Result<ScalarImpl> {
+    let v = match data_type {
+        DataType::Boolean => str_to_bool(v)?.into(),
+        DataType::Int16 => ScalarImpl::Int16(str_parse(v)?),
+        DataType::Int32 => ScalarImpl::Int32(str_parse(v)?),
+        DataType::Int64 => ScalarImpl::Int64(str_parse(v)?),
+        DataType::Float32 => ScalarImpl::Float32(str_parse(v)?),
+        DataType::Float64 => ScalarImpl::Float64(str_parse(v)?),
+        DataType::Decimal => ScalarImpl::Decimal(str_parse(v)?),
+        DataType::Varchar => v.to_string().into(),
+        DataType::Date => str_to_date(v)?.into(),
+        DataType::Time => str_to_time(v)?.into(),
+        DataType::Timestamp => str_to_timestamp(v)?.into(),
+        DataType::Timestampz => ScalarImpl::Int64(str_to_timestampz(v)?),
+        other => return Err(anyhow!("type {:?} can't be parsed from text", other)),
+    };
+    Ok(v)
+}
+
 pub(crate) fn json_parse_value(column: &ColumnDesc, value: Option<&Value>) -> Result<Datum> {
     match value {
         None | Some(Value::Null) => Ok(None),
@@ -0,0 +1,217 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::array::Op;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::{DataType, Datum};
+use risingwave_pb::plan_common::CsvInfo;
+
+use crate::parser::common::str_parse_value;
+use crate::{Event, SourceColumnDesc, SourceParser};
+
+/// Parser for CSV format. A payload may contain many records, e.g. a whole file, each of which is
+/// parsed into an inserted row. The fields of a record are mapped to the columns in order, except
+/// for the columns which skip parsing, and empty fields are parsed as `NULL`.
+#[derive(Debug)]
+pub struct CsvParser {
+    delimiter: u8,
+    quote: u8,
+    has_header: bool,
+}
+
+impl CsvParser {
+    pub fn new(csv_info: &CsvInfo) -> Result<Self> {
+        let to_ascii = |c: u32, name: &str| {
+            u8::try_from(c).ok().filter(u8::is_ascii).ok_or_else(|| {
+                RwError::from(ProtocolError(format!(
+                    "CSV {} must be an ASCII character, found: {}",
+                    name, c
+                )))
+            })
+        };
+        Ok(Self {
+            delimiter: to_ascii(csv_info.delimiter, "delimiter")?,
+            quote: to_ascii(csv_info.quote, "quote")?,
+            has_header: csv_info.has_header,
+        })
+    }
+}
+
+fn csv_parse_value(data_type: &DataType, v: &str) -> anyhow::Result<Datum> {
+    if v.is_empty() {
+        return Ok(None);
+    }
+    str_parse_value(data_type, v).map(Some)
+}
+
+impl CsvParser {
+    /// Parses a payload continuing the previous one in the middle of a file, which never starts
+    /// with the header.
+    pub fn parse_continued(&self, payload: &[u8], columns: &[SourceColumnDesc]) -> Result<Event> {
+        self.parse_records(payload, false, columns)
+    }
+
+    fn parse_records(
+        &self,
+        payload: &[u8],
+        has_header: bool,
+        columns: &[SourceColumnDesc],
+    ) -> Result<Event> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(has_header)
+            .from_reader(payload);
+        let num_fields = columns.iter().filter(|column| !column.skip_parse).count();
+
+        let mut rows = vec![];
+        for record in reader.records() {
+            let record = record.map_err(|e| RwError::from(ProtocolError(e.to_string())))?;
+            if record.len() != num_fields {
+                return Err(RwError::from(ProtocolError(format!(
+                    "expect {} fields in CSV record, but found {}",
+                    num_fields,
+                    record.len()
+                ))));
+            }
+
+            let mut fields = record.iter();
+            let row = columns
+                .iter()
+                .map(|column| {
+                    if column.skip_parse {
+                        return Ok(None);
+                    }
+                    let field = fields.next().unwrap();
+                    csv_parse_value(&column.data_type, field).map_err(|e| {
+                        RwError::from(ProtocolError(format!(
+                            "failed to parse CSV field \"{}\" of column {}: {}",
+                            field, column.name, e
+                        )))
+                    })
+                })
+                .collect::<Result<Vec<Datum>>>()?;
+            rows.push(row);
+        }
+
+        Ok(Event {
+            ops: vec![Op::Insert; rows.len()],
+            rows,
+        })
+    }
+}
+
+impl SourceParser for CsvParser {
+    fn parse(&self, payload: &[u8], columns: &[SourceColumnDesc]) -> Result<Event> {
+        self.parse_records(payload, self.has_header, columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::ColumnId;
+    use risingwave_common::types::ScalarImpl;
+    use risingwave_expr::vector_op::cast::str_to_date;
+
+    use super::*;
+
+    fn test_columns() -> Vec<SourceColumnDesc> {
+        [
+            ("_row_id", DataType::Int64),
+            ("id", DataType::Int32),
+            ("name", DataType::Varchar),
+            ("score", DataType::Float64),
+            ("birthday", DataType::Date),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, data_type))| SourceColumnDesc {
+            name: name.to_string(),
+            data_type,
+            column_id: ColumnId::from(i as i32),
+            fields: vec![],
+            skip_parse: i == 0,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_csv_parser() {
+        let parser = CsvParser::new(&CsvInfo {
+            delimiter: b',' as u32,
+            quote: b'"' as u32,
+            has_header: true,
+        })
+        .unwrap();
+        let payload = b"id,name,score,birthday\n\
+            1,alice,1.5,2000-01-01\n\
+            2,\"bob, jr.\",,\n";
+
+        let event = parser.parse(payload, &test_columns()).unwrap();
+        assert_eq!(event.ops, vec![Op::Insert, Op::Insert]);
+        assert_eq!(
+            event.rows,
+            vec![
+                vec![
+                    None,
+                    Some(ScalarImpl::Int32(1)),
+                    Some(ScalarImpl::Utf8("alice".to_string())),
+                    Some(ScalarImpl::Float64(1.5.into())),
+                    Some(ScalarImpl::NaiveDate(str_to_date("2000-01-01").unwrap())),
+                ],
+                vec![
+                    None,
+                    Some(ScalarImpl::Int32(2)),
+                    Some(ScalarImpl::Utf8("bob, jr.".to_string())),
+                    None,
+                    None,
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_parser_without_header() {
+        let parser = CsvParser::new(&CsvInfo {
+            delimiter: b'|' as u32,
+            quote: b'\'' as u32,
+            has_header: false,
+        })
+        .unwrap();
+
+        let event = parser
+            .parse(b"1|'a|b'|2|2000-01-01", &test_columns())
+            .unwrap();
+        assert_eq!(event.rows.len(), 1);
+        assert_eq!(event.rows[0][2], Some(ScalarImpl::Utf8("a|b".to_string())));
+
+        // A continued payload has no header.
+        let header_parser = CsvParser::new(&CsvInfo {
+            delimiter: b'|' as u32,
+            quote: b'\'' as u32,
+            has_header: true,
+        })
+        .unwrap();
+        let event = header_parser
+            .parse_continued(b"1|a|2|2000-01-01", &test_columns())
+            .unwrap();
+        assert_eq!(event.rows.len(), 1);
+
+        // Too few fields.
+        assert!(parser.parse(b"1|a|2", &test_columns()).is_err());
+        // Not an integer.
+        assert!(parser.parse(b"x|a|2|2000-01-01", &test_columns()).is_err());
+    }
+}
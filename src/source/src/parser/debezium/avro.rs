@@ -0,0 +1,364 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use apache_avro::types::Value;
+use apache_avro::{from_avro_datum, Schema};
+use risingwave_common::array::Op;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::{DataType, Datum};
+
+use crate::parser::avro_parser::{avro_record_to_datums, parse_avro_schema};
+use crate::parser::schema_registry::{
+    client_from_properties, extract_schema_id, ConfluentSchemaResolver, SCHEMA_REGISTRY_KEY,
+};
+use crate::{Event, SourceColumnDesc};
+
+const DEBEZIUM_READ_OP: &str = "r";
+const DEBEZIUM_CREATE_OP: &str = "c";
+const DEBEZIUM_UPDATE_OP: &str = "u";
+const DEBEZIUM_DELETE_OP: &str = "d";
+
+/// Parser for the Debezium envelopes encoded by the Avro converter, whose keys and values are in
+/// the Confluent wire format with the schemas in the registry.
+///
+/// The row before a change may be missing, e.g. in `PostgreSQL` tables without the full replica
+/// identity, in which case the key of the message, i.e. the primary key of the row, is used
+/// instead. The tombstones following the deletes carry no change, and are skipped as the other
+/// messages without payloads.
+#[derive(Debug)]
+pub struct DebeziumAvroParser {
+    /// The schemas of both the keys and the values, which share the ids in the registry.
+    schema_resolver: ConfluentSchemaResolver<Schema>,
+}
+
+impl DebeziumAvroParser {
+    pub fn new(props: &HashMap<String, String>) -> Result<Self> {
+        let url = props.get(SCHEMA_REGISTRY_KEY).ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "'{}' must be specified for the Debezium Avro format",
+                SCHEMA_REGISTRY_KEY
+            )))
+        })?;
+        Ok(Self {
+            schema_resolver: ConfluentSchemaResolver::new(client_from_properties(url, props)?),
+        })
+    }
+
+    async fn decode(&self, payload: &[u8]) -> Result<Value> {
+        let (schema_id, mut data) = extract_schema_id(payload)?;
+        let schema: Arc<Schema> = match self.schema_resolver.get(schema_id) {
+            Some(schema) => schema,
+            None => {
+                let schema = self
+                    .schema_resolver
+                    .client()
+                    .get_schema_by_id(schema_id)
+                    .await?;
+                self.schema_resolver
+                    .insert(schema_id, parse_avro_schema(&schema.schema)?)
+            }
+        };
+        from_avro_datum(&schema, &mut data, None)
+            .map_err(|e| RwError::from(ProtocolError(e.to_string())))
+    }
+
+    /// Returns the row of the `before` or `after` field of the envelope, or of the key if it's
+    /// absent.
+    async fn row(
+        &self,
+        envelope: &[(String, Value)],
+        field: &str,
+        key: Option<&[u8]>,
+        columns: &[SourceColumnDesc],
+    ) -> Result<Vec<Datum>> {
+        match envelope.iter().find(|(name, _)| name == field) {
+            Some((_, value)) => {
+                if let Some(fields) = unwrap_record(value) {
+                    return avro_record_to_datums(&debezium_fields(fields, columns), columns);
+                }
+            }
+            None => {
+                return Err(RwError::from(ProtocolError(format!(
+                    "{} is missing in debezium envelope",
+                    field
+                ))))
+            }
+        }
+        let key = key.ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "{} is null and the message has no key",
+                field
+            )))
+        })?;
+        match self.decode(key).await? {
+            Value::Record(fields) => {
+                avro_record_to_datums(&debezium_fields(&fields, columns), columns)
+            }
+            _ => Err(RwError::from(ProtocolError(
+                "debezium key is not a record".to_string(),
+            ))),
+        }
+    }
+
+    pub async fn parse(
+        &self,
+        payload: &[u8],
+        key: Option<&[u8]>,
+        columns: &[SourceColumnDesc],
+    ) -> Result<Event> {
+        let envelope = match self.decode(payload).await? {
+            Value::Record(fields) => fields,
+            _ => {
+                return Err(RwError::from(ProtocolError(
+                    "debezium envelope is not a record".to_string(),
+                )))
+            }
+        };
+        let op = match envelope.iter().find(|(name, _)| name == "op") {
+            Some((_, Value::String(op))) => op.clone(),
+            _ => {
+                return Err(RwError::from(ProtocolError(
+                    "op is missing in debezium envelope".to_string(),
+                )))
+            }
+        };
+
+        match op.as_str() {
+            DEBEZIUM_CREATE_OP | DEBEZIUM_READ_OP => Ok(Event {
+                ops: vec![Op::Insert],
+                rows: vec![self.row(&envelope, "after", None, columns).await?],
+            }),
+            DEBEZIUM_UPDATE_OP => {
+                let before = self.row(&envelope, "before", key, columns).await?;
+                let after = self.row(&envelope, "after", None, columns).await?;
+                if before == after {
+                    return Ok(Event::default());
+                }
+                Ok(Event {
+                    ops: vec![Op::UpdateDelete, Op::UpdateInsert],
+                    rows: vec![before, after],
+                })
+            }
+            DEBEZIUM_DELETE_OP => Ok(Event {
+                ops: vec![Op::Delete],
+                rows: vec![self.row(&envelope, "before", key, columns).await?],
+            }),
+            _ => Err(RwError::from(ProtocolError(format!(
+                "unknown debezium op: {}",
+                op
+            )))),
+        }
+    }
+}
+
+/// Returns the fields of a record, or of a record in the union with `null`.
+fn unwrap_record(value: &Value) -> Option<&Vec<(String, Value)>> {
+    match value {
+        Value::Record(fields) => Some(fields),
+        Value::Union(_, value) => unwrap_record(value),
+        _ => None,
+    }
+}
+
+/// Debezium encodes the dates and the timestamps in plain `int` days and `long` milliseconds, which
+/// are converted to the logical types here. The unions with `null` are unwrapped as well.
+fn debezium_fields(
+    fields: &[(String, Value)],
+    columns: &[SourceColumnDesc],
+) -> Vec<(String, Value)> {
+    fields
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::Union(_, value) => value.as_ref().clone(),
+                value => value.clone(),
+            };
+            let data_type = columns
+                .iter()
+                .find(|column| &column.name == name)
+                .map(|column| &column.data_type);
+            let value = match (data_type, value) {
+                (Some(DataType::Date), Value::Int(days)) => Value::Date(days),
+                (Some(DataType::Timestamp), Value::Long(millis)) => Value::TimestampMillis(millis),
+                (_, value) => value,
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use apache_avro::to_avro_datum;
+    use apache_avro::types::Record;
+    use maplit::{convert_args, hashmap};
+    use risingwave_common::catalog::ColumnId;
+    use risingwave_common::types::{NaiveDateWrapper, ScalarImpl};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::parser::avro_parser::unix_epoch_days;
+
+    const KEY_SCHEMA: &str =
+        r#"{"type":"record","name":"Key","fields":[{"name":"id","type":"int"}]}"#;
+    const ENVELOPE_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Envelope",
+        "fields": [
+            {"name": "before", "type": ["null", {
+                "type": "record",
+                "name": "Value",
+                "fields": [
+                    {"name": "id", "type": "int"},
+                    {"name": "name", "type": ["null", "string"], "default": null},
+                    {"name": "birthday", "type": ["null", {"type": "int", "connect.name": "io.debezium.time.Date"}], "default": null}
+                ]
+            }], "default": null},
+            {"name": "after", "type": ["null", "Value"], "default": null},
+            {"name": "op", "type": "string"},
+            {"name": "ts_ms", "type": ["null", "long"], "default": null}
+        ]
+    }"#;
+
+    fn columns() -> Vec<SourceColumnDesc> {
+        [
+            ("id", DataType::Int32),
+            ("name", DataType::Varchar),
+            ("birthday", DataType::Date),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, data_type))| SourceColumnDesc {
+            name: name.to_string(),
+            data_type,
+            column_id: ColumnId::from(i as i32),
+            fields: vec![],
+            skip_parse: false,
+        })
+        .collect()
+    }
+
+    fn value(id: i32, name: &str, days: i32) -> Value {
+        Value::Union(
+            1,
+            Box::new(Value::Record(vec![
+                ("id".to_string(), Value::Int(id)),
+                (
+                    "name".to_string(),
+                    Value::Union(1, Box::new(Value::String(name.to_string()))),
+                ),
+                (
+                    "birthday".to_string(),
+                    Value::Union(1, Box::new(Value::Int(days))),
+                ),
+            ])),
+        )
+    }
+
+    fn encode(schema_id: u8, schema: &Schema, value: Value) -> Vec<u8> {
+        let mut payload = vec![0, 0, 0, 0, schema_id];
+        payload.extend(to_avro_datum(schema, value).unwrap());
+        payload
+    }
+
+    fn envelope(schema: &Schema, before: Value, after: Value, op: &str) -> Vec<u8> {
+        let mut record = Record::new(schema).unwrap();
+        record.put("before", before);
+        record.put("after", after);
+        record.put("op", op);
+        record.put("ts_ms", Value::Union(0, Box::new(Value::Null)));
+        encode(2, schema, record.into())
+    }
+
+    #[tokio::test]
+    #[cfg_attr(madsim, ignore)] // MockServer is not supported in simulation.
+    async fn test_debezium_avro_parser() {
+        let server = MockServer::start().await;
+        for (id, schema) in [(1, KEY_SCHEMA), (2, ENVELOPE_SCHEMA)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/schemas/ids/{}", id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "schema": schema })))
+                .mount(&server)
+                .await;
+        }
+        let parser = DebeziumAvroParser::new(&convert_args!(hashmap!(
+            "schema.registry" => server.uri(),
+        )))
+        .unwrap();
+        let key_schema = Schema::parse_str(KEY_SCHEMA).unwrap();
+        let schema = Schema::parse_str(ENVELOPE_SCHEMA).unwrap();
+        let null = || Value::Union(0, Box::new(Value::Null));
+        let row = |id: i32, name: &str, days: i32| {
+            vec![
+                Some(ScalarImpl::Int32(id)),
+                Some(ScalarImpl::Utf8(name.to_string())),
+                Some(ScalarImpl::NaiveDate(
+                    NaiveDateWrapper::with_days(days + unix_epoch_days()).unwrap(),
+                )),
+            ]
+        };
+
+        let event = parser
+            .parse(
+                &envelope(&schema, null(), value(1, "a", 1), "c"),
+                None,
+                &columns(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(event.ops, vec![Op::Insert]);
+        assert_eq!(event.rows, vec![row(1, "a", 1)]);
+
+        let event = parser
+            .parse(
+                &envelope(&schema, value(1, "a", 1), value(1, "b", 1), "u"),
+                None,
+                &columns(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(event.ops, vec![Op::UpdateDelete, Op::UpdateInsert]);
+        assert_eq!(event.rows, vec![row(1, "a", 1), row(1, "b", 1)]);
+
+        // The row before the delete is missing, so the key is used.
+        let key = encode(
+            1,
+            &key_schema,
+            Value::Record(vec![("id".to_string(), Value::Int(1))]),
+        );
+        let event = parser
+            .parse(
+                &envelope(&schema, null(), null(), "d"),
+                Some(&key),
+                &columns(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(event.ops, vec![Op::Delete]);
+        assert_eq!(
+            event.rows,
+            vec![vec![Some(ScalarImpl::Int32(1)), None, None]]
+        );
+        assert!(parser
+            .parse(&envelope(&schema, null(), null(), "d"), None, &columns())
+            .await
+            .is_err());
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use avro::*;
 pub use json::*;
 
+mod avro;
 mod json;
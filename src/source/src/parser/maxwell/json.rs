@@ -0,0 +1,219 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use risingwave_common::array::Op;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use risingwave_common::types::Datum;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parser::common::json_parse_value;
+use crate::{Event, SourceColumnDesc, SourceParser};
+
+const MAXWELL_INSERT_OP: &str = "insert";
+const MAXWELL_BOOTSTRAP_INSERT_OP: &str = "bootstrap-insert";
+const MAXWELL_UPDATE_OP: &str = "update";
+const MAXWELL_DELETE_OP: &str = "delete";
+const MAXWELL_BOOTSTRAP_START_OP: &str = "bootstrap-start";
+const MAXWELL_BOOTSTRAP_COMPLETE_OP: &str = "bootstrap-complete";
+
+/// An event of [Maxwell](https://maxwells-daemon.io/dataformat/), e.g.
+/// `{"database":"test","table":"t","type":"update","ts":1,"data":{"id":1,"v":2},"old":{"v":1}}`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaxwellEvent {
+    #[serde(rename = "type")]
+    pub op: String,
+    pub data: Option<BTreeMap<String, Value>>,
+    /// The old values of the changed columns in an update.
+    pub old: Option<BTreeMap<String, Value>>,
+}
+
+#[derive(Debug)]
+pub struct MaxwellParser {}
+
+impl MaxwellParser {
+    fn value_to_datums(
+        columns: &[SourceColumnDesc],
+        map: &BTreeMap<String, Value>,
+    ) -> Result<Vec<Datum>> {
+        columns
+            .iter()
+            .map(|column| {
+                if column.skip_parse {
+                    Ok(None)
+                } else {
+                    json_parse_value(&column.into(), map.get(&column.name)).map_err(|e| e.into())
+                }
+            })
+            .collect::<Result<Vec<Datum>>>()
+    }
+}
+
+impl SourceParser for MaxwellParser {
+    fn parse(&self, payload: &[u8], columns: &[SourceColumnDesc]) -> Result<Event> {
+        let event: MaxwellEvent = serde_json::from_slice(payload)
+            .map_err(|e| RwError::from(ProtocolError(e.to_string())))?;
+        let data = || {
+            event.data.as_ref().ok_or_else(|| {
+                RwError::from(ProtocolError(format!(
+                    "data is missing for {} event",
+                    event.op
+                )))
+            })
+        };
+
+        match event.op.as_str() {
+            MAXWELL_INSERT_OP | MAXWELL_BOOTSTRAP_INSERT_OP => Ok(Event {
+                ops: vec![Op::Insert],
+                rows: vec![Self::value_to_datums(columns, data()?)?],
+            }),
+            MAXWELL_DELETE_OP => Ok(Event {
+                ops: vec![Op::Delete],
+                rows: vec![Self::value_to_datums(columns, data()?)?],
+            }),
+            MAXWELL_UPDATE_OP => {
+                let after = data()?;
+                // The old values are only of the changed columns.
+                let mut before = after.clone();
+                if let Some(old) = &event.old {
+                    before.extend(old.clone());
+                }
+                let before = Self::value_to_datums(columns, &before)?;
+                let after = Self::value_to_datums(columns, after)?;
+                // Skip the update if none of the columns is changed.
+                if before == after {
+                    return Ok(Event::default());
+                }
+                Ok(Event {
+                    ops: vec![Op::UpdateDelete, Op::UpdateInsert],
+                    rows: vec![before, after],
+                })
+            }
+            MAXWELL_BOOTSTRAP_START_OP | MAXWELL_BOOTSTRAP_COMPLETE_OP => Ok(Event::default()),
+            _ => Err(RwError::from(ProtocolError(format!(
+                "unknown maxwell type: {}",
+                event.op
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use risingwave_common::array::Op;
+    use risingwave_common::catalog::ColumnId;
+    use risingwave_common::types::{DataType, ScalarImpl};
+
+    use crate::parser::maxwell::json::MaxwellParser;
+    use crate::{SourceColumnDesc, SourceParser};
+
+    fn get_test_columns() -> Vec<SourceColumnDesc> {
+        vec![
+            SourceColumnDesc {
+                name: "id".to_string(),
+                data_type: DataType::Int32,
+                column_id: ColumnId::from(0),
+                skip_parse: false,
+                fields: vec![],
+            },
+            SourceColumnDesc {
+                name: "name".to_string(),
+                data_type: DataType::Varchar,
+                column_id: ColumnId::from(1),
+                skip_parse: false,
+                fields: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_maxwell_parser_insert_and_delete() {
+        let parser = MaxwellParser {};
+        let columns = get_test_columns();
+
+        for (data, op) in [
+            (
+                r#"{"database":"test","table":"t","type":"insert","ts":1,"xid":1,"commit":true,"data":{"id":1,"name":"a"}}"#,
+                Op::Insert,
+            ),
+            (
+                r#"{"database":"test","table":"t","type":"bootstrap-insert","ts":1,"data":{"id":1,"name":"a"}}"#,
+                Op::Insert,
+            ),
+            (
+                r#"{"database":"test","table":"t","type":"delete","ts":1,"xid":1,"commit":true,"data":{"id":1,"name":"a"}}"#,
+                Op::Delete,
+            ),
+        ] {
+            let result = parser.parse(data.as_bytes(), &columns).unwrap();
+            assert_eq!(result.ops, vec![op]);
+            assert_eq!(
+                result.rows,
+                vec![vec![
+                    Some(ScalarImpl::Int32(1)),
+                    Some(ScalarImpl::Utf8("a".to_string()))
+                ]]
+            );
+        }
+    }
+
+    #[test]
+    fn test_maxwell_parser_update() {
+        let parser = MaxwellParser {};
+        let columns = get_test_columns();
+
+        let data = r#"{"database":"test","table":"t","type":"update","ts":1,"xid":1,"commit":true,"data":{"id":1,"name":"b","v":2},"old":{"name":"a","v":1}}"#;
+        let result = parser.parse(data.as_bytes(), &columns).unwrap();
+        assert_eq!(result.ops, vec![Op::UpdateDelete, Op::UpdateInsert]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![
+                    Some(ScalarImpl::Int32(1)),
+                    Some(ScalarImpl::Utf8("a".to_string()))
+                ],
+                vec![
+                    Some(ScalarImpl::Int32(1)),
+                    Some(ScalarImpl::Utf8("b".to_string()))
+                ],
+            ]
+        );
+
+        // Only the column not selected is changed.
+        let data = r#"{"database":"test","table":"t","type":"update","ts":1,"data":{"id":1,"name":"b","v":2},"old":{"v":1}}"#;
+        let result = parser.parse(data.as_bytes(), &columns).unwrap();
+        assert!(result.ops.is_empty());
+        assert!(result.rows.is_empty());
+    }
+
+    #[test]
+    fn test_maxwell_parser_other_types() {
+        let parser = MaxwellParser {};
+        let columns = get_test_columns();
+
+        let data = r#"{"database":"test","table":"t","type":"bootstrap-start","ts":1,"data":{}}"#;
+        assert!(parser
+            .parse(data.as_bytes(), &columns)
+            .unwrap()
+            .ops
+            .is_empty());
+
+        let data = r#"{"database":"test","table":"t","type":"table-alter","ts":1}"#;
+        assert!(parser.parse(data.as_bytes(), &columns).is_err());
+    }
+}
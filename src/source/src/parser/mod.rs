@@ -16,22 +16,32 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 
+pub use avro_parser::AvroParser;
+pub use canal::*;
+pub use csv_parser::*;
 pub use debezium::*;
 pub use json_parser::*;
+pub use maxwell::*;
 pub use protobuf_parser::*;
 use risingwave_common::array::Op;
 use risingwave_common::error::ErrorCode::ProtocolError;
 use risingwave_common::error::{Result, RwError};
 use risingwave_common::types::Datum;
+use risingwave_connector::source::SourceMeta;
+use risingwave_pb::plan_common::CsvInfo;
 
-use crate::parser::avro_parser::AvroParser;
+use crate::parser::schema_registry::SCHEMA_REGISTRY_KEY;
 use crate::{SourceColumnDesc, SourceFormat};
 
 mod avro_parser;
+mod canal;
 mod common;
+mod csv_parser;
 mod debezium;
 mod json_parser;
+mod maxwell;
 mod protobuf_parser;
+pub mod schema_registry;
 
 #[derive(Debug, Default)]
 pub struct Event {
@@ -53,16 +63,39 @@ pub enum SourceParserImpl {
     Json(JSONParser),
     Protobuf(ProtobufParser),
     DebeziumJson(DebeziumJsonParser),
+    DebeziumAvro(DebeziumAvroParser),
     Avro(AvroParser),
+    Csv(CsvParser),
+    Maxwell(MaxwellParser),
+    CanalJson(CanalJsonParser),
 }
 
 impl SourceParserImpl {
-    pub fn parse(&self, payload: &[u8], columns: &[SourceColumnDesc]) -> Result<Event> {
+    /// Parses a message. It's async because the parsers with a schema registry may need to fetch
+    /// the schema of the message first. The metadata of the message is only used by the formats
+    /// whose deletes may carry nothing but the key, and by the formats with a header.
+    pub async fn parse(
+        &self,
+        payload: &[u8],
+        meta: &SourceMeta,
+        columns: &[SourceColumnDesc],
+    ) -> Result<Event> {
         match self {
             Self::Json(parser) => parser.parse(payload, columns),
+            Self::Protobuf(parser) if parser.uses_schema_registry() => {
+                parser.parse_confluent(payload, columns).await
+            }
             Self::Protobuf(parser) => parser.parse(payload, columns),
             Self::DebeziumJson(parser) => parser.parse(payload, columns),
+            Self::DebeziumAvro(parser) => parser.parse(payload, meta.key.as_deref(), columns).await,
+            Self::Avro(avro_parser) if avro_parser.uses_schema_registry() => {
+                avro_parser.parse_confluent(payload, columns).await
+            }
             Self::Avro(avro_parser) => avro_parser.parse(payload, columns),
+            Self::Csv(parser) if meta.continued => parser.parse_continued(payload, columns),
+            Self::Csv(parser) => parser.parse(payload, columns),
+            Self::Maxwell(parser) => parser.parse(payload, columns),
+            Self::CanalJson(parser) => parser.parse(payload, columns),
         }
     }
 
@@ -70,6 +103,7 @@ impl SourceParserImpl {
         format: &SourceFormat,
         properties: &HashMap<String, String>,
         schema_location: &str,
+        csv_info: Option<&CsvInfo>,
     ) -> Result<Arc<Self>> {
         const PROTOBUF_MESSAGE_KEY: &str = "proto.message";
         let parser = match format {
@@ -81,11 +115,34 @@ impl SourceParserImpl {
                         PROTOBUF_MESSAGE_KEY
                     )))
                 })?;
-                SourceParserImpl::Protobuf(ProtobufParser::new(schema_location, message_name)?)
+                if properties.contains_key(SCHEMA_REGISTRY_KEY) {
+                    SourceParserImpl::Protobuf(
+                        ProtobufParser::new_with_schema_registry(properties, message_name).await?,
+                    )
+                } else {
+                    SourceParserImpl::Protobuf(ProtobufParser::new(schema_location, message_name)?)
+                }
             }
             SourceFormat::DebeziumJson => SourceParserImpl::DebeziumJson(DebeziumJsonParser {}),
+            SourceFormat::DebeziumAvro => {
+                SourceParserImpl::DebeziumAvro(DebeziumAvroParser::new(properties)?)
+            }
+            SourceFormat::Maxwell => SourceParserImpl::Maxwell(MaxwellParser {}),
+            SourceFormat::CanalJson => SourceParserImpl::CanalJson(CanalJsonParser {}),
             SourceFormat::Avro => {
-                SourceParserImpl::Avro(AvroParser::new(schema_location, properties.clone()).await?)
+                if properties.contains_key(SCHEMA_REGISTRY_KEY) {
+                    SourceParserImpl::Avro(AvroParser::new_with_schema_registry(properties).await?)
+                } else {
+                    SourceParserImpl::Avro(
+                        AvroParser::new(schema_location, properties.clone()).await?,
+                    )
+                }
+            }
+            SourceFormat::Csv => {
+                let csv_info = csv_info.ok_or_else(|| {
+                    RwError::from(ProtocolError("CSV settings not provided".to_string()))
+                })?;
+                SourceParserImpl::Csv(CsvParser::new(csv_info)?)
             }
             _ => {
                 return Err(RwError::from(ProtocolError(
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 use protobuf::descriptor::FileDescriptorSet;
@@ -28,13 +29,24 @@ use serde_protobuf::descriptor::{Descriptors, FieldDescriptor, FieldType};
 use serde_value::Value;
 use url::Url;
 
+use super::schema_registry::{
+    client_from_properties, extract_schema_id, subject_from_properties, ConfluentSchema,
+    ConfluentSchemaResolver, SchemaRegistryClient, SCHEMA_REGISTRY_KEY,
+};
 use crate::{Event, SourceColumnDesc, SourceParser};
 
+/// The name of the file of the schema fetched from the registry, which is compiled along with the
+/// files it imports.
+const CONFLUENT_SCHEMA_FILE: &str = "__confluent_schema.proto";
+
 /// Parser for Protobuf-encoded bytes.
 #[derive(Debug)]
 pub struct ProtobufParser {
     descriptors: Descriptors,
     message_name: String,
+    /// Set if the messages are in the Confluent wire format, in which case each message is decoded
+    /// with the schema it's written in, and `descriptors` are of the latest schema of the subject.
+    schema_resolver: Option<ConfluentSchemaResolver<Descriptors>>,
 }
 
 impl ProtobufParser {
@@ -49,15 +61,20 @@ impl ProtobufParser {
 
     /// Decode payload to `SerdeValue`
     fn decode(&self, data: &[u8]) -> Result<Value> {
+        Self::decode_with(&self.descriptors, &self.message_name, data)
+    }
+
+    fn decode_with(descriptors: &Descriptors, message_name: &str, data: &[u8]) -> Result<Value> {
         let input_stream = protobuf::CodedInputStream::from_bytes(data);
         let mut deserializer =
-            Deserializer::for_named_message(&self.descriptors, &self.message_name, input_stream)
-                .map_err(|e| {
+            Deserializer::for_named_message(descriptors, message_name, input_stream).map_err(
+                |e| {
                     RwError::from(ProtocolError(format!(
                         "Creating an input stream to parse protobuf: {:?}",
                         e
                     )))
-                })?;
+                },
+            )?;
 
         let deserialized_message = Value::deserialize(&mut deserializer).map_err(|e| {
             RwError::from(ProtocolError(format!(
@@ -77,18 +94,110 @@ impl ProtobufParser {
         inputs: &[&Path],
         message_name: &str,
     ) -> Result<Self> {
+        Ok(ProtobufParser {
+            descriptors: Self::compile(includes, inputs)?,
+            message_name: Self::normalize_message_name(message_name),
+            schema_resolver: None,
+        })
+    }
+
+    fn compile(includes: &[&Path], inputs: &[&Path]) -> Result<Descriptors> {
         let parsed_result = protobuf_codegen_pure::parse_and_typecheck(includes, inputs)
             .map_err(|e| RwError::from(ProtocolError(e.to_string())))?;
 
         let mut file_descriptor_set = FileDescriptorSet::new();
         file_descriptor_set.set_file(RepeatedField::from(parsed_result.file_descriptors));
 
+        Ok(Descriptors::from_proto(&file_descriptor_set))
+    }
+
+    /// Create a parser of the messages in the Confluent wire format, with the schemas in the
+    /// registry set by [`SCHEMA_REGISTRY_KEY`].
+    pub async fn new_with_schema_registry(
+        props: &HashMap<String, String>,
+        message_name: &str,
+    ) -> Result<Self> {
+        let url = props.get(SCHEMA_REGISTRY_KEY).ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "'{}' not provided",
+                SCHEMA_REGISTRY_KEY
+            )))
+        })?;
+        let client = client_from_properties(url, props)?;
+        let subject = subject_from_properties(props)?;
+        let latest = client.get_latest_schema(&subject).await?;
         Ok(ProtobufParser {
-            descriptors: Descriptors::from_proto(&file_descriptor_set),
+            descriptors: Self::compile_confluent_schema(&client, latest).await?,
             message_name: Self::normalize_message_name(message_name),
+            schema_resolver: Some(ConfluentSchemaResolver::new(client)),
         })
     }
 
+    /// Compiles a schema from the registry, along with the schemas it references, which are
+    /// written to a temporary directory as the files it imports.
+    async fn compile_confluent_schema(
+        client: &SchemaRegistryClient,
+        schema: ConfluentSchema,
+    ) -> Result<Descriptors> {
+        let dir = tempfile::tempdir()?;
+        let write_file = |name: &str, content: &str| -> Result<()> {
+            let path = dir.path().join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+            Ok(())
+        };
+
+        let mut references = schema.references.clone();
+        let mut written = HashSet::new();
+        while let Some(reference) = references.pop() {
+            if !written.insert(reference.name.clone()) {
+                continue;
+            }
+            let referenced = client
+                .get_schema_by_subject(&reference.subject, &reference.version.to_string())
+                .await?;
+            write_file(&reference.name, &referenced.schema)?;
+            references.extend(referenced.references);
+        }
+        write_file(CONFLUENT_SCHEMA_FILE, &schema.schema)?;
+
+        Self::compile(
+            &[dir.path()],
+            &[dir.path().join(CONFLUENT_SCHEMA_FILE).as_path()],
+        )
+    }
+
+    pub fn uses_schema_registry(&self) -> bool {
+        self.schema_resolver.is_some()
+    }
+
+    /// Parses a message in the Confluent wire format, fetching its schema from the registry if
+    /// it's the first message written in the schema.
+    pub async fn parse_confluent(
+        &self,
+        payload: &[u8],
+        columns: &[SourceColumnDesc],
+    ) -> Result<Event> {
+        let resolver = self
+            .schema_resolver
+            .as_ref()
+            .ok_or_else(|| RwError::from(InternalError("schema registry not set".to_string())))?;
+        let (schema_id, data) = extract_schema_id(payload)?;
+        let data = skip_message_indexes(data)?;
+        let descriptors = match resolver.get(schema_id) {
+            Some(descriptors) => descriptors,
+            None => {
+                let schema = resolver.client().get_schema_by_id(schema_id).await?;
+                let descriptors = Self::compile_confluent_schema(resolver.client(), schema).await?;
+                resolver.insert(schema_id, descriptors)
+            }
+        };
+        let value = Self::decode_with(&descriptors, &self.message_name, data)?;
+        protobuf_value_to_event(value, columns)
+    }
+
     /// Create a protobuf parser from a URL.
     pub fn new(location: &str, message_name: &str) -> Result<Self> {
         let url = Url::parse(location)
@@ -216,76 +325,99 @@ fn protobuf_type_mapping(f: &FieldDescriptor, descriptors: &Descriptors) -> Resu
     Ok(t)
 }
 
+/// Skips the indexes of the message type in the schema, which follow the schema id in the
+/// Confluent wire format. They're encoded as zigzag varints of the count and then the indexes, or
+/// a single 0 for the first message type. The message type is given by its name instead.
+fn skip_message_indexes(data: &[u8]) -> Result<&[u8]> {
+    let mut input = protobuf::CodedInputStream::from_bytes(data);
+    let read_err = |e: protobuf::ProtobufError| {
+        RwError::from(ProtocolError(format!(
+            "failed to read message indexes: {}",
+            e
+        )))
+    };
+    let count = input.read_sint32().map_err(read_err)?;
+    for _ in 0..count {
+        input.read_sint32().map_err(read_err)?;
+    }
+    let pos = input.pos() as usize;
+    Ok(&data[pos..])
+}
+
 impl SourceParser for ProtobufParser {
     fn parse(&self, payload: &[u8], columns: &[SourceColumnDesc]) -> Result<Event> {
-        let mut map = match self.decode(payload)? {
-            Value::Map(m) => m,
-            _ => return Err(RwError::from(ProtocolError("".to_string()))),
-        };
+        protobuf_value_to_event(self.decode(payload)?, columns)
+    }
+}
 
-        let row = columns.iter().map(|column| {
-            if column.skip_parse {
-                return None;
-            }
+fn protobuf_value_to_event(value: Value, columns: &[SourceColumnDesc]) -> Result<Event> {
+    let mut map = match value {
+        Value::Map(m) => m,
+        _ => return Err(RwError::from(ProtocolError("".to_string()))),
+    };
 
-            let key = Value::String(column.name.clone());
+    let row = columns.iter().map(|column| {
+        if column.skip_parse {
+            return None;
+        }
 
-            // Use `remove` instead of `get` to take the ownership of the value
-            let value = map.remove(&key);
-            match column.data_type {
-                DataType::Boolean => {
-                    protobuf_match_type!(value, ScalarImpl::Bool, { Bool }, bool)
-                }
-                DataType::Int16 => {
-                    protobuf_match_type!(value, ScalarImpl::Int16, { I8, I16, U8 }, i16)
-                }
-                DataType::Int32 => {
-                    protobuf_match_type!(value, ScalarImpl::Int32, { I8, I16, I32, U8, U16 }, i32)
-                }
-                DataType::Int64 => {
-                    protobuf_match_type!(value, ScalarImpl::Int64, { I8, I16, I32, I64, U8, U16, U32 }, i64)
-                }
-                DataType::Float32 => {
-                    protobuf_match_type!(value, ScalarImpl::Float32, { I8, I16, U8, U16, F32 }, OrderedF32)
-                }
-                DataType::Float64 => {
-                    protobuf_match_type!(value, ScalarImpl::Float64, { I8, I16, I32, U8, U16, U32, F32, F64}, OrderedF64)
-                }
-                DataType::Decimal => {
-                    protobuf_match_type!(value, ScalarImpl::Decimal, { I8, I16, I32, I64, U8, U16, U32, U64}, Decimal)
-                }
-                DataType::Varchar => {
-                    protobuf_match_type!(value, ScalarImpl::Utf8, { String }, String)
-                }
-                DataType::Date => {
-                    value.and_then(|v| match v {
+        let key = Value::String(column.name.clone());
+
+        // Use `remove` instead of `get` to take the ownership of the value
+        let value = map.remove(&key);
+        match column.data_type {
+            DataType::Boolean => {
+                protobuf_match_type!(value, ScalarImpl::Bool, { Bool }, bool)
+            }
+            DataType::Int16 => {
+                protobuf_match_type!(value, ScalarImpl::Int16, { I8, I16, U8 }, i16)
+            }
+            DataType::Int32 => {
+                protobuf_match_type!(value, ScalarImpl::Int32, { I8, I16, I32, U8, U16 }, i32)
+            }
+            DataType::Int64 => {
+                protobuf_match_type!(value, ScalarImpl::Int64, { I8, I16, I32, I64, U8, U16, U32 }, i64)
+            }
+            DataType::Float32 => {
+                protobuf_match_type!(value, ScalarImpl::Float32, { I8, I16, U8, U16, F32 }, OrderedF32)
+            }
+            DataType::Float64 => {
+                protobuf_match_type!(value, ScalarImpl::Float64, { I8, I16, I32, U8, U16, U32, F32, F64}, OrderedF64)
+            }
+            DataType::Decimal => {
+                protobuf_match_type!(value, ScalarImpl::Decimal, { I8, I16, I32, I64, U8, U16, U32, U64}, Decimal)
+            }
+            DataType::Varchar => {
+                protobuf_match_type!(value, ScalarImpl::Utf8, { String }, String)
+            }
+            DataType::Date => {
+                value.and_then(|v| match v {
+                    Value::String(b) => str_to_date(&b).ok(),
+                    Value::Option(Some(boxed_value)) => match *boxed_value {
                         Value::String(b) => str_to_date(&b).ok(),
-                        Value::Option(Some(boxed_value)) => match *boxed_value {
-                            Value::String(b) => str_to_date(&b).ok(),
-                            _ => None,
-                        }
                         _ => None,
-                    }).map(ScalarImpl::NaiveDate)
-                }
-                DataType::Timestamp =>{
-                    value.and_then(|v| match v {
+                    }
+                    _ => None,
+                }).map(ScalarImpl::NaiveDate)
+            }
+            DataType::Timestamp =>{
+                value.and_then(|v| match v {
+                    Value::String(b) => str_to_timestamp(&b).ok(),
+                    Value::Option(Some(boxed_value)) => match *boxed_value {
                         Value::String(b) => str_to_timestamp(&b).ok(),
-                        Value::Option(Some(boxed_value)) => match *boxed_value {
-                            Value::String(b) => str_to_timestamp(&b).ok(),
-                            _ => None,
-                        }
                         _ => None,
-                    }).map(ScalarImpl::NaiveDateTime)
-                }
-                _ => unimplemented!(),
+                    }
+                    _ => None,
+                }).map(ScalarImpl::NaiveDateTime)
             }
-        }).collect::<Vec<Datum>>();
+            _ => unimplemented!(),
+        }
+    }).collect::<Vec<Datum>>();
 
-        Ok(Event {
-            ops: vec![Op::Insert],
-            rows: vec![row],
-        })
-    }
+    Ok(Event {
+        ops: vec![Op::Insert],
+        rows: vec![row],
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,141 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support of the [Confluent Schema Registry](https://docs.confluent.io/platform/current/schema-registry/index.html),
+//! from which the Avro and Protobuf parsers fetch the schemas of the messages in the Confluent wire
+//! format.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+pub use risingwave_connector::schema_registry::{
+    ConfluentSchema, SchemaRegistryClient, SCHEMA_REGISTRY_KEY,
+};
+use risingwave_connector::schema_registry::{SchemaRegistryAuth, MAGIC_BYTE};
+
+/// The subject whose latest version is the schema of the source. It's `<topic>-value` by default,
+/// following the default subject name strategy of Confluent.
+pub const SCHEMA_REGISTRY_SUBJECT_KEY: &str = "schema.registry.subject";
+
+/// Splits a message in the Confluent wire format into the id of its schema and the encoded data.
+/// The message starts with a zero magic byte, followed by the schema id in 4 bytes of big endian.
+pub fn extract_schema_id(payload: &[u8]) -> Result<(i32, &[u8])> {
+    match payload {
+        [MAGIC_BYTE, b0, b1, b2, b3, data @ ..] => {
+            Ok((i32::from_be_bytes([*b0, *b1, *b2, *b3]), data))
+        }
+        _ => Err(RwError::from(ProtocolError(
+            "message is not in the Confluent wire format".to_string(),
+        ))),
+    }
+}
+
+/// Returns the subject of the source, set by [`SCHEMA_REGISTRY_SUBJECT_KEY`] or derived from the
+/// topic.
+pub fn subject_from_properties(properties: &HashMap<String, String>) -> Result<String> {
+    if let Some(subject) = properties.get(SCHEMA_REGISTRY_SUBJECT_KEY) {
+        return Ok(subject.clone());
+    }
+    properties
+        .get("kafka.topic")
+        .or_else(|| properties.get("topic"))
+        .map(|topic| format!("{}-value", topic))
+        .ok_or_else(|| {
+            RwError::from(ProtocolError(format!(
+                "'{}' must be specified if the source has no topic",
+                SCHEMA_REGISTRY_SUBJECT_KEY
+            )))
+        })
+}
+
+/// Creates the client of the registry at `url`, with the credentials of the basic authentication
+/// in the properties if any.
+pub fn client_from_properties(
+    url: &str,
+    properties: &HashMap<String, String>,
+) -> Result<SchemaRegistryClient> {
+    let auth = SchemaRegistryAuth::from_properties(properties)
+        .map_err(|e| RwError::from(ProtocolError(e.to_string())))?;
+    Ok(SchemaRegistryClient::new(url, auth.as_ref()))
+}
+
+/// Fetches the schemas of the messages from the registry, and keeps them after being compiled.
+/// A schema never changes once registered, so the compiled ones are never evicted.
+#[derive(Debug)]
+pub struct ConfluentSchemaResolver<T> {
+    client: SchemaRegistryClient,
+    schemas: RwLock<HashMap<i32, Arc<T>>>,
+}
+
+impl<T> ConfluentSchemaResolver<T> {
+    pub fn new(client: SchemaRegistryClient) -> Self {
+        Self {
+            client,
+            schemas: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn client(&self) -> &SchemaRegistryClient {
+        &self.client
+    }
+
+    pub fn get(&self, id: i32) -> Option<Arc<T>> {
+        self.schemas.read().get(&id).cloned()
+    }
+
+    pub fn insert(&self, id: i32, schema: T) -> Arc<T> {
+        self.schemas
+            .write()
+            .entry(id)
+            .or_insert_with(|| Arc::new(schema))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::{convert_args, hashmap};
+
+    use super::*;
+
+    #[test]
+    fn test_extract_schema_id() {
+        let (id, data) = extract_schema_id(b"\x00\x00\x00\x01\x02abc").unwrap();
+        assert_eq!(id, 258);
+        assert_eq!(data, b"abc");
+
+        assert!(extract_schema_id(b"\x01\x00\x00\x00\x01abc").is_err());
+        assert!(extract_schema_id(b"\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_subject_from_properties() {
+        assert_eq!(
+            subject_from_properties(&convert_args!(hashmap!("kafka.topic" => "t"))).unwrap(),
+            "t-value"
+        );
+        assert_eq!(
+            subject_from_properties(&convert_args!(hashmap!(
+                "kafka.topic" => "t",
+                "schema.registry.subject" => "s",
+            )))
+            .unwrap(),
+            "s"
+        );
+        assert!(subject_from_properties(&HashMap::new()).is_err());
+    }
+}
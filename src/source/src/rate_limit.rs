@@ -0,0 +1,177 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Throttling of the rows read from the sources.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use risingwave_common::error::ErrorCode::ProtocolError;
+use risingwave_common::error::{Result, RwError};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// The option in the WITH clause of `CREATE SOURCE` and `CREATE MATERIALIZED VIEW` to limit the
+/// rows per second read from the source. The limit applies to each parallel reader of the source
+/// rather than the whole source, since the splits are assigned to the readers by the meta, so the
+/// source may read up to `parallelism` times the limit.
+pub const STREAMING_RATE_LIMIT: &str = "streaming_rate_limit";
+
+/// Returns the rows per second set by [`STREAMING_RATE_LIMIT`], or `None` for no limit.
+pub fn rate_limit_from_properties(properties: &HashMap<String, String>) -> Result<Option<u32>> {
+    match properties.get(STREAMING_RATE_LIMIT) {
+        None => Ok(None),
+        Some(value) => match value.parse::<u32>() {
+            Ok(rate_limit) if rate_limit > 0 => Ok(Some(rate_limit)),
+            _ => Err(RwError::from(ProtocolError(format!(
+                "{} must be a positive integer, found: \"{}\"",
+                STREAMING_RATE_LIMIT, value
+            )))),
+        },
+    }
+}
+
+/// Limits the rows per second read by a source reader, which can be changed while the reader is
+/// waiting. The reader stops pulling messages while it waits, so the external system is not read
+/// ahead beyond the buffer of the reader.
+pub struct RateLimiter {
+    state: Mutex<RateLimiterState>,
+    /// Wakes up the waiting reader when the limit changes.
+    changed: Notify,
+}
+
+struct RateLimiterState {
+    rate_limit: Option<u32>,
+    /// When the rows reserved before are all paid for.
+    next_available: Instant,
+}
+
+impl Debug for RateLimiter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("rate_limit", &self.rate_limit())
+            .finish()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(rate_limit: Option<u32>) -> Self {
+        Self {
+            state: Mutex::new(RateLimiterState {
+                rate_limit,
+                next_available: Instant::now(),
+            }),
+            changed: Notify::new(),
+        }
+    }
+
+    pub fn rate_limit(&self) -> Option<u32> {
+        self.state.lock().rate_limit
+    }
+
+    /// Changes the limit, which takes effect on the waiting reader immediately.
+    pub fn set_rate_limit(&self, rate_limit: Option<u32>) {
+        let mut state = self.state.lock();
+        state.rate_limit = rate_limit;
+        state.next_available = Instant::now();
+        drop(state);
+        self.changed.notify_waiters();
+    }
+
+    /// Reserves `rows` under the limit, and returns when the ones reserved before are paid for.
+    /// Returns `None` if there's no limit.
+    fn reserve(&self, rows: usize) -> Option<Instant> {
+        let mut state = self.state.lock();
+        let rate_limit = state.rate_limit?;
+        let start = state.next_available.max(Instant::now());
+        state.next_available = start + Duration::from_secs_f64(rows as f64 / rate_limit as f64);
+        Some(start)
+    }
+
+    /// Waits until `rows` can be emitted under the limit.
+    pub async fn wait(&self, rows: usize) {
+        loop {
+            // Registered before reserving, so that a change in between is not missed.
+            let changed = self.changed.notified();
+            let deadline = match self.reserve(rows) {
+                Some(deadline) => deadline,
+                None => return,
+            };
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => return,
+                // Reserve again under the new limit.
+                _ = changed => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use maplit::{convert_args, hashmap};
+
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_from_properties() {
+        assert_eq!(rate_limit_from_properties(&HashMap::new()).unwrap(), None);
+        assert_eq!(
+            rate_limit_from_properties(&convert_args!(hashmap!(
+                "streaming_rate_limit" => "1000",
+            )))
+            .unwrap(),
+            Some(1000)
+        );
+        for invalid in ["0", "-1", "fast"] {
+            assert!(rate_limit_from_properties(&convert_args!(hashmap!(
+                "streaming_rate_limit" => invalid,
+            )))
+            .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = Arc::new(RateLimiter::new(Some(1000)));
+        let start = Instant::now();
+        // The first rows are emitted at once, and the next ones wait for them to be paid for.
+        limiter.wait(100).await;
+        limiter.wait(100).await;
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // The next rows wait for 100 seconds, until the limit is removed.
+        limiter.set_rate_limit(Some(1));
+        limiter.wait(100).await;
+        let waiter = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.wait(1).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        limiter.set_rate_limit(None);
+        tokio::time::timeout(Duration::from_secs(10), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -125,6 +125,7 @@ impl StreamSourceReader for TableV2StreamReader {
         Ok(StreamChunkWithState {
             chunk,
             split_offset_mapping: None,
+            dead_letters: vec![],
         })
     }
 }
@@ -938,6 +938,8 @@ pub enum Statement {
         /// A SQL query that specifies what to explain
         statement: Box<Statement>,
     },
+    /// EXPLAIN ANALYZE MATERIALIZED VIEW, to show the runtime statistics of the actors
+    ExplainAnalyzeMaterializedView { name: ObjectName },
     /// CREATE USER
     CreateUser(CreateUserStatement),
     /// ALTER USER
@@ -981,6 +983,9 @@ impl fmt::Display for Statement {
 
                 write!(f, "{}", statement)
             }
+            Statement::ExplainAnalyzeMaterializedView { name } => {
+                write!(f, "EXPLAIN ANALYZE MATERIALIZED VIEW {}", name)
+            }
             Statement::Query(s) => write!(f, "{}", s),
             Statement::Truncate { table_name } => {
                 write!(f, "TRUNCATE TABLE {}", table_name)?;
@@ -19,6 +19,7 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use super::ObjectType;
+use crate::ast::value::escape_single_quote_string;
 use crate::ast::{
     display_comma_separated, display_separated, ColumnDef, ObjectName, SqlOption, TableConstraint,
 };
@@ -85,8 +86,13 @@ pub struct CreateSourceStatement {
 pub enum SourceSchema {
     Protobuf(ProtobufSchema),
     // Keyword::PROTOBUF ProtobufSchema
-    Json,         // Keyword::JSON
-    DebeziumJson, // Keyword::DEBEZIUM_JSON
+    Json,             // Keyword::JSON
+    DebeziumJson,     // Keyword::DEBEZIUM_JSON
+    DebeziumAvro,     // Keyword::DEBEZIUM_AVRO
+    Avro(AvroSchema), // Keyword::AVRO AvroSchema
+    Csv(CsvInfo),     // Keyword::CSV CsvInfo
+    Maxwell,          // Keyword::MAXWELL
+    CanalJson,        // Keyword::CANAL_JSON
 }
 
 impl ParseTo for SourceSchema {
@@ -98,9 +104,23 @@ impl ParseTo for SourceSchema {
             SourceSchema::Protobuf(protobuf_schema)
         } else if p.parse_keywords(&[Keyword::DEBEZIUM_JSON]) {
             SourceSchema::DebeziumJson
+        } else if p.parse_keywords(&[Keyword::DEBEZIUM_AVRO]) {
+            SourceSchema::DebeziumAvro
+        } else if p.parse_keywords(&[Keyword::AVRO]) {
+            impl_parse_to!(avro_schema: AvroSchema, p);
+            SourceSchema::Avro(avro_schema)
+        } else if p.parse_keywords(&[Keyword::CSV]) {
+            impl_parse_to!(csv_info: CsvInfo, p);
+            SourceSchema::Csv(csv_info)
+        } else if p.parse_keywords(&[Keyword::MAXWELL]) {
+            SourceSchema::Maxwell
+        } else if p.parse_keywords(&[Keyword::CANAL_JSON]) {
+            SourceSchema::CanalJson
         } else {
             return Err(ParserError::ParserError(
-                "expected JSON | PROTOBUF after ROW FORMAT".to_string(),
+                "expected JSON | PROTOBUF | DEBEZIUM_JSON | DEBEZIUM_AVRO | AVRO | CSV | MAXWELL | \
+                 CANAL_JSON after ROW FORMAT"
+                    .to_string(),
             ));
         };
         Ok(schema)
@@ -113,29 +133,114 @@ impl fmt::Display for SourceSchema {
             SourceSchema::Protobuf(protobuf_schema) => write!(f, "PROTOBUF {}", protobuf_schema),
             SourceSchema::Json => write!(f, "JSON"),
             SourceSchema::DebeziumJson => write!(f, "DEBEZIUM JSON"),
+            SourceSchema::DebeziumAvro => write!(f, "DEBEZIUM_AVRO"),
+            SourceSchema::Avro(avro_schema) => write!(f, "AVRO{}", avro_schema),
+            SourceSchema::Csv(csv_info) => write!(f, "CSV{}", csv_info),
+            SourceSchema::Maxwell => write!(f, "MAXWELL"),
+            SourceSchema::CanalJson => write!(f, "CANAL_JSON"),
         }
     }
 }
 
+/// The settings of `ROW FORMAT CSV [WITHOUT HEADER] [DELIMITED BY 'c'] [QUOTE 'c']`. The records
+/// have a header line, and are delimited by `,` and quoted by `"` by default.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CsvInfo {
+    pub has_header: bool,
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Default for CsvInfo {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+}
+
+/// Parses a single ASCII character literal, e.g. the delimiter of CSV.
+fn parse_ascii_char(p: &mut Parser, name: &str) -> Result<u8, ParserError> {
+    let s = p.parse_literal_string()?;
+    match s.as_bytes() {
+        [c] if c.is_ascii() => Ok(*c),
+        _ => Err(ParserError::ParserError(format!(
+            "{} must be a single ASCII character, found: '{}'",
+            name, s
+        ))),
+    }
+}
+
+impl ParseTo for CsvInfo {
+    fn parse_to(p: &mut Parser) -> Result<Self, ParserError> {
+        let mut csv_info = CsvInfo::default();
+        if p.parse_keywords(&[Keyword::WITHOUT, Keyword::HEADER]) {
+            csv_info.has_header = false;
+        }
+        if p.parse_keywords(&[Keyword::DELIMITED, Keyword::BY]) {
+            csv_info.delimiter = parse_ascii_char(p, "delimiter")?;
+        }
+        if p.parse_keywords(&[Keyword::QUOTE]) {
+            csv_info.quote = parse_ascii_char(p, "quote")?;
+        }
+        Ok(csv_info)
+    }
+}
+
+impl fmt::Display for CsvInfo {
+    /// Only the settings different from the defaults are displayed, each led by a space.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let default = CsvInfo::default();
+        if !self.has_header {
+            write!(f, " WITHOUT HEADER")?;
+        }
+        if self.delimiter != default.delimiter {
+            let delimiter = (self.delimiter as char).to_string();
+            write!(
+                f,
+                " DELIMITED BY '{}'",
+                escape_single_quote_string(&delimiter)
+            )?;
+        }
+        if self.quote != default.quote {
+            let quote = (self.quote as char).to_string();
+            write!(f, " QUOTE '{}'", escape_single_quote_string(&quote))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the optional `ROW SCHEMA LOCATION 'location'`, which may be omitted if the schema is
+/// fetched from the schema registry set in the WITH clause.
+fn parse_row_schema_location(p: &mut Parser) -> Result<Option<AstString>, ParserError> {
+    if p.parse_keywords(&[Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION]) {
+        Ok(Some(AstString::parse_to(p)?))
+    } else {
+        Ok(None)
+    }
+}
+
 // sql_grammar!(ProtobufSchema {
 //     [Keyword::MESSAGE],
 //     message_name: AstString,
-//     [Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION],
-//     row_schema_location: AstString,
+//     [Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION]?,
+//     row_schema_location: AstString?,
 // });
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProtobufSchema {
     pub message_name: AstString,
-    pub row_schema_location: AstString,
+    pub row_schema_location: Option<AstString>,
 }
 
 impl ParseTo for ProtobufSchema {
     fn parse_to(p: &mut Parser) -> Result<Self, ParserError> {
         impl_parse_to!([Keyword::MESSAGE], p);
         impl_parse_to!(message_name: AstString, p);
-        impl_parse_to!([Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION], p);
-        impl_parse_to!(row_schema_location: AstString, p);
+        let row_schema_location = parse_row_schema_location(p)?;
         Ok(Self {
             message_name,
             row_schema_location,
@@ -148,12 +253,43 @@ impl fmt::Display for ProtobufSchema {
         let mut v: Vec<String> = vec![];
         impl_fmt_display!([Keyword::MESSAGE], v);
         impl_fmt_display!(message_name, v, self);
-        impl_fmt_display!([Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION], v);
-        impl_fmt_display!(row_schema_location, v, self);
+        if let Some(row_schema_location) = &self.row_schema_location {
+            impl_fmt_display!([Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION], v);
+            v.push(row_schema_location.to_string());
+        }
         v.iter().join(" ").fmt(f)
     }
 }
 
+// sql_grammar!(AvroSchema {
+//     [Keyword::ROW, Keyword::SCHEMA, Keyword::LOCATION]?,
+//     row_schema_location: AstString?,
+// });
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AvroSchema {
+    pub row_schema_location: Option<AstString>,
+}
+
+impl ParseTo for AvroSchema {
+    fn parse_to(p: &mut Parser) -> Result<Self, ParserError> {
+        let row_schema_location = parse_row_schema_location(p)?;
+        Ok(Self {
+            row_schema_location,
+        })
+    }
+}
+
+impl fmt::Display for AvroSchema {
+    /// The location is led by a space if it's present.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(row_schema_location) = &self.row_schema_location {
+            write!(f, " ROW SCHEMA LOCATION {}", row_schema_location)?;
+        }
+        Ok(())
+    }
+}
+
 impl ParseTo for CreateSourceStatement {
     fn parse_to(p: &mut Parser) -> Result<Self, ParserError> {
         impl_parse_to!(if_not_exists => [Keyword::IF, Keyword::NOT, Keyword::EXISTS], p);
@@ -106,6 +106,7 @@ define_keywords!(
     CACHE,
     CALL,
     CALLED,
+    CANAL_JSON,
     CARDINALITY,
     CASCADE,
     CASCADED,
@@ -168,12 +169,14 @@ define_keywords!(
     DATE,
     DAY,
     DEALLOCATE,
+    DEBEZIUM_AVRO,
     DEBEZIUM_JSON,
     DEC,
     DECIMAL,
     DECLARE,
     DEFAULT,
     DELETE,
+    DELIMITED,
     DENSE_RANK,
     DEREF,
     DESC,
@@ -277,6 +280,7 @@ define_keywords!(
     MATCH,
     MATERIALIZED,
     MAX,
+    MAXWELL,
     MEMBER,
     MERGE,
     MESSAGE,
@@ -353,6 +357,7 @@ define_keywords!(
     PROCEDURE,
     PROTOBUF,
     PURGE,
+    QUOTE,
     RANGE,
     RANK,
     RCFILE,
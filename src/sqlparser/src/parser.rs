@@ -2362,6 +2362,10 @@ impl Parser {
 
     pub fn parse_explain(&mut self, describe_alias: bool) -> Result<Statement, ParserError> {
         let analyze = self.parse_keyword(Keyword::ANALYZE);
+        if analyze && self.parse_keywords(&[Keyword::MATERIALIZED, Keyword::VIEW]) {
+            let name = self.parse_object_name()?;
+            return Ok(Statement::ExplainAnalyzeMaterializedView { name });
+        }
         let verbose = self.parse_keyword(Keyword::VERBOSE);
         let trace = self.parse_keyword(Keyword::TRACE);
 
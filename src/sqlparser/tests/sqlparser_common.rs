@@ -1790,6 +1790,16 @@ fn parse_explain_analyze_with_simple_select() {
     );
 }
 
+#[test]
+fn parse_explain_analyze_materialized_view() {
+    match verified_stmt("EXPLAIN ANALYZE MATERIALIZED VIEW s.mv") {
+        Statement::ExplainAnalyzeMaterializedView { name } => {
+            assert_eq!(name, ObjectName(vec![Ident::new("s"), Ident::new("mv")]));
+        }
+        _ => panic!("Unexpected Statement, must be ExplainAnalyzeMaterializedView"),
+    }
+}
+
 #[test]
 fn parse_named_argument_function() {
     let sql = "SELECT FUN(a => '1', b => '2') FROM foo";
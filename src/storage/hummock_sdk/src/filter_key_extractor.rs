@@ -519,6 +519,7 @@ mod tests {
                 String::from("300"),
             )]),
             read_pattern_prefix_column: column_count, // 1 column
+            statistics: None,
         }
     }
 
@@ -15,9 +15,10 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use itertools::Itertools;
 use risingwave_common::array::{Op, Row, StreamChunk};
-use risingwave_common::catalog::{Schema, TableId};
-use risingwave_common::types::Datum;
+use risingwave_common::catalog::{Field, Schema, TableId};
+use risingwave_common::types::{DataType, Datum, ScalarImpl};
 use risingwave_common::util::ordered::{OrderedRow, OrderedRowDeserializer};
 use risingwave_common::util::sort_util::{OrderPair, OrderType};
 use risingwave_storage::StateStore;
@@ -43,6 +44,7 @@ impl<S: StateStore> GroupTopNExecutor<S> {
         executor_id: u64,
         key_indices: Vec<usize>,
         group_by: Vec<usize>,
+        output_rank: bool,
     ) -> StreamExecutorResult<Self> {
         let info = input.info();
         let schema = input.schema().clone();
@@ -61,6 +63,7 @@ impl<S: StateStore> GroupTopNExecutor<S> {
                 executor_id,
                 key_indices,
                 group_by,
+                output_rank,
             )?,
         })
     }
@@ -96,6 +99,9 @@ pub struct InnerGroupTopNExecutorNew<S: StateStore> {
     /// group key -> cache for this group
     caches: HashMap<Vec<Datum>, TopNCache>,
 
+    /// Whether the row number of each row in its group is output after the input columns.
+    output_rank: bool,
+
     #[expect(dead_code)]
     /// Indices of the columns on which key distribution depends.
     key_indices: Vec<usize>,
@@ -115,6 +121,7 @@ impl<S: StateStore> InnerGroupTopNExecutorNew<S> {
         executor_id: u64,
         key_indices: Vec<usize>,
         group_by: Vec<usize>,
+        output_rank: bool,
     ) -> StreamExecutorResult<Self> {
         let (internal_key_indices, internal_key_data_types, internal_key_order_types) =
             generate_internal_key(&order_pairs, &pk_indices, &schema);
@@ -137,11 +144,18 @@ impl<S: StateStore> InnerGroupTopNExecutorNew<S> {
             internal_key_indices.clone(),
         );
 
+        let mut schema = schema;
+        if output_rank {
+            schema
+                .fields
+                .push(Field::with_name(DataType::Int64, "row_number"));
+        }
+
         Ok(Self {
             info: ExecutorInfo {
                 schema: input_info.schema,
                 pk_indices: input_info.pk_indices,
-                identity: format!("TopNExecutorNew {:X}", executor_id),
+                identity: format!("GroupTopNExecutor {:X}", executor_id),
             },
             schema,
             offset: offset_and_limit.0,
@@ -153,9 +167,17 @@ impl<S: StateStore> InnerGroupTopNExecutorNew<S> {
             key_indices,
             group_by,
             caches: HashMap::new(),
+            output_rank,
         })
     }
 
+    /// Returns the row followed by its row number, given its index in the result set.
+    fn with_rank(&self, row: Row, idx: usize) -> Row {
+        let mut datums = row.0;
+        datums.push(Some(ScalarImpl::Int64((self.offset + idx + 1) as i64)));
+        Row::new(datums)
+    }
+
     async fn flush_inner(&mut self, epoch: u64) -> StreamExecutorResult<()> {
         self.managed_state.flush(epoch).await
     }
@@ -186,8 +208,10 @@ impl<S: StateStore> TopNExecutorBase for InnerGroupTopNExecutorNew<S> {
         chunk: StreamChunk,
         epoch: u64,
     ) -> StreamExecutorResult<StreamChunk> {
-        let mut res_ops = Vec::with_capacity(self.limit.unwrap_or(1024));
-        let mut res_rows = Vec::with_capacity(self.limit.unwrap_or(1024));
+        let mut res_ops = Vec::with_capacity(chunk.cardinality());
+        let mut res_rows = Vec::with_capacity(chunk.cardinality());
+        // group key -> result set of the group before the chunk, if the row numbers are output.
+        let mut old_results: HashMap<Vec<Datum>, Vec<Row>> = HashMap::new();
 
         for (op, row_ref) in chunk.rows() {
             let pk_row = row_ref.row_by_indices(&self.internal_key_indices);
@@ -216,6 +240,10 @@ impl<S: StateStore> TopNExecutorBase for InnerGroupTopNExecutorNew<S> {
             self.caches
                 .entry(group_key.clone())
                 .or_insert_with(|| TopNCache::new(self.offset, self.limit.unwrap_or(1024)));
+            if self.output_rank && !old_results.contains_key(&group_key) {
+                let old_result = self.caches[&group_key].middle.values().cloned().collect();
+                old_results.insert(group_key.clone(), old_result);
+            }
 
             // update the corresponding rows in the group cache.
             let pk_prefix = Row::new(group_key);
@@ -234,6 +262,39 @@ impl<S: StateStore> TopNExecutorBase for InnerGroupTopNExecutorNew<S> {
                 )
                 .await?;
         }
+
+        if self.output_rank {
+            // The changes of the cache are replaced by the ones of the rows whose row numbers are
+            // changed. All the deletes are emitted before the inserts, so that a row moved to
+            // another place is not inserted before the old one is deleted.
+            res_ops.clear();
+            res_rows.clear();
+            let mut inserted_rows = vec![];
+            for (group_key, old_result) in old_results {
+                let new_result = self.caches[&group_key]
+                    .middle
+                    .values()
+                    .cloned()
+                    .collect_vec();
+                for idx in 0..old_result.len().max(new_result.len()) {
+                    let old_row = old_result.get(idx);
+                    let new_row = new_result.get(idx);
+                    if old_row == new_row {
+                        continue;
+                    }
+                    if let Some(old_row) = old_row {
+                        res_ops.push(Op::Delete);
+                        res_rows.push(self.with_rank(old_row.clone(), idx));
+                    }
+                    if let Some(new_row) = new_row {
+                        inserted_rows.push(self.with_rank(new_row.clone(), idx));
+                    }
+                }
+            }
+            res_ops.extend(inserted_rows.iter().map(|_| Op::Insert));
+            res_rows.extend(inserted_rows);
+        }
+
         // compare the those two ranges and emit the differantial result
         generate_output(res_rows, res_ops, &self.schema)
     }
@@ -375,6 +436,7 @@ mod tests {
         test_with_offset_and_with_limits().await;
         test_without_limits().await;
         test_multi_group_key().await;
+        test_output_rank().await;
     }
     async fn test_without_offset_and_with_limits() {
         let order_types = create_order_pairs();
@@ -391,6 +453,7 @@ mod tests {
                 1,
                 vec![],
                 vec![1],
+                false,
             )
             .unwrap(),
         );
@@ -475,6 +538,7 @@ mod tests {
                 1,
                 vec![],
                 vec![1],
+                false,
             )
             .unwrap(),
         );
@@ -553,6 +617,7 @@ mod tests {
                 1,
                 vec![],
                 vec![1],
+                false,
             )
             .unwrap(),
         );
@@ -638,6 +703,7 @@ mod tests {
                 1,
                 vec![],
                 vec![1, 2],
+                false,
             )
             .unwrap(),
         );
@@ -708,4 +774,81 @@ mod tests {
             ),
         );
     }
+
+    async fn test_output_rank() {
+        let source = Box::new(MockSource::with_messages(
+            create_schema(),
+            PkIndices::new(),
+            vec![
+                Message::Barrier(Barrier::new_test_barrier(1)),
+                Message::Chunk(StreamChunk::from_pretty(
+                    "  I I I
+                    + 10 1 2
+                    +  9 1 3
+                    +  8 9 1",
+                )),
+                Message::Barrier(Barrier::new_test_barrier(2)),
+                Message::Chunk(StreamChunk::from_pretty(
+                    "  I I I
+                    +  7 1 1
+                    -  8 9 1",
+                )),
+                Message::Barrier(Barrier::new_test_barrier(3)),
+            ],
+        ));
+        let top_n_executor = Box::new(
+            GroupTopNExecutor::new(
+                source as Box<dyn Executor>,
+                create_order_pairs(),
+                (0, Some(2)),
+                vec![],
+                MemoryStateStore::new(),
+                TableId::from(0x2333),
+                0,
+                1,
+                vec![],
+                vec![1],
+                true,
+            )
+            .unwrap(),
+        );
+        let mut top_n_executor = top_n_executor.execute();
+
+        // consume the init barrier
+        top_n_executor.next().await.unwrap().unwrap();
+        let res = top_n_executor.next().await.unwrap().unwrap();
+        compare_stream_chunk(
+            res.as_chunk().unwrap(),
+            &StreamChunk::from_pretty(
+                "  I I I I
+                + 10 1 2 1
+                +  9 1 3 2
+                +  8 9 1 1",
+            ),
+        );
+
+        // barrier
+        assert_matches!(
+            top_n_executor.next().await.unwrap().unwrap(),
+            Message::Barrier(_)
+        );
+        // The rows after the new one are moved down, and the last one is out of the result set.
+        let res = top_n_executor.next().await.unwrap().unwrap();
+        let chunk = res.as_chunk().unwrap();
+        compare_stream_chunk(
+            chunk,
+            &StreamChunk::from_pretty(
+                "  I I I I
+                - 10 1 2 1
+                -  9 1 3 2
+                -  8 9 1 1
+                +  7 1 1 1
+                + 10 1 2 2",
+            ),
+        );
+        assert_eq!(
+            chunk.ops(),
+            &[Op::Delete, Op::Delete, Op::Delete, Op::Insert, Op::Insert]
+        );
+    }
 }
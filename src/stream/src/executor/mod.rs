@@ -36,7 +36,7 @@ use risingwave_pb::stream_plan::update_mutation::{DispatcherUpdate, MergeUpdate}
 use risingwave_pb::stream_plan::{
     AddMutation, Barrier as ProstBarrier, Dispatcher as ProstDispatcher, PauseMutation,
     ResumeMutation, SourceChangeSplitMutation, StopMutation, StreamMessage as ProstStreamMessage,
-    UpdateMutation,
+    ThrottleMutation, UpdateMutation,
 };
 use smallvec::SmallVec;
 use tracing::trace_span;
@@ -94,6 +94,7 @@ pub use error::StreamExecutorResult;
 pub use expand::ExpandExecutor;
 pub use filter::FilterExecutor;
 pub use global_simple_agg::GlobalSimpleAggExecutor;
+pub use group_top_n::GroupTopNExecutor;
 pub use hash_agg::HashAggExecutor;
 pub use hash_join::*;
 pub use hop_window::HopWindowExecutor;
@@ -196,6 +197,8 @@ pub enum Mutation {
     SourceChangeSplit(HashMap<ActorId, ConnectorState>),
     Pause,
     Resume,
+    /// The new rate limits of the sources of the actors, where `None` removes the limit.
+    Throttle(HashMap<ActorId, Option<u32>>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -381,6 +384,12 @@ impl Mutation {
             }
             Mutation::Pause => ProstMutation::Pause(PauseMutation {}),
             Mutation::Resume => ProstMutation::Resume(ResumeMutation {}),
+            Mutation::Throttle(changes) => ProstMutation::Throttle(ThrottleMutation {
+                actor_throttle: changes
+                    .iter()
+                    .map(|(&actor_id, rate_limit)| (actor_id, rate_limit.unwrap_or(0)))
+                    .collect(),
+            }),
         }
     }
 
@@ -448,6 +457,18 @@ impl Mutation {
             }
             ProstMutation::Pause(_) => Mutation::Pause,
             ProstMutation::Resume(_) => Mutation::Resume,
+            ProstMutation::Throttle(throttle) => Mutation::Throttle(
+                throttle
+                    .actor_throttle
+                    .iter()
+                    .map(|(&actor_id, &rate_limit)| {
+                        (
+                            actor_id,
+                            Some(rate_limit).filter(|rate_limit| *rate_limit > 0),
+                        )
+                    })
+                    .collect(),
+            ),
         };
         Ok(mutation)
     }
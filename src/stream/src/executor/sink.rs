@@ -12,16 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use core::default::Default;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
+use bytes::Bytes;
 use futures::StreamExt;
 use futures_async_stream::try_stream;
 use risingwave_common::catalog::Schema;
 use risingwave_connector::sink::{Sink, SinkConfig, SinkImpl};
-use risingwave_storage::StateStore;
+use risingwave_storage::storage_value::StorageValue;
+use risingwave_storage::store::{ReadOptions, WriteOptions};
+use risingwave_storage::{Keyspace, StateStore};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
 use super::error::{StreamExecutorError, StreamExecutorResult};
 use super::{BoxedExecutor, Executor, Message};
@@ -30,39 +33,149 @@ use crate::executor::PkIndices;
 
 pub struct SinkExecutor<S: StateStore> {
     input: BoxedExecutor,
-    _store: S,
+    prepared_txns: PreparedTransactions<S>,
     metrics: Arc<StreamingMetrics>,
     properties: HashMap<String, String>,
     identity: String,
     pk_indices: PkIndices,
+    hidden_column_indices: Vec<usize>,
 }
 
-async fn build_sink(config: SinkConfig) -> StreamExecutorResult<Box<SinkImpl>> {
+async fn build_sink(
+    config: SinkConfig,
+    pk_indices: PkIndices,
+    hidden_column_indices: Vec<usize>,
+) -> StreamExecutorResult<Box<SinkImpl>> {
     Ok(Box::new(
-        SinkImpl::new(config)
+        SinkImpl::new(config, pk_indices, hidden_column_indices)
             .await
             .map_err(StreamExecutorError::sink_error)?,
     ))
 }
 
+/// The transactions prepared by a sink which supports the two-phase commit, keyed by their
+/// epochs. A transaction is persisted along with the checkpoint of its epoch, so that after
+/// recovery, the persisted transactions are exactly those to be committed.
+#[derive(Clone)]
+struct PreparedTransactions<S: StateStore> {
+    keyspace: Keyspace<S>,
+}
+
+impl<S: StateStore> PreparedTransactions<S> {
+    async fn load(&self, epoch: u64) -> StreamExecutorResult<Vec<(u64, Bytes)>> {
+        let pairs = self
+            .keyspace
+            .scan(
+                None,
+                ReadOptions {
+                    epoch,
+                    table_id: Some(self.keyspace.table_id()),
+                    retention_seconds: None,
+                },
+            )
+            .await?;
+        Ok(pairs
+            .into_iter()
+            .map(|(key, metadata)| {
+                let txn_epoch = u64::from_be_bytes(key.as_ref().try_into().unwrap());
+                (txn_epoch, metadata)
+            })
+            .collect())
+    }
+
+    /// Persists the transaction `prepared` and removes the `committed` ones in `epoch`.
+    async fn update(
+        &self,
+        epoch: u64,
+        prepared: Option<&(u64, Bytes)>,
+        committed: &[u64],
+    ) -> StreamExecutorResult<()> {
+        if prepared.is_none() && committed.is_empty() {
+            return Ok(());
+        }
+        let mut write_batch = self.keyspace.state_store().start_write_batch(WriteOptions {
+            epoch,
+            table_id: self.keyspace.table_id(),
+        });
+        let mut local_batch = write_batch.prefixify(&self.keyspace);
+        for txn_epoch in committed {
+            local_batch.delete(txn_epoch.to_be_bytes());
+        }
+        if let Some((txn_epoch, metadata)) = prepared {
+            local_batch.put(
+                txn_epoch.to_be_bytes(),
+                StorageValue::new_default_put(metadata.clone()),
+            );
+        }
+        write_batch.ingest().await?;
+        Ok(())
+    }
+
+    /// Waits until `epoch` is checkpointed.
+    async fn wait_epoch(&self, epoch: u64) -> StreamExecutorResult<()> {
+        self.keyspace.state_store().wait_epoch(epoch).await?;
+        Ok(())
+    }
+}
+
+/// Commits the transactions prepared by the sink executor once their epochs are checkpointed, and
+/// sends the committed epochs back to the executor, which removes them from the state store. It
+/// runs in the background with its own sink, so that the barriers are not blocked by the
+/// checkpoints.
+async fn commit_prepared_transactions<S: StateStore>(
+    mut sink: Box<SinkImpl>,
+    prepared_txns: PreparedTransactions<S>,
+    mut txn_rx: UnboundedReceiver<(u64, Bytes)>,
+    committed_tx: UnboundedSender<StreamExecutorResult<u64>>,
+    metrics: Arc<StreamingMetrics>,
+    identity: String,
+    connector: &'static str,
+) {
+    while let Some((txn_epoch, metadata)) = txn_rx.recv().await {
+        let result = async {
+            prepared_txns.wait_epoch(txn_epoch).await?;
+            let start_time = Instant::now();
+            sink.commit_prepared(txn_epoch, &metadata)
+                .await
+                .map_err(StreamExecutorError::sink_error)?;
+            metrics
+                .sink_commit_duration
+                .with_label_values(&[identity.as_str(), connector])
+                .observe(start_time.elapsed().as_millis() as f64);
+            Ok::<_, StreamExecutorError>(txn_epoch)
+        }
+        .await;
+        let failed = result.is_err();
+        // The executor has exited if the channel is closed.
+        if committed_tx.send(result).is_err() || failed {
+            return;
+        }
+    }
+}
+
 impl<S: StateStore> SinkExecutor<S> {
     pub fn new(
         materialize_executor: BoxedExecutor,
-        _store: S,
+        keyspace: Keyspace<S>,
         metrics: Arc<StreamingMetrics>,
         mut properties: HashMap<String, String>,
         executor_id: u64,
+        pk_indices: PkIndices,
+        hidden_column_indices: Vec<usize>,
     ) -> Self {
         // This field can be used to distinguish a specific actor in parallelism to prevent
         // transaction execution errors
         properties.insert("identifier".to_string(), format!("sink-{:?}", executor_id));
         Self {
             input: materialize_executor,
-            _store,
+            prepared_txns: PreparedTransactions {
+                keyspace: keyspace.append(executor_id.to_be_bytes().to_vec()),
+            },
             metrics,
             properties,
             identity: format!("SinkExecutor_{:?}", executor_id),
-            pk_indices: Default::default(), // todo
+            pk_indices,
+            hidden_column_indices,
         }
     }
 
@@ -71,15 +184,42 @@ impl<S: StateStore> SinkExecutor<S> {
         let sink_config = SinkConfig::from_hashmap(self.properties.clone())
             .map_err(StreamExecutorError::sink_error)?;
 
-        let mut sink = build_sink(sink_config.clone())
-            .await
-            .map_err(StreamExecutorError::sink_error)?;
+        let mut sink = build_sink(
+            sink_config.clone(),
+            self.pk_indices.clone(),
+            self.hidden_column_indices.clone(),
+        )
+        .await
+        .map_err(StreamExecutorError::sink_error)?;
+        let two_phase_commit = sink.support_two_phase_commit();
 
         // the flag is required because kafka transaction requires at least one
         // message, so we should abort the transaction if the flag is true.
         let mut empty_epoch_flag = true;
         let mut in_transaction = false;
         let mut epoch = 0;
+        let mut is_first_barrier = true;
+        // The prepared transactions are sent to the committer, which sends back the committed
+        // epochs.
+        let (txn_tx, txn_rx) = unbounded_channel();
+        let (committed_tx, mut committed_rx) = unbounded_channel();
+        if two_phase_commit {
+            let committer = build_sink(
+                sink_config.clone(),
+                self.pk_indices.clone(),
+                self.hidden_column_indices.clone(),
+            )
+            .await?;
+            tokio::spawn(commit_prepared_transactions(
+                committer,
+                self.prepared_txns.clone(),
+                txn_rx,
+                committed_tx,
+                self.metrics.clone(),
+                self.identity.clone(),
+                sink_config.get_connector(),
+            ));
+        }
 
         let schema = self.schema().clone();
 
@@ -112,6 +252,25 @@ impl<S: StateStore> SinkExecutor<S> {
                     yield Message::Chunk(chunk);
                 }
                 Message::Barrier(barrier) => {
+                    let mut committed = vec![];
+                    if is_first_barrier && two_phase_commit {
+                        // The transactions persisted before the failure belong to the checkpointed
+                        // epochs, so they're committed, and the others are aborted.
+                        for (txn_epoch, metadata) in
+                            self.prepared_txns.load(barrier.epoch.prev).await?
+                        {
+                            sink.commit_prepared(txn_epoch, &metadata)
+                                .await
+                                .map_err(StreamExecutorError::sink_error)?;
+                            committed.push(txn_epoch);
+                        }
+                        sink.abort_prepared()
+                            .await
+                            .map_err(StreamExecutorError::sink_error)?;
+                    }
+                    is_first_barrier = false;
+
+                    let mut prepared_txn = None;
                     if in_transaction {
                         if empty_epoch_flag {
                             sink.abort()
@@ -121,6 +280,12 @@ impl<S: StateStore> SinkExecutor<S> {
                                 "transaction abort due to empty epoch, epoch: {:?}",
                                 epoch
                             );
+                        } else if two_phase_commit {
+                            let metadata = sink
+                                .pre_commit()
+                                .await
+                                .map_err(StreamExecutorError::sink_error)?;
+                            prepared_txn = Some((epoch, Bytes::from(metadata)));
                         } else {
                             let start_time = Instant::now();
                             sink.commit()
@@ -135,6 +300,21 @@ impl<S: StateStore> SinkExecutor<S> {
                                 .observe(start_time.elapsed().as_millis() as f64);
                         }
                     }
+
+                    if two_phase_commit {
+                        while let Ok(txn_epoch) = committed_rx.try_recv() {
+                            committed.push(txn_epoch?);
+                        }
+                        self.prepared_txns
+                            .update(barrier.epoch.prev, prepared_txn.as_ref(), &committed)
+                            .await?;
+                        if let Some(txn) = prepared_txn {
+                            txn_tx.send(txn).map_err(|_| {
+                                StreamExecutorError::channel_closed("sink committer")
+                            })?;
+                        }
+                    }
+
                     in_transaction = false;
                     empty_epoch_flag = true;
                     epoch = barrier.epoch.curr;
@@ -166,7 +346,13 @@ impl<S: StateStore> Executor for SinkExecutor<S> {
 #[cfg(test)]
 mod test {
 
+    use maplit::hashmap;
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::array::StreamChunk;
+    use risingwave_common::catalog::{Field, TableId};
+    use risingwave_common::types::DataType;
     use risingwave_connector::sink::mysql::{MySQLConfig, MySQLSink};
+    use risingwave_storage::memory::MemoryStateStore;
 
     use super::*;
     use crate::executor::test_utils::*;
@@ -181,11 +367,72 @@ mod test {
             password: Some(String::from("<password>")),
         };
 
-        let _mysql_sink = MySQLSink::new(cfg);
+        let _mysql_sink = MySQLSink::new(cfg, vec![]);
 
         // Mock `child`
         let _mock = MockSource::with_messages(Schema::default(), PkIndices::new(), vec![]);
 
         // let _sink_executor = SinkExecutor::_new(Box::new(mock), mysql_sink);
     }
+
+    #[tokio::test]
+    async fn test_two_phase_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let properties = hashmap! {
+            "connector".to_string() => "file".to_string(),
+            "file.path".to_string() => format!("disk://{}", dir.path().display()),
+            "file.format".to_string() => "json".to_string(),
+        };
+        let schema = Schema::new(vec![Field::unnamed(DataType::Int64)]);
+        let keyspace = Keyspace::table_root(MemoryStateStore::new(), &TableId::new(1));
+        let data_file = dir.path().join("00000000000000000001-sink-1.jsonl");
+        let manifest = dir
+            .path()
+            .join("_manifest/00000000000000000001-sink-1.json");
+
+        let new_sink_executor = |source| {
+            Box::new(SinkExecutor::new(
+                Box::new(source),
+                keyspace.clone(),
+                Arc::new(StreamingMetrics::unused()),
+                properties.clone(),
+                1,
+                vec![0],
+                vec![],
+            ))
+            .execute()
+        };
+
+        let (mut tx, source) = MockSource::channel(schema.clone(), vec![0]);
+        let mut sink_executor = new_sink_executor(source);
+        tx.push_barrier(1, false);
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I
+            + 1",
+        ));
+        tx.push_barrier(2, false);
+        for _ in 0..3 {
+            sink_executor.next().await.unwrap().unwrap();
+        }
+        // The transaction is prepared at the barrier, and committed in the background once its
+        // epoch is checkpointed, which is immediate for the memory state store.
+        assert!(data_file.exists());
+        for _ in 0..100 {
+            if manifest.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(manifest.exists());
+
+        // The sink fails before the committed transaction is removed from the state store, so
+        // it's committed again after recovery.
+        drop(sink_executor);
+        std::fs::remove_file(&manifest).unwrap();
+        let (mut tx, source) = MockSource::channel(schema, vec![0]);
+        let mut sink_executor = new_sink_executor(source);
+        tx.push_barrier(3, false);
+        sink_executor.next().await.unwrap().unwrap();
+        assert!(manifest.exists());
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
@@ -25,10 +26,12 @@ use risingwave_common::bail;
 use risingwave_common::catalog::{ColumnId, Schema, TableId};
 use risingwave_common::error::Result;
 use risingwave_common::util::epoch::UNIX_SINGULARITY_DATE_EPOCH;
-use risingwave_connector::source::{ConnectorState, SplitImpl, SplitMetaData};
+use risingwave_connector::source::{ConnectorState, OffsetCommitter, SplitImpl, SplitMetaData};
 use risingwave_source::connector_source::SourceContext;
+use risingwave_source::rate_limit::RateLimiter;
 use risingwave_source::row_id::RowIdGenerator;
 use risingwave_source::*;
+use risingwave_storage::table::state_table::RowBasedStateTable;
 use risingwave_storage::{Keyspace, StateStore};
 use tokio::sync::mpsc::UnboundedReceiver;
 
@@ -70,6 +73,20 @@ pub struct SourceExecutor<S: StateStore> {
 
     state_cache: HashMap<String, SplitImpl>,
 
+    /// The senders of the committed offsets of the splits whose readers acknowledge them, e.g. the
+    /// replication slots of postgres.
+    offset_committers: HashMap<String, OffsetCommitter>,
+
+    /// The latest offsets of the splits in `offset_committers` in the current epoch.
+    uncommitted_offsets: HashMap<String, String>,
+
+    /// The table to write the messages that failed to be parsed, if the error policy of the
+    /// source is `dead_letter`.
+    dead_letter_table: Option<RowBasedStateTable<S>>,
+
+    /// Limits the rows per second read by the reader, which is changed by the throttle mutations.
+    rate_limiter: Arc<RateLimiter>,
+
     #[expect(dead_code)]
     /// Expected barrier latency
     expected_barrier_latency_ms: u64,
@@ -83,6 +100,8 @@ impl<S: StateStore> SourceExecutor<S> {
         source_desc: SourceDesc,
         vnodes: Bitmap,
         keyspace: Keyspace<S>,
+        dead_letter_table: Option<RowBasedStateTable<S>>,
+        rate_limit: Option<u32>,
         column_ids: Vec<ColumnId>,
         schema: Schema,
         pk_indices: PkIndices,
@@ -113,6 +132,10 @@ impl<S: StateStore> SourceExecutor<S> {
             source_identify: "Table_".to_string() + &source_id.table_id().to_string(),
             split_state_store: SourceStateHandler::new(keyspace),
             state_cache: HashMap::new(),
+            offset_committers: HashMap::new(),
+            uncommitted_offsets: HashMap::new(),
+            dead_letter_table,
+            rate_limiter: Arc::new(RateLimiter::new(rate_limit)),
             expected_barrier_latency_ms,
         })
     }
@@ -174,25 +197,39 @@ impl<S: StateStore> SourceExecutor<S> {
 }
 
 impl<S: StateStore> SourceExecutor<S> {
-    fn get_diff(&self, rhs: ConnectorState) -> ConnectorState {
-        // rhs can not be None because we do not support split number reduction
+    /// Returns the splits to read with their latest offsets if the assignment changes. The
+    /// offsets are in the cache if the splits are polled in this epoch, or in the state store.
+    async fn get_diff(
+        &self,
+        rhs: ConnectorState,
+        epoch: u64,
+    ) -> StreamExecutorResult<Option<Vec<SplitImpl>>> {
+        // `None` if all the splits of the actor are dropped.
+        let split_change = rhs.unwrap_or_default();
+        let current_split_ids: HashSet<_> = self
+            .stream_source_splits
+            .iter()
+            .map(SplitImpl::id)
+            .collect();
+        let target_split_ids: HashSet<_> = split_change.iter().map(SplitImpl::id).collect();
+        if current_split_ids == target_split_ids {
+            return Ok(None);
+        }
 
-        let split_change = rhs.unwrap();
         let mut target_state: Vec<SplitImpl> = Vec::with_capacity(split_change.len());
-        let mut no_change_flag = true;
-        for sc in &split_change {
-            // SplitImpl is identified by its id, target_state always follows offsets in cache
-            // here we introduce a hypothesis that every split is polled at least once in one epoch
-            match self.state_cache.get(&sc.id()) {
-                Some(s) => target_state.push(s.clone()),
-                None => {
-                    no_change_flag = false;
-                    target_state.push(sc.clone())
-                }
-            }
+        for sc in split_change {
+            let split = match self.state_cache.get(&sc.id()) {
+                Some(s) => s.clone(),
+                None => self
+                    .split_state_store
+                    .try_recover_from_state_store(&sc, epoch)
+                    .await?
+                    .unwrap_or(sc),
+            };
+            target_state.push(split);
         }
 
-        (!no_change_flag).then_some(target_state)
+        Ok(Some(target_state))
     }
 
     async fn take_snapshot(&mut self, epoch: u64) -> StreamExecutorResult<()> {
@@ -209,6 +246,30 @@ impl<S: StateStore> SourceExecutor<S> {
         Ok(())
     }
 
+    /// Sends the offsets of the epoch to the readers once it's committed, so that the external
+    /// source keeps the data after them for recovery until then.
+    fn commit_offsets(&mut self, epoch: u64) {
+        if self.uncommitted_offsets.is_empty() {
+            return;
+        }
+        let offsets = std::mem::take(&mut self.uncommitted_offsets);
+        let committers = self.offset_committers.clone();
+        let state_store = self.split_state_store.clone();
+        tokio::spawn(async move {
+            if let Err(e) = state_store.wait_epoch(epoch).await {
+                tracing::warn!("failed to wait for epoch {}: {}", epoch, e);
+                return;
+            }
+            for (split_id, offset) in offsets {
+                // The reader may have been replaced, in which case the new one continues from
+                // the committed offset anyway.
+                if let Some(committer) = committers.get(&split_id) {
+                    committer.send(offset).ok();
+                }
+            }
+        });
+    }
+
     async fn build_stream_source_reader(
         &mut self,
         state: ConnectorState,
@@ -223,12 +284,14 @@ impl<S: StateStore> SourceExecutor<S> {
                     state,
                     self.column_ids.clone(),
                     self.source_desc.metrics.clone(),
-                    SourceContext::new(self.actor_id as u32, self.source_id),
+                    SourceContext::new(self.actor_id as u32, self.source_id)
+                        .with_rate_limiter(self.rate_limiter.clone()),
                 )
                 .await
                 .map(SourceStreamReaderImpl::Connector),
         }
         .map_err(StreamExecutorError::source_error)?;
+        self.offset_committers = reader.offset_committers();
 
         Ok(Box::new(reader))
     }
@@ -283,12 +346,18 @@ impl<S: StateStore> SourceExecutor<S> {
                     let barrier = barrier?;
                     let epoch = barrier.epoch.prev;
                     self.take_snapshot(epoch).await?;
+                    self.commit_offsets(epoch);
+                    if let Some(table) = &mut self.dead_letter_table {
+                        table.commit(epoch).await?;
+                    }
 
                     if let Some(mutation) = barrier.mutation.as_deref() {
                         match mutation {
                             Mutation::SourceChangeSplit(mapping) => {
                                 if let Some(target_splits) = mapping.get(&self.actor_id).cloned() {
-                                    if let Some(target_state) = self.get_diff(target_splits) {
+                                    if let Some(target_state) =
+                                        self.get_diff(target_splits, epoch).await?
+                                    {
                                         log::info!(
                                             "actor {:?} apply source split change to {:?}",
                                             self.actor_id,
@@ -298,7 +367,10 @@ impl<S: StateStore> SourceExecutor<S> {
                                         // Replace the source reader with a new one of the new
                                         // state.
                                         let reader = self
-                                            .build_stream_source_reader(Some(target_state.clone()))
+                                            .build_stream_source_reader(
+                                                (!target_state.is_empty())
+                                                    .then(|| target_state.clone()),
+                                            )
                                             .await?;
                                         stream.replace_source_chunk_reader(reader);
 
@@ -308,6 +380,16 @@ impl<S: StateStore> SourceExecutor<S> {
                             }
                             Mutation::Pause => stream.pause_source(),
                             Mutation::Resume => stream.resume_source(),
+                            Mutation::Throttle(actor_throttle) => {
+                                if let Some(rate_limit) = actor_throttle.get(&self.actor_id) {
+                                    log::info!(
+                                        "actor {:?} change the rate limit of the source to {:?}",
+                                        self.actor_id,
+                                        rate_limit
+                                    );
+                                    self.rate_limiter.set_rate_limit(*rate_limit);
+                                }
+                            }
                             _ => {}
                         }
                     }
@@ -319,9 +401,22 @@ impl<S: StateStore> SourceExecutor<S> {
                     let StreamChunkWithState {
                         mut chunk,
                         split_offset_mapping,
+                        dead_letters,
                     } = chunk_with_state?;
 
+                    if let Some(table) = &mut self.dead_letter_table {
+                        for dead_letter in &dead_letters {
+                            table.insert(dead_letter.to_row())?;
+                        }
+                    }
+
                     if let Some(mapping) = split_offset_mapping {
+                        for (split, offset) in &mapping {
+                            if self.offset_committers.contains_key(split) {
+                                self.uncommitted_offsets
+                                    .insert(split.clone(), offset.clone());
+                            }
+                        }
                         let state: HashMap<String, SplitImpl> = mapping
                             .iter()
                             .map(|(split, offset)| {
@@ -340,7 +435,7 @@ impl<S: StateStore> SourceExecutor<S> {
                                 } else {
                                     Ok::<_, StreamExecutorError>((
                                         split.clone(),
-                                        origin_split_impl[0].update(offset.clone()),
+                                        origin_split_impl[0].update(offset.clone())?,
                                     ))
                                 }
                             })
@@ -489,6 +584,8 @@ mod tests {
             source_desc,
             vnodes,
             keyspace,
+            None,
+            None,
             column_ids,
             schema,
             pk_indices,
@@ -607,6 +704,8 @@ mod tests {
             source_desc,
             vnodes,
             keyspace,
+            None,
+            None,
             column_ids,
             schema,
             pk_indices,
@@ -678,6 +777,7 @@ mod tests {
             row_id_index: 0,
             columns,
             pk_column_ids: vec![0],
+            csv_info: None,
         }
     }
 
@@ -731,6 +831,8 @@ mod tests {
             source_desc,
             vnodes,
             keyspace.clone(),
+            None,
+            None,
             column_ids.clone(),
             schema,
             pk_indices,
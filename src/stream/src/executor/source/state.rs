@@ -52,6 +52,12 @@ impl<S: StateStore> SourceStateHandler<S> {
         Self { keyspace }
     }
 
+    /// Waits until the states of `epoch` are committed.
+    pub async fn wait_epoch(&self, epoch: u64) -> StreamExecutorResult<()> {
+        self.keyspace.state_store().wait_epoch(epoch).await?;
+        Ok(())
+    }
+
     /// This function provides the ability to persist the source state
     /// and needs to be invoked by the ``SourceReader`` to call it,
     /// and will return the error when the dependent ``StateStore`` handles the error.
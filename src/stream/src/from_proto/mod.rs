@@ -21,6 +21,7 @@ mod dynamic_filter;
 mod expand;
 mod filter;
 mod global_simple_agg;
+mod group_top_n;
 mod hash_agg;
 mod hash_join;
 mod hop_window;
@@ -51,6 +52,7 @@ use self::dynamic_filter::*;
 use self::expand::*;
 use self::filter::*;
 use self::global_simple_agg::*;
+use self::group_top_n::*;
 use self::hash_agg::*;
 use self::hash_join::*;
 use self::hop_window::*;
@@ -109,6 +111,7 @@ pub fn create_executor(
         NodeBody::Project => ProjectExecutorBuilder,
         NodeBody::TopN => TopNExecutorNewBuilder,
         NodeBody::AppendOnlyTopN => AppendOnlyTopNExecutorBuilder,
+        NodeBody::GroupTopN => GroupTopNExecutorBuilder,
         NodeBody::LocalSimpleAgg => LocalSimpleAggExecutorBuilder,
         NodeBody::GlobalSimpleAgg => GlobalSimpleAggExecutorBuilder,
         NodeBody::HashAgg => HashAggExecutorBuilder,
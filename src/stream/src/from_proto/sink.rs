@@ -34,12 +34,19 @@ impl ExecutorBuilder for SinkExecutorBuilder {
             .map(|i| ColumnId::from(*i))
             .collect::<Vec<ColumnId>>();
 
+        let keyspace = Keyspace::table_root(store, &TableId::new(node.state_table_id));
+
         Ok(Box::new(SinkExecutor::new(
             params.input.remove(0),
-            store,
+            keyspace,
             stream.streaming_metrics.clone(),
             node.properties.clone(),
             params.executor_id,
+            params.pk_indices,
+            node.hidden_column_indices
+                .iter()
+                .map(|&i| i as usize)
+                .collect(),
         )))
     }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use risingwave_common::catalog::{ColumnId, Field, Schema, TableId};
+use risingwave_storage::table::state_table::RowBasedStateTable;
 use tokio::sync::mpsc::unbounded_channel;
 
 use super::*;
@@ -52,6 +53,14 @@ impl ExecutorBuilder for SourceExecutorBuilder {
             Field::with_name(column_desc.data_type.clone(), column_desc.name.clone())
         }));
         let schema = Schema::new(fields);
+        // The dead letter table has no distribution key, so all of the actors write to the default
+        // vnode, with the split ids in the keys never overlapping.
+        let dead_letter_table = node.dead_letter_table.as_ref().map(|table| {
+            let mut table = RowBasedStateTable::from_table_catalog(table, store.clone(), None);
+            // A message may be delivered again after recovery.
+            table.disable_sanity_check();
+            table
+        });
         let keyspace = Keyspace::table_root(store, &source_id);
         let vnodes = params
             .vnode_bitmap
@@ -63,6 +72,9 @@ impl ExecutorBuilder for SourceExecutorBuilder {
             source_desc,
             vnodes,
             keyspace,
+            dead_letter_table,
+            // Zero means no limit.
+            Some(node.rate_limit).filter(|rate_limit| *rate_limit > 0),
             column_ids,
             schema,
             params.pk_indices,
@@ -178,6 +178,32 @@ impl LocalStreamManager {
             .collect()
     }
 
+    /// Get the statistics of the given actors that run on this worker.
+    pub fn get_actor_stats(
+        &self,
+        actor_ids: &[ActorId],
+    ) -> HashMap<ActorId, stream_service::actor_stats_response::ActorStats> {
+        let core = self.core.lock();
+        let metrics = &core.streaming_metrics;
+        actor_ids
+            .iter()
+            .filter(|actor_id| core.handles.contains_key(actor_id))
+            .map(|actor_id| {
+                let actor_id_str = actor_id.to_string();
+                let label = [actor_id_str.as_str()];
+                let stats = stream_service::actor_stats_response::ActorStats {
+                    input_rows: metrics.actor_in_record_cnt.with_label_values(&label).get(),
+                    output_rows: metrics.actor_out_record_cnt.with_label_values(&label).get(),
+                    output_blocking_duration_ns: metrics
+                        .actor_output_buffer_blocking_duration_ns
+                        .with_label_values(&label)
+                        .get(),
+                };
+                (*actor_id, stats)
+            })
+            .collect()
+    }
+
     /// Broadcast a barrier to all senders. Save a receiver in barrier manager
     pub fn send_barrier(
         &self,
@@ -38,7 +38,7 @@ pub enum FeMessage {
     Execute(FeExecuteMessage),
     Close(FeCloseMessage),
     Sync,
-    CancelQuery,
+    CancelQuery(FeCancelMessage),
     Terminate,
 }
 
@@ -70,6 +70,29 @@ impl FeStartupMessage {
     }
 }
 
+/// Cancel request sent on a new connection, with the key of the session to cancel. See
+/// [`BeMessage::BackendKeyData`].
+#[derive(Debug)]
+pub struct FeCancelMessage {
+    pub target_process_id: i32,
+    pub target_secret_key: i32,
+}
+
+impl FeCancelMessage {
+    pub fn build_with_payload(mut payload: &[u8]) -> Result<Self> {
+        if payload.len() != 8 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid cancel request of {} bytes", payload.len()),
+            ));
+        }
+        Ok(FeCancelMessage {
+            target_process_id: payload.get_i32(),
+            target_secret_key: payload.get_i32(),
+        })
+    }
+}
+
 /// Query message contains the string sql.
 pub struct FeQueryMessage {
     pub sql_bytes: Bytes,
@@ -292,7 +315,9 @@ impl FeStartupMessage {
             )?)),
             80877103 => Ok(FeMessage::Ssl),
             // Cancel request code.
-            80877102 => Ok(FeMessage::CancelQuery),
+            80877102 => Ok(FeMessage::CancelQuery(FeCancelMessage::build_with_payload(
+                &payload,
+            )?)),
             _ => Err(std::io::Error::new(
                 ErrorKind::InvalidInput,
                 format!(
@@ -330,6 +355,9 @@ pub enum BeMessage<'a> {
     AuthenticationOk,
     AuthenticationCleartextPassword,
     AuthenticationMD5Password(&'a [u8; 4]),
+    /// The process ID and the secret key of the session, sent back by the client to cancel the
+    /// running query.
+    BackendKeyData((i32, i32)),
     CommandComplete(BeCommandCompleteMessage),
     // Single byte - used in response to SSLRequest/GSSENCRequest.
     EncryptionResponse,
@@ -399,6 +427,17 @@ impl<'a> BeMessage<'a> {
                 buf.put_slice(&salt[..]);
             }
 
+            // BackendKeyData
+            // +-----+-----------+-----------------+-------------------+
+            // | 'K' | int32(12) | int32 processID | int32 secret key  |
+            // +-----+-----------+-----------------+-------------------+
+            BeMessage::BackendKeyData((process_id, secret_key)) => {
+                buf.put_u8(b'K');
+                buf.put_i32(12);
+                buf.put_i32(*process_id);
+                buf.put_i32(*secret_key);
+            }
+
             // ParameterStatus
             // +-----+-----------+----------+------+-----------+------+
             // | 'S' | int32 len | str name | '\0' | str value | '\0' |
@@ -669,7 +708,7 @@ fn write_cstr(buf: &mut BytesMut, s: &[u8]) -> Result<()> {
 mod tests {
     use bytes::Bytes;
 
-    use crate::pg_message::FeQueryMessage;
+    use crate::pg_message::{FeCancelMessage, FeQueryMessage};
 
     #[test]
     fn test_get_sql() {
@@ -682,4 +721,13 @@ mod tests {
         };
         assert!(fe.get_sql().is_err(), "{}", true);
     }
+
+    #[test]
+    fn test_cancel_message() {
+        let payload = [0, 0, 0, 42, 0xff, 0xff, 0xff, 0xfe];
+        let msg = FeCancelMessage::build_with_payload(&payload).unwrap();
+        assert_eq!(msg.target_process_id, 42);
+        assert_eq!(msg.target_secret_key, -2);
+        assert!(FeCancelMessage::build_with_payload(&payload[..4]).is_err());
+    }
 }
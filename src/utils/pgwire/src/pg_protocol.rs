@@ -26,9 +26,9 @@ use crate::error::{PsqlError, PsqlResult};
 use crate::pg_extended::{PgPortal, PgStatement};
 use crate::pg_field_descriptor::{PgFieldDescriptor, TypeOid};
 use crate::pg_message::{
-    BeCommandCompleteMessage, BeMessage, BeParameterStatusMessage, FeBindMessage, FeCloseMessage,
-    FeDescribeMessage, FeExecuteMessage, FeMessage, FeParseMessage, FePasswordMessage,
-    FeStartupMessage,
+    BeCommandCompleteMessage, BeMessage, BeParameterStatusMessage, FeBindMessage, FeCancelMessage,
+    FeCloseMessage, FeDescribeMessage, FeExecuteMessage, FeMessage, FeParseMessage,
+    FePasswordMessage, FeStartupMessage,
 };
 use crate::pg_response::PgResponse;
 use crate::pg_server::{Session, SessionManager, UserAuthenticator};
@@ -97,7 +97,13 @@ where
 
     /// Processes one message. Returns true if the connection is terminated.
     pub async fn process(&mut self) -> bool {
-        self.do_process().await || self.is_terminate
+        let terminated = self.do_process().await || self.is_terminate;
+        if terminated {
+            if let Some(session) = &self.session {
+                self.session_mgr.end_session(session);
+            }
+        }
+        terminated
     }
 
     async fn do_process(&mut self) -> bool {
@@ -163,7 +169,7 @@ where
             FeMessage::Startup(msg) => self.process_startup_msg(msg)?,
             FeMessage::Password(msg) => self.process_password_msg(msg)?,
             FeMessage::Query(query_msg) => self.process_query_msg(query_msg.get_sql()).await?,
-            FeMessage::CancelQuery => self.process_cancel_msg()?,
+            FeMessage::CancelQuery(m) => self.process_cancel_msg(m),
             FeMessage::Terminate => self.process_terminate(),
             FeMessage::Parse(m) => self.process_parse_msg(m).await?,
             FeMessage::Bind(m) => self.process_bind_msg(m).await?,
@@ -212,6 +218,9 @@ where
                 self.stream
                     .write_no_flush(&BeMessage::AuthenticationOk)
                     .map_err(|err| PsqlError::StartupError(Box::new(err)))?;
+                self.stream
+                    .write_no_flush(&BeMessage::BackendKeyData(session.id()))
+                    .map_err(|err| PsqlError::StartupError(Box::new(err)))?;
                 self.stream
                     .write_parameter_status_msg_no_flush()
                     .map_err(|err| PsqlError::StartupError(Box::new(err)))?;
@@ -243,9 +252,13 @@ where
                 "Invalid password",
             )));
         }
+        let session_id = self.session.as_ref().unwrap().id();
         self.stream
             .write_no_flush(&BeMessage::AuthenticationOk)
             .map_err(PsqlError::PasswordError)?;
+        self.stream
+            .write_no_flush(&BeMessage::BackendKeyData(session_id))
+            .map_err(PsqlError::PasswordError)?;
         self.stream
             .write_parameter_status_msg_no_flush()
             .map_err(PsqlError::PasswordError)?;
@@ -256,10 +269,12 @@ where
         Ok(())
     }
 
-    fn process_cancel_msg(&mut self) -> PsqlResult<()> {
-        self.stream
-            .write_no_flush(&BeMessage::ErrorResponse(Box::new(PsqlError::cancel())))?;
-        Ok(())
+    /// The cancel request comes on a new connection, which is closed without a response, like
+    /// Postgres does.
+    fn process_cancel_msg(&mut self, msg: FeCancelMessage) {
+        self.session_mgr
+            .cancel_queries_in_session((msg.target_process_id, msg.target_secret_key));
+        self.is_terminate = true;
     }
 
     async fn process_query_msg(&mut self, query_string: io::Result<&str>) -> PsqlResult<()> {
@@ -57,6 +57,7 @@ pub enum StatementType {
     UPDATE_USER,
     ABORT,
     FLUSH,
+    ANALYZE,
     OTHER,
     // EMPTY is used when query statement is empty (e.g. ";").
     EMPTY,
@@ -23,6 +23,9 @@ use crate::pg_protocol::PgProtocol;
 use crate::pg_response::PgResponse;
 
 pub type BoxedError = Box<dyn std::error::Error + Send + Sync>;
+/// The process ID and the secret key sent to the client in `BackendKeyData`, which identifies the
+/// session in the cancel requests.
+pub type SessionId = (i32, i32);
 
 /// The interface for a database system behind pgwire protocol.
 /// We can mock it for testing purpose.
@@ -30,6 +33,12 @@ pub trait SessionManager: Send + Sync + 'static {
     type Session: Session;
 
     fn connect(&self, database: &str, user_name: &str) -> Result<Arc<Self::Session>, BoxedError>;
+
+    /// Cancels the running queries of the session, if it exists.
+    fn cancel_queries_in_session(&self, session_id: SessionId);
+
+    /// Called when the connection of the session is closed.
+    fn end_session(&self, session: &Self::Session);
 }
 
 /// A psql connection. Each connection binds with a database. Switching database will need to
@@ -50,6 +59,7 @@ pub trait Session: Send + Sync {
         sql: &str,
     ) -> Result<Vec<PgFieldDescriptor>, BoxedError>;
     fn user_authenticator(&self) -> &UserAuthenticator;
+    fn id(&self) -> SessionId;
 }
 
 #[derive(Debug, Clone)]
@@ -114,7 +124,7 @@ mod tests {
 
     use crate::pg_field_descriptor::{PgFieldDescriptor, TypeOid};
     use crate::pg_response::{PgResponse, StatementType};
-    use crate::pg_server::{pg_serve, Session, SessionManager, UserAuthenticator};
+    use crate::pg_server::{pg_serve, Session, SessionId, SessionManager, UserAuthenticator};
     use crate::types::Row;
 
     struct MockSessionManager {}
@@ -129,6 +139,10 @@ mod tests {
         ) -> Result<Arc<Self::Session>, Box<dyn Error + Send + Sync>> {
             Ok(Arc::new(MockSession {}))
         }
+
+        fn cancel_queries_in_session(&self, _session_id: SessionId) {}
+
+        fn end_session(&self, _session: &Self::Session) {}
     }
 
     struct MockSession {}
@@ -170,6 +184,10 @@ mod tests {
             &UserAuthenticator::None
         }
 
+        fn id(&self) -> SessionId {
+            (0, 0)
+        }
+
         async fn infer_return_type(
             self: Arc<Self>,
             sql: &str,